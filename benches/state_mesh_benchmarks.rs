@@ -1,6 +1,6 @@
 use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
 use std::hint::black_box;
-use zed::StateNode;
+use zed::{SharedStateNode, StateNode};
 
 #[derive(Clone, Debug, PartialEq)]
 struct MeshState {
@@ -43,7 +43,7 @@ fn bench_node_connection(c: &mut Criterion) {
 
                     for i in 0..node_count {
                         let node = StateNode::new(format!("node_{i}"), MeshState::new(i));
-                        main_node.connect(node);
+                        main_node.connect(&node.into_shared());
                     }
 
                     black_box(main_node);
@@ -98,7 +98,7 @@ fn bench_state_propagation(c: &mut Criterion) {
                         }
                     });
 
-                    main_node.connect(node);
+                    main_node.connect(&node.into_shared());
                 }
 
                 b.iter(|| {
@@ -130,7 +130,7 @@ fn bench_complex_mesh_topology(c: &mut Criterion) {
     c.bench_function("complex_mesh_topology", |b| {
         b.iter(|| {
             // Create a fully connected mesh of 20 nodes
-            let mut nodes: Vec<StateNode<MeshState>> = (0..20)
+            let nodes: Vec<SharedStateNode<MeshState>> = (0..20)
                 .map(|i| {
                     let mut node = StateNode::new(format!("node_{i}"), MeshState::new(i));
                     node.set_conflict_resolver(|current: &mut MeshState, remote: &MeshState| {
@@ -138,7 +138,7 @@ fn bench_complex_mesh_topology(c: &mut Criterion) {
                             *current = remote.clone();
                         }
                     });
-                    node
+                    node.into_shared()
                 })
                 .collect();
 
@@ -146,14 +146,13 @@ fn bench_complex_mesh_topology(c: &mut Criterion) {
             for i in 0..nodes.len() {
                 for j in 0..nodes.len() {
                     if i != j {
-                        let other_node = nodes[j].clone();
-                        nodes[i].connect(other_node);
+                        nodes[i].lock().unwrap().connect(&nodes[j]);
                     }
                 }
             }
 
             // Propagate updates from first node
-            nodes[0].propagate_update();
+            nodes[0].lock().unwrap().propagate_update();
 
             black_box(nodes);
         })
@@ -182,7 +181,7 @@ fn bench_large_state_propagation(c: &mut Criterion) {
                 // Connect 10 nodes
                 for i in 0..10 {
                     let node = StateNode::new(format!("node_{i}"), MeshState::new(i));
-                    main_node.connect(node);
+                    main_node.connect(&node.into_shared());
                 }
 
                 b.iter(|| {
@@ -1,5 +1,4 @@
 use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
-use std::any::Any;
 use std::hint::black_box;
 use zed::StateManager;
 
@@ -16,28 +15,24 @@ enum TimelineAction {
     Reset,
 }
 
-fn timeline_reducer(state: &TimelineState, action: &dyn Any) -> TimelineState {
-    if let Some(action) = action.downcast_ref::<TimelineAction>() {
-        match action {
-            TimelineAction::Increment => TimelineState {
-                counter: state.counter + 1,
-                history: state.history.clone(),
+fn timeline_reducer(state: &TimelineState, action: &TimelineAction) -> TimelineState {
+    match action {
+        TimelineAction::Increment => TimelineState {
+            counter: state.counter + 1,
+            history: state.history.clone(),
+        },
+        TimelineAction::AddHistory(s) => TimelineState {
+            counter: state.counter,
+            history: {
+                let mut new_history = state.history.clone();
+                new_history.push(s.clone());
+                new_history
             },
-            TimelineAction::AddHistory(s) => TimelineState {
-                counter: state.counter,
-                history: {
-                    let mut new_history = state.history.clone();
-                    new_history.push(s.clone());
-                    new_history
-                },
-            },
-            TimelineAction::Reset => TimelineState {
-                counter: 0,
-                history: vec![],
-            },
-        }
-    } else {
-        state.clone()
+        },
+        TimelineAction::Reset => TimelineState {
+            counter: 0,
+            history: vec![],
+        },
     }
 }
 
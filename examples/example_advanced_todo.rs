@@ -1,5 +1,4 @@
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
 use zed::*;
@@ -161,77 +160,62 @@ fn simulate_load_todos() -> Result<Vec<Todo>, String> {
 fn main() {
     println!("=== Advanced Todo App with Zed ===\n");
 
-    let store = todo_store();
-
-    store.subscribe(|state: &TodoState| {
-        println!("📊 State Update:");
-        println!("   Items: {} total", state.items.len());
-        println!("   Active: {}", state.active_count());
-        println!("   Completed: {}", state.completed_count());
-        println!("   Filter: {:?}", state.filter);
-        println!("   Loading: {}", state.is_loading);
-        if let Some(error) = &state.error {
-            println!("   ❌ Error: {error}");
-        }
-        println!();
-    });
-
-    let store_clone = Arc::new(store);
-    let store_for_async = Arc::clone(&store_clone);
+    let store = configure_store_with_enhancer(
+        TODO_INITIAL_STATE,
+        create_reducer(todo_reducer),
+        apply_middleware(LoggerMiddleware::new(|entry: &str| println!("{entry}"))),
+    );
 
     println!("🔄 Loading initial todos...");
-    store_clone.dispatch(TodoActions::StartLoading);
-
-    thread::spawn(move || match simulate_load_todos() {
-        Ok(todos) => {
-            store_for_async.dispatch(TodoActions::LoadTodosSuccess { todos });
-        }
-        Err(error) => {
-            store_for_async.dispatch(TodoActions::LoadTodosError { error });
+    let loading = store.dispatch_async(|dispatch, _get_state| async move {
+        dispatch(TodoActions::StartLoading);
+        match simulate_load_todos() {
+            Ok(todos) => dispatch(TodoActions::LoadTodosSuccess { todos }),
+            Err(error) => dispatch(TodoActions::LoadTodosError { error }),
         }
     });
 
-    thread::sleep(Duration::from_millis(600));
+    loading.join().unwrap();
 
     println!("➕ Adding new todos...");
-    store_clone.dispatch(TodoActions::AddTodo {
+    store.dispatch(TodoActions::AddTodo {
         text: "Master state management".to_string(),
     });
 
-    store_clone.dispatch(TodoActions::AddTodo {
+    store.dispatch(TodoActions::AddTodo {
         text: "Write comprehensive tests".to_string(),
     });
 
     println!("✅ Completing a todo...");
-    let current_state = store_clone.get_state();
+    let current_state = store.get_state();
     if let Some(todo) = current_state.items.first() {
-        store_clone.dispatch(TodoActions::ToggleTodo { id: todo.id });
+        store.dispatch(TodoActions::ToggleTodo { id: todo.id });
     }
 
     println!("🔍 Filtering active todos...");
-    store_clone.dispatch(TodoActions::SetFilter {
+    store.dispatch(TodoActions::SetFilter {
         filter: TodoFilter::Active,
     });
 
     println!("📝 Editing a todo...");
-    let current_state = store_clone.get_state();
+    let current_state = store.get_state();
     if let Some(todo) = current_state.filtered_items().first() {
-        store_clone.dispatch(TodoActions::EditTodo {
+        store.dispatch(TodoActions::EditTodo {
             id: todo.id,
             text: format!("{} (edited)", todo.text),
         });
     }
 
     println!("🧹 Clearing completed todos...");
-    store_clone.dispatch(TodoActions::SetFilter {
+    store.dispatch(TodoActions::SetFilter {
         filter: TodoFilter::All,
     });
-    store_clone.dispatch(TodoActions::ClearCompleted);
+    store.dispatch(TodoActions::ClearCompleted);
 
     println!("🔄 Toggle all remaining todos...");
-    store_clone.dispatch(TodoActions::ToggleAll);
+    store.dispatch(TodoActions::ToggleAll);
 
-    let final_state = store_clone.get_state();
+    let final_state = store.get_state();
     println!("🏁 Final Summary:");
     println!("   Total todos: {}", final_state.items.len());
     println!(
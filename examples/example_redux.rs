@@ -57,11 +57,14 @@ create_slice! {
 fn main() {
     println!("=== Redux-like Store Example (Counter) ===");
 
-    let store = counter_store();
-
-    store.subscribe(|state: &CounterState| {
-        println!("[Redux] Updated state: {state:?}");
-    });
+    let store = configure_store_with_enhancer(
+        COUNTER_INITIAL_STATE,
+        create_reducer(counter_reducer),
+        apply_middleware(
+            LoggerMiddleware::new(|entry: &str| println!("{entry}"))
+                .verbosity(LogVerbosity::Verbose),
+        ),
+    );
 
     store.dispatch(CounterActions::StartLoading);
 
@@ -19,9 +19,10 @@ fn main() {
         DocumentState {
             content: "World".into(),
         },
-    );
+    )
+    .into_shared();
 
-    node1.connect(node2.clone());
+    node1.connect(&node2);
 
     node1.set_conflict_resolver(|local, remote| {
         local.content = format!("{} {}", local.content, remote.content);
@@ -33,12 +34,15 @@ fn main() {
 
     node1.propagate_update();
 
-    node1.merge(&node2);
+    node1.merge(&node2.lock().unwrap());
 
     let removed = node1.remove_connection(&"node2".to_string());
 
     println!("[State Mesh] Node1 state: {:?}", node1.state);
     if let Some(removed_node) = removed {
-        println!("[State Mesh] Removed node2 state: {:?}", removed_node.state);
+        println!(
+            "[State Mesh] Removed node2 state: {:?}",
+            removed_node.lock().unwrap().state
+        );
     }
 }
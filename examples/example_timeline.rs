@@ -1,5 +1,4 @@
 use serde::{Deserialize, Serialize};
-use std::any::Any;
 use zed::*;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -33,15 +32,7 @@ create_slice! {
 fn main() {
     println!("=== Timeline Example ===");
 
-    let reducer = |state: &CounterState, action: &dyn Any| -> CounterState {
-        if let Some(action) = action.downcast_ref::<CounterActions>() {
-            counter_reducer(state, action)
-        } else {
-            state.clone()
-        }
-    };
-
-    let mut timeline = timeline::StateManager::new(COUNTER_INITIAL_STATE, reducer);
+    let mut timeline = timeline::StateManager::new(COUNTER_INITIAL_STATE, counter_reducer);
 
     timeline.dispatch(CounterActions::Incremented);
     timeline.dispatch(CounterActions::Incremented);
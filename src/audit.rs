@@ -0,0 +1,255 @@
+//! # Audit Module
+//!
+//! An append-only audit trail of the actions applied to a store, written as
+//! JSON Lines. Requires the `audit` feature.
+//!
+//! Every applied action becomes one line holding a sequence number, a
+//! timestamp, the serialized action and a hash of the serialized state
+//! after the action:
+//!
+//! ```text
+//! {"seq":1,"timestamp_ms":1700000000000,"action":{"Deposit":100},"state_hash":"af63bd4c8601b7df"}
+//! ```
+//!
+//! The file is only ever appended to. With rotation enabled, a file that
+//! would grow past the size limit is renamed to `<path>.1` (shifting older
+//! files to `<path>.2` and so on) and a new file is started; sequence
+//! numbers continue across files and across restarts.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use serde::Serialize;
+//! use zed::{AuditLog, Store, create_reducer};
+//!
+//! #[derive(Clone, Serialize)]
+//! struct Account { balance: i64 }
+//!
+//! #[derive(Clone, Serialize)]
+//! enum Action { Deposit(i64) }
+//!
+//! let store = Store::new(
+//!     Account { balance: 0 },
+//!     Box::new(create_reducer(|state: &Account, action: &Action| match action {
+//!         Action::Deposit(amount) => Account { balance: state.balance + amount },
+//!     })),
+//! );
+//!
+//! let path = std::env::temp_dir().join("zed-audit-doc.jsonl");
+//! # let _ = std::fs::remove_file(&path);
+//! let audit = AuditLog::open(&path).unwrap().with_rotation(10 * 1024 * 1024, 5);
+//! audit.attach(&store);
+//!
+//! store.dispatch(Action::Deposit(100));
+//! assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 1);
+//! # std::fs::remove_file(&path).unwrap();
+//! ```
+
+use crate::persist::fnv1a;
+use crate::store::{Store, SubscriptionId};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One line of an audit log.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry<Action> {
+    /// Position of the entry in the log, starting at 1
+    pub seq: u64,
+    /// Milliseconds since the Unix epoch at which the entry was written
+    pub timestamp_ms: u64,
+    /// The applied action
+    pub action: Action,
+    /// Hash of the state after the action, see [`AuditLog::state_hash`]
+    pub state_hash: String,
+}
+
+struct Writer {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    next_seq: u64,
+    /// Maximum file size and number of rotated files to keep
+    rotation: Option<(u64, usize)>,
+    last_error: Option<io::Error>,
+}
+
+impl Writer {
+    fn append(&mut self, line: &[u8]) -> io::Result<()> {
+        if let Some((max_bytes, max_files)) = self.rotation
+            && self.size > 0
+            && self.size + line.len() as u64 > max_bytes
+        {
+            self.rotate(max_files)?;
+        }
+        self.file.write_all(line)?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    fn rotate(&mut self, max_files: usize) -> io::Result<()> {
+        for index in (1..max_files).rev() {
+            let from = rotated_path(&self.path, index);
+            if from.exists() {
+                fs::rename(&from, rotated_path(&self.path, index + 1))?;
+            }
+        }
+        fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        self.file = open_append(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{index}"));
+    PathBuf::from(name)
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// Returns the sequence number of the last entry in an existing log file.
+fn last_seq(path: &Path) -> io::Result<u64> {
+    #[derive(Deserialize)]
+    struct Seq {
+        seq: u64,
+    }
+
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(err) => return Err(err),
+    };
+    let mut last = 0;
+    for line in BufReader::new(file).lines() {
+        if let Ok(entry) = serde_json::from_str::<Seq>(&line?) {
+            last = entry.seq;
+        }
+    }
+    Ok(last)
+}
+
+/// An append-only JSON Lines audit log.
+///
+/// Cloning the log yields another handle to the same file, so one log can
+/// be attached to several stores.
+#[derive(Clone)]
+pub struct AuditLog {
+    writer: Arc<Mutex<Writer>>,
+}
+
+impl AuditLog {
+    /// Opens a log file for appending, creating it if needed.
+    ///
+    /// If the file already holds entries, sequence numbers continue after
+    /// the last one.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let last = match last_seq(&path)? {
+            // The file may have just been rotated
+            0 => last_seq(&rotated_path(&path, 1))?,
+            last => last,
+        };
+        let next_seq = last + 1;
+        let file = open_append(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            writer: Arc::new(Mutex::new(Writer {
+                path,
+                file,
+                size,
+                next_seq,
+                rotation: None,
+                last_error: None,
+            })),
+        })
+    }
+
+    /// Rotates the file before it grows past `max_bytes`.
+    ///
+    /// At most `max_files` rotated files are kept; the oldest is deleted
+    /// when another one is rotated out.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_files` is zero, which would delete the live log.
+    pub fn with_rotation(self, max_bytes: u64, max_files: usize) -> Self {
+        assert!(max_files > 0, "at least one rotated file must be kept");
+        self.lock().rotation = Some((max_bytes, max_files));
+        self
+    }
+
+    /// Writes every action applied to a store to this log.
+    ///
+    /// Entries are written on the dispatch path while the reducer lock is
+    /// held, so they follow the order in which actions were applied and
+    /// each hash covers exactly the state the action produced. Rejected
+    /// actions are not logged.
+    ///
+    /// # Returns
+    ///
+    /// The listener's ID, which can be passed to `Store::unsubscribe()` to
+    /// stop auditing.
+    pub fn attach<State, Action>(&self, store: &Store<State, Action>) -> SubscriptionId
+    where
+        State: Clone + Send + Serialize + 'static,
+        Action: Send + Serialize + 'static,
+    {
+        let log = self.clone();
+        let (id, ()) = store.observe_actions(
+            |_| (),
+            move |action: &Action, state: &State| log.append(action, Self::state_hash(state)),
+        );
+        id
+    }
+
+    /// Hashes the JSON serialization of a state.
+    ///
+    /// The hash is not cryptographic: it detects divergence between a state
+    /// and the audit trail, not deliberate tampering.
+    pub fn state_hash<State: Serialize>(state: &State) -> String {
+        let json = serde_json::to_vec(state).unwrap_or_default();
+        format!("{:016x}", fnv1a(&json))
+    }
+
+    /// Takes the last error hit while writing, if any.
+    ///
+    /// Write errors cannot be returned from `dispatch`, so they are kept
+    /// here; entries that failed to serialize or write are missing from
+    /// the log.
+    pub fn take_error(&self) -> Option<io::Error> {
+        self.lock().last_error.take()
+    }
+
+    fn append<Action: Serialize>(&self, action: &Action, state_hash: String) {
+        let mut writer = self.lock();
+        let entry = AuditEntry {
+            seq: writer.next_seq,
+            timestamp_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_millis() as u64),
+            action,
+            state_hash,
+        };
+        let result = serde_json::to_vec(&entry)
+            .map_err(io::Error::from)
+            .and_then(|mut line| {
+                line.push(b'\n');
+                writer.append(&line)
+            });
+        match result {
+            Ok(()) => writer.next_seq += 1,
+            Err(err) => writer.last_error = Some(err),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Writer> {
+        self.writer.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
@@ -0,0 +1,171 @@
+//! # Bridge Module
+//!
+//! Keeps several stores coordinated by forwarding actions between them.
+//!
+//! A [`StoreBridge`] listens to the actions applied to a source store, maps
+//! them to actions of a target store and dispatches them there. Bridges can
+//! point both ways, e.g. between per-document stores and a global app store:
+//! an action forwarded by a bridge is never forwarded back into a store it
+//! already passed through, so bridges cannot loop.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use zed::{Store, StoreBridge, create_reducer};
+//!
+//! #[derive(Clone)]
+//! struct Document { text: String }
+//!
+//! enum DocumentAction { Insert(String) }
+//!
+//! #[derive(Clone)]
+//! struct App { edits: u32 }
+//!
+//! enum AppAction { DocumentEdited }
+//!
+//! let document = Store::new(
+//!     Document { text: String::new() },
+//!     Box::new(create_reducer(|state: &Document, action: &DocumentAction| match action {
+//!         DocumentAction::Insert(text) => Document { text: format!("{}{}", state.text, text) },
+//!     })),
+//! );
+//! let app = Store::new(
+//!     App { edits: 0 },
+//!     Box::new(create_reducer(|state: &App, _: &AppAction| App { edits: state.edits + 1 })),
+//! );
+//!
+//! let _bridge = StoreBridge::new(&document, &app, |action: &DocumentAction| match action {
+//!     DocumentAction::Insert(_) => Some(AppAction::DocumentEdited),
+//! });
+//!
+//! document.dispatch(DocumentAction::Insert("hello".to_string()));
+//! assert_eq!(app.get_state().edits, 1);
+//! ```
+
+use crate::store::{Store, SubscriptionGuard};
+use std::cell::RefCell;
+
+thread_local! {
+    /// Stores the action being forwarded on this thread has passed through.
+    static FORWARD_CHAIN: RefCell<Vec<usize>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Runs a forward from `source` into `target`, unless `target` is already
+/// part of the current chain of forwards.
+fn forward(source: usize, target: usize, dispatch: impl FnOnce()) {
+    let started = FORWARD_CHAIN.with(|chain| {
+        let mut chain = chain.borrow_mut();
+        if chain.contains(&target) {
+            return None;
+        }
+        let started = chain.is_empty();
+        if started {
+            chain.push(source);
+        }
+        chain.push(target);
+        Some(started)
+    });
+    let Some(started) = started else {
+        return;
+    };
+
+    // Unwind the chain even if the target's reducer panics
+    struct Pop(bool);
+    impl Drop for Pop {
+        fn drop(&mut self) {
+            FORWARD_CHAIN.with(|chain| {
+                let mut chain = chain.borrow_mut();
+                chain.pop();
+                if self.0 {
+                    chain.pop();
+                }
+            });
+        }
+    }
+
+    let _pop = Pop(started);
+    dispatch();
+}
+
+/// Forwards the actions applied to one store into another.
+///
+/// Forwarding stops when the bridge is dropped.
+#[derive(Debug)]
+#[must_use = "the bridge stops forwarding as soon as it is dropped"]
+pub struct StoreBridge {
+    _subscription: SubscriptionGuard,
+}
+
+impl StoreBridge {
+    /// Forwards mapped actions from `source` into `target`.
+    ///
+    /// After every action applied to `source`, `map` decides which action,
+    /// if any, to dispatch into `target`. Forwarding happens synchronously
+    /// on the dispatching thread, after the source's subscribers have been
+    /// notified.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The store whose actions are forwarded
+    /// * `target` - The store the mapped actions are dispatched to
+    /// * `map` - Maps a source action to a target action, or `None` to skip it
+    pub fn new<S, A, T, B, F>(source: &Store<S, A>, target: &Store<T, B>, map: F) -> Self
+    where
+        S: Clone + Send + 'static,
+        A: Send + 'static,
+        T: Clone + Send + Sync + 'static,
+        B: Send + 'static,
+        F: Fn(&A) -> Option<B> + Send + Sync + 'static,
+    {
+        let source_id = source.identity();
+        let target = target.clone();
+        let id = source.add_listener(
+            |_| true,
+            move |action: &A, _: &Store<S, A>| {
+                if let Some(action) = map(action) {
+                    forward(source_id, target.identity(), || target.dispatch(action));
+                }
+            },
+        );
+
+        Self {
+            _subscription: source.guard(id),
+        }
+    }
+
+    /// Forwards state changes of `source` into `target` as actions.
+    ///
+    /// After every state update of `source`, `map` decides which action, if
+    /// any, to dispatch into `target`. Loop protection relies on forwarding
+    /// running on the dispatching thread, so it does not cover a source
+    /// store with background notifications.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The store whose state is observed
+    /// * `target` - The store the mapped actions are dispatched to
+    /// * `map` - Derives a target action from the new source state, or `None`
+    pub fn from_state<S, A, T, B, F>(source: &Store<S, A>, target: &Store<T, B>, map: F) -> Self
+    where
+        S: Clone + Send + 'static,
+        A: Send + 'static,
+        T: Clone + Send + Sync + 'static,
+        B: Send + 'static,
+        F: Fn(&S) -> Option<B> + Send + Sync + 'static,
+    {
+        let source_id = source.identity();
+        let target = target.clone();
+        let id = source.subscribe(move |state: &S| {
+            if let Some(action) = map(state) {
+                forward(source_id, target.identity(), || target.dispatch(action));
+            }
+        });
+
+        Self {
+            _subscription: source.guard(id),
+        }
+    }
+
+    /// Stops forwarding; same as dropping the bridge.
+    pub fn disconnect(self) {}
+}
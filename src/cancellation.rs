@@ -0,0 +1,187 @@
+//! # Cancellation Module
+//!
+//! Cooperative cancellation for async work started from a store, such as
+//! async thunks and sagas.
+//!
+//! A [`CancellationToken`] is cancelled once and stays cancelled. Tokens form
+//! a tree: cancelling a token also cancels every token created from it with
+//! [`child_token`](CancellationToken::child_token). Each store owns a root
+//! token, so [`Store::cancel_all_effects`](crate::Store::cancel_all_effects)
+//! aborts all in-flight work, while a scope token obtained from
+//! [`Store::cancellation_token`](crate::Store::cancellation_token) aborts only
+//! the work started with it, e.g. when the user navigates away from a view.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use zed::{Store, create_reducer};
+//!
+//! #[derive(Clone)]
+//! struct State { loaded: bool }
+//!
+//! enum Action { Loaded }
+//!
+//! let store = Store::new(
+//!     State { loaded: false },
+//!     Box::new(create_reducer(|_: &State, _: &Action| State { loaded: true })),
+//! );
+//!
+//! let view = store.cancellation_token();
+//! let request = store.dispatch_async_with_token(&view, |dispatch, _, token| async move {
+//!     // Wait for a response that never arrives before the view closes
+//!     token.cancelled().await;
+//!     dispatch(Action::Loaded);
+//! });
+//!
+//! // The user navigated away
+//! view.cancel();
+//! assert_eq!(request.join().unwrap(), None);
+//! assert!(!store.get_state().loaded);
+//! ```
+
+use std::future::{Future, poll_fn};
+use std::pin::pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError, Weak};
+use std::task::{Context, Poll, Waker};
+
+struct Inner {
+    cancelled: AtomicBool,
+    children: Mutex<Vec<Weak<Inner>>>,
+    wakers: Mutex<Vec<Waker>>,
+}
+
+impl Inner {
+    fn cancel(&self) {
+        if self.cancelled.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let wakers =
+            std::mem::take(&mut *self.wakers.lock().unwrap_or_else(PoisonError::into_inner));
+        for waker in wakers {
+            waker.wake();
+        }
+        let children =
+            std::mem::take(&mut *self.children.lock().unwrap_or_else(PoisonError::into_inner));
+        for child in children.iter().filter_map(Weak::upgrade) {
+            child.cancel();
+        }
+    }
+}
+
+/// A shareable flag signalling that async work should stop.
+///
+/// Cloning a token yields another handle to the same flag.
+#[derive(Clone)]
+pub struct CancellationToken {
+    inner: Arc<Inner>,
+}
+
+impl CancellationToken {
+    /// Creates a token that is not cancelled.
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                cancelled: AtomicBool::new(false),
+                children: Mutex::new(Vec::new()),
+                wakers: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// Cancels the token and all of its children, waking their waiters.
+    pub fn cancel(&self) {
+        self.inner.cancel();
+    }
+
+    /// Returns `true` if the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Creates a token that is cancelled together with this one.
+    ///
+    /// Cancelling the child does not affect this token.
+    pub fn child_token(&self) -> CancellationToken {
+        let child = CancellationToken::new();
+        self.link(&child);
+        child
+    }
+
+    /// Makes `child` cancelled whenever this token is.
+    pub(crate) fn link(&self, child: &CancellationToken) {
+        {
+            let mut children = self
+                .inner
+                .children
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            if !self.is_cancelled() {
+                children.retain(|child| child.strong_count() > 0);
+                children.push(Arc::downgrade(&child.inner));
+                return;
+            }
+        }
+        child.cancel();
+    }
+
+    /// Completes once the token is cancelled.
+    pub async fn cancelled(&self) {
+        poll_fn(|cx| self.poll_cancelled(cx)).await
+    }
+
+    /// Runs a future until it completes or the token is cancelled.
+    ///
+    /// # Returns
+    ///
+    /// The future's output, or `None` if the token was cancelled before the
+    /// future completed.
+    pub async fn run_until_cancelled<F: Future>(&self, future: F) -> Option<F::Output> {
+        let mut future = pin!(future);
+        poll_fn(|cx| {
+            if self.poll_cancelled(cx).is_ready() {
+                return Poll::Ready(None);
+            }
+            match future.as_mut().poll(cx) {
+                // The token may have been cancelled while the future ran
+                Poll::Ready(_) if self.is_cancelled() => Poll::Ready(None),
+                poll => poll.map(Some),
+            }
+        })
+        .await
+    }
+
+    /// Registers the task's waker unless the token is already cancelled.
+    pub(crate) fn poll_cancelled(&self, cx: &mut Context<'_>) -> Poll<()> {
+        if self.is_cancelled() {
+            return Poll::Ready(());
+        }
+        let mut wakers = self
+            .inner
+            .wakers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        // Checked again under the lock, as `cancel()` drains the wakers
+        if self.is_cancelled() {
+            return Poll::Ready(());
+        }
+        if !wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+            wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for CancellationToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CancellationToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
@@ -0,0 +1,82 @@
+//! # Combine Reducers Module
+//!
+//! Provides the [`combine_reducers!`](crate::combine_reducers) macro, which
+//! builds a single root reducer out of several slice reducers, each owning
+//! one field of the root state, like Redux's `combineReducers`.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use zed::{combine_reducers, configure_store};
+//!
+//! #[derive(Clone, Debug, PartialEq)]
+//! struct AppState {
+//!     cart: Vec<String>,
+//!     visits: u32,
+//! }
+//!
+//! enum AppAction {
+//!     AddToCart(String),
+//!     Visit,
+//! }
+//!
+//! fn cart_reducer(cart: &Vec<String>, action: &AppAction) -> Vec<String> {
+//!     match action {
+//!         AppAction::AddToCart(item) => {
+//!             let mut cart = cart.clone();
+//!             cart.push(item.clone());
+//!             cart
+//!         }
+//!         _ => cart.clone(),
+//!     }
+//! }
+//!
+//! let root_reducer = combine_reducers! {
+//!     state: AppState,
+//!     action: AppAction,
+//!     reducers: {
+//!         cart: cart_reducer,
+//!         visits: |visits: &u32, action: &AppAction| match action {
+//!             AppAction::Visit => visits + 1,
+//!             _ => *visits,
+//!         },
+//!     }
+//! };
+//!
+//! let store = configure_store(AppState { cart: vec![], visits: 0 }, root_reducer);
+//! store.dispatch(AppAction::AddToCart("book".to_string()));
+//! store.dispatch(AppAction::Visit);
+//!
+//! assert_eq!(store.get_state(), AppState { cart: vec!["book".to_string()], visits: 1 });
+//! ```
+
+/// Combines slice reducers operating on fields of a root state into one root reducer.
+///
+/// Each entry maps a field of the root state to a function or closure of the
+/// form `Fn(&FieldType, &Action) -> FieldType`. Every action is passed to all
+/// slice reducers; fields without a slice reducer are carried over unchanged.
+///
+/// The resulting reducer implements [`Reducer`](crate::Reducer) and can be
+/// passed to `configure_store` or `Store::new`. The root state must be `Clone`.
+#[macro_export]
+macro_rules! combine_reducers {
+    (
+        state: $state_ty:ty,
+        action: $action_ty:ty,
+        reducers: {
+            $( $field:ident : $reducer:expr ),+ $(,)?
+        } $(,)?
+    ) => {{
+        $(
+            let $field = $crate::create_reducer($reducer);
+        )+
+
+        $crate::create_reducer(move |state: &$state_ty, action: &$action_ty| -> $state_ty {
+            let mut next = ::std::clone::Clone::clone(state);
+            $(
+                next.$field = $crate::Reducer::reduce(&$field, &state.$field, action);
+            )+
+            next
+        })
+    }};
+}
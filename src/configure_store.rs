@@ -37,6 +37,7 @@
 //! assert_eq!(store.get_state().counter, 1);
 //! ```
 
+use crate::enhancer::StoreEnhancer;
 use crate::reducer::Reducer;
 use crate::store::Store;
 
@@ -89,3 +90,53 @@ where
 {
     Store::new(initial_state, Box::new(reducer))
 }
+
+/// Configures a store and applies a [`StoreEnhancer`] to it.
+///
+/// The enhancer first wraps the reducer, then decorates the created store.
+/// Compose several enhancers with [`StoreEnhancer::and_then`] to layer
+/// devtools, persistence, logging and similar integrations.
+///
+/// # Arguments
+///
+/// * `initial_state` - The initial state of the store
+/// * `reducer` - A reducer that implements the Reducer trait
+/// * `enhancer` - The enhancer (or composition of enhancers) to apply
+///
+/// # Returns
+///
+/// The enhanced Store instance.
+///
+/// # Example
+///
+/// ```rust
+/// use zed::{Store, StoreEnhancer, configure_store_with_enhancer, create_reducer};
+///
+/// #[derive(Clone, PartialEq)]
+/// struct Counter { value: i32 }
+///
+/// enum Action { Add(i32) }
+///
+/// let store = configure_store_with_enhancer(
+///     Counter { value: 0 },
+///     create_reducer(|state: &Counter, Action::Add(n): &Action| Counter { value: state.value + n }),
+///     |store: Store<Counter, Action>| store.with_change_detection(),
+/// );
+///
+/// store.dispatch(Action::Add(5));
+/// assert_eq!(store.get_state().value, 5);
+/// ```
+pub fn configure_store_with_enhancer<State, Action, R, E>(
+    initial_state: State,
+    reducer: R,
+    enhancer: E,
+) -> Store<State, Action>
+where
+    State: Clone + Send + 'static,
+    Action: Send + 'static,
+    R: Reducer<State, Action> + Send + Sync + 'static,
+    E: StoreEnhancer<State, Action>,
+{
+    let reducer = enhancer.enhance_reducer(Box::new(reducer));
+    enhancer.enhance_store(Store::new(initial_state, reducer))
+}
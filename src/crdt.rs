@@ -0,0 +1,376 @@
+//! # CRDT Module
+//!
+//! Conflict-free replicated data types: states that replicas can change
+//! independently and merge in any order, any number of times, and still
+//! end up equal.
+//!
+//! Every type implements [`Mergeable`]; a [`StateNode`](crate::StateNode)
+//! created with [`StateNode::mergeable`](crate::StateNode::mergeable) uses
+//! it as its conflict resolver, so no merge logic has to be written by hand.
+//!
+//! - [`GCounter`] - a counter that only grows
+//! - [`PNCounter`] - a counter that can also shrink
+//! - [`LwwRegister`] - a single value where the latest write wins
+//! - [`OrSet`] - a set where an add wins over a concurrent remove
+//! - [`CrdtMap`] - a map of CRDT values, merged key by key
+//!
+//! Operations take the ID of the replica performing them, usually the
+//! node's ID, so concurrent operations on different replicas never collide.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use zed::{Mergeable, PNCounter, StateNode};
+//!
+//! let mut likes = PNCounter::new();
+//! likes.increment("alice", 3);
+//!
+//! let mut node = StateNode::mergeable("bob".to_string(), likes.clone());
+//! node.state.decrement("bob", 1);
+//!
+//! // Alice's replica keeps counting independently
+//! likes.increment("alice", 2);
+//! node.resolve_conflict(likes);
+//! assert_eq!(node.state.value(), 4);
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// States that can be merged with a concurrently changed copy.
+///
+/// Implementations must be commutative, associative and idempotent: merging
+/// the same states in any order, grouping or number of times has to give
+/// the same result. That is what lets replicas converge however updates
+/// reach them.
+pub trait Mergeable {
+    /// Merges the changes of `other` into `self`.
+    fn merge(&mut self, other: &Self);
+}
+
+/// A counter that only grows.
+///
+/// Each replica counts its own increments; the value is their sum.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GCounter {
+    counts: BTreeMap<String, u64>,
+}
+
+impl GCounter {
+    /// Creates a counter at zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `amount` on behalf of a replica.
+    pub fn increment(&mut self, replica: &str, amount: u64) {
+        *self.counts.entry(replica.to_string()).or_insert(0) += amount;
+    }
+
+    /// Returns the total of all increments.
+    pub fn value(&self) -> u64 {
+        self.counts.values().sum()
+    }
+}
+
+impl Mergeable for GCounter {
+    fn merge(&mut self, other: &Self) {
+        for (replica, &count) in &other.counts {
+            let local = self.counts.entry(replica.clone()).or_insert(0);
+            *local = (*local).max(count);
+        }
+    }
+}
+
+/// A counter that can grow and shrink, kept as two [`GCounter`]s.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PNCounter {
+    increments: GCounter,
+    decrements: GCounter,
+}
+
+impl PNCounter {
+    /// Creates a counter at zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `amount` on behalf of a replica.
+    pub fn increment(&mut self, replica: &str, amount: u64) {
+        self.increments.increment(replica, amount);
+    }
+
+    /// Subtracts `amount` on behalf of a replica.
+    pub fn decrement(&mut self, replica: &str, amount: u64) {
+        self.decrements.increment(replica, amount);
+    }
+
+    /// Returns the increments minus the decrements.
+    pub fn value(&self) -> i64 {
+        self.increments.value() as i64 - self.decrements.value() as i64
+    }
+}
+
+impl Mergeable for PNCounter {
+    fn merge(&mut self, other: &Self) {
+        self.increments.merge(&other.increments);
+        self.decrements.merge(&other.decrements);
+    }
+}
+
+/// A single value where the write with the highest timestamp wins.
+///
+/// Timestamps are supplied by the caller, e.g. milliseconds since the Unix
+/// epoch or a logical clock. Writes with equal timestamps are ordered by
+/// replica ID, so every replica picks the same winner.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LwwRegister<V> {
+    value: V,
+    timestamp: u64,
+    replica: String,
+}
+
+impl<V> LwwRegister<V> {
+    /// Creates a register holding an initial value, which any write
+    /// replaces.
+    pub fn new(value: V) -> Self {
+        Self {
+            value,
+            timestamp: 0,
+            replica: String::new(),
+        }
+    }
+
+    /// Writes a value, unless the register already holds a later write.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the value was written.
+    pub fn set(&mut self, value: V, timestamp: u64, replica: &str) -> bool {
+        if (timestamp, replica) <= (self.timestamp, self.replica.as_str()) {
+            return false;
+        }
+        self.value = value;
+        self.timestamp = timestamp;
+        self.replica = replica.to_string();
+        true
+    }
+
+    /// Returns the current value.
+    pub fn get(&self) -> &V {
+        &self.value
+    }
+
+    /// Returns the timestamp of the current value.
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+}
+
+impl<V: Clone> Mergeable for LwwRegister<V> {
+    fn merge(&mut self, other: &Self) {
+        self.set(other.value.clone(), other.timestamp, &other.replica);
+    }
+}
+
+/// A set where adding an element wins over a concurrent removal of it
+/// (an observed-remove set).
+///
+/// Every add is tagged with the adding replica and a sequence number; a
+/// removal only removes the adds it has seen. Removed tags are kept to
+/// recognize them in later merges, so the set grows with the number of
+/// operations.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrSet<V: Ord> {
+    adds: BTreeSet<(V, String, u64)>,
+    removed: BTreeSet<(String, u64)>,
+    clocks: BTreeMap<String, u64>,
+}
+
+impl<V: Ord> Default for OrSet<V> {
+    fn default() -> Self {
+        Self {
+            adds: BTreeSet::new(),
+            removed: BTreeSet::new(),
+            clocks: BTreeMap::new(),
+        }
+    }
+}
+
+impl<V: Ord + Clone> OrSet<V> {
+    /// Creates an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an element on behalf of a replica.
+    pub fn insert(&mut self, value: V, replica: &str) {
+        let clock = self.clocks.entry(replica.to_string()).or_insert(0);
+        *clock += 1;
+        self.adds.insert((value, replica.to_string(), *clock));
+    }
+
+    /// Removes an element, as far as this replica has seen it added.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the element was in the set.
+    pub fn remove(&mut self, value: &V) -> bool {
+        let tags: Vec<(String, u64)> = self
+            .live_tags(value)
+            .map(|(replica, seq)| (replica.clone(), seq))
+            .collect();
+        let present = !tags.is_empty();
+        self.removed.extend(tags);
+        present
+    }
+
+    /// Returns `true` if the set contains the element.
+    pub fn contains(&self, value: &V) -> bool {
+        self.live_tags(value).next().is_some()
+    }
+
+    /// Iterates over the elements in order.
+    pub fn iter(&self) -> impl Iterator<Item = &V> + '_ {
+        let mut previous = None;
+        self.adds
+            .iter()
+            .filter(|(_, replica, seq)| !self.removed.contains(&(replica.clone(), *seq)))
+            .map(|(value, _, _)| value)
+            .filter(move |value| previous.replace(*value) != Some(*value))
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Returns `true` if the set has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    /// Iterates over the tags of the adds of `value` that were not removed.
+    fn live_tags<'a>(&'a self, value: &'a V) -> impl Iterator<Item = (&'a String, u64)> + 'a {
+        self.adds
+            .iter()
+            .skip_while(move |(candidate, _, _)| candidate < value)
+            .take_while(move |(candidate, _, _)| candidate == value)
+            .map(|(_, replica, seq)| (replica, *seq))
+            .filter(|(replica, seq)| !self.removed.contains(&((*replica).clone(), *seq)))
+    }
+}
+
+impl<V: Ord + Clone> Mergeable for OrSet<V> {
+    fn merge(&mut self, other: &Self) {
+        self.adds.extend(other.adds.iter().cloned());
+        self.removed.extend(other.removed.iter().cloned());
+        for (replica, &clock) in &other.clocks {
+            let local = self.clocks.entry(replica.clone()).or_insert(0);
+            *local = (*local).max(clock);
+        }
+    }
+}
+
+/// A map whose values are CRDTs themselves, merged key by key.
+///
+/// Keys behave like an [`OrSet`]: inserting a key wins over a concurrent
+/// removal. Values of removed keys are kept, so a key that is inserted
+/// again merges with its earlier value. In JSON, keys have to serialize as
+/// strings or integers, as with any map.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrdtMap<K: Ord, V> {
+    keys: OrSet<K>,
+    values: BTreeMap<K, V>,
+}
+
+impl<K: Ord, V> Default for CrdtMap<K, V> {
+    fn default() -> Self {
+        Self {
+            keys: OrSet::default(),
+            values: BTreeMap::new(),
+        }
+    }
+}
+
+impl<K: Ord + Clone, V: Mergeable + Clone> CrdtMap<K, V> {
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts a value on behalf of a replica, merging it with the value
+    /// already stored for the key.
+    pub fn insert(&mut self, key: K, value: V, replica: &str) {
+        match self.values.get_mut(&key) {
+            Some(existing) => existing.merge(&value),
+            None => {
+                self.values.insert(key.clone(), value);
+            }
+        }
+        self.keys.insert(key, replica);
+    }
+
+    /// Changes the value of a key in place, inserting `default` first if
+    /// the key is missing.
+    pub fn update<F>(&mut self, key: K, default: V, replica: &str, f: F)
+    where
+        F: FnOnce(&mut V),
+    {
+        if !self.keys.contains(&key) {
+            self.insert(key.clone(), default, replica);
+        }
+        if let Some(value) = self.values.get_mut(&key) {
+            f(value);
+        }
+    }
+
+    /// Removes a key, as far as this replica has seen it inserted.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the key was in the map.
+    pub fn remove(&mut self, key: &K) -> bool {
+        self.keys.remove(key)
+    }
+
+    /// Returns the value of a key.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        if self.keys.contains(key) {
+            self.values.get(key)
+        } else {
+            None
+        }
+    }
+
+    /// Iterates over the entries in key order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> + '_ {
+        self.keys
+            .iter()
+            .filter_map(|key| Some((key, self.values.get(key)?)))
+    }
+
+    /// Returns the number of keys.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns `true` if the map has no keys.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+impl<K: Ord + Clone, V: Mergeable + Clone> Mergeable for CrdtMap<K, V> {
+    fn merge(&mut self, other: &Self) {
+        self.keys.merge(&other.keys);
+        for (key, value) in &other.values {
+            match self.values.get_mut(key) {
+                Some(existing) => existing.merge(value),
+                None => {
+                    self.values.insert(key.clone(), value.clone());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,552 @@
+//! # Delta Module
+//!
+//! A timeline for large states that stores differences between consecutive
+//! states instead of full copies.
+//!
+//! A [`DeltaStateManager`] offers the navigation of
+//! [`StateManager`](crate::StateManager), but keeps a full snapshot only
+//! every `snapshot_interval` entries. The entries in between hold the delta
+//! from the previous state, and earlier states are rebuilt on demand from
+//! the nearest snapshot. Only the current state is kept materialized.
+//! An existing `StateManager` history can be moved to delta storage with
+//! [`StateManager::into_delta_storage`](crate::StateManager::into_delta_storage)
+//! or [`into_json_delta_storage`](crate::StateManager::into_json_delta_storage).
+//!
+//! Deltas come either from a [`Diff`] implementation on the state, or, for
+//! any state that implements `Serialize` and `Deserialize`, from a
+//! structural comparison of the states' JSON representations ([`JsonDelta`]).
+//!
+//! ## Example
+//!
+//! ```rust
+//! use zed::{DeltaStateManager, Diff};
+//!
+//! #[derive(Clone)]
+//! struct Document { lines: Vec<String> }
+//!
+//! // Edits replace one line or append a new one
+//! impl Diff for Document {
+//!     type Delta = (usize, String);
+//!
+//!     fn diff(&self, next: &Self) -> Self::Delta {
+//!         let index = (0..next.lines.len())
+//!             .find(|&i| self.lines.get(i) != Some(&next.lines[i]))
+//!             .unwrap_or(0);
+//!         (index, next.lines[index].clone())
+//!     }
+//!
+//!     fn apply(&self, (index, line): &Self::Delta) -> Self {
+//!         let mut lines = self.lines.clone();
+//!         match lines.get_mut(*index) {
+//!             Some(existing) => *existing = line.clone(),
+//!             None => lines.push(line.clone()),
+//!         }
+//!         Document { lines }
+//!     }
+//! }
+//!
+//! fn reducer(doc: &Document, line: &String) -> Document {
+//!     doc.apply(&(doc.lines.len(), line.clone()))
+//! }
+//!
+//! let mut timeline = DeltaStateManager::new(Document { lines: vec![] }, reducer, 100);
+//! timeline.dispatch("first".to_string());
+//! timeline.dispatch("second".to_string());
+//!
+//! timeline.rewind(1);
+//! assert_eq!(timeline.current_state().lines, vec!["first"]);
+//! ```
+
+use crate::timeline::TimelineReducer;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// States that can describe the difference to another state compactly.
+pub trait Diff: Clone {
+    /// The change turning one state into another
+    type Delta;
+
+    /// Computes the change from `self` to `next`.
+    fn diff(&self, next: &Self) -> Self::Delta;
+
+    /// Applies a change computed by `diff()` to `self`.
+    fn apply(&self, delta: &Self::Delta) -> Self;
+}
+
+/// The difference between two JSON values.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum JsonDelta {
+    /// The values are equal
+    Unchanged,
+    /// The value was replaced as a whole
+    Replace(Value),
+    /// Some fields of an object changed
+    Object {
+        /// Changed and added fields
+        changed: BTreeMap<String, JsonDelta>,
+        /// Removed fields
+        removed: Vec<String>,
+    },
+    /// Some elements of an array changed
+    Array {
+        /// Length of the new array
+        len: usize,
+        /// Changed and appended elements by position
+        changed: Vec<(usize, JsonDelta)>,
+    },
+}
+
+impl JsonDelta {
+    /// Computes the difference from `old` to `new`.
+    ///
+    /// Objects and arrays are compared element by element, so a change deep
+    /// inside a large document only records the path to it.
+    pub fn between(old: &Value, new: &Value) -> Self {
+        if old == new {
+            return JsonDelta::Unchanged;
+        }
+        match (old, new) {
+            (Value::Object(old), Value::Object(new)) => JsonDelta::Object {
+                changed: new
+                    .iter()
+                    .filter_map(|(key, value)| {
+                        let delta = match old.get(key) {
+                            Some(previous) => Self::between(previous, value),
+                            None => JsonDelta::Replace(value.clone()),
+                        };
+                        (delta != JsonDelta::Unchanged).then(|| (key.clone(), delta))
+                    })
+                    .collect(),
+                removed: old
+                    .keys()
+                    .filter(|key| !new.contains_key(*key))
+                    .cloned()
+                    .collect(),
+            },
+            (Value::Array(old), Value::Array(new)) => JsonDelta::Array {
+                len: new.len(),
+                changed: new
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, value)| {
+                        let delta = match old.get(index) {
+                            Some(previous) => Self::between(previous, value),
+                            None => JsonDelta::Replace(value.clone()),
+                        };
+                        (delta != JsonDelta::Unchanged).then_some((index, delta))
+                    })
+                    .collect(),
+            },
+            _ => JsonDelta::Replace(new.clone()),
+        }
+    }
+
+    /// Applies the difference to a value in place.
+    pub fn apply_to(&self, value: &mut Value) {
+        match self {
+            JsonDelta::Unchanged => {}
+            JsonDelta::Replace(new) => *value = new.clone(),
+            JsonDelta::Object { changed, removed } => {
+                if !value.is_object() {
+                    *value = Value::Object(Default::default());
+                }
+                if let Value::Object(fields) = value {
+                    for key in removed {
+                        fields.remove(key);
+                    }
+                    for (key, delta) in changed {
+                        delta.apply_to(fields.entry(key.clone()).or_insert(Value::Null));
+                    }
+                }
+            }
+            JsonDelta::Array { len, changed } => {
+                if !value.is_array() {
+                    *value = Value::Array(Vec::new());
+                }
+                if let Value::Array(elements) = value {
+                    elements.resize(*len, Value::Null);
+                    for (index, delta) in changed {
+                        if let Some(element) = elements.get_mut(*index) {
+                            delta.apply_to(element);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// One changed value in a [`StateDiff`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    /// JSON Pointer to the value, e.g. `/todos/2/done`; empty for the root
+    pub path: String,
+    /// The value before the change, or `None` if it was added
+    pub old: Option<Value>,
+    /// The value after the change, or `None` if it was removed
+    pub new: Option<Value>,
+}
+
+/// A flat list of the values that differ between two states, for display
+/// in debuggers and audit views.
+///
+/// Objects and arrays are compared element by element; every other
+/// difference is reported as one change at the path where it occurs.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct StateDiff {
+    changes: Vec<FieldChange>,
+}
+
+impl StateDiff {
+    /// Computes the changes from `old` to `new`.
+    pub fn between(old: &Value, new: &Value) -> Self {
+        let mut diff = StateDiff::default();
+        diff.collect(String::new(), Some(old), Some(new));
+        diff
+    }
+
+    /// Returns the changes in the order of the paths' first appearance.
+    pub fn changes(&self) -> &[FieldChange] {
+        &self.changes
+    }
+
+    /// Returns the change at the given path, if that value changed.
+    pub fn get(&self, path: &str) -> Option<&FieldChange> {
+        self.changes.iter().find(|change| change.path == path)
+    }
+
+    /// Returns `true` if the states are equal.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    fn collect(&mut self, path: String, old: Option<&Value>, new: Option<&Value>) {
+        match (old, new) {
+            (Some(old), Some(new)) if old == new => {}
+            (Some(Value::Object(old)), Some(Value::Object(new))) => {
+                for (key, value) in old {
+                    self.collect(child_path(&path, key), Some(value), new.get(key));
+                }
+                for (key, value) in new.iter().filter(|(key, _)| !old.contains_key(*key)) {
+                    self.collect(child_path(&path, key), None, Some(value));
+                }
+            }
+            (Some(Value::Array(old)), Some(Value::Array(new))) => {
+                for index in 0..old.len().max(new.len()) {
+                    let path = child_path(&path, &index.to_string());
+                    self.collect(path, old.get(index), new.get(index));
+                }
+            }
+            _ => self.changes.push(FieldChange {
+                path,
+                old: old.cloned(),
+                new: new.cloned(),
+            }),
+        }
+    }
+}
+
+/// Appends an escaped JSON Pointer segment.
+fn child_path(path: &str, key: &str) -> String {
+    format!("{path}/{}", key.replace('~', "~0").replace('/', "~1"))
+}
+
+enum Entry<T, D> {
+    Snapshot(T),
+    Delta(D),
+}
+
+fn trait_diff<T: Diff>(state: &T, next: &T) -> Option<T::Delta> {
+    Some(state.diff(next))
+}
+
+fn trait_apply<T: Diff>(state: &T, delta: &T::Delta) -> T {
+    state.apply(delta)
+}
+
+fn json_diff<T: Serialize + DeserializeOwned>(state: &T, next: &T) -> Option<JsonDelta> {
+    let old = serde_json::to_value(state).ok()?;
+    let new = serde_json::to_value(next).ok()?;
+    // Rebuilding deserializes the patched JSON, so a delta is only recorded
+    // if the new state survives the round trip unchanged
+    let rebuilt: T = serde_json::from_value(new.clone()).ok()?;
+    if serde_json::to_value(&rebuilt).ok()? != new {
+        return None;
+    }
+    Some(JsonDelta::between(&old, &new))
+}
+
+fn json_apply<T: Serialize + DeserializeOwned>(state: &T, delta: &JsonDelta) -> T {
+    // Both states' round trips were checked when the delta was recorded
+    let mut value = serde_json::to_value(state).expect("state serialized when it was recorded");
+    delta.apply_to(&mut value);
+    serde_json::from_value(value).expect("state deserialized when it was recorded")
+}
+
+/// A state manager that stores its history as periodic snapshots plus deltas.
+pub struct DeltaStateManager<T, Action, D> {
+    /// The history; the first entry is always a snapshot
+    entries: Vec<Entry<T, D>>,
+    /// Current position in the history (0-indexed)
+    current: usize,
+    /// The state at the current position
+    state: T,
+    /// Number of entries from one snapshot to the next
+    snapshot_interval: usize,
+    /// Reducer function that applies actions to create new states
+    reducer: TimelineReducer<T, Action>,
+    /// Computes a delta, or `None` to store a snapshot instead
+    diff: fn(&T, &T) -> Option<D>,
+    /// Applies a delta
+    apply: fn(&T, &D) -> T,
+}
+
+impl<T: Diff, Action> DeltaStateManager<T, Action, T::Delta> {
+    /// Creates a delta-compressed timeline using the state's [`Diff`]
+    /// implementation.
+    ///
+    /// # Arguments
+    ///
+    /// * `initial_state` - The first state in the history
+    /// * `reducer` - Applies actions to create new states
+    /// * `snapshot_interval` - Number of entries from one full snapshot to the
+    ///   next; larger intervals save memory but make rebuilding earlier
+    ///   states slower (1 stores every state in full)
+    pub fn new<F>(initial_state: T, reducer: F, snapshot_interval: usize) -> Self
+    where
+        F: Fn(&T, &Action) -> T + Send + Sync + 'static,
+    {
+        Self::with_codec(
+            initial_state,
+            Arc::new(reducer),
+            snapshot_interval,
+            trait_diff::<T>,
+            trait_apply::<T>,
+        )
+    }
+
+    /// Rebuilds a recorded history as snapshots plus deltas, see
+    /// `StateManager::into_delta_storage()`.
+    pub(crate) fn diff_from_history(
+        history: Vec<T>,
+        current: usize,
+        reducer: TimelineReducer<T, Action>,
+        snapshot_interval: usize,
+    ) -> Self {
+        Self::from_history(
+            history,
+            current,
+            reducer,
+            snapshot_interval,
+            trait_diff::<T>,
+            trait_apply::<T>,
+        )
+    }
+}
+
+impl<T, Action> DeltaStateManager<T, Action, JsonDelta>
+where
+    T: Clone + Serialize + DeserializeOwned,
+{
+    /// Creates a delta-compressed timeline that diffs the states' JSON
+    /// serializations.
+    ///
+    /// States that fail to serialize, or that do not deserialize back to
+    /// the same JSON, are stored as full snapshots.
+    ///
+    /// # Arguments
+    ///
+    /// See [`DeltaStateManager::new`].
+    pub fn with_json_diff<F>(initial_state: T, reducer: F, snapshot_interval: usize) -> Self
+    where
+        F: Fn(&T, &Action) -> T + Send + Sync + 'static,
+    {
+        Self::with_codec(
+            initial_state,
+            Arc::new(reducer),
+            snapshot_interval,
+            json_diff::<T>,
+            json_apply::<T>,
+        )
+    }
+
+    /// Rebuilds a recorded history as JSON snapshots plus deltas, see
+    /// `StateManager::into_json_delta_storage()`.
+    pub(crate) fn json_from_history(
+        history: Vec<T>,
+        current: usize,
+        reducer: TimelineReducer<T, Action>,
+        snapshot_interval: usize,
+    ) -> Self {
+        Self::from_history(
+            history,
+            current,
+            reducer,
+            snapshot_interval,
+            json_diff::<T>,
+            json_apply::<T>,
+        )
+    }
+}
+
+impl<T: Clone, Action, D> DeltaStateManager<T, Action, D> {
+    fn with_codec(
+        initial_state: T,
+        reducer: TimelineReducer<T, Action>,
+        snapshot_interval: usize,
+        diff: fn(&T, &T) -> Option<D>,
+        apply: fn(&T, &D) -> T,
+    ) -> Self {
+        Self {
+            entries: vec![Entry::Snapshot(initial_state.clone())],
+            current: 0,
+            state: initial_state,
+            snapshot_interval: snapshot_interval.max(1),
+            reducer,
+            diff,
+            apply,
+        }
+    }
+
+    fn from_history(
+        history: Vec<T>,
+        current: usize,
+        reducer: TimelineReducer<T, Action>,
+        snapshot_interval: usize,
+        diff: fn(&T, &T) -> Option<D>,
+        apply: fn(&T, &D) -> T,
+    ) -> Self {
+        let mut states = history.into_iter();
+        let initial_state = states.next().expect("a history holds at least one state");
+        let mut manager = Self::with_codec(initial_state, reducer, snapshot_interval, diff, apply);
+        for state in states {
+            manager.record(state);
+        }
+        manager.jump_to(current);
+        manager
+    }
+
+    /// Dispatches an action to create a new state.
+    ///
+    /// As with `StateManager::dispatch()`, states after the current position
+    /// are discarded.
+    pub fn dispatch(&mut self, action: Action) {
+        let new_state = (self.reducer)(&self.state, &action);
+        self.entries.truncate(self.current + 1);
+        self.record(new_state);
+    }
+
+    /// Appends a state after the last entry and moves to it.
+    fn record(&mut self, new_state: T) {
+        let index = self.entries.len();
+        let entry = match index % self.snapshot_interval {
+            0 => Entry::Snapshot(new_state.clone()),
+            _ => match (self.diff)(&self.state, &new_state) {
+                Some(delta) => Entry::Delta(delta),
+                None => Entry::Snapshot(new_state.clone()),
+            },
+        };
+        self.entries.push(entry);
+        self.state = new_state;
+        self.current = index;
+    }
+
+    /// Rewinds the timeline by the specified number of steps.
+    pub fn rewind(&mut self, steps: usize) {
+        self.jump_to(self.current.saturating_sub(steps));
+    }
+
+    /// Moves the timeline forward again by the specified number of steps.
+    pub fn redo(&mut self, steps: usize) {
+        self.jump_to(
+            self.current
+                .saturating_add(steps)
+                .min(self.entries.len() - 1),
+        );
+    }
+
+    /// Returns `true` if there is an earlier state to rewind to.
+    pub fn can_undo(&self) -> bool {
+        self.current > 0
+    }
+
+    /// Returns `true` if there is a rewound state to redo.
+    pub fn can_redo(&self) -> bool {
+        self.current + 1 < self.entries.len()
+    }
+
+    /// Moves to the state at the given position in the history.
+    ///
+    /// # Returns
+    ///
+    /// `false`, without moving, if the position is past the end of the history.
+    pub fn jump_to(&mut self, index: usize) -> bool {
+        if index == self.current {
+            return true;
+        }
+        match self.state_at(index) {
+            Some(state) => {
+                self.state = state;
+                self.current = index;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Rebuilds the state at the given position without moving there.
+    ///
+    /// Starts from the current state when the position lies ahead of it, or
+    /// from the nearest snapshot otherwise.
+    pub fn state_at(&self, index: usize) -> Option<T> {
+        if index >= self.entries.len() {
+            return None;
+        }
+        let (snapshot, base) = (0..=index)
+            .rev()
+            .find_map(|i| match &self.entries[i] {
+                Entry::Snapshot(state) => Some((i, state)),
+                Entry::Delta(_) => None,
+            })
+            .expect("the first entry is always a snapshot");
+
+        let (start, mut state) = if (snapshot..=index).contains(&self.current) {
+            (self.current, self.state.clone())
+        } else {
+            (snapshot, base.clone())
+        };
+        for entry in &self.entries[start + 1..=index] {
+            state = match entry {
+                Entry::Snapshot(snapshot) => snapshot.clone(),
+                Entry::Delta(delta) => (self.apply)(&state, delta),
+            };
+        }
+        Some(state)
+    }
+
+    /// Returns a reference to the current state.
+    pub fn current_state(&self) -> &T {
+        &self.state
+    }
+
+    /// Returns the length of the timeline history.
+    pub fn history_len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns the current position in the timeline.
+    pub fn current_position(&self) -> usize {
+        self.current
+    }
+
+    /// Returns the number of history entries stored as full snapshots.
+    pub fn snapshot_count(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| matches!(entry, Entry::Snapshot(_)))
+            .count()
+    }
+}
@@ -0,0 +1,304 @@
+//! # DevTools Module
+//!
+//! A small WebSocket server that lets an external inspector attach to a
+//! running store. Requires the `devtools` feature.
+//!
+//! Every connected client receives JSON events:
+//!
+//! - `{"type": "init", "state": ...}` right after connecting
+//! - `{"type": "action", "action": ..., "state": ...}` after every applied action
+//! - `{"type": "state", "state": ...}` after the state was replaced by a command
+//!
+//! and may send JSON commands:
+//!
+//! - `{"type": "jump_to_state", "state": ...}` replaces the store's state
+//! - `{"type": "dispatch", "action": ...}` dispatches an action
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use serde::{Deserialize, Serialize};
+//! use zed::{DevToolsServer, Store, create_reducer};
+//!
+//! #[derive(Clone, Serialize, Deserialize)]
+//! struct State { count: i32 }
+//!
+//! #[derive(Clone, Serialize, Deserialize)]
+//! enum Action { Increment }
+//!
+//! let store = Store::new(
+//!     State { count: 0 },
+//!     Box::new(create_reducer(|state: &State, _: &Action| State { count: state.count + 1 })),
+//! );
+//!
+//! let devtools = DevToolsServer::start(store.clone(), "127.0.0.1:8000").unwrap();
+//! println!("inspector can connect to ws://{}", devtools.local_addr());
+//! ```
+
+use crate::store::{Store, SubscriptionId};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::Duration;
+use tungstenite::{Message, WebSocket};
+
+/// How long server threads sleep between polls for connections and commands.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// An event sent to connected inspectors.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum DevToolsEvent<'a, State, Action> {
+    Init {
+        state: &'a State,
+    },
+    Action {
+        action: &'a Action,
+        state: &'a State,
+    },
+    State {
+        state: &'a State,
+    },
+}
+
+/// A command sent by an inspector.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum DevToolsCommand<State, Action> {
+    JumpToState { state: State },
+    Dispatch { action: Action },
+}
+
+type Clients = Arc<Mutex<Vec<Sender<String>>>>;
+
+/// Sends an encoded event to every client, dropping disconnected ones.
+fn broadcast(clients: &Clients, message: String) {
+    clients
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .retain(|client| client.send(message.clone()).is_ok());
+}
+
+/// A running DevTools WebSocket server attached to a store.
+///
+/// The server stops accepting connections and disconnects its clients when
+/// dropped.
+pub struct DevToolsServer<State, Action>
+where
+    State: Clone + Send + 'static,
+    Action: Send + 'static,
+{
+    store: Store<State, Action>,
+    addr: SocketAddr,
+    listener: SubscriptionId,
+    clients: Clients,
+    running: Arc<AtomicBool>,
+}
+
+impl<State, Action> DevToolsServer<State, Action>
+where
+    State: Clone + Send + Sync + Serialize + DeserializeOwned + 'static,
+    Action: Send + Serialize + DeserializeOwned + 'static,
+{
+    /// Starts a DevTools server for the store on the given address.
+    ///
+    /// Bind to port `0` to let the OS pick a free port, then read it back
+    /// with `local_addr()`.
+    ///
+    /// # Arguments
+    ///
+    /// * `store` - The store to inspect
+    /// * `addr` - The address to listen on
+    ///
+    /// # Returns
+    ///
+    /// The running server, or the error raised while binding the address.
+    pub fn start(store: Store<State, Action>, addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let tcp = TcpListener::bind(addr)?;
+        tcp.set_nonblocking(true)?;
+        let addr = tcp.local_addr()?;
+
+        let clients: Clients = Arc::new(Mutex::new(Vec::new()));
+        let running = Arc::new(AtomicBool::new(true));
+
+        let event_clients = Arc::clone(&clients);
+        let listener = store.add_listener(
+            |_| true,
+            move |action: &Action, store: &Store<State, Action>| {
+                let state = store.get_state_shared();
+                let event = DevToolsEvent::Action {
+                    action,
+                    state: &*state,
+                };
+                if let Ok(message) = serde_json::to_string(&event) {
+                    broadcast(&event_clients, message);
+                }
+            },
+        );
+
+        let accept_store = store.clone();
+        let accept_clients = Arc::clone(&clients);
+        let accept_running = Arc::clone(&running);
+        thread::Builder::new()
+            .name("zed-devtools".to_string())
+            .spawn(move || {
+                while accept_running.load(Ordering::SeqCst) {
+                    match tcp.accept() {
+                        Ok((stream, _)) => {
+                            let (sender, receiver) = mpsc::channel();
+                            accept_clients
+                                .lock()
+                                .unwrap_or_else(PoisonError::into_inner)
+                                .push(sender);
+                            let store = accept_store.clone();
+                            let clients = Arc::clone(&accept_clients);
+                            let running = Arc::clone(&accept_running);
+                            thread::spawn(move || {
+                                serve_client(stream, receiver, store, clients, running)
+                            });
+                        }
+                        Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                            thread::sleep(POLL_INTERVAL)
+                        }
+                        Err(_) => thread::sleep(POLL_INTERVAL),
+                    }
+                }
+            })?;
+
+        Ok(Self {
+            store,
+            addr,
+            listener,
+            clients,
+            running,
+        })
+    }
+}
+
+impl<State, Action> DevToolsServer<State, Action>
+where
+    State: Clone + Send + 'static,
+    Action: Send + 'static,
+{
+    /// Returns the address the server is listening on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Returns how many inspectors are currently connected.
+    pub fn client_count(&self) -> usize {
+        self.clients
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+}
+
+impl<State, Action> Drop for DevToolsServer<State, Action>
+where
+    State: Clone + Send + 'static,
+    Action: Send + 'static,
+{
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        self.store.unsubscribe(self.listener);
+        self.clients
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+}
+
+/// Runs one client connection until it closes or the server stops.
+fn serve_client<State, Action>(
+    stream: TcpStream,
+    events: Receiver<String>,
+    store: Store<State, Action>,
+    clients: Clients,
+    running: Arc<AtomicBool>,
+) where
+    State: Clone + Send + Sync + Serialize + DeserializeOwned + 'static,
+    Action: Send + Serialize + DeserializeOwned + 'static,
+{
+    // The accepted stream inherits non-blocking mode from the listener; the
+    // handshake is done blocking, then reads poll with a short timeout
+    let setup = stream
+        .set_nonblocking(false)
+        .and_then(|_| stream.set_read_timeout(Some(POLL_INTERVAL)));
+    if setup.is_err() {
+        return;
+    }
+    let Ok(mut socket) = tungstenite::accept(stream) else {
+        return;
+    };
+
+    let init = store.with_state(|state| {
+        let event: DevToolsEvent<'_, State, Action> = DevToolsEvent::Init { state };
+        serde_json::to_string(&event)
+    });
+    let Ok(init) = init else {
+        return;
+    };
+    if socket.send(Message::text(init)).is_err() {
+        return;
+    }
+
+    while running.load(Ordering::SeqCst) {
+        loop {
+            match events.try_recv() {
+                Ok(event) => {
+                    if socket.send(Message::text(event)).is_err() {
+                        return;
+                    }
+                }
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => return close(&mut socket),
+            }
+        }
+
+        match socket.read() {
+            Ok(Message::Text(text)) => handle_command(text.as_str(), &store, &clients),
+            Ok(Message::Close(_)) => return,
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(err))
+                if matches!(
+                    err.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) => {}
+            Err(_) => return,
+        }
+    }
+    close(&mut socket);
+}
+
+fn close(socket: &mut WebSocket<TcpStream>) {
+    let _ = socket.close(None);
+    let _ = socket.flush();
+}
+
+/// Applies a command received from an inspector; invalid commands are ignored.
+fn handle_command<State, Action>(text: &str, store: &Store<State, Action>, clients: &Clients)
+where
+    State: Clone + Send + Sync + Serialize + DeserializeOwned + 'static,
+    Action: Send + Serialize + DeserializeOwned + 'static,
+{
+    match serde_json::from_str::<DevToolsCommand<State, Action>>(text) {
+        Ok(DevToolsCommand::JumpToState { state }) => {
+            store.replace_state(state);
+            let event = store.with_state(|state| {
+                let event: DevToolsEvent<'_, State, Action> = DevToolsEvent::State { state };
+                serde_json::to_string(&event)
+            });
+            if let Ok(event) = event {
+                broadcast(clients, event);
+            }
+        }
+        Ok(DevToolsCommand::Dispatch { action }) => store.dispatch(action),
+        Err(_) => {}
+    }
+}
@@ -0,0 +1,61 @@
+//! # Effect Module
+//!
+//! Effects are the home for side effects that react to actions: after an
+//! action has been reduced, every registered [`Effect`] sees the action and
+//! the new state, may perform work such as I/O, and returns follow-up
+//! actions for the store to dispatch.
+//!
+//! Effects are registered with [`Store::add_effect`](crate::Store::add_effect)
+//! and removed with [`Store::unsubscribe`](crate::Store::unsubscribe).
+//!
+//! ## Example
+//!
+//! ```rust
+//! use zed::{Store, create_reducer};
+//!
+//! #[derive(Clone)]
+//! struct State { query: String, results: Vec<String> }
+//!
+//! enum Action { Search(String), Loaded(Vec<String>) }
+//!
+//! let store = Store::new(
+//!     State { query: String::new(), results: vec![] },
+//!     Box::new(create_reducer(|state: &State, action: &Action| match action {
+//!         Action::Search(query) => State { query: query.clone(), ..state.clone() },
+//!         Action::Loaded(results) => State { results: results.clone(), ..state.clone() },
+//!     })),
+//! );
+//!
+//! store.add_effect(|action: &Action, state: &State| match action {
+//!     // A real effect would query a backend here
+//!     Action::Search(_) => vec![Action::Loaded(vec![format!("result for {}", state.query)])],
+//!     Action::Loaded(_) => vec![],
+//! });
+//!
+//! store.dispatch(Action::Search("zed".to_string()));
+//! assert_eq!(store.get_state().results, vec!["result for zed".to_string()]);
+//! ```
+
+/// Reacts to reduced actions and produces follow-up actions.
+pub trait Effect<State, Action>: Send + Sync {
+    /// Runs the effect for an action that has been applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `action` - The applied action
+    /// * `state` - The state after the action was applied
+    ///
+    /// # Returns
+    ///
+    /// The actions to dispatch next, in order.
+    fn run(&self, action: &Action, state: &State) -> Vec<Action>;
+}
+
+impl<State, Action, F> Effect<State, Action> for F
+where
+    F: Fn(&Action, &State) -> Vec<Action> + Send + Sync,
+{
+    fn run(&self, action: &Action, state: &State) -> Vec<Action> {
+        self(action, state)
+    }
+}
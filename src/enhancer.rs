@@ -0,0 +1,125 @@
+//! # Enhancer Module
+//!
+//! Store enhancers layer cross-cutting behaviour (devtools, persistence,
+//! logging, ...) onto a store in a composable way, similar to Redux's
+//! `enhancer` argument to `createStore`.
+//!
+//! An enhancer gets two hooks, both optional:
+//!
+//! - `enhance_reducer` wraps the reducer before the store is created
+//! - `enhance_store` decorates the created store, e.g. by adding subscribers
+//!   or listeners
+//!
+//! Enhancers are composed with [`StoreEnhancer::and_then`] and applied with
+//! [`configure_store_with_enhancer`](crate::configure_store::configure_store_with_enhancer).
+//!
+//! ## Example
+//!
+//! ```rust
+//! use std::sync::{Arc, Mutex};
+//! use zed::reducer::BoxedReducer;
+//! use zed::{Store, StoreEnhancer, configure_store_with_enhancer, create_reducer};
+//!
+//! #[derive(Clone)]
+//! struct State { count: i32 }
+//!
+//! enum Action { Increment }
+//!
+//! // An enhancer that applies every action twice
+//! struct Twice;
+//!
+//! impl StoreEnhancer<State, Action> for Twice {
+//!     fn enhance_reducer(&self, reducer: BoxedReducer<State, Action>) -> BoxedReducer<State, Action> {
+//!         Box::new(create_reducer(move |state: &State, action: &Action| {
+//!             let once = reducer.reduce(state, action);
+//!             reducer.reduce(&once, action)
+//!         }))
+//!     }
+//! }
+//!
+//! let log = Arc::new(Mutex::new(Vec::new()));
+//! let log_clone = Arc::clone(&log);
+//!
+//! // Any closure taking and returning a store is an enhancer too
+//! let logger = move |store: Store<State, Action>| {
+//!     let log = Arc::clone(&log_clone);
+//!     store.subscribe(move |state: &State| log.lock().unwrap().push(state.count));
+//!     store
+//! };
+//!
+//! let store = configure_store_with_enhancer(
+//!     State { count: 0 },
+//!     create_reducer(|state: &State, _: &Action| State { count: state.count + 1 }),
+//!     Twice.and_then(logger),
+//! );
+//!
+//! store.dispatch(Action::Increment);
+//! assert_eq!(*log.lock().unwrap(), vec![2]);
+//! ```
+
+use crate::reducer::BoxedReducer;
+use crate::store::Store;
+
+/// A composable extension applied when a store is configured.
+pub trait StoreEnhancer<State, Action> {
+    /// Wraps the reducer before the store is created.
+    ///
+    /// The default implementation returns the reducer unchanged.
+    fn enhance_reducer(&self, reducer: BoxedReducer<State, Action>) -> BoxedReducer<State, Action> {
+        reducer
+    }
+
+    /// Decorates the store after it has been created.
+    ///
+    /// The default implementation returns the store unchanged.
+    fn enhance_store(&self, store: Store<State, Action>) -> Store<State, Action> {
+        store
+    }
+
+    /// Composes this enhancer with another one, applied after this one.
+    ///
+    /// Reducers are wrapped from the inside out, so `next` wraps the reducer
+    /// produced by `self`, and `next` decorates the store produced by `self`.
+    fn and_then<E>(self, next: E) -> ComposedEnhancer<Self, E>
+    where
+        Self: Sized,
+        E: StoreEnhancer<State, Action>,
+    {
+        ComposedEnhancer {
+            first: self,
+            second: next,
+        }
+    }
+}
+
+impl<State, Action, F> StoreEnhancer<State, Action> for F
+where
+    F: Fn(Store<State, Action>) -> Store<State, Action>,
+{
+    fn enhance_store(&self, store: Store<State, Action>) -> Store<State, Action> {
+        self(store)
+    }
+}
+
+/// Two enhancers applied one after the other.
+///
+/// Created by [`StoreEnhancer::and_then`].
+pub struct ComposedEnhancer<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<State, Action, A, B> StoreEnhancer<State, Action> for ComposedEnhancer<A, B>
+where
+    A: StoreEnhancer<State, Action>,
+    B: StoreEnhancer<State, Action>,
+{
+    fn enhance_reducer(&self, reducer: BoxedReducer<State, Action>) -> BoxedReducer<State, Action> {
+        self.second
+            .enhance_reducer(self.first.enhance_reducer(reducer))
+    }
+
+    fn enhance_store(&self, store: Store<State, Action>) -> Store<State, Action> {
+        self.second.enhance_store(self.first.enhance_store(store))
+    }
+}
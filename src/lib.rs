@@ -6,9 +6,23 @@
 //!
 //! - Redux-like Store with centralized state management
 //! - Timeline for undo/redo functionality
-//! - State Mesh for distributed state synchronization
+//! - Time travel for stores, with undo, redo and jumps through their history
+//! - Delta-compressed timelines for large states
+//! - State Mesh for distributed state synchronization, in process or over TCP
+//!   and WebSockets (with the `tcp` and `websocket` features)
+//! - LZ4 and Zstandard compression of mesh synchronization (with the `lz4`
+//!   and `zstd` features)
+//! - Encrypted, authenticated mesh channels between trusted node identities
+//!   (with the `encryption` feature)
+//! - mDNS discovery of mesh peers on the local network (with the `discovery`
+//!   feature)
+//! - CRDT state types that merge without conflicts
 //! - Capsules for encapsulated state domains
 //! - Reactive System for event-driven updates
+//! - Memoized selectors for derived state
+//! - Middleware, including a built-in action logger
+//! - Persistence of store state to disk
+//! - Append-only JSONL audit log of applied actions (with the `audit` feature)
 //!
 //! ## Quick Start
 //!
@@ -50,23 +64,117 @@
 //! # }
 //! ```
 
+#[cfg(feature = "audit")]
+pub mod audit;
+pub mod bridge;
+pub mod cancellation;
 pub mod capsule;
+pub mod combine_reducers;
 pub mod configure_store;
+pub mod crdt;
 pub mod create_slice;
+pub mod delta;
+#[cfg(feature = "devtools")]
+pub mod devtools;
+pub mod effect;
+pub mod enhancer;
+mod executor;
+pub mod meta;
+pub mod metrics;
+pub mod middleware;
+pub mod optimistic;
+pub mod persist;
+pub mod projection;
+pub mod queue;
 pub mod reactive;
+pub mod recorder;
 pub mod reducer;
+pub mod saga;
+pub mod scoped;
+pub mod selector;
+pub mod sharded;
 pub mod simple_cache;
 pub mod state_mesh;
+pub mod storage;
 pub mod store;
+pub mod time_travel;
 pub mod timeline;
+pub mod undoable;
+mod worker;
 
+#[cfg(feature = "audit")]
+pub use audit::{AuditEntry, AuditLog};
+pub use bridge::StoreBridge;
+pub use cancellation::CancellationToken;
 pub use capsule::{Cache, Capsule};
-pub use configure_store::configure_store;
+pub use configure_store::{configure_store, configure_store_with_enhancer};
+pub use crdt::{CrdtMap, GCounter, LwwRegister, Mergeable, OrSet, PNCounter};
+pub use delta::{DeltaStateManager, Diff, FieldChange, JsonDelta, StateDiff};
+#[cfg(feature = "devtools")]
+pub use devtools::DevToolsServer;
+pub use effect::Effect;
+pub use enhancer::{ComposedEnhancer, StoreEnhancer};
+pub use meta::{ActionMeta, MetaReducer, WithMeta, with_meta};
+pub use metrics::{ActionMetrics, NodeMetrics, StoreMetrics};
+pub use middleware::{
+    LogVerbosity, LoggerMiddleware, Middleware, MiddlewareEnhancer, Next, apply_middleware,
+};
+pub use optimistic::OptimisticHandle;
 pub use paste::paste;
-pub use reactive::ReactiveSystem;
-pub use reducer::{ClosureReducer, Reducer, create_reducer};
+pub use persist::{
+    PersistError, PersistedStore, configure_store_persisted, configure_store_with_storage,
+};
+pub use projection::ProjectedStore;
+pub use queue::Priority;
+pub use reactive::{Computed, EmitError, Flow, ReactionContext, ReactionId, ReactiveSystem};
+pub use recorder::{ActionLog, ActionRecorder, RecordedAction, ReplayMismatch};
+pub use reducer::{
+    ClosureReducer, ClosureTryReducer, FallibleReducer, Reducer, ReducerError, TryReducer,
+    create_reducer, create_try_reducer,
+};
+pub use saga::{Saga, SagaHandle};
+pub use scoped::{Lens, ScopedStore};
+pub use selector::{Selector, create_selector};
+pub use sharded::ShardedStore;
 pub use simple_cache::SimpleCache;
-pub use state_mesh::StateNode;
+pub use state_mesh::compression::Compression;
+#[cfg(feature = "discovery")]
+pub use state_mesh::discovery::{DiscoveredPeer, Discovery};
+pub use state_mesh::dot::export_dot;
+pub use state_mesh::field_merge::FieldMerge;
+pub use state_mesh::gossip::{Gossip, GossipRound};
+pub use state_mesh::lease::{Lease, LeaseError, LeasePolicy};
+pub use state_mesh::merkle::MerkleTree;
+pub use state_mesh::mesh_store::MeshStore;
+#[cfg(feature = "async")]
+pub use state_mesh::propagator::AsyncPropagator;
+pub use state_mesh::scheduler::MeshScheduler;
+#[cfg(feature = "encryption")]
+pub use state_mesh::secure::{NodeIdentity, SecureTransport};
+pub use state_mesh::snapshot::{load_mesh, save_mesh};
+#[cfg(feature = "tcp")]
+pub use state_mesh::transport::TcpTransport;
+#[cfg(feature = "websocket")]
+pub use state_mesh::transport::WebSocketTransport;
+pub use state_mesh::transport::{MeshLink, MeshMessage, Transport};
+pub use state_mesh::{
+    ConflictEvent, ConflictOutcome, QuorumError, SharedStateNode, StateNode, UpdateId,
+};
+#[cfg(feature = "redb")]
+pub use storage::RedbStorage;
+#[cfg(feature = "sled")]
+pub use storage::SledStorage;
+pub use storage::{JsonFileStorage, MemoryStorage, StorageBackend, VersionedStorage};
 pub use store::Store;
-pub use store::SubscriptionId;
-pub use timeline::StateManager;
+#[cfg(feature = "async")]
+pub use store::{ACTION_STREAM_CAPACITY, STREAM_BATCH_LIMIT};
+pub use store::{
+    BatchGuard, DispatchLock, DispatchOutcome, NotificationMode, StateGuard, StoreBuilder,
+    SubscriptionGuard, SubscriptionId,
+};
+pub use store::{Dispatch, GetState, PanicPolicy, ReducerPanic};
+pub use time_travel::TimeTravelConfig;
+pub use timeline::{
+    CheckpointPolicy, GcStats, RetentionPolicy, SharedStateManager, StateManager, TimelineEvent,
+};
+pub use undoable::UndoableStore;
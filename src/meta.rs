@@ -0,0 +1,178 @@
+//! # Meta Module
+//!
+//! Metadata attached to dispatched actions, for auditing and for correlating
+//! actions across devtools, logs and synchronized stores.
+//!
+//! A store whose action type is [`WithMeta<Action>`] dispatches actions in an
+//! envelope carrying an [`ActionMeta`]: a unique ID, a timestamp, and an
+//! optional source and correlation ID. Reducers written for the plain action
+//! type are reused unchanged by wrapping them with [`with_meta`], while
+//! middleware and action listeners see the whole envelope.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use zed::{ActionMeta, Store, WithMeta, create_reducer, with_meta};
+//!
+//! #[derive(Clone)]
+//! struct State { count: i32 }
+//!
+//! #[derive(Clone)]
+//! enum Action { Increment }
+//!
+//! let store: Store<State, WithMeta<Action>> = Store::new(
+//!     State { count: 0 },
+//!     Box::new(with_meta(create_reducer(|state: &State, _: &Action| State { count: state.count + 1 }))),
+//! );
+//!
+//! store.add_listener(|_| true, |action: &WithMeta<Action>, _| {
+//!     println!("action {} from {:?}", action.meta.id, action.meta.source);
+//! });
+//!
+//! // Plain actions get fresh metadata
+//! store.dispatch(Action::Increment.into());
+//! store.dispatch(WithMeta::with(Action::Increment, ActionMeta::new().with_source("button")));
+//! assert_eq!(store.get_state().count, 2);
+//! ```
+
+use crate::reducer::{Reducer, ReducerError};
+use serde::{Deserialize, Serialize};
+use std::ops::Deref;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
+
+static NEXT_ACTION_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Metadata describing one dispatched action.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActionMeta {
+    /// Identifier of the action, unique within the process
+    pub id: u64,
+    /// Wall-clock time at which the metadata was created
+    pub timestamp: SystemTime,
+    /// Where the action came from, e.g. a component or a remote peer
+    pub source: Option<String>,
+    /// Groups actions that belong to the same operation
+    pub correlation_id: Option<String>,
+}
+
+impl ActionMeta {
+    /// Creates metadata with a fresh ID and the current time.
+    pub fn new() -> Self {
+        Self {
+            id: NEXT_ACTION_ID.fetch_add(1, Ordering::Relaxed),
+            timestamp: SystemTime::now(),
+            source: None,
+            correlation_id: None,
+        }
+    }
+
+    /// Sets where the action came from.
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Sets the correlation ID.
+    pub fn with_correlation_id(mut self, correlation_id: impl Into<String>) -> Self {
+        self.correlation_id = Some(correlation_id.into());
+        self
+    }
+
+    /// Creates metadata for an action caused by the one described by `self`.
+    ///
+    /// The new metadata keeps the correlation ID and source, or uses this
+    /// action's ID as correlation ID if it has none.
+    pub fn follow_up(&self) -> Self {
+        Self {
+            source: self.source.clone(),
+            correlation_id: Some(
+                self.correlation_id
+                    .clone()
+                    .unwrap_or_else(|| self.id.to_string()),
+            ),
+            ..Self::new()
+        }
+    }
+}
+
+impl Default for ActionMeta {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An action together with its metadata.
+///
+/// Dereferences to the wrapped action.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WithMeta<Action> {
+    /// The dispatched action
+    pub action: Action,
+    /// The action's metadata
+    pub meta: ActionMeta,
+}
+
+impl<Action> WithMeta<Action> {
+    /// Wraps an action with fresh metadata.
+    pub fn new(action: Action) -> Self {
+        Self::with(action, ActionMeta::new())
+    }
+
+    /// Wraps an action with the given metadata.
+    pub fn with(action: Action, meta: ActionMeta) -> Self {
+        Self { action, meta }
+    }
+
+    /// Unwraps the action, discarding the metadata.
+    pub fn into_action(self) -> Action {
+        self.action
+    }
+}
+
+impl<Action> From<Action> for WithMeta<Action> {
+    fn from(action: Action) -> Self {
+        Self::new(action)
+    }
+}
+
+impl<Action> Deref for WithMeta<Action> {
+    type Target = Action;
+
+    fn deref(&self) -> &Action {
+        &self.action
+    }
+}
+
+/// Adapts a reducer of plain actions to actions with metadata.
+///
+/// Created by [`with_meta`].
+pub struct MetaReducer<R> {
+    inner: R,
+}
+
+impl<State, Action, R> Reducer<State, WithMeta<Action>> for MetaReducer<R>
+where
+    R: Reducer<State, Action>,
+{
+    fn reduce(&self, state: &State, action: &WithMeta<Action>) -> State {
+        self.inner.reduce(state, &action.action)
+    }
+
+    fn try_reduce(&self, state: &State, action: &WithMeta<Action>) -> Result<State, ReducerError> {
+        self.inner.try_reduce(state, &action.action)
+    }
+}
+
+/// Lets a reducer of plain actions reduce actions with metadata.
+///
+/// # Arguments
+///
+/// * `reducer` - The reducer to apply to the wrapped actions
+///
+/// # Returns
+///
+/// A reducer of `WithMeta<Action>` that ignores the metadata.
+pub fn with_meta<R>(reducer: R) -> MetaReducer<R> {
+    MetaReducer { inner: reducer }
+}
@@ -0,0 +1,190 @@
+//! # Metrics Module
+//!
+//! Optional instrumentation of store dispatches, enabled with
+//! [`Store::with_metrics`](crate::Store::with_metrics).
+//!
+//! The store measures how long every reducer run and every subscriber
+//! notification takes, and counts actions per variant, so slow actions can
+//! be found without an external profiler.
+//!
+//! State mesh nodes always count what they send, receive and resolve; see
+//! [`StateNode::metrics`](crate::StateNode::metrics).
+//!
+//! ## Example
+//!
+//! ```rust
+//! use zed::{Store, create_reducer};
+//!
+//! #[derive(Clone)]
+//! struct State { count: i32 }
+//!
+//! #[derive(Debug)]
+//! enum Action { Add(i32), Reset }
+//!
+//! let store = Store::new(
+//!     State { count: 0 },
+//!     Box::new(create_reducer(|state: &State, action: &Action| match action {
+//!         Action::Add(n) => State { count: state.count + n },
+//!         Action::Reset => State { count: 0 },
+//!     })),
+//! )
+//! .with_metrics();
+//!
+//! store.dispatch(Action::Add(1));
+//! store.dispatch(Action::Add(2));
+//! store.dispatch(Action::Reset);
+//!
+//! let metrics = store.metrics();
+//! assert_eq!(metrics.actions["Add"].count, 2);
+//! assert_eq!(metrics.actions["Reset"].count, 1);
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+/// Timing and count of one action variant.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ActionMetrics {
+    /// How many times the reducer ran for this variant
+    pub count: u64,
+    /// Total time spent in the reducer for this variant
+    pub total_reducer_time: Duration,
+    /// Longest single reducer run for this variant
+    pub max_reducer_time: Duration,
+}
+
+impl ActionMetrics {
+    /// Returns the average reducer time, or zero if the variant never ran.
+    pub fn average_reducer_time(&self) -> Duration {
+        match u32::try_from(self.count) {
+            Ok(0) => Duration::ZERO,
+            Ok(count) => self.total_reducer_time / count,
+            Err(_) => self.total_reducer_time.div_f64(self.count as f64),
+        }
+    }
+}
+
+/// A snapshot of the metrics collected by a store.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StoreMetrics {
+    /// Total number of reducer runs
+    pub reducer_runs: u64,
+    /// Total time spent in the reducer
+    pub reducer_time: Duration,
+    /// Number of subscriber notifications
+    pub notifications: u64,
+    /// Total time spent notifying subscribers
+    pub notify_time: Duration,
+    /// Per-variant metrics, keyed by the variant name
+    pub actions: HashMap<String, ActionMetrics>,
+}
+
+/// A snapshot of the counters of a state mesh node.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NodeMetrics {
+    /// States and operations sent to peers, counted once per peer
+    pub updates_sent: u64,
+    /// Remote states and operations applied by the node
+    pub updates_received: u64,
+    /// Conflicts in which the node kept its own state
+    pub conflicts_kept_local: u64,
+    /// Conflicts in which the node took over the remote state
+    pub conflicts_accepted_remote: u64,
+    /// Conflicts in which the node combined both states
+    pub conflicts_merged: u64,
+    /// JSON-encoded size of the messages sent over `MeshLink`s
+    pub bytes_sent: u64,
+    /// JSON-encoded size of the messages received over `MeshLink`s
+    pub bytes_received: u64,
+    /// Number of propagations started by the node
+    pub propagations: u64,
+    /// Total time spent delivering propagations to peers
+    pub propagation_time: Duration,
+    /// Longest single propagation
+    pub max_propagation_time: Duration,
+}
+
+impl NodeMetrics {
+    /// Returns the number of conflicts resolved, whatever the outcome.
+    pub fn conflicts(&self) -> u64 {
+        self.conflicts_kept_local + self.conflicts_accepted_remote + self.conflicts_merged
+    }
+
+    /// Returns the average propagation time, or zero if the node never
+    /// propagated.
+    pub fn average_propagation_time(&self) -> Duration {
+        match u32::try_from(self.propagations) {
+            Ok(0) => Duration::ZERO,
+            Ok(count) => self.propagation_time / count,
+            Err(_) => self.propagation_time.div_f64(self.propagations as f64),
+        }
+    }
+
+    pub(crate) fn record_propagation(&mut self, sent: usize, elapsed: Duration) {
+        self.updates_sent += sent as u64;
+        self.propagations += 1;
+        self.propagation_time += elapsed;
+        self.max_propagation_time = self.max_propagation_time.max(elapsed);
+    }
+}
+
+/// Collects metrics for a store; shared by all of its clones.
+pub(crate) struct MetricsCollector<Action> {
+    label: fn(&Action) -> String,
+    data: Mutex<StoreMetrics>,
+}
+
+impl<Action> MetricsCollector<Action> {
+    /// Creates a collector naming actions after their `Debug` variant name.
+    pub(crate) fn new() -> Self
+    where
+        Action: Debug,
+    {
+        Self {
+            label: variant_name::<Action>,
+            data: Mutex::new(StoreMetrics::default()),
+        }
+    }
+
+    pub(crate) fn record_reducer(&self, action: &Action, elapsed: Duration) {
+        let label = (self.label)(action);
+        let mut data = self.data.lock().unwrap_or_else(PoisonError::into_inner);
+        data.reducer_runs += 1;
+        data.reducer_time += elapsed;
+
+        let entry = data.actions.entry(label).or_default();
+        entry.count += 1;
+        entry.total_reducer_time += elapsed;
+        entry.max_reducer_time = entry.max_reducer_time.max(elapsed);
+    }
+
+    pub(crate) fn record_notify(&self, elapsed: Duration) {
+        let mut data = self.data.lock().unwrap_or_else(PoisonError::into_inner);
+        data.notifications += 1;
+        data.notify_time += elapsed;
+    }
+
+    pub(crate) fn snapshot(&self) -> StoreMetrics {
+        self.data
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    pub(crate) fn reset(&self) {
+        *self.data.lock().unwrap_or_else(PoisonError::into_inner) = StoreMetrics::default();
+    }
+}
+
+/// Extracts the variant name from an action's `Debug` output.
+///
+/// `Add(1)` and `Rename { name: .. }` become `Add` and `Rename`.
+fn variant_name<Action: Debug>(action: &Action) -> String {
+    let debug = format!("{action:?}");
+    let end = debug
+        .find(|c: char| c == '(' || c == '{' || c.is_whitespace())
+        .unwrap_or(debug.len());
+    debug[..end].to_string()
+}
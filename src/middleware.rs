@@ -0,0 +1,338 @@
+//! # Middleware Module
+//!
+//! Middleware wraps every dispatched action, Redux style: it gets the store
+//! and the action on its way to the reducer, decides whether and how to pass
+//! it on, and can read the resulting state once the rest of the chain has
+//! run.
+//!
+//! Middleware runs on the dispatch path, before the reducer lock is taken,
+//! so it may dispatch further actions itself. It sees the actions passed to
+//! `dispatch()`, `dispatch_try()` and `dispatch_checked()`, and so also
+//! queued actions and effect follow-ups; batches and optimistic updates go
+//! straight to the reducer.
+//!
+//! Middleware is applied to a store as an enhancer with [`apply_middleware`],
+//! so it composes with other enhancers through
+//! [`StoreEnhancer::and_then`](crate::enhancer::StoreEnhancer::and_then).
+//!
+//! ## Features
+//!
+//! - [`Middleware`] trait, also implemented for plain closures
+//! - [`LoggerMiddleware`] logging actions, timing and state diffs
+//!
+//! ## Example
+//!
+//! ```rust
+//! use zed::{LogVerbosity, LoggerMiddleware, apply_middleware, configure_store_with_enhancer, create_reducer};
+//!
+//! #[derive(Clone, Debug)]
+//! struct State { count: i32 }
+//!
+//! #[derive(Debug)]
+//! enum Action { Increment }
+//!
+//! let store = configure_store_with_enhancer(
+//!     State { count: 0 },
+//!     create_reducer(|state: &State, _: &Action| State { count: state.count + 1 }),
+//!     apply_middleware(
+//!         LoggerMiddleware::new(|entry: &str| println!("{entry}")).verbosity(LogVerbosity::Verbose),
+//!     ),
+//! );
+//!
+//! // Prints the action, how long the dispatch took and the changed lines
+//! store.dispatch(Action::Increment);
+//! ```
+
+use crate::enhancer::StoreEnhancer;
+use crate::reducer::ReducerError;
+use crate::store::Store;
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Continues a middleware chain with an action.
+///
+/// Runs the remaining middleware, then dispatches the action to the reducer.
+pub type Next<'a, Action> = &'a dyn Fn(Action) -> Result<(), ReducerError>;
+
+/// Intercepts actions on their way to the reducer.
+///
+/// `next` runs the rest of the chain (other middleware, then the reducer and
+/// the notifications). A middleware may call it once, skip it to drop the
+/// action, pass on a different action, or dispatch further actions through
+/// the store.
+pub trait Middleware<State, Action>: Send + Sync {
+    /// Handles an action.
+    ///
+    /// # Arguments
+    ///
+    /// * `store` - The store the action was dispatched to
+    /// * `action` - The dispatched action
+    /// * `next` - Continues the chain with an action
+    ///
+    /// # Returns
+    ///
+    /// The reducer's error if the action was rejected, as returned by `next`.
+    fn handle(
+        &self,
+        store: &Store<State, Action>,
+        action: Action,
+        next: Next<'_, Action>,
+    ) -> Result<(), ReducerError>;
+}
+
+impl<State, Action, F> Middleware<State, Action> for F
+where
+    F: Fn(&Store<State, Action>, Action, Next<'_, Action>) -> Result<(), ReducerError>
+        + Send
+        + Sync,
+{
+    fn handle(
+        &self,
+        store: &Store<State, Action>,
+        action: Action,
+        next: Next<'_, Action>,
+    ) -> Result<(), ReducerError> {
+        self(store, action, next)
+    }
+}
+
+/// Store enhancer adding a middleware to the store's dispatch path.
+///
+/// Created by [`apply_middleware`].
+pub struct MiddlewareEnhancer<M> {
+    middleware: Arc<M>,
+}
+
+/// Wraps a middleware into a store enhancer.
+///
+/// Apply several middleware by chaining the enhancers with `and_then`; the
+/// last one applied is the outermost and sees each action first.
+///
+/// # Example
+///
+/// ```rust
+/// use zed::{Next, ReducerError, Store, apply_middleware, configure_store_with_enhancer, create_reducer};
+///
+/// #[derive(Clone)]
+/// struct State { count: i32 }
+///
+/// enum Action { Add(i32) }
+///
+/// // Drops negative additions before they reach the reducer
+/// let only_positive = |_: &Store<State, Action>, action: Action, next: Next<'_, Action>| {
+///     match action {
+///         Action::Add(n) if n < 0 => Ok::<_, ReducerError>(()),
+///         _ => next(action),
+///     }
+/// };
+///
+/// let store = configure_store_with_enhancer(
+///     State { count: 0 },
+///     create_reducer(|state: &State, action: &Action| match action {
+///         Action::Add(n) => State { count: state.count + n },
+///     }),
+///     apply_middleware(only_positive),
+/// );
+///
+/// store.dispatch(Action::Add(-5));
+/// store.dispatch(Action::Add(2));
+/// assert_eq!(store.get_state().count, 2);
+/// ```
+pub fn apply_middleware<M>(middleware: M) -> MiddlewareEnhancer<M> {
+    MiddlewareEnhancer {
+        middleware: Arc::new(middleware),
+    }
+}
+
+impl<State, Action, M> StoreEnhancer<State, Action> for MiddlewareEnhancer<M>
+where
+    State: Clone + Send + 'static,
+    Action: Send + 'static,
+    M: Middleware<State, Action> + 'static,
+{
+    fn enhance_store(&self, store: Store<State, Action>) -> Store<State, Action> {
+        store.add_middleware(Arc::clone(&self.middleware) as Arc<dyn Middleware<State, Action>>);
+        store
+    }
+}
+
+/// How much detail [`LoggerMiddleware`] prints per action.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LogVerbosity {
+    /// Only the action's variant name
+    Minimal,
+    /// The action's variant name and how long its dispatch took (the
+    /// default)
+    #[default]
+    Normal,
+    /// The whole action, the timing and the lines of the state that changed
+    Verbose,
+}
+
+type LogSink = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// Middleware logging every action, with optional timing and state diff.
+///
+/// Each action produces one log entry, passed to the output given to
+/// [`LoggerMiddleware::new`], or emitted as a `tracing` event by
+/// `LoggerMiddleware::tracing()` with the `tracing` feature. Entries name
+/// the action by its variant, the text of its `Debug` output before any
+/// field; `LogVerbosity::Verbose` prints the whole `Debug` output.
+///
+/// The timing covers the reducer and the notifications, from the moment the
+/// action gets the reducer lock. State diffs compare the pretty-printed
+/// `Debug` output of the states the action was reduced between, taken under
+/// the reducer lock, so other threads' actions never show up in them.
+///
+/// # Example
+///
+/// ```rust
+/// use std::sync::{Arc, Mutex};
+/// use zed::{LogVerbosity, LoggerMiddleware, apply_middleware, configure_store_with_enhancer, create_reducer};
+///
+/// #[derive(Clone, Debug)]
+/// struct State { count: i32 }
+///
+/// #[derive(Debug)]
+/// enum Action { Increment }
+///
+/// let lines = Arc::new(Mutex::new(Vec::new()));
+/// let sink = Arc::clone(&lines);
+///
+/// let logger = LoggerMiddleware::new(move |entry: &str| sink.lock().unwrap().push(entry.to_string()))
+///     .verbosity(LogVerbosity::Minimal);
+///
+/// let store = configure_store_with_enhancer(
+///     State { count: 0 },
+///     create_reducer(|state: &State, _: &Action| State { count: state.count + 1 }),
+///     apply_middleware(logger),
+/// );
+///
+/// store.dispatch(Action::Increment);
+/// assert_eq!(*lines.lock().unwrap(), vec!["action Increment".to_string()]);
+/// ```
+#[derive(Clone)]
+pub struct LoggerMiddleware {
+    verbosity: LogVerbosity,
+    sink: LogSink,
+}
+
+impl LoggerMiddleware {
+    /// Creates a logger with `LogVerbosity::Normal` sending entries to `sink`.
+    ///
+    /// # Arguments
+    ///
+    /// * `sink` - Called with each log entry; multi-line entries are joined with `\n`
+    pub fn new<F>(sink: F) -> Self
+    where
+        F: Fn(&str) + Send + Sync + 'static,
+    {
+        Self {
+            verbosity: LogVerbosity::default(),
+            sink: Arc::new(sink),
+        }
+    }
+
+    /// Creates a logger emitting each entry as an `info` event of the
+    /// `zed::middleware` target.
+    #[cfg(feature = "tracing")]
+    pub fn tracing() -> Self {
+        Self::new(|entry: &str| tracing::info!(target: "zed::middleware", "{entry}"))
+    }
+
+    /// Sets how much detail is logged per action.
+    pub fn verbosity(mut self, verbosity: LogVerbosity) -> Self {
+        self.verbosity = verbosity;
+        self
+    }
+
+    /// Formats the entry of an action; `states` is `None` if the action
+    /// never reached the reducer or was rejected.
+    fn format_entry<State: Debug>(
+        &self,
+        action: &str,
+        states: Option<(&State, &State)>,
+        elapsed: Duration,
+    ) -> String {
+        match self.verbosity {
+            LogVerbosity::Minimal => format!("action {}", variant_name(action)),
+            LogVerbosity::Normal => format!("action {} ({elapsed:?})", variant_name(action)),
+            LogVerbosity::Verbose => {
+                let mut entry = format!("action {action} ({elapsed:?})");
+                let Some((prev, next)) = states else {
+                    entry.push_str("\n  (not applied)");
+                    return entry;
+                };
+                let diff = state_diff(&format!("{prev:#?}"), &format!("{next:#?}"));
+                if diff.is_empty() {
+                    entry.push_str("\n  (state unchanged)");
+                }
+                for line in diff {
+                    entry.push_str("\n  ");
+                    entry.push_str(&line);
+                }
+                entry
+            }
+        }
+    }
+}
+
+impl<State, Action> Middleware<State, Action> for LoggerMiddleware
+where
+    State: Clone + Debug + Send + 'static,
+    Action: Debug + Send + 'static,
+{
+    fn handle(
+        &self,
+        store: &Store<State, Action>,
+        action: Action,
+        next: Next<'_, Action>,
+    ) -> Result<(), ReducerError> {
+        let label = format!("{action:?}");
+        let start = Instant::now();
+        let (result, transition) = store.capture_transition(|| next(action));
+
+        let entry = match &transition {
+            Some(transition) => self.format_entry(
+                &label,
+                Some((&*transition.before, &*transition.after)),
+                transition.started.elapsed(),
+            ),
+            None => self.format_entry::<State>(&label, None, start.elapsed()),
+        };
+        (self.sink)(&entry);
+        result
+    }
+}
+
+/// The variant name of an action's `Debug` output, e.g. `Add` for `Add(1)`.
+fn variant_name(action: &str) -> &str {
+    let end = action.find(['(', ' ', '{']).unwrap_or(action.len());
+    &action[..end]
+}
+
+/// Line diff of two renderings, trimming the common prefix and suffix.
+///
+/// Returns removed lines prefixed with `- ` followed by added lines prefixed
+/// with `+ `, or nothing if both renderings are equal.
+fn state_diff(prev: &str, next: &str) -> Vec<String> {
+    let prev: Vec<&str> = prev.lines().collect();
+    let next: Vec<&str> = next.lines().collect();
+
+    let prefix = prev.iter().zip(&next).take_while(|(a, b)| a == b).count();
+    let suffix = prev[prefix..]
+        .iter()
+        .rev()
+        .zip(next[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let removed = prev[prefix..prev.len() - suffix]
+        .iter()
+        .map(|line| format!("- {}", line.trim()));
+    let added = next[prefix..next.len() - suffix]
+        .iter()
+        .map(|line| format!("+ {}", line.trim()));
+    removed.chain(added).collect()
+}
@@ -0,0 +1,191 @@
+//! # Optimistic Module
+//!
+//! Optimistic updates apply an action immediately, before a server or other
+//! authority has confirmed it, and undo it later if it is rejected.
+//!
+//! [`Store::dispatch_optimistic`] returns an [`OptimisticHandle`]. Calling
+//! [`commit`](OptimisticHandle::commit) keeps the change, while
+//! [`revert`](OptimisticHandle::revert) removes it: the state is rebuilt from
+//! the state before the optimistic action by replaying every action that was
+//! dispatched after it, so unrelated updates are preserved.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use zed::{Store, create_reducer};
+//!
+//! #[derive(Clone)]
+//! struct State { likes: u32, views: u32 }
+//!
+//! #[derive(Clone)]
+//! enum Action { Like, View }
+//!
+//! let store = Store::new(
+//!     State { likes: 0, views: 0 },
+//!     Box::new(create_reducer(|state: &State, action: &Action| match action {
+//!         Action::Like => State { likes: state.likes + 1, ..state.clone() },
+//!         Action::View => State { views: state.views + 1, ..state.clone() },
+//!     })),
+//! );
+//!
+//! let like = store.dispatch_optimistic(Action::Like);
+//! store.dispatch(Action::View);
+//! assert_eq!(store.get_state().likes, 1);
+//!
+//! // The server rejected the like; the view is kept
+//! like.revert();
+//! assert_eq!(store.get_state().likes, 0);
+//! assert_eq!(store.get_state().views, 1);
+//! ```
+
+use crate::store::Store;
+use std::sync::Arc;
+
+/// Identifies a pending optimistic action.
+pub(crate) type OptimisticId = u64;
+
+/// An applied action, tagged with its ID while it is a pending optimistic one.
+pub(crate) type LogEntry<Action> = (Option<OptimisticId>, Action);
+
+/// Actions applied since the oldest pending optimistic action.
+///
+/// The log is only active while at least one optimistic action is pending;
+/// otherwise it holds nothing and dispatching does not record actions.
+pub(crate) struct OptimisticLog<State, Action> {
+    /// State before the oldest pending optimistic action
+    base: Option<Arc<State>>,
+    entries: Vec<LogEntry<Action>>,
+    clone_action: Option<fn(&Action) -> Action>,
+    next_id: OptimisticId,
+}
+
+impl<State, Action> OptimisticLog<State, Action> {
+    pub(crate) fn new() -> Self {
+        Self {
+            base: None,
+            entries: Vec::new(),
+            clone_action: None,
+            next_id: 0,
+        }
+    }
+
+    /// Records an action applied by a regular dispatch.
+    pub(crate) fn record(&mut self, action: &Action) {
+        if let Some(clone_action) = self.clone_action {
+            self.entries.push((None, clone_action(action)));
+        }
+    }
+
+    /// Records an optimistic action applied on top of `before`.
+    pub(crate) fn record_pending(&mut self, before: Arc<State>, action: Action) -> OptimisticId
+    where
+        Action: Clone,
+    {
+        if self.base.is_none() {
+            self.base = Some(before);
+            self.clone_action = Some(Action::clone);
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        self.entries.push((Some(id), action));
+        id
+    }
+
+    /// Marks a pending action as confirmed.
+    pub(crate) fn confirm(&mut self, id: OptimisticId) {
+        for entry in &mut self.entries {
+            if entry.0 == Some(id) {
+                entry.0 = None;
+            }
+        }
+        self.compact();
+    }
+
+    /// Drops a pending action, returning the base state and the remaining
+    /// actions to replay on top of it, or `None` if the action is unknown.
+    pub(crate) fn discard(
+        &mut self,
+        id: OptimisticId,
+    ) -> Option<(Arc<State>, &[LogEntry<Action>])> {
+        let position = self.entries.iter().position(|entry| entry.0 == Some(id))?;
+        self.entries.remove(position);
+        let base = self.base.clone()?;
+        Some((base, &self.entries))
+    }
+
+    /// Forgets all recorded actions, including pending ones.
+    pub(crate) fn reset(&mut self) {
+        self.base = None;
+        self.entries.clear();
+        self.clone_action = None;
+    }
+
+    /// Forgets all recorded actions once nothing is pending anymore.
+    pub(crate) fn compact(&mut self) {
+        if self.entries.iter().all(|entry| entry.0.is_none()) {
+            self.reset();
+        }
+    }
+}
+
+/// A pending optimistic action, returned by [`Store::dispatch_optimistic`].
+///
+/// The action stays applied until the handle is settled with `commit()` or
+/// `revert()`. Dropping an unsettled handle reverts the action, like an
+/// uncommitted transaction.
+#[must_use = "dropping the handle reverts the optimistic action"]
+pub struct OptimisticHandle<State, Action>
+where
+    State: Clone + Send + 'static,
+    Action: Send + 'static,
+{
+    store: Store<State, Action>,
+    id: Option<OptimisticId>,
+}
+
+impl<State, Action> OptimisticHandle<State, Action>
+where
+    State: Clone + Send + 'static,
+    Action: Send + 'static,
+{
+    pub(crate) fn new(store: Store<State, Action>, id: Option<OptimisticId>) -> Self {
+        Self { store, id }
+    }
+
+    /// Returns `true` while the action is applied but not yet settled.
+    ///
+    /// A handle for an action rejected by the reducer is never pending.
+    pub fn is_pending(&self) -> bool {
+        self.id.is_some()
+    }
+
+    /// Confirms the optimistic action, keeping its effect on the state.
+    pub fn commit(mut self) {
+        if let Some(id) = self.id.take() {
+            self.store.settle_optimistic(id, true);
+        }
+    }
+
+    /// Undoes the optimistic action.
+    ///
+    /// The state is rebuilt by replaying the actions dispatched after it on
+    /// top of the state it was applied to. Subscribers are notified if the
+    /// result differs; listeners do not run again for replayed actions.
+    pub fn revert(mut self) {
+        if let Some(id) = self.id.take() {
+            self.store.settle_optimistic(id, false);
+        }
+    }
+}
+
+impl<State, Action> Drop for OptimisticHandle<State, Action>
+where
+    State: Clone + Send + 'static,
+    Action: Send + 'static,
+{
+    fn drop(&mut self) {
+        if let Some(id) = self.id.take() {
+            self.store.settle_optimistic(id, false);
+        }
+    }
+}
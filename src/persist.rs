@@ -0,0 +1,380 @@
+//! # Persist Module
+//!
+//! Keeps a store's state on disk so it survives restarts.
+//!
+//! A [`PersistedStore`] saves the state to a
+//! [`StorageBackend`](crate::storage::StorageBackend) after every change,
+//! and [`configure_store_persisted`] creates a store whose initial state is
+//! hydrated from a JSON file when it exists. Use
+//! [`configure_store_with_storage`] to hydrate from any other backend.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use serde::{Deserialize, Serialize};
+//! use zed::{configure_store_persisted, create_reducer};
+//!
+//! #[derive(Clone, Serialize, Deserialize)]
+//! struct Settings { volume: u8 }
+//!
+//! enum Action { SetVolume(u8) }
+//!
+//! let path = std::env::temp_dir().join("zed-persist-doc-settings.json");
+//! # let _ = std::fs::remove_file(&path);
+//! let reducer = || create_reducer(|_: &Settings, action: &Action| match action {
+//!     Action::SetVolume(volume) => Settings { volume: *volume },
+//! });
+//!
+//! let store = configure_store_persisted(&path, Settings { volume: 5 }, reducer()).unwrap();
+//! store.dispatch(Action::SetVolume(8));
+//! drop(store);
+//!
+//! // The next run starts from the saved state
+//! let store = configure_store_persisted(&path, Settings { volume: 5 }, reducer()).unwrap();
+//! assert_eq!(store.get_state().volume, 8);
+//! # std::fs::remove_file(&path).unwrap();
+//! ```
+
+use crate::reducer::Reducer;
+use crate::storage::{JsonFileStorage, StorageBackend};
+use crate::store::{Store, SubscriptionId};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Error raised while saving or loading persisted state.
+#[derive(Debug)]
+pub enum PersistError {
+    /// Reading or writing the file failed
+    Io(io::Error),
+    /// The state could not be serialized, or the saved content is invalid
+    Serde(serde_json::Error),
+    /// A storage backend reported an error of its own
+    Backend(Box<dyn std::error::Error + Send + Sync>),
+    /// No chain of registered migrations leads from the saved schema
+    /// version to the current one
+    Migration {
+        /// The version the saved data could be migrated to
+        from: u32,
+        /// The current schema version
+        to: u32,
+    },
+    /// The saved content is truncated, tampered with or not in the expected format
+    Corrupted(String),
+}
+
+impl PersistError {
+    /// Wraps an error reported by a storage backend.
+    pub fn backend<E>(err: E) -> Self
+    where
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        PersistError::Backend(err.into())
+    }
+}
+
+impl fmt::Display for PersistError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PersistError::Io(err) => write!(f, "persistence I/O error: {err}"),
+            PersistError::Serde(err) => write!(f, "persistence format error: {err}"),
+            PersistError::Backend(err) => write!(f, "persistence backend error: {err}"),
+            PersistError::Migration { from, to } => {
+                write!(f, "no migration from schema version {from} to {to}")
+            }
+            PersistError::Corrupted(reason) => write!(f, "persisted data is corrupted: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for PersistError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PersistError::Io(err) => Some(err),
+            PersistError::Serde(err) => Some(err),
+            PersistError::Backend(err) => Some(err.as_ref()),
+            PersistError::Migration { .. } | PersistError::Corrupted(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for PersistError {
+    fn from(err: io::Error) -> Self {
+        PersistError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for PersistError {
+    fn from(err: serde_json::Error) -> Self {
+        PersistError::Serde(err)
+    }
+}
+
+/// 64-bit FNV-1a, chosen because its output is stable across platforms
+/// and Rust versions.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Writes a file holding a header line (`magic`, format version and a
+/// checksum of `body`) followed by `body`.
+///
+/// The file is written through a temporary file, so a crash mid-write keeps
+/// the old file.
+pub(crate) fn write_checked(
+    path: &Path,
+    magic: &str,
+    version: u32,
+    body: &str,
+) -> Result<(), PersistError> {
+    let contents = format!("{magic} {version} {:016x}\n{body}", fnv1a(body.as_bytes()));
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Reads a file written by `write_checked()` and returns its body, after
+/// checking its header.
+pub(crate) fn read_checked(path: &Path, magic: &str, version: u32) -> Result<String, PersistError> {
+    let mut contents = fs::read_to_string(path)?;
+    let corrupted = |reason: &str| PersistError::Corrupted(reason.to_string());
+
+    let header_len = contents
+        .find('\n')
+        .ok_or_else(|| corrupted("missing header"))?;
+    let header = &contents[..header_len];
+    let mut fields = header.split(' ');
+    if fields.next() != Some(magic) {
+        return Err(corrupted(&format!("not a {magic} file")));
+    }
+    let saved_version = fields
+        .next()
+        .and_then(|version| version.parse::<u32>().ok())
+        .ok_or_else(|| corrupted("invalid format version"))?;
+    if saved_version != version {
+        return Err(PersistError::Migration {
+            from: saved_version,
+            to: version,
+        });
+    }
+    let checksum = fields
+        .next()
+        .and_then(|checksum| u64::from_str_radix(checksum, 16).ok())
+        .ok_or_else(|| corrupted("invalid checksum"))?;
+    if checksum != fnv1a(&contents.as_bytes()[header_len + 1..]) {
+        return Err(corrupted("checksum mismatch"));
+    }
+
+    contents.drain(..=header_len);
+    Ok(contents)
+}
+
+type SharedStorage<State> = Arc<dyn StorageBackend<State>>;
+
+/// A store whose state is saved to a storage backend after every change.
+///
+/// Dereferences to the wrapped [`Store`], so it can be used like one.
+/// Saving stops when the `PersistedStore` is dropped; clones of the inner
+/// store obtained through `store()` are not persisted after that.
+pub struct PersistedStore<State, Action>
+where
+    State: Clone + Send + 'static,
+    Action: Send + 'static,
+{
+    store: Store<State, Action>,
+    storage: SharedStorage<State>,
+    subscription: SubscriptionId,
+    last_error: Arc<Mutex<Option<PersistError>>>,
+}
+
+impl<State, Action> PersistedStore<State, Action>
+where
+    State: Clone + Send + 'static,
+    Action: Send + 'static,
+{
+    /// Starts persisting a store to the given JSON file.
+    ///
+    /// The state is written whenever subscribers are notified. The current
+    /// state is not written until the first change; call `save()` to write
+    /// it right away.
+    ///
+    /// # Arguments
+    ///
+    /// * `store` - The store to persist
+    /// * `path` - The JSON file the state is written to
+    pub fn new(store: Store<State, Action>, path: impl Into<PathBuf>) -> Self
+    where
+        State: Serialize + DeserializeOwned,
+    {
+        Self::with_storage(store, JsonFileStorage::new(path))
+    }
+
+    /// Starts persisting a store to a storage backend.
+    ///
+    /// # Arguments
+    ///
+    /// * `store` - The store to persist
+    /// * `storage` - The backend the state is saved to
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use zed::{MemoryStorage, PersistedStore, StorageBackend, Store, create_reducer};
+    ///
+    /// #[derive(Clone)]
+    /// struct State { count: i32 }
+    ///
+    /// enum Action { Increment }
+    ///
+    /// let storage = MemoryStorage::new();
+    /// let store = PersistedStore::with_storage(
+    ///     Store::new(State { count: 0 }, Box::new(create_reducer(|state: &State, _: &Action| State { count: state.count + 1 }))),
+    ///     storage.clone(),
+    /// );
+    ///
+    /// store.dispatch(Action::Increment);
+    /// assert_eq!(storage.load().unwrap().map(|state| state.count), Some(1));
+    /// ```
+    pub fn with_storage<S>(store: Store<State, Action>, storage: S) -> Self
+    where
+        S: StorageBackend<State> + 'static,
+    {
+        let storage: SharedStorage<State> = Arc::new(storage);
+        let last_error = Arc::new(Mutex::new(None));
+
+        let save_storage = Arc::clone(&storage);
+        let save_error = Arc::clone(&last_error);
+        let subscription = store.subscribe(move |state: &State| {
+            if let Err(err) = save_storage.save(state) {
+                *save_error.lock().unwrap_or_else(PoisonError::into_inner) = Some(err);
+            }
+        });
+
+        Self {
+            store,
+            storage,
+            subscription,
+            last_error,
+        }
+    }
+
+    /// Writes the current state to the storage immediately.
+    pub fn save(&self) -> Result<(), PersistError> {
+        self.store.with_state(|state| self.storage.save(state))
+    }
+
+    /// Removes the saved state from the storage.
+    ///
+    /// The store keeps its current state, and the next change is saved
+    /// again as usual.
+    pub fn clear(&self) -> Result<(), PersistError> {
+        self.storage.clear()
+    }
+
+    /// Takes the error of the most recent failed automatic save, if any.
+    ///
+    /// Automatic saves run inside a subscriber and cannot return errors, so
+    /// the last failure is kept here until it is taken.
+    pub fn take_error(&self) -> Option<PersistError> {
+        self.last_error
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+    }
+
+    /// Returns the storage backend the state is saved to.
+    pub fn storage(&self) -> &dyn StorageBackend<State> {
+        &*self.storage
+    }
+
+    /// Returns the wrapped store.
+    pub fn store(&self) -> &Store<State, Action> {
+        &self.store
+    }
+}
+
+impl<State, Action> Deref for PersistedStore<State, Action>
+where
+    State: Clone + Send + 'static,
+    Action: Send + 'static,
+{
+    type Target = Store<State, Action>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.store
+    }
+}
+
+impl<State, Action> Drop for PersistedStore<State, Action>
+where
+    State: Clone + Send + 'static,
+    Action: Send + 'static,
+{
+    fn drop(&mut self) {
+        self.store.unsubscribe(self.subscription);
+    }
+}
+
+/// Creates a persisted store, hydrating its state from `path` if present.
+///
+/// When the file exists, its content replaces `initial_state`; otherwise the
+/// store starts from `initial_state`. Every change is then saved to `path`.
+///
+/// # Arguments
+///
+/// * `path` - The JSON file to hydrate from and save to
+/// * `initial_state` - The state used when nothing has been saved yet
+/// * `reducer` - A reducer that implements the Reducer trait
+///
+/// # Returns
+///
+/// The persisted store, or an error if the file exists but cannot be read
+/// or does not contain a valid state.
+pub fn configure_store_persisted<State, Action, R>(
+    path: impl Into<PathBuf>,
+    initial_state: State,
+    reducer: R,
+) -> Result<PersistedStore<State, Action>, PersistError>
+where
+    State: Clone + Send + Serialize + DeserializeOwned + 'static,
+    Action: Send + 'static,
+    R: Reducer<State, Action> + Send + Sync + 'static,
+{
+    configure_store_with_storage(JsonFileStorage::new(path), initial_state, reducer)
+}
+
+/// Creates a persisted store, hydrating its state from a storage backend.
+///
+/// # Arguments
+///
+/// * `storage` - The backend to hydrate from and save to
+/// * `initial_state` - The state used when nothing has been saved yet
+/// * `reducer` - A reducer that implements the Reducer trait
+///
+/// # Returns
+///
+/// The persisted store, or the error the backend reported while loading.
+pub fn configure_store_with_storage<State, Action, S, R>(
+    storage: S,
+    initial_state: State,
+    reducer: R,
+) -> Result<PersistedStore<State, Action>, PersistError>
+where
+    State: Clone + Send + 'static,
+    Action: Send + 'static,
+    S: StorageBackend<State> + 'static,
+    R: Reducer<State, Action> + Send + Sync + 'static,
+{
+    let state = storage.load()?.unwrap_or(initial_state);
+    let store = Store::new(state, Box::new(reducer));
+    Ok(PersistedStore::with_storage(store, storage))
+}
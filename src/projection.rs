@@ -0,0 +1,181 @@
+//! # Projection Module
+//!
+//! Read-only stores derived from a parent store.
+//!
+//! [`Store::map`](crate::Store::map) projects the parent state into another
+//! shape, e.g. a view model, and returns a [`ProjectedStore`] holding the
+//! projected value. The projection is recomputed after every parent update,
+//! and the projected store notifies its own subscribers only when the result
+//! changes, so views depend on the projection instead of the root state.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use zed::{Store, create_reducer};
+//!
+//! #[derive(Clone)]
+//! struct AppState { todos: Vec<(String, bool)> }
+//!
+//! enum Action { Add(String), Toggle(usize) }
+//!
+//! #[derive(Clone, PartialEq, Debug)]
+//! struct Summary { open: usize, done: usize }
+//!
+//! let store = Store::new(
+//!     AppState { todos: vec![] },
+//!     Box::new(create_reducer(|state: &AppState, action: &Action| {
+//!         let mut todos = state.todos.clone();
+//!         match action {
+//!             Action::Add(title) => todos.push((title.clone(), false)),
+//!             Action::Toggle(index) => todos[*index].1 = !todos[*index].1,
+//!         }
+//!         AppState { todos }
+//!     })),
+//! );
+//!
+//! let summary = store.map(|state: &AppState| {
+//!     let done = state.todos.iter().filter(|(_, done)| *done).count();
+//!     Summary { open: state.todos.len() - done, done }
+//! });
+//!
+//! store.dispatch(Action::Add("write docs".to_string()));
+//! store.dispatch(Action::Toggle(0));
+//! assert_eq!(summary.get_state(), Summary { open: 0, done: 1 });
+//! ```
+
+use crate::store::{SubscriptionGuard, SubscriptionId};
+use arc_swap::ArcSwap;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+
+type ProjectionSubscriber<T> = Box<dyn Fn(&T) + Send + Sync>;
+
+/// The projected value and the subscribers of a projected store.
+pub(crate) struct Projection<T> {
+    current: ArcSwap<T>,
+    subscribers: Mutex<BTreeMap<SubscriptionId, ProjectionSubscriber<T>>>,
+    next_subscriber_id: AtomicUsize,
+}
+
+impl<T: PartialEq> Projection<T> {
+    pub(crate) fn new(initial: T) -> Self {
+        Self {
+            current: ArcSwap::from_pointee(initial),
+            subscribers: Mutex::new(BTreeMap::new()),
+            next_subscriber_id: AtomicUsize::new(0),
+        }
+    }
+
+    /// Stores a newly computed projection, notifying subscribers if it changed.
+    pub(crate) fn update(&self, next: T) {
+        if **self.current.load() == next {
+            return;
+        }
+        let next = Arc::new(next);
+        self.current.store(Arc::clone(&next));
+
+        let subscribers = self
+            .subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        for subscriber in subscribers.values() {
+            subscriber(&next);
+        }
+    }
+}
+
+/// A read-only store holding a projection of a parent store's state.
+///
+/// Created with [`Store::map`](crate::Store::map). Cloning is cheap; all
+/// clones share the projected value and its subscribers. The projection
+/// stays attached to the parent store until the last clone is dropped.
+pub struct ProjectedStore<T> {
+    projection: Arc<Projection<T>>,
+    _parent_subscription: Arc<SubscriptionGuard>,
+}
+
+impl<T> Clone for ProjectedStore<T> {
+    fn clone(&self) -> Self {
+        Self {
+            projection: Arc::clone(&self.projection),
+            _parent_subscription: Arc::clone(&self._parent_subscription),
+        }
+    }
+}
+
+impl<T> ProjectedStore<T> {
+    pub(crate) fn new(
+        projection: Arc<Projection<T>>,
+        parent_subscription: SubscriptionGuard,
+    ) -> Self {
+        Self {
+            projection,
+            _parent_subscription: Arc::new(parent_subscription),
+        }
+    }
+
+    /// Gets a clone of the current projection.
+    pub fn get_state(&self) -> T
+    where
+        T: Clone,
+    {
+        T::clone(&self.projection.current.load())
+    }
+
+    /// Returns the current projection without cloning it.
+    pub fn get_state_shared(&self) -> Arc<T> {
+        self.projection.current.load_full()
+    }
+
+    /// Accesses the current projection without cloning.
+    pub fn with_state<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&T) -> R,
+    {
+        f(&self.projection.current.load())
+    }
+
+    /// Subscribes to changes of the projection.
+    ///
+    /// The callback fires after a parent update only if the recomputed
+    /// projection differs from the previous one.
+    ///
+    /// # Returns
+    ///
+    /// A `SubscriptionId` that can be used with `unsubscribe()`.
+    pub fn subscribe<F>(&self, f: F) -> SubscriptionId
+    where
+        F: Fn(&T) + Send + Sync + 'static,
+    {
+        let id = self
+            .projection
+            .next_subscriber_id
+            .fetch_add(1, Ordering::SeqCst);
+        self.projection
+            .subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(id, Box::new(f));
+        id
+    }
+
+    /// Removes a subscription created through this projected store.
+    pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        self.projection
+            .subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&id)
+            .is_some()
+    }
+
+    /// Returns the number of subscribers of the projection.
+    pub fn subscriber_count(&self) -> usize {
+        self.projection
+            .subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+}
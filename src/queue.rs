@@ -0,0 +1,108 @@
+//! # Queue Module
+//!
+//! Prioritized dispatch. Actions dispatched with
+//! [`Store::dispatch_with_priority`](crate::Store::dispatch_with_priority)
+//! go through a queue that is drained by one thread at a time: while an
+//! action is being reduced, newly queued actions wait, and the next one
+//! taken is always the oldest action of the highest [`Priority`].
+//!
+//! This keeps latency-sensitive actions such as user input from waiting
+//! behind a burst of background work such as analytics events.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use zed::{Priority, Store, create_reducer};
+//!
+//! #[derive(Clone)]
+//! struct State { log: Vec<&'static str> }
+//!
+//! enum Action { Record(&'static str) }
+//!
+//! let store = Store::new(
+//!     State { log: vec![] },
+//!     Box::new(create_reducer(|state: &State, action: &Action| match action {
+//!         Action::Record(entry) => {
+//!             let mut log = state.log.clone();
+//!             log.push(entry);
+//!             State { log }
+//!         }
+//!     })),
+//! );
+//!
+//! store.dispatch_with_priority(Action::Record("click"), Priority::High);
+//! store.dispatch_with_priority(Action::Record("page view"), Priority::Low);
+//! assert_eq!(store.get_state().log, vec!["click", "page view"]);
+//! ```
+
+use std::collections::VecDeque;
+
+/// The priority of an action dispatched through the queue.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    /// Background work that can wait, e.g. analytics
+    Low,
+    /// Regular actions
+    #[default]
+    Normal,
+    /// Latency-sensitive actions, e.g. user input
+    High,
+}
+
+impl Priority {
+    /// Index of the priority's lane, highest priority first.
+    fn lane(self) -> usize {
+        match self {
+            Priority::High => 0,
+            Priority::Normal => 1,
+            Priority::Low => 2,
+        }
+    }
+}
+
+/// Actions waiting to be reduced, one FIFO lane per priority.
+pub(crate) struct ActionQueue<Action> {
+    lanes: [VecDeque<Action>; 3],
+    /// Whether a thread is currently draining the queue
+    draining: bool,
+}
+
+impl<Action> ActionQueue<Action> {
+    pub(crate) fn new() -> Self {
+        Self {
+            lanes: [VecDeque::new(), VecDeque::new(), VecDeque::new()],
+            draining: false,
+        }
+    }
+
+    /// Queues an action.
+    ///
+    /// Returns `true` if the caller should drain the queue, i.e. no other
+    /// thread is already doing so.
+    pub(crate) fn push(&mut self, action: Action, priority: Priority) -> bool {
+        self.lanes[priority.lane()].push_back(action);
+        !std::mem::replace(&mut self.draining, true)
+    }
+
+    /// Takes the next action to reduce, or ends the drain if none is left.
+    pub(crate) fn pop(&mut self) -> Option<Action> {
+        let next = self.lanes.iter_mut().find_map(VecDeque::pop_front);
+        if next.is_none() {
+            self.draining = false;
+        }
+        next
+    }
+
+    /// Ends the drain without taking the remaining actions.
+    ///
+    /// Used when the draining thread unwinds; the actions left are reduced
+    /// by the next call to `dispatch_with_priority`.
+    pub(crate) fn abandon(&mut self) {
+        self.draining = false;
+    }
+
+    /// Returns how many actions are waiting.
+    pub(crate) fn len(&self) -> usize {
+        self.lanes.iter().map(VecDeque::len).sum()
+    }
+}
@@ -1,43 +1,806 @@
+//! # Reactive Module
+//!
+//! An event-driven system running reactions on a state when events are
+//! triggered.
+//!
+//! Reactions are registered for one event with `on()`, or for several:
+//! `on_pattern()` matches string events against a glob such as `"user:*"`,
+//! and `on_any()` runs for every event, e.g. for logging.
+//!
+//! Reactions run by priority, then in registration order. A reaction may
+//! return [`Flow::Stop`] to veto the event, so the reactions after it do
+//! not run, e.g. a high-priority guard in front of expensive handlers.
+//!
+//! `derive()` creates a [`Computed`] value from the state, cached between
+//! the triggers that may change it.
+//!
+//! Reactions registered with `on_with_context()` emit further events with
+//! `ctx.emit()`. They are triggered once the current reactions have run;
+//! an event emitting itself, or events nesting too deep, end the trigger
+//! with an [`EmitError`] instead of recursing forever.
+//!
+//! For noisy events such as scroll or sensor data, `on_throttled()`
+//! reactions run at most once per period, and `on_debounced()` reactions
+//! run from `run_timers()` once the triggers have stopped for a delay.
+//!
+//! Events are strings by default. Any `Eq + Hash` type can be used instead,
+//! e.g. an enum, so the compiler catches misspelled events:
+//!
+//! ```rust
+//! use zed::ReactiveSystem;
+//!
+//! #[derive(PartialEq, Eq, Hash)]
+//! enum Event {
+//!     Increment,
+//!     Reset,
+//! }
+//!
+//! let mut system = ReactiveSystem::with_events(0);
+//! system.on(Event::Increment, |count: &mut i32| *count += 1);
+//! system.on(Event::Reset, |count: &mut i32| *count = 0);
+//!
+//! system.trigger(Event::Increment);
+//! assert_eq!(*system.current_state(), 1);
+//! ```
+
+use std::cell::RefCell;
+use std::cmp::Reverse;
 use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 
 pub type ActionType = String;
 
-pub type Reaction<T> = Box<dyn Fn(&mut T)>;
+/// A reaction, as registered with `ReactiveSystem::on_with_context()`
+pub type Reaction<T, E = ActionType> = Box<dyn Fn(&mut T, &mut ReactionContext<E>) -> Flow>;
+
+/// Tells whether the reactions after this one run for the same trigger.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Flow {
+    /// The next reactions run
+    #[default]
+    Continue,
+    /// No other reaction runs for this trigger
+    Stop,
+}
+
+impl From<()> for Flow {
+    fn from(_: ()) -> Self {
+        Flow::Continue
+    }
+}
+
+/// Identifies a reaction registered with `ReactiveSystem::on()`
+pub type ReactionId = usize;
+
+pub type ReactionMap<T, E = ActionType> = HashMap<E, Vec<(ReactionId, Reaction<T, E>)>>;
+
+/// Handles the errors of `ReactiveSystem::trigger()`
+type ErrorHandler<E> = Box<dyn Fn(&EmitError<E>)>;
+
+/// Tells which events a pattern or catch-all reaction runs for
+type EventFilter<E> = Box<dyn Fn(&E) -> bool>;
+
+/// When a reaction removes itself
+enum Expiry<T> {
+    /// After its first run
+    Once,
+    /// As soon as the predicate fails, checked before every run
+    While(Box<dyn Fn(&T) -> bool>),
+}
+
+/// How deep emitted events may nest unless `set_max_depth()` is called
+pub const DEFAULT_MAX_DEPTH: usize = 32;
+
+/// Passed to reactions registered with `ReactiveSystem::on_with_context()`
+/// to emit further events.
+pub struct ReactionContext<E = ActionType> {
+    emitted: Vec<E>,
+}
+
+impl<E> ReactionContext<E> {
+    fn new() -> Self {
+        Self {
+            emitted: Vec::new(),
+        }
+    }
+
+    /// Emits an event, triggered once the reactions to the current batch
+    /// of events have run.
+    pub fn emit(&mut self, event: E) {
+        self.emitted.push(event);
+    }
+}
+
+/// Error returned by `ReactiveSystem::try_trigger()` when emitted events
+/// do not settle.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EmitError<E = ActionType> {
+    /// An event emitted itself; the events from its first trigger to the
+    /// emission that closed the cycle
+    Cycle(Vec<E>),
+    /// Emitted events nested deeper than the maximum depth
+    TooDeep { max_depth: usize },
+}
+
+impl<E> fmt::Display for EmitError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EmitError::Cycle(events) => {
+                write!(
+                    f,
+                    "emitted events form a cycle of {}",
+                    events.len().saturating_sub(1)
+                )
+            }
+            EmitError::TooDeep { max_depth } => {
+                write!(f, "emitted events nest deeper than {max_depth}")
+            }
+        }
+    }
+}
+
+impl<E: fmt::Debug> Error for EmitError<E> {}
+
+/// Limits how often a reaction runs
+enum Timer {
+    /// Runs at most once per period, dropping the triggers in between
+    Throttle {
+        period: Duration,
+        last_run: Option<Instant>,
+    },
+    /// Runs once the triggers stop for the delay
+    Debounce {
+        delay: Duration,
+        due: Option<Instant>,
+    },
+}
+
+impl Timer {
+    /// Records a trigger at `now`, and returns `true` if the reaction runs
+    /// right away.
+    fn trigger(&mut self, now: Instant) -> bool {
+        match self {
+            Timer::Throttle { period, last_run } => {
+                if last_run.is_some_and(|last_run| now < last_run + *period) {
+                    return false;
+                }
+                *last_run = Some(now);
+                true
+            }
+            Timer::Debounce { delay, due } => {
+                *due = Some(now + *delay);
+                false
+            }
+        }
+    }
+}
 
-pub type ReactionMap<T> = HashMap<ActionType, Vec<Reaction<T>>>;
+/// Tells systems apart, so a `Computed` is only read from its own system
+static NEXT_SYSTEM_ID: AtomicUsize = AtomicUsize::new(0);
 
-pub struct ReactiveSystem<T> {
+pub struct ReactiveSystem<T, E = ActionType> {
     state: T,
-    reactions: ReactionMap<T>,
+    system_id: usize,
+    /// Counts the reaction runs, as each may change the state
+    version: u64,
+    reactions: ReactionMap<T, E>,
+    next_id: ReactionId,
+    /// Reactions that remove themselves, by ID
+    expiries: HashMap<ReactionId, Expiry<T>>,
+    /// Priorities other than 0, by reaction ID
+    priorities: HashMap<ReactionId, i32>,
+    /// Throttled and debounced reactions, by ID
+    timers: HashMap<ReactionId, Timer>,
+    clock: Box<dyn Fn() -> Instant>,
+    /// Reactions registered by `on_pattern()` and `on_any()`
+    filtered: Vec<(ReactionId, EventFilter<E>, Reaction<T, E>)>,
+    max_depth: usize,
+    /// Handlers registered by `on_error()`
+    error_handlers: Vec<ErrorHandler<E>>,
 }
 
 impl<T> ReactiveSystem<T> {
+    /// Creates a system with string events.
     pub fn new(initial_state: T) -> Self {
+        Self::with_events(initial_state)
+    }
+}
+
+impl<T, E: Eq + Hash> ReactiveSystem<T, E> {
+    /// Creates a system with events of type `E`.
+    pub fn with_events(initial_state: T) -> Self {
         Self {
             state: initial_state,
+            system_id: NEXT_SYSTEM_ID.fetch_add(1, Ordering::Relaxed),
+            version: 0,
             reactions: HashMap::new(),
+            next_id: 0,
+            expiries: HashMap::new(),
+            priorities: HashMap::new(),
+            timers: HashMap::new(),
+            clock: Box::new(Instant::now),
+            filtered: Vec::new(),
+            max_depth: DEFAULT_MAX_DEPTH,
+            error_handlers: Vec::new(),
         }
     }
 
-    pub fn on<F>(&mut self, action_type: ActionType, callback: F)
+    /// Registers a reaction to an event. The reaction returns `()`, or a
+    /// [`Flow`] to stop the reactions after it.
+    ///
+    /// # Returns
+    ///
+    /// An ID to remove the reaction with `off()`.
+    pub fn on<F, R>(&mut self, event: E, callback: F) -> ReactionId
+    where
+        F: 'static + Fn(&mut T) -> R,
+        R: Into<Flow>,
+    {
+        self.on_with_context(event, move |state: &mut T, _: &mut ReactionContext<E>| {
+            callback(state)
+        })
+    }
+
+    /// Registers a reaction to an event that may emit further events
+    /// through its [`ReactionContext`]. They are triggered after the
+    /// reactions to the current event, see `try_trigger()`.
+    pub fn on_with_context<F, R>(&mut self, event: E, callback: F) -> ReactionId
+    where
+        F: 'static + Fn(&mut T, &mut ReactionContext<E>) -> R,
+        R: Into<Flow>,
+    {
+        let id = self.next_reaction_id();
+        self.reactions.entry(event).or_default().push((
+            id,
+            Box::new(move |state: &mut T, context: &mut ReactionContext<E>| {
+                callback(state, context).into()
+            }),
+        ));
+        id
+    }
+
+    /// Registers a reaction to an event with a priority. Reactions with a
+    /// higher priority run first; `on()` registers them with priority 0.
+    pub fn on_with_priority<F, R>(&mut self, event: E, priority: i32, callback: F) -> ReactionId
+    where
+        F: 'static + Fn(&mut T) -> R,
+        R: Into<Flow>,
+    {
+        let id = self.on(event, callback);
+        self.set_priority(id, priority);
+        id
+    }
+
+    /// Changes the priority of a registered reaction.
+    ///
+    /// # Returns
+    ///
+    /// `false` if the reaction was removed.
+    pub fn set_priority(&mut self, id: ReactionId, priority: i32) -> bool {
+        let registered = self
+            .reactions
+            .values()
+            .flatten()
+            .map(|(other, _)| other)
+            .chain(self.filtered.iter().map(|(other, _, _)| other))
+            .any(|other| *other == id);
+        if registered {
+            match priority {
+                0 => self.priorities.remove(&id),
+                _ => self.priorities.insert(id, priority),
+            };
+        }
+        registered
+    }
+
+    /// Registers a reaction to every event, e.g. for logging.
+    pub fn on_any<F, R>(&mut self, callback: F) -> ReactionId
+    where
+        F: 'static + Fn(&mut T) -> R,
+        R: Into<Flow>,
+    {
+        self.on_filtered(Box::new(|_| true), callback)
+    }
+
+    /// Registers a reaction that runs once, at the next trigger of the
+    /// event, then removes itself.
+    pub fn on_once<F, R>(&mut self, event: E, callback: F) -> ReactionId
+    where
+        F: 'static + Fn(&mut T) -> R,
+        R: Into<Flow>,
+    {
+        let id = self.on(event, callback);
+        self.expiries.insert(id, Expiry::Once);
+        id
+    }
+
+    /// Registers a reaction that runs while `predicate` holds for the
+    /// state. The predicate is checked before every run; the first time it
+    /// fails, the reaction removes itself without running.
+    pub fn on_while<P, F, R>(&mut self, event: E, predicate: P, callback: F) -> ReactionId
+    where
+        P: 'static + Fn(&T) -> bool,
+        F: 'static + Fn(&mut T) -> R,
+        R: Into<Flow>,
+    {
+        let id = self.on(event, callback);
+        self.expiries.insert(id, Expiry::While(Box::new(predicate)));
+        id
+    }
+
+    /// Registers a reaction that runs at most once per `period`: it runs on
+    /// a trigger, then ignores the triggers until `period` has elapsed.
+    pub fn on_throttled<F, R>(&mut self, event: E, period: Duration, callback: F) -> ReactionId
+    where
+        F: 'static + Fn(&mut T) -> R,
+        R: Into<Flow>,
+    {
+        self.on_throttled_with_context(
+            event,
+            period,
+            move |state: &mut T, _: &mut ReactionContext<E>| callback(state),
+        )
+    }
+
+    /// Registers a throttled reaction, like `on_throttled()`, that may emit
+    /// further events through its [`ReactionContext`].
+    pub fn on_throttled_with_context<F, R>(
+        &mut self,
+        event: E,
+        period: Duration,
+        callback: F,
+    ) -> ReactionId
+    where
+        F: 'static + Fn(&mut T, &mut ReactionContext<E>) -> R,
+        R: Into<Flow>,
+    {
+        let id = self.on_with_context(event, callback);
+        let timer = Timer::Throttle {
+            period,
+            last_run: None,
+        };
+        self.timers.insert(id, timer);
+        id
+    }
+
+    /// Registers a reaction that runs once the event has not been
+    /// triggered for `delay`. Every trigger postpones it; it then runs
+    /// from `run_timers()`, once however many triggers there were.
+    pub fn on_debounced<F, R>(&mut self, event: E, delay: Duration, callback: F) -> ReactionId
     where
-        F: 'static + Fn(&mut T),
+        F: 'static + Fn(&mut T) -> R,
+        R: Into<Flow>,
     {
-        self.reactions
-            .entry(action_type)
-            .or_default()
-            .push(Box::new(callback));
+        self.on_debounced_with_context(
+            event,
+            delay,
+            move |state: &mut T, _: &mut ReactionContext<E>| callback(state),
+        )
     }
 
-    pub fn trigger(&mut self, action_type: ActionType) {
-        if let Some(callbacks) = self.reactions.get(&action_type) {
-            for callback in callbacks {
-                callback(&mut self.state);
+    /// Registers a debounced reaction, like `on_debounced()`, that may emit
+    /// further events through its [`ReactionContext`]. They are triggered
+    /// from `run_timers()`, after the reactions due.
+    pub fn on_debounced_with_context<F, R>(
+        &mut self,
+        event: E,
+        delay: Duration,
+        callback: F,
+    ) -> ReactionId
+    where
+        F: 'static + Fn(&mut T, &mut ReactionContext<E>) -> R,
+        R: Into<Flow>,
+    {
+        let id = self.on_with_context(event, callback);
+        self.timers.insert(id, Timer::Debounce { delay, due: None });
+        id
+    }
+
+    /// Runs the debounced reactions that are due, by priority then in
+    /// registration order, until one returns `Flow::Stop`. Call it from a
+    /// timer, e.g. at `next_timer()`.
+    ///
+    /// The events emitted by the reactions are triggered after them, at
+    /// depth 1, like those emitted while reacting to a trigger. If they form
+    /// a cycle or nest too deep, the events left are dropped and the error
+    /// is reported to the `on_error()` handlers.
+    ///
+    /// # Returns
+    ///
+    /// The number of reactions run.
+    pub fn run_timers(&mut self) -> usize {
+        let now = (self.clock)();
+        let mut due: Vec<ReactionId> = self
+            .timers
+            .iter_mut()
+            .filter_map(|(id, timer)| match timer {
+                Timer::Debounce { due, .. } if due.is_some_and(|due| due <= now) => {
+                    *due = None;
+                    Some(*id)
+                }
+                _ => None,
+            })
+            .collect();
+        if due.is_empty() {
+            return 0;
+        }
+        due.sort_by_key(|id| (Reverse(self.priorities.get(id).copied().unwrap_or(0)), *id));
+        let mut context = ReactionContext::new();
+        let mut ran = 0;
+        for id in &due {
+            let reaction = self
+                .reactions
+                .values()
+                .flatten()
+                .find(|(other, _)| other == id);
+            let Some((_, callback)) = reaction else {
+                continue;
+            };
+            self.version += 1;
+            ran += 1;
+            if callback(&mut self.state, &mut context) == Flow::Stop {
+                break;
+            }
+        }
+        if !context.emitted.is_empty() {
+            let result = match self.max_depth {
+                0 => Err(EmitError::TooDeep { max_depth: 0 }),
+                _ => {
+                    let events = context.emitted.into_iter().map(|event| (event, None));
+                    self.settle(events.collect(), 1)
+                }
+            };
+            self.report(&result);
+        }
+        ran
+    }
+
+    /// Returns when the next debounced reaction is due, if any is pending.
+    pub fn next_timer(&self) -> Option<Instant> {
+        self.timers
+            .values()
+            .filter_map(|timer| match timer {
+                Timer::Debounce { due, .. } => *due,
+                Timer::Throttle { .. } => None,
+            })
+            .min()
+    }
+
+    /// Sets the timer source of the throttled and debounced reactions,
+    /// e.g. a simulated clock. Systems start with `Instant::now()`.
+    pub fn set_clock<C>(&mut self, clock: C)
+    where
+        C: 'static + Fn() -> Instant,
+    {
+        self.clock = Box::new(clock);
+    }
+
+    /// Derives a value from the state, such as a total or a filtered list.
+    ///
+    /// The value is computed on the first `Computed::get()`, then cached
+    /// until a reaction runs, which may change the state.
+    pub fn derive<R, F>(&self, compute: F) -> Computed<T, R>
+    where
+        F: 'static + Fn(&T) -> R,
+    {
+        Computed {
+            compute: Box::new(compute),
+            system_id: self.system_id,
+            cache: RefCell::new(None),
+        }
+    }
+
+    /// Removes a reaction.
+    ///
+    /// # Returns
+    ///
+    /// `false` if the reaction was already removed.
+    pub fn off(&mut self, id: ReactionId) -> bool {
+        let mut removed = false;
+        self.reactions.retain(|_, reactions| {
+            let len = reactions.len();
+            reactions.retain(|(other, _)| *other != id);
+            removed |= reactions.len() < len;
+            !reactions.is_empty()
+        });
+        let len = self.filtered.len();
+        self.filtered.retain(|(other, _, _)| *other != id);
+        removed |= self.filtered.len() < len;
+        self.expiries.remove(&id);
+        self.priorities.remove(&id);
+        self.timers.remove(&id);
+        removed
+    }
+
+    /// Removes every reaction registered for an event with `on()`,
+    /// `on_once()` or `on_while()`. Pattern and catch-all reactions are
+    /// kept.
+    ///
+    /// # Returns
+    ///
+    /// The number of reactions removed.
+    pub fn clear(&mut self, event: &E) -> usize {
+        let Some(reactions) = self.reactions.remove(event) else {
+            return 0;
+        };
+        for (id, _) in &reactions {
+            self.expiries.remove(id);
+            self.priorities.remove(id);
+            self.timers.remove(id);
+        }
+        reactions.len()
+    }
+
+    /// Runs the reactions to an event: those registered for it, and the
+    /// pattern and catch-all reactions matching it. They run by priority,
+    /// highest first, then in registration order, until one returns
+    /// `Flow::Stop`.
+    ///
+    /// The events emitted by the reactions are triggered after them, see
+    /// `try_trigger()`. If they form a cycle or nest too deep, the events
+    /// left are dropped and the error is reported to the `on_error()`
+    /// handlers.
+    pub fn trigger(&mut self, event: E) {
+        let _ = self.try_trigger(event);
+    }
+
+    /// Runs the reactions to an event like `trigger()`, then the events
+    /// they emitted.
+    ///
+    /// Events are processed in batches: the events emitted while reacting
+    /// to one batch form the next batch, at the next depth, in the order
+    /// they were emitted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`EmitError`] if an event emits itself, directly or
+    /// through the events it emitted, or if the events nest deeper than
+    /// `set_max_depth()`. The events left are dropped; the reactions
+    /// already run keep their changes to the state. The error is also
+    /// reported to the `on_error()` handlers.
+    pub fn try_trigger(&mut self, event: E) -> Result<(), EmitError<E>> {
+        let result = self.settle(vec![(event, None)], 0);
+        self.report(&result);
+        result
+    }
+
+    /// Reports an error of emitted events to the `on_error()` handlers.
+    fn report(&self, result: &Result<(), EmitError<E>>) {
+        if let Err(error) = result {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(%error, "reactive trigger aborted");
+            for handler in &self.error_handlers {
+                handler(error);
+            }
+        }
+    }
+
+    /// Registers a handler for the errors of `trigger()` and
+    /// `try_trigger()`, e.g. to log emission cycles.
+    pub fn on_error<F>(&mut self, handler: F)
+    where
+        F: 'static + Fn(&EmitError<E>),
+    {
+        self.error_handlers.push(Box::new(handler));
+    }
+
+    /// Sets how deep emitted events may nest: the events emitted by the
+    /// reactions to a trigger are at depth 1, those they emit at depth 2,
+    /// and so on. Systems start with `DEFAULT_MAX_DEPTH`.
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+
+    /// Runs the reactions to a batch of events at `depth`, then the events
+    /// they emitted, batch by batch.
+    ///
+    /// Each event is paired with the index of the event that emitted it.
+    fn settle(
+        &mut self,
+        mut events: Vec<(E, Option<usize>)>,
+        mut depth: usize,
+    ) -> Result<(), EmitError<E>> {
+        let mut batch = 0..events.len();
+        while !batch.is_empty() {
+            for index in batch.clone() {
+                for emitted in self.run_reactions(&events[index].0) {
+                    let mut ancestor = Some(index);
+                    while let Some(i) = ancestor {
+                        if events[i].0 == emitted {
+                            return Err(EmitError::Cycle(cycle(events, index, emitted)));
+                        }
+                        ancestor = events[i].1;
+                    }
+                    if depth == self.max_depth {
+                        return Err(EmitError::TooDeep {
+                            max_depth: self.max_depth,
+                        });
+                    }
+                    events.push((emitted, Some(index)));
+                }
             }
+            batch = batch.end..events.len();
+            depth += 1;
         }
+        Ok(())
+    }
+
+    /// Runs the reactions to one event, and returns the events they
+    /// emitted.
+    fn run_reactions(&mut self, event: &E) -> Vec<E> {
+        let mut callbacks: Vec<(&ReactionId, &Reaction<T, E>)> = self
+            .reactions
+            .get(event)
+            .into_iter()
+            .flatten()
+            .map(|(id, callback)| (id, callback))
+            .collect();
+        let exact = callbacks.len();
+        callbacks.extend(
+            self.filtered
+                .iter()
+                .filter(|(_, matches, _)| matches(event))
+                .map(|(id, _, callback)| (id, callback)),
+        );
+        if callbacks.len() > exact || !self.priorities.is_empty() {
+            let priority = |id: &ReactionId| self.priorities.get(id).copied().unwrap_or(0);
+            callbacks.sort_by_key(|(id, _)| (Reverse(priority(id)), **id));
+        }
+        let now = (!self.timers.is_empty()).then(|| (self.clock)());
+        let mut context = ReactionContext::new();
+        let mut expired = Vec::new();
+        for (id, callback) in callbacks {
+            if let (Some(timer), Some(now)) = (self.timers.get_mut(id), now)
+                && !timer.trigger(now)
+            {
+                continue;
+            }
+            match self.expiries.get(id) {
+                Some(Expiry::While(holds)) if !holds(&self.state) => {
+                    expired.push(*id);
+                    continue;
+                }
+                Some(Expiry::Once) => expired.push(*id),
+                _ => {}
+            }
+            self.version += 1;
+            if callback(&mut self.state, &mut context) == Flow::Stop {
+                break;
+            }
+        }
+        if expired.is_empty() {
+            return context.emitted;
+        }
+        for id in &expired {
+            self.expiries.remove(id);
+        }
+        if let Some(reactions) = self.reactions.get_mut(event) {
+            reactions.retain(|(id, _)| !expired.contains(id));
+            if reactions.is_empty() {
+                self.reactions.remove(event);
+            }
+        }
+        context.emitted
     }
 
     pub fn current_state(&self) -> &T {
         &self.state
     }
+
+    fn next_reaction_id(&mut self) -> ReactionId {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    fn on_filtered<F, R>(&mut self, matches: EventFilter<E>, callback: F) -> ReactionId
+    where
+        F: 'static + Fn(&mut T) -> R,
+        R: Into<Flow>,
+    {
+        let id = self.next_reaction_id();
+        self.filtered.push((
+            id,
+            matches,
+            Box::new(move |state: &mut T, _: &mut ReactionContext<E>| callback(state).into()),
+        ));
+        id
+    }
+}
+
+/// A value derived from the state of a `ReactiveSystem`, created by
+/// `ReactiveSystem::derive()`.
+pub struct Computed<T, R> {
+    compute: Box<dyn Fn(&T) -> R>,
+    system_id: usize,
+    /// The last value, and the version of the state it was computed from
+    cache: RefCell<Option<(u64, R)>>,
+}
+
+impl<T, R: Clone> Computed<T, R> {
+    /// Returns the value, computed again if the state may have changed
+    /// since it was last read.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `system` is not the system that created this value.
+    pub fn get<E>(&self, system: &ReactiveSystem<T, E>) -> R {
+        assert_eq!(
+            self.system_id, system.system_id,
+            "computed value read from another system"
+        );
+        let mut cache = self.cache.borrow_mut();
+        match &*cache {
+            Some((version, value)) if *version == system.version => value.clone(),
+            _ => {
+                let value = (self.compute)(&system.state);
+                *cache = Some((system.version, value.clone()));
+                value
+            }
+        }
+    }
+}
+
+impl<T, E: Eq + Hash + AsRef<str>> ReactiveSystem<T, E> {
+    /// Registers a reaction to every event matching a glob pattern, where
+    /// `*` stands for any sequence of characters, e.g. `"user:*"` for all
+    /// events starting with `user:`.
+    pub fn on_pattern<F, R>(&mut self, pattern: &str, callback: F) -> ReactionId
+    where
+        F: 'static + Fn(&mut T) -> R,
+        R: Into<Flow>,
+    {
+        let pattern = pattern.to_string();
+        self.on_filtered(
+            Box::new(move |event: &E| glob_match(&pattern, event.as_ref())),
+            callback,
+        )
+    }
+}
+
+/// Returns the events of a cycle: from the ancestor of `events[last]` equal
+/// to `emitted`, down to `events[last]`, then `emitted`.
+fn cycle<E: PartialEq>(events: Vec<(E, Option<usize>)>, last: usize, emitted: E) -> Vec<E> {
+    let mut chain = vec![last];
+    while events[chain[chain.len() - 1]].0 != emitted {
+        chain.extend(events[chain[chain.len() - 1]].1);
+    }
+    let mut events: Vec<Option<E>> = events.into_iter().map(|(event, _)| Some(event)).collect();
+    let mut cycle: Vec<E> = chain
+        .iter()
+        .rev()
+        .filter_map(|&i| events[i].take())
+        .collect();
+    cycle.push(emitted);
+    cycle
+}
+
+/// Returns `true` if `text` matches `pattern`, where `*` matches any
+/// sequence of characters.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let (pattern, text) = (pattern.as_bytes(), text.as_bytes());
+    let (mut p, mut t) = (0, 0);
+    // Position after the last `*`, and the text position it matched up to
+    let mut star = None;
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == b'*' {
+            star = Some((p + 1, t));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == text[t] {
+            p += 1;
+            t += 1;
+        } else if let Some((after, matched)) = star {
+            // Let the last `*` absorb one more character
+            p = after;
+            t = matched + 1;
+            star = Some((after, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
 }
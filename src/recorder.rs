@@ -0,0 +1,187 @@
+//! # Recorder Module
+//!
+//! Records the actions dispatched to a store so a session can be replayed
+//! later, e.g. to reproduce a bug or as a regression test.
+//!
+//! An [`ActionRecorder`] captures the state at the time it was attached and
+//! every applied action with a timestamp. Its [`ActionLog`] is serializable,
+//! and [`Store::replay`](crate::Store::replay) re-applies it and checks that
+//! the same final state is reached.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use zed::{ActionRecorder, Store, create_reducer};
+//!
+//! #[derive(Clone, Debug, PartialEq)]
+//! struct State { count: i32 }
+//!
+//! #[derive(Clone)]
+//! enum Action { Add(i32) }
+//!
+//! let reducer = || Box::new(create_reducer(|state: &State, action: &Action| match action {
+//!     Action::Add(n) => State { count: state.count + n },
+//! }));
+//!
+//! let store = Store::new(State { count: 0 }, reducer());
+//! let recorder = ActionRecorder::attach(&store);
+//! store.dispatch(Action::Add(2));
+//! store.dispatch(Action::Add(3));
+//! let log = recorder.stop();
+//!
+//! // Reproduce the session on a fresh store
+//! let replica = Store::new(State { count: 100 }, reducer());
+//! replica.replay(&log).unwrap();
+//! assert_eq!(replica.get_state().count, 5);
+//! ```
+
+use crate::store::{Store, SubscriptionId};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant, SystemTime};
+
+/// An action captured by an [`ActionRecorder`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordedAction<Action> {
+    /// Time since recording started
+    pub elapsed: Duration,
+    /// The applied action
+    pub action: Action,
+}
+
+/// A recorded session: the starting state, the actions and the end state.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ActionLog<State, Action> {
+    /// Wall-clock time at which recording started
+    pub started_at: SystemTime,
+    /// The state when recording started
+    pub initial_state: State,
+    /// The applied actions, in dispatch order
+    pub actions: Vec<RecordedAction<Action>>,
+    /// The state when the log was taken
+    pub final_state: State,
+}
+
+/// Error returned by [`Store::replay`] when the replay is not deterministic.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReplayMismatch<State> {
+    /// The final state stored in the log
+    pub expected: State,
+    /// The state the replay produced
+    pub actual: State,
+}
+
+impl<State: fmt::Debug> fmt::Display for ReplayMismatch<State> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "replay diverged: expected {:?}, got {:?}",
+            self.expected, self.actual
+        )
+    }
+}
+
+impl<State: fmt::Debug> std::error::Error for ReplayMismatch<State> {}
+
+/// Records the actions applied to a store.
+///
+/// Actions are captured by an action listener, so rejected actions are not
+/// recorded. Under concurrent dispatch the recorded order is the order in
+/// which listeners ran, which can differ from the order the reducer applied
+/// the actions in.
+pub struct ActionRecorder<State, Action>
+where
+    State: Clone + Send + 'static,
+    Action: Send + 'static,
+{
+    store: Store<State, Action>,
+    listener: SubscriptionId,
+    started: Instant,
+    started_at: SystemTime,
+    initial_state: State,
+    actions: Arc<Mutex<Vec<RecordedAction<Action>>>>,
+}
+
+impl<State, Action> ActionRecorder<State, Action>
+where
+    State: Clone + Send + 'static,
+    Action: Clone + Send + 'static,
+{
+    /// Starts recording the actions applied to a store.
+    pub fn attach(store: &Store<State, Action>) -> Self {
+        let started = Instant::now();
+        let actions = Arc::new(Mutex::new(Vec::new()));
+
+        // Read the initial state and register under the reducer lock, so
+        // every later action is recorded, in the order it was applied
+        let recorded = Arc::clone(&actions);
+        let (listener, initial_state) =
+            store.observe_actions(State::clone, move |action: &Action, _: &State| {
+                recorded
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .push(RecordedAction {
+                        elapsed: started.elapsed(),
+                        action: action.clone(),
+                    });
+            });
+
+        Self {
+            store: store.clone(),
+            listener,
+            started,
+            started_at: SystemTime::now(),
+            initial_state,
+            actions,
+        }
+    }
+
+    /// Returns how many actions have been recorded so far.
+    pub fn len(&self) -> usize {
+        self.actions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Returns `true` if no action has been recorded yet.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns how long the recorder has been running.
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Takes a snapshot of the log without stopping the recording.
+    pub fn log(&self) -> ActionLog<State, Action> {
+        ActionLog {
+            started_at: self.started_at,
+            initial_state: self.initial_state.clone(),
+            actions: self
+                .actions
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone(),
+            final_state: self.store.get_state(),
+        }
+    }
+
+    /// Stops recording and returns the log.
+    pub fn stop(self) -> ActionLog<State, Action> {
+        self.store.unsubscribe(self.listener);
+        self.log()
+    }
+}
+
+impl<State, Action> Drop for ActionRecorder<State, Action>
+where
+    State: Clone + Send + 'static,
+    Action: Send + 'static,
+{
+    fn drop(&mut self) {
+        self.store.unsubscribe(self.listener);
+    }
+}
@@ -37,6 +37,15 @@
 
 use std::marker::PhantomData;
 
+/// Type-erased error produced by a fallible reducer.
+///
+/// Any error type implementing `std::error::Error`, as well as `String` and
+/// `&str`, can be converted into a `ReducerError` with `.into()`.
+pub type ReducerError = Box<dyn std::error::Error + Send + Sync>;
+
+/// A boxed, thread-safe reducer as stored by a `Store`.
+pub type BoxedReducer<State, Action> = Box<dyn Reducer<State, Action> + Send + Sync>;
+
 /// A trait for implementing reducers that transform state based on actions.
 ///
 /// Reducers are pure functions that take the current state and an action,
@@ -54,6 +63,46 @@ pub trait Reducer<State, Action> {
     ///
     /// A new state after applying the action
     fn reduce(&self, state: &State, action: &Action) -> State;
+
+    /// Applies an action, reporting failures instead of producing a state.
+    ///
+    /// The default implementation never fails and delegates to `reduce()`.
+    /// Fallible reducers (see [`FallibleReducer`]) override it so that the
+    /// store can route errors to its error subscribers.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The current state
+    /// * `action` - The action to apply
+    ///
+    /// # Returns
+    ///
+    /// The new state, or the error that prevented the transition
+    fn try_reduce(&self, state: &State, action: &Action) -> Result<State, ReducerError> {
+        Ok(self.reduce(state, action))
+    }
+}
+
+/// A trait for reducers whose state transitions can fail.
+///
+/// Use this for domain validation that should reject an action rather than
+/// encoding errors into the state or panicking. Wrap a `TryReducer` in a
+/// [`FallibleReducer`] to use it with a `Store`.
+pub trait TryReducer<State, Action> {
+    /// The error returned when an action is rejected.
+    type Error;
+
+    /// Applies an action to the current state, or rejects it with an error.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The current state
+    /// * `action` - The action to apply
+    ///
+    /// # Returns
+    ///
+    /// The new state, or an error if the action is invalid for this state
+    fn try_reduce(&self, state: &State, action: &Action) -> Result<State, Self::Error>;
 }
 
 /// A reducer implementation that wraps a closure function.
@@ -139,3 +188,99 @@ where
         _phantom: PhantomData,
     }
 }
+
+/// A fallible reducer implementation that wraps a closure function.
+///
+/// Created by [`create_try_reducer`].
+pub struct ClosureTryReducer<State, Action, F> {
+    /// The closure function that performs the reduction
+    pub f: F,
+    /// Phantom data to maintain type information
+    _phantom: PhantomData<(State, Action)>,
+}
+
+impl<State, Action, E, F> TryReducer<State, Action> for ClosureTryReducer<State, Action, F>
+where
+    F: Fn(&State, &Action) -> Result<State, E>,
+{
+    type Error = E;
+
+    fn try_reduce(&self, state: &State, action: &Action) -> Result<State, E> {
+        (self.f)(state, action)
+    }
+}
+
+/// Adapts a [`TryReducer`] into a [`Reducer`] usable by a `Store`.
+///
+/// When the wrapped reducer rejects an action, `reduce()` leaves the state
+/// unchanged and `try_reduce()` reports the error, which the store forwards
+/// to its error subscribers.
+pub struct FallibleReducer<R> {
+    inner: R,
+}
+
+impl<R> FallibleReducer<R> {
+    /// Wraps a fallible reducer.
+    pub fn new(inner: R) -> Self {
+        Self { inner }
+    }
+}
+
+impl<State, Action, R> Reducer<State, Action> for FallibleReducer<R>
+where
+    State: Clone,
+    R: TryReducer<State, Action>,
+    R::Error: Into<ReducerError>,
+{
+    fn reduce(&self, state: &State, action: &Action) -> State {
+        self.inner
+            .try_reduce(state, action)
+            .unwrap_or_else(|_| state.clone())
+    }
+
+    fn try_reduce(&self, state: &State, action: &Action) -> Result<State, ReducerError> {
+        self.inner.try_reduce(state, action).map_err(Into::into)
+    }
+}
+
+/// Creates a fallible reducer from a closure returning a `Result`.
+///
+/// The returned reducer can be passed directly to `Store::new` or
+/// `configure_store`. Rejected actions leave the state untouched and are
+/// reported through `Store::dispatch_try` and `Store::subscribe_errors`.
+///
+/// # Arguments
+///
+/// * `f` - A function that takes a state reference and action reference, returns a new state or an error
+///
+/// # Example
+///
+/// ```rust
+/// use zed::{configure_store, create_try_reducer};
+///
+/// #[derive(Clone)]
+/// struct Account { balance: u32 }
+///
+/// enum Action { Withdraw(u32) }
+///
+/// let reducer = create_try_reducer(|state: &Account, action: &Action| match action {
+///     Action::Withdraw(amount) if *amount > state.balance => Err("insufficient funds"),
+///     Action::Withdraw(amount) => Ok(Account { balance: state.balance - amount }),
+/// });
+///
+/// let store = configure_store(Account { balance: 10 }, reducer);
+/// assert!(store.dispatch_try(Action::Withdraw(5)).is_ok());
+/// assert!(store.dispatch_try(Action::Withdraw(50)).is_err());
+/// assert_eq!(store.get_state().balance, 5);
+/// ```
+pub fn create_try_reducer<State, Action, E, F>(
+    f: F,
+) -> FallibleReducer<ClosureTryReducer<State, Action, F>>
+where
+    F: Fn(&State, &Action) -> Result<State, E>,
+{
+    FallibleReducer::new(ClosureTryReducer {
+        f,
+        _phantom: PhantomData,
+    })
+}
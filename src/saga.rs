@@ -0,0 +1,260 @@
+//! # Saga Module
+//!
+//! Sagas are long-running workflows that orchestrate several steps of an
+//! application flow, such as a login with retries or a checkout, as plain
+//! async code.
+//!
+//! A saga is started with [`Store::run_saga`](crate::Store::run_saga) and
+//! runs on its own background thread. It receives a [`Saga`] context to:
+//!
+//! - [`take`](Saga::take) the next dispatched action matching a predicate
+//! - [`put`](Saga::put) an action into the store
+//! - [`select`](Saga::select) a value from the current state
+//! - [`delay`](Saga::delay) for a while
+//!
+//! Each saga has a [`CancellationToken`]: it is cancelled through its
+//! [`SagaHandle`], by [`Store::cancel_all_effects`](crate::Store::cancel_all_effects),
+//! or together with the scope token passed to
+//! [`Store::run_saga_with_token`](crate::Store::run_saga_with_token).
+//!
+//! ## Example
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use zed::{Store, create_reducer};
+//!
+//! #[derive(Clone)]
+//! struct State { user: Option<String>, attempts: u32 }
+//!
+//! #[derive(Clone)]
+//! enum Action { Login(String), Attempt, LoggedIn(String) }
+//!
+//! let store = Store::new(
+//!     State { user: None, attempts: 0 },
+//!     Box::new(create_reducer(|state: &State, action: &Action| match action {
+//!         Action::Login(_) => state.clone(),
+//!         Action::Attempt => State { attempts: state.attempts + 1, ..state.clone() },
+//!         Action::LoggedIn(user) => State { user: Some(user.clone()), ..state.clone() },
+//!     })),
+//! );
+//!
+//! let saga = store.run_saga(|saga| async move {
+//!     let Some(Action::Login(user)) = saga.take(|action| matches!(action, Action::Login(_))).await else {
+//!         return;
+//!     };
+//!     // Retry until the (simulated) backend accepts the login
+//!     while saga.select(|state| state.attempts) < 2 {
+//!         saga.put(Action::Attempt);
+//!         saga.delay(Duration::from_millis(1)).await;
+//!     }
+//!     saga.put(Action::LoggedIn(user));
+//! });
+//!
+//! store.dispatch(Action::Login("ana".to_string()));
+//! saga.join().unwrap();
+//! assert_eq!(store.get_state().user.as_deref(), Some("ana"));
+//! ```
+
+use crate::cancellation::CancellationToken;
+use crate::store::{Store, SubscriptionGuard};
+use std::collections::VecDeque;
+use std::future::poll_fn;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::task::{Poll, Waker};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Actions dispatched since the saga started, waiting to be taken.
+struct Inbox<Action> {
+    actions: VecDeque<Action>,
+    waker: Option<Waker>,
+}
+
+type SharedInbox<Action> = Arc<Mutex<Inbox<Action>>>;
+
+/// Locks a mutex, recovering the guard if a previous holder panicked.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// The context a saga uses to interact with its store.
+///
+/// Every action applied to the store after the saga started is buffered,
+/// so actions dispatched while the saga is busy, e.g. during a `delay()`,
+/// are not missed by the next `take()`.
+pub struct Saga<State, Action>
+where
+    State: Clone + Send + 'static,
+    Action: Send + 'static,
+{
+    store: Store<State, Action>,
+    inbox: SharedInbox<Action>,
+    token: CancellationToken,
+    _listener: SubscriptionGuard,
+}
+
+impl<State, Action> Saga<State, Action>
+where
+    State: Clone + Send + 'static,
+    Action: Clone + Send + 'static,
+{
+    pub(crate) fn new(store: Store<State, Action>, token: CancellationToken) -> Self {
+        let inbox: SharedInbox<Action> = Arc::new(Mutex::new(Inbox {
+            actions: VecDeque::new(),
+            waker: None,
+        }));
+
+        let listener_inbox = Arc::clone(&inbox);
+        let listener_token = token.clone();
+        let id = store.add_listener(
+            |_| true,
+            move |action: &Action, _: &Store<State, Action>| {
+                if listener_token.is_cancelled() {
+                    return;
+                }
+                let mut inbox = lock(&listener_inbox);
+                inbox.actions.push_back(action.clone());
+                if let Some(waker) = inbox.waker.take() {
+                    waker.wake();
+                }
+            },
+        );
+
+        Self {
+            _listener: store.guard(id),
+            store,
+            inbox,
+            token,
+        }
+    }
+
+    /// Waits for the next action matching a predicate.
+    ///
+    /// Buffered actions that do not match are discarded.
+    ///
+    /// # Arguments
+    ///
+    /// * `matcher` - Decides which action to wait for
+    ///
+    /// # Returns
+    ///
+    /// The matching action, or `None` once the saga has been cancelled.
+    pub async fn take<M>(&self, matcher: M) -> Option<Action>
+    where
+        M: Fn(&Action) -> bool,
+    {
+        poll_fn(|cx| {
+            if self.token.poll_cancelled(cx).is_ready() {
+                return Poll::Ready(None);
+            }
+            let mut inbox = lock(&self.inbox);
+            while let Some(action) = inbox.actions.pop_front() {
+                if matcher(&action) {
+                    return Poll::Ready(Some(action));
+                }
+            }
+            inbox.waker = Some(cx.waker().clone());
+            Poll::Pending
+        })
+        .await
+    }
+
+    /// Dispatches an action to the store, unless the saga was cancelled.
+    pub fn put(&self, action: Action) {
+        if !self.token.is_cancelled() {
+            self.store.dispatch(action);
+        }
+    }
+
+    /// Reads a value from the current state.
+    pub fn select<T, F>(&self, selector: F) -> T
+    where
+        F: FnOnce(&State) -> T,
+    {
+        self.store.with_state(selector)
+    }
+
+    /// Waits for the given duration, or until the saga is cancelled.
+    ///
+    /// Actions dispatched in the meantime are buffered for `take()`.
+    pub async fn delay(&self, duration: Duration) {
+        self.token.run_until_cancelled(sleep(duration)).await;
+    }
+
+    /// Returns `true` once the saga has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Returns the saga's cancellation token.
+    ///
+    /// Pass it to other async work the saga starts so it is aborted along
+    /// with the saga.
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    /// Returns the store the saga runs against.
+    pub fn store(&self) -> &Store<State, Action> {
+        &self.store
+    }
+}
+
+/// Completes after the given duration, using a helper thread as the timer.
+async fn sleep(duration: Duration) {
+    let deadline = Instant::now() + duration;
+    let waker: Arc<Mutex<Option<Waker>>> = Arc::new(Mutex::new(None));
+    let mut timer_started = false;
+
+    poll_fn(|cx| {
+        if Instant::now() >= deadline {
+            return Poll::Ready(());
+        }
+        *lock(&waker) = Some(cx.waker().clone());
+        if !timer_started {
+            timer_started = true;
+            let waker = Arc::clone(&waker);
+            thread::spawn(move || {
+                thread::sleep(deadline.saturating_duration_since(Instant::now()));
+                if let Some(waker) = lock(&waker).take() {
+                    waker.wake();
+                }
+            });
+        }
+        Poll::Pending
+    })
+    .await
+}
+
+/// A handle to a running saga.
+pub struct SagaHandle<T> {
+    thread: JoinHandle<T>,
+    token: CancellationToken,
+}
+
+impl<T> SagaHandle<T> {
+    pub(crate) fn new(thread: JoinHandle<T>, token: CancellationToken) -> Self {
+        Self { thread, token }
+    }
+
+    /// Cancels the saga.
+    ///
+    /// The saga's pending and future `take()` calls resolve to `None`,
+    /// `delay()` returns early and `put()` is ignored, so a saga looping over
+    /// `take()` ends at its next step.
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+
+    /// Returns `true` if the saga has finished running.
+    pub fn is_finished(&self) -> bool {
+        self.thread.is_finished()
+    }
+
+    /// Waits for the saga to finish and returns its output.
+    ///
+    /// Returns an error if the saga panicked.
+    pub fn join(self) -> thread::Result<T> {
+        self.thread.join()
+    }
+}
@@ -0,0 +1,163 @@
+//! # Scoped Store Module
+//!
+//! Scoped stores are child views onto a slice of a parent store's state.
+//!
+//! A [`Lens`] describes how to focus on the child state and how to lift
+//! child actions into parent actions. The resulting [`ScopedStore`] reads and
+//! subscribes to the child state only, and dispatches through the parent, so
+//! components can depend on their slice without knowing the full app state.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use zed::{Lens, Store, create_reducer};
+//!
+//! #[derive(Clone, PartialEq)]
+//! struct Cart { items: Vec<String> }
+//!
+//! #[derive(Clone)]
+//! struct AppState { cart: Cart, visits: u32 }
+//!
+//! enum CartAction { Add(String) }
+//!
+//! enum AppAction { Cart(CartAction), Visit }
+//!
+//! let store = Store::new(
+//!     AppState { cart: Cart { items: vec![] }, visits: 0 },
+//!     Box::new(create_reducer(|state: &AppState, action: &AppAction| {
+//!         let mut state = state.clone();
+//!         match action {
+//!             AppAction::Cart(CartAction::Add(item)) => state.cart.items.push(item.clone()),
+//!             AppAction::Visit => state.visits += 1,
+//!         }
+//!         state
+//!     })),
+//! );
+//!
+//! let cart = store.scoped(Lens::new(|state: &AppState| &state.cart, AppAction::Cart));
+//!
+//! cart.dispatch(CartAction::Add("book".to_string()));
+//! assert_eq!(cart.get_state().items, vec!["book".to_string()]);
+//! ```
+
+use crate::store::{Store, SubscriptionId};
+use std::sync::Arc;
+
+type Getter<State, Child> = Arc<dyn Fn(&State) -> &Child + Send + Sync>;
+type Embed<ChildAction, Action> = Arc<dyn Fn(ChildAction) -> Action + Send + Sync>;
+
+/// Focuses on a child slice of a parent state and lifts child actions.
+pub struct Lens<State, Child, ChildAction, Action> {
+    get: Getter<State, Child>,
+    embed: Embed<ChildAction, Action>,
+}
+
+impl<State, Child, ChildAction, Action> Lens<State, Child, ChildAction, Action> {
+    /// Creates a lens from a getter and an action embedding.
+    ///
+    /// # Arguments
+    ///
+    /// * `get` - Returns a reference to the child state inside the parent state
+    /// * `embed` - Wraps a child action into a parent action
+    pub fn new<G, E>(get: G, embed: E) -> Self
+    where
+        G: Fn(&State) -> &Child + Send + Sync + 'static,
+        E: Fn(ChildAction) -> Action + Send + Sync + 'static,
+    {
+        Self {
+            get: Arc::new(get),
+            embed: Arc::new(embed),
+        }
+    }
+}
+
+impl<State, Child, ChildAction, Action> Clone for Lens<State, Child, ChildAction, Action> {
+    fn clone(&self) -> Self {
+        Self {
+            get: Arc::clone(&self.get),
+            embed: Arc::clone(&self.embed),
+        }
+    }
+}
+
+/// A child store view operating on a slice of a parent store.
+///
+/// Created with [`Store::scoped`]. Cloning is cheap; all clones share the
+/// parent store.
+pub struct ScopedStore<State, Action, Child, ChildAction> {
+    parent: Store<State, Action>,
+    lens: Lens<State, Child, ChildAction, Action>,
+}
+
+impl<State, Action, Child, ChildAction> Clone for ScopedStore<State, Action, Child, ChildAction> {
+    fn clone(&self) -> Self {
+        Self {
+            parent: self.parent.clone(),
+            lens: self.lens.clone(),
+        }
+    }
+}
+
+impl<State, Action, Child, ChildAction> ScopedStore<State, Action, Child, ChildAction>
+where
+    State: Clone + Send + 'static,
+    Action: Send + 'static,
+    Child: 'static,
+    ChildAction: 'static,
+{
+    pub(crate) fn new(
+        parent: Store<State, Action>,
+        lens: Lens<State, Child, ChildAction, Action>,
+    ) -> Self {
+        Self { parent, lens }
+    }
+
+    /// Dispatches a child action, lifted into a parent action by the lens.
+    pub fn dispatch(&self, action: ChildAction) {
+        self.parent.dispatch((self.lens.embed)(action));
+    }
+
+    /// Gets a clone of the current child state.
+    pub fn get_state(&self) -> Child
+    where
+        Child: Clone,
+    {
+        self.with_state(Child::clone)
+    }
+
+    /// Accesses the child state without cloning.
+    pub fn with_state<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&Child) -> R,
+    {
+        self.parent.with_state(|state| f((self.lens.get)(state)))
+    }
+
+    /// Subscribes to changes of the child state.
+    ///
+    /// The callback only fires when the child state actually changes, so
+    /// updates to other parts of the parent state are ignored.
+    ///
+    /// # Returns
+    ///
+    /// A `SubscriptionId` that can be used with `unsubscribe()`.
+    pub fn subscribe<F>(&self, f: F) -> SubscriptionId
+    where
+        Child: Clone + PartialEq + Send,
+        F: Fn(&Child) + Send + Sync + 'static,
+    {
+        let get = Arc::clone(&self.lens.get);
+        self.parent
+            .subscribe_selector(move |state: &State| get(state).clone(), f)
+    }
+
+    /// Removes a subscription created through this scoped store.
+    pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        self.parent.unsubscribe(id)
+    }
+
+    /// Returns the parent store.
+    pub fn parent(&self) -> &Store<State, Action> {
+        &self.parent
+    }
+}
@@ -0,0 +1,157 @@
+//! # Selector Module
+//!
+//! Memoized selectors for deriving data from state, inspired by Reselect.
+//!
+//! A selector is built from an input selector, which extracts the values the
+//! derivation depends on, and a combiner, which computes the derived value.
+//! The derived value is cached and only recomputed when the extracted inputs
+//! change, so expensive derivations are not repeated on every read.
+//!
+//! Several inputs can be composed by returning a tuple from the input
+//! selector, and selectors can themselves be used as inputs to other selectors.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use zed::create_selector;
+//!
+//! #[derive(Clone)]
+//! struct TodoState {
+//!     items: Vec<(String, bool)>,
+//!     show_completed: bool,
+//! }
+//!
+//! let visible_todos = create_selector(
+//!     |state: &TodoState| (state.items.clone(), state.show_completed),
+//!     |(items, show_completed): &(Vec<(String, bool)>, bool)| {
+//!         items
+//!             .iter()
+//!             .filter(|(_, done)| *show_completed || !done)
+//!             .map(|(text, _)| text.clone())
+//!             .collect::<Vec<_>>()
+//!     },
+//! );
+//!
+//! let state = TodoState {
+//!     items: vec![("Learn Rust".to_string(), true), ("Ship it".to_string(), false)],
+//!     show_completed: false,
+//! };
+//!
+//! assert_eq!(visible_todos.select(&state), vec!["Ship it".to_string()]);
+//! assert_eq!(visible_todos.select(&state), vec!["Ship it".to_string()]);
+//! assert_eq!(visible_todos.recomputations(), 1);
+//! ```
+
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+type InputFn<State, Input> = Box<dyn Fn(&State) -> Input + Send + Sync>;
+type CombinerFn<Input, Output> = Box<dyn Fn(&Input) -> Output + Send + Sync>;
+
+/// A memoized selector that derives an `Output` from a `State`.
+///
+/// The selector remembers the last inputs it saw together with the derived
+/// value. When called again with inputs equal to the cached ones, the cached
+/// value is returned without running the combiner.
+pub struct Selector<State, Input, Output> {
+    input: InputFn<State, Input>,
+    combiner: CombinerFn<Input, Output>,
+    cache: Mutex<Option<(Input, Output)>>,
+    recomputations: AtomicUsize,
+}
+
+impl<State, Input, Output> Selector<State, Input, Output>
+where
+    Input: PartialEq,
+    Output: Clone,
+{
+    /// Selects the derived value from the given state.
+    ///
+    /// The combiner only runs when the inputs differ from the previous call.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The state to derive the value from
+    ///
+    /// # Returns
+    ///
+    /// A clone of the (possibly cached) derived value.
+    pub fn select(&self, state: &State) -> Output {
+        let input = (self.input)(state);
+        let mut cache = self.cache.lock().unwrap();
+
+        if let Some((cached_input, cached_output)) = cache.as_ref()
+            && *cached_input == input
+        {
+            return cached_output.clone();
+        }
+
+        let output = (self.combiner)(&input);
+        self.recomputations.fetch_add(1, Ordering::SeqCst);
+        *cache = Some((input, output.clone()));
+        output
+    }
+
+    /// Returns how many times the combiner has been run.
+    ///
+    /// Useful in tests to verify memoization.
+    pub fn recomputations(&self) -> usize {
+        self.recomputations.load(Ordering::SeqCst)
+    }
+
+    /// Clears the cached value, forcing the next `select()` to recompute.
+    pub fn reset(&self) {
+        *self.cache.lock().unwrap() = None;
+    }
+}
+
+/// Creates a memoized selector from an input selector and a combiner.
+///
+/// # Arguments
+///
+/// * `input` - Extracts the values the derivation depends on; return a tuple to compose several inputs
+/// * `combiner` - Computes the derived value from the extracted inputs
+///
+/// # Returns
+///
+/// A `Selector` that only recomputes when its inputs change.
+///
+/// # Example
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use zed::create_selector;
+///
+/// struct State { prices: Vec<u32>, discount: u32 }
+///
+/// // Selectors can be composed by using one as the input of another
+/// let total = Arc::new(create_selector(
+///     |state: &State| state.prices.clone(),
+///     |prices: &Vec<u32>| prices.iter().sum::<u32>(),
+/// ));
+///
+/// let total_input = Arc::clone(&total);
+/// let discounted = create_selector(
+///     move |state: &State| (total_input.select(state), state.discount),
+///     |(total, discount): &(u32, u32)| total.saturating_sub(*discount),
+/// );
+///
+/// let state = State { prices: vec![10, 20, 30], discount: 5 };
+/// assert_eq!(discounted.select(&state), 55);
+/// assert_eq!(total.recomputations(), 1);
+/// ```
+pub fn create_selector<State, Input, Output, I, C>(
+    input: I,
+    combiner: C,
+) -> Selector<State, Input, Output>
+where
+    I: Fn(&State) -> Input + Send + Sync + 'static,
+    C: Fn(&Input) -> Output + Send + Sync + 'static,
+{
+    Selector {
+        input: Box::new(input),
+        combiner: Box::new(combiner),
+        cache: Mutex::new(None),
+        recomputations: AtomicUsize::new(0),
+    }
+}
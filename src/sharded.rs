@@ -0,0 +1,350 @@
+//! # Sharded Store Module
+//!
+//! A store for large keyed state, such as tens of thousands of entities,
+//! where a single lock around the whole state would make unrelated updates
+//! contend.
+//!
+//! A [`ShardedStore`] partitions its entities by key hash across several
+//! shards, each behind its own lock. Every action names the key it applies
+//! to; it is routed to that key's shard and reduced against that entity
+//! alone, so actions for keys in different shards run in parallel.
+//!
+//! The entity reducer is an ordinary [`Reducer`] over `Option<Entity>`:
+//! `None` stands for a missing entity, and returning `None` removes it.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use zed::{ShardedStore, create_reducer};
+//!
+//! #[derive(Clone)]
+//! struct Player { score: u32 }
+//!
+//! enum Action { Join(u64), Score(u64, u32), Leave(u64) }
+//!
+//! let players = ShardedStore::new(
+//!     16,
+//!     |action: &Action| match action {
+//!         Action::Join(id) | Action::Score(id, _) | Action::Leave(id) => *id,
+//!     },
+//!     create_reducer(|player: &Option<Player>, action: &Action| match action {
+//!         Action::Join(_) => Some(Player { score: 0 }),
+//!         Action::Score(_, points) => player.as_ref().map(|p| Player { score: p.score + points }),
+//!         Action::Leave(_) => None,
+//!     }),
+//! );
+//!
+//! players.dispatch(Action::Join(7));
+//! players.dispatch(Action::Score(7, 10));
+//! assert_eq!(players.get(&7).unwrap().score, 10);
+//!
+//! players.dispatch(Action::Leave(7));
+//! assert!(players.get(&7).is_none());
+//! ```
+
+use crate::reducer::{Reducer, ReducerError};
+use crate::store::SubscriptionId;
+use std::cell::Cell;
+use std::collections::hash_map::RandomState;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{BuildHasher, Hash};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+
+type KeyOf<K, Action> = Arc<dyn Fn(&Action) -> K + Send + Sync>;
+type EntityReducer<V, Action> = Arc<dyn Reducer<Option<V>, Action> + Send + Sync>;
+type EntitySubscriber<K, V> = Arc<dyn Fn(&K, Option<&V>) + Send + Sync>;
+type SubscriberList<K, V> = Vec<(SubscriptionId, EntitySubscriber<K, V>)>;
+
+/// Locks a mutex, recovering the guard if a previous holder panicked.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// One shard: its entities and the changes still to be reported.
+struct Shard<K, V> {
+    entities: Mutex<HashMap<K, V>>,
+    outbox: Mutex<Outbox<K, V>>,
+    /// Signalled whenever a change has been delivered or delivery stops
+    delivered: Condvar,
+}
+
+impl<K, V> Shard<K, V> {
+    fn new() -> Self {
+        Self {
+            entities: Mutex::new(HashMap::new()),
+            outbox: Mutex::new(Outbox {
+                pending: VecDeque::new(),
+                next_sequence: 0,
+                delivered: 0,
+                delivering: false,
+            }),
+            delivered: Condvar::new(),
+        }
+    }
+}
+
+/// Changes of one shard, numbered in the order they were applied.
+struct Outbox<K, V> {
+    pending: VecDeque<(u64, K, Option<V>)>,
+    next_sequence: u64,
+    /// Sequence number after the last delivered change
+    delivered: u64,
+    /// Whether a thread is currently delivering this shard's changes
+    delivering: bool,
+}
+
+thread_local! {
+    /// Number of shards the current thread is delivering changes for
+    static DELIVERING: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Hands delivery of a shard back, even if a subscriber panicked.
+struct Delivering<'a, K, V>(&'a Shard<K, V>);
+
+impl<'a, K, V> Delivering<'a, K, V> {
+    fn start(shard: &'a Shard<K, V>, outbox: &mut Outbox<K, V>) -> Self {
+        outbox.delivering = true;
+        DELIVERING.set(DELIVERING.get() + 1);
+        Self(shard)
+    }
+}
+
+impl<K, V> Drop for Delivering<'_, K, V> {
+    fn drop(&mut self) {
+        DELIVERING.set(DELIVERING.get() - 1);
+        lock(&self.0.outbox).delivering = false;
+        self.0.delivered.notify_all();
+    }
+}
+
+/// A keyed store whose entities are spread over independently locked shards.
+///
+/// Cloning a sharded store is cheap and yields another handle to the same
+/// entities and subscribers.
+pub struct ShardedStore<K, V, Action> {
+    shards: Arc<[Shard<K, V>]>,
+    hasher: RandomState,
+    key_of: KeyOf<K, Action>,
+    reducer: EntityReducer<V, Action>,
+    subscribers: Arc<Mutex<SubscriberList<K, V>>>,
+    next_subscriber_id: Arc<AtomicUsize>,
+}
+
+impl<K, V, Action> Clone for ShardedStore<K, V, Action> {
+    fn clone(&self) -> Self {
+        Self {
+            shards: Arc::clone(&self.shards),
+            hasher: self.hasher.clone(),
+            key_of: Arc::clone(&self.key_of),
+            reducer: Arc::clone(&self.reducer),
+            subscribers: Arc::clone(&self.subscribers),
+            next_subscriber_id: Arc::clone(&self.next_subscriber_id),
+        }
+    }
+}
+
+impl<K, V, Action> ShardedStore<K, V, Action>
+where
+    K: Hash + Eq + Clone + Send + 'static,
+    V: Clone + Send + 'static,
+    Action: 'static,
+{
+    /// Creates an empty sharded store.
+    ///
+    /// # Arguments
+    ///
+    /// * `shard_count` - Number of shards; more shards mean less contention
+    ///   (at least one is always used)
+    /// * `key_of` - Returns the key of the entity an action applies to
+    /// * `reducer` - Reduces an action against that entity
+    pub fn new<F, R>(shard_count: usize, key_of: F, reducer: R) -> Self
+    where
+        F: Fn(&Action) -> K + Send + Sync + 'static,
+        R: Reducer<Option<V>, Action> + Send + Sync + 'static,
+    {
+        Self {
+            shards: (0..shard_count.max(1)).map(|_| Shard::new()).collect(),
+            hasher: RandomState::new(),
+            key_of: Arc::new(key_of),
+            reducer: Arc::new(reducer),
+            subscribers: Arc::new(Mutex::new(Vec::new())),
+            next_subscriber_id: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Applies an action to the entity it names.
+    ///
+    /// Only the entity's shard is locked. A rejected action leaves the
+    /// entity unchanged; use `dispatch_try()` to receive the error.
+    pub fn dispatch(&self, action: Action) {
+        let _ = self.dispatch_try(action);
+    }
+
+    /// Applies an action, returning the error if the reducer rejects it.
+    ///
+    /// Subscribers are notified after the shard lock has been released, so
+    /// they may dispatch further actions. Changes to one shard still reach
+    /// subscribers in the order they were applied, and `dispatch_try`
+    /// returns once its change has been delivered, unless it is called from
+    /// a subscriber and the shard is already being delivered; that change
+    /// then follows once the current one has reached every subscriber.
+    /// Subscribers are not notified when the action leaves a missing entity
+    /// missing.
+    pub fn dispatch_try(&self, action: Action) -> Result<(), ReducerError> {
+        let key = (self.key_of)(&action);
+        let shard = self.shard(&key);
+        let sequence = {
+            let mut entities = lock(&shard.entities);
+            let current = entities.get(&key).cloned();
+            let next = self.reducer.try_reduce(&current, &action)?;
+            match &next {
+                Some(entity) => entities.insert(key.clone(), entity.clone()),
+                // Nothing to remove or report for a missing entity
+                None if current.is_none() => return Ok(()),
+                None => entities.remove(&key),
+            };
+            // Numbered while the shard is locked, so in the order applied
+            let mut outbox = lock(&shard.outbox);
+            let sequence = outbox.next_sequence;
+            outbox.next_sequence += 1;
+            outbox.pending.push_back((sequence, key, next));
+            sequence
+        };
+
+        self.deliver(shard, sequence);
+        Ok(())
+    }
+
+    /// Delivers a shard's pending changes in sequence order until the one
+    /// numbered `sequence` has been delivered.
+    ///
+    /// Only one thread delivers a shard at a time; others wait for it. A
+    /// subscriber dispatching to a shard that is already being delivered
+    /// leaves its change to the delivering thread instead of waiting, which
+    /// could wait on itself or on a thread waiting for it.
+    fn deliver(&self, shard: &Shard<K, V>, sequence: u64) {
+        let nested = DELIVERING.get() > 0;
+        let mut outbox = lock(&shard.outbox);
+        loop {
+            if outbox.delivered > sequence || (outbox.delivering && nested) {
+                return;
+            }
+            if !outbox.delivering {
+                break;
+            }
+            outbox = shard
+                .delivered
+                .wait(outbox)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+
+        let _delivering = Delivering::start(shard, &mut outbox);
+        while let Some((sequence, key, entity)) = outbox.pending.pop_front() {
+            drop(outbox);
+            let subscribers: Vec<_> = lock(&self.subscribers)
+                .iter()
+                .map(|(_, subscriber)| Arc::clone(subscriber))
+                .collect();
+            for subscriber in subscribers {
+                subscriber(&key, entity.as_ref());
+            }
+            outbox = lock(&shard.outbox);
+            outbox.delivered = sequence + 1;
+            shard.delivered.notify_all();
+        }
+        // Released before `_delivering` takes the lock again
+        drop(outbox);
+    }
+
+    /// Returns a clone of the entity with the given key.
+    pub fn get(&self, key: &K) -> Option<V> {
+        lock(&self.shard(key).entities).get(key).cloned()
+    }
+
+    /// Reads an entity through a closure without cloning it.
+    ///
+    /// The entity's shard stays locked while the closure runs.
+    pub fn with_entity<R, F>(&self, key: &K, f: F) -> R
+    where
+        F: FnOnce(Option<&V>) -> R,
+    {
+        f(lock(&self.shard(key).entities).get(key))
+    }
+
+    /// Returns `true` if an entity with the given key exists.
+    pub fn contains_key(&self, key: &K) -> bool {
+        lock(&self.shard(key).entities).contains_key(key)
+    }
+
+    /// Returns the number of entities across all shards.
+    pub fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| lock(&shard.entities).len())
+            .sum()
+    }
+
+    /// Returns `true` if the store holds no entities.
+    pub fn is_empty(&self) -> bool {
+        self.shards
+            .iter()
+            .all(|shard| lock(&shard.entities).is_empty())
+    }
+
+    /// Returns the number of shards.
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Collects all entities into one map.
+    ///
+    /// Shards are copied one after another, so actions dispatched meanwhile
+    /// may be reflected for some shards and not for others.
+    pub fn snapshot(&self) -> HashMap<K, V> {
+        self.shards
+            .iter()
+            .flat_map(|shard| {
+                lock(&shard.entities)
+                    .iter()
+                    .map(|(key, entity)| (key.clone(), entity.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// Subscribes to entity changes.
+    ///
+    /// The subscriber receives the key and the entity after every applied
+    /// action, or `None` if the action removed it. Subscribers are called
+    /// in the order they subscribed.
+    ///
+    /// # Returns
+    ///
+    /// A `SubscriptionId` that can be passed to `unsubscribe()`.
+    pub fn subscribe<F>(&self, f: F) -> SubscriptionId
+    where
+        F: Fn(&K, Option<&V>) + Send + Sync + 'static,
+    {
+        let id = self.next_subscriber_id.fetch_add(1, Ordering::SeqCst);
+        lock(&self.subscribers).push((id, Arc::new(f)));
+        id
+    }
+
+    /// Removes a subscriber.
+    ///
+    /// # Returns
+    ///
+    /// `true` if a subscriber with that ID existed.
+    pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        let mut subscribers = lock(&self.subscribers);
+        let before = subscribers.len();
+        subscribers.retain(|(subscriber_id, _)| *subscriber_id != id);
+        subscribers.len() != before
+    }
+
+    fn shard(&self, key: &K) -> &Shard<K, V> {
+        let index = self.hasher.hash_one(key) as usize % self.shards.len();
+        &self.shards[index]
+    }
+}
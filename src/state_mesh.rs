@@ -9,7 +9,46 @@
 //! - **Distributed State**: State represented as nodes in a graph
 //! - **Conflict Resolution**: Pluggable conflict resolution strategies
 //! - **State Propagation**: Automatic propagation of updates to connected nodes
-//! - **Flexible Topology**: Arbitrary connection patterns between nodes
+//! - **Flexible Topology**: Arbitrary connection patterns between nodes, one-way
+//!   or bidirectional
+//! - **Network Sync**: Synchronization with nodes in other processes over a
+//!   [`transport`]
+//! - **Compression**: LZ4 or Zstandard compression of synchronization
+//!   messages, negotiated per link (with the `lz4` and `zstd` features)
+//! - **Encryption**: Authenticated, encrypted synchronization between
+//!   trusted node identities (with the `encryption` feature)
+//! - **Discovery**: Peers on the local network found with mDNS (with the
+//!   `discovery` feature)
+//! - **Gossip**: Eventually consistent synchronization of large meshes with
+//!   [`gossip`] rounds
+//! - **Anti-entropy**: Background gossip rounds on a [`scheduler`] repair
+//!   updates that propagation missed
+//! - **Stores**: A [`Store`](crate::Store) joins a mesh as a
+//!   [`mesh_store::MeshStore`]
+//! - **Merkle Sync**: Keyed states reconciled by exchanging only the
+//!   entries that differ, found with a [`merkle::MerkleTree`]
+//! - **Snapshots**: Whole meshes saved to disk and restored with
+//!   [`snapshot`] functions
+//! - **Async Propagation**: Updates queued per peer with backpressure
+//!   by a [`propagator::AsyncPropagator`] (`async` feature)
+//! - **Visualization**: Topologies exported to Graphviz with
+//!   [`dot::export_dot`]
+//! - **Metrics**: Per-node counters of updates, conflicts, bytes and
+//!   propagation time, with `metrics()`
+//! - **Authority**: Nodes weighted by authority, so a server wins ties
+//!   against its clients, with `set_authority_resolver()`
+//! - **Leases**: Time-limited locks on regions of state, holding back
+//!   other nodes' changes to them, with [`lease`] methods
+//! - **Validated Writes**: Writes applied only once enough in-process peers
+//!   approve them, with `commit()`
+//! - **Field Merging**: Conflict resolvers built from a strategy per field
+//!   with [`field_merge::FieldMerge`]
+//! - **CRDTs**: Nodes holding [`crdt`](crate::crdt) types merge without a
+//!   hand-written conflict resolver
+//!
+//! Connections hold [`SharedStateNode`]s, handles to nodes that are shared
+//! between their owner and the nodes connected to them, so updates reach
+//! the live nodes rather than copies.
 //!
 //! ## Use Cases
 //!
@@ -37,23 +76,51 @@
 //!
 //! let node2 = StateNode::new("user2".to_string(), Document {
 //!     content: "Hi".to_string(),
-//!     version: 2,
-//! });
+//!     version: 0,
+//! })
+//! .into_shared();
 //!
 //! // Set up last-write-wins conflict resolution
-//! node1.set_conflict_resolver(|current: &mut Document, remote: &Document| {
+//! node2.lock().unwrap().set_conflict_resolver(|current: &mut Document, remote: &Document| {
 //!     if remote.version > current.version {
 //!         *current = remote.clone();
 //!     }
 //! });
 //!
-//! node1.connect(node2);
+//! node1.connect(&node2);
 //! node1.propagate_update(); // Sync states
+//! assert_eq!(node2.lock().unwrap().state.content, "Hello");
 //! # }
 //! ```
 
-use std::collections::HashMap;
-use std::sync::Arc;
+pub mod compression;
+#[cfg(feature = "discovery")]
+pub mod discovery;
+pub mod dot;
+pub mod field_merge;
+pub mod gossip;
+pub mod lease;
+pub mod merkle;
+pub mod mesh_store;
+#[cfg(feature = "async")]
+pub mod propagator;
+pub mod scheduler;
+#[cfg(feature = "encryption")]
+pub mod secure;
+pub mod snapshot;
+pub mod transport;
+
+use crate::crdt::Mergeable;
+use crate::metrics::NodeMetrics;
+use crate::state_mesh::lease::{Lease, LeasePolicy, RegionDiff};
+use crate::store::SubscriptionId;
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Instant;
 
 /// Type alias for node identifiers
 pub type NodeId = String;
@@ -65,15 +132,99 @@ pub type NodeId = String;
 /// resolution strategies like last-write-wins, merge, or custom logic.
 pub type ConflictResolver<T> = Arc<dyn Fn(&mut T, &T) + Send + Sync>;
 
+/// A state node shared between its owner and the nodes connected to it
+pub type SharedStateNode<T> = Arc<Mutex<StateNode<T>>>;
+
 /// Type alias for the connections map
-pub type StateNodeConnections<T> = HashMap<NodeId, StateNode<T>>;
+pub type StateNodeConnections<T> = HashMap<NodeId, SharedStateNode<T>>;
+
+/// A subscriber called with the previous and the new state of a node
+type NodeSubscriber<T> = Arc<dyn Fn(&T, &T) + Send + Sync>;
+
+/// A conflict resolver that reports what it did
+type OutcomeResolver<T> = Arc<dyn Fn(&mut T, &T) -> ConflictOutcome + Send + Sync>;
+
+/// A listener for the conflicts a node resolves
+type ConflictListener<T> = Arc<dyn Fn(&ConflictEvent<'_, T>) + Send + Sync>;
+
+/// A reducer for operations of one type, returning `None` for other types
+type OpReducer<T> = Arc<dyn Fn(&T, &dyn Any) -> Option<T> + Send + Sync>;
+
+/// Orders two states by version, for the authority resolver
+type AuthorityResolver<T> = Arc<dyn Fn(&T, &T) -> Ordering + Send + Sync>;
+
+/// Decides whether a node approves a state proposed by a peer
+type WriteValidator<T> = Arc<dyn Fn(&T, &T) -> bool + Send + Sync>;
+
+/// How a conflict between a node's state and a remote state was resolved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConflictOutcome {
+    /// The node kept its own state
+    KeptLocal,
+    /// The node took over the remote state
+    AcceptedRemote,
+    /// The node combined both states
+    Merged,
+}
+
+/// A conflict resolved by a node, passed to its conflict listener.
+#[derive(Debug)]
+pub struct ConflictEvent<'a, T> {
+    /// ID of the node that resolved the conflict
+    pub node: &'a NodeId,
+    /// The node's state before the conflict
+    pub previous: &'a T,
+    /// The incoming remote state
+    pub remote: &'a T,
+    /// The node's state after the conflict
+    pub resolved: &'a T,
+    /// How the conflict was resolved
+    pub outcome: ConflictOutcome,
+}
+
+/// Identifies one update sent by `propagate_with_ttl()`.
+///
+/// Sequence numbers grow with every update from the same origin, so a node
+/// applies each update once and ignores older ones arriving late.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct UpdateId {
+    /// ID of the node the update started from
+    pub origin: NodeId,
+    /// Position of the update among those from its origin
+    pub sequence: u64,
+}
+
+/// Error returned by `commit()` when too few peers approved a write.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QuorumError {
+    /// Number of peers that approved the write
+    pub acknowledged: usize,
+    /// Number of approvals the write quorum requires
+    pub required: usize,
+}
+
+impl fmt::Display for QuorumError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "write quorum not reached: {} of {} acknowledgements",
+            self.acknowledged, self.required
+        )
+    }
+}
+
+impl std::error::Error for QuorumError {}
+
+/// Locks a mutex, recovering the guard if a previous holder panicked.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
 
 /// A node in the state mesh representing a piece of distributed state.
 ///
 /// Each node maintains its own state and connections to other nodes. When conflicts
 /// arise between different versions of state, the node uses its conflict resolver
 /// to determine how to merge or choose between conflicting states.
-#[derive(Clone)]
 pub struct StateNode<T: Clone> {
     /// Unique identifier for this node
     pub id: NodeId,
@@ -83,6 +234,70 @@ pub struct StateNode<T: Clone> {
     pub connections: StateNodeConnections<T>,
     /// Optional conflict resolution strategy
     pub on_conflict: Option<ConflictResolver<T>>,
+    /// Connections created by `connect_bidirectional()`
+    linked: HashSet<NodeId>,
+    /// Sequence number of the last update started here
+    sequence: u64,
+    /// Highest sequence number applied per origin
+    seen: HashMap<NodeId, u64>,
+    subscribers: Vec<(SubscriptionId, NodeSubscriber<T>)>,
+    next_subscriber_id: SubscriptionId,
+    /// Set by `set_conflict_resolver_with_outcome()`, next to `on_conflict`
+    outcome_resolver: Option<OutcomeResolver<T>>,
+    conflict_listener: Option<ConflictListener<T>>,
+    op_reducer: Option<OpReducer<T>>,
+    /// States waiting for connections marked offline, by connection
+    outbox: HashMap<NodeId, VecDeque<T>>,
+    /// Acknowledgements `commit()` needs before applying a write
+    write_quorum: Option<usize>,
+    write_validator: Option<WriteValidator<T>>,
+    /// Locked by `MeshLink::send_state()`, which only borrows the node
+    metrics: Mutex<NodeMetrics>,
+    /// Weight of this node's states in ties, see `set_authority()`
+    authority: u32,
+    /// Takes precedence over `on_conflict` when set
+    authority_resolver: Option<AuthorityResolver<T>>,
+    /// Leases known to this node, by region
+    leases: HashMap<String, Lease>,
+    regions: Option<RegionDiff<T>>,
+    lease_policy: LeasePolicy,
+    /// States held back by leases, with their sender and authority
+    deferred: Vec<(NodeId, T, u32)>,
+    /// Merkle tree of a keyed state, kept by `merkle_tree()`
+    merkle: Option<Box<dyn Any + Send + Sync>>,
+}
+
+/// Clones the state, connections, resolvers and update bookkeeping;
+/// subscribers and the conflict listener stay with the original, and the
+/// clone's metrics start at zero and its Merkle tree is rebuilt.
+impl<T: Clone> Clone for StateNode<T> {
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id.clone(),
+            state: self.state.clone(),
+            connections: self.connections.clone(),
+            on_conflict: self.on_conflict.clone(),
+            linked: self.linked.clone(),
+            sequence: self.sequence,
+            seen: self.seen.clone(),
+            subscribers: Vec::new(),
+            next_subscriber_id: 0,
+            outcome_resolver: self.outcome_resolver.clone(),
+            conflict_listener: None,
+            op_reducer: self.op_reducer.clone(),
+            outbox: self.outbox.clone(),
+            write_quorum: self.write_quorum,
+            write_validator: self.write_validator.clone(),
+            metrics: Mutex::default(),
+            authority: self.authority,
+            authority_resolver: self.authority_resolver.clone(),
+            leases: self.leases.clone(),
+            regions: self.regions.clone(),
+            lease_policy: self.lease_policy,
+            deferred: self.deferred.clone(),
+            merkle: None,
+        }
+    }
 }
 
 impl<T: Clone> StateNode<T> {
@@ -109,13 +324,41 @@ impl<T: Clone> StateNode<T> {
             state: initial_state,
             connections: HashMap::new(),
             on_conflict: None,
+            linked: HashSet::new(),
+            sequence: 0,
+            seen: HashMap::new(),
+            subscribers: Vec::new(),
+            next_subscriber_id: 0,
+            outcome_resolver: None,
+            conflict_listener: None,
+            op_reducer: None,
+            outbox: HashMap::new(),
+            write_quorum: None,
+            write_validator: None,
+            metrics: Mutex::default(),
+            authority: 0,
+            authority_resolver: None,
+            leases: HashMap::new(),
+            regions: None,
+            lease_policy: LeasePolicy::Reject,
+            deferred: Vec::new(),
+            merkle: None,
         }
     }
 
+    /// Wraps this node in a [`SharedStateNode`], so other nodes can connect
+    /// to it.
+    pub fn into_shared(self) -> SharedStateNode<T> {
+        Arc::new(Mutex::new(self))
+    }
+
     /// Connects this node to another node.
     ///
     /// This creates a one-way connection from this node to the other node.
-    /// For bidirectional connections, you need to call connect on both nodes.
+    /// For bidirectional connections, use `connect_bidirectional()`.
+    ///
+    /// The other node is locked briefly to read its ID, so it must not be
+    /// locked by the caller, and a node cannot be connected to itself.
     ///
     /// # Arguments
     ///
@@ -127,16 +370,72 @@ impl<T: Clone> StateNode<T> {
     /// # use zed::StateNode;
     /// # #[derive(Clone)] struct MyState { value: i32 }
     /// let mut node1 = StateNode::new("node1".to_string(), MyState { value: 1 });
-    /// let node2 = StateNode::new("node2".to_string(), MyState { value: 2 });
+    /// let node2 = StateNode::new("node2".to_string(), MyState { value: 2 }).into_shared();
+    ///
+    /// node1.connect(&node2);
+    /// ```
+    pub fn connect(&mut self, other: &SharedStateNode<T>) {
+        let id = lock(other).id.clone();
+        self.connections.insert(id, Arc::clone(other));
+    }
+
+    /// Connects two nodes to each other.
+    ///
+    /// Both connections are registered while both nodes are locked, so no
+    /// thread sees only one of them, and removing either one with
+    /// `remove_connection()` also removes the other. The nodes hold each
+    /// other until then, so they are not dropped while linked.
+    ///
+    /// # Returns
     ///
-    /// node1.connect(node2);
+    /// `false`, without connecting anything, if both handles are the same
+    /// node.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use zed::StateNode;
+    /// # #[derive(Clone)] struct MyState { value: i32 }
+    /// let alice = StateNode::new("alice".to_string(), MyState { value: 1 }).into_shared();
+    /// let bob = StateNode::new("bob".to_string(), MyState { value: 2 }).into_shared();
+    ///
+    /// StateNode::connect_bidirectional(&alice, &bob);
+    /// assert!(bob.lock().unwrap().connections.contains_key("alice"));
+    ///
+    /// alice.lock().unwrap().remove_connection(&"bob".to_string());
+    /// assert!(bob.lock().unwrap().connections.is_empty());
     /// ```
-    pub fn connect(&mut self, other: StateNode<T>) {
-        self.connections.insert(other.id.clone(), other);
+    pub fn connect_bidirectional(node: &SharedStateNode<T>, other: &SharedStateNode<T>) -> bool {
+        if Arc::ptr_eq(node, other) {
+            return false;
+        }
+        // Lock in a fixed order so concurrent calls cannot deadlock
+        let (first, second) = if Arc::as_ptr(node) < Arc::as_ptr(other) {
+            (node, other)
+        } else {
+            (other, node)
+        };
+        let mut first_node = lock(first);
+        let mut second_node = lock(second);
+
+        let (first_id, second_id) = (first_node.id.clone(), second_node.id.clone());
+        first_node
+            .connections
+            .insert(second_id.clone(), Arc::clone(second));
+        first_node.linked.insert(second_id.clone());
+        second_node
+            .connections
+            .insert(first_id.clone(), Arc::clone(first));
+        second_node.linked.insert(first_id);
+        true
     }
 
     /// Removes a connection to another node.
     ///
+    /// If the connection was created with `connect_bidirectional()`, the
+    /// other node's connection back to this one is removed as well, which
+    /// locks the other node.
+    ///
     /// # Arguments
     ///
     /// * `id` - ID of the node to disconnect
@@ -151,12 +450,90 @@ impl<T: Clone> StateNode<T> {
     /// # use zed::StateNode;
     /// # #[derive(Clone)] struct MyState { value: i32 }
     /// # let mut node1 = StateNode::new("node1".to_string(), MyState { value: 1 });
-    /// # let node2 = StateNode::new("node2".to_string(), MyState { value: 2 });
-    /// # node1.connect(node2);
+    /// # let node2 = StateNode::new("node2".to_string(), MyState { value: 2 }).into_shared();
+    /// # node1.connect(&node2);
     /// let removed = node1.remove_connection(&"node2".to_string());
     /// ```
-    pub fn remove_connection(&mut self, id: &NodeId) -> Option<StateNode<T>> {
-        self.connections.remove(id)
+    pub fn remove_connection(&mut self, id: &NodeId) -> Option<SharedStateNode<T>> {
+        let removed = self.connections.remove(id)?;
+        self.outbox.remove(id);
+        if self.linked.remove(id) {
+            let mut other = lock(&removed);
+            other.connections.remove(&self.id);
+            other.linked.remove(&self.id);
+            other.outbox.remove(&self.id);
+        }
+        Some(removed)
+    }
+
+    /// Marks a connection as down, e.g. while the peer is unreachable.
+    ///
+    /// Until `mark_online()`, states propagated by `propagate_update()` and
+    /// `propagate_with_ttl()` are queued in this node's outbox instead of
+    /// being sent to the peer. Operations sent by `propagate_op()` are not
+    /// queued; they skip the peer. Only this node's side of the connection
+    /// is affected.
+    ///
+    /// # Returns
+    ///
+    /// `false` if this node has no connection with that ID.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use zed::StateNode;
+    /// let mut laptop = StateNode::new("laptop".to_string(), 0);
+    /// let server = StateNode::new("server".to_string(), 0).into_shared();
+    /// laptop.connect(&server);
+    ///
+    /// laptop.mark_offline(&"server".to_string());
+    /// for edit in 1..=3 {
+    ///     laptop.state = edit;
+    ///     laptop.propagate_update();
+    /// }
+    /// assert_eq!(server.lock().unwrap().state, 0);
+    ///
+    /// // Back online, the queued states are delivered in order
+    /// assert_eq!(laptop.mark_online(&"server".to_string()), 3);
+    /// assert_eq!(server.lock().unwrap().state, 3);
+    /// ```
+    pub fn mark_offline(&mut self, id: &NodeId) -> bool {
+        if !self.connections.contains_key(id) {
+            return false;
+        }
+        self.outbox.entry(id.clone()).or_default();
+        true
+    }
+
+    /// Marks a connection as up again and delivers the states queued for it
+    /// in the meantime, oldest first, through the peer's conflict resolver.
+    ///
+    /// # Returns
+    ///
+    /// The number of states delivered.
+    pub fn mark_online(&mut self, id: &NodeId) -> usize {
+        let (Some(queued), Some(peer)) = (self.outbox.remove(id), self.connections.get(id)) else {
+            return 0;
+        };
+        let start = Instant::now();
+        let mut peer = lock(peer);
+        let delivered = queued.len();
+        for state in queued {
+            peer.receive_state(&self.id, state, self.authority);
+        }
+        drop(peer);
+        self.record_propagation(delivered, start);
+        delivered
+    }
+
+    /// Returns `true` if the connection exists and is not marked offline.
+    pub fn is_online(&self, id: &NodeId) -> bool {
+        self.connections.contains_key(id) && !self.outbox.contains_key(id)
+    }
+
+    /// Returns the number of states queued for a connection marked offline.
+    pub fn pending_updates(&self, id: &NodeId) -> usize {
+        self.outbox.get(id).map_or(0, VecDeque::len)
     }
 
     /// Sets a conflict resolution strategy for this node.
@@ -189,6 +566,147 @@ impl<T: Clone> StateNode<T> {
         F: 'static + Fn(&mut T, &T) + Send + Sync,
     {
         self.on_conflict = Some(Arc::new(resolver));
+        self.outcome_resolver = None;
+        self.authority_resolver = None;
+    }
+
+    /// Sets a conflict resolution strategy that reports how it resolved
+    /// each conflict.
+    ///
+    /// Works like `set_conflict_resolver()`, but the returned
+    /// [`ConflictOutcome`] is passed on to the conflict listener and
+    /// returned by `resolve_conflict()`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use zed::{ConflictOutcome, StateNode};
+    /// # #[derive(Clone)] struct MyState { value: i32, version: u32 }
+    /// # let mut node = StateNode::new("node1".to_string(), MyState { value: 1, version: 1 });
+    /// node.set_conflict_resolver_with_outcome(|current: &mut MyState, remote: &MyState| {
+    ///     if remote.version > current.version {
+    ///         *current = remote.clone();
+    ///         ConflictOutcome::AcceptedRemote
+    ///     } else {
+    ///         ConflictOutcome::KeptLocal
+    ///     }
+    /// });
+    ///
+    /// let outcome = node.resolve_conflict(MyState { value: 2, version: 0 });
+    /// assert_eq!(outcome, ConflictOutcome::KeptLocal);
+    /// ```
+    pub fn set_conflict_resolver_with_outcome<F>(&mut self, resolver: F)
+    where
+        T: 'static,
+        F: 'static + Fn(&mut T, &T) -> ConflictOutcome + Send + Sync,
+    {
+        let resolver: OutcomeResolver<T> = Arc::new(resolver);
+        let plain = Arc::clone(&resolver);
+        self.on_conflict = Some(Arc::new(move |current: &mut T, remote: &T| {
+            plain(current, remote);
+        }));
+        self.outcome_resolver = Some(resolver);
+        self.authority_resolver = None;
+    }
+
+    /// Sets the weight of this node's states when they tie with others
+    /// under an authority resolver. Nodes start with an authority of 0.
+    ///
+    /// A server can be given a higher authority than its clients, so its
+    /// state wins whenever a client changed the same version concurrently.
+    pub fn set_authority(&mut self, authority: u32) {
+        self.authority = authority;
+    }
+
+    /// Returns the authority set by `set_authority()`.
+    pub fn authority(&self) -> u32 {
+        self.authority
+    }
+
+    /// Resolves conflicts by version, and ties by authority.
+    ///
+    /// The state with the greater `version` wins. If both versions are
+    /// equal, the remote state wins only if it comes from a node with a
+    /// higher authority than this one; otherwise the local state is kept.
+    /// Replaces the resolver set by `set_conflict_resolver()` or
+    /// `set_conflict_resolver_with_outcome()`.
+    ///
+    /// The remote authority is known for states propagated between nodes
+    /// in this process. For `resolve_conflict()` it is 0, and for states
+    /// received over a `MeshLink` it is the one set with
+    /// `MeshLink::peer_authority()`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use zed::{ConflictOutcome, StateNode};
+    ///
+    /// #[derive(Clone, Debug, PartialEq)]
+    /// struct Slide { title: String, version: u32 }
+    ///
+    /// let mut server = StateNode::new("server".to_string(), Slide { title: "Intro".into(), version: 2 });
+    /// server.set_authority(10);
+    /// let client = StateNode::new("client".to_string(), Slide { title: "Hello".into(), version: 2 })
+    ///     .into_shared();
+    /// client.lock().unwrap().set_authority_resolver(|slide: &Slide| slide.version);
+    /// server.connect(&client);
+    ///
+    /// // Same version: the server's edit wins the tie
+    /// server.propagate_update();
+    /// assert_eq!(client.lock().unwrap().state.title, "Intro");
+    ///
+    /// // Anyone else loses it
+    /// let outcome = client.lock().unwrap().resolve_conflict(Slide { title: "Other".into(), version: 2 });
+    /// assert_eq!(outcome, ConflictOutcome::KeptLocal);
+    /// ```
+    pub fn set_authority_resolver<K, F>(&mut self, version: F)
+    where
+        K: Ord,
+        F: Fn(&T) -> K + Send + Sync + 'static,
+    {
+        self.authority_resolver = Some(Arc::new(move |remote: &T, local: &T| {
+            version(remote).cmp(&version(local))
+        }));
+        self.on_conflict = None;
+        self.outcome_resolver = None;
+    }
+
+    /// Sets a listener called for every conflict this node resolves, e.g.
+    /// to tell users that their edit was overridden.
+    ///
+    /// The listener runs while the node is borrowed (and locked, if
+    /// shared), so it must not lock the node again. It replaces any
+    /// previous listener.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::sync::{Arc, Mutex};
+    /// use zed::{ConflictOutcome, StateNode};
+    ///
+    /// let overridden = Arc::new(Mutex::new(Vec::new()));
+    /// let mut node = StateNode::new("editor".to_string(), "my edit".to_string());
+    ///
+    /// let notices = Arc::clone(&overridden);
+    /// node.set_conflict_listener(move |event| {
+    ///     if event.outcome == ConflictOutcome::AcceptedRemote {
+    ///         notices.lock().unwrap().push(event.previous.clone());
+    ///     }
+    /// });
+    ///
+    /// node.resolve_conflict("their edit".to_string());
+    /// assert_eq!(*overridden.lock().unwrap(), vec!["my edit".to_string()]);
+    /// ```
+    pub fn set_conflict_listener<F>(&mut self, listener: F)
+    where
+        F: 'static + Fn(&ConflictEvent<'_, T>) + Send + Sync,
+    {
+        self.conflict_listener = Some(Arc::new(listener));
+    }
+
+    /// Removes the conflict listener.
+    pub fn clear_conflict_listener(&mut self) {
+        self.conflict_listener = None;
     }
 
     /// Resolves a conflict with remote state using the configured strategy.
@@ -196,6 +714,8 @@ impl<T: Clone> StateNode<T> {
     /// If no conflict resolver is set, this defaults to replacing the current
     /// state with the remote state.
     ///
+    /// The conflict listener and subscribers are notified afterwards.
+    ///
     /// # Arguments
     ///
     /// * `remote_state` - The conflicting state from a remote source
@@ -209,11 +729,292 @@ impl<T: Clone> StateNode<T> {
     /// let remote_state = MyState { value: 42 };
     /// node.resolve_conflict(remote_state);
     /// ```
-    pub fn resolve_conflict(&mut self, remote_state: T) {
-        if let Some(ref resolver) = self.on_conflict {
-            resolver(&mut self.state, &remote_state);
-        } else {
-            self.state = remote_state;
+    ///
+    /// # Returns
+    ///
+    /// How the conflict was resolved. Resolvers set with
+    /// `set_conflict_resolver()` cannot tell, so they always report
+    /// `ConflictOutcome::Merged`.
+    pub fn resolve_conflict(&mut self, remote_state: T) -> ConflictOutcome {
+        self.resolve_conflict_from(remote_state, 0)
+    }
+
+    /// Resolves a conflict with a state from a node with the given
+    /// authority, like `resolve_conflict()`.
+    ///
+    /// The authority only matters to the resolver set by
+    /// `set_authority_resolver()`.
+    pub fn resolve_conflict_from(&mut self, remote_state: T, authority: u32) -> ConflictOutcome {
+        // Only kept when someone needs to see it
+        let previous = (!self.subscribers.is_empty() || self.conflict_listener.is_some())
+            .then(|| self.state.clone());
+        self.metrics_mut().updates_received += 1;
+        let outcome = match (
+            &self.on_conflict,
+            &self.outcome_resolver,
+            &self.authority_resolver,
+        ) {
+            (_, _, Some(compare)) => {
+                let accept = match compare(&remote_state, &self.state) {
+                    Ordering::Greater => true,
+                    Ordering::Less => false,
+                    Ordering::Equal => authority > self.authority,
+                };
+                if accept {
+                    self.state.clone_from(&remote_state);
+                    ConflictOutcome::AcceptedRemote
+                } else {
+                    ConflictOutcome::KeptLocal
+                }
+            }
+            (Some(_), Some(resolver), None) => resolver(&mut self.state, &remote_state),
+            (Some(resolver), None, None) => {
+                resolver(&mut self.state, &remote_state);
+                ConflictOutcome::Merged
+            }
+            (None, _, None) => match previous {
+                None => {
+                    self.state = remote_state;
+                    self.record_conflict(ConflictOutcome::AcceptedRemote);
+                    return ConflictOutcome::AcceptedRemote;
+                }
+                Some(_) => {
+                    self.state.clone_from(&remote_state);
+                    ConflictOutcome::AcceptedRemote
+                }
+            },
+        };
+        self.record_conflict(outcome);
+        if let Some(previous) = previous {
+            if let Some(listener) = &self.conflict_listener {
+                listener(&ConflictEvent {
+                    node: &self.id,
+                    previous: &previous,
+                    remote: &remote_state,
+                    resolved: &self.state,
+                    outcome,
+                });
+            }
+            self.notify_subscribers(&previous);
+        }
+        outcome
+    }
+
+    /// Sets the reducer this node applies operations of type `Op` with.
+    ///
+    /// Nodes that exchange operations instead of whole states keep the
+    /// intent of concurrent edits, e.g. two insertions into the same text,
+    /// where replacing one state with the other would lose one of them.
+    /// A node has one operation reducer; setting another replaces it.
+    /// Operations are matched by type, so they have to be passed as exactly
+    /// `Op`, e.g. `&5i64` rather than `&5` for an `i64` reducer.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use zed::StateNode;
+    ///
+    /// enum Edit { Append(String) }
+    ///
+    /// let mut node = StateNode::new("doc".to_string(), String::from("Hello"));
+    /// node.set_op_reducer(|text: &String, edit: &Edit| match edit {
+    ///     Edit::Append(suffix) => format!("{text}{suffix}"),
+    /// });
+    ///
+    /// assert!(node.apply_remote_op(&Edit::Append(", world".to_string())));
+    /// assert_eq!(node.state, "Hello, world");
+    /// ```
+    pub fn set_op_reducer<Op, F>(&mut self, reducer: F)
+    where
+        T: 'static,
+        Op: 'static,
+        F: Fn(&T, &Op) -> T + Send + Sync + 'static,
+    {
+        self.op_reducer = Some(Arc::new(move |state: &T, op: &dyn Any| {
+            op.downcast_ref::<Op>().map(|op| reducer(state, op))
+        }));
+    }
+
+    /// Applies an operation received from another node with this node's
+    /// operation reducer, notifying subscribers.
+    ///
+    /// # Returns
+    ///
+    /// `false`, leaving the state unchanged, if no reducer for operations
+    /// of type `Op` is set.
+    pub fn apply_remote_op<Op: 'static>(&mut self, op: &Op) -> bool {
+        let Some(next) = self
+            .op_reducer
+            .as_ref()
+            .and_then(|reducer| reducer(&self.state, op))
+        else {
+            return false;
+        };
+        let previous = std::mem::replace(&mut self.state, next);
+        self.metrics_mut().updates_received += 1;
+        self.notify_subscribers(&previous);
+        true
+    }
+
+    /// Sends an operation to all connected nodes, which apply it with
+    /// `apply_remote_op()`.
+    ///
+    /// This node's state is left unchanged; see `dispatch_op()`. Connected
+    /// nodes are locked in turn, as in `propagate_update()`.
+    ///
+    /// # Returns
+    ///
+    /// The number of connected nodes that applied the operation.
+    pub fn propagate_op<Op: 'static>(&mut self, op: &Op) -> usize {
+        let start = Instant::now();
+        let applied = self
+            .connections
+            .iter()
+            .filter(|(id, _)| !self.outbox.contains_key(*id))
+            .filter(|(_, node)| lock(node).apply_remote_op(op))
+            .count();
+        self.record_propagation(applied, start);
+        applied
+    }
+
+    /// Applies an operation to this node and sends it to all connected
+    /// nodes.
+    ///
+    /// # Returns
+    ///
+    /// `false` if this node has no reducer for operations of type `Op`, in
+    /// which case the operation is not sent either.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use zed::StateNode;
+    ///
+    /// fn add(total: &i64, amount: &i64) -> i64 { total + amount }
+    ///
+    /// let mut alice = StateNode::new("alice".to_string(), 10);
+    /// alice.set_op_reducer(add);
+    /// let bob = StateNode::new("bob".to_string(), 10).into_shared();
+    /// bob.lock().unwrap().set_op_reducer(add);
+    /// alice.connect(&bob);
+    ///
+    /// // Bob's own change survives Alice's, unlike with whole states
+    /// bob.lock().unwrap().apply_remote_op(&5i64);
+    /// alice.dispatch_op(&-3i64);
+    /// assert_eq!(alice.state, 7);
+    /// assert_eq!(bob.lock().unwrap().state, 12);
+    /// ```
+    pub fn dispatch_op<Op: 'static>(&mut self, op: &Op) -> bool {
+        if !self.apply_remote_op(op) {
+            return false;
+        }
+        self.propagate_op(op);
+        true
+    }
+
+    /// Removes a subscriber.
+    ///
+    /// # Returns
+    ///
+    /// `true` if a subscriber with that ID existed.
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        let len = self.subscribers.len();
+        self.subscribers.retain(|(subscriber, _)| *subscriber != id);
+        self.subscribers.len() < len
+    }
+
+    /// Returns the connections that are not marked offline, queueing
+    /// `state` in the outbox of the others.
+    fn reachable(
+        connections: &StateNodeConnections<T>,
+        outbox: &mut HashMap<NodeId, VecDeque<T>>,
+        state: &T,
+    ) -> Vec<(NodeId, SharedStateNode<T>)> {
+        let mut reachable = Vec::with_capacity(connections.len());
+        for (id, node) in connections {
+            match outbox.get_mut(id) {
+                Some(queued) => queued.push_back(state.clone()),
+                None => reachable.push((id.clone(), Arc::clone(node))),
+            }
+        }
+        reachable
+    }
+
+    /// Returns a snapshot of this node's counters.
+    ///
+    /// Counted from the node's creation, or the last `reset_metrics()`:
+    ///
+    /// * States and operations sent by `propagate_update()`,
+    ///   `propagate_with_ttl()`, `propagate_op()`, `mark_online()` and
+    ///   `MeshLink::send_state()`
+    /// * Remote states resolved by `resolve_conflict()`, by outcome, and
+    ///   operations applied by `apply_remote_op()`
+    /// * Bytes sent and received over `MeshLink`s
+    /// * Time spent in propagations started by the node, which for updates
+    ///   reaching other threads' nodes includes waiting for their locks
+    ///
+    /// With the `tracing` feature, propagations and conflicts are also
+    /// reported as `tracing` events at debug and trace level.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use zed::StateNode;
+    ///
+    /// let mut node = StateNode::new("a".to_string(), 1);
+    /// let peer = StateNode::new("b".to_string(), 0).into_shared();
+    /// node.connect(&peer);
+    /// node.propagate_update();
+    ///
+    /// assert_eq!(node.metrics().updates_sent, 1);
+    /// assert_eq!(peer.lock().unwrap().metrics().conflicts_accepted_remote, 1);
+    /// ```
+    pub fn metrics(&self) -> NodeMetrics {
+        lock(&self.metrics).clone()
+    }
+
+    /// Sets all of this node's counters back to zero.
+    pub fn reset_metrics(&mut self) {
+        *self.metrics_mut() = NodeMetrics::default();
+    }
+
+    fn record_sent_bytes(&self, bytes: u64) {
+        let mut metrics = lock(&self.metrics);
+        metrics.updates_sent += 1;
+        metrics.bytes_sent += bytes;
+    }
+
+    fn record_received_bytes(&mut self, bytes: u64) {
+        self.metrics_mut().bytes_received += bytes;
+    }
+
+    fn metrics_mut(&mut self) -> &mut NodeMetrics {
+        self.metrics
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn record_propagation(&mut self, sent: usize, start: Instant) {
+        let elapsed = start.elapsed();
+        self.metrics_mut().record_propagation(sent, elapsed);
+        #[cfg(feature = "tracing")]
+        tracing::debug!(node = %self.id, sent, ?elapsed, "propagated state");
+    }
+
+    fn record_conflict(&mut self, outcome: ConflictOutcome) {
+        let metrics = self.metrics_mut();
+        match outcome {
+            ConflictOutcome::KeptLocal => metrics.conflicts_kept_local += 1,
+            ConflictOutcome::AcceptedRemote => metrics.conflicts_accepted_remote += 1,
+            ConflictOutcome::Merged => metrics.conflicts_merged += 1,
+        }
+        #[cfg(feature = "tracing")]
+        tracing::trace!(node = %self.id, ?outcome, "resolved conflict");
+    }
+
+    fn notify_subscribers(&self, previous: &T) {
+        for (_, subscriber) in &self.subscribers {
+            subscriber(previous, &self.state);
         }
     }
 
@@ -222,20 +1023,250 @@ impl<T: Clone> StateNode<T> {
     /// This triggers conflict resolution on each connected node, potentially
     /// updating their states based on their conflict resolution strategies.
     ///
+    /// Each connected node is locked in turn while it resolves the update.
+    /// If this node is itself shared, two threads propagating between the
+    /// same pair of nodes in opposite directions can deadlock; copy the
+    /// state and connections out of the lock first in that case.
+    ///
     /// # Example
     ///
     /// ```rust
     /// # use zed::StateNode;
     /// # #[derive(Clone)] struct MyState { value: i32 }
     /// # let mut node1 = StateNode::new("node1".to_string(), MyState { value: 1 });
-    /// # let node2 = StateNode::new("node2".to_string(), MyState { value: 2 });
-    /// # node1.connect(node2);
+    /// # let node2 = StateNode::new("node2".to_string(), MyState { value: 2 }).into_shared();
+    /// # node1.connect(&node2);
     /// node1.propagate_update(); // All connected nodes receive this node's state
+    /// assert_eq!(node2.lock().unwrap().state.value, 1);
     /// ```
     pub fn propagate_update(&mut self) {
-        for node in self.connections.values_mut() {
-            node.resolve_conflict(self.state.clone());
+        let start = Instant::now();
+        let reachable = Self::reachable(&self.connections, &mut self.outbox, &self.state);
+        for (_, node) in &reachable {
+            lock(node).receive_state(&self.id, self.state.clone(), self.authority);
+        }
+        self.record_propagation(reachable.len(), start);
+    }
+
+    /// Propagates this node's current state through the mesh, up to `ttl`
+    /// hops away.
+    ///
+    /// Every node that applies the update forwards it to its own
+    /// connections while hops remain. The update carries an [`UpdateId`],
+    /// so each node applies it at most once and cycles in the topology end
+    /// the propagation instead of repeating it. A `ttl` of 1 reaches the
+    /// same nodes as `propagate_update()`.
+    ///
+    /// One node is locked at a time, and this node is never locked, so it
+    /// may be called while holding this node's lock. Two threads
+    /// propagating from nodes they hold locked can still deadlock, as with
+    /// `propagate_update()`.
+    ///
+    /// # Returns
+    ///
+    /// The number of nodes that applied the update.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use zed::StateNode;
+    /// # #[derive(Clone)] struct MyState { value: i32 }
+    /// let a = StateNode::new("a".to_string(), MyState { value: 1 }).into_shared();
+    /// let b = StateNode::new("b".to_string(), MyState { value: 0 }).into_shared();
+    /// let c = StateNode::new("c".to_string(), MyState { value: 0 }).into_shared();
+    ///
+    /// // A cycle: a -> b -> c -> a
+    /// a.lock().unwrap().connect(&b);
+    /// b.lock().unwrap().connect(&c);
+    /// c.lock().unwrap().connect(&a);
+    ///
+    /// assert_eq!(a.lock().unwrap().propagate_with_ttl(8), 2);
+    /// assert_eq!(c.lock().unwrap().state.value, 1);
+    /// ```
+    pub fn propagate_with_ttl(&mut self, ttl: u32) -> usize {
+        self.sequence += 1;
+        let id = UpdateId {
+            origin: self.id.clone(),
+            sequence: self.sequence,
+        };
+        self.seen.insert(self.id.clone(), self.sequence);
+
+        // Breadth-first, so every node is reached on its shortest path
+        let mut visited = HashSet::from([self.id.clone()]);
+        let mut next = Self::reachable(&self.connections, &mut self.outbox, &self.state);
+        let start = Instant::now();
+        let mut hops = ttl;
+        let mut sent = 0;
+        let mut applied = 0;
+        while hops > 0 && !next.is_empty() {
+            let layer: Vec<SharedStateNode<T>> = next
+                .drain(..)
+                .filter(|(peer_id, _)| visited.insert(peer_id.clone()))
+                .map(|(_, peer)| peer)
+                .collect();
+            hops -= 1;
+            sent += layer.len();
+            for peer in layer {
+                let mut peer = lock(&peer);
+                if peer.apply_update_from(&id, self.state.clone(), self.authority) {
+                    applied += 1;
+                    if hops > 0 {
+                        let peer = &mut *peer;
+                        next.extend(Self::reachable(
+                            &peer.connections,
+                            &mut peer.outbox,
+                            &self.state,
+                        ));
+                    }
+                }
+            }
+        }
+        self.record_propagation(sent, start);
+        applied
+    }
+
+    /// Applies an update received from another node, like
+    /// `resolve_conflict()`, unless this node has already applied it or a
+    /// later update from the same origin.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the update was applied.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use zed::{StateNode, UpdateId};
+    /// # #[derive(Clone)] struct MyState { value: i32 }
+    /// let mut node = StateNode::new("node".to_string(), MyState { value: 0 });
+    /// let id = UpdateId { origin: "remote".to_string(), sequence: 1 };
+    ///
+    /// assert!(node.apply_update(&id, MyState { value: 1 }));
+    /// assert!(!node.apply_update(&id, MyState { value: 1 }));
+    /// ```
+    pub fn apply_update(&mut self, id: &UpdateId, state: T) -> bool {
+        self.apply_update_from(id, state, 0)
+    }
+
+    fn apply_update_from(&mut self, id: &UpdateId, state: T, authority: u32) -> bool {
+        if self.has_seen(id) {
+            return false;
         }
+        self.seen.insert(id.origin.clone(), id.sequence);
+        self.receive_state(&id.origin, state, authority);
+        true
+    }
+
+    /// Returns `true` if this node has applied the update, or a later one
+    /// from the same origin.
+    pub fn has_seen(&self, id: &UpdateId) -> bool {
+        self.seen
+            .get(&id.origin)
+            .is_some_and(|&sequence| sequence >= id.sequence)
+    }
+
+    /// Sets how many peers must approve a write before `commit()` applies
+    /// it, or `None` to apply every write.
+    ///
+    /// This gates writes on in-process peers' validators; it is not a
+    /// consensus protocol and does not prevent split-brain divergence. The
+    /// count is not checked against the number of connections, so two
+    /// partitions may both reach a quorum below a majority, and approving
+    /// peers reserve nothing (see `commit()`). Peers marked offline cannot
+    /// approve.
+    pub fn set_write_quorum(&mut self, quorum: Option<usize>) {
+        self.write_quorum = quorum;
+    }
+
+    /// Returns the write quorum set by `set_write_quorum()`.
+    pub fn write_quorum(&self) -> Option<usize> {
+        self.write_quorum
+    }
+
+    /// Sets how this node answers writes proposed by peers through
+    /// `commit()`.
+    ///
+    /// The validator receives this node's current state and the proposed
+    /// state, and returns `true` to approve the write. Without a validator,
+    /// every write is approved.
+    pub fn set_write_validator<F>(&mut self, validator: F)
+    where
+        F: Fn(&T, &T) -> bool + Send + Sync + 'static,
+    {
+        self.write_validator = Some(Arc::new(validator));
+    }
+
+    /// Returns `true` if this node approves a write of `proposed`.
+    pub fn acknowledges(&self, proposed: &T) -> bool {
+        self.write_validator
+            .as_ref()
+            .is_none_or(|validator| validator(&self.state, proposed))
+    }
+
+    /// Writes a new state once enough in-process peers approve it, and
+    /// propagates it.
+    ///
+    /// Every online connection is asked, through its validator, whether it
+    /// approves the state. If fewer peers approve it than
+    /// `set_write_quorum()` requires, the write is dropped: this node keeps
+    /// its state and nothing is sent. Otherwise the state is applied, this
+    /// node's subscribers are notified and the state is propagated as by
+    /// `propagate_update()`.
+    ///
+    /// Approval only runs the validators; it reserves nothing. The
+    /// propagated state still goes through each peer's conflict resolution
+    /// and leases, which may keep the peer's own state. Peers reached over a
+    /// `MeshLink` or another transport take no part.
+    ///
+    /// Connected nodes are locked one at a time, with the same deadlock
+    /// caveat as `propagate_update()`.
+    ///
+    /// # Returns
+    ///
+    /// The number of peers that approved the write.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`QuorumError`] if the quorum was not reached.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use zed::StateNode;
+    ///
+    /// let mut warehouse = StateNode::new("warehouse".to_string(), 10u32);
+    /// let shop = StateNode::new("shop".to_string(), 10u32).into_shared();
+    /// let web = StateNode::new("web".to_string(), 10u32).into_shared();
+    /// warehouse.connect(&shop);
+    /// warehouse.connect(&web);
+    /// warehouse.set_write_quorum(Some(2));
+    ///
+    /// assert_eq!(warehouse.commit(9), Ok(2));
+    /// assert_eq!(shop.lock().unwrap().state, 9);
+    ///
+    /// // With a peer unreachable, the write is refused
+    /// warehouse.mark_offline(&"web".to_string());
+    /// assert!(warehouse.commit(8).is_err());
+    /// assert_eq!(warehouse.state, 9);
+    /// ```
+    pub fn commit(&mut self, state: T) -> Result<usize, QuorumError> {
+        let acknowledged = self
+            .connections
+            .iter()
+            .filter(|(id, _)| !self.outbox.contains_key(*id))
+            .filter(|(_, node)| lock(node).acknowledges(&state))
+            .count();
+        let required = self.write_quorum.unwrap_or(0);
+        if acknowledged < required {
+            return Err(QuorumError {
+                acknowledged,
+                required,
+            });
+        }
+        let previous = std::mem::replace(&mut self.state, state);
+        self.notify_subscribers(&previous);
+        self.propagate_update();
+        Ok(acknowledged)
     }
 
     /// Merges state from another node using conflict resolution.
@@ -255,7 +1286,78 @@ impl<T: Clone> StateNode<T> {
     /// # let node2 = StateNode::new("node2".to_string(), MyState { value: 2 });
     /// node1.merge(&node2); // Merge node2's state into node1
     /// ```
-    pub fn merge(&mut self, other: &StateNode<T>) {
-        self.resolve_conflict(other.state.clone());
+    pub fn merge(&mut self, other: &StateNode<T>) -> ConflictOutcome {
+        self.receive_state(&other.id, other.state.clone(), other.authority)
+    }
+}
+
+impl<T: Clone + PartialEq + 'static> StateNode<T> {
+    /// Subscribes to changes of this node's state made by
+    /// `resolve_conflict()`, and so by `merge()` and remote updates, or by
+    /// `commit()`.
+    ///
+    /// The subscriber is called with the new state, and only if the update
+    /// changed it. It runs while the node is borrowed (and locked, if
+    /// shared), so it must not lock the node again. Assigning to `state`
+    /// directly is not reported.
+    ///
+    /// # Returns
+    ///
+    /// A `SubscriptionId` that can be passed to `unsubscribe()`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::sync::{Arc, Mutex};
+    /// use zed::StateNode;
+    ///
+    /// let shown = Arc::new(Mutex::new(String::new()));
+    /// let mut node = StateNode::new("editor".to_string(), "draft".to_string());
+    ///
+    /// let view = Arc::clone(&shown);
+    /// node.subscribe(move |text: &String| *view.lock().unwrap() = text.clone());
+    ///
+    /// node.resolve_conflict("remote edit".to_string());
+    /// assert_eq!(*shown.lock().unwrap(), "remote edit");
+    /// ```
+    pub fn subscribe<F>(&mut self, f: F) -> SubscriptionId
+    where
+        F: Fn(&T) + Send + Sync + 'static,
+    {
+        let id = self.next_subscriber_id;
+        self.next_subscriber_id += 1;
+        self.subscribers.push((
+            id,
+            Arc::new(move |previous: &T, state: &T| {
+                if previous != state {
+                    f(state);
+                }
+            }),
+        ));
+        id
+    }
+}
+
+impl<T: Clone + Mergeable + 'static> StateNode<T> {
+    /// Creates a node whose conflicts are resolved by [`Mergeable::merge`],
+    /// e.g. for states built from the [`crdt`](crate::crdt) types.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use zed::{GCounter, StateNode};
+    ///
+    /// let mut remote = GCounter::new();
+    /// remote.increment("remote", 2);
+    ///
+    /// let mut node = StateNode::mergeable("local".to_string(), GCounter::new());
+    /// node.state.increment("local", 1);
+    /// node.resolve_conflict(remote);
+    /// assert_eq!(node.state.value(), 3);
+    /// ```
+    pub fn mergeable(id: NodeId, initial_state: T) -> Self {
+        let mut node = Self::new(id, initial_state);
+        node.set_conflict_resolver(|current: &mut T, remote: &T| current.merge(remote));
+        node
     }
 }
@@ -0,0 +1,131 @@
+//! # Compression Module
+//!
+//! Compression of the states and deltas a [`MeshLink`](super::transport::MeshLink)
+//! sends, since document states in collaborative applications shrink a lot.
+//!
+//! Two algorithms can be enabled with crate features:
+//!
+//! - `Lz4`, fast with a moderate ratio (requires the `lz4` feature)
+//! - `Zstd`, a better ratio for more CPU time (requires the `zstd` feature)
+//!
+//! Compression is negotiated per connection: a link created with
+//! [`MeshLink::compression`](super::transport::MeshLink::compression)
+//! announces the algorithms it can decode, and compresses only once the
+//! peer has announced the preferred one. Peers without compression keep
+//! receiving plain messages.
+//!
+//! ## Example
+//!
+//! ```rust
+//! # #[cfg(feature = "lz4")]
+//! # {
+//! use zed::{Compression, MeshLink, MeshMessage, StateNode, Transport};
+//! # use std::collections::VecDeque;
+//! # use std::sync::{Arc, Mutex};
+//! # #[derive(Default)]
+//! # struct Wire(Arc<Mutex<VecDeque<MeshMessage>>>, Arc<Mutex<VecDeque<MeshMessage>>>);
+//! # impl Transport for Wire {
+//! #     fn send(&mut self, m: &MeshMessage) -> std::io::Result<()> { self.0.lock().unwrap().push_back(m.clone()); Ok(()) }
+//! #     fn receive(&mut self) -> std::io::Result<Option<MeshMessage>> { Ok(self.1.lock().unwrap().pop_front()) }
+//! # }
+//! # let a = Wire::default();
+//! # let b = Wire(Arc::clone(&a.1), Arc::clone(&a.0));
+//!
+//! let mut client = MeshLink::new(a).compression(Compression::Lz4);
+//! let mut server = MeshLink::new(b).compression(Compression::Lz4);
+//!
+//! let mut local = StateNode::new("client".to_string(), "lorem ipsum ".repeat(100));
+//! let mut remote = StateNode::new("server".to_string(), String::new());
+//!
+//! // The first exchange negotiates; later states travel compressed
+//! client.send_state(&local).unwrap();
+//! server.receive(&mut remote).unwrap();
+//! client.receive(&mut local).unwrap();
+//! assert_eq!(client.negotiated_compression(), Some(Compression::Lz4));
+//! # }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::io;
+
+/// A compression algorithm for the messages of a mesh link.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Compression {
+    /// LZ4, requires the `lz4` feature
+    Lz4,
+    /// Zstandard, requires the `zstd` feature
+    Zstd,
+}
+
+impl Compression {
+    /// Returns the algorithms enabled by the crate features.
+    pub fn available() -> Vec<Compression> {
+        [Compression::Lz4, Compression::Zstd]
+            .into_iter()
+            .filter(|compression| compression.is_available())
+            .collect()
+    }
+
+    /// Returns `true` if the algorithm's feature is enabled.
+    pub fn is_available(self) -> bool {
+        match self {
+            Compression::Lz4 => cfg!(feature = "lz4"),
+            Compression::Zstd => cfg!(feature = "zstd"),
+        }
+    }
+
+    /// Compresses `data` and encodes it as base64, to fit in a JSON message.
+    #[cfg(any(feature = "lz4", feature = "zstd"))]
+    pub(super) fn compress(self, data: &[u8]) -> io::Result<String> {
+        use base64::Engine;
+
+        let compressed = match self {
+            #[cfg(feature = "lz4")]
+            Compression::Lz4 => lz4_flex::compress_prepend_size(data),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => zstd::bulk::compress(data, zstd::DEFAULT_COMPRESSION_LEVEL)?,
+            #[allow(unreachable_patterns)]
+            _ => return Err(self.unavailable()),
+        };
+        Ok(base64::engine::general_purpose::STANDARD.encode(compressed))
+    }
+
+    /// Compresses `data` and encodes it as base64, to fit in a JSON message.
+    #[cfg(not(any(feature = "lz4", feature = "zstd")))]
+    pub(super) fn compress(self, _data: &[u8]) -> io::Result<String> {
+        Err(self.unavailable())
+    }
+
+    /// Decodes and decompresses a payload made by `compress()`.
+    #[cfg(any(feature = "lz4", feature = "zstd"))]
+    pub(super) fn decompress(self, payload: &str) -> io::Result<Vec<u8>> {
+        use base64::Engine;
+
+        let compressed = base64::engine::general_purpose::STANDARD
+            .decode(payload)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        match self {
+            #[cfg(feature = "lz4")]
+            Compression::Lz4 => lz4_flex::decompress_size_prepended(&compressed)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err)),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => zstd::stream::decode_all(compressed.as_slice()),
+            #[allow(unreachable_patterns)]
+            _ => Err(self.unavailable()),
+        }
+    }
+
+    /// Decodes and decompresses a payload made by `compress()`.
+    #[cfg(not(any(feature = "lz4", feature = "zstd")))]
+    pub(super) fn decompress(self, _payload: &str) -> io::Result<Vec<u8>> {
+        Err(self.unavailable())
+    }
+
+    fn unavailable(self) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("{self:?} compression is not enabled"),
+        )
+    }
+}
@@ -0,0 +1,211 @@
+//! # Discovery Module
+//!
+//! Finds peers on the local network with mDNS, so nodes of a LAN session
+//! can connect without a hard-coded list of peers. Requires the
+//! `discovery` feature.
+//!
+//! A [`Discovery`] announces a node and the port it accepts
+//! [`TcpTransport`] connections on, and browses for other nodes doing the
+//! same. [`Discovery::connect_new_peers`] then dials the peers found since
+//! the last call. Only the node with the lower ID of a pair dials, so
+//! every pair ends up with exactly one connection; the other node accepts
+//! it from its `TcpListener`.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use serde::{Deserialize, Serialize};
+//! use std::net::TcpListener;
+//! use zed::{Discovery, MeshLink, StateNode, TcpTransport};
+//!
+//! #[derive(Clone, Serialize, Deserialize)]
+//! struct Document { text: String }
+//!
+//! let mut node = StateNode::new("laptop".to_string(), Document { text: String::new() });
+//! let listener = TcpListener::bind("0.0.0.0:0").unwrap();
+//! listener.set_nonblocking(true).unwrap();
+//! let mut discovery = Discovery::start(&node.id, listener.local_addr().unwrap().port()).unwrap();
+//!
+//! let mut links = Vec::new();
+//! loop {
+//!     for (_, link) in discovery.connect_new_peers() {
+//!         links.extend(link.ok());
+//!     }
+//!     while let Ok((stream, _)) = listener.accept() {
+//!         links.push(MeshLink::new(TcpTransport::from_stream(stream).unwrap()));
+//!     }
+//!     for link in &mut links {
+//!         link.send_state(&node).unwrap();
+//!         link.receive(&mut node).unwrap();
+//!     }
+//! }
+//! ```
+
+use super::NodeId;
+use super::transport::{MeshLink, TcpTransport};
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use std::collections::HashMap;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+
+/// The mDNS service type nodes are announced as.
+pub const SERVICE_TYPE: &str = "_zed-mesh._tcp.local.";
+
+/// A node found on the local network.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiscoveredPeer {
+    /// ID of the node
+    pub id: NodeId,
+    /// Address the node accepts connections on
+    pub addr: SocketAddr,
+}
+
+impl DiscoveredPeer {
+    /// Creates a peer from the addresses it announced, choosing the one to
+    /// dial: IPv4 first, as it works on more networks. Link-local IPv6
+    /// addresses come without their interface, so they cannot be dialed.
+    ///
+    /// # Returns
+    ///
+    /// `None` if no address can be dialed.
+    pub fn from_addresses<I>(id: NodeId, addresses: I, port: u16) -> Option<Self>
+    where
+        I: IntoIterator<Item = IpAddr>,
+    {
+        let ip = addresses
+            .into_iter()
+            .filter(|ip| !matches!(ip, IpAddr::V6(v6) if v6.is_unicast_link_local()))
+            .min_by_key(|ip| matches!(ip, IpAddr::V6(_)))?;
+        Some(Self {
+            id,
+            addr: SocketAddr::new(ip, port),
+        })
+    }
+}
+
+/// Announces a node on the local network and finds its peers.
+///
+/// The announcement is withdrawn when the `Discovery` is dropped.
+pub struct Discovery {
+    daemon: ServiceDaemon,
+    events: mdns_sd::Receiver<ServiceEvent>,
+    id: NodeId,
+    fullname: String,
+    /// Peers found so far, by their mDNS instance name
+    peers: HashMap<String, DiscoveredPeer>,
+}
+
+impl Discovery {
+    /// Announces a node accepting connections on `port` and starts looking
+    /// for peers.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the mDNS daemon cannot be started, e.g. because
+    /// no network interface supports multicast, or the ID cannot be used as
+    /// an mDNS instance name.
+    pub fn start(id: &NodeId, port: u16) -> io::Result<Self> {
+        let daemon = ServiceDaemon::new().map_err(mdns_error)?;
+        // Host names may only hold letters, digits and hyphens
+        let host: String = id
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        let service = ServiceInfo::new(
+            SERVICE_TYPE,
+            id,
+            &format!("zed-{host}.local."),
+            "",
+            port,
+            &[("id", id.as_str())][..],
+        )
+        .map_err(mdns_error)?
+        .enable_addr_auto();
+        let fullname = service.get_fullname().to_string();
+        daemon.register(service).map_err(mdns_error)?;
+        let events = daemon.browse(SERVICE_TYPE).map_err(mdns_error)?;
+        Ok(Self {
+            daemon,
+            events,
+            id: id.clone(),
+            fullname,
+            peers: HashMap::new(),
+        })
+    }
+
+    /// Processes the announcements received so far.
+    ///
+    /// # Returns
+    ///
+    /// The peers found since the last call.
+    pub fn poll(&mut self) -> Vec<DiscoveredPeer> {
+        let mut found = Vec::new();
+        while let Ok(event) = self.events.try_recv() {
+            match event {
+                ServiceEvent::ServiceResolved(info) => {
+                    let Some(id) = info.get_property_val_str("id") else {
+                        continue;
+                    };
+                    if id == self.id {
+                        continue;
+                    }
+                    let addresses = info.get_addresses().iter().copied();
+                    let Some(peer) =
+                        DiscoveredPeer::from_addresses(id.to_string(), addresses, info.get_port())
+                    else {
+                        continue;
+                    };
+                    // Later announcements only update the address
+                    if self
+                        .peers
+                        .insert(info.get_fullname().to_string(), peer.clone())
+                        .is_none()
+                    {
+                        found.push(peer);
+                    }
+                }
+                ServiceEvent::ServiceRemoved(_, fullname) => {
+                    self.peers.remove(&fullname);
+                }
+                _ => {}
+            }
+        }
+        found
+    }
+
+    /// Returns the peers currently announced on the network.
+    pub fn peers(&self) -> Vec<DiscoveredPeer> {
+        self.peers.values().cloned().collect()
+    }
+
+    /// Connects to the peers found since the last call, unless they are the
+    /// ones to dial this node.
+    ///
+    /// Finds peers with `poll()`, so peers already returned by `poll()` are
+    /// not dialed.
+    ///
+    /// # Returns
+    ///
+    /// A link, or the error raised while connecting, for every peer dialed.
+    pub fn connect_new_peers(&mut self) -> Vec<(NodeId, io::Result<MeshLink<TcpTransport>>)> {
+        self.poll()
+            .into_iter()
+            .filter(|peer| self.id < peer.id)
+            .map(|peer| {
+                let link = TcpTransport::connect(peer.addr).map(MeshLink::new);
+                (peer.id, link)
+            })
+            .collect()
+    }
+}
+
+impl Drop for Discovery {
+    fn drop(&mut self) {
+        let _ = self.daemon.unregister(&self.fullname);
+        let _ = self.daemon.shutdown();
+    }
+}
+
+fn mdns_error(err: mdns_sd::Error) -> io::Error {
+    io::Error::other(err.to_string())
+}
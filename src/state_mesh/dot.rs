@@ -0,0 +1,125 @@
+//! # DOT Module
+//!
+//! Describes a mesh in the Graphviz DOT language, to visualize its topology
+//! and debug how updates travel through it.
+//!
+//! [`export_dot`] draws every node with its update sequence, authority and
+//! the latest update it applied from each origin, and every connection
+//! between them:
+//!
+//! - Connections made both ways are drawn as one edge with two arrows
+//! - Connections marked offline are dashed and show their queued updates
+//! - Connected nodes outside the exported set are dashed
+//!
+//! The output can be rendered with `dot -Tsvg mesh.dot -o mesh.svg`.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use zed::{StateNode, export_dot};
+//!
+//! let alice = StateNode::new("alice".to_string(), 0).into_shared();
+//! let bob = StateNode::new("bob".to_string(), 0).into_shared();
+//! StateNode::connect_bidirectional(&alice, &bob);
+//! alice.lock().unwrap().propagate_with_ttl(4);
+//!
+//! let dot = export_dot(&[alice, bob]);
+//! assert!(dot.starts_with("digraph mesh {"));
+//! assert!(dot.contains("\"alice\" -> \"bob\" [dir=both];"));
+//! ```
+
+use super::{NodeId, SharedStateNode, lock};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+/// A connection as seen by the node it starts from.
+struct Edge {
+    online: bool,
+    pending: usize,
+}
+
+/// Returns a Graphviz DOT description of a set of nodes and their
+/// connections.
+///
+/// Nodes are labelled with their ID, the sequence number of the last update
+/// started there with `propagate_with_ttl()`, their authority if not 0, and
+/// the highest sequence number applied from each other origin. Nodes are locked one at a time, so the description
+/// reflects each node at the moment it was read.
+///
+/// The output only depends on the nodes, not on the order of their
+/// connections, so exports of the same mesh can be diffed.
+pub fn export_dot<T: Clone>(nodes: &[SharedStateNode<T>]) -> String {
+    let mut labels = BTreeMap::new();
+    let mut edges = BTreeMap::new();
+    for node in nodes {
+        let node = lock(node);
+        let mut label = format!("{}\\nsequence {}", escape(&node.id), node.sequence);
+        if node.authority != 0 {
+            let _ = write!(label, "\\nauthority {}", node.authority);
+        }
+        let seen: BTreeMap<&NodeId, &u64> = node
+            .seen
+            .iter()
+            .filter(|(origin, _)| **origin != node.id)
+            .collect();
+        for (origin, sequence) in seen {
+            let _ = write!(label, "\\nseen {}: {}", escape(origin), sequence);
+        }
+        labels.insert(node.id.clone(), label);
+        for peer in node.connections.keys() {
+            let edge = Edge {
+                online: node.is_online(peer),
+                pending: node.pending_updates(peer),
+            };
+            edges.insert((node.id.clone(), peer.clone()), edge);
+        }
+    }
+
+    let mut dot = String::from("digraph mesh {\n");
+    for (id, label) in &labels {
+        let _ = writeln!(dot, "    \"{}\" [label=\"{label}\"];", escape(id));
+    }
+    let outside: BTreeSet<&NodeId> = edges
+        .keys()
+        .map(|(_, to)| to)
+        .filter(|to| !labels.contains_key(*to))
+        .collect();
+    for id in outside {
+        let _ = writeln!(dot, "    \"{}\" [style=dashed];", escape(id));
+    }
+
+    let both_online = |from: &NodeId, to: &NodeId| {
+        let forward = edges.get(&(from.clone(), to.clone()));
+        let back = edges.get(&(to.clone(), from.clone()));
+        matches!((forward, back), (Some(forward), Some(back)) if forward.online && back.online)
+    };
+    for ((from, to), edge) in &edges {
+        let attributes = if both_online(from, to) {
+            if from > to {
+                // Drawn with the opposite direction
+                continue;
+            }
+            " [dir=both]".to_string()
+        } else if edge.online {
+            String::new()
+        } else {
+            format!(
+                " [style=dashed, label=\"offline, {} pending\"]",
+                edge.pending
+            )
+        };
+        let _ = writeln!(
+            dot,
+            "    \"{}\" -> \"{}\"{attributes};",
+            escape(from),
+            escape(to)
+        );
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// Escapes a string for a quoted DOT ID or label.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -0,0 +1,172 @@
+//! # Field Merge Module
+//!
+//! Builds conflict resolvers from a merge strategy per field, instead of
+//! hand-writing a function that merges a struct field by field.
+//!
+//! A [`FieldMerge`] lists the fields of a state with the strategy used for
+//! each one:
+//!
+//! - `lww` - the value with the latest timestamp wins
+//! - `max` - the greater value wins
+//! - `union` - items of both collections are kept
+//! - `custom` - a function merges the two values
+//!
+//! Fields are given as accessors returning a mutable reference into the
+//! state. Fields without a strategy keep their local value.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use zed::{FieldMerge, StateNode};
+//!
+//! #[derive(Clone, Debug, PartialEq)]
+//! struct Task {
+//!     title: String,
+//!     edited_at: u64,
+//!     votes: u32,
+//!     tags: Vec<String>,
+//! }
+//!
+//! let resolver = FieldMerge::new()
+//!     .lww(|task: &mut Task| &mut task.title, |task: &Task| task.edited_at)
+//!     .max(|task: &mut Task| &mut task.votes)
+//!     .union(|task: &mut Task| &mut task.tags)
+//!     .max(|task: &mut Task| &mut task.edited_at)
+//!     .build();
+//!
+//! let mut node = StateNode::new("local".to_string(), Task {
+//!     title: "Draft".to_string(),
+//!     edited_at: 1,
+//!     votes: 5,
+//!     tags: vec!["docs".to_string()],
+//! });
+//! node.set_conflict_resolver(resolver);
+//!
+//! node.resolve_conflict(Task {
+//!     title: "Final".to_string(),
+//!     edited_at: 2,
+//!     votes: 3,
+//!     tags: vec!["release".to_string()],
+//! });
+//! assert_eq!(node.state.title, "Final");
+//! assert_eq!(node.state.votes, 5);
+//! assert_eq!(node.state.tags, vec!["docs", "release"]);
+//! ```
+
+/// Merges one field of the local state with the remote state.
+type FieldStrategy<T> = Box<dyn Fn(&mut T, &mut T) + Send + Sync>;
+
+/// Builder of a conflict resolver merging a state field by field.
+///
+/// Strategies run in the order they were added, each on the local state as
+/// left by the previous ones. A field whose value is also used as an `lww`
+/// timestamp should be merged after the fields it timestamps.
+pub struct FieldMerge<T> {
+    fields: Vec<FieldStrategy<T>>,
+}
+
+impl<T> FieldMerge<T> {
+    /// Creates a builder without strategies, whose resolver keeps the local
+    /// state.
+    pub fn new() -> Self {
+        Self { fields: Vec::new() }
+    }
+
+    /// Merges a field by last-write-wins: the remote value replaces the
+    /// local one if the remote state has a later timestamp.
+    ///
+    /// # Arguments
+    ///
+    /// * `field` - Returns the field inside a state
+    /// * `timestamp` - Returns the time a state was last written, e.g. a
+    ///   version or clock field
+    pub fn lww<V, K, A, S>(self, field: A, timestamp: S) -> Self
+    where
+        V: Clone,
+        K: Ord,
+        A: Fn(&mut T) -> &mut V + Send + Sync + 'static,
+        S: Fn(&T) -> K + Send + Sync + 'static,
+    {
+        self.field(move |local, remote| {
+            if timestamp(remote) > timestamp(local) {
+                *field(local) = field(remote).clone();
+            }
+        })
+    }
+
+    /// Merges a field by keeping the greater of both values.
+    pub fn max<V, A>(self, field: A) -> Self
+    where
+        V: Ord + Clone,
+        A: Fn(&mut T) -> &mut V + Send + Sync + 'static,
+    {
+        self.field(move |local, remote| {
+            let remote = field(remote);
+            let local = field(local);
+            if *remote > *local {
+                *local = remote.clone();
+            }
+        })
+    }
+
+    /// Merges a collection field by adding the remote items missing
+    /// locally, after the local ones.
+    pub fn union<C, I, A>(self, field: A) -> Self
+    where
+        C: Extend<I>,
+        for<'a> &'a C: IntoIterator<Item = &'a I>,
+        I: PartialEq + Clone,
+        A: Fn(&mut T) -> &mut C + Send + Sync + 'static,
+    {
+        self.field(move |local, remote| {
+            let remote = field(remote);
+            let local = field(local);
+            let missing: Vec<I> = remote
+                .into_iter()
+                .filter(|item| !local.into_iter().any(|own| own == *item))
+                .cloned()
+                .collect();
+            local.extend(missing);
+        })
+    }
+
+    /// Merges a field with a function receiving the local value to update
+    /// and the remote value.
+    pub fn custom<V, A, F>(self, field: A, merge: F) -> Self
+    where
+        A: Fn(&mut T) -> &mut V + Send + Sync + 'static,
+        F: Fn(&mut V, &V) + Send + Sync + 'static,
+    {
+        self.field(move |local, remote| merge(field(local), field(remote)))
+    }
+
+    /// Returns a conflict resolver applying the strategies, to pass to
+    /// `StateNode::set_conflict_resolver()`.
+    pub fn build(self) -> impl Fn(&mut T, &T) + Send + Sync + 'static
+    where
+        T: Clone + 'static,
+    {
+        let fields = self.fields;
+        move |local: &mut T, remote: &T| {
+            // Accessors need a mutable state, so they read from a copy
+            let mut remote = remote.clone();
+            for field in &fields {
+                field(local, &mut remote);
+            }
+        }
+    }
+
+    fn field<F>(mut self, strategy: F) -> Self
+    where
+        F: Fn(&mut T, &mut T) + Send + Sync + 'static,
+    {
+        self.fields.push(Box::new(strategy));
+        self
+    }
+}
+
+impl<T> Default for FieldMerge<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
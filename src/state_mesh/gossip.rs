@@ -0,0 +1,206 @@
+//! # Gossip Module
+//!
+//! Eventually consistent synchronization for large meshes, where pushing
+//! every change to every connected node would flood the mesh.
+//!
+//! In each [`Gossip::round`], every member node picks a few of its
+//! connections at random and compares a digest of its state with theirs.
+//! Only when the digests differ does the member pull the peer's state
+//! through its conflict resolver, then push the resolved state back. Changes spread to the whole mesh in a
+//! number of rounds that grows with the logarithm of its size, while each
+//! round costs at most `fanout` digest exchanges per member.
+//!
+//! Nodes converge if their conflict resolvers do, i.e. if merging states
+//! in any order and any number of times leads to the same result, as with
+//! last-write-wins by version.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use serde::Serialize;
+//! use zed::{Gossip, StateNode};
+//!
+//! #[derive(Clone, Serialize)]
+//! struct Config { version: u32, motd: String }
+//!
+//! let nodes: Vec<_> = (0..8)
+//!     .map(|i| {
+//!         let mut node = StateNode::new(format!("node{i}"), Config { version: 0, motd: String::new() });
+//!         node.set_conflict_resolver(|current: &mut Config, remote: &Config| {
+//!             if remote.version > current.version {
+//!                 *current = remote.clone();
+//!             }
+//!         });
+//!         node.into_shared()
+//!     })
+//!     .collect();
+//!
+//! // A ring: every node only knows its two neighbours
+//! let mut gossip = Gossip::new(1);
+//! for (i, node) in nodes.iter().enumerate() {
+//!     StateNode::connect_bidirectional(node, &nodes[(i + 1) % nodes.len()]);
+//!     gossip.add_node(node);
+//! }
+//!
+//! nodes[0].lock().unwrap().state = Config { version: 1, motd: "Welcome".into() };
+//! while !gossip.is_converged() {
+//!     gossip.round();
+//! }
+//! assert!(nodes.iter().all(|node| node.lock().unwrap().state.motd == "Welcome"));
+//! ```
+
+use super::{NodeId, SharedStateNode, lock};
+use crate::persist::fnv1a;
+use serde::Serialize;
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::sync::Arc;
+
+/// What one gossip round did.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GossipRound {
+    /// Number of digests compared between a member and one of its peers
+    pub digests_exchanged: usize,
+    /// Number of states transferred because the digests differed: one
+    /// pulled by the member, one pushed back to the peer
+    pub states_pulled: usize,
+}
+
+/// Gossip-based synchronization over the connections of a set of nodes.
+pub struct Gossip<T: Clone> {
+    members: Vec<SharedStateNode<T>>,
+    fanout: usize,
+    rng: XorShift,
+}
+
+impl<T: Clone + Serialize> Gossip<T> {
+    /// Creates a gossip group in which every member contacts `fanout` of its
+    /// connections per round (at least one).
+    pub fn new(fanout: usize) -> Self {
+        Self {
+            members: Vec::new(),
+            fanout: fanout.max(1),
+            rng: XorShift::new(),
+        }
+    }
+
+    /// Changes how many connections every member contacts per round (at
+    /// least one).
+    pub fn set_fanout(&mut self, fanout: usize) {
+        self.fanout = fanout.max(1);
+    }
+
+    /// Adds a node that takes part in every round.
+    ///
+    /// The node gossips with the nodes it is connected to; connected nodes
+    /// that are not members are still updated when contacted.
+    pub fn add_node(&mut self, node: &SharedStateNode<T>) {
+        if !self.members.iter().any(|member| Arc::ptr_eq(member, node)) {
+            self.members.push(Arc::clone(node));
+        }
+    }
+
+    /// Removes a member node.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the node was a member.
+    pub fn remove_node(&mut self, node: &SharedStateNode<T>) -> bool {
+        let len = self.members.len();
+        self.members.retain(|member| !Arc::ptr_eq(member, node));
+        self.members.len() < len
+    }
+
+    /// Runs one round in which every member exchanges digests with up to
+    /// `fanout` randomly chosen connections.
+    ///
+    /// At most one node is locked at a time, so rounds may run while other
+    /// threads use the nodes.
+    pub fn round(&mut self) -> GossipRound {
+        let mut round = GossipRound::default();
+        for member in self.members.clone() {
+            let peers: Vec<SharedStateNode<T>> =
+                lock(&member).connections.values().cloned().collect();
+            for index in self.choose(peers.len()) {
+                let peer = &peers[index];
+                round.digests_exchanged += 1;
+                let ours = snapshot(&member);
+                let theirs = snapshot(peer);
+                if ours.digest != theirs.digest {
+                    // Pushing the state from before the pull would undo it
+                    // at the peer: with resolvers accepting remote states,
+                    // the two nodes would swap states instead of converging
+                    lock(&member).receive_state(&theirs.id, theirs.state, theirs.authority);
+                    let resolved = snapshot(&member);
+                    lock(peer).receive_state(&resolved.id, resolved.state, resolved.authority);
+                    round.states_pulled += 2;
+                }
+            }
+        }
+        round
+    }
+
+    /// Returns `true` if all members hold states with the same digest.
+    pub fn is_converged(&self) -> bool {
+        let mut digests = self.members.iter().map(|member| snapshot(member).digest);
+        match digests.next() {
+            Some(first) => digests.all(|digest| digest == first),
+            None => true,
+        }
+    }
+
+    /// Picks up to `fanout` distinct indices below `len`.
+    fn choose(&mut self, len: usize) -> Vec<usize> {
+        let mut indices: Vec<usize> = (0..len).collect();
+        let count = self.fanout.min(len);
+        // Partial Fisher-Yates shuffle
+        for slot in 0..count {
+            let pick = slot + (self.rng.next_u64() % (len - slot) as u64) as usize;
+            indices.swap(slot, pick);
+        }
+        indices.truncate(count);
+        indices
+    }
+}
+
+/// A xorshift generator, randomly seeded; good enough to pick peers and
+/// delays, not for anything secret.
+pub(super) struct XorShift(u64);
+
+impl XorShift {
+    pub(super) fn new() -> Self {
+        // Never zero, which xorshift could not leave
+        Self(RandomState::new().hash_one("zed-gossip") | 1)
+    }
+
+    pub(super) fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+/// A node's state as read by a round.
+struct Snapshot<T> {
+    id: NodeId,
+    state: T,
+    /// Hash of the state's JSON serialization
+    digest: u64,
+    authority: u32,
+}
+
+/// Clones a node's ID, state and authority, and computes its digest.
+fn snapshot<T: Clone + Serialize>(node: &SharedStateNode<T>) -> Snapshot<T> {
+    let (id, state, authority) = {
+        let node = lock(node);
+        (node.id.clone(), node.state.clone(), node.authority())
+    };
+    let digest = fnv1a(&serde_json::to_vec(&state).unwrap_or_default());
+    Snapshot {
+        id,
+        state,
+        digest,
+        authority,
+    }
+}
@@ -0,0 +1,285 @@
+//! # Lease Module
+//!
+//! Cooperative locking of state regions, e.g. "one editor per paragraph".
+//!
+//! A region is a named part of a state. A node tells which regions differ
+//! between two states with `set_regions()`, then acquires a time-limited
+//! [`Lease`] on a region with `acquire_lease()`. The lease is granted by
+//! the node and its connections, and while it holds, states from other
+//! nodes that change the region are not applied there:
+//!
+//! - [`LeasePolicy::Reject`] drops them (the default)
+//! - [`LeasePolicy::Defer`] keeps the latest one per sender, and applies it
+//!   once the lease is released or expires
+//!
+//! Leases are checked for states received from other nodes, by propagation,
+//! gossip, `merge()` and mesh links. `resolve_conflict()` applies a state
+//! from an unknown sender, so it ignores leases. Updates are whole states:
+//! a state that changes a leased region is held back entirely, including
+//! its changes to other regions.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use zed::StateNode;
+//!
+//! fn changed_paragraphs(a: &Vec<String>, b: &Vec<String>) -> Vec<String> {
+//!     (0..a.len().max(b.len()))
+//!         .filter(|&i| a.get(i) != b.get(i))
+//!         .map(|i| format!("paragraph {i}"))
+//!         .collect()
+//! }
+//!
+//! let doc = vec!["Intro".to_string(), "Body".to_string()];
+//! let alice = StateNode::new("alice".to_string(), doc.clone()).into_shared();
+//! let bob = StateNode::new("bob".to_string(), doc).into_shared();
+//! StateNode::connect_bidirectional(&alice, &bob);
+//! for node in [&alice, &bob] {
+//!     node.lock().unwrap().set_regions(changed_paragraphs);
+//! }
+//!
+//! // Alice edits the intro; Bob may not, while her lease holds
+//! alice.lock().unwrap().acquire_lease("paragraph 0", Duration::from_secs(30)).unwrap();
+//! assert!(bob.lock().unwrap().acquire_lease("paragraph 0", Duration::from_secs(30)).is_err());
+//!
+//! bob.lock().unwrap().state[0] = "Bob's intro".to_string();
+//! bob.lock().unwrap().propagate_update();
+//! assert_eq!(alice.lock().unwrap().state[0], "Intro");
+//! ```
+
+use super::{ConflictOutcome, NodeId, StateNode, lock};
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Names the regions that differ between two states
+pub(super) type RegionDiff<T> = Arc<dyn Fn(&T, &T) -> Vec<String> + Send + Sync>;
+
+/// A time-limited lease on a region of state.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Lease {
+    /// ID of the node holding the lease
+    pub holder: NodeId,
+    /// When the lease ends unless renewed
+    pub expires_at: Instant,
+}
+
+/// What a node does with a state changing a region leased by another node.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LeasePolicy {
+    /// The state is dropped
+    #[default]
+    Reject,
+    /// The latest state per sender is applied once the lease ends
+    Defer,
+}
+
+/// Error returned by `StateNode::acquire_lease()` when another node holds
+/// the region.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LeaseError {
+    /// The region that could not be leased
+    pub region: String,
+    /// ID of the node holding it
+    pub holder: NodeId,
+}
+
+impl fmt::Display for LeaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "region {:?} is leased by {}", self.region, self.holder)
+    }
+}
+
+impl Error for LeaseError {}
+
+impl<T: Clone> StateNode<T> {
+    /// Sets how this node tells which regions differ between two states,
+    /// to check incoming states against leases.
+    ///
+    /// Without it, leases are still granted but no state is held back.
+    ///
+    /// # Arguments
+    ///
+    /// * `regions` - Returns the names of the regions that differ between
+    ///   the local and a remote state
+    pub fn set_regions<F>(&mut self, regions: F)
+    where
+        F: Fn(&T, &T) -> Vec<String> + Send + Sync + 'static,
+    {
+        self.regions = Some(Arc::new(regions));
+    }
+
+    /// Sets what this node does with states changing a region leased by
+    /// another node. Nodes start with `LeasePolicy::Reject`.
+    pub fn set_lease_policy(&mut self, policy: LeasePolicy) {
+        self.lease_policy = policy;
+    }
+
+    /// Acquires a lease on a region for `duration`, or renews the lease
+    /// this node holds.
+    ///
+    /// The lease is granted by this node and by every connection that is
+    /// not marked offline; if one of them knows of a lease by another node,
+    /// the leases granted so far are withdrawn. Each connection is locked
+    /// in turn, with the same deadlock caveat as `propagate_update()`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LeaseError`] naming the holder if another node holds the
+    /// region.
+    pub fn acquire_lease(&mut self, region: &str, duration: Duration) -> Result<(), LeaseError> {
+        let lease = Lease {
+            holder: self.id.clone(),
+            expires_at: Instant::now() + duration,
+        };
+        let previous = self.grant_lease(region, &lease)?;
+        let mut granted = Vec::new();
+        let mut refused = None;
+        for (id, peer) in &self.connections {
+            if !self.is_online(id) {
+                continue;
+            }
+            match lock(peer).grant_lease(region, &lease) {
+                Ok(previous) => granted.push((peer, previous)),
+                Err(err) => {
+                    refused = Some(err);
+                    break;
+                }
+            }
+        }
+        let Some(err) = refused else {
+            return Ok(());
+        };
+        for (peer, previous) in granted {
+            lock(peer).restore_lease(region, previous);
+        }
+        self.restore_lease(region, previous);
+        Err(err)
+    }
+
+    /// Releases a lease this node holds, here and at its connections, and
+    /// applies the states deferred because of it.
+    ///
+    /// # Returns
+    ///
+    /// `false` if this node did not hold the region.
+    pub fn release_lease(&mut self, region: &str) -> bool {
+        if self
+            .lease(region)
+            .is_none_or(|lease| lease.holder != self.id)
+        {
+            return false;
+        }
+        let id = self.id.clone();
+        for peer in self.connections.values() {
+            lock(peer).revoke_lease(region, &id);
+        }
+        self.revoke_lease(region, &id);
+        true
+    }
+
+    /// Returns the lease on a region, unless it has expired.
+    pub fn lease(&self, region: &str) -> Option<&Lease> {
+        self.leases
+            .get(region)
+            .filter(|lease| lease.expires_at > Instant::now())
+    }
+
+    /// Forgets the expired leases, and applies the states deferred because
+    /// of them.
+    ///
+    /// Expired leases are also forgotten whenever this node receives a
+    /// state or grants a lease, so this is only needed to apply deferred
+    /// states without waiting for the next one.
+    ///
+    /// # Returns
+    ///
+    /// The number of leases that expired.
+    pub fn expire_leases(&mut self) -> usize {
+        let now = Instant::now();
+        let len = self.leases.len();
+        self.leases.retain(|_, lease| lease.expires_at > now);
+        let expired = len - self.leases.len();
+        if expired > 0 {
+            self.apply_deferred();
+        }
+        expired
+    }
+
+    /// Returns the number of states waiting for leases to end.
+    pub fn deferred_updates(&self) -> usize {
+        self.deferred.len()
+    }
+
+    /// Resolves a state sent by another node, unless it changes a region
+    /// leased by a third node.
+    pub(super) fn receive_state(
+        &mut self,
+        from: &NodeId,
+        state: T,
+        authority: u32,
+    ) -> ConflictOutcome {
+        self.expire_leases();
+        if !self.is_leased_against(from, &state) {
+            return self.resolve_conflict_from(state, authority);
+        }
+        if self.lease_policy == LeasePolicy::Defer {
+            // A later state from the same sender replaces the earlier one
+            self.deferred.retain(|(sender, _, _)| sender != from);
+            self.deferred.push((from.clone(), state, authority));
+        }
+        ConflictOutcome::KeptLocal
+    }
+
+    fn is_leased_against(&self, from: &NodeId, state: &T) -> bool {
+        let Some(regions) = self.regions.as_ref().filter(|_| !self.leases.is_empty()) else {
+            return false;
+        };
+        regions(&self.state, state).iter().any(|region| {
+            self.leases
+                .get(region)
+                .is_some_and(|lease| lease.holder != *from)
+        })
+    }
+
+    fn grant_lease(&mut self, region: &str, lease: &Lease) -> Result<Option<Lease>, LeaseError> {
+        self.expire_leases();
+        if let Some(current) = self.leases.get(region)
+            && current.holder != lease.holder
+        {
+            return Err(LeaseError {
+                region: region.to_string(),
+                holder: current.holder.clone(),
+            });
+        }
+        Ok(self.leases.insert(region.to_string(), lease.clone()))
+    }
+
+    fn restore_lease(&mut self, region: &str, previous: Option<Lease>) {
+        match previous {
+            Some(lease) => self.leases.insert(region.to_string(), lease),
+            None => self.leases.remove(region),
+        };
+    }
+
+    fn revoke_lease(&mut self, region: &str, holder: &NodeId) {
+        if self
+            .leases
+            .get(region)
+            .is_some_and(|lease| lease.holder == *holder)
+        {
+            self.leases.remove(region);
+            self.apply_deferred();
+        }
+    }
+
+    /// Resolves the deferred states in the order they arrived; those still
+    /// held back by a lease are deferred again.
+    fn apply_deferred(&mut self) {
+        for (from, state, authority) in std::mem::take(&mut self.deferred) {
+            self.receive_state(&from, state, authority);
+        }
+    }
+}
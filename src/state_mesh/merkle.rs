@@ -0,0 +1,528 @@
+//! # Merkle Module
+//!
+//! Finds which keys of two large keyed states differ without comparing
+//! every entry, so nodes can reconcile by exchanging only those entries.
+//!
+//! A [`MerkleTree`] hashes every entry of a map into one of 4096 buckets,
+//! chosen by the key, and builds a tree of digests over the buckets.
+//! [`MerkleTree::diff`] compares two trees top-down and only descends into
+//! subtrees whose digests differ, so a handful of changed keys in a map of
+//! millions costs a few dozen digest comparisons.
+//!
+//! Trees update in place as entries change, and serialize for sending.
+//! Every node keeps the tree of its state between syncs, see
+//! [`StateNode::merkle_tree`]. [`StateNode::sync_from`] uses both nodes'
+//! trees to pull the entries an in-process peer changed and resolve them
+//! like a whole propagated state; [`MeshLink::request_sync`] does the same
+//! with a remote peer, exchanging one level of digests per round trip.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use std::collections::BTreeMap;
+//! use zed::MerkleTree;
+//!
+//! let mut ours: BTreeMap<u32, String> = (0..10_000).map(|i| (i, format!("row {i}"))).collect();
+//! let mut theirs = ours.clone();
+//! theirs.insert(42, "changed".to_string());
+//! theirs.remove(&7);
+//! ours.insert(20_000, "new".to_string());
+//!
+//! let diff = MerkleTree::new(&ours).diff(&MerkleTree::new(&theirs));
+//! assert_eq!(diff, vec![7, 42, 20_000]);
+//! ```
+
+#[cfg(doc)]
+use super::transport::MeshLink;
+use super::transport::MeshMessage;
+use super::{SharedStateNode, StateNode, lock};
+use crate::persist::fnv1a;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::io;
+
+/// Children per inner node of the tree.
+const FANOUT: usize = 16;
+/// Levels below the root; the tree has `FANOUT^DEPTH` buckets.
+const DEPTH: usize = 3;
+/// Bits of a key hash selecting its bucket.
+const BUCKET_BITS: u32 = FANOUT.ilog2() * DEPTH as u32;
+
+/// Digests of the entries of a keyed state, for finding differing keys.
+///
+/// Serializes as the keys and entry hashes of its buckets; the digests
+/// above them are rebuilt when it is deserialized.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(
+    into = "Buckets<K>",
+    try_from = "Buckets<K>",
+    bound(
+        serialize = "K: Clone + Serialize",
+        deserialize = "K: Ord + Deserialize<'de>"
+    )
+)]
+pub struct MerkleTree<K> {
+    /// Digests per level, from the root down to the buckets
+    levels: Vec<Vec<u64>>,
+    /// Keys and entry hashes per bucket, sorted by key
+    buckets: Vec<Vec<(K, u64)>>,
+}
+
+/// Serialized form of a [`MerkleTree`].
+#[derive(Serialize, Deserialize)]
+struct Buckets<K> {
+    buckets: Vec<Vec<(K, u64)>>,
+}
+
+impl<K> From<MerkleTree<K>> for Buckets<K> {
+    fn from(tree: MerkleTree<K>) -> Self {
+        Self {
+            buckets: tree.buckets,
+        }
+    }
+}
+
+impl<K: Ord> TryFrom<Buckets<K>> for MerkleTree<K> {
+    type Error = String;
+
+    fn try_from(Buckets { mut buckets }: Buckets<K>) -> Result<Self, String> {
+        if buckets.len() != FANOUT.pow(DEPTH as u32) {
+            return Err(format!(
+                "expected {} buckets, got {}",
+                FANOUT.pow(DEPTH as u32),
+                buckets.len()
+            ));
+        }
+        let levels = build_levels(&mut buckets);
+        Ok(Self { levels, buckets })
+    }
+}
+
+impl<K: Ord + Clone + Serialize> MerkleTree<K> {
+    /// Builds the tree of a map, or of any key-value pairs with unique
+    /// keys.
+    ///
+    /// Keys and values are hashed through their JSON serialization, so two
+    /// equal states give equal trees across processes.
+    pub fn new<'a, V, I>(entries: I) -> Self
+    where
+        K: 'a,
+        V: Serialize + 'a,
+        I: IntoIterator<Item = (&'a K, &'a V)>,
+    {
+        let mut buckets = vec![Vec::new(); FANOUT.pow(DEPTH as u32)];
+        for (key, value) in entries {
+            let (bucket, hash) = entry_hash(key, value);
+            buckets[bucket].push((key.clone(), hash));
+        }
+        let levels = build_levels(&mut buckets);
+        Self { levels, buckets }
+    }
+
+    /// Adds an entry, or replaces the value of its key, rehashing only its
+    /// bucket and the digests above it.
+    pub fn insert<V: Serialize>(&mut self, key: &K, value: &V) {
+        let (bucket, hash) = entry_hash(key, value);
+        let entries = &mut self.buckets[bucket];
+        match entries.binary_search_by(|(other, _)| other.cmp(key)) {
+            Ok(found) => entries[found].1 = hash,
+            Err(index) => entries.insert(index, (key.clone(), hash)),
+        }
+        self.rehash(bucket);
+    }
+
+    /// Removes the entry of a key, rehashing only its bucket and the
+    /// digests above it.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the key had an entry.
+    pub fn remove(&mut self, key: &K) -> bool {
+        let bucket = bucket_of(fnv1a(&serde_json::to_vec(key).unwrap_or_default()));
+        let entries = &mut self.buckets[bucket];
+        let Ok(found) = entries.binary_search_by(|(other, _)| other.cmp(key)) else {
+            return false;
+        };
+        entries.remove(found);
+        self.rehash(bucket);
+        true
+    }
+}
+
+impl<K: Ord + Clone> MerkleTree<K> {
+    /// Returns the digest of the whole state. Equal states have equal
+    /// roots.
+    pub fn root(&self) -> u64 {
+        self.levels[0][0]
+    }
+
+    /// Returns the keys whose entries differ between both trees, in order:
+    /// keys with different values, and keys present in only one of them.
+    pub fn diff(&self, other: &MerkleTree<K>) -> Vec<K> {
+        let mut keys = Vec::new();
+        self.diff_node(other, 0, 0, &mut keys);
+        keys.sort();
+        keys
+    }
+
+    fn diff_node(&self, other: &MerkleTree<K>, depth: usize, index: usize, keys: &mut Vec<K>) {
+        if self.levels[depth][index] == other.levels[depth][index] {
+            return;
+        }
+        if depth < DEPTH {
+            for child in children(index) {
+                self.diff_node(other, depth + 1, child, keys);
+            }
+            return;
+        }
+        let ours = &self.buckets[index];
+        let theirs = &other.buckets[index];
+        for (key, hash) in ours {
+            match theirs.binary_search_by(|(other, _)| other.cmp(key)) {
+                Ok(found) if theirs[found].1 == *hash => {}
+                _ => keys.push(key.clone()),
+            }
+        }
+        for (key, _) in theirs {
+            if ours.binary_search_by(|(own, _)| own.cmp(key)).is_err() {
+                keys.push(key.clone());
+            }
+        }
+    }
+
+    /// Recomputes the digest of a bucket and of the nodes above it.
+    fn rehash(&mut self, bucket: usize) {
+        self.levels[DEPTH][bucket] = bucket_digest(&self.buckets[bucket]);
+        let mut index = bucket;
+        for depth in (0..DEPTH).rev() {
+            index /= FANOUT;
+            let start = index * FANOUT;
+            self.levels[depth][index] = combine(&self.levels[depth + 1][start..start + FANOUT]);
+        }
+    }
+
+    /// Returns the digests of the nodes at `indices` of a level.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the level or an index does not exist.
+    fn digests(&self, depth: usize, indices: &[usize]) -> io::Result<Vec<(usize, u64)>> {
+        indices
+            .iter()
+            .map(|&index| {
+                self.levels
+                    .get(depth)
+                    .and_then(|level| level.get(index))
+                    .map(|&digest| (index, digest))
+                    .ok_or_else(|| invalid(format!("no Merkle node {index} at depth {depth}")))
+            })
+            .collect()
+    }
+
+    /// Returns the keys of the buckets at `buckets`.
+    fn bucket_keys(&self, buckets: &[usize]) -> io::Result<Vec<K>> {
+        let mut keys = Vec::new();
+        for &bucket in buckets {
+            let entries = self
+                .buckets
+                .get(bucket)
+                .ok_or_else(|| invalid(format!("no Merkle bucket {bucket}")))?;
+            keys.extend(entries.iter().map(|(key, _)| key.clone()));
+        }
+        Ok(keys)
+    }
+}
+
+/// The tree of a node's state, cached on the node between syncs.
+struct MerkleIndex<K, V> {
+    tree: MerkleTree<K>,
+    /// The entries the tree was last updated to
+    entries: BTreeMap<K, V>,
+}
+
+impl<K, V> MerkleIndex<K, V>
+where
+    K: Ord + Clone + Serialize,
+    V: Clone + Serialize + PartialEq,
+{
+    /// Updates the tree to `state`, rehashing only the entries that changed
+    /// since the last update.
+    fn update(&mut self, state: &BTreeMap<K, V>) {
+        let mut changes: Vec<(K, Option<V>)> = Vec::new();
+        let mut ours = self.entries.iter().peekable();
+        let mut theirs = state.iter().peekable();
+        loop {
+            let order = match (ours.peek(), theirs.peek()) {
+                (None, None) => break,
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some((old, _)), Some((new, _))) => old.cmp(new),
+            };
+            match order {
+                Ordering::Less => {
+                    let (key, _) = ours.next().unwrap();
+                    changes.push((key.clone(), None));
+                }
+                Ordering::Greater => {
+                    let (key, value) = theirs.next().unwrap();
+                    changes.push((key.clone(), Some(value.clone())));
+                }
+                Ordering::Equal => {
+                    let (_, old) = ours.next().unwrap();
+                    let (key, value) = theirs.next().unwrap();
+                    if old != value {
+                        changes.push((key.clone(), Some(value.clone())));
+                    }
+                }
+            }
+        }
+        for (key, value) in changes {
+            match value {
+                Some(value) => {
+                    self.tree.insert(&key, &value);
+                    self.entries.insert(key, value);
+                }
+                None => {
+                    self.tree.remove(&key);
+                    self.entries.remove(&key);
+                }
+            }
+        }
+    }
+}
+
+impl<K, V> StateNode<BTreeMap<K, V>>
+where
+    K: Ord + Clone + Serialize + Send + Sync + 'static,
+    V: Clone + Serialize + PartialEq + Send + Sync + 'static,
+{
+    /// Returns the Merkle tree of this node's state.
+    ///
+    /// The tree is built on the first call and kept with the node. Later
+    /// calls compare the state with the entries the tree was built from
+    /// and rehash only the entries that changed, however the state was
+    /// modified in between.
+    pub fn merkle_tree(&mut self) -> &MerkleTree<K> {
+        let index = self
+            .merkle
+            .take()
+            .and_then(|index| index.downcast::<MerkleIndex<K, V>>().ok());
+        let index = match index {
+            Some(mut index) => {
+                index.update(&self.state);
+                index
+            }
+            None => Box::new(MerkleIndex {
+                tree: MerkleTree::new(&self.state),
+                entries: self.state.clone(),
+            }),
+        };
+        let index = self.merkle.insert(index);
+        &index
+            .downcast_ref::<MerkleIndex<K, V>>()
+            .expect("Merkle index was just stored")
+            .tree
+    }
+
+    /// Reconciles with a peer by copying only the entries that differ.
+    ///
+    /// The peer's state is rebuilt from this node's state and the differing
+    /// entries, then resolved like a state propagated by the peer, with its
+    /// authority. The result is the same as with `propagate_update()` from
+    /// the peer, but only the differing entries are read from it. Nothing
+    /// happens if both states are equal.
+    ///
+    /// Both nodes keep their trees between calls, see `merkle_tree()`. The
+    /// peer is locked while its tree is updated, the trees compared and
+    /// the differing entries copied. For a peer in another process, use
+    /// `MeshLink::request_sync()`.
+    ///
+    /// # Returns
+    ///
+    /// The number of keys that differed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::collections::BTreeMap;
+    /// use zed::StateNode;
+    ///
+    /// let rows: BTreeMap<u32, u32> = (0..1000).map(|i| (i, i)).collect();
+    /// let mut replica = StateNode::new("replica".to_string(), rows.clone());
+    /// let primary = StateNode::new("primary".to_string(), rows).into_shared();
+    /// primary.lock().unwrap().state.insert(500, 0);
+    ///
+    /// assert_eq!(replica.sync_from(&primary), 1);
+    /// assert_eq!(replica.state[&500], 0);
+    /// ```
+    pub fn sync_from(&mut self, peer: &SharedStateNode<BTreeMap<K, V>>) -> usize {
+        let ours = self.merkle_tree().clone();
+        let (keys, changes, id, authority) = {
+            let mut peer = lock(peer);
+            let keys = ours.diff(peer.merkle_tree());
+            let changes: Vec<(K, Option<V>)> = keys
+                .iter()
+                .map(|key| (key.clone(), peer.state.get(key).cloned()))
+                .collect();
+            (keys, changes, peer.id.clone(), peer.authority())
+        };
+        if keys.is_empty() {
+            return 0;
+        }
+        let mut remote = self.state.clone();
+        for (key, value) in changes {
+            match value {
+                Some(value) => remote.insert(key, value),
+                None => remote.remove(&key),
+            };
+        }
+        self.receive_state(&id, remote, authority);
+        keys.len()
+    }
+
+    /// Handles a Merkle sync message received by a `MeshLink`.
+    ///
+    /// # Returns
+    ///
+    /// The message to send back, if any, and whether received entries were
+    /// applied.
+    pub(super) fn answer_sync(
+        &mut self,
+        message: MeshMessage,
+        authority: u32,
+    ) -> io::Result<(Option<MeshMessage>, bool)>
+    where
+        K: DeserializeOwned,
+        V: DeserializeOwned,
+    {
+        match message {
+            MeshMessage::DigestRequest { depth, indices } => {
+                let digests = self.merkle_tree().digests(depth, &indices)?;
+                Ok((Some(MeshMessage::Digests { depth, digests }), false))
+            }
+            MeshMessage::Digests { depth, digests } => {
+                let tree = self.merkle_tree();
+                let indices: Vec<usize> = digests.iter().map(|(index, _)| *index).collect();
+                let differing: Vec<usize> = tree
+                    .digests(depth, &indices)?
+                    .into_iter()
+                    .zip(&digests)
+                    .filter(|((_, ours), (_, theirs))| ours != theirs)
+                    .map(|((index, _), _)| index)
+                    .collect();
+                let reply = if differing.is_empty() {
+                    None
+                } else if depth < DEPTH {
+                    Some(MeshMessage::DigestRequest {
+                        depth: depth + 1,
+                        indices: differing.into_iter().flat_map(children).collect(),
+                    })
+                } else {
+                    Some(MeshMessage::EntryRequest { buckets: differing })
+                };
+                Ok((reply, false))
+            }
+            MeshMessage::EntryRequest { buckets } => {
+                let keys = self.merkle_tree().bucket_keys(&buckets)?;
+                let entries: Vec<(&K, &V)> = keys
+                    .iter()
+                    .filter_map(|key| self.state.get_key_value(key))
+                    .collect();
+                let entries = serde_json::to_value(entries)?;
+                let from = self.id.clone();
+                Ok((
+                    Some(MeshMessage::Entries {
+                        from,
+                        buckets,
+                        entries,
+                    }),
+                    false,
+                ))
+            }
+            MeshMessage::Entries {
+                from,
+                buckets,
+                entries,
+            } => {
+                let entries: Vec<(K, V)> = serde_json::from_value(entries)?;
+                let stale = self.merkle_tree().bucket_keys(&buckets)?;
+                let mut remote = self.state.clone();
+                for key in &stale {
+                    remote.remove(key);
+                }
+                remote.extend(entries);
+                if remote == self.state {
+                    return Ok((None, false));
+                }
+                self.receive_state(&from, remote, authority);
+                Ok((None, true))
+            }
+            _ => Err(invalid("not a Merkle sync message")),
+        }
+    }
+}
+
+/// Hashes an entry, returning its bucket and hash.
+fn entry_hash<K: Serialize, V: Serialize>(key: &K, value: &V) -> (usize, u64) {
+    let mut bytes = serde_json::to_vec(key).unwrap_or_default();
+    let bucket = bucket_of(fnv1a(&bytes));
+    bytes.push(0);
+    // Values that fail to serialize hash like their key alone
+    let _ = serde_json::to_writer(&mut bytes, value);
+    (bucket, fnv1a(&bytes))
+}
+
+/// Sorts the buckets by key and computes the digests of every level, from
+/// the root down to the buckets.
+fn build_levels<K: Ord>(buckets: &mut [Vec<(K, u64)>]) -> Vec<Vec<u64>> {
+    let mut level: Vec<u64> = buckets
+        .iter_mut()
+        .map(|bucket| {
+            bucket.sort_by(|a, b| a.0.cmp(&b.0));
+            bucket_digest(bucket)
+        })
+        .collect();
+    let mut levels = vec![level.clone()];
+    while level.len() > 1 {
+        level = level.chunks(FANOUT).map(combine).collect();
+        levels.push(level.clone());
+    }
+    levels.reverse();
+    levels
+}
+
+/// Returns the digest of a bucket's entries.
+fn bucket_digest<K>(bucket: &[(K, u64)]) -> u64 {
+    // Order-independent, like the bucket's contents
+    bucket
+        .iter()
+        .fold(0u64, |digest, (_, hash)| digest.wrapping_add(*hash))
+}
+
+/// Returns the indices of the children of an inner node.
+fn children(index: usize) -> std::ops::Range<usize> {
+    index * FANOUT..(index + 1) * FANOUT
+}
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// Returns the bucket of a key from the top bits of its hash.
+fn bucket_of(key_hash: u64) -> usize {
+    (key_hash >> (64 - BUCKET_BITS)) as usize
+}
+
+/// Combines the digests of the children of an inner node.
+fn combine(children: &[u64]) -> u64 {
+    if children.iter().all(|&digest| digest == 0) {
+        return 0;
+    }
+    let bytes: Vec<u8> = children
+        .iter()
+        .flat_map(|digest| digest.to_le_bytes())
+        .collect();
+    fnv1a(&bytes)
+}
@@ -0,0 +1,109 @@
+//! # Mesh Store Module
+//!
+//! Brings a [`Store`] into a state mesh.
+//!
+//! A [`MeshStore`] pairs a store with a [`StateNode`] that mirrors its
+//! state. Actions dispatched to the store are propagated to the node's
+//! peers, and states arriving from peers go through the node's conflict
+//! resolver into the store, which notifies its subscribers as after any
+//! other change. Application code keeps using the store as before and
+//! becomes part of a distributed mesh.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use zed::{MeshStore, StateNode, Store, create_reducer};
+//!
+//! #[derive(Clone, Debug, PartialEq)]
+//! struct Counter { value: i32 }
+//!
+//! enum Action { Increment }
+//!
+//! let store = || Store::new(
+//!     Counter { value: 0 },
+//!     Box::new(create_reducer(|state: &Counter, _: &Action| Counter { value: state.value + 1 })),
+//! );
+//! let laptop = MeshStore::new("laptop".to_string(), store());
+//! let phone = MeshStore::new("phone".to_string(), store());
+//! StateNode::connect_bidirectional(laptop.node(), phone.node());
+//!
+//! laptop.store().dispatch(Action::Increment);
+//! assert_eq!(phone.store().get_state().value, 1);
+//! ```
+
+use super::{NodeId, SharedStateNode, StateNode, lock};
+use crate::store::{Store, SubscriptionGuard, SubscriptionId};
+use std::sync::Arc;
+
+/// A [`Store`] taking part in a state mesh through a [`StateNode`].
+///
+/// The store and the node stay synchronized until the `MeshStore` is
+/// dropped.
+#[must_use = "the store leaves the mesh as soon as the MeshStore is dropped"]
+pub struct MeshStore<State: Clone, Action> {
+    store: Store<State, Action>,
+    node: SharedStateNode<State>,
+    /// Subscription of the store to the node's state changes
+    node_subscription: SubscriptionId,
+    _listener: SubscriptionGuard,
+}
+
+impl<State, Action> MeshStore<State, Action>
+where
+    State: Clone + PartialEq + Send + Sync + 'static,
+    Action: Send + 'static,
+{
+    /// Creates a node with the given ID that mirrors the store's state.
+    ///
+    /// Connect the node to peers with [`node()`](Self::node), and set its
+    /// conflict resolver there to decide how remote states are merged.
+    ///
+    /// Both sides of the synchronization run while the node is locked: the
+    /// store's action listeners and subscribers must not lock the node, and
+    /// peers are updated on the dispatching thread.
+    pub fn new(id: NodeId, store: Store<State, Action>) -> Self {
+        let node = StateNode::new(id, store.get_state()).into_shared();
+
+        let local = Arc::clone(&node);
+        let listener = store.add_listener(
+            |_| true,
+            move |_: &Action, store: &Store<State, Action>| {
+                let mut node = lock(&local);
+                // Assigned directly, so the store is not updated again
+                node.state = store.get_state();
+                node.propagate_update();
+            },
+        );
+
+        let remote = store.clone();
+        let node_subscription =
+            lock(&node).subscribe(move |state: &State| remote.replace_state(state.clone()));
+
+        Self {
+            _listener: store.guard(listener),
+            store,
+            node,
+            node_subscription,
+        }
+    }
+}
+
+impl<State: Clone, Action> MeshStore<State, Action> {
+    /// Returns the store.
+    pub fn store(&self) -> &Store<State, Action> {
+        &self.store
+    }
+
+    /// Returns the node representing the store in the mesh.
+    pub fn node(&self) -> &SharedStateNode<State> {
+        &self.node
+    }
+}
+
+impl<State: Clone, Action> Drop for MeshStore<State, Action> {
+    fn drop(&mut self) {
+        // The subscription holds a handle to the store, and the store's
+        // listener one to the node
+        lock(&self.node).unsubscribe(self.node_subscription);
+    }
+}
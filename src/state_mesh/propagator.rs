@@ -0,0 +1,185 @@
+//! # Propagator Module
+//!
+//! Asynchronous propagation with backpressure. Requires the `async`
+//! feature.
+//!
+//! `propagate_update()` locks every connected node in turn and resolves the
+//! update there, so one slow peer holds up the caller, and a caller that
+//! cannot wait has nowhere to put its updates. An [`AsyncPropagator`] gives
+//! every peer a bounded queue, drained by a delivery thread of its own.
+//! Propagating only waits for room in the queues:
+//!
+//! - A slow peer delays its own deliveries, not the caller nor other peers
+//! - A producer faster than a peer is suspended once the peer's queue is
+//!   full, so queued states never exceed the queue capacity per peer
+//!
+//! ## Example
+//!
+//! ```rust
+//! # #[cfg(feature = "async")]
+//! # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+//! use zed::{AsyncPropagator, StateNode};
+//!
+//! let editor = StateNode::new("editor".to_string(), "draft".to_string()).into_shared();
+//! let viewer = StateNode::new("viewer".to_string(), String::new()).into_shared();
+//! editor.lock().unwrap().connect(&viewer);
+//!
+//! let propagator = AsyncPropagator::new(&editor, 16);
+//! assert_eq!(propagator.propagate_update().await, 1);
+//! propagator.flush().await;
+//! assert_eq!(viewer.lock().unwrap().state, "draft");
+//! # });
+//! ```
+
+use super::{NodeId, SharedStateNode, StateNode, lock};
+use std::collections::{HashMap, HashSet};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Instant;
+use tokio::sync::{mpsc, watch};
+
+/// The queue of states waiting for one peer.
+#[derive(Clone)]
+struct PeerQueue<T> {
+    sender: mpsc::Sender<(T, u32)>,
+    /// States queued or being applied
+    pending: Arc<AtomicUsize>,
+}
+
+/// Propagates a node's state to its connections through bounded per-peer
+/// queues. Requires the `async` feature.
+///
+/// Queues and their delivery threads are created on the first propagation
+/// to each connection. Dropping the propagator stops the threads once they
+/// have delivered the states already queued.
+pub struct AsyncPropagator<T: Clone> {
+    node: SharedStateNode<T>,
+    capacity: usize,
+    queues: Mutex<HashMap<NodeId, PeerQueue<T>>>,
+    /// Counts deliveries, to wake up `flush()`
+    delivered: watch::Sender<u64>,
+}
+
+impl<T: Clone + Send + 'static> AsyncPropagator<T> {
+    /// Creates a propagator for a node, queueing up to `capacity` states
+    /// per connection.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is 0.
+    pub fn new(node: &SharedStateNode<T>, capacity: usize) -> Self {
+        assert!(capacity > 0, "queue capacity must be at least 1");
+        Self {
+            node: Arc::clone(node),
+            capacity,
+            queues: Mutex::new(HashMap::new()),
+            delivered: watch::Sender::new(0),
+        }
+    }
+
+    /// Queues the node's current state for every connection, like
+    /// `StateNode::propagate_update()`, and returns once it is queued
+    /// everywhere.
+    ///
+    /// Waits while a connection's queue is full. The node is locked only to
+    /// read its state and connections, so it may be updated meanwhile.
+    /// Dropping the future before it completes leaves the state queued for
+    /// some connections only. Connections marked offline keep receiving the
+    /// state in the node's outbox instead, delivered by `mark_online()`.
+    ///
+    /// # Returns
+    ///
+    /// The number of connections the state was queued for.
+    pub async fn propagate_update(&self) -> usize {
+        let start = Instant::now();
+        let (from, state, authority, reachable, connected) = {
+            let mut node = lock(&self.node);
+            let node = &mut *node;
+            let reachable = StateNode::reachable(&node.connections, &mut node.outbox, &node.state);
+            let connected: HashSet<NodeId> = node.connections.keys().cloned().collect();
+            let from = node.id.clone();
+            (
+                from,
+                node.state.clone(),
+                node.authority,
+                reachable,
+                connected,
+            )
+        };
+        let queues: Vec<PeerQueue<T>> = {
+            let mut queues = lock(&self.queues);
+            // Queues of removed connections close, ending their threads
+            queues.retain(|id, _| connected.contains(id));
+            reachable
+                .into_iter()
+                .map(|(id, peer)| {
+                    queues
+                        .entry(id)
+                        .or_insert_with(|| self.spawn_delivery(from.clone(), peer))
+                        .clone()
+                })
+                .collect()
+        };
+        let mut queued = 0;
+        for queue in &queues {
+            // Counted before it is queued, so the delivery cannot come first
+            if let Ok(permit) = queue.sender.reserve().await {
+                queue.pending.fetch_add(1, Ordering::SeqCst);
+                permit.send((state.clone(), authority));
+                queued += 1;
+            }
+        }
+        lock(&self.node).record_propagation(queued, start);
+        queued
+    }
+
+    /// Waits until every queued state has been delivered.
+    pub async fn flush(&self) {
+        let mut deliveries = self.delivered.subscribe();
+        while self.pending_total() > 0 {
+            if deliveries.changed().await.is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Returns the number of states queued for a connection or being
+    /// applied to it.
+    pub fn pending(&self, id: &NodeId) -> usize {
+        lock(&self.queues)
+            .get(id)
+            .map_or(0, |queue| queue.pending.load(Ordering::SeqCst))
+    }
+
+    /// Returns the number of states a connection's queue holds at most.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn pending_total(&self) -> usize {
+        lock(&self.queues)
+            .values()
+            .map(|queue| queue.pending.load(Ordering::SeqCst))
+            .sum()
+    }
+
+    fn spawn_delivery(&self, from: NodeId, peer: SharedStateNode<T>) -> PeerQueue<T> {
+        let (sender, mut receiver) = mpsc::channel::<(T, u32)>(self.capacity);
+        let pending = Arc::new(AtomicUsize::new(0));
+        let delivered = self.delivered.clone();
+        let counter = Arc::clone(&pending);
+        thread::spawn(move || {
+            while let Some((state, authority)) = receiver.blocking_recv() {
+                // A panicking resolver loses this state, not the queue
+                let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+                    lock(&peer).receive_state(&from, state, authority);
+                }));
+                counter.fetch_sub(1, Ordering::SeqCst);
+                delivered.send_modify(|count| *count += 1);
+            }
+        });
+        PeerQueue { sender, pending }
+    }
+}
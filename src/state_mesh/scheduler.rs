@@ -0,0 +1,198 @@
+//! # Scheduler Module
+//!
+//! Anti-entropy for state meshes: a background thread that runs
+//! [`Gossip`] rounds at a regular interval.
+//!
+//! Pushing updates with `propagate_update()` only reaches nodes that are
+//! connected at that moment and keep every message. A [`MeshScheduler`]
+//! repairs whatever such pushes missed, e.g. updates lost while a node was
+//! disconnected, so every change no longer has to reach every node right
+//! away. A random jitter is added to each interval, so schedulers started
+//! together do not keep running their rounds at the same time.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use serde::Serialize;
+//! use std::time::Duration;
+//! use zed::{MeshScheduler, StateNode};
+//!
+//! #[derive(Clone, Serialize)]
+//! struct Status { version: u32, text: String }
+//!
+//! let nodes: Vec<_> = (0..4)
+//!     .map(|i| {
+//!         let mut node = StateNode::new(format!("node{i}"), Status { version: 0, text: String::new() });
+//!         node.set_conflict_resolver(|current: &mut Status, remote: &Status| {
+//!             if remote.version > current.version {
+//!                 *current = remote.clone();
+//!             }
+//!         });
+//!         node.into_shared()
+//!     })
+//!     .collect();
+//!
+//! let mut scheduler = MeshScheduler::new(Duration::from_millis(10))
+//!     .jitter(Duration::from_millis(5))
+//!     .fanout(2);
+//! for (i, node) in nodes.iter().enumerate() {
+//!     StateNode::connect_bidirectional(node, &nodes[(i + 1) % nodes.len()]);
+//!     scheduler.add_node(node);
+//! }
+//! scheduler.start();
+//!
+//! // A change that was never propagated
+//! nodes[0].lock().unwrap().state = Status { version: 1, text: "online".into() };
+//! while !scheduler.is_converged() {
+//!     std::thread::sleep(Duration::from_millis(10));
+//! }
+//! scheduler.stop();
+//! assert!(nodes.iter().all(|node| node.lock().unwrap().state.text == "online"));
+//! ```
+
+use super::gossip::{Gossip, GossipRound, XorShift};
+use super::{SharedStateNode, lock};
+use serde::Serialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Fanout used unless `fanout()` sets another one.
+const DEFAULT_FANOUT: usize = 3;
+
+/// Runs anti-entropy rounds over a set of nodes in the background.
+///
+/// The scheduler stops when it is dropped.
+pub struct MeshScheduler<T: Clone> {
+    gossip: Arc<Mutex<Gossip<T>>>,
+    interval: Duration,
+    jitter: Duration,
+    rounds: Arc<AtomicUsize>,
+    /// Dropping the sender wakes and ends the background thread
+    worker: Option<(mpsc::Sender<()>, JoinHandle<()>)>,
+}
+
+impl<T: Clone + Serialize + Send + 'static> MeshScheduler<T> {
+    /// Creates a stopped scheduler that runs a round every `interval` once
+    /// started.
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            gossip: Arc::new(Mutex::new(Gossip::new(DEFAULT_FANOUT))),
+            interval,
+            jitter: Duration::ZERO,
+            rounds: Arc::new(AtomicUsize::new(0)),
+            worker: None,
+        }
+    }
+
+    /// Adds a random delay of up to `jitter` to every interval.
+    pub fn jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Sets how many connections every node contacts per round (default
+    /// 3, at least 1).
+    pub fn fanout(self, fanout: usize) -> Self {
+        lock(&self.gossip).set_fanout(fanout);
+        self
+    }
+
+    /// Adds a node to reconcile, also while the scheduler is running.
+    pub fn add_node(&self, node: &SharedStateNode<T>) {
+        lock(&self.gossip).add_node(node);
+    }
+
+    /// Removes a node.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the node was added before.
+    pub fn remove_node(&self, node: &SharedStateNode<T>) -> bool {
+        lock(&self.gossip).remove_node(node)
+    }
+
+    /// Starts running rounds on a background thread.
+    ///
+    /// # Returns
+    ///
+    /// `false` if the scheduler was already running.
+    pub fn start(&mut self) -> bool {
+        if self.is_running() {
+            return false;
+        }
+        let (stop, stopped) = mpsc::channel::<()>();
+        let gossip = Arc::clone(&self.gossip);
+        let rounds = Arc::clone(&self.rounds);
+        let (interval, jitter) = (self.interval, self.jitter);
+        let thread = thread::spawn(move || {
+            let mut rng = XorShift::new();
+            loop {
+                let extra = match jitter.as_nanos() as u64 {
+                    0 => 0,
+                    max => rng.next_u64() % (max + 1),
+                };
+                match stopped.recv_timeout(interval + Duration::from_nanos(extra)) {
+                    Err(RecvTimeoutError::Timeout) => {
+                        lock(&gossip).round();
+                        rounds.fetch_add(1, Ordering::SeqCst);
+                    }
+                    // Stopped or dropped
+                    _ => break,
+                }
+            }
+        });
+        self.worker = Some((stop, thread));
+        true
+    }
+
+    /// Stops the background thread, waiting for a running round to finish.
+    ///
+    /// # Returns
+    ///
+    /// `false` if the scheduler was not running.
+    pub fn stop(&mut self) -> bool {
+        match self.worker.take() {
+            Some((stop, thread)) => {
+                drop(stop);
+                let _ = thread.join();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns `true` if the scheduler runs rounds in the background.
+    pub fn is_running(&self) -> bool {
+        self.worker.is_some()
+    }
+
+    /// Runs a round right away, e.g. after a node reconnected, without
+    /// waiting for the next interval.
+    pub fn run_round(&self) -> GossipRound {
+        let round = lock(&self.gossip).round();
+        self.rounds.fetch_add(1, Ordering::SeqCst);
+        round
+    }
+
+    /// Returns the number of rounds run so far.
+    pub fn rounds(&self) -> usize {
+        self.rounds.load(Ordering::SeqCst)
+    }
+
+    /// Returns `true` if all nodes hold states with the same digest.
+    pub fn is_converged(&self) -> bool {
+        lock(&self.gossip).is_converged()
+    }
+}
+
+impl<T: Clone> Drop for MeshScheduler<T> {
+    fn drop(&mut self) {
+        if let Some((stop, thread)) = self.worker.take() {
+            drop(stop);
+            let _ = thread.join();
+        }
+    }
+}
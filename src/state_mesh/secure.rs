@@ -0,0 +1,291 @@
+//! # Secure Module
+//!
+//! Encrypted and authenticated synchronization over untrusted networks.
+//! Requires the `encryption` feature.
+//!
+//! Every node has a [`NodeIdentity`], a long-term X25519 key pair whose
+//! public key its peers trust. A [`SecureTransport`] wraps another
+//! [`Transport`] and runs a Noise `XX` handshake over it: both ends prove
+//! they hold their keys, and the connection is refused unless the peer's
+//! public key is among the trusted ones. Every message is then encrypted
+//! and authenticated with ChaCha20-Poly1305, so it can neither be read nor
+//! forged or replayed by anyone on the network.
+//!
+//! Trusted peers are trusted with every node ID: the `from` of the states
+//! they send is not checked against their key.
+//!
+//! ## Example
+//!
+//! ```rust
+//! # #[cfg(feature = "tcp")]
+//! # {
+//! use std::net::TcpListener;
+//! use std::thread;
+//! use zed::{MeshLink, NodeIdentity, SecureTransport, StateNode, TcpTransport};
+//!
+//! let server_identity = NodeIdentity::generate().unwrap();
+//! let client_identity = NodeIdentity::generate().unwrap();
+//! let (server_key, client_key) = (server_identity.public_key(), client_identity.public_key());
+//!
+//! let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+//! let addr = listener.local_addr().unwrap();
+//! let server = thread::spawn(move || {
+//!     let stream = TcpTransport::from_stream(listener.accept().unwrap().0).unwrap();
+//!     let mut link =
+//!         MeshLink::new(SecureTransport::accept(stream, &server_identity, &[client_key]).unwrap());
+//!     let mut node = StateNode::new("server".to_string(), String::new());
+//!     while link.receive(&mut node).unwrap() == 0 {}
+//!     node.state
+//! });
+//!
+//! let stream = TcpTransport::connect(addr).unwrap();
+//! let mut link =
+//!     MeshLink::new(SecureTransport::connect(stream, &client_identity, &[server_key]).unwrap());
+//! link.send_state(&StateNode::new("client".to_string(), "secret".to_string())).unwrap();
+//! assert_eq!(server.join().unwrap(), "secret");
+//! # }
+//! ```
+
+use super::transport::{MeshMessage, Transport};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use snow::{Builder, HandshakeState, TransportState};
+use std::fmt;
+use std::io;
+use std::thread;
+use std::time::{Duration, Instant};
+use x25519_dalek::{PublicKey, StaticSecret};
+
+/// Noise protocol used by `SecureTransport`.
+const NOISE_PARAMS: &str = "Noise_XX_25519_ChaChaPoly_BLAKE2s";
+/// Largest Noise message; longer messages are sent in chunks.
+const MAX_NOISE_LEN: usize = 65535;
+/// Size of the authentication tag added to every chunk.
+const TAG_LEN: usize = 16;
+/// How long the handshake waits for the peer.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The long-term key pair identifying a node to its peers.
+///
+/// The secret key has to be stored safely to keep the identity across
+/// restarts; the public key is handed to the peers that should trust the
+/// node.
+#[derive(Clone)]
+pub struct NodeIdentity {
+    secret: [u8; 32],
+    public: [u8; 32],
+}
+
+impl NodeIdentity {
+    /// Generates a new random identity.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the system random number generator fails.
+    pub fn generate() -> io::Result<Self> {
+        let keypair = builder().generate_keypair().map_err(noise_error)?;
+        let secret = keypair
+            .private
+            .try_into()
+            .map_err(|_| io::Error::other("unexpected private key length"))?;
+        Ok(Self::from_secret_key(secret))
+    }
+
+    /// Restores an identity from its secret key.
+    pub fn from_secret_key(secret: [u8; 32]) -> Self {
+        let public = PublicKey::from(&StaticSecret::from(secret)).to_bytes();
+        Self { secret, public }
+    }
+
+    /// Returns the secret key, to store the identity.
+    pub fn secret_key(&self) -> [u8; 32] {
+        self.secret
+    }
+
+    /// Returns the public key peers use to recognize this node.
+    pub fn public_key(&self) -> [u8; 32] {
+        self.public
+    }
+}
+
+/// Shows the public key only.
+impl fmt::Debug for NodeIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NodeIdentity")
+            .field("public", &self.public)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A [`Transport`] encrypting and authenticating every message sent over
+/// another transport. Requires the `encryption` feature.
+pub struct SecureTransport<Tr> {
+    inner: Tr,
+    noise: TransportState,
+    peer_key: [u8; 32],
+}
+
+impl<Tr: Transport> SecureTransport<Tr> {
+    /// Secures a connection opened by this node, e.g. with
+    /// `TcpTransport::connect()`.
+    ///
+    /// Blocks until the handshake with the peer, which has to call
+    /// `accept()`, completes.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - The connected transport
+    /// * `identity` - This node's identity
+    /// * `trusted` - Public keys of the peers this node accepts
+    ///
+    /// # Errors
+    ///
+    /// * `ErrorKind::PermissionDenied` - The peer's key is not trusted
+    /// * `ErrorKind::TimedOut` - The peer did not answer within 10 seconds
+    /// * `ErrorKind::InvalidData` - The handshake failed, e.g. because the
+    ///   peer does not hold the key it claims
+    pub fn connect(inner: Tr, identity: &NodeIdentity, trusted: &[[u8; 32]]) -> io::Result<Self> {
+        let noise = builder()
+            .local_private_key(&identity.secret)
+            .build_initiator()
+            .map_err(noise_error)?;
+        Self::handshake(inner, noise, trusted, true)
+    }
+
+    /// Secures a connection accepted by this node, e.g. with
+    /// `TcpTransport::from_stream()`.
+    ///
+    /// Blocks until the handshake with the peer, which has to call
+    /// `connect()`, completes.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `connect()`.
+    pub fn accept(inner: Tr, identity: &NodeIdentity, trusted: &[[u8; 32]]) -> io::Result<Self> {
+        let noise = builder()
+            .local_private_key(&identity.secret)
+            .build_responder()
+            .map_err(noise_error)?;
+        Self::handshake(inner, noise, trusted, false)
+    }
+
+    /// Returns the public key of the peer.
+    pub fn peer_key(&self) -> [u8; 32] {
+        self.peer_key
+    }
+
+    /// Returns the underlying transport.
+    pub fn transport(&self) -> &Tr {
+        &self.inner
+    }
+
+    fn handshake(
+        mut inner: Tr,
+        mut noise: HandshakeState,
+        trusted: &[[u8; 32]],
+        mut writing: bool,
+    ) -> io::Result<Self> {
+        let deadline = Instant::now() + HANDSHAKE_TIMEOUT;
+        let mut buffer = vec![0; MAX_NOISE_LEN];
+        while !noise.is_handshake_finished() {
+            if writing {
+                let len = noise.write_message(&[], &mut buffer).map_err(noise_error)?;
+                inner.send(&MeshMessage::Handshake {
+                    payload: STANDARD.encode(&buffer[..len]),
+                })?;
+            } else {
+                let payload = loop {
+                    match inner.receive()? {
+                        Some(MeshMessage::Handshake { payload }) => break payload,
+                        Some(_) => return Err(invalid_data("expected a handshake message")),
+                        None if Instant::now() >= deadline => {
+                            return Err(io::ErrorKind::TimedOut.into());
+                        }
+                        None => thread::sleep(Duration::from_millis(1)),
+                    }
+                };
+                noise
+                    .read_message(&decode(&payload)?, &mut buffer)
+                    .map_err(noise_error)?;
+                // Checked as soon as known, before revealing this node's key
+                if let Some(key) = noise.get_remote_static()
+                    && !trusted.iter().any(|trusted| trusted == key)
+                {
+                    return Err(io::Error::new(
+                        io::ErrorKind::PermissionDenied,
+                        "peer key is not trusted",
+                    ));
+                }
+            }
+            writing = !writing;
+        }
+        let peer_key = noise
+            .get_remote_static()
+            .and_then(|key| key.try_into().ok())
+            .ok_or_else(|| invalid_data("peer sent no key"))?;
+        let noise = noise.into_transport_mode().map_err(noise_error)?;
+        Ok(Self {
+            inner,
+            noise,
+            peer_key,
+        })
+    }
+}
+
+impl<Tr: Transport> Transport for SecureTransport<Tr> {
+    fn send(&mut self, message: &MeshMessage) -> io::Result<()> {
+        let plain = serde_json::to_vec(message)?;
+        let chunk_len = MAX_NOISE_LEN - TAG_LEN;
+        let mut sealed =
+            Vec::with_capacity(plain.len() + TAG_LEN * plain.len().div_ceil(chunk_len));
+        let mut buffer = vec![0; MAX_NOISE_LEN];
+        for chunk in plain.chunks(chunk_len) {
+            let len = self
+                .noise
+                .write_message(chunk, &mut buffer)
+                .map_err(noise_error)?;
+            sealed.extend_from_slice(&buffer[..len]);
+        }
+        self.inner.send(&MeshMessage::Encrypted {
+            payload: STANDARD.encode(sealed),
+        })
+    }
+
+    fn receive(&mut self) -> io::Result<Option<MeshMessage>> {
+        let Some(message) = self.inner.receive()? else {
+            return Ok(None);
+        };
+        let MeshMessage::Encrypted { payload } = message else {
+            return Err(invalid_data("unencrypted message on a secure transport"));
+        };
+        let sealed = decode(&payload)?;
+        let mut plain = Vec::with_capacity(sealed.len());
+        let mut buffer = vec![0; MAX_NOISE_LEN];
+        for chunk in sealed.chunks(MAX_NOISE_LEN) {
+            let len = self
+                .noise
+                .read_message(chunk, &mut buffer)
+                .map_err(|_| invalid_data("message failed authentication"))?;
+            plain.extend_from_slice(&buffer[..len]);
+        }
+        Ok(Some(serde_json::from_slice(&plain)?))
+    }
+}
+
+fn builder<'a>() -> Builder<'a> {
+    Builder::new(NOISE_PARAMS.parse().expect("valid Noise parameters"))
+}
+
+fn decode(payload: &str) -> io::Result<Vec<u8>> {
+    STANDARD
+        .decode(payload)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+fn invalid_data(reason: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, reason)
+}
+
+fn noise_error(err: snow::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
@@ -0,0 +1,148 @@
+//! # Snapshot Module
+//!
+//! Saves a mesh to disk and restores it, so a distributed session can be
+//! suspended and resumed, or inspected offline.
+//!
+//! [`save_mesh`] writes the IDs, states and connections of a set of nodes
+//! to a file; [`load_mesh`] rebuilds the nodes and connects them again.
+//! Conflict resolvers, operation reducers, subscribers and listeners are
+//! code, not data, so they are not saved and have to be set again after
+//! loading.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use serde::{Deserialize, Serialize};
+//! use zed::{StateNode, load_mesh, save_mesh};
+//!
+//! #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+//! struct Cursor { line: u32 }
+//!
+//! let alice = StateNode::new("alice".to_string(), Cursor { line: 4 }).into_shared();
+//! let bob = StateNode::new("bob".to_string(), Cursor { line: 9 }).into_shared();
+//! StateNode::connect_bidirectional(&alice, &bob);
+//!
+//! let path = std::env::temp_dir().join("zed-mesh-doc-session.mesh");
+//! save_mesh(&[alice, bob], &path).unwrap();
+//!
+//! let nodes = load_mesh::<Cursor>(&path).unwrap();
+//! let bob = nodes[1].lock().unwrap();
+//! assert_eq!(bob.state, Cursor { line: 9 });
+//! assert!(bob.connections.contains_key("alice"));
+//! # drop(bob);
+//! # std::fs::remove_file(&path).unwrap();
+//! ```
+
+use super::{NodeId, SharedStateNode, StateNode, lock};
+use crate::persist::{PersistError, read_checked, write_checked};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+/// First word of a saved mesh file.
+const FILE_MAGIC: &str = "zed-mesh";
+/// Version of the saved mesh format written by `save_mesh()`.
+const FILE_VERSION: u32 = 1;
+
+/// A node in a saved mesh file.
+#[derive(Serialize, Deserialize)]
+struct SavedNode<S> {
+    id: NodeId,
+    state: S,
+    connections: Vec<NodeId>,
+    /// Connections made by `connect_bidirectional()`
+    linked: Vec<NodeId>,
+}
+
+/// Saves the IDs, states and connections of a set of nodes to a file.
+///
+/// Connections to nodes outside the set are saved too, but cannot be
+/// restored by `load_mesh()`. Nodes are locked one at a time, so the file
+/// reflects each node at the moment it was saved.
+///
+/// The file has the same layout as saved timelines: a header line with a
+/// format version and a checksum, then a JSON body. It is written through a
+/// temporary file, so a crash mid-save keeps the old file.
+///
+/// # Errors
+///
+/// Returns an error if a state cannot be serialized or the file cannot be
+/// written.
+pub fn save_mesh<T>(
+    nodes: &[SharedStateNode<T>],
+    path: impl AsRef<Path>,
+) -> Result<(), PersistError>
+where
+    T: Clone + Serialize,
+{
+    let saved = nodes
+        .iter()
+        .map(|node| {
+            let node = lock(node);
+            let mut connections: Vec<NodeId> = node.connections.keys().cloned().collect();
+            connections.sort();
+            let mut linked: Vec<NodeId> = node.linked.iter().cloned().collect();
+            linked.sort();
+            Ok(SavedNode {
+                id: node.id.clone(),
+                state: serde_json::to_value(&node.state)?,
+                connections,
+                linked,
+            })
+        })
+        .collect::<Result<Vec<SavedNode<Value>>, serde_json::Error>>()?;
+    let body = serde_json::to_string(&saved)?;
+    write_checked(path.as_ref(), FILE_MAGIC, FILE_VERSION, &body)
+}
+
+/// Loads the nodes saved by `save_mesh()`, in the order they were saved,
+/// and connects them as they were connected.
+///
+/// # Errors
+///
+/// * `PersistError::Corrupted` - The file is not a saved mesh, its checksum
+///   does not match, or two nodes have the same ID
+/// * `PersistError::Migration` - The file was written in another format
+///   version
+/// * `PersistError::Serde` - A state does not deserialize into `T`
+pub fn load_mesh<T>(path: impl AsRef<Path>) -> Result<Vec<SharedStateNode<T>>, PersistError>
+where
+    T: Clone + DeserializeOwned,
+{
+    let body = read_checked(path.as_ref(), FILE_MAGIC, FILE_VERSION)?;
+    let saved: Vec<SavedNode<T>> = serde_json::from_str(&body)?;
+
+    let mut topology = Vec::with_capacity(saved.len());
+    let mut nodes = Vec::with_capacity(saved.len());
+    let mut by_id = HashMap::with_capacity(saved.len());
+    for node in saved {
+        let shared = StateNode::new(node.id.clone(), node.state).into_shared();
+        if by_id.insert(node.id, Arc::clone(&shared)).is_some() {
+            return Err(PersistError::Corrupted("duplicate node ID".to_string()));
+        }
+        topology.push((node.connections, node.linked));
+        nodes.push(shared);
+    }
+
+    for (shared, (connections, linked)) in nodes.iter().zip(topology) {
+        let mut node = lock(shared);
+        for id in connections {
+            // A node connected to itself would deadlock when propagating
+            if id == node.id {
+                continue;
+            }
+            if let Some(other) = by_id.get(&id) {
+                node.connections.insert(id, Arc::clone(other));
+            }
+        }
+        let node = &mut *node;
+        node.linked = linked
+            .into_iter()
+            .filter(|id| node.connections.contains_key(id))
+            .collect();
+    }
+    Ok(nodes)
+}
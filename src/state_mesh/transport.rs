@@ -0,0 +1,602 @@
+//! # Transport Module
+//!
+//! Synchronization of [`StateNode`]s that live in different processes or on
+//! different machines.
+//!
+//! A [`Transport`] carries [`MeshMessage`]s to a remote peer: either a
+//! node's whole state or the changes since the state sent before, both as
+//! JSON. A [`MeshLink`] sits on top of a transport and takes care of the
+//! bookkeeping: it sends a node's state as a delta whenever the peer has
+//! already seen an earlier one, and rebuilds received states before handing
+//! them to the node's conflict resolver. Links can also
+//! [compress](super::compression) what they send, and reconcile keyed
+//! states by exchanging [Merkle](super::merkle) digests.
+//!
+//! Two transports are included:
+//!
+//! - `TcpTransport`, JSON Lines over TCP (requires the `tcp` feature)
+//! - `WebSocketTransport`, one JSON text message per update (requires the
+//!   `websocket` feature)
+//!
+//! Either can be wrapped in a [`secure`](super::secure) transport to
+//! encrypt and authenticate what it carries.
+//!
+//! ## Example
+//!
+//! ```rust
+//! # #[cfg(feature = "tcp")]
+//! # {
+//! use serde::{Deserialize, Serialize};
+//! use std::net::TcpListener;
+//! use zed::{MeshLink, StateNode, TcpTransport};
+//!
+//! #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+//! struct Document { content: String }
+//!
+//! let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+//! let mut client = MeshLink::new(TcpTransport::connect(listener.local_addr().unwrap()).unwrap());
+//! let mut server = MeshLink::new(TcpTransport::from_stream(listener.accept().unwrap().0).unwrap());
+//!
+//! let local = StateNode::new("laptop".to_string(), Document { content: "Hello".into() });
+//! let mut remote = StateNode::new("server".to_string(), Document { content: String::new() });
+//!
+//! client.send_state(&local).unwrap();
+//! while server.receive(&mut remote).unwrap() == 0 {}
+//! assert_eq!(remote.state, local.state);
+//! # }
+//! ```
+
+use super::compression::Compression;
+use super::{NodeId, StateNode};
+use crate::delta::JsonDelta;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+#[cfg(any(feature = "tcp", feature = "websocket"))]
+use std::net::{TcpStream, ToSocketAddrs};
+#[cfg(any(feature = "tcp", feature = "websocket"))]
+use std::time::Duration;
+
+/// Encoded messages shorter than this are sent uncompressed, as compressing
+/// them saves little or nothing.
+const MIN_COMPRESSED_LEN: usize = 256;
+
+/// How long `receive()` of the included transports waits for a message.
+#[cfg(any(feature = "tcp", feature = "websocket"))]
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// A message exchanged between nodes over a [`Transport`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MeshMessage {
+    /// A node's whole state
+    State {
+        /// ID of the sending node
+        from: NodeId,
+        /// The state, serialized as JSON
+        state: Value,
+    },
+    /// The changes to a node's state since the last message from it
+    Delta {
+        /// ID of the sending node
+        from: NodeId,
+        /// The changes to the previously sent state
+        delta: JsonDelta,
+    },
+    /// Asks for the Merkle digests of some nodes of one level of the
+    /// peer's tree, see `MeshLink::request_sync()`
+    DigestRequest {
+        /// The level, 0 being the root
+        depth: usize,
+        /// Indices of the nodes within the level
+        indices: Vec<usize>,
+    },
+    /// The Merkle digests asked for by a `DigestRequest`
+    Digests {
+        /// The level, 0 being the root
+        depth: usize,
+        /// Index and digest of every node asked for
+        digests: Vec<(usize, u64)>,
+    },
+    /// Asks for the entries of some buckets of the peer's Merkle tree
+    EntryRequest {
+        /// Indices of the buckets
+        buckets: Vec<usize>,
+    },
+    /// The entries asked for by an `EntryRequest`
+    Entries {
+        /// ID of the sending node
+        from: NodeId,
+        /// Indices of the buckets the entries are all of
+        buckets: Vec<usize>,
+        /// The key-value pairs, serialized as a JSON array
+        entries: Value,
+    },
+    /// The compression algorithms the sending link can decode, sent once
+    /// per connection by links with compression enabled
+    Hello {
+        /// The algorithms, which may be empty
+        compression: Vec<Compression>,
+    },
+    /// Any other message, compressed
+    Compressed {
+        /// The algorithm used
+        compression: Compression,
+        /// The compressed JSON message, encoded as base64
+        payload: String,
+    },
+    /// A handshake message of a `SecureTransport`
+    Handshake {
+        /// The Noise handshake message, encoded as base64
+        payload: String,
+    },
+    /// A message encrypted by a `SecureTransport`
+    Encrypted {
+        /// The encrypted JSON message, encoded as base64
+        payload: String,
+    },
+}
+
+/// A connection to a remote peer that carries [`MeshMessage`]s.
+pub trait Transport {
+    /// Sends a message to the peer.
+    fn send(&mut self, message: &MeshMessage) -> io::Result<()>;
+
+    /// Returns the next message received from the peer.
+    ///
+    /// # Returns
+    ///
+    /// `None` if no message arrived; implementations may wait briefly for
+    /// one first.
+    fn receive(&mut self) -> io::Result<Option<MeshMessage>>;
+}
+
+/// Synchronizes nodes with a remote peer over a [`Transport`].
+///
+/// The link remembers the last state it sent from every local node and the
+/// last state received from every remote node, so only changes have to
+/// travel.
+pub struct MeshLink<Tr> {
+    transport: Tr,
+    sent: HashMap<NodeId, Value>,
+    received: HashMap<NodeId, Value>,
+    /// Algorithm used once the peer supports it
+    compression: Option<Compression>,
+    hello_sent: bool,
+    /// Algorithms the peer announced it can decode
+    peer_compression: Vec<Compression>,
+    /// Authority granted to the states received from the peer
+    peer_authority: u32,
+}
+
+impl<Tr: Transport> MeshLink<Tr> {
+    /// Creates a link over a connected transport.
+    pub fn new(transport: Tr) -> Self {
+        Self {
+            transport,
+            sent: HashMap::new(),
+            received: HashMap::new(),
+            compression: None,
+            hello_sent: false,
+            peer_compression: Vec::new(),
+            peer_authority: 0,
+        }
+    }
+
+    /// Sets the authority received states are resolved with, see
+    /// `StateNode::set_authority_resolver()`. Defaults to 0.
+    ///
+    /// The authority is granted by this end of the link, e.g. to the link
+    /// a client opened to its server, rather than claimed by the peer.
+    pub fn peer_authority(mut self, authority: u32) -> Self {
+        self.peer_authority = authority;
+        self
+    }
+
+    /// Compresses the states and deltas this link sends with `compression`,
+    /// once the peer announced it can decode it.
+    ///
+    /// The link announces the algorithms it can decode with its first
+    /// message, and in reply to the peer's announcement. Until `receive()`
+    /// has seen the peer's announcement, or if the peer does not support
+    /// `compression`, messages are sent uncompressed. Messages shorter than
+    /// a few hundred bytes are always sent uncompressed.
+    ///
+    /// Peers running a version without compression fail on the
+    /// announcement, so only enable compression once every peer supports it.
+    pub fn compression(mut self, compression: Compression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    /// Returns the algorithm messages are compressed with, if both ends of
+    /// the link agreed on one.
+    pub fn negotiated_compression(&self) -> Option<Compression> {
+        self.compression.filter(|compression| {
+            compression.is_available() && self.peer_compression.contains(compression)
+        })
+    }
+
+    /// Sends a node's state to the peer.
+    ///
+    /// The first state of every node is sent whole, later ones as a delta
+    /// to the state of the same node sent before; nothing is sent if the
+    /// state did not change.
+    pub fn send_state<T>(&mut self, node: &StateNode<T>) -> io::Result<()>
+    where
+        T: Clone + Serialize,
+    {
+        let state = serde_json::to_value(&node.state)?;
+        let message = match self.sent.get(&node.id) {
+            Some(previous) => match JsonDelta::between(previous, &state) {
+                JsonDelta::Unchanged => return Ok(()),
+                delta => MeshMessage::Delta {
+                    from: node.id.clone(),
+                    delta,
+                },
+            },
+            None => MeshMessage::State {
+                from: node.id.clone(),
+                state: state.clone(),
+            },
+        };
+        let len = self.send(message)?;
+        node.record_sent_bytes(len);
+        self.sent.insert(node.id.clone(), state);
+        Ok(())
+    }
+
+    /// Sends the nodes' whole states on the next `send_state()`, e.g. after
+    /// the peer reconnected, and negotiates compression again.
+    pub fn resend_full_state(&mut self) {
+        self.sent.clear();
+        self.hello_sent = false;
+        self.peer_compression.clear();
+    }
+
+    /// Applies every waiting message to a node, like `resolve_conflict()`
+    /// with the remote state.
+    ///
+    /// # Returns
+    ///
+    /// The number of messages applied.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the transport fails, a received state does not
+    /// deserialize into `T`, a delta arrives from a node whose whole state
+    /// was never received, a compressed message cannot be decompressed, or
+    /// a Merkle sync message arrives, which only `receive_keyed()` answers.
+    pub fn receive<T>(&mut self, node: &mut StateNode<T>) -> io::Result<usize>
+    where
+        T: Clone + DeserializeOwned,
+    {
+        self.receive_with(node, |_, _, _| {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Merkle sync message received by receive() instead of receive_keyed()",
+            ))
+        })
+    }
+
+    /// Starts reconciling a keyed state with the peer's by exchanging
+    /// Merkle digests, so that only the entries that differ travel.
+    ///
+    /// The exchange takes a few round trips: both ends must call
+    /// `receive_keyed()` until it ends. Each round compares the digests of
+    /// one level of both trees and asks for the children of the nodes
+    /// that differ; at the buckets, the peer sends their entries, which are
+    /// resolved like a whole state from the peer, as with
+    /// `StateNode::sync_from()`. Nothing is exchanged past the root if
+    /// both states are equal.
+    pub fn request_sync(&mut self) -> io::Result<()> {
+        self.send(MeshMessage::DigestRequest {
+            depth: 0,
+            indices: vec![0],
+        })
+        .map(|_| ())
+    }
+
+    /// Like `receive()`, but also answers the Merkle sync messages started
+    /// by `request_sync()` on either end.
+    ///
+    /// # Returns
+    ///
+    /// The number of messages applied, counting received entries that
+    /// changed the state.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error like `receive()`, or if a sync message refers to
+    /// nodes the tree does not have or holds entries that do not
+    /// deserialize.
+    pub fn receive_keyed<K, V>(&mut self, node: &mut StateNode<BTreeMap<K, V>>) -> io::Result<usize>
+    where
+        K: Ord + Clone + Serialize + DeserializeOwned + Send + Sync + 'static,
+        V: Clone + Serialize + DeserializeOwned + PartialEq + Send + Sync + 'static,
+    {
+        self.receive_with(node, |link, node, message| {
+            let (reply, applied) = node.answer_sync(message, link.peer_authority)?;
+            if let Some(reply) = reply {
+                let len = link.send(reply)?;
+                node.record_sent_bytes(len);
+            }
+            Ok(usize::from(applied))
+        })
+    }
+
+    /// Applies every waiting message to a node, passing Merkle sync
+    /// messages to `sync`.
+    fn receive_with<T, F>(&mut self, node: &mut StateNode<T>, mut sync: F) -> io::Result<usize>
+    where
+        T: Clone + DeserializeOwned,
+        F: FnMut(&mut Self, &mut StateNode<T>, MeshMessage) -> io::Result<usize>,
+    {
+        let mut applied = 0;
+        while let Some(message) = self.transport.receive()? {
+            node.record_received_bytes(encoded_len(&message));
+            let message = match message {
+                MeshMessage::Hello { compression } => {
+                    self.peer_compression = compression;
+                    self.send_hello()?;
+                    continue;
+                }
+                MeshMessage::Compressed {
+                    compression,
+                    payload,
+                } => serde_json::from_slice(&compression.decompress(&payload)?)?,
+                message => message,
+            };
+            let (from, state) = match message {
+                MeshMessage::State { from, state } => (from, state),
+                MeshMessage::Delta { from, delta } => {
+                    let mut state = self.received.get(&from).cloned().ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("delta from {from} without a previous state"),
+                        )
+                    })?;
+                    delta.apply_to(&mut state);
+                    (from, state)
+                }
+                message @ (MeshMessage::DigestRequest { .. }
+                | MeshMessage::Digests { .. }
+                | MeshMessage::EntryRequest { .. }
+                | MeshMessage::Entries { .. }) => {
+                    applied += sync(self, node, message)?;
+                    continue;
+                }
+                MeshMessage::Hello { .. } | MeshMessage::Compressed { .. } => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "compressed message does not hold a state or delta",
+                    ));
+                }
+                MeshMessage::Handshake { .. } | MeshMessage::Encrypted { .. } => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "encrypted message outside a secure transport",
+                    ));
+                }
+            };
+            let remote = T::deserialize(&state)?;
+            node.receive_state(&from, remote, self.peer_authority);
+            self.received.insert(from, state);
+            applied += 1;
+        }
+        Ok(applied)
+    }
+
+    /// Returns the underlying transport.
+    pub fn transport(&self) -> &Tr {
+        &self.transport
+    }
+
+    /// Sends a message, compressed if negotiated.
+    ///
+    /// # Returns
+    ///
+    /// The encoded length of the message sent.
+    fn send(&mut self, message: MeshMessage) -> io::Result<u64> {
+        self.send_hello()?;
+        let message = self.compress(message)?;
+        self.transport.send(&message)?;
+        Ok(encoded_len(&message))
+    }
+
+    /// Announces the algorithms this link decodes, once per connection, if
+    /// compression is enabled.
+    fn send_hello(&mut self) -> io::Result<()> {
+        if self.compression.is_none() || self.hello_sent {
+            return Ok(());
+        }
+        self.transport.send(&MeshMessage::Hello {
+            compression: Compression::available(),
+        })?;
+        self.hello_sent = true;
+        Ok(())
+    }
+
+    /// Compresses a message if an algorithm was negotiated and it is long
+    /// enough to benefit.
+    fn compress(&self, message: MeshMessage) -> io::Result<MeshMessage> {
+        let Some(compression) = self.negotiated_compression() else {
+            return Ok(message);
+        };
+        let json = serde_json::to_vec(&message)?;
+        if json.len() < MIN_COMPRESSED_LEN {
+            return Ok(message);
+        }
+        Ok(MeshMessage::Compressed {
+            compression,
+            payload: compression.compress(&json)?,
+        })
+    }
+}
+
+/// Returns the size of a message encoded as JSON, without allocating it.
+fn encoded_len(message: &MeshMessage) -> u64 {
+    struct Counter(u64);
+
+    impl io::Write for Counter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0 += buf.len() as u64;
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut counter = Counter(0);
+    // A message that was sent or received encodes, and counting cannot fail
+    let _ = serde_json::to_writer(&mut counter, message);
+    counter.0
+}
+
+/// A [`Transport`] sending one JSON message per line over TCP. Requires the
+/// `tcp` feature.
+#[cfg(feature = "tcp")]
+pub struct TcpTransport {
+    stream: TcpStream,
+    /// Bytes received after the last complete line
+    buffer: Vec<u8>,
+}
+
+#[cfg(feature = "tcp")]
+impl TcpTransport {
+    /// Connects to a peer listening on the given address.
+    pub fn connect(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Self::from_stream(TcpStream::connect(addr)?)
+    }
+
+    /// Uses an established connection, e.g. one accepted from a
+    /// `TcpListener`.
+    pub fn from_stream(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(POLL_INTERVAL))?;
+        stream.set_nodelay(true)?;
+        Ok(Self {
+            stream,
+            buffer: Vec::new(),
+        })
+    }
+}
+
+#[cfg(feature = "tcp")]
+impl Transport for TcpTransport {
+    fn send(&mut self, message: &MeshMessage) -> io::Result<()> {
+        use std::io::Write;
+
+        let mut line = serde_json::to_vec(message)?;
+        line.push(b'\n');
+        self.stream.write_all(&line)
+    }
+
+    fn receive(&mut self) -> io::Result<Option<MeshMessage>> {
+        use std::io::Read;
+
+        loop {
+            if let Some(end) = self.buffer.iter().position(|&byte| byte == b'\n') {
+                let line: Vec<u8> = self.buffer.drain(..=end).collect();
+                return Ok(Some(serde_json::from_slice(&line[..end])?));
+            }
+            let mut chunk = [0; 4096];
+            match self.stream.read(&mut chunk) {
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(read) => self.buffer.extend_from_slice(&chunk[..read]),
+                Err(err)
+                    if matches!(
+                        err.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    return Ok(None);
+                }
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+/// A [`Transport`] sending one JSON text message per update over a
+/// WebSocket. Requires the `websocket` feature.
+#[cfg(feature = "websocket")]
+pub struct WebSocketTransport {
+    socket: tungstenite::WebSocket<TcpStream>,
+}
+
+#[cfg(feature = "websocket")]
+impl WebSocketTransport {
+    /// Connects to a peer accepting WebSocket connections on the given
+    /// address.
+    pub fn connect(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let stream = TcpStream::connect(addr)?;
+        let url = format!("ws://{}/", stream.peer_addr()?);
+        let (socket, _) =
+            tungstenite::client(url, stream).map_err(|err| io::Error::other(err.to_string()))?;
+        Self::from_socket(socket)
+    }
+
+    /// Completes the WebSocket handshake on a connection accepted from a
+    /// `TcpListener`.
+    pub fn accept(stream: TcpStream) -> io::Result<Self> {
+        // Accepted streams inherit non-blocking mode from their listener
+        stream.set_nonblocking(false)?;
+        let socket =
+            tungstenite::accept(stream).map_err(|err| io::Error::other(err.to_string()))?;
+        Self::from_socket(socket)
+    }
+
+    fn from_socket(socket: tungstenite::WebSocket<TcpStream>) -> io::Result<Self> {
+        // Set after the handshake, which must not time out halfway
+        socket.get_ref().set_read_timeout(Some(POLL_INTERVAL))?;
+        Ok(Self { socket })
+    }
+}
+
+#[cfg(feature = "websocket")]
+impl Transport for WebSocketTransport {
+    fn send(&mut self, message: &MeshMessage) -> io::Result<()> {
+        let text = serde_json::to_string(message)?;
+        self.socket
+            .send(tungstenite::Message::text(text))
+            .map_err(websocket_error)
+    }
+
+    fn receive(&mut self) -> io::Result<Option<MeshMessage>> {
+        loop {
+            match self.socket.read() {
+                Ok(tungstenite::Message::Text(text)) => {
+                    return Ok(Some(serde_json::from_str(text.as_str())?));
+                }
+                Ok(tungstenite::Message::Close(_)) => {
+                    return Err(io::ErrorKind::UnexpectedEof.into());
+                }
+                // Pings are answered by tungstenite itself
+                Ok(_) => {}
+                Err(tungstenite::Error::Io(err))
+                    if matches!(
+                        err.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    return Ok(None);
+                }
+                Err(err) => return Err(websocket_error(err)),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "websocket")]
+fn websocket_error(err: tungstenite::Error) -> io::Error {
+    match err {
+        tungstenite::Error::Io(err) => err,
+        err => io::Error::other(err.to_string()),
+    }
+}
@@ -0,0 +1,456 @@
+//! # Storage Module
+//!
+//! Storage backends used by the [`persist`](crate::persist) module.
+//!
+//! A [`StorageBackend`] loads, saves and clears a single state value. The
+//! crate ships the following backends:
+//!
+//! - [`JsonFileStorage`] - a pretty-printed JSON file, replaced atomically
+//! - [`MemoryStorage`] - an in-process slot, handy for tests
+//! - `SledStorage` - a key in a sled tree (with the `sled` feature)
+//! - `RedbStorage` - a key in a redb table (with the `redb` feature)
+//!
+//! [`VersionedStorage`] wraps any of them to tag the saved state with a
+//! schema version and migrate older saves when they are loaded.
+//!
+//! Custom backends (browser storage, a remote service, ...) only need to
+//! implement the three trait methods.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use zed::{MemoryStorage, StorageBackend};
+//!
+//! let storage = MemoryStorage::new();
+//! assert_eq!(storage.load().unwrap(), None);
+//!
+//! storage.save(&42).unwrap();
+//! assert_eq!(storage.load().unwrap(), Some(42));
+//!
+//! storage.clear().unwrap();
+//! assert_eq!(storage.load().unwrap(), None);
+//! ```
+
+use crate::persist::PersistError;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// A place where a single state value can be persisted.
+pub trait StorageBackend<State>: Send + Sync {
+    /// Loads the saved state, or `None` if nothing has been saved yet.
+    fn load(&self) -> Result<Option<State>, PersistError>;
+
+    /// Saves the state, replacing any previously saved one.
+    fn save(&self, state: &State) -> Result<(), PersistError>;
+
+    /// Removes the saved state. Clearing an empty storage is not an error.
+    fn clear(&self) -> Result<(), PersistError>;
+}
+
+/// Stores the state as pretty-printed JSON in a file.
+///
+/// The file is written through a temporary file next to it and renamed into
+/// place, so a crash mid-write never leaves a truncated state file behind.
+#[derive(Clone, Debug)]
+pub struct JsonFileStorage {
+    path: PathBuf,
+}
+
+impl JsonFileStorage {
+    /// Creates a storage writing to the given file.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Returns the file the state is written to.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl<State> StorageBackend<State> for JsonFileStorage
+where
+    State: Serialize + DeserializeOwned,
+{
+    fn load(&self) -> Result<Option<State>, PersistError> {
+        match fs::read(&self.path) {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn save(&self, state: &State) -> Result<(), PersistError> {
+        let json = serde_json::to_vec_pretty(state)?;
+        let mut tmp = self.path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        fs::write(&tmp, json)?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+
+    fn clear(&self) -> Result<(), PersistError> {
+        match fs::remove_file(&self.path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Keeps the state in memory.
+///
+/// Clones share the same slot, so a clone kept by a test can observe what a
+/// persisted store saved.
+pub struct MemoryStorage<State> {
+    slot: Arc<Mutex<Option<State>>>,
+}
+
+impl<State> MemoryStorage<State> {
+    /// Creates an empty storage.
+    pub fn new() -> Self {
+        Self {
+            slot: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Creates a storage that already holds a saved state.
+    pub fn with_state(state: State) -> Self {
+        Self {
+            slot: Arc::new(Mutex::new(Some(state))),
+        }
+    }
+}
+
+impl<State> Default for MemoryStorage<State> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<State> Clone for MemoryStorage<State> {
+    fn clone(&self) -> Self {
+        Self {
+            slot: Arc::clone(&self.slot),
+        }
+    }
+}
+
+impl<State> StorageBackend<State> for MemoryStorage<State>
+where
+    State: Clone + Send,
+{
+    fn load(&self) -> Result<Option<State>, PersistError> {
+        Ok(self
+            .slot
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone())
+    }
+
+    fn save(&self, state: &State) -> Result<(), PersistError> {
+        *self.slot.lock().unwrap_or_else(PoisonError::into_inner) = Some(state.clone());
+        Ok(())
+    }
+
+    fn clear(&self) -> Result<(), PersistError> {
+        *self.slot.lock().unwrap_or_else(PoisonError::into_inner) = None;
+        Ok(())
+    }
+}
+
+type Migration = Box<dyn Fn(Value) -> Value + Send + Sync>;
+
+const VERSION_KEY: &str = "schema_version";
+const STATE_KEY: &str = "state";
+
+/// Tags the saved state with a schema version and migrates older saves.
+///
+/// The state is saved as `{ "schema_version": n, "state": ... }` through an
+/// inner backend storing JSON values. On load, registered migrations are
+/// applied one after the other until the saved data reaches the current
+/// version, and only then is it deserialized into the current state type.
+/// Data saved without a version (e.g. before versioning was introduced) is
+/// treated as version 0.
+///
+/// # Example
+///
+/// ```rust
+/// use serde::{Deserialize, Serialize};
+/// use serde_json::json;
+/// use zed::{MemoryStorage, StorageBackend, VersionedStorage};
+///
+/// // Version 2 split `name` into first and last name
+/// #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// struct User { first: String, last: String }
+///
+/// let inner = MemoryStorage::with_state(json!({ "schema_version": 1, "state": { "name": "Ada Lovelace" } }));
+/// let storage = VersionedStorage::new(inner, 2).register_migration(1, 2, |mut state| {
+///     let name = state["name"].take();
+///     let (first, last) = name.as_str().unwrap_or_default().split_once(' ').unwrap_or_default();
+///     json!({ "first": first, "last": last })
+/// });
+///
+/// let user: User = storage.load().unwrap().unwrap();
+/// assert_eq!(user, User { first: "Ada".into(), last: "Lovelace".into() });
+/// ```
+pub struct VersionedStorage<B> {
+    inner: B,
+    version: u32,
+    migrations: BTreeMap<u32, (u32, Migration)>,
+}
+
+impl<B> VersionedStorage<B>
+where
+    B: StorageBackend<Value>,
+{
+    /// Wraps a backend, saving states tagged with `version`.
+    pub fn new(inner: B, version: u32) -> Self {
+        Self {
+            inner,
+            version,
+            migrations: BTreeMap::new(),
+        }
+    }
+
+    /// Registers a migration from one schema version to a later one.
+    ///
+    /// The migration receives the saved state as a JSON value and returns
+    /// it in the shape expected by version `to`. Registering a second
+    /// migration from the same version replaces the first.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - The version the migration applies to
+    /// * `to` - The version the migrated data conforms to; must be greater than `from`
+    /// * `migrate` - Transforms the saved state
+    ///
+    /// # Panics
+    ///
+    /// Panics if `to` is not greater than `from`.
+    pub fn register_migration<F>(mut self, from: u32, to: u32, migrate: F) -> Self
+    where
+        F: Fn(Value) -> Value + Send + Sync + 'static,
+    {
+        assert!(to > from, "migration must move to a later version");
+        self.migrations.insert(from, (to, Box::new(migrate)));
+        self
+    }
+
+    /// Returns the schema version states are saved with.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Brings saved data up to the current version.
+    fn migrate(&self, saved: Value) -> Result<Value, PersistError> {
+        let (mut version, mut state) = match saved {
+            Value::Object(mut envelope)
+                if envelope.len() == 2 && envelope.contains_key(STATE_KEY) =>
+            {
+                match envelope.get(VERSION_KEY).and_then(Value::as_u64) {
+                    Some(version) => (
+                        u32::try_from(version).unwrap_or(u32::MAX),
+                        envelope.remove(STATE_KEY).unwrap_or_default(),
+                    ),
+                    None => (0, Value::Object(envelope)),
+                }
+            }
+            unversioned => (0, unversioned),
+        };
+
+        while version < self.version {
+            let Some((to, migrate)) = self.migrations.get(&version) else {
+                break;
+            };
+            state = migrate(state);
+            version = *to;
+        }
+
+        if version != self.version {
+            return Err(PersistError::Migration {
+                from: version,
+                to: self.version,
+            });
+        }
+        Ok(state)
+    }
+}
+
+impl<State, B> StorageBackend<State> for VersionedStorage<B>
+where
+    State: Serialize + DeserializeOwned,
+    B: StorageBackend<Value>,
+{
+    fn load(&self) -> Result<Option<State>, PersistError> {
+        match self.inner.load()? {
+            Some(saved) => Ok(Some(serde_json::from_value(self.migrate(saved)?)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn save(&self, state: &State) -> Result<(), PersistError> {
+        let mut envelope = serde_json::Map::new();
+        envelope.insert(VERSION_KEY.to_string(), Value::from(self.version));
+        envelope.insert(STATE_KEY.to_string(), serde_json::to_value(state)?);
+        self.inner.save(&Value::Object(envelope))
+    }
+
+    fn clear(&self) -> Result<(), PersistError> {
+        self.inner.clear()
+    }
+}
+
+#[cfg(feature = "sled")]
+pub use self::sled_backend::SledStorage;
+
+#[cfg(feature = "sled")]
+mod sled_backend {
+    use super::StorageBackend;
+    use crate::persist::PersistError;
+    use serde::Serialize;
+    use serde::de::DeserializeOwned;
+    use std::path::Path;
+
+    /// Stores the state as JSON under a key of a sled tree.
+    ///
+    /// Requires the `sled` feature.
+    #[derive(Clone)]
+    pub struct SledStorage {
+        tree: sled::Tree,
+        key: String,
+    }
+
+    impl SledStorage {
+        /// Uses the given key of an existing sled tree.
+        pub fn new(tree: sled::Tree, key: impl Into<String>) -> Self {
+            Self {
+                tree,
+                key: key.into(),
+            }
+        }
+
+        /// Opens (or creates) a sled database and uses a key of its default tree.
+        pub fn open(path: impl AsRef<Path>, key: impl Into<String>) -> Result<Self, PersistError> {
+            let db = sled::open(path).map_err(PersistError::backend)?;
+            Ok(Self::new(sled::Tree::clone(&db), key))
+        }
+    }
+
+    impl<State> StorageBackend<State> for SledStorage
+    where
+        State: Serialize + DeserializeOwned,
+    {
+        fn load(&self) -> Result<Option<State>, PersistError> {
+            match self.tree.get(&self.key).map_err(PersistError::backend)? {
+                Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+                None => Ok(None),
+            }
+        }
+
+        fn save(&self, state: &State) -> Result<(), PersistError> {
+            let json = serde_json::to_vec(state)?;
+            self.tree
+                .insert(&self.key, json)
+                .map_err(PersistError::backend)?;
+            self.tree.flush().map_err(PersistError::backend)?;
+            Ok(())
+        }
+
+        fn clear(&self) -> Result<(), PersistError> {
+            self.tree.remove(&self.key).map_err(PersistError::backend)?;
+            self.tree.flush().map_err(PersistError::backend)?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "redb")]
+pub use self::redb_backend::RedbStorage;
+
+#[cfg(feature = "redb")]
+mod redb_backend {
+    use super::StorageBackend;
+    use crate::persist::PersistError;
+    use redb::{Database, TableDefinition, TableError};
+    use serde::Serialize;
+    use serde::de::DeserializeOwned;
+    use std::path::Path;
+    use std::sync::Arc;
+
+    const TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("zed_state");
+
+    /// Stores the state as JSON under a key of a redb table.
+    ///
+    /// Requires the `redb` feature.
+    #[derive(Clone)]
+    pub struct RedbStorage {
+        db: Arc<Database>,
+        key: String,
+    }
+
+    impl RedbStorage {
+        /// Uses the given key of an existing redb database.
+        pub fn new(db: Arc<Database>, key: impl Into<String>) -> Self {
+            Self {
+                db,
+                key: key.into(),
+            }
+        }
+
+        /// Opens (or creates) a redb database file and uses one of its keys.
+        pub fn open(path: impl AsRef<Path>, key: impl Into<String>) -> Result<Self, PersistError> {
+            let db = Database::create(path).map_err(PersistError::backend)?;
+            Ok(Self::new(Arc::new(db), key))
+        }
+    }
+
+    impl<State> StorageBackend<State> for RedbStorage
+    where
+        State: Serialize + DeserializeOwned,
+    {
+        fn load(&self) -> Result<Option<State>, PersistError> {
+            let txn = self.db.begin_read().map_err(PersistError::backend)?;
+            let table = match txn.open_table(TABLE) {
+                Ok(table) => table,
+                Err(TableError::TableDoesNotExist(_)) => return Ok(None),
+                Err(err) => return Err(PersistError::backend(err)),
+            };
+            match table
+                .get(self.key.as_str())
+                .map_err(PersistError::backend)?
+            {
+                Some(bytes) => Ok(Some(serde_json::from_slice(bytes.value())?)),
+                None => Ok(None),
+            }
+        }
+
+        fn save(&self, state: &State) -> Result<(), PersistError> {
+            let json = serde_json::to_vec(state)?;
+            let txn = self.db.begin_write().map_err(PersistError::backend)?;
+            {
+                let mut table = txn.open_table(TABLE).map_err(PersistError::backend)?;
+                table
+                    .insert(self.key.as_str(), json.as_slice())
+                    .map_err(PersistError::backend)?;
+            }
+            txn.commit().map_err(PersistError::backend)
+        }
+
+        fn clear(&self) -> Result<(), PersistError> {
+            let txn = self.db.begin_write().map_err(PersistError::backend)?;
+            {
+                let mut table = txn.open_table(TABLE).map_err(PersistError::backend)?;
+                table
+                    .remove(self.key.as_str())
+                    .map_err(PersistError::backend)?;
+            }
+            txn.commit().map_err(PersistError::backend)
+        }
+    }
+}
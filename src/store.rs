@@ -4,11 +4,39 @@
 //!
 //! ## Features
 //!
-//! - Thread-safe with `Arc<Mutex<T>>`
-//! - Subscribe/unsubscribe to state changes
-//! - Batch dispatch operations
+//! - Thread-safe, with lock-free state reads backed by atomically swapped `Arc<State>` snapshots
+//! - Subscribe/unsubscribe to state changes, or hold an RAII `SubscriptionGuard`
+//! - Weak subscriptions that expire together with their owner
+//! - Pausing and resuming individual subscribers
+//! - Deterministic subscriber order, with optional priorities
+//! - Subscribers may dispatch follow-up actions without deadlocking
+//! - Optimistic updates that can be committed or reverted
+//! - Selector-based subscriptions that only fire on relevant changes
+//! - Predicate-filtered subscriptions
+//! - Dispatch reporting whether the state changed
+//! - Batch dispatch operations, from a list of actions or through a `BatchGuard`
+//! - Prioritized dispatch through an action queue
 //! - Dynamic reducer replacement
-//! - Read-only state access
+//! - Wholesale state replacement for hydration
+//! - Deterministic replay of recorded sessions
+//! - Optional time travel with undo, redo and jumps through the history
+//! - Optional per-action metrics
+//! - `tracing` spans for dispatch, reducers and notifications (with the `tracing` feature)
+//! - Optional change detection to skip no-op notifications
+//! - Read-only state access, through closures or a borrowed `StateGuard`
+//! - Async thunks via `dispatch_async`
+//! - Saga workflows via `run_saga`
+//! - Cancellation tokens to abort in-flight async work
+//! - Fallible reducers with an error channel
+//! - Panic isolation for reducers and subscribers
+//! - Action listeners for declarative side effects
+//! - Effects that dispatch follow-up actions
+//! - Scoped child stores focused on a slice of the state
+//! - Read-only projected stores derived from the state
+//! - Optional background thread for subscriber notifications, optionally coalescing bursts
+//! - Optional parallel fan-out to many subscribers
+//! - `StoreBuilder` to configure notifications, panic policy and capacity in one place
+//! - Tokio `watch`/`broadcast` channels (with the `async` feature)
 //!
 //! ## Example
 //!
@@ -49,17 +77,674 @@
 //! # }
 //! ```
 
-use crate::reducer::Reducer;
-use std::collections::HashMap;
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::{Arc, Mutex};
+use crate::cancellation::CancellationToken;
+use crate::effect::Effect;
+use crate::executor;
+use crate::metrics::{MetricsCollector, StoreMetrics};
+use crate::middleware::Middleware;
+use crate::optimistic::{OptimisticHandle, OptimisticId, OptimisticLog};
+use crate::projection::{ProjectedStore, Projection};
+use crate::queue::{ActionQueue, Priority};
+use crate::recorder::{ActionLog, ReplayMismatch};
+use crate::reducer::{Reducer, ReducerError};
+use crate::saga::{Saga, SagaHandle};
+use crate::scoped::{Lens, ScopedStore};
+use crate::time_travel::{TimeTravel, TimeTravelConfig};
+use crate::worker::Worker;
+use arc_swap::ArcSwap;
+#[cfg(feature = "async")]
+use futures_core::Stream;
+use std::cell::Cell;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::future::Future;
+#[cfg(feature = "async")]
+use std::future::poll_fn;
+use std::ops::{Deref, DerefMut};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError, Weak};
+#[cfg(feature = "async")]
+use std::task::Poll;
+use std::thread::{self, JoinHandle, ThreadId};
+use std::time::Instant;
+#[cfg(feature = "async")]
+use tokio::sync::{broadcast, watch};
 
 /// Type alias for subscription IDs
 pub type SubscriptionId = usize;
 
-type SharedState<S> = Arc<Mutex<S>>;
-type Subscriber<State> = Box<dyn Fn(&State) + Send + Sync>;
-type SubscriberMap<State> = Arc<Mutex<HashMap<SubscriptionId, Subscriber<State>>>>;
+/// Handle passed to async thunks for dispatching actions back into the store.
+pub type Dispatch<Action> = Arc<dyn Fn(Action) + Send + Sync>;
+
+/// Handle passed to async thunks for reading the latest state.
+pub type GetState<State> = Arc<dyn Fn() -> State + Send + Sync>;
+
+type SharedState<S> = Arc<ArcSwap<S>>;
+/// A state subscriber; returns `false` once it has expired and should be removed.
+type Subscriber<State> = Arc<dyn Fn(&State) -> bool + Send + Sync>;
+type SubscriberMap<State> = Arc<Mutex<SubscriberList<State>>>;
+type ErrorSubscriber = Arc<dyn Fn(&ReducerError) + Send + Sync>;
+type ErrorSubscriberMap = Arc<Mutex<HashMap<SubscriptionId, ErrorSubscriber>>>;
+type Listener<State, Action> = Arc<dyn Fn(&Action, &Store<State, Action>) + Send + Sync>;
+type ListenerMap<State, Action> = Arc<Mutex<ListenerList<State, Action>>>;
+type MiddlewareChain<State, Action> = Arc<ArcSwap<Vec<Arc<dyn Middleware<State, Action>>>>>;
+type ActionObserver<State, Action> = Arc<dyn Fn(&Action, &State) + Send + Sync>;
+type ActionObservers<State, Action> =
+    Arc<ArcSwap<Vec<(SubscriptionId, ActionObserver<State, Action>)>>>;
+type FollowUps<Action> = Arc<Mutex<HashMap<ThreadId, VecDeque<Action>>>>;
+type Transitions<State> = Arc<TransitionSlots<State>>;
+type StateEq<State> = Arc<dyn Fn(&State, &State) -> bool + Send + Sync>;
+type BackgroundNotify<State> = Arc<dyn Fn(Arc<State>, Delivery<State>) + Send + Sync>;
+
+/// Capacity of the broadcast channel returned by `Store::action_stream()`.
+///
+/// Receivers that fall further behind than this miss the oldest actions and
+/// observe a `RecvError::Lagged` error.
+#[cfg(feature = "async")]
+pub const ACTION_STREAM_CAPACITY: usize = 1024;
+
+/// Largest batch `Store::dispatch_from_stream()` applies at once.
+///
+/// Bounds how long a stream that is always ready delays notifications.
+#[cfg(feature = "async")]
+pub const STREAM_BATCH_LIMIT: usize = 256;
+
+/// Unsubscribes a subscriber when dropped.
+///
+/// Created by `Store::subscribe_scoped()`. Keep the guard alive for as long
+/// as the subscription should stay active, e.g. as a field of the component
+/// that owns the callback.
+#[must_use = "the subscription is removed as soon as the guard is dropped"]
+pub struct SubscriptionGuard {
+    id: SubscriptionId,
+    unsubscribe: Option<Box<dyn FnOnce(SubscriptionId) + Send + Sync>>,
+}
+
+impl SubscriptionGuard {
+    /// Returns the ID of the guarded subscription.
+    pub fn id(&self) -> SubscriptionId {
+        self.id
+    }
+
+    /// Releases the guard without unsubscribing.
+    ///
+    /// The subscription then lives until it is removed manually with
+    /// `Store::unsubscribe()`.
+    pub fn detach(mut self) -> SubscriptionId {
+        self.unsubscribe = None;
+        self.id
+    }
+}
+
+impl Drop for SubscriptionGuard {
+    fn drop(&mut self) {
+        if let Some(unsubscribe) = self.unsubscribe.take() {
+            unsubscribe(self.id);
+        }
+    }
+}
+
+impl fmt::Debug for SubscriptionGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SubscriptionGuard")
+            .field("id", &self.id)
+            .field("active", &self.unsubscribe.is_some())
+            .finish()
+    }
+}
+
+/// Borrowed access to a state snapshot.
+///
+/// Created by `Store::read()`. The guard dereferences to the state as it was
+/// when `read()` was called; dispatches made while it is alive store a new
+/// snapshot and never wait for the guard, so it should not be used to
+/// observe later changes.
+pub struct StateGuard<State> {
+    snapshot: arc_swap::Guard<Arc<State>>,
+}
+
+impl<State> StateGuard<State> {
+    /// Converts the guard into a shared handle to the same snapshot.
+    pub fn into_arc(self) -> Arc<State> {
+        arc_swap::Guard::into_inner(self.snapshot)
+    }
+}
+
+impl<State> Deref for StateGuard<State> {
+    type Target = State;
+
+    fn deref(&self) -> &State {
+        &self.snapshot
+    }
+}
+
+impl<State: fmt::Debug> fmt::Debug for StateGuard<State> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// The result of an action applied with `Store::dispatch_checked()`.
+#[derive(Clone)]
+pub struct DispatchOutcome<State> {
+    changed: bool,
+    state: Arc<State>,
+}
+
+impl<State> DispatchOutcome<State> {
+    /// Returns `true` if the action stored a new state.
+    ///
+    /// With change detection enabled, an action producing an equal state
+    /// leaves the store unchanged; otherwise every applied action counts as
+    /// a change.
+    pub fn changed(&self) -> bool {
+        self.changed
+    }
+
+    /// Returns the state right after the action was applied.
+    pub fn state(&self) -> &Arc<State> {
+        &self.state
+    }
+
+    /// Converts the outcome into a shared handle to the state.
+    pub fn into_state(self) -> Arc<State> {
+        self.state
+    }
+}
+
+impl<State> Deref for DispatchOutcome<State> {
+    type Target = State;
+
+    fn deref(&self) -> &State {
+        &self.state
+    }
+}
+
+impl<State: fmt::Debug> fmt::Debug for DispatchOutcome<State> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DispatchOutcome")
+            .field("changed", &self.changed)
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+/// Determines how a store reacts to panics in reducers and subscribers.
+///
+/// Regardless of the policy, a panic never leaves the store in a broken
+/// state: the state is only replaced after the reducer returns, and locks
+/// poisoned by a panic are recovered on the next access.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PanicPolicy {
+    /// Let panics unwind to the caller of `dispatch` (the default).
+    /// Subscribers notified on a background thread panic there instead, and
+    /// the panic is re-raised by the next `Store::flush()`.
+    #[default]
+    Propagate,
+    /// Catch panics: a panicking reducer keeps the previous state and is
+    /// reported as a [`ReducerPanic`] error, and a panicking subscriber is
+    /// skipped while the remaining subscribers are still notified.
+    Recover,
+}
+
+/// Error reported when a reducer panics under [`PanicPolicy::Recover`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReducerPanic {
+    /// The panic message, if it was a string
+    pub message: String,
+}
+
+impl ReducerPanic {
+    fn from_payload(payload: Box<dyn std::any::Any + Send>) -> Self {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        Self { message }
+    }
+}
+
+impl fmt::Display for ReducerPanic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "reducer panicked: {}", self.message)
+    }
+}
+
+impl std::error::Error for ReducerPanic {}
+
+/// Determines on which thread and how often subscribers are notified.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NotificationMode {
+    /// Notify subscribers on the dispatching thread (the default).
+    #[default]
+    Sync,
+    /// Notify subscribers of every new state on a background thread.
+    Background,
+    /// Notify subscribers on a background thread, skipping intermediate
+    /// states: subscribers that fall behind only receive the latest state.
+    Coalesced,
+}
+
+/// Determines which lock serializes dispatches.
+///
+/// State reads never take a lock; this only affects how concurrent
+/// dispatches, batches and time travel wait for each other.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DispatchLock {
+    /// A plain mutex (the default): the fastest, but under contention one
+    /// thread may dispatch several times while others keep waiting.
+    #[default]
+    Mutex,
+    /// A fair lock: contending dispatches are applied in the order they
+    /// started waiting, at the cost of a handoff between threads.
+    Fair,
+}
+
+/// A FIFO ticket lock taken before the reducer mutex by fair stores.
+struct TicketLock {
+    /// The next ticket to hand out and the ticket being served
+    tickets: Mutex<(u64, u64)>,
+    turn: Condvar,
+}
+
+impl TicketLock {
+    fn new() -> Self {
+        Self {
+            tickets: Mutex::new((0, 0)),
+            turn: Condvar::new(),
+        }
+    }
+
+    fn acquire(&self) {
+        let mut tickets = lock(&self.tickets);
+        let ticket = tickets.0;
+        tickets.0 += 1;
+        let _tickets = self
+            .turn
+            .wait_while(tickets, |tickets| tickets.1 != ticket)
+            .unwrap_or_else(PoisonError::into_inner);
+    }
+
+    fn release(&self) {
+        lock(&self.tickets).1 += 1;
+        self.turn.notify_all();
+    }
+}
+
+type BoxedStoreReducer<State, Action> = Box<dyn Reducer<State, Action> + Send + Sync>;
+
+/// The locked reducer; holding it serializes dispatches.
+struct ReducerGuard<'a, State, Action> {
+    reducer: MutexGuard<'a, BoxedStoreReducer<State, Action>>,
+    fair: Option<&'a TicketLock>,
+}
+
+impl<State, Action> Deref for ReducerGuard<'_, State, Action> {
+    type Target = BoxedStoreReducer<State, Action>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.reducer
+    }
+}
+
+impl<State, Action> DerefMut for ReducerGuard<'_, State, Action> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.reducer
+    }
+}
+
+impl<State, Action> Drop for ReducerGuard<'_, State, Action> {
+    fn drop(&mut self) {
+        if let Some(fair) = self.fair {
+            fair.release();
+        }
+    }
+}
+
+/// Enters a `tracing` span at debug level when the `tracing` feature is
+/// enabled; expands to a no-op guard otherwise.
+macro_rules! enter_span {
+    ($($span:tt)*) => {{
+        #[cfg(feature = "tracing")]
+        let guard = tracing::debug_span!($($span)*).entered();
+        #[cfg(not(feature = "tracing"))]
+        let guard = NoSpan;
+        guard
+    }};
+}
+
+/// Stand-in for an entered span when the `tracing` feature is disabled.
+#[cfg(not(feature = "tracing"))]
+struct NoSpan;
+
+/// Locks a mutex, recovering the guard if a previous holder panicked.
+fn lock<T: ?Sized>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Runs a callback according to the panic policy.
+fn isolate<F: FnOnce()>(policy: PanicPolicy, f: F) {
+    match policy {
+        PanicPolicy::Propagate => f(),
+        PanicPolicy::Recover => {
+            let _ = panic::catch_unwind(AssertUnwindSafe(f));
+        }
+    }
+}
+
+/// A registered state subscriber and its pause status.
+struct SubscriberEntry<State> {
+    callback: Subscriber<State>,
+    paused: bool,
+    /// Whether a notification was skipped while paused
+    missed: bool,
+    /// Sequence number of the latest notification delivered
+    delivered: Arc<AtomicU64>,
+}
+
+impl<State> SubscriberEntry<State> {
+    fn new(callback: Subscriber<State>) -> Self {
+        Self {
+            callback,
+            paused: false,
+            missed: false,
+            delivered: Arc::new(AtomicU64::new(0)),
+        }
+    }
+}
+
+/// An active subscriber captured at the start of a notification.
+type SubscriberSnapshot<State> = (SubscriptionId, Subscriber<State>, Arc<AtomicU64>);
+
+/// State subscribers in invocation order.
+///
+/// Entries are kept sorted by descending priority; subscribers with equal
+/// priority stay in registration order.
+struct SubscriberList<State> {
+    entries: Vec<(SubscriptionId, i32, SubscriberEntry<State>)>,
+    /// Sequence number of the latest notification started
+    sequence: u64,
+}
+
+impl<State> SubscriberList<State> {
+    fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            sequence: 0,
+        }
+    }
+
+    fn insert(&mut self, id: SubscriptionId, priority: i32, entry: SubscriberEntry<State>) {
+        let index = self
+            .entries
+            .partition_point(|(_, existing, _)| *existing >= priority);
+        self.entries.insert(index, (id, priority, entry));
+    }
+
+    fn remove(&mut self, id: &SubscriptionId) -> Option<SubscriberEntry<State>> {
+        let index = self
+            .entries
+            .iter()
+            .position(|(existing, ..)| existing == id)?;
+        Some(self.entries.remove(index).2)
+    }
+
+    fn get_mut(&mut self, id: &SubscriptionId) -> Option<&mut SubscriberEntry<State>> {
+        self.entries
+            .iter_mut()
+            .find(|(existing, ..)| existing == id)
+            .map(|(_, _, entry)| entry)
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.entries.reserve(additional);
+    }
+
+    /// Starts a notification, marking paused subscribers as having missed it.
+    ///
+    /// Returns the notification's sequence number and the subscribers to call.
+    fn snapshot(&mut self) -> (u64, Vec<SubscriberSnapshot<State>>) {
+        self.sequence += 1;
+        let active = self
+            .entries
+            .iter_mut()
+            .filter_map(|(id, _, entry)| {
+                if entry.paused {
+                    entry.missed = true;
+                    return None;
+                }
+                Some((
+                    *id,
+                    Arc::clone(&entry.callback),
+                    Arc::clone(&entry.delivered),
+                ))
+            })
+            .collect();
+        (self.sequence, active)
+    }
+}
+
+/// Action listeners in registration order.
+struct ListenerList<State, Action> {
+    entries: Vec<(SubscriptionId, Listener<State, Action>)>,
+}
+
+impl<State, Action> ListenerList<State, Action> {
+    fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    fn insert(&mut self, id: SubscriptionId, listener: Listener<State, Action>) {
+        self.entries.push((id, listener));
+    }
+
+    fn remove(&mut self, id: &SubscriptionId) -> Option<Listener<State, Action>> {
+        let index = self
+            .entries
+            .iter()
+            .position(|(existing, _)| existing == id)?;
+        Some(self.entries.remove(index).1)
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.entries.reserve(additional);
+    }
+
+    fn snapshot(&self) -> Vec<Listener<State, Action>> {
+        self.entries
+            .iter()
+            .map(|(_, listener)| Arc::clone(listener))
+            .collect()
+    }
+}
+
+/// Runs a notification's subscribers spread over several threads.
+///
+/// Kept as a function pointer so that only stores enabling parallel fan-out
+/// need `State: Sync`.
+type FanOutFn<State> = fn(
+    &FanOut<State>,
+    &[SubscriberSnapshot<State>],
+    &Arc<State>,
+    &Notification<'_, State>,
+) -> Vec<SubscriptionId>;
+
+/// Parallel fan-out settings.
+struct FanOut<State> {
+    /// Threads kept for the store's lifetime, running all chunks but the
+    /// first one
+    workers: Arc<[Worker]>,
+    run: FanOutFn<State>,
+}
+
+impl<State> Clone for FanOut<State> {
+    fn clone(&self) -> Self {
+        Self {
+            workers: Arc::clone(&self.workers),
+            run: self.run,
+        }
+    }
+}
+
+/// How a notification calls its subscribers.
+struct Delivery<State> {
+    policy: PanicPolicy,
+    fan_out: Option<FanOut<State>>,
+}
+
+thread_local! {
+    /// Whether the current thread is running a fan-out chunk
+    static IN_FAN_OUT: Cell<bool> = const { Cell::new(false) };
+}
+
+/// A state notification in progress.
+struct Notification<'a, State> {
+    state: &'a State,
+    sequence: u64,
+    policy: PanicPolicy,
+}
+
+impl<State> Notification<'_, State> {
+    /// Calls one subscriber, returning `false` if it has expired.
+    fn call(&self, (_, callback, delivered): &SubscriberSnapshot<State>) -> bool {
+        if delivered.fetch_max(self.sequence, Ordering::SeqCst) > self.sequence {
+            return true;
+        }
+        let mut keep = true;
+        isolate(self.policy, || keep = callback(self.state));
+        keep
+    }
+
+    /// Calls subscribers in order, returning the IDs of expired ones.
+    fn call_all(&self, active: &[SubscriberSnapshot<State>]) -> Vec<SubscriptionId> {
+        active
+            .iter()
+            .filter(|subscriber| !self.call(subscriber))
+            .map(|(id, ..)| *id)
+            .collect()
+    }
+}
+
+/// Splits the subscribers into one chunk per thread, running the first
+/// chunk on the calling thread and the others on the store's fan-out
+/// threads, and waits for all of them.
+///
+/// A subscriber running on a fan-out thread that dispatches gets its
+/// notification inline, so fan-out threads never wait on each other.
+fn fan_out_parallel<State: Send + Sync + 'static>(
+    fan_out: &FanOut<State>,
+    active: &[SubscriberSnapshot<State>],
+    state: &Arc<State>,
+    notification: &Notification<'_, State>,
+) -> Vec<SubscriptionId> {
+    if IN_FAN_OUT.get() {
+        return notification.call_all(active);
+    }
+    let threads = fan_out.workers.len() + 1;
+    let mut chunks = active.chunks(active.len().div_ceil(threads).max(1));
+    let Some(first) = chunks.next() else {
+        return Vec::new();
+    };
+
+    let (sender, results) = mpsc::channel();
+    let mut pending = 0;
+    for (worker, chunk) in fan_out.workers.iter().zip(chunks) {
+        let chunk = chunk.to_vec();
+        let state = Arc::clone(state);
+        let sender = sender.clone();
+        let (sequence, policy) = (notification.sequence, notification.policy);
+        worker.submit(move || {
+            IN_FAN_OUT.set(true);
+            let notification = Notification {
+                state: &*state,
+                sequence,
+                policy,
+            };
+            let result = panic::catch_unwind(AssertUnwindSafe(|| notification.call_all(&chunk)));
+            IN_FAN_OUT.set(false);
+            let _ = sender.send(result);
+        });
+        pending += 1;
+    }
+
+    let mut expired = notification.call_all(first);
+    for result in results.iter().take(pending) {
+        match result {
+            Ok(ids) => expired.extend(ids),
+            Err(payload) => panic::resume_unwind(payload),
+        }
+    }
+    expired
+}
+
+/// Calls every active subscriber with the new state, dropping expired ones.
+///
+/// Subscribers are called on a snapshot of the list, without holding its
+/// lock, so they can dispatch, subscribe or unsubscribe. A subscriber that
+/// already received a newer state, e.g. from a dispatch it made itself, is
+/// not called with this older one.
+fn deliver<State>(
+    subscribers: &SubscriberMap<State>,
+    delivery: &Delivery<State>,
+    state: &Arc<State>,
+) {
+    let (sequence, active) = lock(subscribers).snapshot();
+    let notification = Notification {
+        state: &**state,
+        sequence,
+        policy: delivery.policy,
+    };
+
+    let expired = match &delivery.fan_out {
+        Some(fan_out) if active.len() > 1 => (fan_out.run)(fan_out, &active, state, &notification),
+        _ => notification.call_all(&active),
+    };
+
+    if !expired.is_empty() {
+        let mut subscribers = lock(subscribers);
+        for id in expired {
+            subscribers.remove(&id);
+        }
+    }
+}
+
+/// The states around an action, taken while the reducer lock was held.
+pub(crate) struct Transition<State> {
+    /// The state the reducer was given
+    pub(crate) before: Arc<State>,
+    /// The state stored after the action
+    pub(crate) after: Arc<State>,
+    /// When the dispatch acquired the reducer lock
+    pub(crate) started: Instant,
+}
+
+impl<State> Clone for Transition<State> {
+    fn clone(&self) -> Self {
+        Self {
+            before: Arc::clone(&self.before),
+            after: Arc::clone(&self.after),
+            started: self.started,
+        }
+    }
+}
+
+/// Per-thread slots filled by `Store::capture_transition()`.
+struct TransitionSlots<State> {
+    /// Number of captures in progress, so dispatches skip the map otherwise
+    active: AtomicUsize,
+    slots: Mutex<HashMap<ThreadId, Option<Transition<State>>>>,
+}
 
 /// Redux-like store for centralized state management.
 ///
@@ -68,57 +753,1667 @@ type SubscriberMap<State> = Arc<Mutex<HashMap<SubscriptionId, Subscriber<State>>
 /// - Subscriber notifications
 /// - Batch dispatch support
 /// - Dynamic reducer replacement
+///
+/// Cloning a store is cheap and yields another handle to the same state,
+/// reducer and subscribers.
 pub struct Store<State, Action> {
     state: SharedState<State>,
-    reducer: Arc<Mutex<Box<dyn Reducer<State, Action> + Send + Sync>>>,
+    reducer: Arc<Mutex<BoxedStoreReducer<State, Action>>>,
+    fair_lock: Option<Arc<TicketLock>>,
     subscribers: SubscriberMap<State>,
-    next_subscriber_id: AtomicUsize,
+    error_subscribers: ErrorSubscriberMap,
+    listeners: ListenerMap<State, Action>,
+    follow_ups: FollowUps<Action>,
+    transitions: Transitions<State>,
+    middleware: MiddlewareChain<State, Action>,
+    observers: ActionObservers<State, Action>,
+    next_subscriber_id: Arc<AtomicUsize>,
+    state_eq: Option<StateEq<State>>,
+    panic_policy: PanicPolicy,
+    worker: Option<Arc<Worker>>,
+    background: Option<BackgroundNotify<State>>,
+    fan_out: Option<FanOut<State>>,
+    optimistic: Arc<Mutex<OptimisticLog<State, Action>>>,
+    queue: Arc<Mutex<ActionQueue<Action>>>,
+    effects: Arc<Mutex<CancellationToken>>,
+    metrics: Option<Arc<MetricsCollector<Action>>>,
+    time_travel: Arc<OnceLock<Mutex<TimeTravel<State>>>>,
+    #[cfg(feature = "async")]
+    state_watch: Arc<OnceLock<watch::Sender<State>>>,
+    #[cfg(feature = "async")]
+    action_broadcast: Arc<OnceLock<broadcast::Sender<Action>>>,
+}
+
+impl<State, Action> Clone for Store<State, Action> {
+    fn clone(&self) -> Self {
+        Self {
+            state: Arc::clone(&self.state),
+            reducer: Arc::clone(&self.reducer),
+            fair_lock: self.fair_lock.clone(),
+            subscribers: Arc::clone(&self.subscribers),
+            error_subscribers: Arc::clone(&self.error_subscribers),
+            listeners: Arc::clone(&self.listeners),
+            follow_ups: Arc::clone(&self.follow_ups),
+            transitions: Arc::clone(&self.transitions),
+            middleware: Arc::clone(&self.middleware),
+            observers: Arc::clone(&self.observers),
+            next_subscriber_id: Arc::clone(&self.next_subscriber_id),
+            state_eq: self.state_eq.clone(),
+            panic_policy: self.panic_policy,
+            worker: self.worker.clone(),
+            background: self.background.clone(),
+            fan_out: self.fan_out.clone(),
+            optimistic: Arc::clone(&self.optimistic),
+            queue: Arc::clone(&self.queue),
+            effects: Arc::clone(&self.effects),
+            metrics: self.metrics.clone(),
+            time_travel: Arc::clone(&self.time_travel),
+            #[cfg(feature = "async")]
+            state_watch: Arc::clone(&self.state_watch),
+            #[cfg(feature = "async")]
+            action_broadcast: Arc::clone(&self.action_broadcast),
+        }
+    }
 }
 
-impl<State: Clone + Send + 'static, Action: Send + 'static> Store<State, Action> {
-    /// Creates a new store with the given initial state and reducer.
+impl<State: Clone + Send + 'static, Action: Send + 'static> Store<State, Action> {
+    /// Creates a new store with the given initial state and reducer.
+    ///
+    /// # Arguments
+    ///
+    /// * `initial_state` - The initial state of the store
+    /// * `reducer` - A boxed reducer that handles state transitions
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use zed::{Store, create_reducer};
+    ///
+    /// #[derive(Clone)]
+    /// struct State { count: i32 }
+    ///
+    /// #[derive(Clone)]
+    /// enum Action { Increment }
+    ///
+    /// let store = Store::new(
+    ///     State { count: 0 },
+    ///     Box::new(create_reducer(|state: &State, _: &Action| State { count: state.count + 1 }))
+    /// );
+    /// ```
+    pub fn new(
+        initial_state: State,
+        reducer: Box<dyn Reducer<State, Action> + Send + Sync>,
+    ) -> Self {
+        Self {
+            state: Arc::new(ArcSwap::from_pointee(initial_state)),
+            reducer: Arc::new(Mutex::new(reducer)),
+            fair_lock: None,
+            subscribers: Arc::new(Mutex::new(SubscriberList::new())),
+            error_subscribers: Arc::new(Mutex::new(HashMap::new())),
+            listeners: Arc::new(Mutex::new(ListenerList::new())),
+            follow_ups: Arc::new(Mutex::new(HashMap::new())),
+            transitions: Arc::new(TransitionSlots {
+                active: AtomicUsize::new(0),
+                slots: Mutex::new(HashMap::new()),
+            }),
+            middleware: Arc::new(ArcSwap::from_pointee(Vec::new())),
+            observers: Arc::new(ArcSwap::from_pointee(Vec::new())),
+            next_subscriber_id: Arc::new(AtomicUsize::new(0)),
+            state_eq: None,
+            panic_policy: PanicPolicy::default(),
+            worker: None,
+            background: None,
+            fan_out: None,
+            optimistic: Arc::new(Mutex::new(OptimisticLog::new())),
+            queue: Arc::new(Mutex::new(ActionQueue::new())),
+            effects: Arc::new(Mutex::new(CancellationToken::new())),
+            metrics: None,
+            time_travel: Arc::new(OnceLock::new()),
+            #[cfg(feature = "async")]
+            state_watch: Arc::new(OnceLock::new()),
+            #[cfg(feature = "async")]
+            action_broadcast: Arc::new(OnceLock::new()),
+        }
+    }
+
+    /// Starts configuring a store with the given initial state and reducer.
+    ///
+    /// # Arguments
+    ///
+    /// * `initial_state` - The initial state of the store
+    /// * `reducer` - The reducer handling state transitions
+    ///
+    /// # Returns
+    ///
+    /// A [`StoreBuilder`]; call `build()` to create the store.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use zed::{DispatchLock, NotificationMode, PanicPolicy, Store, create_reducer};
+    ///
+    /// #[derive(Clone, PartialEq)]
+    /// struct State { count: i32 }
+    ///
+    /// enum Action { Increment }
+    ///
+    /// let store = Store::builder(
+    ///     State { count: 0 },
+    ///     create_reducer(|state: &State, _: &Action| State { count: state.count + 1 }),
+    /// )
+    /// .dispatch_lock(DispatchLock::Fair)
+    /// .panic_policy(PanicPolicy::Recover)
+    /// .notification_mode(NotificationMode::Coalesced)
+    /// .subscriber_capacity(64)
+    /// .change_detection()
+    /// .build();
+    ///
+    /// store.dispatch(Action::Increment);
+    /// assert_eq!(store.get_state().count, 1);
+    /// ```
+    pub fn builder<R>(initial_state: State, reducer: R) -> StoreBuilder<State, Action>
+    where
+        R: Reducer<State, Action> + Send + Sync + 'static,
+    {
+        StoreBuilder {
+            store: Store::new(initial_state, Box::new(reducer)),
+        }
+    }
+
+    /// Moves subscriber notifications to a background worker thread.
+    ///
+    /// In this mode `dispatch` only enqueues the new state; a dedicated worker
+    /// thread notifies subscribers in dispatch order, so slow subscribers no
+    /// longer block the dispatching thread. Call `flush()` to wait until all
+    /// queued notifications have been delivered.
+    ///
+    /// Error subscribers and action listeners still run synchronously.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use std::sync::{Arc, Mutex};
+    /// # use zed::{Store, create_reducer};
+    /// # #[derive(Clone)] struct State { count: i32 }
+    /// # #[derive(Clone)] enum Action { Increment }
+    /// let store = Store::new(
+    ///     State { count: 0 },
+    ///     Box::new(create_reducer(|state: &State, _: &Action| State { count: state.count + 1 })),
+    /// )
+    /// .with_background_notifications();
+    ///
+    /// let seen = Arc::new(Mutex::new(Vec::new()));
+    /// let seen_clone = Arc::clone(&seen);
+    /// store.subscribe(move |state: &State| seen_clone.lock().unwrap().push(state.count));
+    ///
+    /// store.dispatch(Action::Increment);
+    /// store.dispatch(Action::Increment);
+    ///
+    /// store.flush();
+    /// assert_eq!(*seen.lock().unwrap(), vec![1, 2]);
+    /// ```
+    pub fn with_background_notifications(self) -> Self
+    where
+        State: Sync,
+    {
+        self.with_notification_mode(NotificationMode::Background)
+    }
+
+    /// Internal helper to set up the notification thread for a mode
+    fn with_notification_mode(mut self, mode: NotificationMode) -> Self
+    where
+        State: Sync,
+    {
+        if mode == NotificationMode::Sync {
+            self.worker = None;
+            self.background = None;
+            return self;
+        }
+
+        let worker = Arc::new(Worker::spawn("zed-notifier"));
+        let subscribers = Arc::clone(&self.subscribers);
+        let queue = Arc::clone(&worker);
+
+        self.background = Some(match mode {
+            NotificationMode::Coalesced => {
+                // Holds the latest state until the queued notification runs
+                let latest: Arc<Mutex<Option<Arc<State>>>> = Arc::new(Mutex::new(None));
+                Arc::new(move |state: Arc<State>, delivery| {
+                    if lock(&latest).replace(state).is_some() {
+                        return;
+                    }
+                    let subscribers = Arc::clone(&subscribers);
+                    let latest = Arc::clone(&latest);
+                    queue.submit(move || {
+                        let state = lock(&latest).take();
+                        if let Some(state) = state {
+                            deliver(&subscribers, &delivery, &state);
+                        }
+                    });
+                })
+            }
+            _ => Arc::new(move |state: Arc<State>, delivery| {
+                let subscribers = Arc::clone(&subscribers);
+                queue.submit(move || deliver(&subscribers, &delivery, &state));
+            }),
+        });
+        self.worker = Some(worker);
+        self
+    }
+
+    /// Notifies subscribers in parallel on up to `threads` threads.
+    ///
+    /// Spawns `threads - 1` threads kept for the store's lifetime. Each
+    /// notification splits the subscribers into one group per thread, runs
+    /// the first group on the notifying thread, and waits for all groups to
+    /// finish, so `dispatch` (or the background
+    /// worker) still returns only once every subscriber has run. Useful for
+    /// stores with many slow subscribers; subscribers then run concurrently
+    /// and no longer in priority or registration order. A value below 2
+    /// disables parallel fan-out.
+    ///
+    /// # Arguments
+    ///
+    /// * `threads` - The maximum number of threads per notification
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use std::sync::Arc;
+    /// # use std::sync::atomic::{AtomicUsize, Ordering};
+    /// # use zed::{Store, create_reducer};
+    /// # #[derive(Clone)] struct State { count: i32 }
+    /// # #[derive(Clone)] enum Action { Increment }
+    /// # let store = Store::new(State { count: 0 }, Box::new(create_reducer(|state: &State, _: &Action| State { count: state.count + 1 })));
+    /// let store = store.with_parallel_notifications(4);
+    ///
+    /// let calls = Arc::new(AtomicUsize::new(0));
+    /// for _ in 0..100 {
+    ///     let calls = Arc::clone(&calls);
+    ///     store.subscribe(move |_: &State| {
+    ///         calls.fetch_add(1, Ordering::SeqCst);
+    ///     });
+    /// }
+    ///
+    /// store.dispatch(Action::Increment);
+    /// assert_eq!(calls.load(Ordering::SeqCst), 100);
+    /// ```
+    pub fn with_parallel_notifications(mut self, threads: usize) -> Self
+    where
+        State: Sync,
+    {
+        self.fan_out = (threads > 1).then(|| FanOut {
+            workers: (1..threads).map(|_| Worker::spawn("zed-fan-out")).collect(),
+            run: fan_out_parallel::<State>,
+        });
+        self
+    }
+
+    /// Waits until all queued subscriber notifications have been delivered.
+    ///
+    /// Only meaningful with `with_background_notifications()`; otherwise
+    /// notifications are delivered synchronously and this returns immediately.
+    /// Must not be called from inside a subscriber, which would wait on itself.
+    ///
+    /// # Panics
+    ///
+    /// Under `PanicPolicy::Propagate`, a subscriber that panicked on the
+    /// notification thread since the last flush is re-raised here, as
+    /// there is no dispatch call left to unwind into.
+    pub fn flush(&self) {
+        if let Some(worker) = &self.worker
+            && let Some(payload) = worker.wait_idle()
+        {
+            panic::resume_unwind(payload);
+        }
+    }
+
+    /// Sets how the store reacts to panics in reducers and subscribers.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - The panic policy to apply
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use zed::{PanicPolicy, Store, create_reducer};
+    ///
+    /// #[derive(Clone)]
+    /// struct State { count: i32 }
+    ///
+    /// enum Action { Increment, Explode }
+    ///
+    /// let store = Store::new(
+    ///     State { count: 0 },
+    ///     Box::new(create_reducer(|state: &State, action: &Action| match action {
+    ///         Action::Increment => State { count: state.count + 1 },
+    ///         Action::Explode => panic!("boom"),
+    ///     })),
+    /// )
+    /// .with_panic_policy(PanicPolicy::Recover);
+    ///
+    /// store.dispatch(Action::Increment);
+    /// let error = store.dispatch_try(Action::Explode).unwrap_err();
+    /// assert_eq!(error.to_string(), "reducer panicked: boom");
+    ///
+    /// // The store keeps working with the previous state
+    /// store.dispatch(Action::Increment);
+    /// assert_eq!(store.get_state().count, 2);
+    /// ```
+    pub fn with_panic_policy(mut self, policy: PanicPolicy) -> Self {
+        self.panic_policy = policy;
+        self
+    }
+
+    /// Sets which lock serializes dispatches.
+    ///
+    /// # Arguments
+    ///
+    /// * `dispatch_lock` - The lock to use; see [`DispatchLock`]
+    pub fn with_dispatch_lock(mut self, dispatch_lock: DispatchLock) -> Self {
+        self.fair_lock = match dispatch_lock {
+            DispatchLock::Mutex => None,
+            DispatchLock::Fair => Some(Arc::new(TicketLock::new())),
+        };
+        self
+    }
+
+    /// Internal helper to take the lock serializing dispatches
+    fn lock_reducer(&self) -> ReducerGuard<'_, State, Action> {
+        let fair = self.fair_lock.as_deref();
+        if let Some(fair) = fair {
+            fair.acquire();
+        }
+        ReducerGuard {
+            reducer: lock(&self.reducer),
+            fair,
+        }
+    }
+
+    /// Enables change detection, skipping notifications for no-op actions.
+    ///
+    /// When enabled, `dispatch` and `dispatch_batch` compare the previous and
+    /// new state with `PartialEq` and only notify subscribers if the state
+    /// actually changed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use zed::{Store, create_reducer};
+    ///
+    /// #[derive(Clone, PartialEq)]
+    /// struct State { count: i32 }
+    ///
+    /// enum Action { Increment, Noop }
+    ///
+    /// let store = Store::new(
+    ///     State { count: 0 },
+    ///     Box::new(create_reducer(|state: &State, action: &Action| match action {
+    ///         Action::Increment => State { count: state.count + 1 },
+    ///         Action::Noop => state.clone(),
+    ///     })),
+    /// )
+    /// .with_change_detection();
+    ///
+    /// store.subscribe(|_| println!("changed"));
+    /// store.dispatch(Action::Noop); // Subscribers are not notified
+    /// ```
+    pub fn with_change_detection(mut self) -> Self
+    where
+        State: PartialEq,
+    {
+        self.state_eq = Some(Arc::new(|old: &State, new: &State| old == new));
+        self
+    }
+
+    /// Enables metrics collection.
+    ///
+    /// The store then measures every reducer run (including replays of
+    /// optimistic updates) and every subscriber notification, and counts
+    /// actions per variant, using the variant name from the action's `Debug`
+    /// output. With background notifications, the notify time only covers
+    /// queueing the notification.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use zed::{Store, create_reducer};
+    /// # #[derive(Clone)] struct State { count: i32 }
+    /// # #[derive(Debug)] enum Action { Increment }
+    /// # let store = Store::new(State { count: 0 }, Box::new(create_reducer(|state: &State, _: &Action| State { count: state.count + 1 })));
+    /// let store = store.with_metrics();
+    /// store.dispatch(Action::Increment);
+    ///
+    /// let metrics = store.metrics();
+    /// assert_eq!(metrics.reducer_runs, 1);
+    /// println!("Increment takes {:?} on average", metrics.actions["Increment"].average_reducer_time());
+    /// ```
+    pub fn with_metrics(mut self) -> Self
+    where
+        Action: fmt::Debug,
+    {
+        self.metrics = Some(Arc::new(MetricsCollector::new()));
+        self
+    }
+
+    /// Returns a snapshot of the collected metrics.
+    ///
+    /// Empty unless metrics were enabled with `with_metrics()`.
+    pub fn metrics(&self) -> StoreMetrics {
+        self.metrics
+            .as_ref()
+            .map(|metrics| metrics.snapshot())
+            .unwrap_or_default()
+    }
+
+    /// Clears the collected metrics.
+    pub fn reset_metrics(&self) {
+        if let Some(metrics) = &self.metrics {
+            metrics.reset();
+        }
+    }
+
+    /// Dispatches an action to update the state.
+    ///
+    /// This method applies the action to the current state using the reducer,
+    /// updates the store's state, and notifies all subscribers.
+    ///
+    /// If the reducer rejects the action (see `create_try_reducer`), the state
+    /// is left unchanged and the error is forwarded to error subscribers.
+    /// Use `dispatch_try()` to receive the error directly.
+    ///
+    /// # Arguments
+    ///
+    /// * `action` - The action to dispatch
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use zed::{Store, create_reducer};
+    /// # #[derive(Clone)] struct State { count: i32 }
+    /// # #[derive(Clone)] enum Action { Increment }
+    /// # let store = Store::new(State { count: 0 }, Box::new(create_reducer(|state: &State, _: &Action| State { count: state.count + 1 })));
+    /// store.dispatch(Action::Increment);
+    /// ```
+    pub fn dispatch(&self, action: Action) {
+        let _ = self.dispatch_try(action);
+    }
+
+    /// Dispatches an action, returning the error if the reducer rejects it.
+    ///
+    /// On success this behaves exactly like `dispatch()`. On failure the state
+    /// is left unchanged, state subscribers are not notified, error
+    /// subscribers receive the error, and the error is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `action` - The action to dispatch
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if the action was applied, or the reducer's error.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use zed::{Store, create_try_reducer};
+    ///
+    /// #[derive(Clone)]
+    /// struct State { count: u32 }
+    ///
+    /// enum Action { Decrement }
+    ///
+    /// let store = Store::new(
+    ///     State { count: 0 },
+    ///     Box::new(create_try_reducer(|state: &State, _: &Action| {
+    ///         state
+    ///             .count
+    ///             .checked_sub(1)
+    ///             .map(|count| State { count })
+    ///             .ok_or("count cannot go below zero")
+    ///     })),
+    /// );
+    ///
+    /// let error = store.dispatch_try(Action::Decrement).unwrap_err();
+    /// assert_eq!(error.to_string(), "count cannot go below zero");
+    /// ```
+    pub fn dispatch_try(&self, action: Action) -> Result<(), ReducerError> {
+        self.dispatch_checked(action).map(|_| ())
+    }
+
+    /// Dispatches an action and reports whether it changed the state.
+    ///
+    /// Behaves like `dispatch_try()`, but also hands back the state the
+    /// action produced, so callers can react right away (e.g. skip a render
+    /// when nothing changed) without reading the store again, which could
+    /// observe a later dispatch from another thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `action` - The action to dispatch
+    ///
+    /// # Returns
+    ///
+    /// A [`DispatchOutcome`] with the change flag and the resulting state,
+    /// or the reducer's error.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use zed::{Store, create_reducer};
+    ///
+    /// #[derive(Clone, PartialEq)]
+    /// struct State { count: i32 }
+    ///
+    /// enum Action { Increment, Noop }
+    ///
+    /// let store = Store::new(
+    ///     State { count: 0 },
+    ///     Box::new(create_reducer(|state: &State, action: &Action| match action {
+    ///         Action::Increment => State { count: state.count + 1 },
+    ///         Action::Noop => state.clone(),
+    ///     })),
+    /// )
+    /// .with_change_detection();
+    ///
+    /// let outcome = store.dispatch_checked(Action::Increment).unwrap();
+    /// assert!(outcome.changed());
+    /// assert_eq!(outcome.count, 1);
+    ///
+    /// assert!(!store.dispatch_checked(Action::Noop).unwrap().changed());
+    /// ```
+    pub fn dispatch_checked(&self, action: Action) -> Result<DispatchOutcome<State>, ReducerError> {
+        let chain = self.middleware.load();
+        if chain.is_empty() {
+            return self.reduce_and_notify(action);
+        }
+
+        // The outcome of the last action that reached the reducer; a
+        // middleware dropping the action leaves the state unchanged
+        let outcome = Mutex::new(None);
+        self.run_middleware(&chain, action, &|action| {
+            *lock(&outcome) = Some(self.reduce_and_notify(action)?);
+            Ok(())
+        })?;
+        let outcome = outcome.into_inner().unwrap_or_else(PoisonError::into_inner);
+        Ok(outcome.unwrap_or_else(|| DispatchOutcome {
+            changed: false,
+            state: self.state.load_full(),
+        }))
+    }
+
+    /// Adds a middleware to the dispatch path, outside those added before.
+    pub(crate) fn add_middleware(&self, middleware: Arc<dyn Middleware<State, Action>>) {
+        self.middleware.rcu(|chain| {
+            let mut chain = Vec::clone(chain);
+            chain.push(Arc::clone(&middleware));
+            chain
+        });
+    }
+
+    /// Internal helper to run an action through the middleware chain,
+    /// outermost first, ending with `last`.
+    fn run_middleware(
+        &self,
+        chain: &[Arc<dyn Middleware<State, Action>>],
+        action: Action,
+        last: &dyn Fn(Action) -> Result<(), ReducerError>,
+    ) -> Result<(), ReducerError> {
+        match chain.split_last() {
+            Some((outer, inner)) => outer.handle(self, action, &|action| {
+                self.run_middleware(inner, action, last)
+            }),
+            None => last(action),
+        }
+    }
+
+    /// Internal helper to apply an action and run the notifications.
+    fn reduce_and_notify(&self, action: Action) -> Result<DispatchOutcome<State>, ReducerError> {
+        let _span = enter_span!("zed.dispatch", action = std::any::type_name::<Action>());
+
+        let outcome = match self.apply(&action, true) {
+            Ok(outcome) => outcome,
+            Err(error) => {
+                self.notify_error_subscribers(&error);
+                return Err(error);
+            }
+        };
+        if outcome.changed {
+            // Notify subscribers (separate lock to reduce contention)
+            self.notify_subscribers(&outcome.state);
+        }
+
+        self.notify_listeners(std::slice::from_ref(&action));
+        Ok(outcome)
+    }
+
+    /// Internal helper to reduce an action and store the new state.
+    ///
+    /// If change detection finds the new state equal to the current one, the
+    /// store keeps the current state and the outcome is unchanged.
+    /// Subscribers and listeners are not notified. The new state is
+    /// recorded for time travel only if `record` is set.
+    fn apply(&self, action: &Action, record: bool) -> Result<DispatchOutcome<State>, ReducerError> {
+        // Hold the reducer lock for the entire read-modify-write cycle so
+        // concurrent dispatches are serialized; readers are never blocked
+        let reducer = self.lock_reducer();
+        let state = self.state.load_full();
+        let capturing = self.transitions.active.load(Ordering::SeqCst) > 0;
+        let before = capturing.then(|| (Arc::clone(&state), Instant::now()));
+        let result = self
+            .run_reducer(&**reducer, &state, action)
+            .map(|new_state| {
+                if self.is_unchanged(&state, &new_state) {
+                    return DispatchOutcome {
+                        changed: false,
+                        state,
+                    };
+                }
+                let new_state = Arc::new(new_state);
+                self.state.store(Arc::clone(&new_state));
+                if record {
+                    self.record_history(&new_state);
+                }
+                DispatchOutcome {
+                    changed: true,
+                    state: new_state,
+                }
+            });
+        if let Ok(outcome) = &result {
+            lock(&self.optimistic).record(action);
+            self.observe(action, &outcome.state);
+            if let Some((before, started)) = before {
+                self.capture(before, &outcome.state, started);
+            }
+        }
+        result
+    }
+
+    /// Runs `f`, returning the transition of the first action the current
+    /// thread applies meanwhile, if any.
+    ///
+    /// Used by middleware to see the states their action was reduced
+    /// between, which `get_state()` cannot tell apart from other threads'
+    /// actions. Captures nest: an inner capture does not end the outer one.
+    pub(crate) fn capture_transition<R>(
+        &self,
+        f: impl FnOnce() -> R,
+    ) -> (R, Option<Transition<State>>) {
+        // Restore the outer capture even if `f` panics
+        struct Restore<'a, State> {
+            transitions: &'a TransitionSlots<State>,
+            thread: ThreadId,
+            outer: Option<Option<Transition<State>>>,
+        }
+        impl<State> Drop for Restore<'_, State> {
+            fn drop(&mut self) {
+                let mut slots = lock(&self.transitions.slots);
+                match self.outer.take() {
+                    Some(outer) => slots.insert(self.thread, outer),
+                    None => slots.remove(&self.thread),
+                };
+                self.transitions.active.fetch_sub(1, Ordering::SeqCst);
+            }
+        }
+
+        let thread = thread::current().id();
+        self.transitions.active.fetch_add(1, Ordering::SeqCst);
+        let mut restore = Restore {
+            transitions: &*self.transitions,
+            thread,
+            outer: lock(&self.transitions.slots).insert(thread, None),
+        };
+        let result = f();
+        let transition = lock(&self.transitions.slots)
+            .get_mut(&thread)
+            .and_then(Option::take);
+        // An outer capture that is still empty sees the same transition
+        if let (Some(outer @ None), Some(transition)) = (&mut restore.outer, &transition) {
+            *outer = Some(transition.clone());
+        }
+        (result, transition)
+    }
+
+    /// Internal helper to fill the current thread's transition slot, if
+    /// it is capturing and still empty; called with the reducer lock held.
+    fn capture(&self, before: Arc<State>, after: &Arc<State>, started: Instant) {
+        let mut slots = lock(&self.transitions.slots);
+        if let Some(slot @ None) = slots.get_mut(&thread::current().id()) {
+            *slot = Some(Transition {
+                before,
+                after: Arc::clone(after),
+                started,
+            });
+        }
+    }
+
+    /// Dispatches multiple actions in a batch.
+    ///
+    /// This is more efficient than dispatching actions individually because
+    /// subscribers are only notified once after all actions have been applied.
+    /// Actions rejected by a fallible reducer are skipped and reported to
+    /// error subscribers.
+    ///
+    /// # Arguments
+    ///
+    /// * `actions` - A vector of actions to dispatch
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use zed::{Store, create_reducer};
+    /// # #[derive(Clone)] struct State { count: i32 }
+    /// # #[derive(Clone)] enum Action { Increment }
+    /// # let store = Store::new(State { count: 0 }, Box::new(create_reducer(|state: &State, _: &Action| State { count: state.count + 1 })));
+    /// // All three increments, but subscribers notified only once
+    /// store.dispatch_batch(vec![Action::Increment, Action::Increment, Action::Increment]);
+    /// assert_eq!(store.get_state().count, 3);
+    /// ```
+    pub fn dispatch_batch(&self, actions: Vec<Action>) {
+        if actions.is_empty() {
+            return;
+        }
+        let _span = enter_span!("zed.dispatch_batch", actions = actions.len());
+
+        let mut errors = Vec::new();
+        let mut applied = Vec::new();
+        let new_state = {
+            let reducer = self.lock_reducer();
+            let original = self.state.load_full();
+            let mut optimistic = lock(&self.optimistic);
+            let mut state = None;
+
+            for action in actions {
+                let current = state.as_ref().unwrap_or(&*original);
+                match self.run_reducer(&**reducer, current, &action) {
+                    Ok(next) => {
+                        self.observe(&action, &next);
+                        state = Some(next);
+                        optimistic.record(&action);
+                        applied.push(action);
+                    }
+                    Err(error) => errors.push(error),
+                }
+            }
+
+            state
+                .filter(|new_state| !self.is_unchanged(&original, new_state))
+                .map(|new_state| {
+                    let new_state = Arc::new(new_state);
+                    self.state.store(Arc::clone(&new_state));
+                    self.record_history(&new_state);
+                    new_state
+                })
+        };
+
+        for error in &errors {
+            self.notify_error_subscribers(error);
+        }
+
+        // Notify subscribers once after all actions
+        if let Some(new_state) = new_state {
+            self.notify_subscribers(&new_state);
+        }
+
+        self.notify_listeners(&applied);
+    }
+
+    /// Dispatches every action produced by an iterator in a single batch.
+    ///
+    /// Like `dispatch_batch()`, subscribers are notified once after all
+    /// actions have been applied. The iterator is consumed before the first
+    /// action is reduced, so it may read from the store.
+    ///
+    /// # Arguments
+    ///
+    /// * `actions` - The actions to dispatch, e.g. parsed from an event log
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use zed::{Store, create_reducer};
+    /// # #[derive(Clone)] struct State { count: i32 }
+    /// # #[derive(Clone)] enum Action { Increment }
+    /// # let store = Store::new(State { count: 0 }, Box::new(create_reducer(|state: &State, _: &Action| State { count: state.count + 1 })));
+    /// let events = "+ + - +";
+    /// store.dispatch_from_iter(events.split(' ').filter(|event| *event == "+").map(|_| Action::Increment));
+    /// assert_eq!(store.get_state().count, 3);
+    /// ```
+    pub fn dispatch_from_iter<I>(&self, actions: I)
+    where
+        I: IntoIterator<Item = Action>,
+    {
+        self.dispatch_batch(actions.into_iter().collect());
+    }
+
+    /// Starts a batch whose notifications are sent when it ends.
+    ///
+    /// Unlike `dispatch_batch()`, the actions do not have to be collected up
+    /// front: each `BatchGuard::dispatch()` is applied right away, so
+    /// `get_state()` sees it immediately, but subscribers are notified only
+    /// once, when the guard is dropped or `end()` is called. Listeners also
+    /// run for the applied actions at that point.
+    ///
+    /// # Returns
+    ///
+    /// A `BatchGuard` to dispatch the batched actions through.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use zed::{Store, create_reducer};
+    /// # #[derive(Clone)] struct State { count: i32 }
+    /// # #[derive(Clone)] enum Action { Increment }
+    /// # let store = Store::new(State { count: 0 }, Box::new(create_reducer(|state: &State, _: &Action| State { count: state.count + 1 })));
+    /// let batch = store.batch();
+    /// batch.dispatch(Action::Increment);
+    /// batch.dispatch(Action::Increment);
+    /// assert_eq!(store.get_state().count, 2);
+    ///
+    /// // Subscribers are notified once, here
+    /// batch.end();
+    /// ```
+    pub fn batch(&self) -> BatchGuard<State, Action> {
+        BatchGuard {
+            store: self.clone(),
+            start: self.state.load_full(),
+            applied: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Dispatches an action through the priority queue.
+    ///
+    /// Queued actions are reduced one at a time, highest priority first and
+    /// in dispatch order within a priority. The first caller to find the
+    /// queue idle drains it, reducing its own action and any queued by other
+    /// threads or by subscribers in the meantime; other callers only enqueue
+    /// their action and return before it is applied.
+    ///
+    /// Actions passed to `dispatch()` bypass the queue.
+    ///
+    /// # Arguments
+    ///
+    /// * `action` - The action to dispatch
+    /// * `priority` - How urgently the action should be reduced
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use zed::{Priority, Store, create_reducer};
+    /// # #[derive(Clone)] struct State { count: i32 }
+    /// # #[derive(Clone)] enum Action { Increment }
+    /// # let store = Store::new(State { count: 0 }, Box::new(create_reducer(|state: &State, _: &Action| State { count: state.count + 1 })));
+    /// store.dispatch_with_priority(Action::Increment, Priority::High);
+    /// assert_eq!(store.get_state().count, 1);
+    /// ```
+    pub fn dispatch_with_priority(&self, action: Action, priority: Priority) {
+        if !lock(&self.queue).push(action, priority) {
+            return;
+        }
+
+        // Release the queue if a reducer or subscriber panics mid-drain
+        struct Drain<'a, Action>(&'a Mutex<ActionQueue<Action>>);
+        impl<Action> Drop for Drain<'_, Action> {
+            fn drop(&mut self) {
+                if thread::panicking() {
+                    lock(self.0).abandon();
+                }
+            }
+        }
+
+        let _drain = Drain(&*self.queue);
+        // The queue lock must not be held while dispatching, so subscribers
+        // can enqueue further actions
+        loop {
+            let next = lock(&self.queue).pop();
+            let Some(action) = next else {
+                break;
+            };
+            self.dispatch(action);
+        }
+    }
+
+    /// Returns how many actions are waiting in the priority queue.
+    pub fn queued_actions(&self) -> usize {
+        lock(&self.queue).len()
+    }
+
+    /// Applies an action optimistically, before it has been confirmed.
+    ///
+    /// The action is dispatched immediately, and the returned handle decides
+    /// its fate later: `commit()` keeps it, while `revert()` (or dropping the
+    /// handle) removes it and replays the actions dispatched since on top of
+    /// the state it was applied to.
+    ///
+    /// While optimistic actions are pending, the store keeps a copy of every
+    /// applied action for replay; the log is cleared once all are settled.
+    /// If the reducer rejects the action, it is reported to error
+    /// subscribers and the returned handle is not pending.
+    ///
+    /// # Arguments
+    ///
+    /// * `action` - The action to apply optimistically
+    ///
+    /// # Returns
+    ///
+    /// An `OptimisticHandle` used to commit or revert the action.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use zed::{Store, create_reducer};
+    /// # #[derive(Clone)] struct State { count: i32 }
+    /// # #[derive(Clone)] enum Action { Increment }
+    /// # let store = Store::new(State { count: 0 }, Box::new(create_reducer(|state: &State, _: &Action| State { count: state.count + 1 })));
+    /// let handle = store.dispatch_optimistic(Action::Increment);
+    /// assert_eq!(store.get_state().count, 1);
+    ///
+    /// // Server confirmed the change
+    /// handle.commit();
+    /// assert_eq!(store.get_state().count, 1);
+    /// ```
+    pub fn dispatch_optimistic(&self, action: Action) -> OptimisticHandle<State, Action>
+    where
+        Action: Clone,
+    {
+        let result = {
+            let reducer = self.lock_reducer();
+            let state = self.state.load_full();
+            self.run_reducer(&**reducer, &state, &action)
+                .map(|new_state| {
+                    let id =
+                        lock(&self.optimistic).record_pending(Arc::clone(&state), action.clone());
+                    self.observe(&action, &new_state);
+                    if self.is_unchanged(&state, &new_state) {
+                        return (id, None);
+                    }
+                    let new_state = Arc::new(new_state);
+                    self.state.store(Arc::clone(&new_state));
+                    self.record_history(&new_state);
+                    (id, Some(new_state))
+                })
+        };
+
+        let id = match result {
+            Ok((id, new_state)) => {
+                if let Some(new_state) = new_state {
+                    self.notify_subscribers(&new_state);
+                }
+                self.notify_listeners(std::slice::from_ref(&action));
+                Some(id)
+            }
+            Err(error) => {
+                self.notify_error_subscribers(&error);
+                None
+            }
+        };
+        OptimisticHandle::new(self.clone(), id)
+    }
+
+    /// Commits or reverts a pending optimistic action.
+    pub(crate) fn settle_optimistic(&self, id: OptimisticId, keep: bool) {
+        let mut errors = Vec::new();
+        let new_state = {
+            let reducer = self.lock_reducer();
+            let mut optimistic = lock(&self.optimistic);
+            if keep {
+                optimistic.confirm(id);
+                return;
+            }
+            let Some((base, entries)) = optimistic.discard(id) else {
+                return;
+            };
+
+            let mut state = base;
+            for (_, action) in entries {
+                match self.run_reducer(&**reducer, &state, action) {
+                    Ok(next) => state = Arc::new(next),
+                    Err(error) => errors.push(error),
+                }
+            }
+            optimistic.compact();
+
+            let current = self.state.load();
+            if Arc::ptr_eq(&current, &state) || self.is_unchanged(&current, &state) {
+                None
+            } else {
+                self.state.store(Arc::clone(&state));
+                self.record_history(&state);
+                Some(state)
+            }
+        };
+
+        for error in &errors {
+            self.notify_error_subscribers(error);
+        }
+        if let Some(new_state) = new_state {
+            self.notify_subscribers(&new_state);
+        }
+    }
+
+    /// Runs an async thunk on a background thread.
+    ///
+    /// The thunk receives a `dispatch` handle and a `get_state` handle, so
+    /// asynchronous workflows (HTTP calls, timers) can dispatch loading,
+    /// success and error actions as they progress, like Redux thunks.
+    ///
+    /// The returned future is driven to completion on a dedicated thread.
+    /// Join the returned handle to wait for the thunk and obtain its output.
+    ///
+    /// # Arguments
+    ///
+    /// * `thunk` - A function taking `(dispatch, get_state)` and returning a future
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use zed::{Store, create_reducer};
+    /// # #[derive(Clone)] struct State { count: i32 }
+    /// # #[derive(Clone)] enum Action { Increment }
+    /// # let store = Store::new(State { count: 0 }, Box::new(create_reducer(|state: &State, _: &Action| State { count: state.count + 1 })));
+    /// let handle = store.dispatch_async(|dispatch, get_state| async move {
+    ///     dispatch(Action::Increment);
+    ///     get_state().count
+    /// });
+    ///
+    /// assert_eq!(handle.join().unwrap(), 1);
+    /// ```
+    pub fn dispatch_async<F, Fut>(&self, thunk: F) -> JoinHandle<Fut::Output>
+    where
+        State: Sync,
+        F: FnOnce(Dispatch<Action>, GetState<State>) -> Fut + Send + 'static,
+        Fut: Future,
+        Fut::Output: Send + 'static,
+    {
+        let dispatch_store = self.clone();
+        let state_store = self.clone();
+        let dispatch: Dispatch<Action> = Arc::new(move |action| dispatch_store.dispatch(action));
+        let get_state: GetState<State> = Arc::new(move || state_store.get_state());
+
+        thread::spawn(move || executor::block_on(thunk(dispatch, get_state)))
+    }
+
+    /// Runs a cancellable async thunk on a background thread.
+    ///
+    /// Works like `dispatch_async()`, but the thunk also receives a
+    /// `CancellationToken`, cancelled together with `scope` and by
+    /// `cancel_all_effects()`. Once it is cancelled, the thunk's future is
+    /// dropped at its next suspension point and its dispatches are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `scope` - A token whose cancellation aborts the thunk
+    /// * `thunk` - A function taking `(dispatch, get_state, token)` and returning a future
+    ///
+    /// # Returns
+    ///
+    /// A handle yielding the thunk's output, or `None` if it was cancelled.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use zed::{Store, create_reducer};
+    /// # #[derive(Clone)] struct State { count: i32 }
+    /// # #[derive(Clone)] enum Action { Increment }
+    /// # let store = Store::new(State { count: 0 }, Box::new(create_reducer(|state: &State, _: &Action| State { count: state.count + 1 })));
+    /// let scope = store.cancellation_token();
+    /// let handle = store.dispatch_async_with_token(&scope, |dispatch, get_state, _| async move {
+    ///     dispatch(Action::Increment);
+    ///     get_state().count
+    /// });
+    ///
+    /// assert_eq!(handle.join().unwrap(), Some(1));
+    /// ```
+    pub fn dispatch_async_with_token<F, Fut>(
+        &self,
+        scope: &CancellationToken,
+        thunk: F,
+    ) -> JoinHandle<Option<Fut::Output>>
+    where
+        State: Sync,
+        F: FnOnce(Dispatch<Action>, GetState<State>, CancellationToken) -> Fut + Send + 'static,
+        Fut: Future,
+        Fut::Output: Send + 'static,
+    {
+        let token = self.effect_token(scope);
+        let dispatch_store = self.clone();
+        let dispatch_token = token.clone();
+        let state_store = self.clone();
+        let dispatch: Dispatch<Action> = Arc::new(move |action| {
+            if !dispatch_token.is_cancelled() {
+                dispatch_store.dispatch(action);
+            }
+        });
+        let get_state: GetState<State> = Arc::new(move || state_store.get_state());
+
+        thread::spawn(move || {
+            executor::block_on(async {
+                let future = thunk(dispatch, get_state, token.clone());
+                token.run_until_cancelled(future).await
+            })
+        })
+    }
+
+    /// Starts a saga, a long-running workflow driven by dispatched actions.
+    ///
+    /// The saga receives a `Saga` context and runs on its own thread until
+    /// its future completes. Every action applied after this call is
+    /// buffered for the saga's `take()`, even before the saga starts running.
+    /// The saga is cancelled by its handle or by `cancel_all_effects()`.
+    ///
+    /// # Arguments
+    ///
+    /// * `saga` - Builds the workflow from its context
+    ///
+    /// # Returns
+    ///
+    /// A `SagaHandle` to cancel the saga or wait for its output.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use zed::{Store, create_reducer};
+    /// # #[derive(Clone)] struct State { count: i32 }
+    /// # #[derive(Clone)] enum Action { Increment }
+    /// # let store = Store::new(State { count: 0 }, Box::new(create_reducer(|state: &State, _: &Action| State { count: state.count + 1 })));
+    /// // Doubles every increment
+    /// let saga = store.run_saga(|saga| async move {
+    ///     while saga.take(|_| true).await.is_some() {
+    ///         if saga.select(|state| state.count % 2 == 1) {
+    ///             saga.put(Action::Increment);
+    ///         }
+    ///     }
+    /// });
+    ///
+    /// saga.cancel();
+    /// saga.join().unwrap();
+    /// ```
+    pub fn run_saga<F, Fut>(&self, saga: F) -> SagaHandle<Fut::Output>
+    where
+        State: Sync,
+        Action: Clone,
+        F: FnOnce(Saga<State, Action>) -> Fut + Send + 'static,
+        Fut: Future,
+        Fut::Output: Send + 'static,
+    {
+        self.run_saga_with_token(&CancellationToken::new(), saga)
+    }
+
+    /// Starts a saga that is also cancelled together with a scope token.
+    ///
+    /// Works like `run_saga()`; cancelling `scope` cancels the saga as well.
+    ///
+    /// # Arguments
+    ///
+    /// * `scope` - A token whose cancellation cancels the saga
+    /// * `saga` - Builds the workflow from its context
+    ///
+    /// # Returns
+    ///
+    /// A `SagaHandle` to cancel the saga or wait for its output.
+    pub fn run_saga_with_token<F, Fut>(
+        &self,
+        scope: &CancellationToken,
+        saga: F,
+    ) -> SagaHandle<Fut::Output>
+    where
+        State: Sync,
+        Action: Clone,
+        F: FnOnce(Saga<State, Action>) -> Fut + Send + 'static,
+        Fut: Future,
+        Fut::Output: Send + 'static,
+    {
+        let token = self.effect_token(scope);
+        let context = Saga::new(self.clone(), token.clone());
+        let thread = thread::spawn(move || executor::block_on(saga(context)));
+        SagaHandle::new(thread, token)
+    }
+
+    /// Returns a scope token for async work started from this store.
+    ///
+    /// The token is cancelled by `cancel_all_effects()`, and can be cancelled
+    /// on its own, e.g. when the view that started the work is closed. Pass
+    /// it to `dispatch_async_with_token()` or `run_saga_with_token()`.
+    ///
+    /// # Returns
+    ///
+    /// A new `CancellationToken` tied to the store.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        lock(&self.effects).child_token()
+    }
+
+    /// Cancels all in-flight async thunks and sagas started with a token.
+    ///
+    /// Every token handed out by `cancellation_token()`, and every saga, is
+    /// cancelled. Work started afterwards is not affected.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use zed::{Store, create_reducer};
+    /// # #[derive(Clone)] struct State { count: i32 }
+    /// # #[derive(Clone)] enum Action { Increment }
+    /// # let store = Store::new(State { count: 0 }, Box::new(create_reducer(|state: &State, _: &Action| State { count: state.count + 1 })));
+    /// let scope = store.cancellation_token();
+    /// store.cancel_all_effects();
+    /// assert!(scope.is_cancelled());
+    /// assert!(!store.cancellation_token().is_cancelled());
+    /// ```
+    pub fn cancel_all_effects(&self) {
+        let root = std::mem::take(&mut *lock(&self.effects));
+        root.cancel();
+    }
+
+    /// Internal helper creating the token of one async effect: a child of
+    /// the store's root token that is also cancelled with `scope`.
+    fn effect_token(&self, scope: &CancellationToken) -> CancellationToken {
+        let token = self.cancellation_token();
+        scope.link(&token);
+        token
+    }
+
+    /// Subscribes to state changes.
+    ///
+    /// The provided function will be called whenever the state is updated
+    /// through a dispatch action. Returns a subscription ID that can be used
+    /// to unsubscribe later.
+    ///
+    /// Subscribers are notified in the order they subscribed; use
+    /// `subscribe_with_priority()` to run a subscriber ahead of others.
+    ///
+    /// A subscriber may dispatch follow-up actions, subscribe or unsubscribe.
+    /// A follow-up action notifies all subscribers right away; the remaining
+    /// subscribers of the outer notification then skip its now outdated state.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - A function that will be called with the new state
+    ///
+    /// # Returns
+    ///
+    /// A `SubscriptionId` that can be used with `unsubscribe()` to cancel the subscription.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use zed::{Store, create_reducer};
+    /// # #[derive(Clone)] struct State { count: i32 }
+    /// # #[derive(Clone)] enum Action { Increment }
+    /// # let store = Store::new(State { count: 0 }, Box::new(create_reducer(|state: &State, _: &Action| State { count: state.count + 1 })));
+    /// let id = store.subscribe(|state: &State| {
+    ///     println!("Count is now: {}", state.count);
+    /// });
+    ///
+    /// // Later, when you no longer need the subscription
+    /// store.unsubscribe(id);
+    /// ```
+    pub fn subscribe<F>(&self, f: F) -> SubscriptionId
+    where
+        F: Fn(&State) + Send + Sync + 'static,
+    {
+        self.subscribe_with_priority(0, f)
+    }
+
+    /// Subscribes to state changes with an invocation priority.
+    ///
+    /// Subscribers are called in descending priority order, and subscribers
+    /// with the same priority in the order they were registered, so e.g.
+    /// validation can be guaranteed to run before logging or persistence.
+    /// `subscribe()` registers with priority `0`.
+    ///
+    /// # Arguments
+    ///
+    /// * `priority` - Higher priorities are notified first
+    /// * `f` - A function that will be called with the new state
+    ///
+    /// # Returns
+    ///
+    /// A `SubscriptionId` that can be used with `unsubscribe()` to cancel the subscription.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use zed::{Store, create_reducer};
+    /// # #[derive(Clone)] struct State { count: i32 }
+    /// # #[derive(Clone)] enum Action { Increment }
+    /// # let store = Store::new(State { count: 0 }, Box::new(create_reducer(|state: &State, _: &Action| State { count: state.count + 1 })));
+    /// store.subscribe(|state: &State| println!("persisting {}", state.count));
+    /// // Runs before the subscriber above
+    /// store.subscribe_with_priority(10, |state: &State| assert!(state.count >= 0));
+    /// ```
+    pub fn subscribe_with_priority<F>(&self, priority: i32, f: F) -> SubscriptionId
+    where
+        F: Fn(&State) + Send + Sync + 'static,
+    {
+        let id = self.next_subscriber_id.fetch_add(1, Ordering::SeqCst);
+        lock(&self.subscribers).insert(
+            id,
+            priority,
+            SubscriberEntry::new(Arc::new(move |state: &State| {
+                f(state);
+                true
+            })),
+        );
+        id
+    }
+
+    /// Subscribes to state changes for the lifetime of the returned guard.
+    ///
+    /// Works like `subscribe()`, but the subscriber is removed automatically
+    /// when the `SubscriptionGuard` is dropped, so it cannot leak when its
+    /// owner goes away.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - A function that will be called with the new state
+    ///
+    /// # Returns
+    ///
+    /// A `SubscriptionGuard` that unsubscribes on drop.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use zed::{Store, create_reducer};
+    /// # #[derive(Clone)] struct State { count: i32 }
+    /// # #[derive(Clone)] enum Action { Increment }
+    /// # let store = Store::new(State { count: 0 }, Box::new(create_reducer(|state: &State, _: &Action| State { count: state.count + 1 })));
+    /// {
+    ///     let _guard = store.subscribe_scoped(|state: &State| {
+    ///         println!("Count is now: {}", state.count);
+    ///     });
+    ///     assert_eq!(store.subscriber_count(), 1);
+    /// }
+    ///
+    /// // The guard went out of scope
+    /// assert_eq!(store.subscriber_count(), 0);
+    /// ```
+    pub fn subscribe_scoped<F>(&self, f: F) -> SubscriptionGuard
+    where
+        F: Fn(&State) + Send + Sync + 'static,
+    {
+        let id = self.subscribe(f);
+        self.guard(id)
+    }
+
+    /// Internal helper to tie an existing subscription or listener to a guard.
+    pub(crate) fn guard(&self, id: SubscriptionId) -> SubscriptionGuard {
+        let subscribers = Arc::clone(&self.subscribers);
+        let listeners = Arc::clone(&self.listeners);
+        SubscriptionGuard {
+            id,
+            unsubscribe: Some(Box::new(move |id| {
+                if lock(&subscribers).remove(&id).is_none() {
+                    lock(&listeners).remove(&id);
+                }
+            })),
+        }
+    }
+
+    /// Internal helper identifying the shared store behind a handle.
+    pub(crate) fn identity(&self) -> usize {
+        Arc::as_ptr(&self.state) as usize
+    }
+
+    /// Subscribes a method of a weakly referenced owner to state changes.
+    ///
+    /// The store only holds a `Weak` reference to the owner, so the
+    /// subscription does not keep it alive. Once the owner has been dropped,
+    /// the subscriber is removed automatically on the next notification.
+    ///
+    /// # Arguments
+    ///
+    /// * `owner` - A weak reference to the object receiving notifications
+    /// * `method` - Called with the upgraded owner and the new state
+    ///
+    /// # Returns
+    ///
+    /// A `SubscriptionId` that can be used with `unsubscribe()`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use std::sync::{Arc, Mutex};
+    /// # use zed::{Store, create_reducer};
+    /// # #[derive(Clone)] struct State { count: i32 }
+    /// # #[derive(Clone)] enum Action { Increment }
+    /// # let store = Store::new(State { count: 0 }, Box::new(create_reducer(|state: &State, _: &Action| State { count: state.count + 1 })));
+    /// struct Counter { label: Mutex<String> }
+    ///
+    /// impl Counter {
+    ///     fn render(&self, state: &State) {
+    ///         *self.label.lock().unwrap() = format!("Count: {}", state.count);
+    ///     }
+    /// }
+    ///
+    /// let widget = Arc::new(Counter { label: Mutex::new(String::new()) });
+    /// store.subscribe_weak(&Arc::downgrade(&widget), Counter::render);
+    ///
+    /// store.dispatch(Action::Increment);
+    /// assert_eq!(*widget.label.lock().unwrap(), "Count: 1");
+    ///
+    /// drop(widget);
+    /// store.dispatch(Action::Increment);
+    /// assert_eq!(store.subscriber_count(), 0);
+    /// ```
+    pub fn subscribe_weak<T, F>(&self, owner: &Weak<T>, method: F) -> SubscriptionId
+    where
+        T: Send + Sync + 'static,
+        F: Fn(&T, &State) + Send + Sync + 'static,
+    {
+        let owner = owner.clone();
+        let id = self.next_subscriber_id.fetch_add(1, Ordering::SeqCst);
+        lock(&self.subscribers).insert(
+            id,
+            0,
+            SubscriberEntry::new(Arc::new(move |state: &State| match owner.upgrade() {
+                Some(owner) => {
+                    method(&owner, state);
+                    true
+                }
+                None => false,
+            })),
+        );
+        id
+    }
+
+    /// Subscribes to state changes for which a predicate holds.
+    ///
+    /// The predicate runs after every state update, and the callback is only
+    /// invoked when it returns `true` for the new state. Unlike
+    /// `subscribe_selector()`, the callback fires on every matching update,
+    /// not just when a value changes.
     ///
     /// # Arguments
     ///
-    /// * `initial_state` - The initial state of the store
-    /// * `reducer` - A boxed reducer that handles state transitions
+    /// * `predicate` - Decides whether the callback should see the new state
+    /// * `f` - Called with the new state when the predicate holds
+    ///
+    /// # Returns
+    ///
+    /// A `SubscriptionId` that can be used with `unsubscribe()`.
     ///
     /// # Example
     ///
     /// ```rust
-    /// use zed::{Store, create_reducer};
+    /// # use zed::{Store, create_reducer};
+    /// # #[derive(Clone)] struct State { count: i32 }
+    /// # #[derive(Clone)] enum Action { Increment }
+    /// # let store = Store::new(State { count: 0 }, Box::new(create_reducer(|state: &State, _: &Action| State { count: state.count + 1 })));
+    /// let id = store.subscribe_filtered(
+    ///     |state: &State| state.count % 2 == 0,
+    ///     |state: &State| println!("Count is even: {}", state.count),
+    /// );
     ///
-    /// #[derive(Clone)]
-    /// struct State { count: i32 }
+    /// store.dispatch(Action::Increment); // 1, callback skipped
+    /// store.dispatch(Action::Increment); // 2, callback fires
+    /// store.unsubscribe(id);
+    /// ```
+    pub fn subscribe_filtered<P, F>(&self, predicate: P, f: F) -> SubscriptionId
+    where
+        P: Fn(&State) -> bool + Send + Sync + 'static,
+        F: Fn(&State) + Send + Sync + 'static,
+    {
+        self.subscribe(move |state| {
+            if predicate(state) {
+                f(state);
+            }
+        })
+    }
+
+    /// Subscribes to changes of a selected part of the state.
+    ///
+    /// The selector runs after every state update, and the callback is only
+    /// invoked when the selected value differs (by `PartialEq`) from the
+    /// previously selected one. The initial value is taken from the state at
+    /// subscription time, so the callback does not fire until it changes.
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - Extracts the value of interest from the state
+    /// * `f` - Called with the newly selected value whenever it changes
+    ///
+    /// # Returns
+    ///
+    /// A `SubscriptionId` that can be used with `unsubscribe()`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use zed::{Store, create_reducer};
+    /// # #[derive(Clone)] struct State { count: i32, name: String }
+    /// # enum Action { Increment, Rename(String) }
+    /// # let store = Store::new(State { count: 0, name: String::new() }, Box::new(create_reducer(|state: &State, action: &Action| match action {
+    /// #     Action::Increment => State { count: state.count + 1, name: state.name.clone() },
+    /// #     Action::Rename(name) => State { count: state.count, name: name.clone() },
+    /// # })));
+    /// let id = store.subscribe_selector(
+    ///     |state: &State| state.count,
+    ///     |count: &i32| println!("Count changed to {count}"),
+    /// );
+    ///
+    /// store.dispatch(Action::Increment); // Callback fires
+    /// store.dispatch(Action::Rename("zed".to_string())); // Count untouched, callback skipped
+    /// store.unsubscribe(id);
+    /// ```
+    pub fn subscribe_selector<T, S, F>(&self, selector: S, f: F) -> SubscriptionId
+    where
+        T: PartialEq + Send + 'static,
+        S: Fn(&State) -> T + Send + Sync + 'static,
+        F: Fn(&T) + Send + Sync + 'static,
+    {
+        let last = Mutex::new(self.with_state(&selector));
+
+        self.subscribe(move |state| {
+            let selected = selector(state);
+            let mut last = lock(&last);
+            if *last != selected {
+                f(&selected);
+                *last = selected;
+            }
+        })
+    }
+
+    /// Subscribes to errors produced by a fallible reducer.
+    ///
+    /// The provided function is called whenever an action dispatched through
+    /// `dispatch()`, `dispatch_try()` or `dispatch_batch()` is rejected by the
+    /// reducer. Use `unsubscribe()` with the returned ID to remove it.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - A function that will be called with each reducer error
+    ///
+    /// # Returns
+    ///
+    /// A `SubscriptionId` that can be used with `unsubscribe()`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use zed::{Store, create_try_reducer};
+    /// # #[derive(Clone)] struct State { count: i32 }
+    /// # enum Action { Fail }
+    /// # let store = Store::new(State { count: 0 }, Box::new(create_try_reducer(|_: &State, _: &Action| Err::<State, _>("rejected"))));
+    /// let id = store.subscribe_errors(|error| {
+    ///     eprintln!("Action rejected: {error}");
+    /// });
+    ///
+    /// store.dispatch(Action::Fail);
+    /// store.unsubscribe(id);
+    /// ```
+    pub fn subscribe_errors<F>(&self, f: F) -> SubscriptionId
+    where
+        F: Fn(&ReducerError) + Send + Sync + 'static,
+    {
+        let id = self.next_subscriber_id.fetch_add(1, Ordering::SeqCst);
+        lock(&self.error_subscribers).insert(id, Arc::new(f));
+        id
+    }
+
+    /// Creates a child store view focused on a slice of this store's state.
+    ///
+    /// The scoped store's `get_state`/`with_state`/`subscribe` operate on the
+    /// child state selected by the lens, and its dispatched actions are
+    /// mapped into parent actions.
+    ///
+    /// # Arguments
+    ///
+    /// * `lens` - Describes the child state and how child actions are lifted
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use zed::{Lens, Store, create_reducer};
     ///
     /// #[derive(Clone)]
-    /// enum Action { Increment }
+    /// struct AppState { counter: i32, title: String }
+    ///
+    /// enum CounterAction { Increment }
+    /// enum AppAction { Counter(CounterAction), Rename(String) }
     ///
     /// let store = Store::new(
-    ///     State { count: 0 },
-    ///     Box::new(create_reducer(|state: &State, _: &Action| State { count: state.count + 1 }))
+    ///     AppState { counter: 0, title: String::new() },
+    ///     Box::new(create_reducer(|state: &AppState, action: &AppAction| match action {
+    ///         AppAction::Counter(CounterAction::Increment) => AppState { counter: state.counter + 1, ..state.clone() },
+    ///         AppAction::Rename(title) => AppState { title: title.clone(), ..state.clone() },
+    ///     })),
     /// );
+    ///
+    /// let counter = store.scoped(Lens::new(|state: &AppState| &state.counter, AppAction::Counter));
+    /// counter.subscribe(|value: &i32| println!("counter is {value}"));
+    ///
+    /// counter.dispatch(CounterAction::Increment);
+    /// store.dispatch(AppAction::Rename("zed".to_string())); // Counter subscribers not notified
+    /// assert_eq!(counter.get_state(), 1);
     /// ```
-    pub fn new(
-        initial_state: State,
-        reducer: Box<dyn Reducer<State, Action> + Send + Sync>,
-    ) -> Self {
-        Self {
-            state: Arc::new(Mutex::new(initial_state)),
-            reducer: Arc::new(Mutex::new(reducer)),
-            subscribers: Arc::new(Mutex::new(HashMap::new())),
-            next_subscriber_id: AtomicUsize::new(0),
-        }
+    pub fn scoped<Child, ChildAction>(
+        &self,
+        lens: Lens<State, Child, ChildAction, Action>,
+    ) -> ScopedStore<State, Action, Child, ChildAction>
+    where
+        Child: 'static,
+        ChildAction: 'static,
+    {
+        ScopedStore::new(self.clone(), lens)
     }
 
-    /// Dispatches an action to update the state.
+    /// Creates a read-only store holding a projection of this store's state.
     ///
-    /// This method applies the action to the current state using the reducer,
-    /// updates the store's state, and notifies all subscribers.
+    /// The projection is computed immediately and recomputed after every
+    /// state update; the projected store's subscribers are only notified
+    /// when the result changes (by `PartialEq`). The parent store does not
+    /// keep the projection alive: it is detached once the last clone of the
+    /// returned store is dropped.
     ///
     /// # Arguments
     ///
-    /// * `action` - The action to dispatch
+    /// * `project` - Derives the projected value from the state
+    ///
+    /// # Returns
+    ///
+    /// A `ProjectedStore` that follows the projection.
     ///
     /// # Example
     ///
@@ -127,30 +2422,106 @@ impl<State: Clone + Send + 'static, Action: Send + 'static> Store<State, Action>
     /// # #[derive(Clone)] struct State { count: i32 }
     /// # #[derive(Clone)] enum Action { Increment }
     /// # let store = Store::new(State { count: 0 }, Box::new(create_reducer(|state: &State, _: &Action| State { count: state.count + 1 })));
+    /// let label = store.map(|state: &State| format!("{} clicks", state.count));
+    /// label.subscribe(|label: &String| println!("{label}"));
+    ///
     /// store.dispatch(Action::Increment);
+    /// assert_eq!(label.get_state(), "1 clicks");
     /// ```
-    pub fn dispatch(&self, action: Action) {
-        // Hold state lock for the entire read-modify-write cycle to ensure atomicity
-        let new_state = {
-            let mut state = self.state.lock().unwrap();
-            let reducer = self.reducer.lock().unwrap();
-            let new_state = reducer.reduce(&state, &action);
-            *state = new_state.clone();
-            new_state
-        };
+    pub fn map<T, F>(&self, project: F) -> ProjectedStore<T>
+    where
+        T: PartialEq + Send + Sync + 'static,
+        F: Fn(&State) -> T + Send + Sync + 'static,
+    {
+        let projection = Arc::new(Projection::new(self.with_state(&project)));
+        let id = self.subscribe_weak(
+            &Arc::downgrade(&projection),
+            move |projection: &Projection<T>, state: &State| projection.update(project(state)),
+        );
+        ProjectedStore::new(projection, self.guard(id))
+    }
 
-        // Notify subscribers (separate lock to reduce contention)
-        self.notify_subscribers(&new_state);
+    /// Adds an action listener, mirroring Redux Toolkit's listener middleware.
+    ///
+    /// After an action has been applied and subscribers have been notified,
+    /// every listener whose predicate matches the action runs its effect.
+    /// The effect receives the action and the store, so it can read the new
+    /// state and dispatch follow-up actions.
+    ///
+    /// # Arguments
+    ///
+    /// * `predicate` - Decides which actions the listener reacts to
+    /// * `effect` - The side effect to run for matching actions
+    ///
+    /// # Returns
+    ///
+    /// A `SubscriptionId` that can be used with `unsubscribe()` to remove the listener.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use zed::{Store, create_reducer};
+    ///
+    /// #[derive(Clone)]
+    /// struct State { saved: bool, dirty: bool }
+    ///
+    /// enum Action { Edit, Save, Saved }
+    ///
+    /// let store = Store::new(
+    ///     State { saved: false, dirty: false },
+    ///     Box::new(create_reducer(|state: &State, action: &Action| match action {
+    ///         Action::Edit => State { dirty: true, ..state.clone() },
+    ///         Action::Save => state.clone(),
+    ///         Action::Saved => State { saved: true, dirty: false },
+    ///     })),
+    /// );
+    ///
+    /// store.add_listener(
+    ///     |action| matches!(action, Action::Save),
+    ///     |_, store| {
+    ///         // Persist the state, then report completion
+    ///         store.dispatch(Action::Saved);
+    ///     },
+    /// );
+    ///
+    /// store.dispatch(Action::Edit);
+    /// store.dispatch(Action::Save);
+    /// assert!(store.get_state().saved);
+    /// ```
+    pub fn add_listener<P, E>(&self, predicate: P, effect: E) -> SubscriptionId
+    where
+        P: Fn(&Action) -> bool + Send + Sync + 'static,
+        E: Fn(&Action, &Store<State, Action>) + Send + Sync + 'static,
+    {
+        let id = self.next_subscriber_id.fetch_add(1, Ordering::SeqCst);
+        let listener: Listener<State, Action> = Arc::new(move |action, store| {
+            if predicate(action) {
+                effect(action, store);
+            }
+        });
+        lock(&self.listeners).insert(id, listener);
+        id
     }
 
-    /// Dispatches multiple actions in a batch.
+    /// Registers an effect that may dispatch follow-up actions.
     ///
-    /// This is more efficient than dispatching actions individually because
-    /// subscribers are only notified once after all actions have been applied.
+    /// After every applied action, the effect is run with the action and the
+    /// new state, and the actions it returns are dispatched in order. Effects
+    /// run on the dispatching thread, after subscribers have been notified;
+    /// for a `dispatch_batch()` they run once per action, with the state
+    /// after the whole batch. Rejected actions do not reach effects.
+    ///
+    /// Follow-up actions are queued and dispatched once every listener has
+    /// seen the current action, so long chains of effects do not grow the
+    /// stack.
     ///
     /// # Arguments
     ///
-    /// * `actions` - A vector of actions to dispatch
+    /// * `effect` - The effect to run after each applied action
+    ///
+    /// # Returns
+    ///
+    /// A `SubscriptionId` that can be used with `unsubscribe()` to remove the effect.
     ///
     /// # Example
     ///
@@ -159,44 +2530,88 @@ impl<State: Clone + Send + 'static, Action: Send + 'static> Store<State, Action>
     /// # #[derive(Clone)] struct State { count: i32 }
     /// # #[derive(Clone)] enum Action { Increment }
     /// # let store = Store::new(State { count: 0 }, Box::new(create_reducer(|state: &State, _: &Action| State { count: state.count + 1 })));
-    /// // All three increments, but subscribers notified only once
-    /// store.dispatch_batch(vec![Action::Increment, Action::Increment, Action::Increment]);
+    /// // Keep incrementing until the count reaches 3
+    /// store.add_effect(|_: &Action, state: &State| {
+    ///     if state.count < 3 { vec![Action::Increment] } else { vec![] }
+    /// });
+    ///
+    /// store.dispatch(Action::Increment);
     /// assert_eq!(store.get_state().count, 3);
     /// ```
-    pub fn dispatch_batch(&self, actions: Vec<Action>) {
-        if actions.is_empty() {
-            return;
-        }
-
-        let new_state = {
-            let mut state = self.state.lock().unwrap();
-            let reducer = self.reducer.lock().unwrap();
+    pub fn add_effect<E>(&self, effect: E) -> SubscriptionId
+    where
+        E: Effect<State, Action> + 'static,
+    {
+        self.add_listener(
+            |_| true,
+            move |action: &Action, store: &Store<State, Action>| {
+                let follow_ups = store.with_state(|state| effect.run(action, state));
+                store.queue_follow_ups(follow_ups);
+            },
+        )
+    }
 
-            for action in actions {
-                let temp_state = reducer.reduce(&state, &action);
-                *state = temp_state;
-            }
+    /// Registers a callback run for every applied action while the reducer
+    /// lock is held, with the state right after that action.
+    ///
+    /// `init` is called with the current state under the same lock, so no
+    /// action can be applied between it and the first observed one; its
+    /// result is returned along with the observer's ID. Observers see
+    /// actions in the order the reducer applied them and must not
+    /// dispatch. Remove them with `unsubscribe()`.
+    pub(crate) fn observe_actions<I, R, F>(&self, init: I, observer: F) -> (SubscriptionId, R)
+    where
+        I: FnOnce(&State) -> R,
+        F: Fn(&Action, &State) + Send + Sync + 'static,
+    {
+        let _reducer = self.lock_reducer();
+        let initial = init(&self.state.load());
+        let id = self.next_subscriber_id.fetch_add(1, Ordering::SeqCst);
+        let observer: ActionObserver<State, Action> = Arc::new(observer);
+        self.observers.rcu(|observers| {
+            let mut observers = Vec::clone(observers);
+            observers.push((id, Arc::clone(&observer)));
+            observers
+        });
+        (id, initial)
+    }
 
-            state.clone()
-        };
+    /// Internal helper to remove an action observer.
+    fn remove_observer(&self, id: SubscriptionId) -> bool {
+        if !self
+            .observers
+            .load()
+            .iter()
+            .any(|(existing, _)| *existing == id)
+        {
+            return false;
+        }
+        self.observers.rcu(|observers| {
+            let mut observers = Vec::clone(observers);
+            observers.retain(|(existing, _)| *existing != id);
+            observers
+        });
+        true
+    }
 
-        // Notify subscribers once after all actions
-        self.notify_subscribers(&new_state);
+    /// Internal helper to run the action observers; called with the reducer
+    /// lock held.
+    fn observe(&self, action: &Action, state: &State) {
+        for (_, observer) in self.observers.load().iter() {
+            self.isolate(|| observer(action, state));
+        }
     }
 
-    /// Subscribes to state changes.
-    ///
-    /// The provided function will be called whenever the state is updated
-    /// through a dispatch action. Returns a subscription ID that can be used
-    /// to unsubscribe later.
+    /// Unsubscribes a previously registered subscriber.
     ///
     /// # Arguments
     ///
-    /// * `f` - A function that will be called with the new state
+    /// * `id` - The subscription ID returned by `subscribe()`, `subscribe_errors()`, `add_listener()` or `add_effect()`
     ///
     /// # Returns
     ///
-    /// A `SubscriptionId` that can be used with `unsubscribe()` to cancel the subscription.
+    /// `true` if the subscriber was found and removed, `false` if no subscriber
+    /// with that ID exists.
     ///
     /// # Example
     ///
@@ -205,32 +2620,142 @@ impl<State: Clone + Send + 'static, Action: Send + 'static> Store<State, Action>
     /// # #[derive(Clone)] struct State { count: i32 }
     /// # #[derive(Clone)] enum Action { Increment }
     /// # let store = Store::new(State { count: 0 }, Box::new(create_reducer(|state: &State, _: &Action| State { count: state.count + 1 })));
-    /// let id = store.subscribe(|state: &State| {
-    ///     println!("Count is now: {}", state.count);
-    /// });
+    /// let id = store.subscribe(|_| {});
     ///
-    /// // Later, when you no longer need the subscription
-    /// store.unsubscribe(id);
+    /// assert!(store.unsubscribe(id));  // Returns true - subscriber removed
+    /// assert!(!store.unsubscribe(id)); // Returns false - already removed
     /// ```
-    pub fn subscribe<F>(&self, f: F) -> SubscriptionId
-    where
-        F: Fn(&State) + Send + Sync + 'static,
-    {
-        let id = self.next_subscriber_id.fetch_add(1, Ordering::SeqCst);
-        self.subscribers.lock().unwrap().insert(id, Box::new(f));
-        id
+    pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        lock(&self.subscribers).remove(&id).is_some()
+            || lock(&self.error_subscribers).remove(&id).is_some()
+            || lock(&self.listeners).remove(&id).is_some()
+            || self.remove_observer(id)
     }
 
-    /// Unsubscribes a previously registered subscriber.
+    /// Pauses a state subscriber without removing it.
     ///
-    /// # Arguments
+    /// A paused subscriber keeps its registration but is skipped by
+    /// notifications until it is resumed.
+    ///
+    /// # Returns
+    ///
+    /// `true` if a state subscriber with that ID exists.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use std::sync::{Arc, Mutex};
+    /// # use zed::{Store, create_reducer};
+    /// # #[derive(Clone)] struct State { count: i32 }
+    /// # #[derive(Clone)] enum Action { Increment }
+    /// # let store = Store::new(State { count: 0 }, Box::new(create_reducer(|state: &State, _: &Action| State { count: state.count + 1 })));
+    /// let seen = Arc::new(Mutex::new(Vec::new()));
+    /// let seen_clone = Arc::clone(&seen);
+    /// let id = store.subscribe(move |state: &State| seen_clone.lock().unwrap().push(state.count));
+    ///
+    /// store.pause_subscriber(id);
+    /// store.dispatch(Action::Increment);
+    /// store.dispatch(Action::Increment);
+    ///
+    /// // Catch up with the latest state once visible again
+    /// store.resume_subscriber_and_replay(id);
+    /// assert_eq!(*seen.lock().unwrap(), vec![2]);
+    /// ```
+    pub fn pause_subscriber(&self, id: SubscriptionId) -> bool {
+        match lock(&self.subscribers).get_mut(&id) {
+            Some(entry) => {
+                entry.paused = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Resumes a paused subscriber.
+    ///
+    /// Notifications skipped while paused are not delivered; the subscriber
+    /// is called again on the next state update. Use
+    /// `resume_subscriber_and_replay()` to catch up immediately.
+    ///
+    /// # Returns
+    ///
+    /// `true` if a state subscriber with that ID exists.
+    pub fn resume_subscriber(&self, id: SubscriptionId) -> bool {
+        match lock(&self.subscribers).get_mut(&id) {
+            Some(entry) => {
+                entry.paused = false;
+                entry.missed = false;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Resumes a paused subscriber and replays the latest state to it.
     ///
-    /// * `id` - The subscription ID returned by `subscribe()`
+    /// The subscriber is called once with the current state if it missed at
+    /// least one notification while paused, so it is up to date without
+    /// waiting for the next dispatch.
     ///
     /// # Returns
     ///
-    /// `true` if the subscriber was found and removed, `false` if no subscriber
-    /// with that ID exists.
+    /// `true` if a state subscriber with that ID exists.
+    pub fn resume_subscriber_and_replay(&self, id: SubscriptionId) -> bool {
+        let mut subscribers = lock(&self.subscribers);
+        let Some(entry) = subscribers.get_mut(&id) else {
+            return false;
+        };
+        let missed = entry.missed;
+        entry.paused = false;
+        entry.missed = false;
+        let callback = Arc::clone(&entry.callback);
+        drop(subscribers);
+
+        if missed {
+            let state = self.state.load_full();
+            let mut keep = true;
+            isolate(self.panic_policy, || keep = callback(&state));
+            if !keep {
+                lock(&self.subscribers).remove(&id);
+            }
+        }
+        true
+    }
+
+    /// Gets the current state.
+    ///
+    /// Returns a clone of the current state. This is safe to call from
+    /// multiple threads concurrently and never waits for in-progress dispatches.
+    ///
+    /// Cloning copies the whole state; for large states, share the current
+    /// snapshot with `get_state_shared()` instead.
+    ///
+    /// For read-only access without cloning, consider using `read()` or
+    /// `with_state()`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use zed::{Store, create_reducer};
+    /// # #[derive(Clone)] struct State { count: i32 }
+    /// # #[derive(Clone)] enum Action { Increment }
+    /// # let store = Store::new(State { count: 0 }, Box::new(create_reducer(|state: &State, _: &Action| State { count: state.count + 1 })));
+    /// let current_state = store.get_state();
+    /// println!("Current count: {}", current_state.count);
+    /// ```
+    pub fn get_state(&self) -> State {
+        State::clone(&self.state.load())
+    }
+
+    /// Returns the current state snapshot as a shared `Arc<State>`.
+    ///
+    /// The store keeps its state in reference-counted snapshots, and
+    /// subscribers are handed a reference into the same snapshot, so this is
+    /// a lock-free read that never copies the state: every dispatch stores a
+    /// new snapshot atomically, and the returned `Arc` stays valid and
+    /// unchanged even while later dispatches update the store. Prefer it over
+    /// `get_state()` for large states and high-frequency readers such as
+    /// render loops.
     ///
     /// # Example
     ///
@@ -239,21 +2764,26 @@ impl<State: Clone + Send + 'static, Action: Send + 'static> Store<State, Action>
     /// # #[derive(Clone)] struct State { count: i32 }
     /// # #[derive(Clone)] enum Action { Increment }
     /// # let store = Store::new(State { count: 0 }, Box::new(create_reducer(|state: &State, _: &Action| State { count: state.count + 1 })));
-    /// let id = store.subscribe(|_| {});
+    /// let snapshot = store.get_state_shared();
+    /// store.dispatch(Action::Increment);
     ///
-    /// assert!(store.unsubscribe(id));  // Returns true - subscriber removed
-    /// assert!(!store.unsubscribe(id)); // Returns false - already removed
+    /// assert_eq!(snapshot.count, 0); // The old snapshot is untouched
+    /// assert_eq!(store.get_state_shared().count, 1);
     /// ```
-    pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
-        self.subscribers.lock().unwrap().remove(&id).is_some()
+    pub fn get_state_shared(&self) -> Arc<State> {
+        self.state.load_full()
     }
 
-    /// Gets the current state.
+    /// Borrows the current state without cloning it.
     ///
-    /// Returns a clone of the current state. This is safe to call from
-    /// multiple threads concurrently.
+    /// The returned guard dereferences to the state, so large states can be
+    /// inspected in place without wrapping the code in a `with_state()`
+    /// closure. Like all reads it is lock-free; the guard holds the snapshot
+    /// that was current when `read()` was called.
+    ///
+    /// # Returns
     ///
-    /// For read-only access without cloning, consider using `with_state()`.
+    /// A `StateGuard` dereferencing to the current state.
     ///
     /// # Example
     ///
@@ -262,11 +2792,18 @@ impl<State: Clone + Send + 'static, Action: Send + 'static> Store<State, Action>
     /// # #[derive(Clone)] struct State { count: i32 }
     /// # #[derive(Clone)] enum Action { Increment }
     /// # let store = Store::new(State { count: 0 }, Box::new(create_reducer(|state: &State, _: &Action| State { count: state.count + 1 })));
-    /// let current_state = store.get_state();
-    /// println!("Current count: {}", current_state.count);
+    /// let state = store.read();
+    /// assert_eq!(state.count, 0);
+    ///
+    /// // Dispatching does not wait for the guard
+    /// store.dispatch(Action::Increment);
+    /// assert_eq!(state.count, 0);
+    /// assert_eq!(store.read().count, 1);
     /// ```
-    pub fn get_state(&self) -> State {
-        self.state.lock().unwrap().clone()
+    pub fn read(&self) -> StateGuard<State> {
+        StateGuard {
+            snapshot: self.state.load(),
+        }
     }
 
     /// Accesses the state without cloning.
@@ -275,6 +2812,9 @@ impl<State: Clone + Send + 'static, Action: Send + 'static> Store<State, Action>
     /// to keep a copy. The provided function receives an immutable reference
     /// to the state and can return a value.
     ///
+    /// Reads are lock-free: any number of threads can call `with_state` at the
+    /// same time, and dispatches never wait for readers to finish.
+    ///
     /// # Arguments
     ///
     /// * `f` - A function that takes an immutable reference to the state
@@ -300,10 +2840,190 @@ impl<State: Clone + Send + 'static, Action: Send + 'static> Store<State, Action>
     where
         F: FnOnce(&State) -> R,
     {
-        let state = self.state.lock().unwrap();
+        let state = self.state.load();
         f(&state)
     }
 
+    /// Replaces the whole state, bypassing the reducer.
+    ///
+    /// Useful to hydrate a store from server-rendered data or an import, or
+    /// to jump to a recorded state. Subscribers are notified as after a
+    /// dispatch (unless change detection finds the state unchanged); action
+    /// listeners are not run. Pending optimistic actions are settled as
+    /// committed, since the new state no longer derives from them.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_state` - The state to install
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use zed::{Store, create_reducer};
+    /// # #[derive(Clone)] struct State { count: i32 }
+    /// # #[derive(Clone)] enum Action { Increment }
+    /// # let store = Store::new(State { count: 0 }, Box::new(create_reducer(|state: &State, _: &Action| State { count: state.count + 1 })));
+    /// store.replace_state(State { count: 42 });
+    /// assert_eq!(store.get_state().count, 42);
+    ///
+    /// store.dispatch(Action::Increment);
+    /// assert_eq!(store.get_state().count, 43);
+    /// ```
+    pub fn replace_state(&self, new_state: State) {
+        let new_state = {
+            let _reducer = self.lock_reducer();
+            lock(&self.optimistic).reset();
+            let current = self.state.load();
+            if self.is_unchanged(&current, &new_state) {
+                None
+            } else {
+                let new_state = Arc::new(new_state);
+                self.state.store(Arc::clone(&new_state));
+                self.record_history(&new_state);
+                Some(new_state)
+            }
+        };
+
+        if let Some(new_state) = new_state {
+            self.notify_subscribers(&new_state);
+        }
+    }
+
+    /// Starts recording the store's history for undo, redo and jumps.
+    ///
+    /// The current state becomes the oldest entry. From then on, every
+    /// committed state is recorded, including those from optimistic
+    /// updates and `replace_state()`, by this store and all its clones. A
+    /// batch is recorded as a single step. See the [`time_travel`](crate::time_travel) module.
+    ///
+    /// # Returns
+    ///
+    /// `false`, leaving the existing history untouched, if time travel was
+    /// already enabled.
+    pub fn enable_time_travel(&self, config: TimeTravelConfig) -> bool {
+        let _reducer = self.lock_reducer();
+        let mut enabled = false;
+        self.time_travel.get_or_init(|| {
+            enabled = true;
+            Mutex::new(TimeTravel::new(self.state.load_full(), config))
+        });
+        enabled
+    }
+
+    /// Reverts to the previous recorded state.
+    ///
+    /// # Returns
+    ///
+    /// `false` if time travel is disabled or there is nothing to undo.
+    pub fn undo(&self) -> bool {
+        self.travel(TimeTravel::undo)
+    }
+
+    /// Re-applies the state reverted by the last `undo()`.
+    ///
+    /// Dispatching after an undo discards the states that could have been
+    /// redone.
+    ///
+    /// # Returns
+    ///
+    /// `false` if time travel is disabled or there is nothing to redo.
+    pub fn redo(&self) -> bool {
+        self.travel(TimeTravel::redo)
+    }
+
+    /// Installs the recorded state at the given position, 0 being the
+    /// oldest one still kept.
+    ///
+    /// # Returns
+    ///
+    /// `false` if time travel is disabled or the position is out of range.
+    pub fn jump_to(&self, position: usize) -> bool {
+        self.travel(|history| history.jump_to(position))
+    }
+
+    /// Returns `true` if `undo()` would change the state.
+    pub fn can_undo(&self) -> bool {
+        self.with_time_travel(TimeTravel::can_undo).unwrap_or(false)
+    }
+
+    /// Returns `true` if `redo()` would change the state.
+    pub fn can_redo(&self) -> bool {
+        self.with_time_travel(TimeTravel::can_redo).unwrap_or(false)
+    }
+
+    /// Returns the number of recorded states and the position of the
+    /// current one, or `None` if time travel is disabled.
+    pub fn time_travel_position(&self) -> Option<(usize, usize)> {
+        self.with_time_travel(TimeTravel::position)
+    }
+
+    fn with_time_travel<R>(&self, f: impl FnOnce(&TimeTravel<State>) -> R) -> Option<R> {
+        self.time_travel
+            .get()
+            .map(|time_travel| f(&lock(time_travel)))
+    }
+
+    /// Records a committed state; called with the reducer lock held.
+    fn record_history(&self, state: &Arc<State>) {
+        if let Some(time_travel) = self.time_travel.get() {
+            lock(time_travel).record(state);
+        }
+    }
+
+    /// Moves through the recorded history and installs the selected state.
+    fn travel(&self, step: impl FnOnce(&mut TimeTravel<State>) -> Option<Arc<State>>) -> bool {
+        let Some(time_travel) = self.time_travel.get() else {
+            return false;
+        };
+        let new_state = {
+            let _reducer = self.lock_reducer();
+            let Some(new_state) = step(&mut lock(time_travel)) else {
+                return false;
+            };
+            lock(&self.optimistic).reset();
+            self.state.store(Arc::clone(&new_state));
+            new_state
+        };
+        self.notify_subscribers(&new_state);
+        true
+    }
+
+    /// Replays a recorded session and checks that it is deterministic.
+    ///
+    /// The state is replaced by the log's initial state, then every recorded
+    /// action is dispatched in order, notifying subscribers and listeners as
+    /// usual. Finally the resulting state is compared with the log's final
+    /// state.
+    ///
+    /// # Arguments
+    ///
+    /// * `log` - A log taken from an `ActionRecorder`
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if the replay reached the recorded final state, or a
+    /// `ReplayMismatch` holding both states otherwise.
+    pub fn replay(&self, log: &ActionLog<State, Action>) -> Result<(), ReplayMismatch<State>>
+    where
+        State: PartialEq,
+        Action: Clone,
+    {
+        self.replace_state(log.initial_state.clone());
+        for recorded in &log.actions {
+            self.dispatch(recorded.action.clone());
+        }
+
+        let actual = self.get_state();
+        if actual == log.final_state {
+            Ok(())
+        } else {
+            Err(ReplayMismatch {
+                expected: log.final_state.clone(),
+                actual,
+            })
+        }
+    }
+
     /// Replaces the current reducer with a new one.
     ///
     /// This is useful for hot-reloading scenarios or dynamic behavior changes.
@@ -324,7 +3044,7 @@ impl<State: Clone + Send + 'static, Action: Send + 'static> Store<State, Action>
     /// store.replace_reducer(Box::new(new_reducer));
     /// ```
     pub fn replace_reducer(&self, new_reducer: Box<dyn Reducer<State, Action> + Send + Sync>) {
-        let mut reducer = self.reducer.lock().unwrap();
+        let mut reducer = self.lock_reducer();
         *reducer = new_reducer;
     }
 
@@ -346,14 +3066,441 @@ impl<State: Clone + Send + 'static, Action: Send + 'static> Store<State, Action>
     /// assert_eq!(store.subscriber_count(), 0);
     /// ```
     pub fn subscriber_count(&self) -> usize {
-        self.subscribers.lock().unwrap().len()
+        lock(&self.subscribers).len()
+    }
+
+    /// Internal helper to check whether an update can be skipped
+    fn is_unchanged(&self, old: &State, new: &State) -> bool {
+        let unchanged = self.state_eq.as_ref().is_some_and(|eq| eq(old, new));
+        #[cfg(feature = "tracing")]
+        if unchanged {
+            tracing::debug!("state unchanged, skipping notification");
+        }
+        unchanged
+    }
+
+    /// Internal helper to run the reducer according to the panic policy
+    fn run_reducer(
+        &self,
+        reducer: &(dyn Reducer<State, Action> + Send + Sync),
+        state: &State,
+        action: &Action,
+    ) -> Result<State, ReducerError> {
+        let _span = enter_span!("zed.reduce");
+        let start = self.metrics.as_ref().map(|_| Instant::now());
+        let result = match self.panic_policy {
+            PanicPolicy::Propagate => reducer.try_reduce(state, action),
+            PanicPolicy::Recover => {
+                panic::catch_unwind(AssertUnwindSafe(|| reducer.try_reduce(state, action)))
+                    .unwrap_or_else(|payload| Err(Box::new(ReducerPanic::from_payload(payload))))
+            }
+        };
+        if let (Some(metrics), Some(start)) = (&self.metrics, start) {
+            metrics.record_reducer(action, start.elapsed());
+        }
+        #[cfg(feature = "tracing")]
+        if let Err(error) = &result {
+            tracing::warn!(%error, "reducer rejected action");
+        }
+        result
+    }
+
+    /// Internal helper to run a callback according to the panic policy
+    fn isolate<F: FnOnce()>(&self, f: F) {
+        isolate(self.panic_policy, f);
+    }
+
+    /// Internal helper describing how subscribers are called
+    fn delivery(&self) -> Delivery<State> {
+        Delivery {
+            policy: self.panic_policy,
+            fan_out: self.fan_out.clone(),
+        }
+    }
+
+    /// Internal helper to notify all subscribers, possibly in the background
+    fn notify_subscribers(&self, new_state: &Arc<State>) {
+        let _span = enter_span!("zed.notify", background = self.background.is_some());
+        let start = self.metrics.as_ref().map(|_| Instant::now());
+        match &self.background {
+            Some(background) => background(Arc::clone(new_state), self.delivery()),
+            None => deliver(&self.subscribers, &self.delivery(), new_state),
+        }
+        if let (Some(metrics), Some(start)) = (&self.metrics, start) {
+            metrics.record_notify(start.elapsed());
+        }
+    }
+
+    /// Internal helper to run matching listeners for applied actions.
+    ///
+    /// The listener list is snapshotted first so effects can dispatch
+    /// follow-up actions or register new listeners without deadlocking.
+    /// The outermost call on a thread then dispatches the follow-up actions
+    /// queued by effects, including those queued while dispatching them.
+    fn notify_listeners(&self, actions: &[Action]) {
+        let listeners = lock(&self.listeners).snapshot();
+        if listeners.is_empty() {
+            return;
+        }
+
+        // Drop the thread's queue even if a listener panics
+        struct Drain<'a, Action>(&'a Mutex<HashMap<ThreadId, VecDeque<Action>>>, ThreadId);
+        impl<Action> Drop for Drain<'_, Action> {
+            fn drop(&mut self) {
+                lock(self.0).remove(&self.1);
+            }
+        }
+
+        let thread = thread::current().id();
+        let drain = match lock(&self.follow_ups).entry(thread) {
+            Entry::Occupied(_) => None,
+            Entry::Vacant(entry) => {
+                entry.insert(VecDeque::new());
+                Some(Drain(&*self.follow_ups, thread))
+            }
+        };
+
+        for action in actions {
+            for listener in &listeners {
+                self.isolate(|| listener(action, self));
+            }
+        }
+
+        if drain.is_none() {
+            return;
+        }
+        loop {
+            let next = lock(&self.follow_ups)
+                .get_mut(&thread)
+                .and_then(VecDeque::pop_front);
+            let Some(action) = next else {
+                break;
+            };
+            self.dispatch(action);
+        }
+    }
+
+    /// Internal helper to queue follow-up actions of an effect.
+    ///
+    /// Outside of `notify_listeners()` the actions are dispatched right away.
+    fn queue_follow_ups(&self, actions: Vec<Action>) {
+        let mut follow_ups = lock(&self.follow_ups);
+        match follow_ups.get_mut(&thread::current().id()) {
+            Some(queue) => queue.extend(actions),
+            None => {
+                drop(follow_ups);
+                for action in actions {
+                    self.dispatch(action);
+                }
+            }
+        }
+    }
+
+    /// Internal helper to notify all error subscribers
+    fn notify_error_subscribers(&self, error: &ReducerError) {
+        let subscribers: Vec<_> = lock(&self.error_subscribers).values().cloned().collect();
+        for subscriber in &subscribers {
+            self.isolate(|| subscriber(error));
+        }
+    }
+}
+
+/// Coalesces the notifications of several dispatches into one.
+///
+/// Created by `Store::batch()`. Actions dispatched through the guard are
+/// applied immediately; subscribers are notified once with the final state
+/// when the guard is dropped, and only if the state changed.
+#[must_use = "notifications are sent as soon as the batch is dropped"]
+pub struct BatchGuard<State, Action>
+where
+    State: Clone + Send + 'static,
+    Action: Send + 'static,
+{
+    store: Store<State, Action>,
+    start: Arc<State>,
+    applied: Mutex<Vec<Action>>,
+}
+
+impl<State, Action> BatchGuard<State, Action>
+where
+    State: Clone + Send + 'static,
+    Action: Send + 'static,
+{
+    /// Applies an action as part of the batch.
+    ///
+    /// A rejected action leaves the state unchanged and is reported to error
+    /// subscribers right away.
+    pub fn dispatch(&self, action: Action) {
+        let _ = self.dispatch_try(action);
+    }
+
+    /// Applies an action as part of the batch, returning the reducer's error.
+    pub fn dispatch_try(&self, action: Action) -> Result<(), ReducerError> {
+        // The batch is recorded as a single step when it ends
+        match self.store.apply(&action, false) {
+            Ok(_) => {
+                lock(&self.applied).push(action);
+                Ok(())
+            }
+            Err(error) => {
+                self.store.notify_error_subscribers(&error);
+                Err(error)
+            }
+        }
+    }
+
+    /// Returns how many actions have been applied in this batch.
+    pub fn len(&self) -> usize {
+        lock(&self.applied).len()
+    }
+
+    /// Returns `true` if no action has been applied in this batch yet.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Ends the batch and notifies subscribers; same as dropping the guard.
+    pub fn end(self) {}
+}
+
+impl<State, Action> Drop for BatchGuard<State, Action>
+where
+    State: Clone + Send + 'static,
+    Action: Send + 'static,
+{
+    fn drop(&mut self) {
+        let applied = std::mem::take(
+            self.applied
+                .get_mut()
+                .unwrap_or_else(PoisonError::into_inner),
+        );
+        let changed = {
+            let _reducer = self.store.lock_reducer();
+            let state = self.store.state.load_full();
+            let changed =
+                !Arc::ptr_eq(&state, &self.start) && !self.store.is_unchanged(&self.start, &state);
+            if changed {
+                self.store.record_history(&state);
+            }
+            changed.then_some(state)
+        };
+        if let Some(state) = changed {
+            self.store.notify_subscribers(&state);
+        }
+        self.store.notify_listeners(&applied);
+    }
+}
+
+/// Configures a store before creating it.
+///
+/// Created by `Store::builder()`. State reads never take a lock; the
+/// builder chooses the lock serializing dispatches, how the store notifies
+/// subscribers and how it recovers from panics.
+#[must_use = "call `build()` to create the store"]
+pub struct StoreBuilder<State, Action> {
+    store: Store<State, Action>,
+}
+
+impl<State, Action> StoreBuilder<State, Action>
+where
+    State: Clone + Send + 'static,
+    Action: Send + 'static,
+{
+    /// Sets how the store reacts to panics in reducers and subscribers.
+    ///
+    /// See `Store::with_panic_policy()`.
+    pub fn panic_policy(mut self, policy: PanicPolicy) -> Self {
+        self.store = self.store.with_panic_policy(policy);
+        self
+    }
+
+    /// Sets which lock serializes dispatches.
+    ///
+    /// See `Store::with_dispatch_lock()`.
+    pub fn dispatch_lock(mut self, dispatch_lock: DispatchLock) -> Self {
+        self.store = self.store.with_dispatch_lock(dispatch_lock);
+        self
+    }
+
+    /// Sets on which thread and how often subscribers are notified.
+    ///
+    /// Error subscribers and action listeners always run synchronously.
+    pub fn notification_mode(mut self, mode: NotificationMode) -> Self
+    where
+        State: Sync,
+    {
+        self.store = self.store.with_notification_mode(mode);
+        self
+    }
+
+    /// Notifies subscribers in parallel on up to `threads` threads.
+    ///
+    /// See `Store::with_parallel_notifications()`.
+    pub fn parallel_notifications(mut self, threads: usize) -> Self
+    where
+        State: Sync,
+    {
+        self.store = self.store.with_parallel_notifications(threads);
+        self
+    }
+
+    /// Reserves room for the given number of subscribers and listeners.
+    pub fn subscriber_capacity(self, capacity: usize) -> Self {
+        lock(&self.store.subscribers).reserve(capacity);
+        lock(&self.store.listeners).reserve(capacity);
+        self
+    }
+
+    /// Skips notifications for actions that leave the state unchanged.
+    ///
+    /// See `Store::with_change_detection()`.
+    pub fn change_detection(mut self) -> Self
+    where
+        State: PartialEq,
+    {
+        self.store = self.store.with_change_detection();
+        self
+    }
+
+    /// Enables metrics collection.
+    ///
+    /// See `Store::with_metrics()`.
+    pub fn metrics(mut self) -> Self
+    where
+        Action: fmt::Debug,
+    {
+        self.store = self.store.with_metrics();
+        self
+    }
+
+    /// Creates the configured store.
+    pub fn build(self) -> Store<State, Action> {
+        self.store
+    }
+}
+
+#[cfg(feature = "async")]
+impl<State, Action> Store<State, Action>
+where
+    State: Clone + Send + Sync + 'static,
+    Action: Send + 'static,
+{
+    /// Returns a Tokio `watch` receiver that always holds the latest state.
+    ///
+    /// Async tasks can `.changed().await` on the receiver to be woken up
+    /// whenever the state is updated. All receivers share a single channel,
+    /// fed by one internal subscriber registered on first use.
+    ///
+    /// Requires the `async` feature.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use zed::{Store, create_reducer};
+    /// # #[derive(Clone)] struct State { count: i32 }
+    /// # #[derive(Clone)] enum Action { Increment }
+    /// # let store = Store::new(State { count: 0 }, Box::new(create_reducer(|state: &State, _: &Action| State { count: state.count + 1 })));
+    /// let mut receiver = store.watch();
+    ///
+    /// store.dispatch(Action::Increment);
+    /// assert!(receiver.has_changed().unwrap());
+    /// assert_eq!(receiver.borrow_and_update().count, 1);
+    /// ```
+    pub fn watch(&self) -> watch::Receiver<State> {
+        self.state_watch
+            .get_or_init(|| {
+                let cell = Arc::clone(&self.state_watch);
+                self.subscribe(move |state: &State| {
+                    if let Some(sender) = cell.get() {
+                        sender.send_replace(state.clone());
+                    }
+                });
+                watch::Sender::new(self.get_state())
+            })
+            .subscribe()
+    }
+
+    /// Returns a Tokio `broadcast` receiver of every applied action.
+    ///
+    /// Each receiver observes the actions dispatched after it was created,
+    /// in dispatch order. Actions rejected by a fallible reducer are not
+    /// broadcast. See [`ACTION_STREAM_CAPACITY`] for the buffer size.
+    ///
+    /// Requires the `async` feature.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use zed::{Store, create_reducer};
+    /// # #[derive(Clone)] struct State { count: i32 }
+    /// # #[derive(Clone, Debug, PartialEq)] enum Action { Increment }
+    /// # let store = Store::new(State { count: 0 }, Box::new(create_reducer(|state: &State, _: &Action| State { count: state.count + 1 })));
+    /// let mut actions = store.action_stream();
+    ///
+    /// store.dispatch(Action::Increment);
+    /// assert_eq!(actions.try_recv().unwrap(), Action::Increment);
+    /// ```
+    pub fn action_stream(&self) -> broadcast::Receiver<Action>
+    where
+        Action: Clone,
+    {
+        self.action_broadcast
+            .get_or_init(|| {
+                let cell = Arc::clone(&self.action_broadcast);
+                self.add_listener(
+                    |_| true,
+                    move |action: &Action, _| {
+                        if let Some(sender) = cell.get() {
+                            let _ = sender.send(action.clone());
+                        }
+                    },
+                );
+                broadcast::Sender::new(ACTION_STREAM_CAPACITY)
+            })
+            .subscribe()
     }
 
-    /// Internal helper to notify all subscribers
-    fn notify_subscribers(&self, new_state: &State) {
-        let subscribers = self.subscribers.lock().unwrap();
-        for subscriber in subscribers.values() {
-            subscriber(new_state);
+    /// Dispatches every action from an async stream until it ends.
+    ///
+    /// Actions that are ready together, e.g. several messages read from a
+    /// socket at once, are applied as one batch with a single notification;
+    /// see [`STREAM_BATCH_LIMIT`] for the largest batch.
+    ///
+    /// Requires the `async` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `actions` - The stream of actions to dispatch
+    ///
+    /// # Returns
+    ///
+    /// The number of actions received from the stream.
+    pub async fn dispatch_from_stream<S>(&self, actions: S) -> usize
+    where
+        S: Stream<Item = Action>,
+    {
+        let mut actions = std::pin::pin!(actions);
+        let mut received = 0;
+        loop {
+            let (batch, ended) = poll_fn(|cx| {
+                let mut batch = Vec::new();
+                while batch.len() < STREAM_BATCH_LIMIT {
+                    match actions.as_mut().poll_next(cx) {
+                        Poll::Ready(Some(action)) => batch.push(action),
+                        Poll::Ready(None) => return Poll::Ready((batch, true)),
+                        Poll::Pending if batch.is_empty() => return Poll::Pending,
+                        Poll::Pending => break,
+                    }
+                }
+                Poll::Ready((batch, false))
+            })
+            .await;
+
+            received += batch.len();
+            self.dispatch_batch(batch);
+            if ended {
+                return received;
+            }
         }
     }
 }
@@ -527,4 +3674,30 @@ mod tests {
         store.dispatch(TestAction::Increment);
         assert_eq!(store.get_state().counter, 11); // 1 + 10
     }
+
+    #[test]
+    fn test_concurrent_readers() {
+        let store = Arc::new(create_test_store());
+        store.dispatch(TestAction::SetValue(7));
+
+        // Both readers must be inside `with_state` at the same time to pass
+        // the barrier, which would deadlock if reads were serialized.
+        let barrier = Arc::new(std::sync::Barrier::new(2));
+        let handles: Vec<_> = (0..2)
+            .map(|_| {
+                let store = Arc::clone(&store);
+                let barrier = Arc::clone(&barrier);
+                thread::spawn(move || {
+                    store.with_state(|state| {
+                        barrier.wait();
+                        state.counter
+                    })
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), 7);
+        }
+    }
 }
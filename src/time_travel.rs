@@ -0,0 +1,143 @@
+//! # Time Travel Module
+//!
+//! Undo, redo and jumps through a store's history, enabled on an existing
+//! [`Store`](crate::Store) with
+//! [`enable_time_travel`](crate::Store::enable_time_travel).
+//!
+//! Once enabled, every state the store commits is recorded in a
+//! [`StateManager`] timeline, whichever way it was produced: dispatches,
+//! batches, optimistic updates and `replace_state()`. `undo()`, `redo()` and `jump_to()` install a recorded
+//! state and notify subscribers like any other change, so code that only
+//! dispatches and subscribes keeps working unchanged.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use zed::{Store, TimeTravelConfig, create_reducer};
+//!
+//! #[derive(Clone)]
+//! struct State { count: i32 }
+//!
+//! enum Action { Increment }
+//!
+//! let store = Store::new(
+//!     State { count: 0 },
+//!     Box::new(create_reducer(|state: &State, _: &Action| State { count: state.count + 1 })),
+//! );
+//! store.enable_time_travel(TimeTravelConfig::new().max_history(100));
+//!
+//! store.dispatch(Action::Increment);
+//! store.dispatch(Action::Increment);
+//!
+//! store.undo();
+//! assert_eq!(store.get_state().count, 1);
+//! store.jump_to(0);
+//! assert_eq!(store.get_state().count, 0);
+//! store.redo();
+//! assert_eq!(store.get_state().count, 1);
+//! ```
+
+use crate::timeline::StateManager;
+use std::convert::Infallible;
+use std::sync::Arc;
+
+/// Settings for [`Store::enable_time_travel`](crate::Store::enable_time_travel).
+#[derive(Clone, Debug, Default)]
+pub struct TimeTravelConfig {
+    max_history: Option<usize>,
+}
+
+impl TimeTravelConfig {
+    /// Creates a configuration that keeps the whole history.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits the number of recorded states, dropping the oldest ones when
+    /// the limit is exceeded (at least two states are always kept).
+    pub fn max_history(mut self, max_history: usize) -> Self {
+        self.max_history = Some(max_history.max(2));
+        self
+    }
+}
+
+/// The history recorded for a store, kept in a [`StateManager`] that
+/// receives committed states instead of dispatching actions.
+///
+/// States past the limit stay in the timeline, hidden, until as many have
+/// accumulated as the limit, and are then dropped in one `squash()`, so
+/// recording stays O(1) amortized.
+pub(crate) struct TimeTravel<State> {
+    timeline: StateManager<Arc<State>, Infallible>,
+    /// Oldest states still in the timeline but past the limit
+    hidden: usize,
+    max_history: Option<usize>,
+}
+
+impl<State: 'static> TimeTravel<State> {
+    pub(crate) fn new(initial_state: Arc<State>, config: TimeTravelConfig) -> Self {
+        Self {
+            timeline: StateManager::new(initial_state, |_: &Arc<State>, never: &Infallible| {
+                match *never {}
+            }),
+            hidden: 0,
+            max_history: config.max_history,
+        }
+    }
+
+    /// Records a committed state, discarding the states that could have
+    /// been redone and the oldest one once the limit is exceeded.
+    pub(crate) fn record(&mut self, state: &Arc<State>) {
+        if Arc::ptr_eq(self.timeline.current_state(), state) {
+            return;
+        }
+        self.timeline.record(Arc::clone(state));
+        let Some(max_history) = self.max_history else {
+            return;
+        };
+        // Never shrinks, so states dropped before a dispatch after undo
+        // stay dropped
+        self.hidden = self
+            .hidden
+            .max(self.timeline.history_len().saturating_sub(max_history));
+        if self.hidden >= max_history {
+            self.timeline.squash(..=self.hidden);
+            self.hidden = 0;
+        }
+    }
+
+    /// Moves to the previous state and returns it.
+    pub(crate) fn undo(&mut self) -> Option<Arc<State>> {
+        self.jump_to(self.position().1.checked_sub(1)?)
+    }
+
+    /// Moves to the next state and returns it.
+    pub(crate) fn redo(&mut self) -> Option<Arc<State>> {
+        self.jump_to(self.position().1 + 1)
+    }
+
+    /// Moves to the given position and returns its state, or `None` if the
+    /// position is out of range or already current.
+    pub(crate) fn jump_to(&mut self, position: usize) -> Option<Arc<State>> {
+        if position == self.position().1 || !self.timeline.jump_to(position + self.hidden) {
+            return None;
+        }
+        Some(Arc::clone(self.timeline.current_state()))
+    }
+
+    pub(crate) fn can_undo(&self) -> bool {
+        self.timeline.current_position() > self.hidden
+    }
+
+    pub(crate) fn can_redo(&self) -> bool {
+        self.timeline.can_redo()
+    }
+
+    /// Returns the number of recorded states and the current position.
+    pub(crate) fn position(&self) -> (usize, usize) {
+        (
+            self.timeline.history_len() - self.hidden,
+            self.timeline.current_position() - self.hidden,
+        )
+    }
+}
@@ -2,9 +2,23 @@
 //!
 //! This module provides time-reversible state management, allowing you to:
 //! - Maintain a complete history of state changes
-//! - Rewind to previous states
+//! - Rewind to previous states and redo them again
+//! - Jump to any point in history
+//! - Rewind to a point in time, using the time each state was recorded
+//! - Return to named checkpoints, created by hand or every N actions or
+//!   every interval
 //! - Branch off from any point in history
-//! - Create alternative timelines
+//! - Create alternative timelines as named branches, switch between them and
+//!   merge them back
+//! - Undo a single past action while keeping the later ones
+//! - Squash and compact the history to reclaim memory, and delete abandoned
+//!   branches with a retention policy
+//! - Move a long history to delta-compressed storage
+//! - Save a session's history to disk and load it again
+//! - Play the history back at real or scaled speed
+//! - Export the history as JSON for visualization tools
+//! - Observe changes to the history, e.g. to update undo and redo buttons
+//! - Share one timeline between threads with `SharedStateManager`
 //!
 //! This is particularly useful for:
 //! - Undo/redo functionality
@@ -12,68 +26,1168 @@
 //! - Git-like state branching
 //! - A/B testing with state variations
 
-use std::any::Any;
+use crate::delta::{DeltaStateManager, Diff, FieldChange, JsonDelta, StateDiff};
+use crate::persist::{PersistError, read_checked, write_checked};
+use crate::reducer::Reducer;
+use crate::store::SubscriptionId;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::ops::{Bound, RangeBounds};
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Reducer shared between a timeline and its branches.
+pub(crate) type TimelineReducer<T, Action> = Arc<dyn Fn(&T, &Action) -> T + Send + Sync>;
+
+/// Observer notified of changes to a timeline.
+type TimelineSubscriber<T, Action> =
+    Arc<dyn Fn(&TimelineEvent, &StateManager<T, Action>) + Send + Sync>;
+
+/// A change to a [`StateManager`], passed to its subscribers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TimelineEvent {
+    /// A new state was added at `position`, by a dispatch or a merge
+    Dispatched {
+        /// Position of the new state
+        position: usize,
+    },
+    /// The timeline moved back to an earlier state
+    Rewound {
+        /// Position before the move
+        from: usize,
+        /// Position after the move
+        to: usize,
+    },
+    /// The timeline moved forward to a later state
+    Redone {
+        /// Position before the move
+        from: usize,
+        /// Position after the move
+        to: usize,
+    },
+    /// Another branch was checked out
+    BranchSwitched {
+        /// The previously checked-out branch
+        from: String,
+        /// The newly checked-out branch
+        to: String,
+    },
+    /// History entries were removed by `squash()`, `compact()`,
+    /// `undo_entry()` or `gc()`
+    HistoryRewritten {
+        /// Number of entries removed
+        removed: usize,
+    },
+}
+
+/// Limits applied by [`StateManager::gc`].
+#[derive(Clone, Debug, Default)]
+pub struct RetentionPolicy {
+    max_history: Option<usize>,
+    max_branch_age: Option<Duration>,
+}
+
+impl RetentionPolicy {
+    /// Creates a policy that keeps everything.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits every branch to its newest states, collapsing the older ones
+    /// into the oldest kept state (at least one state is always kept).
+    pub fn max_history(mut self, max_history: usize) -> Self {
+        self.max_history = Some(max_history.max(1));
+        self
+    }
+
+    /// Deletes branches other than the checked-out one whose newest state
+    /// was recorded longer ago than `max_age`.
+    pub fn max_branch_age(mut self, max_age: Duration) -> Self {
+        self.max_branch_age = Some(max_age);
+        self
+    }
+}
+
+/// What a run of [`StateManager::gc`] reclaimed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GcStats {
+    /// Number of branches deleted
+    pub branches_removed: usize,
+    /// Number of history entries dropped, including those of deleted branches
+    pub states_removed: usize,
+}
+
+/// When [`StateManager`] creates checkpoints on its own, set with
+/// [`StateManager::set_checkpoint_policy`].
+///
+/// Automatic checkpoints are named `<prefix>-<n>`, with `n` counting up
+/// from 1. Both triggers are checked when an action is dispatched; the
+/// counters restart after every automatic checkpoint.
+#[derive(Clone, Debug)]
+pub struct CheckpointPolicy {
+    prefix: String,
+    every_actions: Option<usize>,
+    every: Option<Duration>,
+    keep_last: Option<usize>,
+}
+
+impl CheckpointPolicy {
+    /// Creates a policy naming its checkpoints after `prefix`, which by
+    /// itself never creates any.
+    pub fn new(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            every_actions: None,
+            every: None,
+            keep_last: None,
+        }
+    }
+
+    /// Creates a checkpoint after every `count` dispatched actions.
+    pub fn every_actions(mut self, count: usize) -> Self {
+        self.every_actions = Some(count.max(1));
+        self
+    }
+
+    /// Creates a checkpoint at the first dispatch after `interval` has
+    /// passed since the last automatic one.
+    pub fn every(mut self, interval: Duration) -> Self {
+        self.every = Some(interval);
+        self
+    }
+
+    /// Keeps only the newest `count` automatic checkpoints of each branch
+    /// (at least one is always kept).
+    pub fn keep_last(mut self, count: usize) -> Self {
+        self.keep_last = Some(count.max(1));
+        self
+    }
+}
+
+/// A checkpoint policy with its progress towards the next checkpoint.
+#[derive(Clone)]
+struct AutoCheckpoint {
+    policy: CheckpointPolicy,
+    actions: usize,
+    since: SystemTime,
+    next_id: u64,
+}
+
+/// First word of a saved timeline file.
+const FILE_MAGIC: &str = "zed-timeline";
+/// Version of the saved timeline format written by `StateManager::save()`.
+const FILE_VERSION: u32 = 1;
+
+/// Body of a saved timeline file, borrowed when saving and owned when loading.
+#[derive(Serialize, Deserialize)]
+struct SavedTimeline<History, Timestamps, Checkpoints> {
+    history: History,
+    timestamps: Timestamps,
+    current: usize,
+    checkpoints: Checkpoints,
+}
+
+/// Document written by `StateManager::export_json()`.
+#[derive(Serialize)]
+struct ExportedTimeline<'a> {
+    branch: &'a str,
+    current: usize,
+    checkpoints: BTreeMap<&'a str, usize>,
+    branches: Vec<ExportedBranch<'a>>,
+    entries: Vec<ExportedEntry>,
+}
+
+#[derive(Serialize)]
+struct ExportedBranch<'a> {
+    name: &'a str,
+    parent: Option<&'a str>,
+    fork_point: usize,
+}
+
+#[derive(Serialize)]
+struct ExportedEntry {
+    index: usize,
+    timestamp_ms: u64,
+    /// Debug representation of the dispatched action
+    action: Option<String>,
+    /// The whole state, for the initial entry only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    state: Option<Value>,
+    /// Changes from the previous entry, for all other entries
+    #[serde(skip_serializing_if = "Option::is_none")]
+    changes: Option<Vec<FieldChange>>,
+}
+
+/// The history of a branch that is not checked out, with its actions
+/// shared as `A`.
+#[derive(Clone)]
+struct ParkedBranch<T, A> {
+    history: Vec<T>,
+    timestamps: Vec<SystemTime>,
+    actions: Vec<Option<A>>,
+    current: usize,
+    checkpoints: HashMap<String, usize>,
+}
+
+/// Where a branch was created from.
+#[derive(Clone)]
+struct BranchOrigin {
+    /// The branch it was created from, if that still exists
+    parent: Option<String>,
+    /// Position in the parent's history at which it was created
+    fork_point: usize,
+    /// Whether the branch still starts with the timeline's initial state
+    rooted: bool,
+}
+
+/// Name of the branch a new timeline starts on.
+const MAIN_BRANCH: &str = "main";
 
 /// A state manager that maintains a complete history of state changes and supports time travel.
-pub struct StateManager<T: Clone> {
+///
+/// `Action` is the type of the actions the reducer accepts, so dispatching
+/// an action of the wrong type is a compile error.
+pub struct StateManager<T: Clone, Action> {
     /// Vector containing the complete history of states
     history: Vec<T>,
+    /// When each state in the history was recorded
+    timestamps: Vec<SystemTime>,
+    /// The action that produced each state, if it was dispatched
+    actions: Vec<Option<Arc<Action>>>,
     /// Current position in the history (0-indexed)
     current: usize,
     /// Reducer function that applies actions to create new states
-    reducer: fn(&T, &dyn Any) -> T,
+    reducer: TimelineReducer<T, Action>,
+    /// Named positions in the history
+    checkpoints: HashMap<String, usize>,
+    /// Policy for creating checkpoints on dispatch
+    auto_checkpoint: Option<AutoCheckpoint>,
+    /// Name of the checked-out branch, whose history the fields above hold
+    branch: String,
+    /// Origins of all branches, including the checked-out one
+    origins: BTreeMap<String, BranchOrigin>,
+    /// Branches that are not checked out
+    parked: HashMap<String, ParkedBranch<T, Arc<Action>>>,
+    /// Observers of changes to the timeline
+    subscribers: Vec<(SubscriptionId, TimelineSubscriber<T, Action>)>,
+    next_subscriber_id: SubscriptionId,
 }
 
-impl<T: Clone> Clone for StateManager<T> {
+/// Clones the history and branches; subscribers stay with the original.
+impl<T: Clone, Action> Clone for StateManager<T, Action> {
     fn clone(&self) -> Self {
         Self {
             history: self.history.clone(),
+            timestamps: self.timestamps.clone(),
+            actions: self.actions.clone(),
             current: self.current,
-            reducer: self.reducer,
+            reducer: Arc::clone(&self.reducer),
+            checkpoints: self.checkpoints.clone(),
+            auto_checkpoint: self.auto_checkpoint.clone(),
+            branch: self.branch.clone(),
+            origins: self.origins.clone(),
+            parked: self.parked.clone(),
+            subscribers: Vec::new(),
+            next_subscriber_id: 0,
         }
     }
 }
 
-impl<T: Clone> StateManager<T> {
+impl<T: Clone, Action> StateManager<T, Action> {
     /// Creates a new StateManager with an initial state and reducer function.
-    pub fn new(initial_state: T, reducer: fn(&T, &dyn Any) -> T) -> Self {
+    ///
+    /// The reducer may be a plain function or a closure capturing
+    /// configuration or services.
+    pub fn new<F>(initial_state: T, reducer: F) -> Self
+    where
+        F: Fn(&T, &Action) -> T + Send + Sync + 'static,
+    {
+        Self::from_parts(
+            vec![initial_state],
+            vec![SystemTime::now()],
+            0,
+            Arc::new(reducer),
+            HashMap::new(),
+        )
+    }
+
+    fn from_parts(
+        history: Vec<T>,
+        timestamps: Vec<SystemTime>,
+        current: usize,
+        reducer: TimelineReducer<T, Action>,
+        checkpoints: HashMap<String, usize>,
+    ) -> Self {
+        let origin = BranchOrigin {
+            parent: None,
+            fork_point: 0,
+            rooted: true,
+        };
         Self {
-            history: vec![initial_state],
-            current: 0,
+            actions: vec![None; history.len()],
+            history,
+            timestamps,
+            current,
             reducer,
+            checkpoints,
+            auto_checkpoint: None,
+            branch: MAIN_BRANCH.to_string(),
+            origins: BTreeMap::from([(MAIN_BRANCH.to_string(), origin)]),
+            parked: HashMap::new(),
+            subscribers: Vec::new(),
+            next_subscriber_id: 0,
         }
     }
 
+    /// Creates a new StateManager driven by a [`Reducer`], such as one built
+    /// with `create_reducer()`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use zed::{StateManager, create_reducer};
+    ///
+    /// let step = 5;
+    /// let mut timeline = StateManager::from_reducer(
+    ///     0,
+    ///     create_reducer(move |count: &i32, _: &()| count + step),
+    /// );
+    /// timeline.dispatch(());
+    /// assert_eq!(*timeline.current_state(), 5);
+    /// ```
+    pub fn from_reducer<R>(initial_state: T, reducer: R) -> Self
+    where
+        R: Reducer<T, Action> + Send + Sync + 'static,
+    {
+        Self::new(initial_state, move |state: &T, action: &Action| {
+            reducer.reduce(state, action)
+        })
+    }
+
     /// Dispatches an action to create a new state.
-    pub fn dispatch<A: 'static + Clone>(&mut self, action: A) {
+    pub fn dispatch(&mut self, action: Action) {
         let current_state = &self.history[self.current];
         let new_state = (self.reducer)(current_state, &action);
+        self.push(new_state, Some(Arc::new(action)));
+        self.auto_checkpoint();
+    }
+
+    /// Appends a state produced elsewhere, like `dispatch()` without an
+    /// action; used by `Store`'s time travel.
+    pub(crate) fn record(&mut self, state: T) {
+        self.push(state, None);
+    }
+
+    /// Creates a checkpoint if the checkpoint policy is due.
+    fn auto_checkpoint(&mut self) {
+        let Some(auto) = &mut self.auto_checkpoint else {
+            return;
+        };
+        auto.actions += 1;
+        let now = SystemTime::now();
+        let due = auto
+            .policy
+            .every_actions
+            .is_some_and(|count| auto.actions >= count)
+            || auto.policy.every.is_some_and(|interval| {
+                now.duration_since(auto.since)
+                    .is_ok_and(|elapsed| elapsed >= interval)
+            });
+        if !due {
+            return;
+        }
+
+        let name = format!("{}-{}", auto.policy.prefix, auto.next_id);
+        auto.actions = 0;
+        auto.since = now;
+        auto.next_id += 1;
+        let keep_last = auto.policy.keep_last;
+        self.checkpoints.insert(name, self.current);
+
+        if let Some(keep_last) = keep_last {
+            let mut automatic = self.auto_checkpoints();
+            let excess = automatic.len().saturating_sub(keep_last);
+            automatic.truncate(excess);
+            let stale: Vec<String> = automatic
+                .into_iter()
+                .map(|(_, name)| name.to_string())
+                .collect();
+            for name in stale {
+                self.checkpoints.remove(&name);
+            }
+        }
+    }
 
+    /// Lists the checked-out branch's automatic checkpoints, oldest first.
+    fn auto_checkpoints(&self) -> Vec<(u64, &str)> {
+        let Some(auto) = &self.auto_checkpoint else {
+            return Vec::new();
+        };
+        let mut automatic: Vec<(u64, &str)> = self
+            .checkpoints
+            .keys()
+            .filter_map(|name| {
+                let id = name
+                    .strip_prefix(auto.policy.prefix.as_str())?
+                    .strip_prefix('-')?;
+                Some((id.parse().ok()?, name.as_str()))
+            })
+            .collect();
+        automatic.sort_unstable();
+        automatic
+    }
+
+    /// Appends a state after the current position, discarding the states
+    /// after it.
+    fn push(&mut self, new_state: T, action: Option<Arc<Action>>) {
         // If we're not at the end, truncate future history
         if self.current + 1 < self.history.len() {
             self.history.truncate(self.current + 1);
+            self.timestamps.truncate(self.current + 1);
+            self.actions.truncate(self.current + 1);
+            let len = self.history.len();
+            self.checkpoints.retain(|_, index| *index < len);
         }
 
         self.history.push(new_state);
+        self.timestamps.push(SystemTime::now());
+        self.actions.push(action);
         self.current += 1;
+        self.notify(TimelineEvent::Dispatched {
+            position: self.current,
+        });
+    }
+
+    /// Moves to a position known to be in range, notifying subscribers.
+    fn move_to(&mut self, index: usize) {
+        let from = std::mem::replace(&mut self.current, index);
+        if index < from {
+            self.notify(TimelineEvent::Rewound { from, to: index });
+        } else if index > from {
+            self.notify(TimelineEvent::Redone { from, to: index });
+        }
+    }
+
+    fn notify(&self, event: TimelineEvent) {
+        for (_, subscriber) in &self.subscribers {
+            subscriber(&event, self);
+        }
+    }
+
+    /// Subscribes to changes of the timeline.
+    ///
+    /// The subscriber receives each event together with the timeline, so
+    /// it can query e.g. `can_undo()` and `can_redo()`. Moves that leave
+    /// the position unchanged are not reported.
+    ///
+    /// # Returns
+    ///
+    /// A `SubscriptionId` that can be passed to `unsubscribe()`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::sync::{Arc, Mutex};
+    /// use zed::{StateManager, TimelineEvent};
+    ///
+    /// let undo_enabled = Arc::new(Mutex::new(false));
+    /// let mut timeline = StateManager::new(0, |count: &i32, step: &i32| count + step);
+    ///
+    /// let button = Arc::clone(&undo_enabled);
+    /// timeline.subscribe(move |_: &TimelineEvent, timeline: &StateManager<i32, i32>| {
+    ///     *button.lock().unwrap() = timeline.can_undo();
+    /// });
+    ///
+    /// timeline.dispatch(1);
+    /// assert!(*undo_enabled.lock().unwrap());
+    /// timeline.rewind(1);
+    /// assert!(!*undo_enabled.lock().unwrap());
+    /// ```
+    pub fn subscribe<F>(&mut self, f: F) -> SubscriptionId
+    where
+        F: Fn(&TimelineEvent, &StateManager<T, Action>) + Send + Sync + 'static,
+    {
+        let id = self.next_subscriber_id;
+        self.next_subscriber_id += 1;
+        self.subscribers.push((id, Arc::new(f)));
+        id
+    }
+
+    /// Removes a subscriber.
+    ///
+    /// # Returns
+    ///
+    /// `true` if a subscriber with that ID existed.
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        let len = self.subscribers.len();
+        self.subscribers.retain(|(subscriber, _)| *subscriber != id);
+        self.subscribers.len() < len
     }
 
     /// Rewinds the timeline by the specified number of steps.
     pub fn rewind(&mut self, steps: usize) {
-        if steps >= self.current {
-            self.current = 0;
-        } else {
-            self.current -= steps;
+        self.move_to(self.current.saturating_sub(steps));
+    }
+
+    /// Moves the timeline forward again by the specified number of steps.
+    ///
+    /// Only states left behind by `rewind()` can be redone; the next
+    /// dispatch discards them. Redoing more steps than available stops at
+    /// the newest state.
+    pub fn redo(&mut self, steps: usize) {
+        self.move_to(
+            self.current
+                .saturating_add(steps)
+                .min(self.history.len() - 1),
+        );
+    }
+
+    /// Returns `true` if there is an earlier state to rewind to.
+    pub fn can_undo(&self) -> bool {
+        self.current > 0
+    }
+
+    /// Returns `true` if there is a rewound state to redo.
+    pub fn can_redo(&self) -> bool {
+        self.current + 1 < self.history.len()
+    }
+
+    /// Moves to the state at the given position in the history.
+    ///
+    /// Position 0 is the initial state. Unlike `dispatch()`, jumping keeps
+    /// the states after the target, so it is possible to jump forward again.
+    ///
+    /// # Returns
+    ///
+    /// `false`, without moving, if the position is past the end of the history.
+    pub fn jump_to(&mut self, index: usize) -> bool {
+        if index >= self.history.len() {
+            return false;
         }
+        self.move_to(index);
+        true
+    }
+
+    /// Moves by `offset` states relative to the current position; negative
+    /// offsets move back.
+    ///
+    /// # Returns
+    ///
+    /// `false`, without moving, if the target lies outside the history.
+    pub fn jump_by(&mut self, offset: isize) -> bool {
+        self.current
+            .checked_add_signed(offset)
+            .is_some_and(|index| self.jump_to(index))
+    }
+
+    /// Moves back one state.
+    ///
+    /// # Returns
+    ///
+    /// `false` if already at the initial state.
+    pub fn step_back(&mut self) -> bool {
+        self.jump_by(-1)
+    }
+
+    /// Moves forward one state.
+    ///
+    /// # Returns
+    ///
+    /// `false` if already at the newest state.
+    pub fn step_forward(&mut self) -> bool {
+        self.jump_by(1)
+    }
+
+    /// Tags the current position with a name, replacing any earlier
+    /// checkpoint with the same name.
+    ///
+    /// The checkpoint stays valid across later dispatches, unless rewinding
+    /// and dispatching again discards the state it points to.
+    pub fn checkpoint(&mut self, name: impl Into<String>) {
+        self.checkpoints.insert(name.into(), self.current);
+    }
+
+    /// Moves to the state tagged by a checkpoint.
+    ///
+    /// # Returns
+    ///
+    /// `false`, without moving, if no checkpoint with that name exists.
+    pub fn restore_checkpoint(&mut self, name: &str) -> bool {
+        match self.checkpoints.get(name) {
+            Some(&index) => self.jump_to(index),
+            None => false,
+        }
+    }
+
+    /// Returns the history position a checkpoint points to.
+    pub fn checkpoint_position(&self, name: &str) -> Option<usize> {
+        self.checkpoints.get(name).copied()
+    }
+
+    /// Removes a checkpoint.
+    ///
+    /// # Returns
+    ///
+    /// `true` if a checkpoint with that name existed.
+    pub fn remove_checkpoint(&mut self, name: &str) -> bool {
+        self.checkpoints.remove(name).is_some()
+    }
+
+    /// Creates checkpoints automatically on dispatch, replacing any previous
+    /// policy.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use zed::{CheckpointPolicy, StateManager};
+    ///
+    /// let mut timeline = StateManager::new(0, |count: &i32, step: &i32| count + step);
+    /// timeline.set_checkpoint_policy(
+    ///     CheckpointPolicy::new("autosave")
+    ///         .every_actions(2)
+    ///         .every(Duration::from_secs(60))
+    ///         .keep_last(3),
+    /// );
+    ///
+    /// for step in [1, 2, 3] {
+    ///     timeline.dispatch(step);
+    /// }
+    /// assert_eq!(timeline.latest_auto_checkpoint(), Some("autosave-1"));
+    ///
+    /// // Restore the last good state
+    /// timeline.restore_checkpoint("autosave-1");
+    /// assert_eq!(*timeline.current_state(), 3);
+    /// ```
+    pub fn set_checkpoint_policy(&mut self, policy: CheckpointPolicy) {
+        self.auto_checkpoint = Some(AutoCheckpoint {
+            policy,
+            actions: 0,
+            since: SystemTime::now(),
+            next_id: 1,
+        });
+    }
+
+    /// Stops creating checkpoints automatically. Checkpoints already
+    /// created are kept.
+    pub fn clear_checkpoint_policy(&mut self) {
+        self.auto_checkpoint = None;
+    }
+
+    /// Returns the name of the newest automatic checkpoint on the
+    /// checked-out branch, recognized by the current policy's prefix.
+    ///
+    /// # Returns
+    ///
+    /// `None` if there is none, or no checkpoint policy is set.
+    pub fn latest_auto_checkpoint(&self) -> Option<&str> {
+        self.auto_checkpoints().last().map(|&(_, name)| name)
+    }
+
+    /// Collapses a range of history entries into one holding the last
+    /// state of the range, e.g. to turn the steps of a file import into a
+    /// single undo step.
+    ///
+    /// Later positions move back accordingly. The current position and
+    /// checkpoints inside the range move to the collapsed entry.
+    ///
+    /// # Returns
+    ///
+    /// `false`, without changing anything, if the range is empty, extends
+    /// past the end of the history, or would drop a state the checked-out
+    /// branch shares with its parent or children (see `gc()`).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use zed::StateManager;
+    ///
+    /// let mut timeline = StateManager::new(0, |count: &i32, step: &i32| count + step);
+    /// for step in [1, 2, 3] {
+    ///     timeline.dispatch(step);
+    /// }
+    ///
+    /// assert!(timeline.squash(1..=3));
+    /// assert_eq!(timeline.history_len(), 2);
+    ///
+    /// // One undo step reverts the whole range
+    /// timeline.step_back();
+    /// assert_eq!(*timeline.current_state(), 0);
+    /// ```
+    pub fn squash<R>(&mut self, range: R) -> bool
+    where
+        R: RangeBounds<usize>,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end,
+            Bound::Excluded(&end) => match end.checked_sub(1) {
+                Some(end) => end,
+                None => return false,
+            },
+            Bound::Unbounded => self.history.len() - 1,
+        };
+        if start > end || end >= self.history.len() {
+            return false;
+        }
+        if self.shared_prefix().is_some_and(|shared| start <= shared) && start < end {
+            return false;
+        }
+        let keep: Vec<bool> = (0..self.history.len())
+            .map(|index| index < start || index >= end)
+            .collect();
+        self.retain_entries(&keep);
+        true
+    }
+
+    /// Drops every state except the initial, current and newest ones,
+    /// those marked by checkpoints, and the history the checked-out branch
+    /// shares with its parent or children.
+    ///
+    /// Each run of dropped states collapses into the kept state after it,
+    /// as with `squash()`.
+    ///
+    /// # Returns
+    ///
+    /// The number of states dropped.
+    pub fn compact(&mut self) -> usize {
+        let mut keep = vec![false; self.history.len()];
+        keep[0] = true;
+        keep[self.current] = true;
+        keep[self.history.len() - 1] = true;
+        for &index in self.checkpoints.values() {
+            keep[index] = true;
+        }
+        if let Some(shared) = self.shared_prefix() {
+            keep[..=shared].fill(true);
+        }
+        let dropped = keep.iter().filter(|&&kept| !kept).count();
+        self.retain_entries(&keep);
+        dropped
+    }
+
+    /// Removes the history entries not marked in `keep`, moving positions
+    /// that pointed at them to the next kept entry. The last entry must be
+    /// kept.
+    fn retain_entries(&mut self, keep: &[bool]) {
+        let Some(first_dropped) = keep.iter().position(|&kept| !kept) else {
+            return;
+        };
+
+        // New position of every old entry, rounding dropped ones up
+        let mut remap = vec![0; keep.len()];
+        let mut next = keep.iter().filter(|&&kept| kept).count();
+        for index in (0..keep.len()).rev() {
+            if keep[index] {
+                next -= 1;
+            }
+            remap[index] = next;
+        }
+
+        let mut kept = keep.iter();
+        self.history.retain(|_| *kept.next().unwrap_or(&true));
+        let mut kept = keep.iter();
+        self.timestamps.retain(|_| *kept.next().unwrap_or(&true));
+        // A state that absorbed dropped ones took more than one action
+        for index in 1..keep.len() {
+            if keep[index] && !keep[index - 1] {
+                self.actions[index] = None;
+            }
+        }
+        let mut kept = keep.iter();
+        self.actions.retain(|_| *kept.next().unwrap_or(&true));
+        self.current = remap[self.current];
+        for index in self.checkpoints.values_mut() {
+            *index = remap[*index];
+        }
+        self.unshare_from(first_dropped);
+
+        let removed = keep.iter().filter(|&&kept| !kept).count();
+        self.notify(TimelineEvent::HistoryRewritten { removed });
+    }
+
+    /// Removes a single past action from the history, re-applying the
+    /// actions recorded after it to the state before it.
+    ///
+    /// Later work is kept: only the effect of that one action disappears.
+    /// Later positions move back by one; the current position and
+    /// checkpoints at the removed entry move to the entry before it.
+    ///
+    /// # Returns
+    ///
+    /// `false`, without changing anything, if `index` is 0 or past the end
+    /// of the history, or if a later state was not produced by a
+    /// dispatched action (e.g. a merged, squashed or loaded state), so it
+    /// cannot be re-applied.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use zed::StateManager;
+    ///
+    /// let mut timeline = StateManager::new(0, |count: &i32, step: &i32| count + step);
+    /// for step in [1, 10, 100] {
+    ///     timeline.dispatch(step);
+    /// }
+    ///
+    /// // Remove the `10` but keep the `100` dispatched after it
+    /// assert!(timeline.undo_entry(2));
+    /// assert_eq!(timeline.iter_history().copied().collect::<Vec<_>>(), [0, 1, 101]);
+    /// ```
+    pub fn undo_entry(&mut self, index: usize) -> bool {
+        if index == 0 || index >= self.history.len() {
+            return false;
+        }
+        let Some(later) = self.actions[index + 1..]
+            .iter()
+            .cloned()
+            .collect::<Option<Vec<_>>>()
+        else {
+            return false;
+        };
+
+        self.history.truncate(index);
+        for action in &later {
+            let next = (self.reducer)(&self.history[self.history.len() - 1], action);
+            self.history.push(next);
+        }
+        self.timestamps.remove(index);
+        self.actions.remove(index);
+        if self.current >= index {
+            self.current -= 1;
+        }
+        for position in self.checkpoints.values_mut() {
+            if *position >= index {
+                *position -= 1;
+            }
+        }
+        self.unshare_from(index);
+
+        self.notify(TimelineEvent::HistoryRewritten { removed: 1 });
+        true
+    }
+
+    /// Reclaims memory from abandoned branches and old states, following a
+    /// retention policy.
+    ///
+    /// Stale branches are deleted first, like with `delete_branch()`; then
+    /// every remaining branch is trimmed to the policy's history length.
+    /// Trimming never drops the history a branch shares with its parent or
+    /// children, up to the newest fork point between them, so merges keep
+    /// their common ancestor; such branches may stay longer than the
+    /// policy's length. Subscribers receive a single `HistoryRewritten`
+    /// event if anything was dropped.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use zed::{RetentionPolicy, StateManager};
+    ///
+    /// let mut timeline = StateManager::new(0, |count: &i32, step: &i32| count + step);
+    /// timeline.create_branch("experiment");
+    /// for step in [1, 2, 3] {
+    ///     timeline.dispatch(step);
+    /// }
+    ///
+    /// let stats = timeline.gc(&RetentionPolicy::new().max_history(2).max_branch_age(Duration::ZERO));
+    /// assert_eq!(stats.branches_removed, 1);
+    /// assert_eq!(stats.states_removed, 3);
+    /// assert_eq!(timeline.list_branches(), ["main"]);
+    /// assert_eq!(timeline.history_len(), 2);
+    /// ```
+    pub fn gc(&mut self, policy: &RetentionPolicy) -> GcStats {
+        let mut stats = GcStats::default();
+        // Report the whole run as one event instead of one per step
+        let subscribers = std::mem::take(&mut self.subscribers);
+
+        if let Some(max_age) = policy.max_branch_age {
+            let now = SystemTime::now();
+            let stale: Vec<String> = self
+                .parked
+                .iter()
+                .filter(|(_, branch)| {
+                    branch
+                        .timestamps
+                        .last()
+                        .and_then(|&recorded| now.duration_since(recorded).ok())
+                        .is_some_and(|age| age > max_age)
+                })
+                .map(|(name, _)| name.clone())
+                .collect();
+            for name in stale {
+                stats.states_removed += self.parked[&name].history.len();
+                stats.branches_removed += 1;
+                self.delete_branch(&name);
+            }
+        }
+
+        if let Some(max_history) = policy.max_history {
+            let checked_out = self.branch.clone();
+            let names: Vec<String> = self.origins.keys().cloned().collect();
+            for name in names {
+                self.switch_branch(&name);
+                let len = self.history.len();
+                let excess = len.saturating_sub(max_history);
+                // Drop the oldest states after the shared history, keeping
+                // the newest one
+                let start = self.shared_prefix().map_or(0, |shared| shared + 1);
+                let excess = excess.min(len.saturating_sub(start + 1));
+                if excess > 0 && self.squash(start..=start + excess) {
+                    stats.states_removed += excess;
+                }
+            }
+            self.switch_branch(&checked_out);
+        }
+
+        self.subscribers = subscribers;
+        if stats.states_removed > 0 {
+            self.notify(TimelineEvent::HistoryRewritten {
+                removed: stats.states_removed,
+            });
+        }
+        stats
+    }
+
+    /// Limits the history the checked-out branch shares with its own
+    /// origin and its children to the entries before `first_changed`.
+    ///
+    /// If the initial state changed, the branch shares nothing any more and
+    /// is detached from its parent and children.
+    fn unshare_from(&mut self, first_changed: usize) {
+        let Some(shared) = first_changed.checked_sub(1) else {
+            for (name, origin) in &mut self.origins {
+                if *name == self.branch {
+                    origin.parent = None;
+                    origin.fork_point = 0;
+                    origin.rooted = false;
+                } else if origin.parent.as_ref() == Some(&self.branch) {
+                    origin.parent = None;
+                }
+            }
+            return;
+        };
+        for (name, origin) in &mut self.origins {
+            if *name == self.branch || origin.parent.as_ref() == Some(&self.branch) {
+                origin.fork_point = origin.fork_point.min(shared);
+            }
+        }
+    }
+
+    /// Returns the newest position up to which the checked-out branch
+    /// shares its history with its parent or one of its children, if it
+    /// has any.
+    fn shared_prefix(&self) -> Option<usize> {
+        self.origins
+            .iter()
+            .filter(|(name, origin)| {
+                (**name == self.branch && origin.parent.is_some())
+                    || origin.parent.as_ref() == Some(&self.branch)
+            })
+            .map(|(_, origin)| origin.fork_point)
+            .max()
     }
 
     /// Creates a new timeline branch from the current state.
+    ///
+    /// The new timeline is independent and starts without history; use
+    /// `create_branch()` for a branch that keeps the history leading up to
+    /// it and can be merged back.
     pub fn branch(&self) -> Self {
-        Self {
-            history: vec![self.current_state().clone()],
-            current: 0,
-            reducer: self.reducer,
+        Self::from_parts(
+            vec![self.current_state().clone()],
+            vec![self.timestamps[self.current]],
+            0,
+            Arc::clone(&self.reducer),
+            HashMap::new(),
+        )
+    }
+
+    /// Creates a named branch at the current position, without switching to it.
+    ///
+    /// The branch starts with the history up to and including the current
+    /// state, so it can be rewound past the point where it was created.
+    ///
+    /// # Returns
+    ///
+    /// `false` if a branch with that name already exists.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use zed::StateManager;
+    ///
+    /// let mut timeline = StateManager::new(0, |count: &i32, step: &i32| count + step);
+    /// timeline.dispatch(1);
+    ///
+    /// timeline.create_branch("experiment");
+    /// timeline.switch_branch("experiment");
+    /// timeline.dispatch(10);
+    ///
+    /// timeline.switch_branch("main");
+    /// timeline.dispatch(100);
+    ///
+    /// // Both sides built on 1; keep the changes of both
+    /// timeline.merge_branch("experiment", |base, ours, theirs| ours + theirs - base);
+    /// assert_eq!(*timeline.current_state(), 111);
+    /// ```
+    pub fn create_branch(&mut self, name: impl Into<String>) -> bool {
+        let name = name.into();
+        if self.origins.contains_key(&name) {
+            return false;
+        }
+        let origin = BranchOrigin {
+            parent: Some(self.branch.clone()),
+            fork_point: self.current,
+            rooted: self.origins[&self.branch].rooted,
+        };
+        let branch = ParkedBranch {
+            history: self.history[..=self.current].to_vec(),
+            timestamps: self.timestamps[..=self.current].to_vec(),
+            actions: self.actions[..=self.current].to_vec(),
+            current: self.current,
+            checkpoints: self
+                .checkpoints
+                .iter()
+                .filter(|&(_, &index)| index <= self.current)
+                .map(|(name, &index)| (name.clone(), index))
+                .collect(),
+        };
+        self.origins.insert(name.clone(), origin);
+        self.parked.insert(name, branch);
+        true
+    }
+
+    /// Checks out a named branch, keeping the history of the current one.
+    ///
+    /// # Returns
+    ///
+    /// `false` if no branch with that name exists.
+    pub fn switch_branch(&mut self, name: &str) -> bool {
+        let Some(target) = self.parked.remove(name) else {
+            return name == self.branch;
+        };
+        let previous = ParkedBranch {
+            history: std::mem::replace(&mut self.history, target.history),
+            timestamps: std::mem::replace(&mut self.timestamps, target.timestamps),
+            actions: std::mem::replace(&mut self.actions, target.actions),
+            current: std::mem::replace(&mut self.current, target.current),
+            checkpoints: std::mem::replace(&mut self.checkpoints, target.checkpoints),
+        };
+        let previous_name = std::mem::replace(&mut self.branch, name.to_string());
+        self.parked.insert(previous_name.clone(), previous);
+        self.notify(TimelineEvent::BranchSwitched {
+            from: previous_name,
+            to: name.to_string(),
+        });
+        true
+    }
+
+    /// Returns the names of all branches in alphabetical order.
+    pub fn list_branches(&self) -> Vec<&str> {
+        self.origins.keys().map(String::as_str).collect()
+    }
+
+    /// Returns the name of the checked-out branch.
+    pub fn current_branch(&self) -> &str {
+        &self.branch
+    }
+
+    /// Deletes a branch that is not checked out.
+    ///
+    /// Branches created from it are kept and keep their history.
+    ///
+    /// # Returns
+    ///
+    /// `false` if no such branch exists or it is checked out.
+    pub fn delete_branch(&mut self, name: &str) -> bool {
+        if name == self.branch {
+            return false;
+        }
+        let Some(removed) = self.origins.remove(name) else {
+            return false;
+        };
+        self.parked.remove(name);
+        // Children now descend from the deleted branch's parent
+        for origin in self.origins.values_mut() {
+            if origin.parent.as_deref() == Some(name) {
+                origin.parent = removed.parent.clone();
+                origin.fork_point = origin.fork_point.min(removed.fork_point);
+            }
+        }
+        true
+    }
+
+    /// Merges the current state of another branch into the checked-out one.
+    ///
+    /// `merge` receives the state both branches last had in common, the
+    /// current state of the checked-out branch and that of the merged
+    /// branch, in this order. Its result is appended to the checked-out
+    /// branch like a dispatched state. The merged branch is left unchanged.
+    ///
+    /// # Returns
+    ///
+    /// `false`, without calling `merge`, if no other branch with that name
+    /// exists or the branches no longer share a state, e.g. after `gc()`
+    /// trimmed the initial state of one that had been detached.
+    pub fn merge_branch<F>(&mut self, name: &str, merge: F) -> bool
+    where
+        F: FnOnce(&T, &T, &T) -> T,
+    {
+        let Some(theirs) = self.parked.get(name) else {
+            return false;
+        };
+        let Some(base) = self.common_ancestor(name) else {
+            return false;
+        };
+        let merged = merge(
+            &self.history[base],
+            self.current_state(),
+            &theirs.history[theirs.current],
+        );
+        self.push(merged, None);
+        true
+    }
+
+    /// Returns the position of the newest state that the checked-out
+    /// branch shares with another branch, if they share any.
+    fn common_ancestor(&self, other: &str) -> Option<usize> {
+        let ours = self.lineage(&self.branch, self.current);
+        let theirs = self.lineage(other, self.parked[other].current);
+        let common = ours.iter().find_map(|(branch, limit)| {
+            theirs
+                .iter()
+                .find(|(candidate, _)| candidate == branch)
+                .map(|(_, other_limit)| (*limit).min(*other_limit))
+        });
+        // Unrelated branches share the initial state unless it was dropped
+        common.or_else(|| {
+            (self.origins[&self.branch].rooted && self.origins[other].rooted).then_some(0)
+        })
+    }
+
+    /// Lists a branch and its ancestors, each with the newest position up
+    /// to which the branch shares its history.
+    fn lineage<'a>(&'a self, mut name: &'a str, current: usize) -> Vec<(&'a str, usize)> {
+        let mut limit = current;
+        let mut lineage = vec![(name, limit)];
+        while let Some(origin) = self.origins.get(name)
+            && let Some(parent) = &origin.parent
+        {
+            limit = limit.min(origin.fork_point);
+            lineage.push((parent, limit));
+            name = parent;
         }
+        lineage
     }
 
     /// Returns a reference to the current state.
@@ -90,4 +1204,814 @@ impl<T: Clone> StateManager<T> {
     pub fn current_position(&self) -> usize {
         self.current
     }
+
+    /// Returns when the state at the given position was recorded.
+    pub fn timestamp_at(&self, index: usize) -> Option<SystemTime> {
+        self.timestamps.get(index).copied()
+    }
+
+    /// Rewinds to the newest state recorded at or before `time`.
+    ///
+    /// Only earlier states are considered, so this never moves forward; if
+    /// every earlier state is newer than `time`, the timeline rewinds to
+    /// the initial state.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::time::{Duration, SystemTime};
+    /// use zed::StateManager;
+    ///
+    /// let mut timeline = StateManager::new(0, |count: &i32, step: &i32| count + step);
+    /// timeline.dispatch(1);
+    ///
+    /// // Undo the last five minutes
+    /// timeline.rewind_to(SystemTime::now() - Duration::from_secs(5 * 60));
+    /// assert_eq!(*timeline.current_state(), 0);
+    /// ```
+    pub fn rewind_to(&mut self, time: SystemTime) {
+        let index = self.timestamps[..=self.current]
+            .iter()
+            .rposition(|&recorded| recorded <= time)
+            .unwrap_or(0);
+        self.move_to(index);
+    }
+
+    /// Plays the whole history back, oldest state first, waiting between
+    /// states as long as passed between their recording.
+    ///
+    /// The waits are divided by `speed`, so `2.0` plays back twice as fast;
+    /// an infinite, zero or negative speed plays back without waiting. The
+    /// callback receives each state's position and the state. The current
+    /// position does not change.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use zed::StateManager;
+    ///
+    /// let mut timeline = StateManager::new(0, |count: &i32, step: &i32| count + step);
+    /// timeline.dispatch(1);
+    /// timeline.dispatch(2);
+    ///
+    /// let mut frames = Vec::new();
+    /// timeline.replay(10.0, |position, state| frames.push((position, *state)));
+    /// assert_eq!(frames, [(0, 0), (1, 1), (2, 3)]);
+    /// ```
+    pub fn replay<F>(&self, speed: f64, callback: F)
+    where
+        F: FnMut(usize, &T),
+    {
+        replay_states(&self.history, &self.timestamps, speed, callback);
+    }
+
+    /// Returns the states recorded between two points in time, inclusive,
+    /// oldest first.
+    ///
+    /// Each entry holds the state's position, its timestamp and the state.
+    pub fn entries_between(
+        &self,
+        start: SystemTime,
+        end: SystemTime,
+    ) -> impl Iterator<Item = (usize, SystemTime, &T)> + '_ {
+        self.timestamps
+            .iter()
+            .zip(&self.history)
+            .enumerate()
+            .filter(move |(_, (recorded, _))| (start..=end).contains(*recorded))
+            .map(|(index, (recorded, state))| (index, *recorded, state))
+    }
+
+    /// Returns the state at the given position in the history.
+    pub fn state_at(&self, index: usize) -> Option<&T> {
+        self.history.get(index)
+    }
+
+    /// Iterates over the whole history, oldest state first.
+    ///
+    /// States after the current position, which can still be redone, are
+    /// included.
+    pub fn iter_history(&self) -> std::slice::Iter<'_, T> {
+        self.history.iter()
+    }
+
+    /// Returns the states in a range of positions.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the range extends past the end of the history.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use zed::StateManager;
+    ///
+    /// let mut timeline = StateManager::new(0, |count: &i32, step: &i32| count + step);
+    /// for step in [1, 2, 3] {
+    ///     timeline.dispatch(step);
+    /// }
+    ///
+    /// assert_eq!(timeline.history_range(1..3), Some(&[1, 3][..]));
+    /// assert_eq!(timeline.history_range(2..), Some(&[3, 6][..]));
+    /// assert_eq!(timeline.history_range(..10), None);
+    /// ```
+    pub fn history_range<R>(&self, range: R) -> Option<&[T]>
+    where
+        R: RangeBounds<usize>,
+    {
+        self.history
+            .get((range.start_bound().cloned(), range.end_bound().cloned()))
+    }
+
+    /// Finds the oldest state matching a predicate.
+    ///
+    /// # Returns
+    ///
+    /// The state's position and the state itself.
+    pub fn find<P>(&self, mut predicate: P) -> Option<(usize, &T)>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.history
+            .iter()
+            .enumerate()
+            .find(|(_, state)| predicate(state))
+    }
+
+    /// Finds the newest state matching a predicate.
+    ///
+    /// # Returns
+    ///
+    /// The state's position and the state itself.
+    pub fn find_last<P>(&self, mut predicate: P) -> Option<(usize, &T)>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.history
+            .iter()
+            .enumerate()
+            .rfind(|(_, state)| predicate(state))
+    }
+
+    /// Lists the values that changed between two positions in the history,
+    /// by comparing the states' JSON serializations.
+    ///
+    /// # Returns
+    ///
+    /// `None` if a position is past the end of the history or a state
+    /// fails to serialize.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serde::Serialize;
+    /// use zed::StateManager;
+    ///
+    /// #[derive(Clone, Serialize)]
+    /// struct Form { name: String, age: u32 }
+    ///
+    /// let mut timeline = StateManager::new(
+    ///     Form { name: "Ada".into(), age: 36 },
+    ///     |form: &Form, age: &u32| Form { age: *age, ..form.clone() },
+    /// );
+    /// timeline.dispatch(37);
+    ///
+    /// let diff = timeline.diff(0, 1).unwrap();
+    /// assert_eq!(diff.changes().len(), 1);
+    /// assert_eq!(diff.changes()[0].path, "/age");
+    /// ```
+    pub fn diff(&self, a: usize, b: usize) -> Option<StateDiff>
+    where
+        T: Serialize,
+    {
+        let old = serde_json::to_value(self.history.get(a)?).ok()?;
+        let new = serde_json::to_value(self.history.get(b)?).ok()?;
+        Some(StateDiff::between(&old, &new))
+    }
+
+    /// Computes the change between two positions in the history with the
+    /// state's [`Diff`] implementation.
+    ///
+    /// # Returns
+    ///
+    /// `None` if a position is past the end of the history.
+    pub fn delta(&self, a: usize, b: usize) -> Option<T::Delta>
+    where
+        T: Diff,
+    {
+        Some(self.history.get(a)?.diff(self.history.get(b)?))
+    }
+
+    /// Converts the timeline into a [`DeltaStateManager`] that stores the
+    /// history as periodic snapshots plus the state's [`Diff`] deltas.
+    ///
+    /// The checked-out branch's states and the current position are kept;
+    /// checkpoints, other branches, timestamps and subscribers are dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `snapshot_interval` - Number of entries from one full snapshot to the next
+    pub fn into_delta_storage(
+        self,
+        snapshot_interval: usize,
+    ) -> DeltaStateManager<T, Action, T::Delta>
+    where
+        T: Diff,
+    {
+        DeltaStateManager::diff_from_history(
+            self.history,
+            self.current,
+            self.reducer,
+            snapshot_interval,
+        )
+    }
+
+    /// Converts the timeline into a [`DeltaStateManager`] that stores the
+    /// history as periodic snapshots plus deltas of the states' JSON
+    /// serializations.
+    ///
+    /// Like `into_delta_storage()`, only the checked-out branch's states and
+    /// the current position are kept.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use zed::StateManager;
+    ///
+    /// let mut timeline = StateManager::new(vec![0; 1000], |items: &Vec<i32>, index: &usize| {
+    ///     let mut items = items.clone();
+    ///     items[*index] += 1;
+    ///     items
+    /// });
+    /// for index in 0..10 {
+    ///     timeline.dispatch(index);
+    /// }
+    /// timeline.rewind(2);
+    ///
+    /// let compact = timeline.into_json_delta_storage(100);
+    /// assert_eq!(compact.snapshot_count(), 1);
+    /// assert_eq!(compact.current_position(), 8);
+    /// assert_eq!(compact.current_state()[7], 1);
+    /// assert_eq!(compact.current_state()[8], 0);
+    /// ```
+    pub fn into_json_delta_storage(
+        self,
+        snapshot_interval: usize,
+    ) -> DeltaStateManager<T, Action, JsonDelta>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        DeltaStateManager::json_from_history(
+            self.history,
+            self.current,
+            self.reducer,
+            snapshot_interval,
+        )
+    }
+
+    /// Describes the checked-out branch's history as a JSON document, for
+    /// tools that draw the timeline.
+    ///
+    /// The document holds the checked-out branch, the current position,
+    /// its checkpoints, every branch with the branch and position it was
+    /// created from, and one entry per state:
+    ///
+    /// ```text
+    /// {"index": 1, "timestamp_ms": 1700000000000, "action": "Rename(\"Ada\")",
+    ///  "changes": [{"path": "/name", "old": "", "new": "Ada"}]}
+    /// ```
+    ///
+    /// `action` is the `Debug` representation of the dispatched action, or
+    /// `null` for states that were not dispatched (the initial state and
+    /// merged, squashed or loaded ones). The initial entry holds the whole
+    /// `state` instead of `changes`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a state fails to serialize.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use zed::StateManager;
+    ///
+    /// let mut timeline = StateManager::new(0, |count: &i32, step: &i32| count + step);
+    /// timeline.dispatch(5);
+    ///
+    /// let document = timeline.export_json().unwrap();
+    /// assert_eq!(document["entries"][0]["state"], 0);
+    /// assert_eq!(document["entries"][1]["action"], "5");
+    /// assert_eq!(document["entries"][1]["changes"][0]["new"], 5);
+    /// ```
+    pub fn export_json(&self) -> Result<Value, serde_json::Error>
+    where
+        T: Serialize,
+        Action: fmt::Debug,
+    {
+        let states = self
+            .history
+            .iter()
+            .map(serde_json::to_value)
+            .collect::<Result<Vec<_>, _>>()?;
+        let entries = states
+            .iter()
+            .enumerate()
+            .map(|(index, state)| {
+                let (state, changes) = match index.checked_sub(1) {
+                    Some(previous) => (
+                        None,
+                        Some(
+                            StateDiff::between(&states[previous], state)
+                                .changes()
+                                .to_vec(),
+                        ),
+                    ),
+                    None => (Some(state.clone()), None),
+                };
+                ExportedEntry {
+                    index,
+                    timestamp_ms: self.timestamps[index]
+                        .duration_since(UNIX_EPOCH)
+                        .map_or(0, |elapsed| elapsed.as_millis() as u64),
+                    action: self.actions[index]
+                        .as_ref()
+                        .map(|action| format!("{action:?}")),
+                    state,
+                    changes,
+                }
+            })
+            .collect();
+
+        serde_json::to_value(ExportedTimeline {
+            branch: &self.branch,
+            current: self.current,
+            checkpoints: self
+                .checkpoints
+                .iter()
+                .map(|(name, &index)| (name.as_str(), index))
+                .collect(),
+            branches: self
+                .origins
+                .iter()
+                .map(|(name, origin)| ExportedBranch {
+                    name,
+                    parent: origin.parent.as_deref(),
+                    fork_point: origin.fork_point,
+                })
+                .collect(),
+            entries,
+        })
+    }
+
+    /// Saves the whole history, position and checkpoints of the checked-out
+    /// branch to a file. Other branches are not saved, and neither are the
+    /// dispatched actions, so `undo_entry()` cannot re-apply the loaded
+    /// states.
+    ///
+    /// The file starts with a header line holding a format version and a
+    /// checksum of the JSON body, which `load()` verifies. It is written
+    /// through a temporary file, so a crash mid-save keeps the old file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), PersistError>
+    where
+        T: Serialize,
+    {
+        let body = serde_json::to_string(&SavedTimeline {
+            history: &self.history,
+            timestamps: &self.timestamps,
+            current: self.current,
+            checkpoints: &self.checkpoints,
+        })?;
+        write_checked(path.as_ref(), FILE_MAGIC, FILE_VERSION, &body)
+    }
+
+    /// Loads a history saved by `save()`.
+    ///
+    /// The reducer is not saved, so it has to be passed again.
+    ///
+    /// # Errors
+    ///
+    /// * `PersistError::Corrupted` - The file is not a saved timeline, or its
+    ///   checksum does not match
+    /// * `PersistError::Migration` - The file was written in another format
+    ///   version
+    pub fn load<F>(path: impl AsRef<Path>, reducer: F) -> Result<Self, PersistError>
+    where
+        T: DeserializeOwned,
+        F: Fn(&T, &Action) -> T + Send + Sync + 'static,
+    {
+        let body = read_checked(path.as_ref(), FILE_MAGIC, FILE_VERSION)?;
+        let corrupted = |reason: &str| PersistError::Corrupted(reason.to_string());
+
+        let saved: SavedTimeline<Vec<T>, Vec<SystemTime>, HashMap<String, usize>> =
+            serde_json::from_str(&body)?;
+        let len = saved.history.len();
+        if saved.timestamps.len() != len {
+            return Err(corrupted("history and timestamps differ in length"));
+        }
+        if saved.current >= len || saved.checkpoints.values().any(|&index| index >= len) {
+            return Err(corrupted("position out of range"));
+        }
+        Ok(Self::from_parts(
+            saved.history,
+            saved.timestamps,
+            saved.current,
+            Arc::new(reducer),
+            saved.checkpoints,
+        ))
+    }
+}
+
+/// Calls `callback` with each state and its position, oldest first,
+/// waiting between states for the time between their timestamps divided by
+/// `speed`.
+fn replay_states<T, F>(history: &[T], timestamps: &[SystemTime], speed: f64, mut callback: F)
+where
+    F: FnMut(usize, &T),
+{
+    let scaled = speed.is_finite() && speed > 0.0;
+    for (index, state) in history.iter().enumerate() {
+        if scaled && index > 0 {
+            // Timestamps of a loaded history may go back in time
+            let elapsed = timestamps[index]
+                .duration_since(timestamps[index - 1])
+                .unwrap_or_default();
+            thread::sleep(elapsed.div_f64(speed));
+        }
+        callback(index, state);
+    }
+}
+
+/// A [`StateManager`] that can be shared between threads.
+///
+/// Cloning yields another handle to the same timeline, so e.g. an
+/// autosave thread and the UI thread can both use it. Every method locks
+/// the timeline for its duration; methods of `StateManager` that return
+/// references or iterators return clones here, and `with()` and
+/// `with_mut()` give direct access to the locked timeline. `replay()`
+/// replays a copy of the history, so the timeline stays unlocked while it
+/// waits between states.
+///
+/// Subscribers run while the timeline is locked, so they must use the
+/// `&StateManager` they receive rather than calling back into the shared
+/// handle.
+///
+/// # Example
+///
+/// ```rust
+/// use std::thread;
+/// use zed::SharedStateManager;
+///
+/// let timeline = SharedStateManager::new(0, |count: &i32, step: &i32| count + step);
+///
+/// let editor = timeline.clone();
+/// thread::spawn(move || editor.dispatch(5)).join().unwrap();
+///
+/// assert_eq!(timeline.current_state(), 5);
+/// assert!(timeline.can_undo());
+/// ```
+pub struct SharedStateManager<T: Clone, Action> {
+    inner: Arc<Mutex<StateManager<T, Action>>>,
+}
+
+impl<T: Clone, Action> Clone for SharedStateManager<T, Action> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<T: Clone, Action> From<StateManager<T, Action>> for SharedStateManager<T, Action> {
+    fn from(manager: StateManager<T, Action>) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(manager)),
+        }
+    }
+}
+
+impl<T: Clone, Action> SharedStateManager<T, Action> {
+    /// Creates a shared timeline; see `StateManager::new()`.
+    pub fn new<F>(initial_state: T, reducer: F) -> Self
+    where
+        F: Fn(&T, &Action) -> T + Send + Sync + 'static,
+    {
+        StateManager::new(initial_state, reducer).into()
+    }
+
+    /// Creates a shared timeline driven by a [`Reducer`]; see
+    /// `StateManager::from_reducer()`.
+    pub fn from_reducer<R>(initial_state: T, reducer: R) -> Self
+    where
+        R: Reducer<T, Action> + Send + Sync + 'static,
+    {
+        StateManager::from_reducer(initial_state, reducer).into()
+    }
+
+    /// Loads a timeline saved by `save()`; see `StateManager::load()`.
+    pub fn load<F>(path: impl AsRef<Path>, reducer: F) -> Result<Self, PersistError>
+    where
+        T: DeserializeOwned,
+        F: Fn(&T, &Action) -> T + Send + Sync + 'static,
+    {
+        StateManager::load(path, reducer).map(Self::from)
+    }
+
+    /// Runs a closure with read access to the locked timeline.
+    pub fn with<R>(&self, f: impl FnOnce(&StateManager<T, Action>) -> R) -> R {
+        f(&self.lock())
+    }
+
+    /// Runs a closure with write access to the locked timeline.
+    pub fn with_mut<R>(&self, f: impl FnOnce(&mut StateManager<T, Action>) -> R) -> R {
+        f(&mut self.lock())
+    }
+
+    /// Dispatches an action to create a new state.
+    pub fn dispatch(&self, action: Action) {
+        self.lock().dispatch(action);
+    }
+
+    /// Subscribes to changes of the timeline; see `StateManager::subscribe()`.
+    pub fn subscribe<F>(&self, f: F) -> SubscriptionId
+    where
+        F: Fn(&TimelineEvent, &StateManager<T, Action>) + Send + Sync + 'static,
+    {
+        self.lock().subscribe(f)
+    }
+
+    /// Removes a subscriber.
+    pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        self.lock().unsubscribe(id)
+    }
+
+    /// Rewinds the timeline by the specified number of steps.
+    pub fn rewind(&self, steps: usize) {
+        self.lock().rewind(steps);
+    }
+
+    /// Moves the timeline forward again by the specified number of steps.
+    pub fn redo(&self, steps: usize) {
+        self.lock().redo(steps);
+    }
+
+    /// Returns `true` if there is an earlier state to rewind to.
+    pub fn can_undo(&self) -> bool {
+        self.lock().can_undo()
+    }
+
+    /// Returns `true` if there is a rewound state to redo.
+    pub fn can_redo(&self) -> bool {
+        self.lock().can_redo()
+    }
+
+    /// Moves to the state at the given position in the history.
+    pub fn jump_to(&self, index: usize) -> bool {
+        self.lock().jump_to(index)
+    }
+
+    /// Moves by `offset` states relative to the current position.
+    pub fn jump_by(&self, offset: isize) -> bool {
+        self.lock().jump_by(offset)
+    }
+
+    /// Moves back one state.
+    pub fn step_back(&self) -> bool {
+        self.lock().step_back()
+    }
+
+    /// Moves forward one state.
+    pub fn step_forward(&self) -> bool {
+        self.lock().step_forward()
+    }
+
+    /// Rewinds to the newest state recorded at or before `time`.
+    pub fn rewind_to(&self, time: SystemTime) {
+        self.lock().rewind_to(time);
+    }
+
+    /// Tags the current position with a name.
+    pub fn checkpoint(&self, name: impl Into<String>) {
+        self.lock().checkpoint(name);
+    }
+
+    /// Moves to the state tagged by a checkpoint.
+    pub fn restore_checkpoint(&self, name: &str) -> bool {
+        self.lock().restore_checkpoint(name)
+    }
+
+    /// Returns the history position a checkpoint points to.
+    pub fn checkpoint_position(&self, name: &str) -> Option<usize> {
+        self.lock().checkpoint_position(name)
+    }
+
+    /// Removes a checkpoint.
+    pub fn remove_checkpoint(&self, name: &str) -> bool {
+        self.lock().remove_checkpoint(name)
+    }
+
+    /// Creates checkpoints automatically on dispatch; see
+    /// `StateManager::set_checkpoint_policy()`.
+    pub fn set_checkpoint_policy(&self, policy: CheckpointPolicy) {
+        self.lock().set_checkpoint_policy(policy);
+    }
+
+    /// Stops creating checkpoints automatically.
+    pub fn clear_checkpoint_policy(&self) {
+        self.lock().clear_checkpoint_policy();
+    }
+
+    /// Returns the name of the newest automatic checkpoint on the
+    /// checked-out branch.
+    pub fn latest_auto_checkpoint(&self) -> Option<String> {
+        self.lock().latest_auto_checkpoint().map(String::from)
+    }
+
+    /// Removes a single past action; see `StateManager::undo_entry()`.
+    pub fn undo_entry(&self, index: usize) -> bool {
+        self.lock().undo_entry(index)
+    }
+
+    /// Collapses a range of history entries into one.
+    pub fn squash<R>(&self, range: R) -> bool
+    where
+        R: RangeBounds<usize>,
+    {
+        self.lock().squash(range)
+    }
+
+    /// Drops intermediate states; see `StateManager::compact()`.
+    pub fn compact(&self) -> usize {
+        self.lock().compact()
+    }
+
+    /// Reclaims memory following a retention policy; see `StateManager::gc()`.
+    pub fn gc(&self, policy: &RetentionPolicy) -> GcStats {
+        self.lock().gc(policy)
+    }
+
+    /// Creates a named branch at the current position.
+    pub fn create_branch(&self, name: impl Into<String>) -> bool {
+        self.lock().create_branch(name)
+    }
+
+    /// Checks out a named branch.
+    pub fn switch_branch(&self, name: &str) -> bool {
+        self.lock().switch_branch(name)
+    }
+
+    /// Returns the names of all branches in alphabetical order.
+    pub fn list_branches(&self) -> Vec<String> {
+        self.lock()
+            .list_branches()
+            .into_iter()
+            .map(String::from)
+            .collect()
+    }
+
+    /// Returns the name of the checked-out branch.
+    pub fn current_branch(&self) -> String {
+        self.lock().current_branch().to_string()
+    }
+
+    /// Deletes a branch that is not checked out.
+    pub fn delete_branch(&self, name: &str) -> bool {
+        self.lock().delete_branch(name)
+    }
+
+    /// Merges another branch into the checked-out one; see
+    /// `StateManager::merge_branch()`.
+    pub fn merge_branch<F>(&self, name: &str, merge: F) -> bool
+    where
+        F: FnOnce(&T, &T, &T) -> T,
+    {
+        self.lock().merge_branch(name, merge)
+    }
+
+    /// Returns a clone of the current state.
+    pub fn current_state(&self) -> T {
+        self.lock().current_state().clone()
+    }
+
+    /// Returns a clone of the state at the given position in the history.
+    pub fn state_at(&self, index: usize) -> Option<T> {
+        self.lock().state_at(index).cloned()
+    }
+
+    /// Returns when the state at the given position was recorded.
+    pub fn timestamp_at(&self, index: usize) -> Option<SystemTime> {
+        self.lock().timestamp_at(index)
+    }
+
+    /// Returns the length of the timeline history.
+    pub fn history_len(&self) -> usize {
+        self.lock().history_len()
+    }
+
+    /// Returns the current position in the timeline.
+    pub fn current_position(&self) -> usize {
+        self.lock().current_position()
+    }
+
+    /// Returns clones of the states recorded between two points in time;
+    /// see `StateManager::entries_between()`.
+    pub fn entries_between(
+        &self,
+        start: SystemTime,
+        end: SystemTime,
+    ) -> Vec<(usize, SystemTime, T)> {
+        self.lock()
+            .entries_between(start, end)
+            .map(|(index, recorded, state)| (index, recorded, state.clone()))
+            .collect()
+    }
+
+    /// Iterates over a copy of the whole history, oldest state first.
+    pub fn iter_history(&self) -> std::vec::IntoIter<T> {
+        self.lock().history.clone().into_iter()
+    }
+
+    /// Returns clones of the states in a range of positions; see
+    /// `StateManager::history_range()`.
+    pub fn history_range<R>(&self, range: R) -> Option<Vec<T>>
+    where
+        R: RangeBounds<usize>,
+    {
+        self.lock().history_range(range).map(<[T]>::to_vec)
+    }
+
+    /// Finds the oldest state matching a predicate, returning its position
+    /// and a clone of it.
+    pub fn find<P>(&self, predicate: P) -> Option<(usize, T)>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.lock()
+            .find(predicate)
+            .map(|(index, state)| (index, state.clone()))
+    }
+
+    /// Finds the newest state matching a predicate, returning its position
+    /// and a clone of it.
+    pub fn find_last<P>(&self, predicate: P) -> Option<(usize, T)>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.lock()
+            .find_last(predicate)
+            .map(|(index, state)| (index, state.clone()))
+    }
+
+    /// Replays a copy of the history; see `StateManager::replay()`.
+    ///
+    /// The timeline is only locked to copy the history, so it may change
+    /// while the replay runs.
+    pub fn replay<F>(&self, speed: f64, callback: F)
+    where
+        F: FnMut(usize, &T),
+    {
+        let (history, timestamps) = {
+            let timeline = self.lock();
+            (timeline.history.clone(), timeline.timestamps.clone())
+        };
+        replay_states(&history, &timestamps, speed, callback);
+    }
+
+    /// Lists the values that changed between two positions in the history.
+    pub fn diff(&self, a: usize, b: usize) -> Option<StateDiff>
+    where
+        T: Serialize,
+    {
+        self.lock().diff(a, b)
+    }
+
+    /// Computes the change between two positions in the history; see
+    /// `StateManager::delta()`.
+    pub fn delta(&self, a: usize, b: usize) -> Option<T::Delta>
+    where
+        T: Diff,
+    {
+        self.lock().delta(a, b)
+    }
+
+    /// Describes the history as a JSON document; see
+    /// `StateManager::export_json()`.
+    pub fn export_json(&self) -> Result<Value, serde_json::Error>
+    where
+        T: Serialize,
+        Action: fmt::Debug,
+    {
+        self.lock().export_json()
+    }
+
+    /// Saves the checked-out branch to a file; see `StateManager::save()`.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), PersistError>
+    where
+        T: Serialize,
+    {
+        self.lock().save(path)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, StateManager<T, Action>> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
 }
@@ -0,0 +1,207 @@
+//! # Undoable Store Module
+//!
+//! A store with undo and redo, for apps that want a history without
+//! re-plumbing their code onto the timeline's API.
+//!
+//! [`UndoableStore`] offers the familiar `dispatch`/`subscribe` surface of a
+//! [`Store`] with [time travel](crate::time_travel) enabled underneath, so
+//! every state change is recorded in a timeline under the store's reducer
+//! lock, in the order the reducer applied it. `undo()` and `redo()` move through that
+//! history and put the selected state back into the store, notifying
+//! subscribers as usual. Dispatching after an undo discards the states that
+//! could have been redone.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use zed::{UndoableStore, create_reducer};
+//!
+//! #[derive(Clone)]
+//! struct Document { text: String }
+//!
+//! enum Edit { Append(&'static str) }
+//!
+//! let editor = UndoableStore::new(
+//!     Document { text: String::new() },
+//!     create_reducer(|doc: &Document, edit: &Edit| match edit {
+//!         Edit::Append(text) => Document { text: doc.text.clone() + text },
+//!     }),
+//! );
+//!
+//! editor.dispatch(Edit::Append("Hello"));
+//! editor.dispatch(Edit::Append(", world"));
+//!
+//! editor.undo();
+//! assert_eq!(editor.get_state().text, "Hello");
+//! editor.redo();
+//! assert_eq!(editor.get_state().text, "Hello, world");
+//! ```
+
+use crate::reducer::{Reducer, ReducerError};
+use crate::store::{Store, SubscriptionId};
+use crate::time_travel::TimeTravelConfig;
+
+/// Number of states an [`UndoableStore`] keeps unless configured otherwise.
+pub const DEFAULT_MAX_HISTORY: usize = 100;
+
+/// A store that records its state changes for undo and redo.
+///
+/// Every committed state is recorded, including those produced by
+/// dispatching through [`store()`](UndoableStore::store).
+pub struct UndoableStore<State, Action>
+where
+    State: Clone + Send + 'static,
+    Action: Send + 'static,
+{
+    store: Store<State, Action>,
+}
+
+impl<State, Action> Clone for UndoableStore<State, Action>
+where
+    State: Clone + Send + 'static,
+    Action: Send + 'static,
+{
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+        }
+    }
+}
+
+impl<State, Action> UndoableStore<State, Action>
+where
+    State: Clone + Send + 'static,
+    Action: Send + 'static,
+{
+    /// Creates an undoable store with the given initial state and reducer.
+    ///
+    /// The history keeps the latest [`DEFAULT_MAX_HISTORY`] states; use
+    /// `with_config()` to choose another limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `initial_state` - The initial state, which is the oldest undo step
+    /// * `reducer` - The reducer handling state transitions
+    pub fn new<R>(initial_state: State, reducer: R) -> Self
+    where
+        R: Reducer<State, Action> + Send + Sync + 'static,
+    {
+        Self::with_config(
+            initial_state,
+            reducer,
+            TimeTravelConfig::new().max_history(DEFAULT_MAX_HISTORY),
+        )
+    }
+
+    /// Creates an undoable store with a custom history configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `initial_state` - The initial state, which is the oldest undo step
+    /// * `reducer` - The reducer handling state transitions
+    /// * `config` - How many states the history keeps
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use zed::{TimeTravelConfig, UndoableStore, create_reducer};
+    ///
+    /// let counter = UndoableStore::with_config(
+    ///     0,
+    ///     create_reducer(|count: &i32, _: &()| count + 1),
+    ///     TimeTravelConfig::new().max_history(3),
+    /// );
+    ///
+    /// for _ in 0..5 {
+    ///     counter.dispatch(());
+    /// }
+    ///
+    /// // Only the last three states are kept
+    /// assert!(counter.undo());
+    /// assert!(counter.undo());
+    /// assert!(!counter.undo());
+    /// assert_eq!(counter.get_state(), 3);
+    /// ```
+    pub fn with_config<R>(initial_state: State, reducer: R, config: TimeTravelConfig) -> Self
+    where
+        R: Reducer<State, Action> + Send + Sync + 'static,
+    {
+        let store = Store::new(initial_state, Box::new(reducer));
+        store.enable_time_travel(config);
+        Self { store }
+    }
+
+    /// Dispatches an action and records the resulting state.
+    ///
+    /// Rejected actions and, with change detection, actions leaving the
+    /// state unchanged do not create an undo step.
+    pub fn dispatch(&self, action: Action) {
+        let _ = self.dispatch_try(action);
+    }
+
+    /// Dispatches an action, returning the error if the reducer rejects it.
+    pub fn dispatch_try(&self, action: Action) -> Result<(), ReducerError> {
+        self.store.dispatch_try(action)
+    }
+
+    /// Restores the state before the last recorded change.
+    ///
+    /// # Returns
+    ///
+    /// `false` if there is nothing to undo.
+    pub fn undo(&self) -> bool {
+        self.store.undo()
+    }
+
+    /// Reapplies the last undone change.
+    ///
+    /// # Returns
+    ///
+    /// `false` if there is nothing to redo.
+    pub fn redo(&self) -> bool {
+        self.store.redo()
+    }
+
+    /// Returns `true` if `undo()` would change the state.
+    pub fn can_undo(&self) -> bool {
+        self.store.can_undo()
+    }
+
+    /// Returns `true` if `redo()` would change the state.
+    pub fn can_redo(&self) -> bool {
+        self.store.can_redo()
+    }
+
+    /// Subscribes to state changes, including those caused by undo and redo.
+    ///
+    /// See `Store::subscribe()`.
+    pub fn subscribe<F>(&self, f: F) -> SubscriptionId
+    where
+        F: Fn(&State) + Send + Sync + 'static,
+    {
+        self.store.subscribe(f)
+    }
+
+    /// Removes a subscription; see `Store::unsubscribe()`.
+    pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        self.store.unsubscribe(id)
+    }
+
+    /// Returns a clone of the current state.
+    pub fn get_state(&self) -> State {
+        self.store.get_state()
+    }
+
+    /// Reads the current state through a closure without cloning it.
+    pub fn with_state<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&State) -> R,
+    {
+        self.store.with_state(f)
+    }
+
+    /// Returns the underlying store, e.g. to create selectors or projections.
+    pub fn store(&self) -> &Store<State, Action> {
+        &self.store
+    }
+}
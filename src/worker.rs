@@ -0,0 +1,107 @@
+//! # Worker Module
+//!
+//! A single background thread that runs queued jobs in FIFO order, used by
+//! the store to fan out subscriber notifications off the dispatching thread.
+
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::thread;
+
+type Job = Box<dyn FnOnce() + Send>;
+type PanicPayload = Box<dyn Any + Send>;
+
+/// Tracks how many queued jobs have not finished yet.
+#[derive(Default)]
+struct Pending {
+    count: Mutex<usize>,
+    done: Condvar,
+}
+
+impl Pending {
+    fn add(&self) {
+        *self.count.lock().unwrap_or_else(PoisonError::into_inner) += 1;
+    }
+
+    fn finish(&self) {
+        let mut count = self.count.lock().unwrap_or_else(PoisonError::into_inner);
+        *count -= 1;
+        if *count == 0 {
+            self.done.notify_all();
+        }
+    }
+
+    fn wait(&self) {
+        let count = self.count.lock().unwrap_or_else(PoisonError::into_inner);
+        let _count = self
+            .done
+            .wait_while(count, |count| *count > 0)
+            .unwrap_or_else(PoisonError::into_inner);
+    }
+}
+
+/// A background thread executing jobs one at a time, in submission order.
+///
+/// The thread exits once the worker is dropped and the queue is drained.
+/// A panicking job does not stop the thread; the first panic is kept until
+/// `wait_idle()` hands it over.
+pub(crate) struct Worker {
+    sender: Mutex<Sender<Job>>,
+    pending: Arc<Pending>,
+    panic: Arc<Mutex<Option<PanicPayload>>>,
+}
+
+impl Worker {
+    /// Spawns the background thread.
+    pub(crate) fn spawn(name: &str) -> Self {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let pending = Arc::new(Pending::default());
+        let worker_pending = Arc::clone(&pending);
+        let panic = Arc::new(Mutex::new(None));
+        let worker_panic = Arc::clone(&panic);
+
+        thread::Builder::new()
+            .name(name.to_string())
+            .spawn(move || {
+                for job in receiver {
+                    // A panicking job must not stall `wait_idle` callers
+                    if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(job)) {
+                        worker_panic
+                            .lock()
+                            .unwrap_or_else(PoisonError::into_inner)
+                            .get_or_insert(payload);
+                    }
+                    worker_pending.finish();
+                }
+            })
+            .expect("failed to spawn worker thread");
+
+        Self {
+            sender: Mutex::new(sender),
+            pending,
+            panic,
+        }
+    }
+
+    /// Queues a job to run on the background thread.
+    pub(crate) fn submit<F: FnOnce() + Send + 'static>(&self, job: F) {
+        self.pending.add();
+        let sender = self.sender.lock().unwrap_or_else(PoisonError::into_inner);
+        if sender.send(Box::new(job)).is_err() {
+            self.pending.finish();
+        }
+    }
+
+    /// Blocks until every job submitted so far has finished.
+    ///
+    /// Returns the payload of the first job that panicked since the last
+    /// call, if any.
+    pub(crate) fn wait_idle(&self) -> Option<PanicPayload> {
+        self.pending.wait();
+        self.panic
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+    }
+}
@@ -0,0 +1,137 @@
+#![cfg(feature = "async")]
+
+use futures_core::Stream;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use zed::{Store, create_reducer};
+
+#[derive(Clone, Debug, PartialEq)]
+struct CounterState {
+    count: i32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum CounterAction {
+    Increment,
+    Add(i32),
+}
+
+fn counter_store() -> Store<CounterState, CounterAction> {
+    Store::new(
+        CounterState { count: 0 },
+        Box::new(create_reducer(
+            |state: &CounterState, action: &CounterAction| match action {
+                CounterAction::Increment => CounterState {
+                    count: state.count + 1,
+                },
+                CounterAction::Add(n) => CounterState {
+                    count: state.count + n,
+                },
+            },
+        )),
+    )
+}
+
+/// Adapts a Tokio channel to a `Stream`, like a socket reader would.
+struct ChannelStream<T>(mpsc::UnboundedReceiver<T>);
+
+impl<T> Stream for ChannelStream<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.0.poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_watch_awaits_state_changes() {
+        let store = counter_store();
+        let mut receiver = store.watch();
+        assert_eq!(receiver.borrow().count, 0);
+
+        let dispatcher = store.clone();
+        let task = tokio::spawn(async move {
+            receiver.changed().await.unwrap();
+            receiver.borrow_and_update().count
+        });
+
+        tokio::task::yield_now().await;
+        dispatcher.dispatch(CounterAction::Add(5));
+
+        assert_eq!(task.await.unwrap(), 5);
+    }
+
+    #[test]
+    fn test_watch_receivers_share_one_subscriber() {
+        let store = counter_store();
+        let first = store.watch();
+        let second = store.watch();
+
+        assert_eq!(store.subscriber_count(), 1);
+
+        store.dispatch(CounterAction::Increment);
+        assert_eq!(first.borrow().count, 1);
+        assert_eq!(second.borrow().count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_action_stream_receives_dispatched_actions() {
+        let store = counter_store();
+        let mut actions = store.action_stream();
+
+        store.dispatch(CounterAction::Increment);
+        store.dispatch_batch(vec![CounterAction::Add(2), CounterAction::Add(3)]);
+
+        assert_eq!(actions.recv().await.unwrap(), CounterAction::Increment);
+        assert_eq!(actions.recv().await.unwrap(), CounterAction::Add(2));
+        assert_eq!(actions.recv().await.unwrap(), CounterAction::Add(3));
+        assert!(actions.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_from_stream_batches_ready_actions() {
+        let store = counter_store();
+        let notifications = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&notifications);
+        store.subscribe(move |state: &CounterState| seen.lock().unwrap().push(state.count));
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+        for n in 1..=3 {
+            sender.send(CounterAction::Add(n)).unwrap();
+        }
+        drop(sender);
+
+        let received = store.dispatch_from_stream(ChannelStream(receiver)).await;
+        assert_eq!(received, 3);
+        assert_eq!(*notifications.lock().unwrap(), vec![6]);
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_from_stream_applies_actions_as_they_arrive() {
+        let store = counter_store();
+        let (sender, receiver) = mpsc::unbounded_channel();
+
+        let producer = tokio::spawn(async move {
+            sender.send(CounterAction::Increment).unwrap();
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            sender.send(CounterAction::Add(5)).unwrap();
+        });
+        let mut changes = store.watch();
+        let ingest = store.dispatch_from_stream(ChannelStream(receiver));
+        let (received, _) = tokio::join!(ingest, async {
+            changes.changed().await.unwrap();
+            assert_eq!(changes.borrow_and_update().count, 1);
+        });
+
+        producer.await.unwrap();
+        assert_eq!(received, 2);
+        assert_eq!(store.get_state().count, 6);
+    }
+}
@@ -0,0 +1,175 @@
+#![cfg(feature = "audit")]
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use zed::*;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Account {
+    balance: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+enum AccountAction {
+    Deposit(i64),
+    Withdraw(i64),
+}
+
+fn account_store() -> Store<Account, AccountAction> {
+    configure_store(
+        Account { balance: 0 },
+        create_try_reducer(|state: &Account, action: &AccountAction| match action {
+            AccountAction::Deposit(amount) => Ok(Account {
+                balance: state.balance + amount,
+            }),
+            AccountAction::Withdraw(amount) if *amount > state.balance => Err("insufficient funds"),
+            AccountAction::Withdraw(amount) => Ok(Account {
+                balance: state.balance - amount,
+            }),
+        }),
+    )
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("zed-{}-{name}", std::process::id()));
+    for index in 0..4 {
+        let _ = fs::remove_file(rotated(&path, index));
+    }
+    path
+}
+
+fn rotated(path: &Path, index: usize) -> PathBuf {
+    match index {
+        0 => path.to_path_buf(),
+        _ => PathBuf::from(format!("{}.{index}", path.display())),
+    }
+}
+
+fn read_entries(path: &Path) -> Vec<AuditEntry<AccountAction>> {
+    fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audit_log_records_applied_actions_with_state_hash() {
+        let path = temp_path("audit.jsonl");
+        let store = account_store();
+        let audit = AuditLog::open(&path).unwrap();
+        audit.attach(&store);
+
+        store.dispatch(AccountAction::Deposit(100));
+        store.dispatch(AccountAction::Withdraw(500));
+        store.dispatch(AccountAction::Withdraw(30));
+
+        let entries = read_entries(&path);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].seq, 1);
+        assert_eq!(entries[0].action, AccountAction::Deposit(100));
+        assert_eq!(
+            entries[0].state_hash,
+            AuditLog::state_hash(&Account { balance: 100 })
+        );
+        assert_eq!(entries[1].seq, 2);
+        assert_eq!(
+            entries[1].state_hash,
+            AuditLog::state_hash(&store.get_state())
+        );
+        assert!(audit.take_error().is_none());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_audit_log_continues_sequence_after_reopen() {
+        let path = temp_path("audit-reopen.jsonl");
+        let store = account_store();
+        let id = AuditLog::open(&path).unwrap().attach(&store);
+        store.dispatch(AccountAction::Deposit(1));
+        store.unsubscribe(id);
+
+        AuditLog::open(&path).unwrap().attach(&store);
+        store.dispatch(AccountAction::Deposit(2));
+
+        let seqs: Vec<_> = read_entries(&path).iter().map(|entry| entry.seq).collect();
+        assert_eq!(seqs, vec![1, 2]);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_audit_log_rotates_and_keeps_limited_files() {
+        let path = temp_path("audit-rotate.jsonl");
+        let store = account_store();
+        // Small enough that every entry starts a new file
+        AuditLog::open(&path)
+            .unwrap()
+            .with_rotation(10, 2)
+            .attach(&store);
+
+        for amount in 1..=4 {
+            store.dispatch(AccountAction::Deposit(amount));
+        }
+
+        let seqs = |index| -> Vec<u64> {
+            read_entries(&rotated(&path, index))
+                .iter()
+                .map(|entry| entry.seq)
+                .collect()
+        };
+        assert_eq!(seqs(0), vec![4]);
+        assert_eq!(seqs(1), vec![3]);
+        assert_eq!(seqs(2), vec![2]);
+        assert!(!rotated(&path, 3).exists());
+        for index in 0..3 {
+            fs::remove_file(rotated(&path, index)).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_audit_log_hashes_match_states_under_concurrent_dispatch() {
+        let path = temp_path("audit-concurrent.jsonl");
+        let store = account_store();
+        AuditLog::open(&path).unwrap().attach(&store);
+
+        let writers: Vec<_> = (0..4)
+            .map(|_| {
+                let store = store.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..50 {
+                        store.dispatch(AccountAction::Deposit(1));
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        // Entry n holds the hash of the balance after the n-th deposit
+        for (index, entry) in read_entries(&path).iter().enumerate() {
+            assert_eq!(entry.seq, index as u64 + 1);
+            assert_eq!(
+                entry.state_hash,
+                AuditLog::state_hash(&Account {
+                    balance: index as i64 + 1
+                })
+            );
+        }
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one rotated file must be kept")]
+    fn test_audit_log_rejects_rotation_without_files() {
+        let path = temp_path("audit-no-files.jsonl");
+        let audit = AuditLog::open(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let _ = audit.with_rotation(10, 0);
+    }
+}
@@ -0,0 +1,86 @@
+use zed::*;
+
+#[derive(Clone, Debug, PartialEq)]
+struct CounterState {
+    count: i32,
+}
+
+#[derive(Clone, Debug)]
+enum CounterAction {
+    Add(i32),
+}
+
+fn counter_store() -> Store<CounterState, CounterAction> {
+    configure_store(
+        CounterState { count: 0 },
+        create_reducer(
+            |state: &CounterState, action: &CounterAction| match action {
+                CounterAction::Add(n) => CounterState {
+                    count: state.count + n,
+                },
+            },
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bridge_forwards_mapped_actions() {
+        let source = counter_store();
+        let target = counter_store();
+        let bridge = StoreBridge::new(&source, &target, |action: &CounterAction| match action {
+            CounterAction::Add(n) if *n > 0 => Some(CounterAction::Add(n * 10)),
+            CounterAction::Add(_) => None,
+        });
+
+        source.dispatch(CounterAction::Add(1));
+        source.dispatch(CounterAction::Add(-1));
+        assert_eq!(source.get_state().count, 0);
+        assert_eq!(target.get_state().count, 10);
+
+        bridge.disconnect();
+        source.dispatch(CounterAction::Add(2));
+        assert_eq!(target.get_state().count, 10);
+    }
+
+    #[test]
+    fn test_bidirectional_bridges_do_not_loop() {
+        let left = counter_store();
+        let right = counter_store();
+        let _to_right =
+            StoreBridge::new(&left, &right, |action: &CounterAction| Some(action.clone()));
+        let _to_left =
+            StoreBridge::new(&right, &left, |action: &CounterAction| Some(action.clone()));
+
+        left.dispatch(CounterAction::Add(1));
+        right.dispatch(CounterAction::Add(2));
+
+        assert_eq!(left.get_state().count, 3);
+        assert_eq!(right.get_state().count, 3);
+    }
+
+    #[test]
+    fn test_state_bridge_chains_through_stores() {
+        let first = counter_store();
+        let second = counter_store();
+        let third = counter_store();
+        let _first_to_second = StoreBridge::from_state(&first, &second, |_: &CounterState| {
+            Some(CounterAction::Add(1))
+        });
+        let _second_to_third = StoreBridge::from_state(&second, &third, |_: &CounterState| {
+            Some(CounterAction::Add(1))
+        });
+        let _third_to_first = StoreBridge::from_state(&third, &first, |_: &CounterState| {
+            Some(CounterAction::Add(1))
+        });
+
+        first.dispatch(CounterAction::Add(5));
+
+        assert_eq!(first.get_state().count, 5);
+        assert_eq!(second.get_state().count, 1);
+        assert_eq!(third.get_state().count, 1);
+    }
+}
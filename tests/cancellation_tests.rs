@@ -0,0 +1,95 @@
+use std::sync::mpsc;
+use std::time::Duration;
+use zed::*;
+
+#[derive(Clone, Debug, PartialEq)]
+struct FeedState {
+    posts: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+enum FeedAction {
+    Loaded(String),
+}
+
+fn feed_store() -> Store<FeedState, FeedAction> {
+    configure_store(
+        FeedState { posts: vec![] },
+        create_reducer(|state: &FeedState, action: &FeedAction| match action {
+            FeedAction::Loaded(post) => {
+                let mut posts = state.posts.clone();
+                posts.push(post.clone());
+                FeedState { posts }
+            }
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancelling_a_token_cancels_its_children_only() {
+        let parent = CancellationToken::new();
+        let child = parent.child_token();
+        let grandchild = child.child_token();
+
+        grandchild.cancel();
+        assert!(!child.is_cancelled());
+
+        let sibling = child.child_token();
+        parent.cancel();
+        assert!(child.is_cancelled());
+        assert!(sibling.is_cancelled());
+        assert!(parent.child_token().is_cancelled());
+    }
+
+    #[test]
+    fn test_scope_cancellation_aborts_thunk() {
+        let store = feed_store();
+        let scope = store.cancellation_token();
+        let (started, ready) = mpsc::channel();
+
+        let handle = store.dispatch_async_with_token(&scope, |dispatch, _, token| async move {
+            dispatch(FeedAction::Loaded("first".to_string()));
+            started.send(()).unwrap();
+            token.cancelled().await;
+            dispatch(FeedAction::Loaded("after cancel".to_string()));
+        });
+
+        ready.recv().unwrap();
+        scope.cancel();
+
+        assert_eq!(handle.join().unwrap(), None);
+        assert_eq!(store.get_state().posts, vec!["first"]);
+    }
+
+    #[test]
+    fn test_cancel_all_effects_stops_sagas_and_thunks() {
+        let store = feed_store();
+        let saga = store.run_saga(|saga| async move {
+            while saga.take(|_| true).await.is_some() {}
+            saga.delay(Duration::from_secs(60)).await;
+            saga.is_cancelled()
+        });
+        let thunk = store
+            .dispatch_async_with_token(&CancellationToken::new(), |_, _, token| async move {
+                token.cancelled().await
+            });
+
+        store.cancel_all_effects();
+        assert!(saga.join().unwrap());
+        assert_eq!(thunk.join().unwrap(), None);
+
+        // Work started afterwards runs normally
+        let handle = store.dispatch_async_with_token(
+            &store.cancellation_token(),
+            |dispatch, get_state, _| async move {
+                dispatch(FeedAction::Loaded("fresh".to_string()));
+                get_state().posts.len()
+            },
+        );
+        assert_eq!(handle.join().unwrap(), Some(1));
+    }
+}
@@ -0,0 +1,131 @@
+use zed::*;
+
+#[derive(Clone, Debug, PartialEq)]
+struct UserState {
+    name: String,
+    logged_in: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct CartState {
+    items: Vec<u32>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct AppState {
+    user: UserState,
+    cart: CartState,
+    total: u32,
+    version: u32,
+}
+
+#[derive(Clone, Debug)]
+enum AppAction {
+    Login(String),
+    Logout,
+    AddItem(u32),
+    ClearCart,
+}
+
+fn user_reducer(state: &UserState, action: &AppAction) -> UserState {
+    match action {
+        AppAction::Login(name) => UserState {
+            name: name.clone(),
+            logged_in: true,
+        },
+        AppAction::Logout => UserState {
+            name: String::new(),
+            logged_in: false,
+        },
+        _ => state.clone(),
+    }
+}
+
+fn cart_reducer(state: &CartState, action: &AppAction) -> CartState {
+    match action {
+        AppAction::AddItem(price) => {
+            let mut cart = state.clone();
+            cart.items.push(*price);
+            cart
+        }
+        AppAction::ClearCart | AppAction::Logout => CartState { items: vec![] },
+        _ => state.clone(),
+    }
+}
+
+fn initial_state() -> AppState {
+    AppState {
+        user: UserState {
+            name: String::new(),
+            logged_in: false,
+        },
+        cart: CartState { items: vec![] },
+        total: 0,
+        version: 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_combined_reducer_routes_actions_to_slices() {
+        let reducer = combine_reducers! {
+            state: AppState,
+            action: AppAction,
+            reducers: {
+                user: user_reducer,
+                cart: cart_reducer,
+                total: |total: &u32, action: &AppAction| match action {
+                    AppAction::AddItem(price) => total + price,
+                    AppAction::ClearCart | AppAction::Logout => 0,
+                    _ => *total,
+                },
+            }
+        };
+
+        let state = reducer.reduce(&initial_state(), &AppAction::Login("ana".to_string()));
+        let state = reducer.reduce(&state, &AppAction::AddItem(10));
+        let state = reducer.reduce(&state, &AppAction::AddItem(5));
+
+        assert_eq!(state.user.name, "ana");
+        assert!(state.user.logged_in);
+        assert_eq!(state.cart.items, vec![10, 5]);
+        assert_eq!(state.total, 15);
+        // Fields without a slice reducer are left untouched
+        assert_eq!(state.version, 1);
+
+        let state = reducer.reduce(&state, &AppAction::Logout);
+        assert!(!state.user.logged_in);
+        assert!(state.cart.items.is_empty());
+        assert_eq!(state.total, 0);
+    }
+
+    #[test]
+    fn test_combined_reducer_in_store() {
+        let store = configure_store(
+            initial_state(),
+            combine_reducers! {
+                state: AppState,
+                action: AppAction,
+                reducers: {
+                    user: user_reducer,
+                    cart: cart_reducer,
+                },
+            },
+        );
+
+        store.dispatch_batch(vec![
+            AppAction::Login("bob".to_string()),
+            AppAction::AddItem(3),
+            AppAction::ClearCart,
+            AppAction::AddItem(7),
+        ]);
+
+        let state = store.get_state();
+        assert_eq!(state.user.name, "bob");
+        assert_eq!(state.cart.items, vec![7]);
+        assert_eq!(state.total, 0);
+    }
+}
@@ -61,7 +61,7 @@ mod configure_store_tests {
 
         // Initial state
         assert_eq!(store.get_state().value, 0);
-        assert_eq!(store.get_state().history, vec![]);
+        assert_eq!(store.get_state().history, Vec::<i32>::new());
 
         // Dispatch increment
         store.dispatch(CounterAction::Increment);
@@ -283,4 +283,82 @@ mod configure_store_tests {
         assert_eq!(store.get_state().items, vec!["item2"]);
         assert_eq!(store.get_state().counter, 2);
     }
+
+    struct CountingEnhancer {
+        calls: std::sync::Arc<std::sync::Mutex<u32>>,
+    }
+
+    impl zed::StoreEnhancer<CounterState, CounterAction> for CountingEnhancer {
+        fn enhance_reducer(
+            &self,
+            reducer: zed::reducer::BoxedReducer<CounterState, CounterAction>,
+        ) -> zed::reducer::BoxedReducer<CounterState, CounterAction> {
+            let calls = std::sync::Arc::clone(&self.calls);
+            Box::new(create_reducer(
+                move |state: &CounterState, action: &CounterAction| {
+                    *calls.lock().unwrap() += 1;
+                    reducer.reduce(state, action)
+                },
+            ))
+        }
+    }
+
+    #[test]
+    fn test_configure_store_with_enhancer() {
+        use std::sync::{Arc, Mutex};
+        use zed::{StoreEnhancer, configure_store_with_enhancer};
+
+        let calls = Arc::new(Mutex::new(0));
+        let values = Arc::new(Mutex::new(Vec::new()));
+        let values_clone = Arc::clone(&values);
+
+        let subscriber = move |store: Store<CounterState, CounterAction>| {
+            let values = Arc::clone(&values_clone);
+            store.subscribe(move |state: &CounterState| values.lock().unwrap().push(state.value));
+            store
+        };
+
+        let store = configure_store_with_enhancer(
+            CounterState::new(),
+            create_reducer(counter_reducer),
+            CountingEnhancer {
+                calls: Arc::clone(&calls),
+            }
+            .and_then(subscriber),
+        );
+
+        store.dispatch(CounterAction::Increment);
+        store.dispatch(CounterAction::Add(4));
+
+        assert_eq!(store.get_state().value, 5);
+        assert_eq!(*calls.lock().unwrap(), 2);
+        assert_eq!(*values.lock().unwrap(), vec![1, 5]);
+    }
+
+    #[test]
+    fn test_enhancer_composition_order() {
+        use std::sync::{Arc, Mutex};
+        use zed::{StoreEnhancer, configure_store_with_enhancer};
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let first_order = Arc::clone(&order);
+        let second_order = Arc::clone(&order);
+
+        let first = move |store: Store<CounterState, CounterAction>| {
+            first_order.lock().unwrap().push("first");
+            store
+        };
+        let second = move |store: Store<CounterState, CounterAction>| {
+            second_order.lock().unwrap().push("second");
+            store
+        };
+
+        let _store = configure_store_with_enhancer(
+            CounterState::new(),
+            create_reducer(counter_reducer),
+            first.and_then(second),
+        );
+
+        assert_eq!(*order.lock().unwrap(), vec!["first", "second"]);
+    }
 }
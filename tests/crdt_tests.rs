@@ -0,0 +1,103 @@
+use zed::{CrdtMap, GCounter, LwwRegister, Mergeable, OrSet, PNCounter, StateNode};
+
+/// Merges `a` and `b` both ways and checks that both replicas agree.
+fn converge<T: Mergeable + Clone + PartialEq + std::fmt::Debug>(a: &T, b: &T) -> T {
+    let mut ab = a.clone();
+    ab.merge(b);
+    let mut ba = b.clone();
+    ba.merge(a);
+    assert_eq!(ab, ba);
+
+    // Merging again changes nothing
+    let mut again = ab.clone();
+    again.merge(b);
+    assert_eq!(again, ab);
+    ab
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counters_and_register_converge() {
+        let mut a = PNCounter::new();
+        a.increment("a", 5);
+        let mut b = a.clone();
+        a.decrement("a", 2);
+        b.increment("b", 3);
+        assert_eq!(converge(&a, &b).value(), 6);
+
+        let mut views = GCounter::new();
+        views.increment("a", 1);
+        let mut other = GCounter::new();
+        other.increment("b", 4);
+        assert_eq!(converge(&views, &other).value(), 5);
+
+        let mut title = LwwRegister::new("draft".to_string());
+        let mut renamed = title.clone();
+        assert!(title.set("first".to_string(), 10, "a"));
+        assert!(renamed.set("second".to_string(), 10, "b"));
+        assert!(!renamed.set("stale".to_string(), 5, "b"));
+        // Equal timestamps are ordered by replica ID
+        assert_eq!(converge(&title, &renamed).get(), "second");
+    }
+
+    #[test]
+    fn test_or_set_add_wins_over_concurrent_remove() {
+        let mut a = OrSet::new();
+        a.insert("apple", "a");
+        a.insert("pear", "a");
+        let mut b = a.clone();
+
+        assert!(a.remove(&"apple"));
+        assert!(!a.remove(&"plum"));
+        b.insert("apple", "b");
+        b.remove(&"pear");
+
+        let merged = converge(&a, &b);
+        assert!(merged.contains(&"apple"));
+        assert!(!merged.contains(&"pear"));
+        assert_eq!(merged.iter().collect::<Vec<_>>(), vec![&"apple"]);
+        assert_eq!(merged.len(), 1);
+    }
+
+    #[test]
+    fn test_crdt_map_merges_values_per_key() {
+        let mut a: CrdtMap<String, PNCounter> = CrdtMap::new();
+        a.update("likes".to_string(), PNCounter::new(), "a", |c| {
+            c.increment("a", 2)
+        });
+        let mut b = a.clone();
+        a.update("likes".to_string(), PNCounter::new(), "a", |c| {
+            c.increment("a", 1)
+        });
+        b.update("likes".to_string(), PNCounter::new(), "b", |c| {
+            c.decrement("b", 1)
+        });
+        b.insert("shares".to_string(), PNCounter::new(), "b");
+        a.remove(&"shares".to_string());
+
+        let merged = converge(&a, &b);
+        assert_eq!(merged.get(&"likes".to_string()).unwrap().value(), 2);
+        assert!(merged.get(&"shares".to_string()).is_some());
+        assert_eq!(merged.len(), 2);
+
+        let json = serde_json::to_string(&merged).unwrap();
+        let restored: CrdtMap<String, PNCounter> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, merged);
+    }
+
+    #[test]
+    fn test_mergeable_state_node_merges_remote_states() {
+        let mut tags = OrSet::new();
+        tags.insert("rust".to_string(), "remote");
+
+        let mut node = StateNode::mergeable("local".to_string(), OrSet::new());
+        node.state.insert("crdt".to_string(), "local");
+        node.resolve_conflict(tags);
+
+        assert!(node.state.contains(&"rust".to_string()));
+        assert!(node.state.contains(&"crdt".to_string()));
+    }
+}
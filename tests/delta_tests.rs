@@ -0,0 +1,206 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use zed::{DeltaStateManager, Diff, JsonDelta, StateDiff, StateManager};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Document {
+    title: String,
+    paragraphs: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+enum DocAction {
+    Append(String),
+    Edit(usize, String),
+    Rename(String),
+}
+
+fn doc_reducer(doc: &Document, action: &DocAction) -> Document {
+    let mut next = doc.clone();
+    match action {
+        DocAction::Append(text) => next.paragraphs.push(text.clone()),
+        DocAction::Edit(index, text) => next.paragraphs[*index] = text.clone(),
+        DocAction::Rename(title) => next.title = title.clone(),
+    }
+    next
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Counter(i64);
+
+impl Diff for Counter {
+    type Delta = i64;
+
+    fn diff(&self, next: &Self) -> i64 {
+        next.0 - self.0
+    }
+
+    fn apply(&self, delta: &i64) -> Self {
+        Counter(self.0 + delta)
+    }
+}
+
+fn add(counter: &Counter, amount: &i64) -> Counter {
+    Counter(counter.0 + amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delta_state_manager_rebuilds_every_state() {
+        let mut timeline = DeltaStateManager::new(Counter(0), add, 4);
+        for step in 1..=10i64 {
+            timeline.dispatch(step);
+        }
+
+        assert_eq!(timeline.history_len(), 11);
+        // Entries 0, 4 and 8 are snapshots
+        assert_eq!(timeline.snapshot_count(), 3);
+        for index in 0..=10i64 {
+            let expected = index * (index + 1) / 2;
+            assert_eq!(timeline.state_at(index as usize), Some(Counter(expected)));
+        }
+        assert_eq!(timeline.state_at(11), None);
+
+        timeline.rewind(5);
+        assert_eq!(timeline.current_state(), &Counter(15));
+        timeline.redo(2);
+        assert_eq!(timeline.current_state(), &Counter(28));
+        assert!(timeline.can_redo());
+
+        // Dispatching after a rewind discards the future states
+        timeline.dispatch(100i64);
+        assert_eq!(timeline.history_len(), 9);
+        assert!(!timeline.can_redo());
+        assert_eq!(timeline.state_at(7), Some(Counter(28)));
+        assert_eq!(timeline.current_state(), &Counter(128));
+    }
+
+    #[test]
+    fn test_delta_state_manager_with_json_diff() {
+        let initial = Document {
+            title: "Draft".to_string(),
+            paragraphs: vec!["intro".to_string()],
+        };
+        let mut timeline = DeltaStateManager::with_json_diff(initial.clone(), doc_reducer, 100);
+
+        timeline.dispatch(DocAction::Append("body".to_string()));
+        timeline.dispatch(DocAction::Edit(0, "new intro".to_string()));
+        timeline.dispatch(DocAction::Rename("Final".to_string()));
+        assert_eq!(timeline.snapshot_count(), 1);
+
+        assert!(timeline.jump_to(1));
+        assert_eq!(
+            timeline.current_state().paragraphs,
+            vec!["intro".to_string(), "body".to_string()]
+        );
+        assert_eq!(timeline.state_at(0), Some(initial));
+
+        timeline.redo(10);
+        assert_eq!(timeline.current_state().title, "Final");
+        assert_eq!(timeline.current_state().paragraphs[0], "new intro");
+    }
+
+    #[test]
+    fn test_json_delta_only_records_changes() {
+        let old = json!({ "title": "a", "items": [1, 2, 3], "gone": true });
+        let new = json!({ "title": "a", "items": [1, 5, 3, 4], "added": null });
+
+        let delta = JsonDelta::between(&old, &new);
+        match &delta {
+            JsonDelta::Object { changed, removed } => {
+                assert!(!changed.contains_key("title"));
+                assert_eq!(removed, &vec!["gone".to_string()]);
+            }
+            other => panic!("expected an object delta, got {other:?}"),
+        }
+
+        let mut value = old.clone();
+        delta.apply_to(&mut value);
+        assert_eq!(value, new);
+        assert_eq!(JsonDelta::between(&new, &new), JsonDelta::Unchanged);
+    }
+
+    #[test]
+    fn test_state_diff_lists_changed_paths() {
+        let old = json!({ "user": { "name": "Ada", "a/b": 1 }, "tags": ["x", "y"] });
+        let new = json!({ "user": { "name": "Grace", "a/b": 2 }, "tags": ["x"], "new": true });
+
+        let diff = StateDiff::between(&old, &new);
+        let paths: Vec<_> = diff.changes().iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(paths, vec!["/tags/1", "/user/a~1b", "/user/name", "/new"]);
+
+        let removed = diff.get("/tags/1").unwrap();
+        assert_eq!(removed.old, Some(json!("y")));
+        assert_eq!(removed.new, None);
+        assert_eq!(diff.get("/new").unwrap().old, None);
+        assert!(StateDiff::between(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_state_manager_diff_and_delta() {
+        let mut counters = StateManager::new(Counter(0), add);
+        counters.dispatch(5);
+        counters.dispatch(-2);
+        assert_eq!(counters.delta(0, 2), Some(3));
+        assert_eq!(counters.delta(2, 1), Some(2));
+        assert_eq!(counters.delta(0, 3), None);
+
+        let mut documents = StateManager::new(
+            Document {
+                title: "Draft".to_string(),
+                paragraphs: vec![],
+            },
+            doc_reducer,
+        );
+        documents.dispatch(DocAction::Append("intro".to_string()));
+        documents.dispatch(DocAction::Rename("Final".to_string()));
+
+        let diff = documents.diff(1, 2).unwrap();
+        assert_eq!(diff.changes().len(), 1);
+        assert_eq!(diff.changes()[0].path, "/title");
+        assert_eq!(diff.changes()[0].new, Some(json!("Final")));
+        assert_eq!(documents.diff(0, 2).unwrap().changes().len(), 2);
+        assert!(documents.diff(0, 5).is_none());
+    }
+
+    #[test]
+    fn test_json_diff_stores_snapshot_when_round_trip_fails() {
+        // NaN serializes to null, which does not deserialize as an f64
+        let mut timeline =
+            DeltaStateManager::with_json_diff(1.0_f64, |_: &f64, next: &f64| *next, 100);
+        timeline.dispatch(f64::NAN);
+        timeline.dispatch(2.0);
+        assert_eq!(timeline.snapshot_count(), 2);
+
+        assert!(timeline.jump_to(1));
+        assert!(timeline.current_state().is_nan());
+        assert!(timeline.jump_to(0));
+        assert_eq!(*timeline.current_state(), 1.0);
+        assert_eq!(timeline.state_at(2), Some(2.0));
+    }
+
+    #[test]
+    fn test_state_manager_moves_to_delta_storage() {
+        let mut timeline = StateManager::new(Counter(0), |counter: &Counter, n: &i64| {
+            Counter(counter.0 + n)
+        });
+        for n in 1..=5 {
+            timeline.dispatch(n);
+        }
+        timeline.rewind(1);
+
+        let mut compact = timeline.into_delta_storage(4);
+        assert_eq!(compact.history_len(), 6);
+        assert_eq!(compact.snapshot_count(), 2);
+        assert_eq!(compact.current_position(), 4);
+        assert_eq!(*compact.current_state(), Counter(10));
+        assert_eq!(compact.state_at(5), Some(Counter(15)));
+
+        compact.dispatch(100);
+        assert_eq!(compact.history_len(), 6);
+        assert_eq!(*compact.current_state(), Counter(110));
+    }
+}
@@ -0,0 +1,121 @@
+#![cfg(feature = "devtools")]
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::net::TcpStream;
+use std::thread;
+use std::time::{Duration, Instant};
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{Message, WebSocket};
+use zed::*;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct CounterState {
+    count: i32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+enum CounterAction {
+    Add(i32),
+}
+
+fn counter_store() -> Store<CounterState, CounterAction> {
+    Store::new(
+        CounterState { count: 0 },
+        Box::new(create_reducer(
+            |state: &CounterState, action: &CounterAction| match action {
+                CounterAction::Add(n) => CounterState {
+                    count: state.count + n,
+                },
+            },
+        )),
+    )
+}
+
+type Client = WebSocket<MaybeTlsStream<TcpStream>>;
+
+fn connect(server: &DevToolsServer<CounterState, CounterAction>) -> Client {
+    let (client, _) = tungstenite::connect(format!("ws://{}", server.local_addr())).unwrap();
+    client
+}
+
+fn next_event(client: &mut Client) -> Value {
+    loop {
+        if let Message::Text(text) = client.read().unwrap() {
+            return serde_json::from_str(text.as_str()).unwrap();
+        }
+    }
+}
+
+fn wait_for<F: Fn() -> bool>(condition: F) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !condition() {
+        assert!(Instant::now() < deadline, "condition not met in time");
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_devtools_publishes_actions() {
+        let store = counter_store();
+        let server = DevToolsServer::start(store.clone(), "127.0.0.1:0").unwrap();
+        let mut client = connect(&server);
+
+        assert_eq!(
+            next_event(&mut client),
+            json!({ "type": "init", "state": { "count": 0 } })
+        );
+
+        store.dispatch(CounterAction::Add(2));
+        assert_eq!(
+            next_event(&mut client),
+            json!({ "type": "action", "action": { "Add": 2 }, "state": { "count": 2 } })
+        );
+    }
+
+    #[test]
+    fn test_devtools_commands() {
+        let store = counter_store();
+        let server = DevToolsServer::start(store.clone(), "127.0.0.1:0").unwrap();
+        let mut client = connect(&server);
+        next_event(&mut client);
+
+        let jump = json!({ "type": "jump_to_state", "state": { "count": 40 } });
+        client.send(Message::text(jump.to_string())).unwrap();
+        assert_eq!(
+            next_event(&mut client),
+            json!({ "type": "state", "state": { "count": 40 } })
+        );
+
+        let dispatch = json!({ "type": "dispatch", "action": { "Add": 2 } });
+        client.send(Message::text(dispatch.to_string())).unwrap();
+        assert_eq!(next_event(&mut client)["state"], json!({ "count": 42 }));
+        wait_for(|| store.get_state().count == 42);
+    }
+
+    #[test]
+    fn test_devtools_stops_on_drop() {
+        let store = counter_store();
+        let server = DevToolsServer::start(store.clone(), "127.0.0.1:0").unwrap();
+        let mut client = connect(&server);
+        next_event(&mut client);
+        assert_eq!(server.client_count(), 1);
+        assert_eq!(store.subscriber_count(), 0);
+
+        drop(server);
+        store.dispatch(CounterAction::Add(1));
+
+        // The client is disconnected instead of receiving the action
+        loop {
+            match client.read() {
+                Ok(Message::Text(_)) => panic!("received an event after shutdown"),
+                Ok(_) => continue,
+                Err(_) => break,
+            }
+        }
+    }
+}
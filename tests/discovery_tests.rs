@@ -0,0 +1,82 @@
+#![cfg(feature = "discovery")]
+
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, TcpListener};
+use std::thread;
+use std::time::{Duration, Instant};
+use zed::{DiscoveredPeer, Discovery, MeshLink, StateNode, TcpTransport};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Note {
+    text: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discovered_peers_connect_once_per_pair() {
+        let suffix = std::process::id();
+        let (alpha_id, beta_id) = (format!("alpha-{suffix}"), format!("beta-{suffix}"));
+        let alpha_listener = TcpListener::bind("0.0.0.0:0").unwrap();
+        let beta_listener = TcpListener::bind("0.0.0.0:0").unwrap();
+        beta_listener.set_nonblocking(true).unwrap();
+
+        let mut alpha =
+            Discovery::start(&alpha_id, alpha_listener.local_addr().unwrap().port()).unwrap();
+        let mut beta =
+            Discovery::start(&beta_id, beta_listener.local_addr().unwrap().port()).unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(20);
+        let mut dialed = Vec::new();
+        while dialed.is_empty() || !beta.peers().iter().any(|peer| peer.id == alpha_id) {
+            assert!(Instant::now() < deadline, "peers were not discovered");
+            // The higher ID never dials
+            assert!(beta.connect_new_peers().is_empty());
+            dialed.extend(alpha.connect_new_peers());
+            thread::sleep(Duration::from_millis(50));
+        }
+        assert_eq!(dialed.len(), 1);
+        let (peer, link) = dialed.pop().unwrap();
+        assert_eq!(peer, beta_id);
+        assert!(alpha.peers().iter().all(|peer| peer.id != alpha_id));
+
+        // The dialed connection synchronizes the nodes
+        let mut client = link.unwrap();
+        let local = StateNode::new(
+            alpha_id,
+            Note {
+                text: "found you".to_string(),
+            },
+        );
+        client.send_state(&local).unwrap();
+
+        beta_listener.set_nonblocking(false).unwrap();
+        let (stream, _) = beta_listener.accept().unwrap();
+        let mut server = MeshLink::new(TcpTransport::from_stream(stream).unwrap());
+        let mut remote = StateNode::new(
+            beta_id,
+            Note {
+                text: String::new(),
+            },
+        );
+        while server.receive(&mut remote).unwrap() == 0 {}
+        assert_eq!(remote.state, local.state);
+    }
+
+    #[test]
+    fn test_discovered_peer_skips_link_local_addresses() {
+        let link_local: IpAddr = "fe80::1".parse().unwrap();
+        let routable_v6: IpAddr = "fd00::1".parse().unwrap();
+        let v4: IpAddr = "192.168.1.20".parse().unwrap();
+        let peer = |addresses: &[IpAddr]| {
+            DiscoveredPeer::from_addresses("peer".to_string(), addresses.iter().copied(), 7000)
+                .map(|peer| peer.addr.ip())
+        };
+
+        assert_eq!(peer(&[link_local]), None);
+        assert_eq!(peer(&[link_local, routable_v6]), Some(routable_v6));
+        assert_eq!(peer(&[routable_v6, link_local, v4]), Some(v4));
+    }
+}
@@ -0,0 +1,93 @@
+use zed::{SharedStateNode, StateNode, export_dot};
+
+fn node(id: &str) -> SharedStateNode<u32> {
+    StateNode::new(id.to_string(), 0).into_shared()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_empty_mesh() {
+        assert_eq!(export_dot::<u32>(&[]), "digraph mesh {\n}\n");
+    }
+
+    #[test]
+    fn test_export_nodes_and_connections() {
+        let alice = node("alice");
+        let bob = node("bob");
+        let carol = node("carol");
+        StateNode::connect_bidirectional(&alice, &bob);
+        bob.lock().unwrap().connect(&carol);
+
+        let dot = export_dot(&[carol, bob, alice]);
+
+        assert_eq!(
+            dot,
+            "digraph mesh {\n\
+             \x20   \"alice\" [label=\"alice\\nsequence 0\"];\n\
+             \x20   \"bob\" [label=\"bob\\nsequence 0\"];\n\
+             \x20   \"carol\" [label=\"carol\\nsequence 0\"];\n\
+             \x20   \"alice\" -> \"bob\" [dir=both];\n\
+             \x20   \"bob\" -> \"carol\";\n\
+             }\n"
+        );
+    }
+
+    #[test]
+    fn test_export_version_info() {
+        let alice = node("alice");
+        let bob = node("bob");
+        StateNode::connect_bidirectional(&alice, &bob);
+        alice.lock().unwrap().propagate_with_ttl(4);
+        alice.lock().unwrap().propagate_with_ttl(4);
+        bob.lock().unwrap().set_authority(3);
+
+        let dot = export_dot(&[alice, bob]);
+
+        assert!(dot.contains("\"alice\" [label=\"alice\\nsequence 2\"];"));
+        assert!(
+            dot.contains("\"bob\" [label=\"bob\\nsequence 0\\nauthority 3\\nseen alice: 2\"];")
+        );
+    }
+
+    #[test]
+    fn test_export_offline_connections() {
+        let alice = node("alice");
+        let bob = node("bob");
+        StateNode::connect_bidirectional(&alice, &bob);
+        {
+            let mut alice = alice.lock().unwrap();
+            alice.mark_offline(&"bob".to_string());
+            alice.propagate_update();
+        }
+
+        let dot = export_dot(&[alice, bob]);
+
+        assert!(dot.contains("\"alice\" -> \"bob\" [style=dashed, label=\"offline, 1 pending\"];"));
+        assert!(dot.contains("\"bob\" -> \"alice\";"));
+        assert!(!dot.contains("dir=both"));
+    }
+
+    #[test]
+    fn test_export_nodes_outside_the_set() {
+        let alice = node("alice");
+        let bob = node("bob");
+        alice.lock().unwrap().connect(&bob);
+
+        let dot = export_dot(&[alice]);
+
+        assert!(dot.contains("\"bob\" [style=dashed];"));
+        assert!(dot.contains("\"alice\" -> \"bob\";"));
+    }
+
+    #[test]
+    fn test_export_escapes_ids() {
+        let quoted = node("say \"hi\"");
+
+        let dot = export_dot(&[quoted]);
+
+        assert!(dot.contains("\"say \\\"hi\\\"\" [label=\"say \\\"hi\\\"\\nsequence 0\"];"));
+    }
+}
@@ -0,0 +1,142 @@
+use std::sync::{Arc, Mutex};
+use zed::*;
+
+#[derive(Clone, Debug, PartialEq)]
+struct LoginState {
+    user: Option<String>,
+    log: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum LoginAction {
+    Login(String),
+    LoggedIn(String),
+    Welcome,
+}
+
+fn login_reducer(state: &LoginState, action: &LoginAction) -> Result<LoginState, String> {
+    let mut next = state.clone();
+    match action {
+        LoginAction::Login(user) if user.is_empty() => return Err("empty user".to_string()),
+        LoginAction::Login(user) => next.log.push(format!("login {user}")),
+        LoginAction::LoggedIn(user) => {
+            next.user = Some(user.clone());
+            next.log.push(format!("logged in {user}"));
+        }
+        LoginAction::Welcome => next.log.push("welcome".to_string()),
+    }
+    Ok(next)
+}
+
+fn login_store() -> Store<LoginState, LoginAction> {
+    configure_store(
+        LoginState {
+            user: None,
+            log: vec![],
+        },
+        create_try_reducer(login_reducer),
+    )
+}
+
+/// Answers every login with a successful response, then a welcome.
+struct FakeAuth {
+    calls: Arc<Mutex<Vec<String>>>,
+}
+
+impl Effect<LoginState, LoginAction> for FakeAuth {
+    fn run(&self, action: &LoginAction, _: &LoginState) -> Vec<LoginAction> {
+        match action {
+            LoginAction::Login(user) => {
+                self.calls.lock().unwrap().push(user.clone());
+                vec![LoginAction::LoggedIn(user.clone()), LoginAction::Welcome]
+            }
+            _ => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_effect_dispatches_follow_up_actions_in_order() {
+        let store = login_store();
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        store.add_effect(FakeAuth {
+            calls: Arc::clone(&calls),
+        });
+
+        store.dispatch(LoginAction::Login("ana".to_string()));
+
+        let state = store.get_state();
+        assert_eq!(state.user.as_deref(), Some("ana"));
+        assert_eq!(state.log, vec!["login ana", "logged in ana", "welcome"]);
+        assert_eq!(*calls.lock().unwrap(), vec!["ana"]);
+    }
+
+    #[test]
+    fn test_effect_sees_new_state_and_skips_rejected_actions() {
+        let store = login_store();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let states = Arc::clone(&seen);
+        store.add_effect(move |action: &LoginAction, state: &LoginState| {
+            states
+                .lock()
+                .unwrap()
+                .push((action.clone(), state.log.len()));
+            Vec::new()
+        });
+
+        assert!(
+            store
+                .dispatch_try(LoginAction::Login(String::new()))
+                .is_err()
+        );
+        store.dispatch(LoginAction::Welcome);
+
+        assert_eq!(*seen.lock().unwrap(), vec![(LoginAction::Welcome, 1)]);
+    }
+
+    #[test]
+    fn test_unsubscribed_effect_stops_running() {
+        let store = login_store();
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let id = store.add_effect(FakeAuth {
+            calls: Arc::clone(&calls),
+        });
+
+        assert!(store.unsubscribe(id));
+        store.dispatch(LoginAction::Login("ana".to_string()));
+
+        assert!(calls.lock().unwrap().is_empty());
+        assert_eq!(store.get_state().user, None);
+    }
+
+    #[test]
+    fn test_effects_run_in_registration_order() {
+        let store = login_store();
+        let order = Arc::new(Mutex::new(Vec::new()));
+        for index in 0..16 {
+            let order = Arc::clone(&order);
+            store.add_effect(move |_: &LoginAction, _: &LoginState| {
+                order.lock().unwrap().push(index);
+                Vec::new()
+            });
+        }
+
+        store.dispatch(LoginAction::Welcome);
+
+        assert_eq!(*order.lock().unwrap(), (0..16).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_long_effect_chain_does_not_recurse() {
+        let store = configure_store(0u32, create_reducer(|count: &u32, _: &()| count + 1));
+        store.add_effect(|_: &(), count: &u32| if *count < 100_000 { vec![()] } else { vec![] });
+
+        store.dispatch(());
+
+        assert_eq!(store.get_state(), 100_000);
+    }
+}
@@ -277,7 +277,7 @@ mod error_handling_tests {
                     data: vec![format!("data_{}", i)],
                 },
             );
-            main_node.connect(node);
+            main_node.connect(&node.into_shared());
         }
 
         // Update main node state
@@ -286,7 +286,8 @@ mod error_handling_tests {
 
         // All connected nodes should have been updated
         for (id, node) in &main_node.connections {
-            assert_eq!(node.state.value, 999, "Node {id} was not updated");
+            let value = node.lock().unwrap().state.value;
+            assert_eq!(value, 999, "Node {id} was not updated");
         }
     }
 
@@ -297,26 +298,20 @@ mod error_handling_tests {
                 value: 0,
                 data: vec![],
             },
-            |state: &TestState, action: &dyn std::any::Any| {
-                if let Some(test_action) = action.downcast_ref::<TestAction>() {
-                    match test_action {
-                        TestAction::Increment => TestState {
-                            value: state.value + 1,
-                            data: state.data.clone(),
-                        },
-                        TestAction::AddData(s) => {
-                            let mut new_data = state.data.clone();
-                            new_data.push(s.clone());
-                            TestState {
-                                value: state.value,
-                                data: new_data,
-                            }
-                        }
-                        _ => state.clone(),
+            |state: &TestState, action: &TestAction| match action {
+                TestAction::Increment => TestState {
+                    value: state.value + 1,
+                    data: state.data.clone(),
+                },
+                TestAction::AddData(s) => {
+                    let mut new_data = state.data.clone();
+                    new_data.push(s.clone());
+                    TestState {
+                        value: state.value,
+                        data: new_data,
                     }
-                } else {
-                    state.clone()
                 }
+                _ => state.clone(),
             },
         );
 
@@ -351,13 +346,14 @@ mod error_handling_tests {
             },
         );
 
-        let mut node2 = StateNode::new(
+        let node2 = StateNode::new(
             "node2".to_string(),
             TestState {
                 value: 2,
                 data: vec![],
             },
-        );
+        )
+        .into_shared();
 
         let node3 = StateNode::new(
             "node3".to_string(),
@@ -365,20 +361,29 @@ mod error_handling_tests {
                 value: 3,
                 data: vec![],
             },
-        );
+        )
+        .into_shared();
 
         // Create circular-like connections
-        node1.connect(node2.clone());
-        node2.connect(node3.clone());
-        node1.connect(node3);
+        node1.connect(&node2);
+        node2.lock().unwrap().connect(&node3);
+        node1.connect(&node3);
 
         // Update node1 and propagate
         node1.state.value = 999;
         node1.propagate_update();
 
         // Check that updates propagated correctly
-        assert_eq!(node1.connections.get("node2").unwrap().state.value, 999);
-        assert_eq!(node1.connections.get("node3").unwrap().state.value, 999);
+        assert_eq!(node2.lock().unwrap().state.value, 999);
+        assert_eq!(node3.lock().unwrap().state.value, 999);
+        assert_eq!(
+            node2.lock().unwrap().connections["node3"]
+                .lock()
+                .unwrap()
+                .state
+                .value,
+            999
+        );
     }
 
     #[test]
@@ -407,4 +412,82 @@ mod error_handling_tests {
         reactive.trigger("nonexistent".to_string());
         assert_eq!(reactive.current_state().value, 0);
     }
+
+    fn panicking_store() -> Store<TestState, TestAction> {
+        configure_store(
+            TestState {
+                value: 0,
+                data: vec![],
+            },
+            create_reducer(|state: &TestState, action: &TestAction| match action {
+                TestAction::Increment => TestState {
+                    value: state.value + 1,
+                    data: state.data.clone(),
+                },
+                TestAction::Decrement => panic!("Decrement is not supported"),
+                _ => state.clone(),
+            }),
+        )
+    }
+
+    #[test]
+    fn test_recover_policy_keeps_previous_state() {
+        let store = panicking_store().with_panic_policy(PanicPolicy::Recover);
+
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let errors_clone = Arc::clone(&errors);
+        store.subscribe_errors(move |error| {
+            let panic = error.downcast_ref::<ReducerPanic>().unwrap();
+            errors_clone.lock().unwrap().push(panic.message.clone());
+        });
+
+        store.dispatch(TestAction::Increment);
+        store.dispatch(TestAction::Decrement);
+        assert_eq!(store.get_state().value, 1);
+
+        // The store is still fully usable after the panic
+        store.dispatch(TestAction::Increment);
+        assert_eq!(store.get_state().value, 2);
+        assert_eq!(
+            *errors.lock().unwrap(),
+            vec!["Decrement is not supported".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_recover_policy_isolates_subscriber_panics() {
+        let store = panicking_store().with_panic_policy(PanicPolicy::Recover);
+        let notifications = Arc::new(Mutex::new(0));
+        let notifications_clone = Arc::clone(&notifications);
+
+        store.subscribe(|state: &TestState| {
+            if state.value == 1 {
+                panic!("bad subscriber");
+            }
+        });
+        store.subscribe(move |_| {
+            *notifications_clone.lock().unwrap() += 1;
+        });
+
+        store.dispatch(TestAction::Increment);
+        store.dispatch(TestAction::Increment);
+
+        assert_eq!(*notifications.lock().unwrap(), 2);
+        assert_eq!(store.get_state().value, 2);
+    }
+
+    #[test]
+    fn test_propagate_policy_recovers_poisoned_locks() {
+        let store = panicking_store();
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            store.dispatch(TestAction::Decrement);
+        }));
+        assert!(result.is_err());
+
+        // The reducer lock was poisoned by the panic but is recovered
+        store.dispatch(TestAction::Increment);
+        assert_eq!(store.get_state().value, 1);
+        assert_eq!(store.subscriber_count(), 0);
+    }
 }
@@ -395,19 +395,19 @@ mod examples_tests {
         };
 
         let mut node1 = StateNode::new("node1".to_string(), initial_state.clone());
-        let node2 = StateNode::new("node2".to_string(), initial_state.clone());
-        let node3 = StateNode::new("node3".to_string(), initial_state.clone());
+        let node2 = StateNode::new("node2".to_string(), initial_state.clone()).into_shared();
+        let node3 = StateNode::new("node3".to_string(), initial_state.clone()).into_shared();
 
         // Connect nodes
-        node1.connect(node2);
-        node1.connect(node3);
+        node1.connect(&node2);
+        node1.connect(&node3);
 
-        // For this test, we'll just verify the basic functionality
-        // The actual propagation and state access would need to be implemented
-        // based on the actual StateNode API
+        node1.state.content = "Hello, world".to_string();
+        node1.propagate_update();
 
-        // Test that nodes can be created and connected
         assert_eq!(node1.id, "node1");
+        assert_eq!(node2.lock().unwrap().state.content, "Hello, world");
+        assert_eq!(node3.lock().unwrap().state.content, "Hello, world");
     }
 
     #[test]
@@ -420,19 +420,15 @@ mod examples_tests {
 
         let mut timeline = StateManager::new(
             initial_state,
-            |state: &CounterState, action: &dyn std::any::Any| {
-                if let Some(counter_action) = action.downcast_ref::<CounterAction>() {
-                    let mut new_state = state.clone();
-                    match counter_action {
-                        CounterAction::Increment => new_state.value += 1,
-                        CounterAction::Decrement => new_state.value -= 1,
-                        CounterAction::SetValue(val) => new_state.value = *val,
-                        _ => {}
-                    }
-                    new_state
-                } else {
-                    state.clone()
+            |state: &CounterState, action: &CounterAction| {
+                let mut new_state = state.clone();
+                match action {
+                    CounterAction::Increment => new_state.value += 1,
+                    CounterAction::Decrement => new_state.value -= 1,
+                    CounterAction::SetValue(val) => new_state.value = *val,
+                    _ => {}
                 }
+                new_state
             },
         );
 
@@ -0,0 +1,92 @@
+use std::collections::BTreeSet;
+use zed::{FieldMerge, StateNode};
+
+#[derive(Clone, Debug, PartialEq)]
+struct Profile {
+    name: String,
+    revision: u64,
+    score: u32,
+    badges: BTreeSet<String>,
+    bio: String,
+}
+
+fn profile(name: &str, revision: u64, score: u32, badges: &[&str], bio: &str) -> Profile {
+    Profile {
+        name: name.to_string(),
+        revision,
+        score,
+        badges: badges.iter().map(|badge| badge.to_string()).collect(),
+        bio: bio.to_string(),
+    }
+}
+
+fn profile_resolver() -> impl Fn(&mut Profile, &Profile) + Send + Sync + 'static {
+    FieldMerge::new()
+        .lww(|p: &mut Profile| &mut p.name, |p: &Profile| p.revision)
+        .max(|p: &mut Profile| &mut p.score)
+        .union(|p: &mut Profile| &mut p.badges)
+        .custom(
+            |p: &mut Profile| &mut p.bio,
+            |local: &mut String, remote: &String| {
+                if remote.len() > local.len() {
+                    *local = remote.clone();
+                }
+            },
+        )
+        .max(|p: &mut Profile| &mut p.revision)
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_merge_applies_each_strategy() {
+        let mut node = StateNode::new(
+            "local".to_string(),
+            profile("Ada", 2, 10, &["early"], "Engineer"),
+        );
+        node.set_conflict_resolver(profile_resolver());
+
+        node.resolve_conflict(profile("Ada L.", 3, 7, &["mentor"], "Dev"));
+        assert_eq!(
+            node.state,
+            profile("Ada L.", 3, 10, &["early", "mentor"], "Engineer")
+        );
+
+        // An older remote name loses, other fields still merge
+        node.resolve_conflict(profile("Old", 1, 12, &["early"], "Engineer, writer"));
+        assert_eq!(
+            node.state,
+            profile("Ada L.", 3, 12, &["early", "mentor"], "Engineer, writer")
+        );
+    }
+
+    #[test]
+    fn test_field_merge_converges_regardless_of_order() {
+        let resolve = profile_resolver();
+        let a = profile("A", 1, 4, &["x"], "short");
+        let b = profile("B", 5, 2, &["y"], "a longer bio");
+
+        let mut ab = a.clone();
+        resolve(&mut ab, &b);
+        let mut ba = b.clone();
+        resolve(&mut ba, &a);
+        assert_eq!(ab, ba);
+    }
+
+    #[test]
+    fn test_field_merge_union_keeps_list_order_without_duplicates() {
+        let resolve = FieldMerge::new().union(|list: &mut Vec<u32>| list).build();
+        let mut local = vec![3, 1];
+        resolve(&mut local, &vec![1, 2, 3, 4]);
+        assert_eq!(local, vec![3, 1, 2, 4]);
+
+        // Without strategies, the local state is kept
+        let keep = FieldMerge::new().build();
+        let mut value = 1;
+        keep(&mut value, &2);
+        assert_eq!(value, 1);
+    }
+}
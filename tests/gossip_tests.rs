@@ -0,0 +1,116 @@
+use serde::Serialize;
+use zed::{Gossip, SharedStateNode, StateNode};
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+struct Versioned {
+    version: u32,
+    value: String,
+}
+
+fn versioned_node(id: String) -> SharedStateNode<Versioned> {
+    let mut node = StateNode::new(
+        id,
+        Versioned {
+            version: 0,
+            value: String::new(),
+        },
+    );
+    node.set_conflict_resolver(|current: &mut Versioned, remote: &Versioned| {
+        if remote.version > current.version {
+            *current = remote.clone();
+        }
+    });
+    node.into_shared()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gossip_converges_across_sparse_mesh() {
+        let nodes: Vec<_> = (0..32).map(|i| versioned_node(format!("n{i}"))).collect();
+        let mut gossip = Gossip::new(2);
+        for (i, node) in nodes.iter().enumerate() {
+            // A ring with a few shortcuts
+            StateNode::connect_bidirectional(node, &nodes[(i + 1) % nodes.len()]);
+            if i % 4 == 0 {
+                StateNode::connect_bidirectional(node, &nodes[(i + 16) % nodes.len()]);
+            }
+            gossip.add_node(node);
+        }
+
+        nodes[5].lock().unwrap().state = Versioned {
+            version: 3,
+            value: "latest".to_string(),
+        };
+        assert!(!gossip.is_converged());
+
+        let mut rounds = 0;
+        while !gossip.is_converged() {
+            gossip.round();
+            rounds += 1;
+            assert!(rounds < 200, "gossip did not converge");
+        }
+
+        for node in &nodes {
+            assert_eq!(node.lock().unwrap().state.value, "latest");
+        }
+
+        // Once converged, digests match and nothing is pulled
+        let round = gossip.round();
+        assert!(round.digests_exchanged > 0);
+        assert_eq!(round.states_pulled, 0);
+    }
+
+    #[test]
+    fn test_gossip_contacts_at_most_fanout_peers() {
+        let hub = versioned_node("hub".to_string());
+        let leaves: Vec<_> = (0..10)
+            .map(|i| versioned_node(format!("leaf{i}")))
+            .collect();
+        for leaf in &leaves {
+            hub.lock().unwrap().connect(leaf);
+        }
+        hub.lock().unwrap().state = Versioned {
+            version: 1,
+            value: "news".to_string(),
+        };
+
+        let mut gossip = Gossip::new(3);
+        gossip.add_node(&hub);
+        gossip.add_node(&hub);
+
+        let round = gossip.round();
+        assert_eq!(round.digests_exchanged, 3);
+        assert_eq!(round.states_pulled, 6);
+        let updated = leaves
+            .iter()
+            .filter(|leaf| leaf.lock().unwrap().state.value == "news")
+            .count();
+        assert_eq!(updated, 3);
+
+        assert!(gossip.remove_node(&hub));
+        assert!(!gossip.remove_node(&hub));
+        assert_eq!(gossip.round().digests_exchanged, 0);
+    }
+
+    #[test]
+    fn test_gossip_converges_with_default_resolver() {
+        let a = StateNode::new("a".to_string(), 1).into_shared();
+        let b = StateNode::new("b".to_string(), 2).into_shared();
+        StateNode::connect_bidirectional(&a, &b);
+        let mut gossip = Gossip::new(1);
+        gossip.add_node(&a);
+        gossip.add_node(&b);
+
+        // Both members pick each other; a pull followed by a push of the
+        // pulled state must not swap the states back
+        for _ in 0..3 {
+            gossip.round();
+        }
+
+        assert!(gossip.is_converged());
+        assert_eq!(a.lock().unwrap().state, b.lock().unwrap().state);
+    }
+}
@@ -89,22 +89,18 @@ mod integration_tests {
                 is_connected: true,
                 last_update: None,
             },
-            |state: &SharedAppState, action: &dyn std::any::Any| {
-                if let Some(app_action) = action.downcast_ref::<AppAction>() {
-                    let mut new_state = state.clone();
-                    match app_action {
-                        AppAction::UserJoined(_) => {
-                            new_state.user_count += 1;
-                        }
-                        AppAction::UserLeft(_) => {
-                            new_state.user_count -= 1;
-                        }
-                        _ => {}
+            |state: &SharedAppState, action: &AppAction| {
+                let mut new_state = state.clone();
+                match action {
+                    AppAction::UserJoined(_) => {
+                        new_state.user_count += 1;
                     }
-                    new_state
-                } else {
-                    state.clone()
+                    AppAction::UserLeft(_) => {
+                        new_state.user_count -= 1;
+                    }
+                    _ => {}
                 }
+                new_state
             },
         );
 
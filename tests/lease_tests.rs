@@ -0,0 +1,205 @@
+use std::thread;
+use std::time::Duration;
+use zed::{LeaseError, LeasePolicy, SharedStateNode, StateNode};
+
+const LONG: Duration = Duration::from_secs(60);
+
+/// Names the paragraphs that differ between two documents.
+fn changed_paragraphs(a: &[String], b: &[String]) -> Vec<String> {
+    (0..a.len().max(b.len()))
+        .filter(|&i| a.get(i) != b.get(i))
+        .map(|i| format!("p{i}"))
+        .collect()
+}
+
+fn editor(id: &str) -> SharedStateNode<Vec<String>> {
+    let mut node = StateNode::new(
+        id.to_string(),
+        vec!["intro".to_string(), "body".to_string()],
+    );
+    node.set_regions(|a: &Vec<String>, b: &Vec<String>| changed_paragraphs(a, b));
+    node.into_shared()
+}
+
+/// Edits a paragraph of a node's document and propagates it.
+fn edit(node: &SharedStateNode<Vec<String>>, paragraph: usize, text: &str) {
+    let mut node = node.lock().unwrap();
+    node.state[paragraph] = text.to_string();
+    node.propagate_update();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lease_is_exclusive() {
+        let alice = editor("alice");
+        let bob = editor("bob");
+        StateNode::connect_bidirectional(&alice, &bob);
+
+        alice.lock().unwrap().acquire_lease("p0", LONG).unwrap();
+        let err = bob.lock().unwrap().acquire_lease("p0", LONG).unwrap_err();
+
+        assert_eq!(
+            err,
+            LeaseError {
+                region: "p0".to_string(),
+                holder: "alice".to_string(),
+            }
+        );
+        assert_eq!(err.to_string(), "region \"p0\" is leased by alice");
+        assert_eq!(bob.lock().unwrap().lease("p0").unwrap().holder, "alice");
+        assert!(bob.lock().unwrap().acquire_lease("p1", LONG).is_ok());
+    }
+
+    #[test]
+    fn test_holder_can_renew_and_release() {
+        let alice = editor("alice");
+        let bob = editor("bob");
+        StateNode::connect_bidirectional(&alice, &bob);
+        alice
+            .lock()
+            .unwrap()
+            .acquire_lease("p0", Duration::from_secs(1))
+            .unwrap();
+
+        alice.lock().unwrap().acquire_lease("p0", LONG).unwrap();
+        let expires_at = alice.lock().unwrap().lease("p0").unwrap().expires_at;
+        assert_eq!(
+            bob.lock().unwrap().lease("p0").unwrap().expires_at,
+            expires_at
+        );
+
+        assert!(!bob.lock().unwrap().release_lease("p0"));
+        assert!(alice.lock().unwrap().release_lease("p0"));
+        assert!(bob.lock().unwrap().lease("p0").is_none());
+        assert!(bob.lock().unwrap().acquire_lease("p0", LONG).is_ok());
+    }
+
+    #[test]
+    fn test_leased_changes_are_rejected() {
+        let alice = editor("alice");
+        let bob = editor("bob");
+        StateNode::connect_bidirectional(&alice, &bob);
+        alice.lock().unwrap().acquire_lease("p0", LONG).unwrap();
+
+        edit(&alice, 0, "alice's intro");
+        assert_eq!(bob.lock().unwrap().state[0], "alice's intro");
+
+        edit(&bob, 0, "bob's intro");
+        assert_eq!(alice.lock().unwrap().state[0], "alice's intro");
+        assert_eq!(alice.lock().unwrap().deferred_updates(), 0);
+
+        // Changes outside the lease still go through
+        bob.lock().unwrap().state[0] = "alice's intro".to_string();
+        edit(&bob, 1, "bob's body");
+        assert_eq!(alice.lock().unwrap().state[1], "bob's body");
+    }
+
+    #[test]
+    fn test_third_nodes_only_accept_the_holder() {
+        let alice = editor("alice");
+        let bob = editor("bob");
+        let carol = editor("carol");
+        for node in [&alice, &bob] {
+            StateNode::connect_bidirectional(node, &carol);
+        }
+        alice.lock().unwrap().acquire_lease("p0", LONG).unwrap();
+
+        edit(&bob, 0, "bob's intro");
+        assert_eq!(carol.lock().unwrap().state[0], "intro");
+
+        edit(&alice, 0, "alice's intro");
+        assert_eq!(carol.lock().unwrap().state[0], "alice's intro");
+    }
+
+    #[test]
+    fn test_deferred_changes_apply_on_release() {
+        let alice = editor("alice");
+        let bob = editor("bob");
+        StateNode::connect_bidirectional(&alice, &bob);
+        alice.lock().unwrap().set_lease_policy(LeasePolicy::Defer);
+        alice.lock().unwrap().acquire_lease("p0", LONG).unwrap();
+
+        edit(&bob, 0, "first");
+        edit(&bob, 0, "second");
+        assert_eq!(alice.lock().unwrap().state[0], "intro");
+        assert_eq!(alice.lock().unwrap().deferred_updates(), 1);
+
+        alice.lock().unwrap().release_lease("p0");
+        assert_eq!(alice.lock().unwrap().state[0], "second");
+        assert_eq!(alice.lock().unwrap().deferred_updates(), 0);
+    }
+
+    #[test]
+    fn test_deferred_changes_apply_on_expiry() {
+        let alice = editor("alice");
+        let bob = editor("bob");
+        StateNode::connect_bidirectional(&alice, &bob);
+        alice.lock().unwrap().set_lease_policy(LeasePolicy::Defer);
+        alice
+            .lock()
+            .unwrap()
+            .acquire_lease("p0", Duration::from_millis(20))
+            .unwrap();
+
+        edit(&bob, 0, "bob's intro");
+        assert_eq!(alice.lock().unwrap().state[0], "intro");
+
+        thread::sleep(Duration::from_millis(40));
+        assert!(alice.lock().unwrap().lease("p0").is_none());
+        assert_eq!(alice.lock().unwrap().expire_leases(), 1);
+        assert_eq!(alice.lock().unwrap().state[0], "bob's intro");
+    }
+
+    #[test]
+    fn test_refused_lease_is_withdrawn_everywhere() {
+        let alice = editor("alice");
+        let bob = editor("bob");
+        let carol = editor("carol");
+        // Carol leased the region before joining, so only she knows
+        carol.lock().unwrap().acquire_lease("p0", LONG).unwrap();
+        for node in [&bob, &carol] {
+            StateNode::connect_bidirectional(&alice, node);
+        }
+
+        let err = alice.lock().unwrap().acquire_lease("p0", LONG).unwrap_err();
+
+        assert_eq!(err.holder, "carol");
+        assert!(alice.lock().unwrap().lease("p0").is_none());
+        assert!(bob.lock().unwrap().lease("p0").is_none());
+        assert_eq!(carol.lock().unwrap().lease("p0").unwrap().holder, "carol");
+    }
+
+    #[test]
+    fn test_leases_without_regions_hold_nothing_back() {
+        let alice = StateNode::new("alice".to_string(), 1).into_shared();
+        let bob = StateNode::new("bob".to_string(), 1).into_shared();
+        StateNode::connect_bidirectional(&alice, &bob);
+        alice.lock().unwrap().acquire_lease("all", LONG).unwrap();
+
+        bob.lock().unwrap().state = 2;
+        bob.lock().unwrap().propagate_update();
+
+        assert_eq!(alice.lock().unwrap().state, 2);
+    }
+
+    #[test]
+    fn test_merge_checks_the_sender() {
+        let alice = editor("alice");
+        let mut bob = StateNode::new(
+            "bob".to_string(),
+            vec!["bob's intro".to_string(), "body".to_string()],
+        );
+        alice.lock().unwrap().acquire_lease("p0", LONG).unwrap();
+
+        alice.lock().unwrap().merge(&bob);
+        assert_eq!(alice.lock().unwrap().state[0], "intro");
+
+        // A direct resolution has no sender to check
+        bob.state[0] = "resolved".to_string();
+        alice.lock().unwrap().resolve_conflict(bob.state.clone());
+        assert_eq!(alice.lock().unwrap().state[0], "resolved");
+    }
+}
@@ -0,0 +1,165 @@
+use std::collections::BTreeMap;
+use zed::{MerkleTree, StateNode};
+
+fn rows(count: u32) -> BTreeMap<u32, String> {
+    (0..count).map(|i| (i, format!("row {i}"))).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_equal_states_have_equal_roots() {
+        let a = MerkleTree::new(&rows(1000));
+        let b = MerkleTree::new(&rows(1000));
+
+        assert_eq!(a.root(), b.root());
+        assert!(a.diff(&b).is_empty());
+    }
+
+    #[test]
+    fn test_root_changes_with_any_entry() {
+        let base = MerkleTree::new(&rows(1000)).root();
+
+        let mut changed = rows(1000);
+        changed.insert(999, "edited".to_string());
+        assert_ne!(MerkleTree::new(&changed).root(), base);
+
+        let mut removed = rows(1000);
+        removed.remove(&0);
+        assert_ne!(MerkleTree::new(&removed).root(), base);
+    }
+
+    #[test]
+    fn test_empty_trees() {
+        let empty: BTreeMap<u32, String> = BTreeMap::new();
+        let tree = MerkleTree::new(&empty);
+
+        assert_eq!(tree.root(), 0);
+        assert!(tree.diff(&MerkleTree::new(&empty)).is_empty());
+        assert_eq!(tree.diff(&MerkleTree::new(&rows(3))), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_diff_finds_changed_added_and_removed_keys() {
+        let ours = rows(50_000);
+        let mut theirs = ours.clone();
+        theirs.insert(123, "changed".to_string());
+        theirs.insert(60_000, "added".to_string());
+        theirs.remove(&49_999);
+
+        let diff = MerkleTree::new(&ours).diff(&MerkleTree::new(&theirs));
+
+        assert_eq!(diff, vec![123, 49_999, 60_000]);
+    }
+
+    #[test]
+    fn test_diff_is_symmetric() {
+        let ours = rows(100);
+        let mut theirs = rows(120);
+        theirs.insert(7, "changed".to_string());
+
+        let a = MerkleTree::new(&ours);
+        let b = MerkleTree::new(&theirs);
+
+        assert_eq!(a.diff(&b), b.diff(&a));
+        assert_eq!(a.diff(&b).len(), 21);
+    }
+
+    #[test]
+    fn test_sync_from_copies_differing_entries() {
+        let mut replica = StateNode::new("replica".to_string(), rows(10_000));
+        let primary = StateNode::new("primary".to_string(), rows(10_000)).into_shared();
+        {
+            let mut primary = primary.lock().unwrap();
+            primary.state.insert(5, "edited".to_string());
+            primary.state.remove(&6);
+            primary.state.insert(10_000, "new".to_string());
+        }
+
+        assert_eq!(replica.sync_from(&primary), 3);
+        assert_eq!(replica.state, primary.lock().unwrap().state);
+        assert_eq!(replica.sync_from(&primary), 0);
+    }
+
+    #[test]
+    fn test_sync_from_applies_conflict_resolver() {
+        let mut replica = StateNode::new("replica".to_string(), rows(100));
+        replica.state.insert(1, "local".to_string());
+        replica.set_conflict_resolver(|local: &mut BTreeMap<u32, String>, remote| {
+            for (key, value) in remote {
+                local.entry(*key).or_insert_with(|| value.clone());
+            }
+        });
+        let primary = StateNode::new("primary".to_string(), rows(100)).into_shared();
+        primary
+            .lock()
+            .unwrap()
+            .state
+            .insert(200, "remote".to_string());
+
+        assert_eq!(replica.sync_from(&primary), 2);
+        assert_eq!(replica.state[&1], "local");
+        assert_eq!(replica.state[&200], "remote");
+    }
+
+    #[test]
+    fn test_sync_from_uses_peer_authority() {
+        let mut replica = StateNode::new("replica".to_string(), rows(10));
+        replica.state.insert(0, "local".to_string());
+        replica.set_authority_resolver(|state: &BTreeMap<u32, String>| state.len());
+        let primary = StateNode::new("primary".to_string(), rows(10)).into_shared();
+        primary.lock().unwrap().set_authority(1);
+
+        replica.sync_from(&primary);
+        assert_eq!(replica.state[&0], "row 0");
+
+        replica.state.insert(0, "local".to_string());
+        replica.set_authority(5);
+        replica.sync_from(&primary);
+        assert_eq!(replica.state[&0], "local");
+    }
+
+    #[test]
+    fn test_insert_and_remove_match_a_rebuilt_tree() {
+        let mut state = rows(1000);
+        let mut tree = MerkleTree::new(&state);
+
+        state.insert(3, "edited".to_string());
+        tree.insert(&3, &"edited".to_string());
+        state.insert(5000, "new".to_string());
+        tree.insert(&5000, &"new".to_string());
+        state.remove(&10);
+        assert!(tree.remove(&10));
+        assert!(!tree.remove(&10));
+
+        assert_eq!(tree, MerkleTree::new(&state));
+    }
+
+    #[test]
+    fn test_tree_round_trips_through_json() {
+        let tree = MerkleTree::new(&rows(500));
+
+        let json = serde_json::to_string(&tree).unwrap();
+        let decoded: MerkleTree<u32> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(decoded, tree);
+        assert!(serde_json::from_str::<MerkleTree<u32>>(r#"{"buckets":[]}"#).is_err());
+    }
+
+    #[test]
+    fn test_node_tree_follows_state_changes() {
+        let mut node = StateNode::new("node".to_string(), rows(1000));
+        let root = node.merkle_tree().root();
+
+        node.state.insert(1, "edited".to_string());
+        node.state.remove(&2);
+        let rebuilt = MerkleTree::new(&node.state);
+        assert_eq!(*node.merkle_tree(), rebuilt);
+        assert_ne!(node.merkle_tree().root(), root);
+
+        node.state = rows(1000);
+        assert_eq!(node.merkle_tree().root(), root);
+    }
+}
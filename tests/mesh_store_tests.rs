@@ -0,0 +1,102 @@
+use std::sync::{Arc, Mutex};
+use zed::{MeshStore, StateNode, Store, create_reducer};
+
+#[derive(Clone, Debug, PartialEq)]
+struct Board {
+    version: u32,
+    cards: Vec<String>,
+}
+
+#[derive(Clone)]
+enum BoardAction {
+    Add(String),
+}
+
+fn board_store() -> Store<Board, BoardAction> {
+    Store::new(
+        Board {
+            version: 0,
+            cards: Vec::new(),
+        },
+        Box::new(create_reducer(
+            |state: &Board, action: &BoardAction| match action {
+                BoardAction::Add(card) => {
+                    let mut cards = state.cards.clone();
+                    cards.push(card.clone());
+                    Board {
+                        version: state.version + 1,
+                        cards,
+                    }
+                }
+            },
+        )),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mesh_store_propagates_dispatches_to_peers() {
+        let alice = MeshStore::new("alice".to_string(), board_store());
+        let bob = MeshStore::new("bob".to_string(), board_store());
+        StateNode::connect_bidirectional(alice.node(), bob.node());
+
+        let rendered = Arc::new(Mutex::new(Vec::new()));
+        let view = Arc::clone(&rendered);
+        bob.store()
+            .subscribe(move |board: &Board| view.lock().unwrap().push(board.cards.len()));
+
+        alice
+            .store()
+            .dispatch(BoardAction::Add("design".to_string()));
+        bob.store().dispatch(BoardAction::Add("build".to_string()));
+
+        // Without a resolver, the last propagated state wins
+        let expected = vec!["design".to_string(), "build".to_string()];
+        assert_eq!(alice.store().get_state().cards, expected);
+        assert_eq!(bob.store().get_state().cards, expected);
+        assert_eq!(alice.node().lock().unwrap().state.cards, expected);
+        assert_eq!(*rendered.lock().unwrap(), vec![1, 2]);
+
+        alice
+            .node()
+            .lock()
+            .unwrap()
+            .remove_connection(&"bob".to_string());
+    }
+
+    #[test]
+    fn test_mesh_store_applies_remote_states_through_resolver() {
+        let local = MeshStore::new("local".to_string(), board_store());
+        local.node().lock().unwrap().set_conflict_resolver(
+            |current: &mut Board, remote: &Board| {
+                if remote.version > current.version {
+                    *current = remote.clone();
+                }
+            },
+        );
+        local.store().dispatch(BoardAction::Add("a".to_string()));
+        local.store().dispatch(BoardAction::Add("b".to_string()));
+
+        let mut remote = StateNode::new(
+            "remote".to_string(),
+            Board {
+                version: 1,
+                cards: vec!["stale".to_string()],
+            },
+        );
+        remote.connect(local.node());
+        remote.propagate_update();
+        assert_eq!(local.store().get_state().cards, vec!["a", "b"]);
+
+        remote.state = Board {
+            version: 5,
+            cards: vec!["newer".to_string()],
+        };
+        remote.propagate_update();
+        assert_eq!(local.store().get_state().cards, vec!["newer"]);
+        assert_eq!(local.store().get_state().version, 5);
+    }
+}
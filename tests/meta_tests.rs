@@ -0,0 +1,92 @@
+use std::sync::{Arc, Mutex};
+use zed::*;
+
+#[derive(Clone, Debug, PartialEq)]
+struct CartState {
+    items: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum CartAction {
+    Add(String),
+    Checkout,
+}
+
+fn cart_reducer(state: &CartState, action: &CartAction) -> Result<CartState, String> {
+    match action {
+        CartAction::Add(item) => {
+            let mut items = state.items.clone();
+            items.push(item.clone());
+            Ok(CartState { items })
+        }
+        CartAction::Checkout if state.items.is_empty() => Err("empty cart".to_string()),
+        CartAction::Checkout => Ok(CartState { items: vec![] }),
+    }
+}
+
+fn cart_store() -> Store<CartState, WithMeta<CartAction>> {
+    configure_store(
+        CartState { items: vec![] },
+        with_meta(create_try_reducer(cart_reducer)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_listeners_receive_action_metadata() {
+        let store = cart_store();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&seen);
+        store.add_listener(
+            |_| true,
+            move |action: &WithMeta<CartAction>, _: &Store<CartState, WithMeta<CartAction>>| {
+                log.lock().unwrap().push(action.clone());
+            },
+        );
+
+        let meta = ActionMeta::new()
+            .with_source("checkout-page")
+            .with_correlation_id("order-7");
+        store.dispatch(WithMeta::with(
+            CartAction::Add("book".to_string()),
+            meta.clone(),
+        ));
+        store.dispatch(CartAction::Add("pen".to_string()).into());
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(store.get_state().items, vec!["book", "pen"]);
+        assert_eq!(seen[0].meta, meta);
+        assert_eq!(seen[1].meta.source, None);
+        assert!(seen[1].meta.id > seen[0].meta.id);
+        assert_eq!(*seen[1], CartAction::Add("pen".to_string()));
+    }
+
+    #[test]
+    fn test_wrapped_reducer_keeps_errors() {
+        let store = cart_store();
+
+        let result = store.dispatch_try(WithMeta::new(CartAction::Checkout));
+
+        assert_eq!(result.unwrap_err().to_string(), "empty cart");
+        assert!(store.get_state().items.is_empty());
+    }
+
+    #[test]
+    fn test_follow_up_metadata_is_correlated() {
+        let first = ActionMeta::new().with_source("sync");
+        let second = first.follow_up();
+        let third = second.follow_up();
+
+        assert_ne!(second.id, first.id);
+        assert_eq!(second.source.as_deref(), Some("sync"));
+        assert_eq!(second.correlation_id, Some(first.id.to_string()));
+        assert_eq!(third.correlation_id, second.correlation_id);
+
+        let json = serde_json::to_string(&first).unwrap();
+        let restored: ActionMeta = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, first);
+    }
+}
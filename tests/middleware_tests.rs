@@ -0,0 +1,243 @@
+use std::sync::{Arc, Mutex};
+use std::thread;
+use zed::*;
+
+#[derive(Clone, Debug, PartialEq)]
+struct CounterState {
+    count: i32,
+    label: String,
+}
+
+#[derive(Clone, Debug)]
+enum CounterAction {
+    Add(i32),
+    Rename(String),
+}
+
+fn counter_reducer(state: &CounterState, action: &CounterAction) -> CounterState {
+    match action {
+        CounterAction::Add(n) => CounterState {
+            count: state.count + n,
+            label: state.label.clone(),
+        },
+        CounterAction::Rename(label) => CounterState {
+            count: state.count,
+            label: label.clone(),
+        },
+    }
+}
+
+fn initial_state() -> CounterState {
+    CounterState {
+        count: 0,
+        label: "counter".to_string(),
+    }
+}
+
+fn recording_logger(verbosity: LogVerbosity) -> (LoggerMiddleware, Arc<Mutex<Vec<String>>>) {
+    let entries = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&entries);
+    let logger =
+        LoggerMiddleware::new(move |entry: &str| sink.lock().unwrap().push(entry.to_string()))
+            .verbosity(verbosity);
+    (logger, entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_middleware_wraps_dispatch() {
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let order_clone = Arc::clone(&order);
+        let tracing = move |store: &Store<CounterState, CounterAction>,
+                            action: CounterAction,
+                            next: Next<'_, CounterAction>| {
+            order_clone
+                .lock()
+                .unwrap()
+                .push(format!("before {}", store.get_state().count));
+            let result = next(action);
+            order_clone
+                .lock()
+                .unwrap()
+                .push(format!("after {}", store.get_state().count));
+            result
+        };
+
+        let store = configure_store_with_enhancer(
+            initial_state(),
+            create_reducer(counter_reducer),
+            apply_middleware(tracing),
+        );
+
+        store.dispatch(CounterAction::Add(3));
+        assert_eq!(store.get_state().count, 3);
+        assert_eq!(*order.lock().unwrap(), vec!["before 0", "after 3"]);
+    }
+
+    #[test]
+    fn test_middleware_keeps_reducer_errors() {
+        let store = configure_store_with_enhancer(
+            initial_state(),
+            create_try_reducer(
+                |state: &CounterState, action: &CounterAction| match action {
+                    CounterAction::Add(n) if *n < 0 => Err("negative amount"),
+                    _ => Ok(counter_reducer(state, action)),
+                },
+            ),
+            apply_middleware(recording_logger(LogVerbosity::Minimal).0),
+        );
+
+        assert!(store.dispatch_try(CounterAction::Add(-1)).is_err());
+        assert!(store.dispatch_try(CounterAction::Add(2)).is_ok());
+        assert_eq!(store.get_state().count, 2);
+    }
+
+    #[test]
+    fn test_logger_verbosity_levels() {
+        let (minimal, minimal_entries) = recording_logger(LogVerbosity::Minimal);
+        let (normal, normal_entries) = recording_logger(LogVerbosity::Normal);
+
+        let store = configure_store_with_enhancer(
+            initial_state(),
+            create_reducer(counter_reducer),
+            StoreEnhancer::<CounterState, CounterAction>::and_then(
+                apply_middleware(minimal),
+                apply_middleware(normal),
+            ),
+        );
+
+        store.dispatch(CounterAction::Add(1));
+
+        assert_eq!(*minimal_entries.lock().unwrap(), vec!["action Add"]);
+        let normal_entries = normal_entries.lock().unwrap();
+        assert!(normal_entries[0].starts_with("action Add ("));
+        assert!(normal_entries[0].ends_with(')'));
+    }
+
+    #[test]
+    fn test_logger_verbose_state_diff() {
+        let (logger, entries) = recording_logger(LogVerbosity::Verbose);
+        let store = configure_store_with_enhancer(
+            initial_state(),
+            create_reducer(counter_reducer),
+            apply_middleware(logger),
+        );
+
+        store.dispatch(CounterAction::Rename("total".to_string()));
+        store.dispatch(CounterAction::Add(0));
+
+        let entries = entries.lock().unwrap();
+        let renamed: Vec<&str> = entries[0].lines().skip(1).collect();
+        assert_eq!(
+            renamed,
+            vec!["  - label: \"counter\",", "  + label: \"total\","]
+        );
+        assert!(entries[1].ends_with("(state unchanged)"));
+    }
+
+    #[test]
+    fn test_middleware_can_dispatch_before_the_reducer_runs() {
+        // Every rename is preceded by a reset of the count
+        let reset = |store: &Store<CounterState, CounterAction>,
+                     action: CounterAction,
+                     next: Next<'_, CounterAction>| {
+            if let CounterAction::Rename(_) = action {
+                let count = store.get_state().count;
+                store.dispatch(CounterAction::Add(-count));
+            }
+            next(action)
+        };
+
+        let store = configure_store_with_enhancer(
+            initial_state(),
+            create_reducer(counter_reducer),
+            apply_middleware(reset),
+        );
+
+        store.dispatch(CounterAction::Add(5));
+        store.dispatch(CounterAction::Rename("fresh".to_string()));
+
+        assert_eq!(
+            store.get_state(),
+            CounterState {
+                count: 0,
+                label: "fresh".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_middleware_is_not_rerun_when_optimistic_actions_are_replayed() {
+        let (logger, entries) = recording_logger(LogVerbosity::Minimal);
+        let store = configure_store_with_enhancer(
+            initial_state(),
+            create_reducer(counter_reducer),
+            apply_middleware(logger),
+        );
+
+        let pending = store.dispatch_optimistic(CounterAction::Add(10));
+        store.dispatch(CounterAction::Add(1));
+        pending.revert();
+
+        assert_eq!(store.get_state().count, 1);
+        assert_eq!(*entries.lock().unwrap(), vec!["action Add"]);
+    }
+
+    #[test]
+    fn test_logger_verbose_entries_show_the_whole_action() {
+        let (logger, entries) = recording_logger(LogVerbosity::Verbose);
+        let store = configure_store_with_enhancer(
+            initial_state(),
+            create_reducer(counter_reducer),
+            apply_middleware(logger),
+        );
+
+        store.dispatch(CounterAction::Add(2));
+
+        let entries = entries.lock().unwrap();
+        assert!(entries[0].starts_with("action Add(2) ("));
+    }
+
+    #[test]
+    fn test_logger_diffs_only_its_own_action_under_concurrency() {
+        let (logger, entries) = recording_logger(LogVerbosity::Verbose);
+        let store = configure_store_with_enhancer(
+            initial_state(),
+            create_reducer(counter_reducer),
+            apply_middleware(logger),
+        );
+
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let store = store.clone();
+                thread::spawn(move || {
+                    for _ in 0..100 {
+                        store.dispatch(CounterAction::Add(1));
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+
+        let entries = entries.lock().unwrap();
+        assert_eq!(entries.len(), 400);
+        for entry in entries.iter() {
+            let counts: Vec<i32> = entry
+                .lines()
+                .skip(1)
+                .map(|line| {
+                    let value = line.trim_start_matches(['-', '+', ' ']);
+                    let value = value.trim_start_matches("count: ").trim_end_matches(',');
+                    value.parse().unwrap()
+                })
+                .collect();
+            assert_eq!(counts.len(), 2);
+            assert_eq!(counts[1], counts[0] + 1);
+        }
+    }
+}
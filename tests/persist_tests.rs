@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use zed::*;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Settings {
+    volume: u8,
+    theme: String,
+}
+
+#[derive(Clone, Debug)]
+enum SettingsAction {
+    SetVolume(u8),
+    SetTheme(String),
+}
+
+fn settings_reducer(state: &Settings, action: &SettingsAction) -> Settings {
+    match action {
+        SettingsAction::SetVolume(volume) => Settings {
+            volume: *volume,
+            theme: state.theme.clone(),
+        },
+        SettingsAction::SetTheme(theme) => Settings {
+            volume: state.volume,
+            theme: theme.clone(),
+        },
+    }
+}
+
+fn default_settings() -> Settings {
+    Settings {
+        volume: 5,
+        theme: "light".to_string(),
+    }
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("zed-{}-{name}.json", std::process::id()));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_persisted_store_saves_on_change() {
+        let path = temp_path("saves-on-change");
+        let store = Store::new(
+            default_settings(),
+            Box::new(create_reducer(settings_reducer)),
+        );
+        let persisted = PersistedStore::new(store, &path);
+        assert!(!path.exists());
+
+        persisted.dispatch(SettingsAction::SetTheme("dark".to_string()));
+
+        let saved: Settings = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.theme, "dark");
+        assert!(persisted.take_error().is_none());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_configure_store_persisted_hydrates() {
+        let path = temp_path("hydrates");
+
+        let first =
+            configure_store_persisted(&path, default_settings(), create_reducer(settings_reducer))
+                .unwrap();
+        assert_eq!(first.get_state(), default_settings());
+        first.dispatch(SettingsAction::SetVolume(9));
+        drop(first);
+
+        let second =
+            configure_store_persisted(&path, default_settings(), create_reducer(settings_reducer))
+                .unwrap();
+        assert_eq!(second.get_state().volume, 9);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_configure_store_persisted_rejects_corrupt_file() {
+        let path = temp_path("corrupt");
+        fs::write(&path, "not json").unwrap();
+
+        let result =
+            configure_store_persisted(&path, default_settings(), create_reducer(settings_reducer));
+        assert!(matches!(result, Err(PersistError::Serde(_))));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_configure_store_with_storage() {
+        let storage = MemoryStorage::with_state(Settings {
+            volume: 2,
+            theme: "dark".to_string(),
+        });
+
+        let store = configure_store_with_storage(
+            storage.clone(),
+            default_settings(),
+            create_reducer(settings_reducer),
+        )
+        .unwrap();
+        assert_eq!(store.get_state().theme, "dark");
+
+        store.dispatch(SettingsAction::SetVolume(7));
+        assert_eq!(storage.load().unwrap().unwrap().volume, 7);
+
+        store.clear().unwrap();
+        assert!(storage.load().unwrap().is_none());
+    }
+}
@@ -0,0 +1,87 @@
+use std::sync::{Arc, Mutex};
+use zed::*;
+
+#[derive(Clone, Debug, PartialEq)]
+struct AppState {
+    items: Vec<String>,
+    theme: String,
+}
+
+#[derive(Clone, Debug)]
+enum AppAction {
+    Add(String),
+    SetTheme(String),
+}
+
+fn app_reducer(state: &AppState, action: &AppAction) -> AppState {
+    let mut next = state.clone();
+    match action {
+        AppAction::Add(item) => next.items.push(item.clone()),
+        AppAction::SetTheme(theme) => next.theme = theme.clone(),
+    }
+    next
+}
+
+fn create_store() -> Store<AppState, AppAction> {
+    configure_store(
+        AppState {
+            items: vec![],
+            theme: "light".to_string(),
+        },
+        create_reducer(app_reducer),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_projection_follows_parent_state() {
+        let store = create_store();
+        let count = store.map(|state: &AppState| state.items.len());
+        assert_eq!(count.get_state(), 0);
+
+        store.dispatch(AppAction::Add("apple".to_string()));
+        store.dispatch(AppAction::Add("pear".to_string()));
+
+        assert_eq!(count.get_state(), 2);
+        assert_eq!(*count.get_state_shared(), 2);
+        assert_eq!(count.with_state(|count| count * 10), 20);
+    }
+
+    #[test]
+    fn test_projection_notifies_only_on_change() {
+        let store = create_store();
+        let count = store.map(|state: &AppState| state.items.len());
+        let seen = Arc::new(Mutex::new(Vec::new()));
+
+        let values = Arc::clone(&seen);
+        let id = count.subscribe(move |count: &usize| values.lock().unwrap().push(*count));
+
+        store.dispatch(AppAction::Add("apple".to_string()));
+        store.dispatch(AppAction::SetTheme("dark".to_string()));
+        store.dispatch(AppAction::Add("pear".to_string()));
+        assert_eq!(*seen.lock().unwrap(), vec![1, 2]);
+
+        assert!(count.unsubscribe(id));
+        assert_eq!(count.subscriber_count(), 0);
+        store.dispatch(AppAction::Add("plum".to_string()));
+        assert_eq!(seen.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_projection_detaches_when_last_clone_is_dropped() {
+        let store = create_store();
+        let theme = store.map(|state: &AppState| state.theme.clone());
+        let clone = theme.clone();
+        assert_eq!(store.subscriber_count(), 1);
+
+        drop(theme);
+        store.dispatch(AppAction::SetTheme("dark".to_string()));
+        assert_eq!(clone.get_state(), "dark");
+
+        drop(clone);
+        assert_eq!(store.subscriber_count(), 0);
+    }
+}
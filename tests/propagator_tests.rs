@@ -0,0 +1,165 @@
+#![cfg(feature = "async")]
+
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread;
+use std::time::Duration;
+use zed::{AsyncPropagator, SharedStateNode, StateNode};
+
+fn node(id: &str, value: u32) -> SharedStateNode<u32> {
+    let mut node = StateNode::new(id.to_string(), value);
+    node.set_conflict_resolver(|local: &mut u32, remote: &u32| *local = *remote);
+    node.into_shared()
+}
+
+/// Waits until a node holds `value`, failing after a second.
+async fn wait_for(node: &SharedStateNode<u32>, value: u32) {
+    tokio::time::timeout(Duration::from_secs(1), async {
+        while node.lock().unwrap().state != value {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+    })
+    .await
+    .expect("state was not delivered");
+}
+
+/// Keeps a node locked on another thread, like a slow peer, until the
+/// returned sender is dropped.
+fn hold_lock(node: &SharedStateNode<u32>) -> mpsc::Sender<()> {
+    let (release, released) = mpsc::channel::<()>();
+    let (locked, is_locked) = mpsc::channel();
+    let node = Arc::clone(node);
+    thread::spawn(move || {
+        let _guard = node.lock().unwrap();
+        locked.send(()).unwrap();
+        let _ = released.recv();
+    });
+    is_locked.recv().unwrap();
+    release
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_propagate_update_delivers_to_every_peer() {
+        let source = node("source", 7);
+        let peers: Vec<_> = (0..3).map(|i| node(&format!("peer{i}"), 0)).collect();
+        for peer in &peers {
+            source.lock().unwrap().connect(peer);
+        }
+
+        let propagator = AsyncPropagator::new(&source, 4);
+        assert_eq!(propagator.propagate_update().await, 3);
+        propagator.flush().await;
+
+        assert!(peers.iter().all(|peer| peer.lock().unwrap().state == 7));
+        assert_eq!(propagator.pending(&"peer0".to_string()), 0);
+        assert_eq!(source.lock().unwrap().metrics().updates_sent, 3);
+    }
+
+    #[tokio::test]
+    async fn test_updates_are_delivered_in_order() {
+        let source = node("source", 0);
+        let peer = node("peer", 0);
+        let applied = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&applied);
+        peer.lock().unwrap().subscribe(move |state: &u32| {
+            log.lock().unwrap().push(*state);
+        });
+        source.lock().unwrap().connect(&peer);
+
+        let propagator = AsyncPropagator::new(&source, 2);
+        for value in 1..=20 {
+            source.lock().unwrap().state = value;
+            propagator.propagate_update().await;
+        }
+        propagator.flush().await;
+
+        assert_eq!(*applied.lock().unwrap(), (1..=20).collect::<Vec<u32>>());
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_suspends_the_producer() {
+        let source = node("source", 1);
+        let peer = node("peer", 0);
+        source.lock().unwrap().connect(&peer);
+        let propagator = AsyncPropagator::new(&source, 1);
+
+        // One state is taken by the delivery thread, blocked on the peer,
+        // and one fills the queue; the next producer has to wait
+        let busy = hold_lock(&peer);
+        let mut accepted = 0;
+        while tokio::time::timeout(Duration::from_millis(50), propagator.propagate_update())
+            .await
+            .is_ok()
+        {
+            accepted += 1;
+        }
+        assert_eq!(accepted, 2);
+        assert_eq!(propagator.pending(&"peer".to_string()), 2);
+
+        drop(busy);
+        source.lock().unwrap().state = 3;
+        assert_eq!(propagator.propagate_update().await, 1);
+        propagator.flush().await;
+        assert_eq!(peer.lock().unwrap().state, 3);
+        assert_eq!(propagator.pending(&"peer".to_string()), 0);
+    }
+
+    #[tokio::test]
+    async fn test_slow_peer_does_not_delay_others() {
+        let source = node("source", 5);
+        let slow = node("slow", 0);
+        let fast = node("fast", 0);
+        source.lock().unwrap().connect(&slow);
+        source.lock().unwrap().connect(&fast);
+        let propagator = AsyncPropagator::new(&source, 4);
+
+        let busy = hold_lock(&slow);
+        assert_eq!(propagator.propagate_update().await, 2);
+        wait_for(&fast, 5).await;
+        assert_eq!(propagator.pending(&"slow".to_string()), 1);
+
+        drop(busy);
+        propagator.flush().await;
+        assert_eq!(slow.lock().unwrap().state, 5);
+    }
+
+    #[tokio::test]
+    async fn test_offline_peers_queue_in_the_outbox() {
+        let source = node("source", 9);
+        let peer = node("peer", 0);
+        source.lock().unwrap().connect(&peer);
+        source.lock().unwrap().mark_offline(&"peer".to_string());
+
+        let propagator = AsyncPropagator::new(&source, 4);
+        assert_eq!(propagator.propagate_update().await, 0);
+        assert_eq!(
+            source.lock().unwrap().pending_updates(&"peer".to_string()),
+            1
+        );
+
+        source.lock().unwrap().mark_online(&"peer".to_string());
+        assert_eq!(peer.lock().unwrap().state, 9);
+    }
+
+    #[tokio::test]
+    async fn test_removed_connections_stop_receiving() {
+        let source = node("source", 1);
+        let peer = node("peer", 0);
+        source.lock().unwrap().connect(&peer);
+        let propagator = AsyncPropagator::new(&source, 4);
+        propagator.propagate_update().await;
+        propagator.flush().await;
+
+        source
+            .lock()
+            .unwrap()
+            .remove_connection(&"peer".to_string());
+        source.lock().unwrap().state = 2;
+
+        assert_eq!(propagator.propagate_update().await, 0);
+        assert_eq!(peer.lock().unwrap().state, 1);
+    }
+}
@@ -1,4 +1,7 @@
-use zed::ReactiveSystem;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use zed::{EmitError, Flow, ReactionContext, ReactiveSystem};
 
 #[derive(Clone, Debug, PartialEq)]
 struct AppState {
@@ -7,6 +10,17 @@ struct AppState {
     is_active: bool,
 }
 
+/// A clock that only moves when advanced, as a timer source
+fn fake_clock() -> (Rc<Cell<Instant>>, impl Fn() -> Instant) {
+    let now = Rc::new(Cell::new(Instant::now()));
+    let clock = Rc::clone(&now);
+    (now, move || clock.get())
+}
+
+fn advance(now: &Cell<Instant>, millis: u64) {
+    now.set(now.get() + Duration::from_millis(millis));
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -259,4 +273,706 @@ mod tests {
             vec!["First", "Second", "Third"]
         );
     }
+
+    #[derive(Debug, PartialEq, Eq, Hash)]
+    enum CounterEvent {
+        Increment,
+        Log,
+    }
+
+    #[test]
+    fn test_reactive_system_enum_events() {
+        let initial_state = AppState {
+            counter: 0,
+            messages: vec![],
+            is_active: false,
+        };
+
+        let mut system = ReactiveSystem::with_events(initial_state);
+
+        system.on(CounterEvent::Increment, |state: &mut AppState| {
+            state.counter += 1;
+        });
+
+        system.on(CounterEvent::Log, |state: &mut AppState| {
+            state.messages.push(format!("Counter is {}", state.counter));
+        });
+
+        system.trigger(CounterEvent::Increment);
+        system.trigger(CounterEvent::Increment);
+        system.trigger(CounterEvent::Log);
+
+        assert_eq!(system.current_state().counter, 2);
+        assert_eq!(system.current_state().messages, vec!["Counter is 2"]);
+    }
+
+    #[test]
+    fn test_reactive_system_custom_event_type_annotation() {
+        let mut system: ReactiveSystem<i32, u8> = ReactiveSystem::with_events(0);
+
+        system.on(1, |count: &mut i32| *count += 1);
+        system.trigger(1);
+        system.trigger(2);
+
+        assert_eq!(*system.current_state(), 1);
+    }
+
+    #[test]
+    fn test_reactive_system_off_removes_one_reaction() {
+        let mut system = ReactiveSystem::new(Vec::new());
+
+        let first = system.on("test".to_string(), |log: &mut Vec<&str>| log.push("first"));
+        let second = system.on("test".to_string(), |log: &mut Vec<&str>| log.push("second"));
+        assert_ne!(first, second);
+
+        assert!(system.off(first));
+        assert!(!system.off(first));
+        system.trigger("test".to_string());
+        assert_eq!(*system.current_state(), vec!["second"]);
+
+        assert!(system.off(second));
+        system.trigger("test".to_string());
+        assert_eq!(*system.current_state(), vec!["second"]);
+    }
+
+    #[test]
+    fn test_reactive_system_clear_removes_event_reactions() {
+        let mut system = ReactiveSystem::new(0);
+
+        system.on("add".to_string(), |count: &mut i32| *count += 1);
+        system.on("add".to_string(), |count: &mut i32| *count += 10);
+        let kept = system.on("double".to_string(), |count: &mut i32| *count *= 2);
+
+        assert_eq!(system.clear(&"add".to_string()), 2);
+        assert_eq!(system.clear(&"add".to_string()), 0);
+        system.trigger("add".to_string());
+        assert_eq!(*system.current_state(), 0);
+
+        // Other events keep their reactions and IDs
+        system.on("add".to_string(), |count: &mut i32| *count += 3);
+        system.trigger("add".to_string());
+        system.trigger("double".to_string());
+        assert_eq!(*system.current_state(), 6);
+        assert!(system.off(kept));
+    }
+
+    #[test]
+    fn test_reactive_system_on_once_runs_once() {
+        let mut system = ReactiveSystem::new(AppState {
+            counter: 0,
+            messages: vec![],
+            is_active: false,
+        });
+
+        system.on("start".to_string(), |state: &mut AppState| {
+            state.counter += 1;
+        });
+        let welcome = system.on_once("start".to_string(), |state: &mut AppState| {
+            state.messages.push("Welcome".to_string());
+        });
+
+        system.trigger("start".to_string());
+        system.trigger("start".to_string());
+
+        assert_eq!(system.current_state().counter, 2);
+        assert_eq!(system.current_state().messages, vec!["Welcome"]);
+        assert!(!system.off(welcome));
+    }
+
+    #[test]
+    fn test_reactive_system_on_once_can_be_removed_before_firing() {
+        let mut system = ReactiveSystem::new(0);
+
+        let id = system.on_once("add".to_string(), |count: &mut i32| *count += 1);
+        assert!(system.off(id));
+        system.trigger("add".to_string());
+
+        assert_eq!(*system.current_state(), 0);
+    }
+
+    #[test]
+    fn test_reactive_system_on_while_stops_when_predicate_fails() {
+        let mut system = ReactiveSystem::new(AppState {
+            counter: 0,
+            messages: vec![],
+            is_active: true,
+        });
+
+        system.on("tick".to_string(), |state: &mut AppState| {
+            state.counter += 1;
+        });
+        let logger = system.on_while(
+            "tick".to_string(),
+            |state: &AppState| state.counter < 3,
+            |state: &mut AppState| {
+                state.messages.push(format!("tick {}", state.counter));
+            },
+        );
+
+        for _ in 0..5 {
+            system.trigger("tick".to_string());
+        }
+
+        assert_eq!(system.current_state().counter, 5);
+        assert_eq!(system.current_state().messages, vec!["tick 1", "tick 2"]);
+        assert!(!system.off(logger));
+    }
+
+    #[test]
+    fn test_reactive_system_on_while_stays_removed() {
+        let mut system = ReactiveSystem::new(0);
+
+        system.on_while(
+            "add".to_string(),
+            |count: &i32| *count < 2,
+            |count: &mut i32| *count += 1,
+        );
+        system.on("reset".to_string(), |count: &mut i32| *count = 0);
+
+        for _ in 0..3 {
+            system.trigger("add".to_string());
+        }
+        assert_eq!(*system.current_state(), 2);
+
+        // The predicate holds again, but the reaction is gone
+        system.trigger("reset".to_string());
+        system.trigger("add".to_string());
+        assert_eq!(*system.current_state(), 0);
+    }
+
+    #[test]
+    fn test_reactive_system_on_pattern_matches_prefix() {
+        let mut system = ReactiveSystem::new(Vec::new());
+
+        system.on_pattern("user:*", |log: &mut Vec<&str>| log.push("user event"));
+
+        system.trigger("user:login".to_string());
+        system.trigger("user:".to_string());
+        system.trigger("admin:login".to_string());
+        system.trigger("user".to_string());
+
+        assert_eq!(*system.current_state(), vec!["user event", "user event"]);
+    }
+
+    #[test]
+    fn test_reactive_system_on_pattern_globs() {
+        let mut system = ReactiveSystem::new(Vec::new());
+
+        system.on_pattern("*:deleted", |log: &mut Vec<String>| {
+            log.push("deleted".into())
+        });
+        system.on_pattern("user:*:changed", |log: &mut Vec<String>| {
+            log.push("changed".into())
+        });
+        system.on_pattern("cart:item", |log: &mut Vec<String>| {
+            log.push("exact".into())
+        });
+
+        system.trigger("post:deleted".to_string());
+        system.trigger("user:name:changed".to_string());
+        system.trigger("user:changed".to_string());
+        system.trigger("cart:item".to_string());
+        system.trigger("cart:items".to_string());
+
+        assert_eq!(*system.current_state(), vec!["deleted", "changed", "exact"]);
+    }
+
+    #[test]
+    fn test_reactive_system_on_any_runs_for_every_event() {
+        let mut system = ReactiveSystem::new(AppState {
+            counter: 0,
+            messages: vec![],
+            is_active: false,
+        });
+
+        system.on("increment".to_string(), |state: &mut AppState| {
+            state.counter += 1;
+        });
+        let logger = system.on_any(|state: &mut AppState| {
+            state.messages.push(format!("counter {}", state.counter));
+        });
+
+        system.trigger("increment".to_string());
+        system.trigger("unknown".to_string());
+        assert!(system.off(logger));
+        system.trigger("increment".to_string());
+
+        assert_eq!(system.current_state().counter, 2);
+        assert_eq!(
+            system.current_state().messages,
+            vec!["counter 1", "counter 1"]
+        );
+    }
+
+    #[test]
+    fn test_reactive_system_mixed_reactions_run_in_registration_order() {
+        let mut system = ReactiveSystem::new(Vec::new());
+
+        system.on_any(|log: &mut Vec<&str>| log.push("any"));
+        system.on("user:login".to_string(), |log: &mut Vec<&str>| {
+            log.push("exact")
+        });
+        system.on_pattern("user:*", |log: &mut Vec<&str>| log.push("pattern"));
+        system.on("user:login".to_string(), |log: &mut Vec<&str>| {
+            log.push("exact again")
+        });
+
+        system.trigger("user:login".to_string());
+        assert_eq!(
+            *system.current_state(),
+            vec!["any", "exact", "pattern", "exact again"]
+        );
+
+        // Clearing the event keeps the pattern and catch-all reactions
+        assert_eq!(system.clear(&"user:login".to_string()), 2);
+        system.trigger("user:login".to_string());
+        assert_eq!(system.current_state()[4..], ["any", "pattern"]);
+    }
+
+    #[test]
+    fn test_reactive_system_on_any_with_enum_events() {
+        #[derive(PartialEq, Eq, Hash)]
+        enum Event {
+            A,
+            B,
+        }
+
+        let mut system = ReactiveSystem::with_events(0);
+        system.on_any(|count: &mut i32| *count += 1);
+
+        system.trigger(Event::A);
+        system.trigger(Event::B);
+
+        assert_eq!(*system.current_state(), 2);
+    }
+
+    #[test]
+    fn test_reactive_system_priorities_run_highest_first() {
+        let mut system = ReactiveSystem::new(Vec::new());
+
+        system.on("save".to_string(), |log: &mut Vec<&str>| {
+            log.push("default")
+        });
+        system.on_with_priority("save".to_string(), -1, |log: &mut Vec<&str>| {
+            log.push("audit")
+        });
+        system.on_with_priority("save".to_string(), 10, |log: &mut Vec<&str>| {
+            log.push("validate")
+        });
+        system.on_pattern("sa*", |log: &mut Vec<&str>| log.push("pattern"));
+        system.on_with_priority("save".to_string(), 10, |log: &mut Vec<&str>| {
+            log.push("validate again")
+        });
+
+        system.trigger("save".to_string());
+        assert_eq!(
+            *system.current_state(),
+            vec!["validate", "validate again", "default", "pattern", "audit"]
+        );
+    }
+
+    #[test]
+    fn test_reactive_system_set_priority_reorders_reactions() {
+        let mut system = ReactiveSystem::new(Vec::new());
+
+        let first = system.on("save".to_string(), |log: &mut Vec<&str>| log.push("first"));
+        let any = system.on_any(|log: &mut Vec<&str>| log.push("any"));
+
+        assert!(system.set_priority(any, 1));
+        system.trigger("save".to_string());
+        assert_eq!(*system.current_state(), vec!["any", "first"]);
+
+        // Back to the default priority, registration order applies again
+        assert!(system.set_priority(any, 0));
+        system.trigger("save".to_string());
+        assert_eq!(system.current_state()[2..], ["first", "any"]);
+
+        assert!(system.off(first));
+        assert!(!system.set_priority(first, 5));
+    }
+
+    #[test]
+    fn test_reactive_system_stop_skips_later_reactions() {
+        let mut system = ReactiveSystem::new((false, Vec::new()));
+
+        system.on("save".to_string(), |(_, log): &mut (bool, Vec<&str>)| {
+            log.push("write")
+        });
+        system.on_any(|(_, log): &mut (bool, Vec<&str>)| log.push("log"));
+        system.on_with_priority(
+            "save".to_string(),
+            10,
+            |(read_only, log): &mut (bool, Vec<&str>)| {
+                log.push("guard");
+                if *read_only {
+                    Flow::Stop
+                } else {
+                    Flow::Continue
+                }
+            },
+        );
+
+        system.trigger("save".to_string());
+        assert_eq!(system.current_state().1, vec!["guard", "write", "log"]);
+
+        // The guard runs first and vetoes the event
+        system.on(
+            "lock".to_string(),
+            |(read_only, _): &mut (bool, Vec<&str>)| *read_only = true,
+        );
+        system.trigger("lock".to_string());
+        system.trigger("save".to_string());
+        assert_eq!(system.current_state().1[3..], ["log", "guard"]);
+    }
+
+    #[test]
+    fn test_reactive_system_stopped_once_reaction_stays() {
+        let mut system = ReactiveSystem::new(0);
+
+        system.on("tick".to_string(), |_: &mut i32| Flow::Stop);
+        let once = system.on_once("tick".to_string(), |count: &mut i32| *count += 1);
+
+        // The one-shot reaction did not run, so it waits for the next trigger
+        system.trigger("tick".to_string());
+        assert_eq!(*system.current_state(), 0);
+
+        assert!(system.set_priority(once, 1));
+        system.trigger("tick".to_string());
+        system.trigger("tick".to_string());
+        assert_eq!(*system.current_state(), 1);
+    }
+
+    #[test]
+    fn test_reactive_system_derive_caches_until_reaction_runs() {
+        let computations = Rc::new(Cell::new(0));
+        let mut system = ReactiveSystem::new(vec![1, 2, 3]);
+        system.on("add".to_string(), |items: &mut Vec<i32>| items.push(4));
+
+        let counter = Rc::clone(&computations);
+        let total = system.derive(move |items: &Vec<i32>| {
+            counter.set(counter.get() + 1);
+            items.iter().sum::<i32>()
+        });
+
+        // Nothing is computed before the first read
+        assert_eq!(computations.get(), 0);
+        assert_eq!(total.get(&system), 6);
+        assert_eq!(total.get(&system), 6);
+        assert_eq!(computations.get(), 1);
+
+        // A trigger without reactions leaves the cache valid
+        system.trigger("unknown".to_string());
+        assert_eq!(total.get(&system), 6);
+        assert_eq!(computations.get(), 1);
+
+        system.trigger("add".to_string());
+        assert_eq!(computations.get(), 1);
+        assert_eq!(total.get(&system), 10);
+        assert_eq!(computations.get(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "computed value read from another system")]
+    fn test_reactive_system_computed_from_another_system_panics() {
+        let first = ReactiveSystem::new(1);
+        let second = ReactiveSystem::new(2);
+
+        let doubled = first.derive(|value: &i32| value * 2);
+        doubled.get(&second);
+    }
+
+    #[test]
+    fn test_reactive_system_emitted_events_run_after_current_batch() {
+        let mut system = ReactiveSystem::new(Vec::new());
+
+        system.on_with_context(
+            "checkout".to_string(),
+            |log: &mut Vec<&str>, ctx: &mut ReactionContext| {
+                log.push("checkout");
+                ctx.emit("charge".to_string());
+                ctx.emit("email".to_string());
+            },
+        );
+        system.on("checkout".to_string(), |log: &mut Vec<&str>| {
+            log.push("checkout again")
+        });
+        system.on_with_context(
+            "charge".to_string(),
+            |log: &mut Vec<&str>, ctx: &mut ReactionContext| {
+                log.push("charge");
+                ctx.emit("receipt".to_string());
+            },
+        );
+        system.on("email".to_string(), |log: &mut Vec<&str>| log.push("email"));
+        system.on("receipt".to_string(), |log: &mut Vec<&str>| {
+            log.push("receipt")
+        });
+
+        assert_eq!(system.try_trigger("checkout".to_string()), Ok(()));
+        assert_eq!(
+            *system.current_state(),
+            vec!["checkout", "checkout again", "charge", "email", "receipt"]
+        );
+    }
+
+    #[test]
+    fn test_reactive_system_emission_cycle_is_an_error() {
+        let mut system = ReactiveSystem::new(0);
+
+        for (event, next) in [("a", "b"), ("b", "c"), ("c", "a")] {
+            system.on_with_context(
+                event.to_string(),
+                move |count: &mut i32, ctx: &mut ReactionContext| {
+                    *count += 1;
+                    ctx.emit(next.to_string());
+                },
+            );
+        }
+
+        let err = system.try_trigger("a".to_string()).unwrap_err();
+        assert_eq!(
+            err,
+            EmitError::Cycle(vec![
+                "a".to_string(),
+                "b".to_string(),
+                "c".to_string(),
+                "a".to_string()
+            ])
+        );
+        assert_eq!(err.to_string(), "emitted events form a cycle of 3");
+        assert_eq!(*system.current_state(), 3);
+    }
+
+    #[test]
+    fn test_empty_cycle_error_displays() {
+        let err = EmitError::<String>::Cycle(vec![]);
+        assert_eq!(err.to_string(), "emitted events form a cycle of 0");
+    }
+
+    #[test]
+    fn test_reactive_system_same_event_on_separate_branches_is_no_cycle() {
+        let mut system = ReactiveSystem::new(0);
+
+        system.on_with_context(
+            "save".to_string(),
+            |_: &mut i32, ctx: &mut ReactionContext| {
+                ctx.emit("validate".to_string());
+                ctx.emit("log".to_string());
+            },
+        );
+        system.on_with_context(
+            "validate".to_string(),
+            |_: &mut i32, ctx: &mut ReactionContext| ctx.emit("log".to_string()),
+        );
+        system.on("log".to_string(), |count: &mut i32| *count += 1);
+
+        assert_eq!(system.try_trigger("save".to_string()), Ok(()));
+        assert_eq!(*system.current_state(), 2);
+    }
+
+    #[test]
+    fn test_reactive_system_max_depth_is_an_error() {
+        let mut system = ReactiveSystem::new(0);
+        system.set_max_depth(2);
+
+        for (event, next) in [("1", "2"), ("2", "3"), ("3", "4")] {
+            system.on_with_context(
+                event.to_string(),
+                move |count: &mut i32, ctx: &mut ReactionContext| {
+                    *count += 1;
+                    ctx.emit(next.to_string());
+                },
+            );
+        }
+
+        assert_eq!(
+            system.try_trigger("1".to_string()),
+            Err(EmitError::TooDeep { max_depth: 2 })
+        );
+        assert_eq!(*system.current_state(), 3);
+
+        // Event "4" is emitted at depth 3
+        system.set_max_depth(3);
+        assert_eq!(system.try_trigger("1".to_string()), Ok(()));
+    }
+
+    #[test]
+    fn test_reactive_system_trigger_reports_cycle_to_error_handlers() {
+        let errors = Rc::new(Cell::new(0));
+        let mut system = ReactiveSystem::new(0);
+        system.on_with_context(
+            "ping".to_string(),
+            |count: &mut i32, ctx: &mut ReactionContext| {
+                *count += 1;
+                ctx.emit("ping".to_string())
+            },
+        );
+        let reported = Rc::clone(&errors);
+        system.on_error(move |err: &EmitError| {
+            assert_eq!(err.to_string(), "emitted events form a cycle of 1");
+            reported.set(reported.get() + 1);
+        });
+
+        // The cycle is cut instead of aborting the process
+        system.trigger("ping".to_string());
+        assert_eq!(*system.current_state(), 1);
+        assert_eq!(errors.get(), 1);
+
+        assert!(system.try_trigger("ping".to_string()).is_err());
+        assert_eq!(errors.get(), 2);
+    }
+
+    #[test]
+    fn test_reactive_system_throttled_reaction_runs_once_per_period() {
+        let (now, clock) = fake_clock();
+        let mut system = ReactiveSystem::new((0, 0));
+        system.set_clock(clock);
+
+        system.on_throttled(
+            "scroll".to_string(),
+            Duration::from_millis(100),
+            |(heavy, _): &mut (i32, i32)| *heavy += 1,
+        );
+        system.on("scroll".to_string(), |(_, light): &mut (i32, i32)| {
+            *light += 1
+        });
+
+        for _ in 0..5 {
+            system.trigger("scroll".to_string());
+            advance(&now, 30);
+        }
+
+        // Runs at 0ms and 120ms, not in between
+        assert_eq!(*system.current_state(), (2, 5));
+    }
+
+    #[test]
+    fn test_reactive_system_debounced_reaction_runs_after_quiet_delay() {
+        let (now, clock) = fake_clock();
+        let mut system = ReactiveSystem::new(0);
+        system.set_clock(clock);
+
+        system.on_debounced(
+            "resize".to_string(),
+            Duration::from_millis(50),
+            |count: &mut i32| *count += 1,
+        );
+        assert_eq!(system.next_timer(), None);
+
+        for _ in 0..3 {
+            system.trigger("resize".to_string());
+            advance(&now, 20);
+            assert_eq!(system.run_timers(), 0);
+        }
+        assert_eq!(*system.current_state(), 0);
+        assert_eq!(
+            system.next_timer(),
+            Some(now.get() + Duration::from_millis(30))
+        );
+
+        advance(&now, 30);
+        assert_eq!(system.run_timers(), 1);
+        assert_eq!(*system.current_state(), 1);
+
+        // Nothing is pending until the next trigger
+        assert_eq!(system.next_timer(), None);
+        assert_eq!(system.run_timers(), 0);
+    }
+
+    #[test]
+    fn test_reactive_system_removed_debounced_reaction_does_not_run() {
+        let (now, clock) = fake_clock();
+        let mut system = ReactiveSystem::new(0);
+        system.set_clock(clock);
+
+        let id = system.on_debounced(
+            "input".to_string(),
+            Duration::from_millis(10),
+            |count: &mut i32| *count += 1,
+        );
+        system.trigger("input".to_string());
+        assert!(system.off(id));
+
+        advance(&now, 10);
+        assert_eq!(system.run_timers(), 0);
+        assert_eq!(system.next_timer(), None);
+        assert_eq!(*system.current_state(), 0);
+    }
+
+    #[test]
+    fn test_reactive_system_debounced_reaction_emits_events() {
+        let (now, clock) = fake_clock();
+        let mut system = ReactiveSystem::new(0);
+        system.set_clock(clock);
+
+        system.on_debounced_with_context(
+            "input".to_string(),
+            Duration::from_millis(10),
+            |count: &mut i32, ctx: &mut ReactionContext| {
+                *count += 1;
+                ctx.emit("save".to_string());
+            },
+        );
+        system.on("save".to_string(), |count: &mut i32| *count += 10);
+        system.trigger("input".to_string());
+
+        advance(&now, 10);
+        assert_eq!(system.run_timers(), 1);
+        assert_eq!(*system.current_state(), 11);
+    }
+
+    #[test]
+    fn test_reactive_system_debounced_emissions_are_checked() {
+        let (now, clock) = fake_clock();
+        let mut system = ReactiveSystem::new(0);
+        system.set_clock(clock);
+        let errors = Rc::new(Cell::new(0));
+        let reported = Rc::clone(&errors);
+        system.on_error(move |_| reported.set(reported.get() + 1));
+
+        system.on_debounced_with_context(
+            "input".to_string(),
+            Duration::from_millis(10),
+            |_: &mut i32, ctx: &mut ReactionContext| ctx.emit("loop".to_string()),
+        );
+        system.on_with_context(
+            "loop".to_string(),
+            |count: &mut i32, ctx: &mut ReactionContext| {
+                *count += 1;
+                ctx.emit("loop".to_string());
+            },
+        );
+        system.trigger("input".to_string());
+
+        advance(&now, 10);
+        assert_eq!(system.run_timers(), 1);
+        assert_eq!(*system.current_state(), 1);
+        assert_eq!(errors.get(), 1);
+    }
+
+    #[test]
+    fn test_reactive_system_debounced_reaction_can_stop_later_ones() {
+        let (now, clock) = fake_clock();
+        let mut system = ReactiveSystem::new(0);
+        system.set_clock(clock);
+
+        system.on_debounced(
+            "input".to_string(),
+            Duration::from_millis(10),
+            |count: &mut i32| {
+                *count += 1;
+                Flow::Stop
+            },
+        );
+        system.on_debounced(
+            "input".to_string(),
+            Duration::from_millis(10),
+            |count: &mut i32| *count += 100,
+        );
+        system.trigger("input".to_string());
+
+        advance(&now, 10);
+        assert_eq!(system.run_timers(), 1);
+        assert_eq!(*system.current_state(), 1);
+    }
 }
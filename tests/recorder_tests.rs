@@ -0,0 +1,137 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicI32, Ordering};
+use zed::*;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct CartState {
+    items: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+enum CartAction {
+    Add(String),
+    Clear,
+}
+
+fn cart_reducer(state: &CartState, action: &CartAction) -> CartState {
+    match action {
+        CartAction::Add(item) => {
+            let mut items = state.items.clone();
+            items.push(item.clone());
+            CartState { items }
+        }
+        CartAction::Clear => CartState { items: vec![] },
+    }
+}
+
+fn cart_store() -> Store<CartState, CartAction> {
+    Store::new(
+        CartState { items: vec![] },
+        Box::new(create_reducer(cart_reducer)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recorder_captures_actions_in_order() {
+        let store = cart_store();
+        store.dispatch(CartAction::Add("before".to_string()));
+
+        let recorder = ActionRecorder::attach(&store);
+        assert!(recorder.is_empty());
+        store.dispatch(CartAction::Add("apple".to_string()));
+        store.dispatch_batch(vec![CartAction::Clear, CartAction::Add("pear".to_string())]);
+
+        let log = recorder.stop();
+        store.dispatch(CartAction::Add("after".to_string()));
+
+        assert_eq!(log.initial_state.items, vec!["before".to_string()]);
+        assert_eq!(log.final_state.items, vec!["pear".to_string()]);
+        let actions: Vec<_> = log.actions.iter().map(|r| r.action.clone()).collect();
+        assert_eq!(
+            actions,
+            vec![
+                CartAction::Add("apple".to_string()),
+                CartAction::Clear,
+                CartAction::Add("pear".to_string()),
+            ]
+        );
+        assert!(log.actions.windows(2).all(|w| w[0].elapsed <= w[1].elapsed));
+    }
+
+    #[test]
+    fn test_replay_serialized_log() {
+        let store = cart_store();
+        let recorder = ActionRecorder::attach(&store);
+        store.dispatch(CartAction::Add("apple".to_string()));
+        store.dispatch(CartAction::Add("pear".to_string()));
+        let json = serde_json::to_string(&recorder.stop()).unwrap();
+
+        let log: ActionLog<CartState, CartAction> = serde_json::from_str(&json).unwrap();
+        let replica = cart_store();
+        replica.dispatch(CartAction::Add("stale".to_string()));
+
+        assert_eq!(replica.replay(&log), Ok(()));
+        assert_eq!(replica.get_state(), store.get_state());
+    }
+
+    #[test]
+    fn test_replay_detects_nondeterminism() {
+        let store = cart_store();
+        let recorder = ActionRecorder::attach(&store);
+        store.dispatch(CartAction::Add("apple".to_string()));
+        let log = recorder.stop();
+
+        // A reducer depending on hidden state is not deterministic
+        let calls = Arc::new(AtomicI32::new(0));
+        let replica = Store::new(
+            CartState { items: vec![] },
+            Box::new(create_reducer(
+                move |state: &CartState, action: &CartAction| {
+                    let call = calls.fetch_add(1, Ordering::SeqCst);
+                    let mut next = cart_reducer(state, action);
+                    next.items.push(format!("call {call}"));
+                    next
+                },
+            )),
+        );
+
+        let mismatch = replica.replay(&log).unwrap_err();
+        assert_eq!(mismatch.expected.items, vec!["apple".to_string()]);
+        assert_eq!(
+            mismatch.actual.items,
+            vec!["apple".to_string(), "call 0".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_recorder_attached_during_concurrent_dispatch_replays() {
+        for _ in 0..20 {
+            let store = cart_store();
+            let writers: Vec<_> = (0..4)
+                .map(|writer| {
+                    let store = store.clone();
+                    std::thread::spawn(move || {
+                        for item in 0..50 {
+                            store.dispatch(CartAction::Add(format!("{writer}-{item}")));
+                        }
+                    })
+                })
+                .collect();
+
+            let recorder = ActionRecorder::attach(&store);
+            for writer in writers {
+                writer.join().unwrap();
+            }
+            let log = recorder.stop();
+
+            // The log starts exactly where the recorded actions pick up,
+            // and lists them in the order the reducer applied them
+            cart_store().replay(&log).unwrap();
+        }
+    }
+}
@@ -202,4 +202,91 @@ mod reducer_tests {
         assert_eq!(state3.active_user, Some("alice".to_string()));
         assert_eq!(state3.settings.get("theme"), Some(&"dark".to_string()));
     }
+
+    #[derive(Debug, PartialEq)]
+    struct NegativeCounter;
+
+    impl std::fmt::Display for NegativeCounter {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "counter cannot be negative")
+        }
+    }
+
+    impl std::error::Error for NegativeCounter {}
+
+    struct NonNegativeReducer;
+
+    impl zed::TryReducer<TestState, TestAction> for NonNegativeReducer {
+        type Error = NegativeCounter;
+
+        fn try_reduce(
+            &self,
+            state: &TestState,
+            action: &TestAction,
+        ) -> Result<TestState, NegativeCounter> {
+            let next = test_reducer(state, action);
+            if next.counter < 0 {
+                Err(NegativeCounter)
+            } else {
+                Ok(next)
+            }
+        }
+    }
+
+    #[test]
+    fn test_try_reducer_trait() {
+        use zed::TryReducer;
+
+        let initial_state = TestState {
+            counter: 0,
+            message: "initial".to_string(),
+        };
+
+        let reducer = NonNegativeReducer;
+        assert_eq!(
+            reducer
+                .try_reduce(&initial_state, &TestAction::Increment)
+                .unwrap()
+                .counter,
+            1
+        );
+        assert_eq!(
+            reducer.try_reduce(&initial_state, &TestAction::Decrement),
+            Err(NegativeCounter)
+        );
+    }
+
+    #[test]
+    fn test_fallible_reducer_adapter() {
+        let reducer = zed::FallibleReducer::new(NonNegativeReducer);
+
+        let initial_state = TestState {
+            counter: 0,
+            message: "initial".to_string(),
+        };
+
+        // `reduce` keeps the previous state when the action is rejected
+        let unchanged = Reducer::reduce(&reducer, &initial_state, &TestAction::Decrement);
+        assert_eq!(unchanged, initial_state);
+
+        let error =
+            Reducer::try_reduce(&reducer, &initial_state, &TestAction::Decrement).unwrap_err();
+        assert_eq!(error.to_string(), "counter cannot be negative");
+        assert!(error.downcast_ref::<NegativeCounter>().is_some());
+    }
+
+    #[test]
+    fn test_plain_reducer_never_fails() {
+        let reducer = create_reducer(test_reducer);
+
+        let initial_state = TestState {
+            counter: 0,
+            message: "initial".to_string(),
+        };
+
+        let new_state = reducer
+            .try_reduce(&initial_state, &TestAction::Decrement)
+            .unwrap();
+        assert_eq!(new_state.counter, -1);
+    }
 }
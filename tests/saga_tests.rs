@@ -0,0 +1,116 @@
+use std::sync::mpsc;
+use std::time::Duration;
+use zed::*;
+
+#[derive(Clone, Debug, PartialEq)]
+struct CheckoutState {
+    step: String,
+    attempts: u32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum CheckoutAction {
+    Start,
+    PaymentFailed,
+    PaymentSucceeded,
+    SetStep(String),
+}
+
+fn checkout_reducer(state: &CheckoutState, action: &CheckoutAction) -> CheckoutState {
+    match action {
+        CheckoutAction::Start => state.clone(),
+        CheckoutAction::PaymentFailed => CheckoutState {
+            attempts: state.attempts + 1,
+            ..state.clone()
+        },
+        CheckoutAction::PaymentSucceeded => state.clone(),
+        CheckoutAction::SetStep(step) => CheckoutState {
+            step: step.clone(),
+            ..state.clone()
+        },
+    }
+}
+
+fn checkout_store() -> Store<CheckoutState, CheckoutAction> {
+    configure_store(
+        CheckoutState {
+            step: "cart".to_string(),
+            attempts: 0,
+        },
+        create_reducer(checkout_reducer),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_saga_orchestrates_multi_step_flow() {
+        let store = checkout_store();
+        let saga = store.run_saga(|saga| async move {
+            saga.take(|action| *action == CheckoutAction::Start).await?;
+            saga.put(CheckoutAction::SetStep("paying".to_string()));
+            loop {
+                match saga
+                    .take(|action| {
+                        matches!(
+                            action,
+                            CheckoutAction::PaymentFailed | CheckoutAction::PaymentSucceeded
+                        )
+                    })
+                    .await?
+                {
+                    CheckoutAction::PaymentSucceeded => break,
+                    _ if saga.select(|state| state.attempts) >= 3 => {
+                        saga.put(CheckoutAction::SetStep("failed".to_string()));
+                        return Some(false);
+                    }
+                    _ => saga.delay(Duration::from_millis(1)).await,
+                }
+            }
+            saga.put(CheckoutAction::SetStep("done".to_string()));
+            Some(true)
+        });
+
+        // Dispatched before the saga waits for them; buffered meanwhile
+        store.dispatch(CheckoutAction::Start);
+        store.dispatch(CheckoutAction::PaymentFailed);
+        store.dispatch(CheckoutAction::PaymentSucceeded);
+
+        assert_eq!(saga.join().unwrap(), Some(true));
+        assert_eq!(store.get_state().step, "done");
+    }
+
+    #[test]
+    fn test_cancelled_saga_stops_taking() {
+        let store = checkout_store();
+        let (taken_tx, taken_rx) = mpsc::channel();
+        let saga = store.run_saga(move |saga| async move {
+            while let Some(action) = saga.take(|_| true).await {
+                taken_tx.send(action).unwrap();
+            }
+            saga.is_cancelled()
+        });
+
+        store.dispatch(CheckoutAction::Start);
+        assert_eq!(taken_rx.recv().unwrap(), CheckoutAction::Start);
+
+        saga.cancel();
+        assert!(saga.join().unwrap());
+        store.dispatch(CheckoutAction::PaymentFailed);
+        assert!(taken_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_delay_waits_at_least_the_duration() {
+        let store = checkout_store();
+        let saga = store.run_saga(|saga| async move {
+            let start = std::time::Instant::now();
+            saga.delay(Duration::from_millis(20)).await;
+            start.elapsed()
+        });
+
+        assert!(saga.join().unwrap() >= Duration::from_millis(20));
+    }
+}
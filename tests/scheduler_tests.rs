@@ -0,0 +1,92 @@
+use serde::Serialize;
+use std::thread;
+use std::time::{Duration, Instant};
+use zed::{MeshScheduler, SharedStateNode, StateNode};
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+struct Versioned {
+    version: u32,
+    value: String,
+}
+
+fn versioned_node(id: String) -> SharedStateNode<Versioned> {
+    let mut node = StateNode::new(
+        id,
+        Versioned {
+            version: 0,
+            value: String::new(),
+        },
+    );
+    node.set_conflict_resolver(|current: &mut Versioned, remote: &Versioned| {
+        if remote.version > current.version {
+            *current = remote.clone();
+        }
+    });
+    node.into_shared()
+}
+
+fn ring(len: usize) -> Vec<SharedStateNode<Versioned>> {
+    let nodes: Vec<_> = (0..len).map(|i| versioned_node(format!("n{i}"))).collect();
+    for (i, node) in nodes.iter().enumerate() {
+        StateNode::connect_bidirectional(node, &nodes[(i + 1) % len]);
+    }
+    nodes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scheduler_repairs_missed_updates_in_background() {
+        let nodes = ring(8);
+        let mut scheduler = MeshScheduler::new(Duration::from_millis(5))
+            .jitter(Duration::from_millis(5))
+            .fanout(2);
+        for node in &nodes {
+            scheduler.add_node(node);
+        }
+        assert!(scheduler.start());
+        assert!(!scheduler.start());
+        assert!(scheduler.is_running());
+
+        // Changed without propagating it
+        nodes[3].lock().unwrap().state = Versioned {
+            version: 2,
+            value: "repaired".to_string(),
+        };
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while !scheduler.is_converged() {
+            assert!(Instant::now() < deadline, "scheduler did not converge");
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert!(scheduler.stop());
+        assert!(!scheduler.is_running());
+        assert!(scheduler.rounds() > 0);
+        for node in &nodes {
+            assert_eq!(node.lock().unwrap().state.value, "repaired");
+        }
+    }
+
+    #[test]
+    fn test_stopped_scheduler_runs_no_rounds() {
+        let nodes = ring(3);
+        let mut scheduler = MeshScheduler::new(Duration::from_millis(1));
+        for node in &nodes {
+            scheduler.add_node(node);
+        }
+        assert!(!scheduler.stop());
+
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(scheduler.rounds(), 0);
+
+        nodes[0].lock().unwrap().state.version = 1;
+        let round = scheduler.run_round();
+        assert_eq!(round.digests_exchanged, 6);
+        assert_eq!(scheduler.rounds(), 1);
+
+        assert!(scheduler.remove_node(&nodes[0]));
+        assert!(!scheduler.remove_node(&nodes[0]));
+    }
+}
@@ -0,0 +1,89 @@
+use std::sync::{Arc, Mutex};
+use zed::*;
+
+#[derive(Clone, Debug, PartialEq)]
+struct CartState {
+    items: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct AppState {
+    cart: CartState,
+    theme: String,
+}
+
+#[derive(Clone, Debug)]
+enum CartAction {
+    Add(String),
+    Clear,
+}
+
+#[derive(Clone, Debug)]
+enum AppAction {
+    Cart(CartAction),
+    SetTheme(String),
+}
+
+fn app_reducer(state: &AppState, action: &AppAction) -> AppState {
+    let mut next = state.clone();
+    match action {
+        AppAction::Cart(CartAction::Add(item)) => next.cart.items.push(item.clone()),
+        AppAction::Cart(CartAction::Clear) => next.cart.items.clear(),
+        AppAction::SetTheme(theme) => next.theme = theme.clone(),
+    }
+    next
+}
+
+fn create_store() -> Store<AppState, AppAction> {
+    configure_store(
+        AppState {
+            cart: CartState { items: vec![] },
+            theme: "light".to_string(),
+        },
+        create_reducer(app_reducer),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scoped_store_reads_and_dispatches() {
+        let store = create_store();
+        let cart = store.scoped(Lens::new(|state: &AppState| &state.cart, AppAction::Cart));
+
+        cart.dispatch(CartAction::Add("apple".to_string()));
+        cart.dispatch(CartAction::Add("pear".to_string()));
+
+        assert_eq!(cart.get_state().items, vec!["apple", "pear"]);
+        assert_eq!(cart.with_state(|cart| cart.items.len()), 2);
+        assert_eq!(store.get_state().cart.items.len(), 2);
+
+        cart.dispatch(CartAction::Clear);
+        assert!(store.get_state().cart.items.is_empty());
+    }
+
+    #[test]
+    fn test_scoped_subscribers_ignore_other_slices() {
+        let store = create_store();
+        let cart = store.scoped(Lens::new(|state: &AppState| &state.cart, AppAction::Cart));
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let seen_clone = Arc::clone(&seen);
+        let id = cart.subscribe(move |cart: &CartState| {
+            seen_clone.lock().unwrap().push(cart.items.len());
+        });
+
+        store.dispatch(AppAction::SetTheme("dark".to_string()));
+        cart.dispatch(CartAction::Add("apple".to_string()));
+        store.dispatch(AppAction::SetTheme("light".to_string()));
+        store.dispatch(AppAction::Cart(CartAction::Add("pear".to_string())));
+
+        assert_eq!(*seen.lock().unwrap(), vec![1, 2]);
+
+        assert!(cart.unsubscribe(id));
+        cart.dispatch(CartAction::Clear);
+        assert_eq!(seen.lock().unwrap().len(), 2);
+    }
+}
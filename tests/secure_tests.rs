@@ -0,0 +1,152 @@
+#![cfg(feature = "encryption")]
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use zed::{MeshLink, MeshMessage, NodeIdentity, SecureTransport, StateNode, Transport};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Ledger {
+    entries: Vec<String>,
+}
+
+/// One end of an in-memory connection.
+#[derive(Clone, Default)]
+struct MemoryTransport {
+    outbox: Arc<Mutex<VecDeque<MeshMessage>>>,
+    inbox: Arc<Mutex<VecDeque<MeshMessage>>>,
+}
+
+impl MemoryTransport {
+    fn pair() -> (Self, Self) {
+        let a = Self::default();
+        let b = Self {
+            outbox: Arc::clone(&a.inbox),
+            inbox: Arc::clone(&a.outbox),
+        };
+        (a, b)
+    }
+}
+
+impl Transport for MemoryTransport {
+    fn send(&mut self, message: &MeshMessage) -> io::Result<()> {
+        self.outbox.lock().unwrap().push_back(message.clone());
+        Ok(())
+    }
+
+    fn receive(&mut self) -> io::Result<Option<MeshMessage>> {
+        Ok(self.inbox.lock().unwrap().pop_front())
+    }
+}
+
+/// Runs the handshake of both ends, the accepting one on another thread.
+fn secure_pair(
+    client: &NodeIdentity,
+    server: &NodeIdentity,
+    client_trusts: [u8; 32],
+    server_trusts: [u8; 32],
+) -> (
+    io::Result<SecureTransport<MemoryTransport>>,
+    io::Result<SecureTransport<MemoryTransport>>,
+) {
+    let (a, b) = MemoryTransport::pair();
+    let server = server.clone();
+    let accepting = thread::spawn(move || SecureTransport::accept(b, &server, &[server_trusts]));
+    let connected = SecureTransport::connect(a, client, &[client_trusts]);
+    (connected, accepting.join().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secure_transport_synchronizes_large_states() {
+        let client = NodeIdentity::generate().unwrap();
+        let server = NodeIdentity::generate().unwrap();
+        let (connected, accepted) =
+            secure_pair(&client, &server, server.public_key(), client.public_key());
+        let (connected, accepted) = (connected.unwrap(), accepted.unwrap());
+        assert_eq!(connected.peer_key(), server.public_key());
+        assert_eq!(accepted.peer_key(), client.public_key());
+
+        // Larger than a single Noise message
+        let entries = (0..10_000).map(|i| format!("entry {i}")).collect();
+        let local = StateNode::new("client".to_string(), Ledger { entries });
+        let mut remote = StateNode::new("server".to_string(), Ledger { entries: vec![] });
+
+        let mut sender = MeshLink::new(connected);
+        let mut receiver = MeshLink::new(accepted);
+        sender.send_state(&local).unwrap();
+
+        // The wire only carries ciphertext
+        let wire = sender
+            .transport()
+            .transport()
+            .outbox
+            .lock()
+            .unwrap()
+            .clone();
+        assert!(matches!(
+            wire.front(),
+            Some(MeshMessage::Encrypted { payload }) if !payload.contains("entry")
+        ));
+
+        assert_eq!(receiver.receive(&mut remote).unwrap(), 1);
+        assert_eq!(remote.state, local.state);
+    }
+
+    #[test]
+    fn test_secure_transport_rejects_untrusted_peers() {
+        let client = NodeIdentity::generate().unwrap();
+        let server = NodeIdentity::generate().unwrap();
+        let stranger = NodeIdentity::generate().unwrap();
+
+        let (connected, accepted) =
+            secure_pair(&client, &server, server.public_key(), stranger.public_key());
+        assert!(connected.is_ok());
+        let err = accepted.err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+
+        // An identity restored from its secret key is the same identity
+        let restored = NodeIdentity::from_secret_key(client.secret_key());
+        assert_eq!(restored.public_key(), client.public_key());
+    }
+
+    #[test]
+    fn test_secure_transport_rejects_forged_messages() {
+        let client = NodeIdentity::generate().unwrap();
+        let server = NodeIdentity::generate().unwrap();
+        let (connected, accepted) =
+            secure_pair(&client, &server, server.public_key(), client.public_key());
+        let (mut connected, mut accepted) = (connected.unwrap(), accepted.unwrap());
+        let mut remote = StateNode::new("server".to_string(), Ledger { entries: vec![] });
+        let wire = Arc::clone(&connected.transport().outbox);
+
+        // Plain messages are refused
+        wire.lock().unwrap().push_back(MeshMessage::State {
+            from: "client".to_string(),
+            state: serde_json::json!({ "entries": ["forged"] }),
+        });
+        assert_eq!(
+            accepted.receive().unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        // So are tampered ones
+        let message = MeshMessage::State {
+            from: "client".to_string(),
+            state: serde_json::json!({ "entries": ["genuine"] }),
+        };
+        connected.send(&message).unwrap();
+        if let Some(MeshMessage::Encrypted { payload }) = wire.lock().unwrap().back_mut() {
+            let flipped = if payload.starts_with('A') { "B" } else { "A" };
+            payload.replace_range(..1, flipped);
+        }
+        let mut link = MeshLink::new(accepted);
+        assert!(link.receive(&mut remote).is_err());
+        assert!(remote.state.entries.is_empty());
+    }
+}
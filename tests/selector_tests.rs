@@ -0,0 +1,109 @@
+use std::sync::Arc;
+use zed::*;
+
+#[derive(Clone, Debug, PartialEq)]
+struct TodoState {
+    items: Vec<Todo>,
+    filter: Filter,
+    title: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Todo {
+    id: u32,
+    completed: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Filter {
+    All,
+    Active,
+}
+
+fn initial_state() -> TodoState {
+    TodoState {
+        items: vec![
+            Todo {
+                id: 1,
+                completed: false,
+            },
+            Todo {
+                id: 2,
+                completed: true,
+            },
+        ],
+        filter: Filter::All,
+        title: "todos".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filtered_ids() -> Selector<TodoState, (Vec<Todo>, Filter), Vec<u32>> {
+        create_selector(
+            |state: &TodoState| (state.items.clone(), state.filter.clone()),
+            |(items, filter): &(Vec<Todo>, Filter)| {
+                items
+                    .iter()
+                    .filter(|todo| *filter == Filter::All || !todo.completed)
+                    .map(|todo| todo.id)
+                    .collect()
+            },
+        )
+    }
+
+    #[test]
+    fn test_selector_memoizes_unchanged_inputs() {
+        let selector = filtered_ids();
+        let mut state = initial_state();
+
+        assert_eq!(selector.select(&state), vec![1, 2]);
+        assert_eq!(selector.select(&state), vec![1, 2]);
+        assert_eq!(selector.recomputations(), 1);
+
+        // Changing an unrelated field does not trigger a recomputation
+        state.title = "renamed".to_string();
+        assert_eq!(selector.select(&state), vec![1, 2]);
+        assert_eq!(selector.recomputations(), 1);
+    }
+
+    #[test]
+    fn test_selector_recomputes_when_inputs_change() {
+        let selector = filtered_ids();
+        let mut state = initial_state();
+
+        assert_eq!(selector.select(&state), vec![1, 2]);
+
+        state.filter = Filter::Active;
+        assert_eq!(selector.select(&state), vec![1]);
+        assert_eq!(selector.recomputations(), 2);
+
+        selector.reset();
+        assert_eq!(selector.select(&state), vec![1]);
+        assert_eq!(selector.recomputations(), 3);
+    }
+
+    #[test]
+    fn test_composed_selectors() {
+        let active_count = Arc::new(create_selector(
+            |state: &TodoState| state.items.clone(),
+            |items: &Vec<Todo>| items.iter().filter(|todo| !todo.completed).count(),
+        ));
+
+        let active_input = Arc::clone(&active_count);
+        let summary = create_selector(
+            move |state: &TodoState| (active_input.select(state), state.title.clone()),
+            |(count, title): &(usize, String)| format!("{title}: {count} left"),
+        );
+
+        let mut state = initial_state();
+        assert_eq!(summary.select(&state), "todos: 1 left");
+
+        state.items[1].completed = false;
+        assert_eq!(summary.select(&state), "todos: 2 left");
+        assert_eq!(active_count.recomputations(), 2);
+        assert_eq!(summary.recomputations(), 2);
+    }
+}
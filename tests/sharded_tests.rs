@@ -0,0 +1,189 @@
+use std::sync::{Arc, Mutex};
+use std::thread;
+use zed::*;
+
+#[derive(Clone, Debug, PartialEq)]
+struct Sensor {
+    readings: u32,
+    last: i32,
+}
+
+#[derive(Clone, Debug)]
+enum SensorAction {
+    Register(u32),
+    Reading(u32, i32),
+    Remove(u32),
+}
+
+fn sensors(shards: usize) -> ShardedStore<u32, Sensor, SensorAction> {
+    ShardedStore::new(
+        shards,
+        |action: &SensorAction| match action {
+            SensorAction::Register(id)
+            | SensorAction::Reading(id, _)
+            | SensorAction::Remove(id) => *id,
+        },
+        create_try_reducer(|sensor: &Option<Sensor>, action: &SensorAction| {
+            match (sensor, action) {
+                (None, SensorAction::Register(_)) => Ok(Some(Sensor {
+                    readings: 0,
+                    last: 0,
+                })),
+                (Some(_), SensorAction::Register(id)) => Err(format!("sensor {id} exists")),
+                (Some(sensor), SensorAction::Reading(_, value)) => Ok(Some(Sensor {
+                    readings: sensor.readings + 1,
+                    last: *value,
+                })),
+                (_, SensorAction::Remove(_)) | (None, SensorAction::Reading(..)) => Ok(None),
+            }
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_actions_are_routed_to_their_entity() {
+        let store = sensors(4);
+        assert_eq!(store.shard_count(), 4);
+
+        store.dispatch(SensorAction::Register(1));
+        store.dispatch(SensorAction::Register(2));
+        store.dispatch(SensorAction::Reading(2, 21));
+        store.dispatch(SensorAction::Reading(9, 5));
+
+        assert_eq!(store.len(), 2);
+        assert_eq!(
+            store.get(&2),
+            Some(Sensor {
+                readings: 1,
+                last: 21
+            })
+        );
+        assert!(!store.contains_key(&9));
+        assert!(store.dispatch_try(SensorAction::Register(1)).is_err());
+
+        store.dispatch(SensorAction::Remove(1));
+        assert_eq!(store.snapshot().keys().collect::<Vec<_>>(), vec![&2]);
+        assert_eq!(store.with_entity(&2, |sensor| sensor.unwrap().last), 21);
+    }
+
+    #[test]
+    fn test_concurrent_updates_to_different_keys() {
+        let store = sensors(8);
+        let workers: Vec<_> = (0..8)
+            .map(|id| {
+                let store = store.clone();
+                thread::spawn(move || {
+                    store.dispatch(SensorAction::Register(id));
+                    for value in 0..100 {
+                        store.dispatch(SensorAction::Reading(id, value));
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+
+        assert_eq!(store.len(), 8);
+        assert!(
+            store
+                .snapshot()
+                .values()
+                .all(|sensor| sensor.readings == 100 && sensor.last == 99)
+        );
+    }
+
+    #[test]
+    fn test_subscribers_see_changed_entities() {
+        let store = sensors(2);
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let changes = Arc::clone(&seen);
+        let id = store.subscribe(move |key: &u32, sensor: Option<&Sensor>| {
+            changes
+                .lock()
+                .unwrap()
+                .push((*key, sensor.map(|sensor| sensor.last)));
+        });
+
+        store.dispatch(SensorAction::Register(3));
+        store.dispatch(SensorAction::Reading(3, 7));
+        store.dispatch(SensorAction::Reading(4, 1));
+        store.dispatch(SensorAction::Remove(3));
+        assert!(store.unsubscribe(id));
+        store.dispatch(SensorAction::Register(5));
+
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![(3, Some(0)), (3, Some(7)), (3, None)]
+        );
+    }
+
+    #[test]
+    fn test_subscribers_are_called_in_subscription_order() {
+        let store = sensors(2);
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        for index in 0..16 {
+            let calls = Arc::clone(&calls);
+            store.subscribe(move |_: &u32, _: Option<&Sensor>| {
+                calls.lock().unwrap().push(index);
+            });
+        }
+
+        store.dispatch(SensorAction::Register(1));
+
+        assert_eq!(*calls.lock().unwrap(), (0..16).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_concurrent_changes_to_a_key_are_delivered_in_order() {
+        let store = sensors(1);
+        store.dispatch(SensorAction::Register(1));
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let readings = Arc::clone(&seen);
+        store.subscribe(move |_: &u32, sensor: Option<&Sensor>| {
+            readings.lock().unwrap().push(sensor.unwrap().readings);
+        });
+
+        let workers: Vec<_> = (0..8)
+            .map(|_| {
+                let store = store.clone();
+                thread::spawn(move || {
+                    for value in 0..250 {
+                        store.dispatch(SensorAction::Reading(1, value));
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+
+        assert_eq!(*seen.lock().unwrap(), (1..=2000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_subscribers_may_dispatch_to_the_same_shard() {
+        let store = sensors(1);
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let changes = Arc::clone(&seen);
+        let inner = store.clone();
+        store.subscribe(move |key: &u32, sensor: Option<&Sensor>| {
+            changes.lock().unwrap().push((*key, sensor.is_some()));
+            if *key == 1 && sensor.is_some() {
+                inner.dispatch(SensorAction::Register(2));
+                inner.dispatch(SensorAction::Remove(1));
+            }
+        });
+
+        store.dispatch(SensorAction::Register(1));
+
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![(1, true), (2, true), (1, false)]
+        );
+    }
+}
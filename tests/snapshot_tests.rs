@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use zed::{PersistError, StateNode, load_mesh, save_mesh};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Shape {
+    kind: String,
+    size: u32,
+}
+
+fn shape(kind: &str, size: u32) -> Shape {
+    Shape {
+        kind: kind.to_string(),
+        size,
+    }
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("zed-mesh-{name}-{}.mesh", std::process::id()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mesh_round_trips_states_and_topology() {
+        let path = temp_path("round-trip");
+        let a = StateNode::new("a".to_string(), shape("circle", 1)).into_shared();
+        let b = StateNode::new("b".to_string(), shape("square", 2)).into_shared();
+        let c = StateNode::new("c".to_string(), shape("line", 3)).into_shared();
+        let outside = StateNode::new("outside".to_string(), shape("dot", 0)).into_shared();
+        StateNode::connect_bidirectional(&a, &b);
+        b.lock().unwrap().connect(&c);
+        c.lock().unwrap().connect(&outside);
+
+        save_mesh(&[a.clone(), b.clone(), c.clone()], &path).unwrap();
+        a.lock().unwrap().remove_connection(&"b".to_string());
+
+        let nodes = load_mesh::<Shape>(&path).unwrap();
+        let ids: Vec<String> = nodes.iter().map(|n| n.lock().unwrap().id.clone()).collect();
+        assert_eq!(ids, vec!["a", "b", "c"]);
+        assert_eq!(nodes[1].lock().unwrap().state, shape("square", 2));
+
+        // Connections outside the saved set are dropped
+        assert!(nodes[2].lock().unwrap().connections.is_empty());
+        let mut b_connections: Vec<String> = nodes[1]
+            .lock()
+            .unwrap()
+            .connections
+            .keys()
+            .cloned()
+            .collect();
+        b_connections.sort();
+        assert_eq!(b_connections, vec!["a", "c"]);
+
+        // Restored connections point at the loaded nodes
+        nodes[0].lock().unwrap().state = shape("circle", 10);
+        nodes[0].lock().unwrap().propagate_update();
+        assert_eq!(nodes[1].lock().unwrap().state.size, 10);
+
+        // Bidirectional links are removed together, as before saving
+        nodes[0].lock().unwrap().remove_connection(&"b".to_string());
+        assert!(!nodes[1].lock().unwrap().connections.contains_key("a"));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_load_mesh_rejects_damaged_files() {
+        let path = temp_path("damaged");
+        let a = StateNode::new("a".to_string(), shape("circle", 1)).into_shared();
+        save_mesh(&[a.clone(), a], &path).unwrap();
+
+        let result = load_mesh::<Shape>(&path);
+        assert!(matches!(result, Err(PersistError::Corrupted(_))));
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, contents.replace("circle", "square")).unwrap();
+        let result = load_mesh::<Shape>(&path);
+        assert!(matches!(result, Err(PersistError::Corrupted(_))));
+
+        std::fs::write(&path, contents.replacen(" 1 ", " 2 ", 1)).unwrap();
+        let result = load_mesh::<Shape>(&path);
+        assert!(matches!(
+            result,
+            Err(PersistError::Migration { from: 2, to: 1 })
+        ));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
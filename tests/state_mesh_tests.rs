@@ -1,4 +1,5 @@
-use zed::StateNode;
+use std::sync::Arc;
+use zed::{QuorumError, StateNode};
 
 #[derive(Clone, Debug, PartialEq)]
 struct TestData {
@@ -37,13 +38,14 @@ mod tests {
         };
 
         let mut node1 = StateNode::new("node1".to_string(), data1);
-        let node2 = StateNode::new("node2".to_string(), data2.clone());
+        let node2 = StateNode::new("node2".to_string(), data2.clone()).into_shared();
 
-        node1.connect(node2);
+        node1.connect(&node2);
 
         assert_eq!(node1.connections.len(), 1);
         assert!(node1.connections.contains_key("node2"));
-        assert_eq!(node1.connections["node2"].state, data2);
+        assert!(Arc::ptr_eq(&node1.connections["node2"], &node2));
+        assert_eq!(node2.lock().unwrap().state, data2);
     }
 
     #[test]
@@ -58,14 +60,14 @@ mod tests {
         };
 
         let mut node1 = StateNode::new("node1".to_string(), data1);
-        let node2 = StateNode::new("node2".to_string(), data2.clone());
+        let node2 = StateNode::new("node2".to_string(), data2.clone()).into_shared();
 
-        node1.connect(node2);
+        node1.connect(&node2);
         assert_eq!(node1.connections.len(), 1);
 
         let removed = node1.remove_connection(&"node2".to_string());
         assert!(removed.is_some());
-        assert_eq!(removed.unwrap().state, data2);
+        assert_eq!(removed.unwrap().lock().unwrap().state, data2);
         assert!(node1.connections.is_empty());
     }
 
@@ -173,17 +175,17 @@ mod tests {
         };
 
         let mut master = StateNode::new("master".to_string(), data1.clone());
-        let slave1 = StateNode::new("slave1".to_string(), data2);
-        let slave2 = StateNode::new("slave2".to_string(), data3);
+        let slave1 = StateNode::new("slave1".to_string(), data2).into_shared();
+        let slave2 = StateNode::new("slave2".to_string(), data3).into_shared();
 
-        master.connect(slave1);
-        master.connect(slave2);
+        master.connect(&slave1);
+        master.connect(&slave2);
 
         master.propagate_update();
 
-        // All connected nodes should have master's state
-        assert_eq!(master.connections["slave1"].state, data1);
-        assert_eq!(master.connections["slave2"].state, data1);
+        // The connected nodes themselves should have master's state
+        assert_eq!(slave1.lock().unwrap().state, data1);
+        assert_eq!(slave2.lock().unwrap().state, data1);
     }
 
     #[test]
@@ -201,9 +203,9 @@ mod tests {
             name: "C".to_string(),
         };
 
-        let mut node_a = StateNode::new("A".to_string(), data_a);
-        let mut node_b = StateNode::new("B".to_string(), data_b);
-        let node_c = StateNode::new("C".to_string(), data_c.clone());
+        let node_a = StateNode::new("A".to_string(), data_a).into_shared();
+        let node_b = StateNode::new("B".to_string(), data_b).into_shared();
+        let node_c = StateNode::new("C".to_string(), data_c).into_shared();
 
         // Set up conflict resolution that always takes the higher value
         let resolver = |current: &mut TestData, remote: &TestData| {
@@ -212,30 +214,614 @@ mod tests {
             }
         };
 
-        node_a.set_conflict_resolver(resolver);
-        node_b.set_conflict_resolver(resolver);
+        node_a.lock().unwrap().set_conflict_resolver(resolver);
+        node_b.lock().unwrap().set_conflict_resolver(resolver);
 
         // Connect A -> B, A -> C
-        node_a.connect(node_b.clone());
-        node_a.connect(node_c.clone());
+        node_a.lock().unwrap().connect(&node_b);
+        node_a.lock().unwrap().connect(&node_c);
 
         // Connect B -> A, B -> C
-        node_b.connect(node_a.clone());
-        node_b.connect(node_c.clone());
+        node_b.lock().unwrap().connect(&node_a);
+        node_b.lock().unwrap().connect(&node_c);
 
         // Propagate from A (value: 1)
-        node_a.propagate_update();
+        node_a.lock().unwrap().propagate_update();
 
         // B and C should get A's value only if it's higher (it's not)
-        assert_eq!(node_a.connections["B"].state.value, 2); // B keeps its value
-        assert_eq!(node_a.connections["C"].state.value, 1); // C gets A's value
+        assert_eq!(node_b.lock().unwrap().state.value, 2); // B keeps its value
+        assert_eq!(node_c.lock().unwrap().state.value, 1); // C gets A's value
+
+        // Now update B to have the highest value and propagate it back
+        node_b.lock().unwrap().state.value = 10;
+        node_b.lock().unwrap().propagate_update();
+
+        // A and C are the same nodes A propagated to before
+        assert_eq!(node_a.lock().unwrap().state.value, 10);
+        assert_eq!(node_c.lock().unwrap().state.value, 10);
+        assert_eq!(
+            node_a.lock().unwrap().connections["C"]
+                .lock()
+                .unwrap()
+                .state
+                .value,
+            10
+        );
+    }
+
+    #[test]
+    fn test_state_node_connections_share_live_nodes() {
+        let data = |value: i32| TestData {
+            value,
+            name: format!("v{value}"),
+        };
+
+        let mut writer = StateNode::new("writer".to_string(), data(1));
+        let mut reader = StateNode::new("reader".to_string(), data(0));
+        let target = StateNode::new("target".to_string(), data(0)).into_shared();
+
+        writer.connect(&target);
+        reader.connect(&target);
+        writer.propagate_update();
+
+        // The reader sees the update through its own connection
+        assert_eq!(reader.connections["target"].lock().unwrap().state, data(1));
+    }
+
+    #[test]
+    fn test_state_node_connect_bidirectional() {
+        let data = |value: i32| TestData {
+            value,
+            name: format!("v{value}"),
+        };
+        let node_a = StateNode::new("A".to_string(), data(1)).into_shared();
+        let node_b = StateNode::new("B".to_string(), data(2)).into_shared();
+
+        assert!(StateNode::connect_bidirectional(&node_a, &node_b));
+        assert!(!StateNode::connect_bidirectional(&node_a, &node_a));
+        assert!(Arc::ptr_eq(
+            &node_a.lock().unwrap().connections["B"],
+            &node_b
+        ));
+        assert!(Arc::ptr_eq(
+            &node_b.lock().unwrap().connections["A"],
+            &node_a
+        ));
+
+        // Updates travel both ways
+        node_b.lock().unwrap().propagate_update();
+        assert_eq!(node_a.lock().unwrap().state, data(2));
+        node_a.lock().unwrap().state = data(3);
+        node_a.lock().unwrap().propagate_update();
+        assert_eq!(node_b.lock().unwrap().state, data(3));
+    }
+
+    #[test]
+    fn test_state_node_remove_bidirectional_connection() {
+        let data = |value: i32| TestData {
+            value,
+            name: format!("v{value}"),
+        };
+        let node_a = StateNode::new("A".to_string(), data(1)).into_shared();
+        let node_b = StateNode::new("B".to_string(), data(2)).into_shared();
+        let node_c = StateNode::new("C".to_string(), data(3)).into_shared();
+        StateNode::connect_bidirectional(&node_a, &node_b);
+        node_c.lock().unwrap().connect(&node_a);
+        node_a.lock().unwrap().connect(&node_c);
+
+        let removed = node_b.lock().unwrap().remove_connection(&"A".to_string());
+        assert!(removed.is_some());
+        assert!(!node_a.lock().unwrap().connections.contains_key("B"));
+        assert!(node_b.lock().unwrap().connections.is_empty());
+
+        // One-way connections are removed on one side only
+        node_a.lock().unwrap().remove_connection(&"C".to_string());
+        assert!(node_c.lock().unwrap().connections.contains_key("A"));
+    }
+
+    #[test]
+    fn test_state_node_propagate_with_ttl_stops_at_cycles() {
+        let nodes: Vec<_> = (0..4)
+            .map(|i| {
+                let mut node = StateNode::new(
+                    format!("node{i}"),
+                    TestData {
+                        value: 0,
+                        name: String::new(),
+                    },
+                );
+                // Counts how often the node applied an update
+                node.set_conflict_resolver(|current: &mut TestData, remote: &TestData| {
+                    current.value += 1;
+                    current.name = remote.name.clone();
+                });
+                node.into_shared()
+            })
+            .collect();
+        // A ring with a chord, so updates can return to their origin
+        for (i, node) in nodes.iter().enumerate() {
+            StateNode::connect_bidirectional(node, &nodes[(i + 1) % nodes.len()]);
+        }
+        StateNode::connect_bidirectional(&nodes[0], &nodes[2]);
+
+        let mut origin = nodes[0].lock().unwrap();
+        origin.state.name = "update".to_string();
+        assert_eq!(origin.propagate_with_ttl(u32::MAX), 3);
+        assert_eq!(origin.propagate_with_ttl(1), 3);
+        drop(origin);
+
+        for node in &nodes[1..] {
+            let node = node.lock().unwrap();
+            assert_eq!(node.state.value, 2);
+            assert_eq!(node.state.name, "update");
+        }
+    }
+
+    #[test]
+    fn test_state_node_propagate_with_ttl_limits_hops() {
+        let nodes: Vec<_> = (0..4)
+            .map(|i| {
+                StateNode::new(
+                    format!("node{i}"),
+                    TestData {
+                        value: 0,
+                        name: String::new(),
+                    },
+                )
+                .into_shared()
+            })
+            .collect();
+        for pair in nodes.windows(2) {
+            pair[0].lock().unwrap().connect(&pair[1]);
+        }
+
+        nodes[0].lock().unwrap().state.value = 7;
+        assert_eq!(nodes[0].lock().unwrap().propagate_with_ttl(2), 2);
+        assert_eq!(nodes[2].lock().unwrap().state.value, 7);
+        assert_eq!(nodes[3].lock().unwrap().state.value, 0);
+        assert_eq!(nodes[0].lock().unwrap().propagate_with_ttl(0), 0);
+    }
+
+    #[test]
+    fn test_state_node_ignores_stale_updates() {
+        let mut node = StateNode::new(
+            "node".to_string(),
+            TestData {
+                value: 0,
+                name: String::new(),
+            },
+        );
+        let update = |sequence| zed::UpdateId {
+            origin: "remote".to_string(),
+            sequence,
+        };
+        let data = |value| TestData {
+            value,
+            name: String::new(),
+        };
+
+        assert!(node.apply_update(&update(2), data(2)));
+        assert!(!node.apply_update(&update(2), data(2)));
+        assert!(!node.apply_update(&update(1), data(1)));
+        assert!(node.has_seen(&update(1)));
+        assert!(!node.has_seen(&update(3)));
+        assert_eq!(node.state.value, 2);
+    }
+
+    #[test]
+    fn test_state_node_subscribers_see_remote_changes() {
+        use std::sync::Mutex;
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut node = StateNode::new(
+            "node".to_string(),
+            TestData {
+                value: 1,
+                name: "local".to_string(),
+            },
+        );
+        let log = Arc::clone(&seen);
+        let id = node.subscribe(move |state: &TestData| log.lock().unwrap().push(state.value));
+
+        node.resolve_conflict(TestData {
+            value: 2,
+            name: "remote".to_string(),
+        });
+        // Unchanged states are not reported
+        node.resolve_conflict(node.state.clone());
+        let other = StateNode::new(
+            "other".to_string(),
+            TestData {
+                value: 3,
+                name: "other".to_string(),
+            },
+        );
+        node.merge(&other);
+        assert_eq!(*seen.lock().unwrap(), vec![2, 3]);
+
+        // Clones do not take subscribers along
+        let mut copy = node.clone();
+        copy.resolve_conflict(TestData {
+            value: 4,
+            name: "copy".to_string(),
+        });
+        assert_eq!(seen.lock().unwrap().len(), 2);
 
-        // Now update A to have the highest value
-        node_a.state.value = 10;
-        node_a.propagate_update();
+        assert!(node.unsubscribe(id));
+        assert!(!node.unsubscribe(id));
+        node.merge(&copy);
+        assert_eq!(seen.lock().unwrap().len(), 2);
+    }
 
-        // Now all should have A's value
-        assert_eq!(node_a.connections["B"].state.value, 10);
-        assert_eq!(node_a.connections["C"].state.value, 10);
+    #[test]
+    fn test_state_node_subscriber_sees_propagated_updates() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let updates = Arc::new(AtomicUsize::new(0));
+        let mut source = StateNode::new(
+            "source".to_string(),
+            TestData {
+                value: 5,
+                name: "source".to_string(),
+            },
+        );
+        let target = StateNode::new(
+            "target".to_string(),
+            TestData {
+                value: 0,
+                name: String::new(),
+            },
+        )
+        .into_shared();
+        let counter = Arc::clone(&updates);
+        target.lock().unwrap().subscribe(move |_: &TestData| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        source.connect(&target);
+        source.propagate_update();
+        source.propagate_with_ttl(1);
+        assert_eq!(updates.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_state_node_reports_conflict_outcomes() {
+        use std::sync::Mutex;
+        use zed::ConflictOutcome;
+
+        let data = |value, name: &str| TestData {
+            value,
+            name: name.to_string(),
+        };
+        let mut node = StateNode::new("node".to_string(), data(1, "local"));
+        assert_eq!(
+            node.resolve_conflict(data(2, "remote")),
+            ConflictOutcome::AcceptedRemote
+        );
+
+        node.set_conflict_resolver(|current: &mut TestData, remote: &TestData| {
+            current.value += remote.value;
+        });
+        assert_eq!(
+            node.resolve_conflict(data(1, "remote")),
+            ConflictOutcome::Merged
+        );
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&events);
+        node.set_conflict_listener(move |event| {
+            log.lock().unwrap().push((
+                event.node.clone(),
+                event.previous.value,
+                event.remote.value,
+                event.resolved.value,
+                event.outcome,
+            ));
+        });
+        node.set_conflict_resolver_with_outcome(|current: &mut TestData, remote: &TestData| {
+            if remote.value > current.value {
+                *current = remote.clone();
+                ConflictOutcome::AcceptedRemote
+            } else {
+                ConflictOutcome::KeptLocal
+            }
+        });
+        assert!(node.on_conflict.is_some());
+
+        assert_eq!(
+            node.resolve_conflict(data(1, "older")),
+            ConflictOutcome::KeptLocal
+        );
+        assert_eq!(
+            node.merge(&StateNode::new("other".to_string(), data(9, "newer"))),
+            ConflictOutcome::AcceptedRemote
+        );
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                ("node".to_string(), 3, 1, 3, ConflictOutcome::KeptLocal),
+                ("node".to_string(), 3, 9, 9, ConflictOutcome::AcceptedRemote),
+            ]
+        );
+
+        node.clear_conflict_listener();
+        node.on_conflict = None;
+        assert_eq!(
+            node.resolve_conflict(data(0, "plain")),
+            ConflictOutcome::AcceptedRemote
+        );
+        assert_eq!(events.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_state_node_operations_preserve_concurrent_edits() {
+        #[derive(Debug)]
+        enum Edit {
+            Insert(usize, char),
+        }
+
+        let apply = |text: &String, edit: &Edit| {
+            let Edit::Insert(at, ch) = edit;
+            let mut text = text.clone();
+            text.insert(*at, *ch);
+            text
+        };
+
+        let alice = StateNode::new("alice".to_string(), "ac".to_string()).into_shared();
+        let bob = StateNode::new("bob".to_string(), "ac".to_string()).into_shared();
+        alice.lock().unwrap().set_op_reducer(apply);
+        bob.lock().unwrap().set_op_reducer(apply);
+        StateNode::connect_bidirectional(&alice, &bob);
+
+        // Both edit at the same time, each at the end and in the middle
+        assert!(alice.lock().unwrap().dispatch_op(&Edit::Insert(2, 'd')));
+        assert!(bob.lock().unwrap().dispatch_op(&Edit::Insert(1, 'b')));
+
+        assert_eq!(alice.lock().unwrap().state, "abcd");
+        assert_eq!(bob.lock().unwrap().state, "abcd");
+        alice.lock().unwrap().remove_connection(&"bob".to_string());
+    }
+
+    #[test]
+    fn test_state_node_ignores_operations_without_reducer() {
+        let mut node = StateNode::new("node".to_string(), 1i32);
+        assert!(!node.apply_remote_op(&1i32));
+
+        node.set_op_reducer(|value: &i32, delta: &i32| value + delta);
+        // Operations of other types are not applied
+        assert!(!node.apply_remote_op(&"text"));
+        assert!(!node.dispatch_op(&1u8));
+
+        let peer = StateNode::new("peer".to_string(), 0i32).into_shared();
+        node.connect(&peer);
+        assert_eq!(node.propagate_op(&2i32), 0);
+        peer.lock()
+            .unwrap()
+            .set_op_reducer(|value: &i32, delta: &i32| value + delta);
+        assert_eq!(node.propagate_op(&2i32), 1);
+        assert_eq!(node.state, 1);
+        assert_eq!(peer.lock().unwrap().state, 2);
+    }
+
+    #[test]
+    fn test_state_node_queues_updates_for_offline_peers() {
+        let data = |value: i32| TestData {
+            value,
+            name: "outbox".to_string(),
+        };
+        let mut laptop = StateNode::new("laptop".to_string(), data(0));
+        let server = StateNode::new("server".to_string(), data(0)).into_shared();
+        let phone = StateNode::new("phone".to_string(), data(0)).into_shared();
+        laptop.connect(&server);
+        laptop.connect(&phone);
+
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let log = Arc::clone(&seen);
+        server.lock().unwrap().set_conflict_resolver(
+            move |local: &mut TestData, remote: &TestData| {
+                log.lock().unwrap().push(remote.value);
+                *local = remote.clone();
+            },
+        );
+
+        assert!(laptop.mark_offline(&"server".to_string()));
+        assert!(!laptop.mark_offline(&"unknown".to_string()));
+        assert!(!laptop.is_online(&"server".to_string()));
+        for value in 1..=3 {
+            laptop.state = data(value);
+            laptop.propagate_update();
+        }
+
+        // Online peers keep receiving updates
+        assert_eq!(phone.lock().unwrap().state.value, 3);
+        assert_eq!(server.lock().unwrap().state.value, 0);
+        assert_eq!(laptop.pending_updates(&"server".to_string()), 3);
+
+        // Queued states are resolved in the order they were propagated
+        assert_eq!(laptop.mark_online(&"server".to_string()), 3);
+        assert_eq!(*seen.lock().unwrap(), vec![1, 2, 3]);
+        assert_eq!(server.lock().unwrap().state.value, 3);
+        assert!(laptop.is_online(&"server".to_string()));
+        assert_eq!(laptop.pending_updates(&"server".to_string()), 0);
+        assert_eq!(laptop.mark_online(&"server".to_string()), 0);
+    }
+
+    #[test]
+    fn test_state_node_outbox_holds_relayed_updates() {
+        let data = |value: i32| TestData {
+            value,
+            name: "relay".to_string(),
+        };
+        let mut origin = StateNode::new("origin".to_string(), data(0));
+        let relay = StateNode::new("relay".to_string(), data(0)).into_shared();
+        let edge = StateNode::new("edge".to_string(), data(0)).into_shared();
+        origin.connect(&relay);
+        relay.lock().unwrap().connect(&edge);
+        relay.lock().unwrap().mark_offline(&"edge".to_string());
+
+        origin.state = data(7);
+        assert_eq!(origin.propagate_with_ttl(2), 1);
+        assert_eq!(edge.lock().unwrap().state.value, 0);
+        assert_eq!(relay.lock().unwrap().mark_online(&"edge".to_string()), 1);
+        assert_eq!(edge.lock().unwrap().state.value, 7);
+
+        // Removing a connection drops its outbox
+        relay.lock().unwrap().mark_offline(&"edge".to_string());
+        relay.lock().unwrap().propagate_update();
+        relay.lock().unwrap().remove_connection(&"edge".to_string());
+        assert_eq!(
+            relay.lock().unwrap().pending_updates(&"edge".to_string()),
+            0
+        );
+    }
+
+    #[test]
+    fn test_state_node_commit_requires_write_quorum() {
+        let stock = |value: i32| TestData {
+            value,
+            name: "stock".to_string(),
+        };
+        let mut primary = StateNode::new("primary".to_string(), stock(10));
+        let replicas: Vec<_> = (0..3)
+            .map(|i| StateNode::new(format!("replica{i}"), stock(10)).into_shared())
+            .collect();
+        for replica in &replicas {
+            primary.connect(replica);
+            // Replicas refuse to go below zero
+            replica
+                .lock()
+                .unwrap()
+                .set_write_validator(|_: &TestData, proposed: &TestData| proposed.value >= 0);
+        }
+        primary.set_write_quorum(Some(2));
+        assert_eq!(primary.write_quorum(), Some(2));
+
+        assert_eq!(primary.commit(stock(4)), Ok(3));
+        assert!(replicas.iter().all(|r| r.lock().unwrap().state.value == 4));
+
+        assert_eq!(
+            primary.commit(stock(-1)),
+            Err(QuorumError {
+                acknowledged: 0,
+                required: 2,
+            })
+        );
+        assert_eq!(primary.state.value, 4);
+        assert!(replicas.iter().all(|r| r.lock().unwrap().state.value == 4));
+
+        // Offline peers cannot acknowledge
+        primary.mark_offline(&"replica0".to_string());
+        primary.mark_offline(&"replica1".to_string());
+        let error = primary.commit(stock(3)).unwrap_err();
+        assert_eq!(error.acknowledged, 1);
+        assert_eq!(
+            error.to_string(),
+            "write quorum not reached: 1 of 2 acknowledgements"
+        );
+        assert_eq!(primary.pending_updates(&"replica0".to_string()), 0);
+    }
+
+    #[test]
+    fn test_state_node_commit_without_quorum_always_applies() {
+        let mut node = StateNode::new("solo".to_string(), 1u32);
+        let notified = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let log = Arc::clone(&notified);
+        node.subscribe(move |state: &u32| log.lock().unwrap().push(*state));
+
+        assert_eq!(node.commit(2), Ok(0));
+        assert_eq!(node.state, 2);
+        assert_eq!(*notified.lock().unwrap(), vec![2]);
+    }
+
+    #[test]
+    fn test_state_node_metrics_count_propagations_and_conflicts() {
+        let data = |value: i32| TestData {
+            value,
+            name: "metrics".to_string(),
+        };
+        let mut origin = StateNode::new("origin".to_string(), data(5));
+        let keeper = StateNode::new("keeper".to_string(), data(9)).into_shared();
+        let taker = StateNode::new("taker".to_string(), data(0)).into_shared();
+        let offline = StateNode::new("offline".to_string(), data(0)).into_shared();
+        keeper.lock().unwrap().set_conflict_resolver_with_outcome(
+            |_: &mut TestData, _: &TestData| zed::ConflictOutcome::KeptLocal,
+        );
+        origin.connect(&keeper);
+        origin.connect(&taker);
+        origin.connect(&offline);
+        origin.mark_offline(&"offline".to_string());
+
+        origin.propagate_update();
+        origin.propagate_update();
+        let metrics = origin.metrics();
+        assert_eq!(metrics.propagations, 2);
+        assert_eq!(metrics.updates_sent, 4);
+        assert!(metrics.max_propagation_time >= metrics.average_propagation_time());
+
+        // Queued updates count once delivered
+        origin.mark_online(&"offline".to_string());
+        assert_eq!(origin.metrics().updates_sent, 6);
+
+        let keeper_metrics = keeper.lock().unwrap().metrics();
+        assert_eq!(keeper_metrics.updates_received, 2);
+        assert_eq!(keeper_metrics.conflicts_kept_local, 2);
+        let taker_metrics = taker.lock().unwrap().metrics();
+        assert_eq!(taker_metrics.conflicts_accepted_remote, 2);
+        assert_eq!(taker_metrics.conflicts(), 2);
+
+        origin.reset_metrics();
+        assert_eq!(origin.metrics(), zed::NodeMetrics::default());
+        // Clones start counting from zero
+        assert_eq!(taker.lock().unwrap().clone().metrics().conflicts(), 0);
+    }
+
+    #[test]
+    fn test_state_node_authority_breaks_version_ties() {
+        let data = |value: i32, name: &str| TestData {
+            value,
+            name: name.to_string(),
+        };
+        let server = StateNode::new("server".to_string(), data(1, "server")).into_shared();
+        let client = StateNode::new("client".to_string(), data(1, "client")).into_shared();
+        for node in [&server, &client] {
+            node.lock()
+                .unwrap()
+                .set_authority_resolver(|state: &TestData| state.value);
+        }
+        server.lock().unwrap().set_authority(5);
+        assert_eq!(server.lock().unwrap().authority(), 5);
+        StateNode::connect_bidirectional(&server, &client);
+
+        // The client loses the tie, the server wins it
+        client.lock().unwrap().propagate_update();
+        assert_eq!(server.lock().unwrap().state.name, "server");
+        server.lock().unwrap().propagate_with_ttl(1);
+        assert_eq!(client.lock().unwrap().state.name, "server");
+
+        // A newer version wins whatever its authority
+        client.lock().unwrap().state = data(2, "client");
+        client.lock().unwrap().propagate_update();
+        assert_eq!(server.lock().unwrap().state, data(2, "client"));
+
+        let outcome = client
+            .lock()
+            .unwrap()
+            .resolve_conflict_from(data(2, "admin"), 9);
+        assert_eq!(outcome, zed::ConflictOutcome::AcceptedRemote);
+
+        // Setting another resolver replaces the authority resolver
+        let mut node = StateNode::new("plain".to_string(), data(3, "local"));
+        node.set_authority_resolver(|state: &TestData| state.value);
+        node.set_conflict_resolver(|_: &mut TestData, _: &TestData| {});
+        assert_eq!(
+            node.resolve_conflict_from(data(4, "remote"), 9),
+            zed::ConflictOutcome::Merged
+        );
+        assert_eq!(node.state.name, "local");
+
+        server
+            .lock()
+            .unwrap()
+            .remove_connection(&"client".to_string());
     }
 }
@@ -0,0 +1,122 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use zed::*;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Profile {
+    name: String,
+    visits: u32,
+}
+
+fn profile() -> Profile {
+    Profile {
+        name: "ada".to_string(),
+        visits: 3,
+    }
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("zed-{}-{name}", std::process::id()));
+    let _ = fs::remove_file(&path);
+    let _ = fs::remove_dir_all(&path);
+    path
+}
+
+/// Exercises the load/save/clear contract shared by every backend.
+fn check_roundtrip(storage: &dyn StorageBackend<Profile>) {
+    assert_eq!(storage.load().unwrap(), None);
+
+    storage.save(&profile()).unwrap();
+    assert_eq!(storage.load().unwrap(), Some(profile()));
+
+    let updated = Profile {
+        visits: 4,
+        ..profile()
+    };
+    storage.save(&updated).unwrap();
+    assert_eq!(storage.load().unwrap(), Some(updated));
+
+    storage.clear().unwrap();
+    assert_eq!(storage.load().unwrap(), None);
+    storage.clear().unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_storage_roundtrip() {
+        check_roundtrip(&MemoryStorage::new());
+    }
+
+    #[test]
+    fn test_json_file_storage_roundtrip() {
+        let path = temp_path("profile.json");
+        check_roundtrip(&JsonFileStorage::new(&path));
+        assert!(!path.exists());
+    }
+
+    #[cfg(feature = "sled")]
+    #[test]
+    fn test_sled_storage_roundtrip() {
+        let path = temp_path("profile.sled");
+        check_roundtrip(&SledStorage::open(&path, "profile").unwrap());
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[cfg(feature = "redb")]
+    #[test]
+    fn test_redb_storage_roundtrip() {
+        let path = temp_path("profile.redb");
+        check_roundtrip(&RedbStorage::open(&path, "profile").unwrap());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_versioned_storage_tags_saved_state() {
+        let inner = MemoryStorage::new();
+        let storage = VersionedStorage::new(inner.clone(), 3);
+
+        storage.save(&profile()).unwrap();
+        assert_eq!(
+            inner.load().unwrap().unwrap(),
+            serde_json::json!({ "schema_version": 3, "state": { "name": "ada", "visits": 3 } })
+        );
+        assert_eq!(storage.load().unwrap(), Some(profile()));
+    }
+
+    #[test]
+    fn test_versioned_storage_chains_migrations() {
+        // Version 0 had no version tag and called the field `username`,
+        // version 1 renamed it and version 2 added `visits`
+        let inner = MemoryStorage::with_state(serde_json::json!({ "username": "ada" }));
+        let storage = VersionedStorage::new(inner, 2)
+            .register_migration(1, 2, |mut state| {
+                state["visits"] = 3.into();
+                state
+            })
+            .register_migration(
+                0,
+                1,
+                |mut state| serde_json::json!({ "name": state["username"].take() }),
+            );
+
+        assert_eq!(storage.load().unwrap(), Some(profile()));
+    }
+
+    #[test]
+    fn test_versioned_storage_missing_migration() {
+        let inner = MemoryStorage::with_state(
+            serde_json::json!({ "schema_version": 1, "state": { "name": "ada" } }),
+        );
+        let storage = VersionedStorage::new(inner, 3).register_migration(1, 2, |state| state);
+
+        let result: Result<Option<Profile>, _> = storage.load();
+        assert!(matches!(
+            result,
+            Err(PersistError::Migration { from: 2, to: 3 })
+        ));
+    }
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
@@ -190,4 +191,1455 @@ mod tests {
         assert_eq!(*counter1.lock().unwrap(), 2);
         assert_eq!(*counter2.lock().unwrap(), 2);
     }
+
+    #[test]
+    fn test_dispatch_async_thunk() {
+        let initial_state = TestState {
+            count: 0,
+            name: "initial".to_string(),
+        };
+        let store = Store::new(initial_state, Box::new(create_reducer(test_reducer)));
+
+        let handle = store.dispatch_async(|dispatch, get_state| async move {
+            dispatch(TestAction::SetName("loading".to_string()));
+            thread::sleep(Duration::from_millis(10));
+            dispatch(TestAction::Increment);
+            dispatch(TestAction::SetName("loaded".to_string()));
+            get_state().count
+        });
+
+        assert_eq!(handle.join().unwrap(), 1);
+
+        let state = store.get_state();
+        assert_eq!(state.count, 1);
+        assert_eq!(state.name, "loaded");
+    }
+
+    #[test]
+    fn test_dispatch_async_notifies_subscribers() {
+        let initial_state = TestState {
+            count: 0,
+            name: "initial".to_string(),
+        };
+        let store = Store::new(initial_state, Box::new(create_reducer(test_reducer)));
+
+        let notifications = Arc::new(Mutex::new(0));
+        let notifications_clone = Arc::clone(&notifications);
+        store.subscribe(move |_| {
+            *notifications_clone.lock().unwrap() += 1;
+        });
+
+        let handles: Vec<_> = (0..5)
+            .map(|_| {
+                store.dispatch_async(|dispatch, _| async move {
+                    dispatch(TestAction::Increment);
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(store.get_state().count, 5);
+        assert_eq!(*notifications.lock().unwrap(), 5);
+    }
+
+    #[test]
+    fn test_subscribe_selector_only_fires_on_change() {
+        let initial_state = TestState {
+            count: 0,
+            name: "initial".to_string(),
+        };
+        let store = Store::new(initial_state, Box::new(create_reducer(test_reducer)));
+
+        let counts = Arc::new(Mutex::new(Vec::new()));
+        let counts_clone = Arc::clone(&counts);
+
+        store.subscribe_selector(
+            |state: &TestState| state.count,
+            move |count: &i32| {
+                counts_clone.lock().unwrap().push(*count);
+            },
+        );
+
+        store.dispatch(TestAction::SetName("renamed".to_string()));
+        store.dispatch(TestAction::Increment);
+        store.dispatch(TestAction::SetName("again".to_string()));
+        store.dispatch(TestAction::Increment);
+        store.dispatch(TestAction::Decrement);
+
+        assert_eq!(*counts.lock().unwrap(), vec![1, 2, 1]);
+    }
+
+    #[test]
+    fn test_subscribe_selector_unsubscribe() {
+        let initial_state = TestState {
+            count: 0,
+            name: "initial".to_string(),
+        };
+        let store = Store::new(initial_state, Box::new(create_reducer(test_reducer)));
+
+        let names = Arc::new(Mutex::new(Vec::new()));
+        let names_clone = Arc::clone(&names);
+
+        let id = store.subscribe_selector(
+            |state: &TestState| state.name.clone(),
+            move |name: &String| {
+                names_clone.lock().unwrap().push(name.clone());
+            },
+        );
+
+        store.dispatch(TestAction::SetName("first".to_string()));
+        assert!(store.unsubscribe(id));
+        store.dispatch(TestAction::SetName("second".to_string()));
+
+        assert_eq!(*names.lock().unwrap(), vec!["first".to_string()]);
+    }
+
+    #[test]
+    fn test_change_detection_skips_noop_notifications() {
+        let initial_state = TestState {
+            count: 0,
+            name: "initial".to_string(),
+        };
+        let store = Store::new(initial_state, Box::new(create_reducer(test_reducer)))
+            .with_change_detection();
+
+        let notifications = Arc::new(Mutex::new(0));
+        let notifications_clone = Arc::clone(&notifications);
+        store.subscribe(move |_| {
+            *notifications_clone.lock().unwrap() += 1;
+        });
+
+        store.dispatch(TestAction::SetName("initial".to_string()));
+        assert_eq!(*notifications.lock().unwrap(), 0);
+
+        store.dispatch(TestAction::Increment);
+        assert_eq!(*notifications.lock().unwrap(), 1);
+
+        // A batch that ends up changing nothing is skipped as a whole
+        store.dispatch_batch(vec![
+            TestAction::SetName("initial".to_string()),
+            TestAction::SetName("initial".to_string()),
+        ]);
+        assert_eq!(*notifications.lock().unwrap(), 1);
+
+        // Only the net effect of a batch is compared
+        store.dispatch_batch(vec![TestAction::Increment, TestAction::Decrement]);
+        assert_eq!(*notifications.lock().unwrap(), 1);
+
+        store.dispatch_batch(vec![TestAction::Increment, TestAction::Increment]);
+        assert_eq!(*notifications.lock().unwrap(), 2);
+        assert_eq!(store.get_state().count, 3);
+    }
+
+    #[test]
+    fn test_without_change_detection_always_notifies() {
+        let initial_state = TestState {
+            count: 0,
+            name: "initial".to_string(),
+        };
+        let store = Store::new(initial_state, Box::new(create_reducer(test_reducer)));
+
+        let notifications = Arc::new(Mutex::new(0));
+        let notifications_clone = Arc::clone(&notifications);
+        store.subscribe(move |_| {
+            *notifications_clone.lock().unwrap() += 1;
+        });
+
+        store.dispatch(TestAction::SetName("initial".to_string()));
+        assert_eq!(*notifications.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_get_state_shared_snapshots() {
+        let initial_state = TestState {
+            count: 0,
+            name: "initial".to_string(),
+        };
+        let store = Store::new(initial_state, Box::new(create_reducer(test_reducer)));
+
+        let before = store.get_state_shared();
+        store.dispatch(TestAction::Increment);
+        let after = store.get_state_shared();
+
+        assert_eq!(before.count, 0);
+        assert_eq!(after.count, 1);
+
+        // Reading twice without a dispatch yields the same snapshot
+        assert!(Arc::ptr_eq(&after, &store.get_state_shared()));
+    }
+
+    #[test]
+    fn test_get_state_shared_concurrent_readers_during_dispatch() {
+        let initial_state = TestState {
+            count: 0,
+            name: "initial".to_string(),
+        };
+        let store = Arc::new(Store::new(
+            initial_state,
+            Box::new(create_reducer(test_reducer)),
+        ));
+
+        let writer = {
+            let store = Arc::clone(&store);
+            thread::spawn(move || {
+                for _ in 0..200 {
+                    store.dispatch(TestAction::Increment);
+                }
+            })
+        };
+
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let store = Arc::clone(&store);
+                thread::spawn(move || {
+                    let mut last = 0;
+                    for _ in 0..200 {
+                        let count = store.get_state_shared().count;
+                        assert!(count >= last);
+                        last = count;
+                    }
+                })
+            })
+            .collect();
+
+        writer.join().unwrap();
+        for reader in readers {
+            reader.join().unwrap();
+        }
+
+        assert_eq!(store.get_state_shared().count, 200);
+    }
+
+    fn validated_store() -> Store<TestState, TestAction> {
+        let initial_state = TestState {
+            count: 0,
+            name: "initial".to_string(),
+        };
+        Store::new(
+            initial_state,
+            Box::new(create_try_reducer(
+                |state: &TestState, action: &TestAction| match action {
+                    TestAction::Decrement if state.count == 0 => {
+                        Err(format!("cannot decrement {}", state.name))
+                    }
+                    TestAction::SetName(name) if name.is_empty() => {
+                        Err("name must not be empty".to_string())
+                    }
+                    _ => Ok(test_reducer(state, action)),
+                },
+            )),
+        )
+    }
+
+    #[test]
+    fn test_dispatch_try_reports_errors() {
+        let store = validated_store();
+
+        let notifications = Arc::new(Mutex::new(0));
+        let notifications_clone = Arc::clone(&notifications);
+        store.subscribe(move |_| {
+            *notifications_clone.lock().unwrap() += 1;
+        });
+
+        let error = store.dispatch_try(TestAction::Decrement).unwrap_err();
+        assert_eq!(error.to_string(), "cannot decrement initial");
+        assert_eq!(store.get_state().count, 0);
+        assert_eq!(*notifications.lock().unwrap(), 0);
+
+        assert!(store.dispatch_try(TestAction::Increment).is_ok());
+        assert_eq!(store.get_state().count, 1);
+        assert_eq!(*notifications.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_subscribe_errors() {
+        let store = validated_store();
+
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let errors_clone = Arc::clone(&errors);
+        let id = store.subscribe_errors(move |error| {
+            errors_clone.lock().unwrap().push(error.to_string());
+        });
+
+        store.dispatch(TestAction::Decrement);
+        store.dispatch_batch(vec![
+            TestAction::Increment,
+            TestAction::SetName(String::new()),
+            TestAction::SetName("valid".to_string()),
+        ]);
+
+        // The rejected action in the batch is skipped, the others apply
+        let state = store.get_state();
+        assert_eq!(state.count, 1);
+        assert_eq!(state.name, "valid");
+        assert_eq!(
+            *errors.lock().unwrap(),
+            vec![
+                "cannot decrement initial".to_string(),
+                "name must not be empty".to_string()
+            ]
+        );
+
+        assert!(store.unsubscribe(id));
+        store.dispatch(TestAction::SetName(String::new()));
+        assert_eq!(errors.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_listener_dispatches_follow_up_actions() {
+        let initial_state = TestState {
+            count: 0,
+            name: "initial".to_string(),
+        };
+        let store = Store::new(initial_state, Box::new(create_reducer(test_reducer)));
+
+        store.add_listener(
+            |action| matches!(action, TestAction::Reset),
+            |_, store| {
+                let count = store.get_state().count;
+                store.dispatch(TestAction::SetName(format!("reset at {count}")));
+            },
+        );
+
+        store.dispatch(TestAction::Increment);
+        assert_eq!(store.get_state().name, "initial");
+
+        store.dispatch(TestAction::Reset);
+        assert_eq!(store.get_state().name, "reset at 0");
+    }
+
+    #[test]
+    fn test_listener_sees_batched_actions_and_unsubscribes() {
+        let initial_state = TestState {
+            count: 0,
+            name: "initial".to_string(),
+        };
+        let store = Store::new(initial_state, Box::new(create_reducer(test_reducer)));
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let seen_clone = Arc::clone(&seen);
+        let id = store.add_listener(
+            |action| matches!(action, TestAction::Increment | TestAction::Decrement),
+            move |action, store| {
+                seen_clone
+                    .lock()
+                    .unwrap()
+                    .push((format!("{action:?}"), store.get_state().count));
+            },
+        );
+
+        store.dispatch_batch(vec![
+            TestAction::Increment,
+            TestAction::SetName("batch".to_string()),
+            TestAction::Decrement,
+        ]);
+
+        // Listeners run after the batch is fully applied
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![("Increment".to_string(), 0), ("Decrement".to_string(), 0)]
+        );
+
+        assert!(store.unsubscribe(id));
+        store.dispatch(TestAction::Increment);
+        assert_eq!(seen.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_background_notifications_do_not_block_dispatch() {
+        let initial_state = TestState {
+            count: 0,
+            name: "initial".to_string(),
+        };
+        let store = Store::new(initial_state, Box::new(create_reducer(test_reducer)))
+            .with_background_notifications();
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let seen_clone = Arc::clone(&seen);
+        store.subscribe(move |state: &TestState| {
+            thread::sleep(Duration::from_millis(50));
+            seen_clone.lock().unwrap().push(state.count);
+        });
+
+        let start = std::time::Instant::now();
+        for _ in 0..3 {
+            store.dispatch(TestAction::Increment);
+        }
+        assert!(start.elapsed() < Duration::from_millis(150));
+        assert_eq!(store.get_state().count, 3);
+
+        store.flush();
+        assert_eq!(*seen.lock().unwrap(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_flush_without_background_notifications() {
+        let initial_state = TestState {
+            count: 0,
+            name: "initial".to_string(),
+        };
+        let store = Store::new(initial_state, Box::new(create_reducer(test_reducer)));
+
+        let seen = Arc::new(Mutex::new(0));
+        let seen_clone = Arc::clone(&seen);
+        store.subscribe(move |_| *seen_clone.lock().unwrap() += 1);
+
+        store.dispatch(TestAction::Increment);
+        store.flush();
+        assert_eq!(*seen.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_subscription_guard_unsubscribes_on_drop() {
+        let initial_state = TestState {
+            count: 0,
+            name: "initial".to_string(),
+        };
+        let store = Store::new(initial_state, Box::new(create_reducer(test_reducer)));
+
+        let calls = Arc::new(Mutex::new(0));
+        let calls_clone = Arc::clone(&calls);
+        let guard = store.subscribe_scoped(move |_| *calls_clone.lock().unwrap() += 1);
+        assert_eq!(store.subscriber_count(), 1);
+
+        store.dispatch(TestAction::Increment);
+        drop(guard);
+        store.dispatch(TestAction::Increment);
+
+        assert_eq!(store.subscriber_count(), 0);
+        assert_eq!(*calls.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_subscription_guard_detach() {
+        let initial_state = TestState {
+            count: 0,
+            name: "initial".to_string(),
+        };
+        let store = Store::new(initial_state, Box::new(create_reducer(test_reducer)));
+
+        let guard = store.subscribe_scoped(|_| {});
+        let id = guard.id();
+        assert_eq!(guard.detach(), id);
+
+        assert_eq!(store.subscriber_count(), 1);
+        assert!(store.unsubscribe(id));
+    }
+
+    #[test]
+    fn test_weak_subscription_calls_owner_method() {
+        struct Widget {
+            renders: Mutex<Vec<i32>>,
+        }
+
+        impl Widget {
+            fn render(&self, state: &TestState) {
+                self.renders.lock().unwrap().push(state.count);
+            }
+        }
+
+        let initial_state = TestState {
+            count: 0,
+            name: "initial".to_string(),
+        };
+        let store = Store::new(initial_state, Box::new(create_reducer(test_reducer)));
+
+        let widget = Arc::new(Widget {
+            renders: Mutex::new(Vec::new()),
+        });
+        store.subscribe_weak(&Arc::downgrade(&widget), Widget::render);
+
+        store.dispatch(TestAction::Increment);
+        store.dispatch(TestAction::Increment);
+        assert_eq!(*widget.renders.lock().unwrap(), vec![1, 2]);
+        assert_eq!(store.subscriber_count(), 1);
+    }
+
+    #[test]
+    fn test_weak_subscription_expires_with_owner() {
+        let initial_state = TestState {
+            count: 0,
+            name: "initial".to_string(),
+        };
+        let store = Store::new(initial_state, Box::new(create_reducer(test_reducer)));
+
+        let owner = Arc::new(Mutex::new(0));
+        store.subscribe_weak(&Arc::downgrade(&owner), |calls, _| {
+            *calls.lock().unwrap() += 1
+        });
+        store.subscribe(|_| {});
+
+        store.dispatch(TestAction::Increment);
+        assert_eq!(*owner.lock().unwrap(), 1);
+
+        drop(owner);
+        store.dispatch(TestAction::Increment);
+        assert_eq!(store.subscriber_count(), 1);
+    }
+
+    #[test]
+    fn test_paused_subscriber_skips_notifications() {
+        let initial_state = TestState {
+            count: 0,
+            name: "initial".to_string(),
+        };
+        let store = Store::new(initial_state, Box::new(create_reducer(test_reducer)));
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let seen_clone = Arc::clone(&seen);
+        let id =
+            store.subscribe(move |state: &TestState| seen_clone.lock().unwrap().push(state.count));
+
+        assert!(store.pause_subscriber(id));
+        store.dispatch(TestAction::Increment);
+        assert_eq!(store.subscriber_count(), 1);
+
+        assert!(store.resume_subscriber(id));
+        assert!(seen.lock().unwrap().is_empty());
+
+        store.dispatch(TestAction::Increment);
+        assert_eq!(*seen.lock().unwrap(), vec![2]);
+        assert!(!store.pause_subscriber(999));
+    }
+
+    #[test]
+    fn test_resume_subscriber_and_replay() {
+        let initial_state = TestState {
+            count: 0,
+            name: "initial".to_string(),
+        };
+        let store = Store::new(initial_state, Box::new(create_reducer(test_reducer)));
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let seen_clone = Arc::clone(&seen);
+        let id =
+            store.subscribe(move |state: &TestState| seen_clone.lock().unwrap().push(state.count));
+
+        // Nothing was missed, so nothing is replayed
+        store.pause_subscriber(id);
+        store.resume_subscriber_and_replay(id);
+        assert!(seen.lock().unwrap().is_empty());
+
+        store.pause_subscriber(id);
+        store.dispatch(TestAction::Increment);
+        store.dispatch(TestAction::Increment);
+        store.resume_subscriber_and_replay(id);
+        assert_eq!(*seen.lock().unwrap(), vec![2]);
+    }
+
+    #[test]
+    fn test_optimistic_commit_keeps_change() {
+        let initial_state = TestState {
+            count: 0,
+            name: "initial".to_string(),
+        };
+        let store = Store::new(initial_state, Box::new(create_reducer(test_reducer)));
+
+        let handle = store.dispatch_optimistic(TestAction::Increment);
+        assert!(handle.is_pending());
+        assert_eq!(store.get_state().count, 1);
+
+        handle.commit();
+        store.dispatch(TestAction::Increment);
+        assert_eq!(store.get_state().count, 2);
+    }
+
+    #[test]
+    fn test_optimistic_revert_replays_later_actions() {
+        let initial_state = TestState {
+            count: 0,
+            name: "initial".to_string(),
+        };
+        let store = Store::new(initial_state, Box::new(create_reducer(test_reducer)));
+
+        let notifications = Arc::new(Mutex::new(Vec::new()));
+        let notifications_clone = Arc::clone(&notifications);
+        store.subscribe(move |state: &TestState| {
+            notifications_clone.lock().unwrap().push(state.count)
+        });
+
+        store.dispatch(TestAction::Increment);
+        let first = store.dispatch_optimistic(TestAction::Increment);
+        let second = store.dispatch_optimistic(TestAction::SetName("pending".to_string()));
+        store.dispatch_batch(vec![TestAction::Increment, TestAction::Increment]);
+        assert_eq!(store.get_state().count, 4);
+
+        first.revert();
+        assert_eq!(
+            store.get_state(),
+            TestState {
+                count: 3,
+                name: "pending".to_string(),
+            }
+        );
+
+        // Dropping an unsettled handle reverts too
+        drop(second);
+        assert_eq!(store.get_state().name, "initial");
+        assert_eq!(*notifications.lock().unwrap(), vec![1, 2, 2, 4, 3, 3]);
+    }
+
+    #[test]
+    fn test_optimistic_rejected_action_is_not_pending() {
+        let store = validated_store();
+        let errors = Arc::new(Mutex::new(0));
+        let errors_clone = Arc::clone(&errors);
+        store.subscribe_errors(move |_| *errors_clone.lock().unwrap() += 1);
+
+        let handle = store.dispatch_optimistic(TestAction::Decrement);
+        assert!(!handle.is_pending());
+        assert_eq!(*errors.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_replace_state_notifies_subscribers() {
+        let initial_state = TestState {
+            count: 0,
+            name: "initial".to_string(),
+        };
+        let store = Store::new(initial_state, Box::new(create_reducer(test_reducer)));
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let seen_clone = Arc::clone(&seen);
+        store.subscribe(move |state: &TestState| seen_clone.lock().unwrap().push(state.clone()));
+
+        let hydrated = TestState {
+            count: 10,
+            name: "server".to_string(),
+        };
+        store.replace_state(hydrated.clone());
+        store.dispatch(TestAction::Increment);
+
+        assert_eq!(store.get_state().count, 11);
+        assert_eq!(seen.lock().unwrap()[0], hydrated);
+        assert_eq!(seen.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_replace_state_settles_optimistic_actions() {
+        let initial_state = TestState {
+            count: 0,
+            name: "initial".to_string(),
+        };
+        let store = Store::new(initial_state, Box::new(create_reducer(test_reducer)))
+            .with_change_detection();
+
+        let calls = Arc::new(Mutex::new(0));
+        let calls_clone = Arc::clone(&calls);
+        store.subscribe(move |_| *calls_clone.lock().unwrap() += 1);
+
+        let handle = store.dispatch_optimistic(TestAction::Increment);
+        store.replace_state(TestState {
+            count: 5,
+            name: "imported".to_string(),
+        });
+        handle.revert();
+        assert_eq!(store.get_state().count, 5);
+
+        // Replacing with an equal state is a no-op under change detection
+        store.replace_state(store.get_state());
+        assert_eq!(*calls.lock().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_metrics_count_actions_per_variant() {
+        let initial_state = TestState {
+            count: 0,
+            name: "initial".to_string(),
+        };
+        let store =
+            Store::new(initial_state, Box::new(create_reducer(test_reducer))).with_metrics();
+        store.subscribe(|_| {});
+
+        store.dispatch(TestAction::Increment);
+        store.dispatch(TestAction::SetName("metrics".to_string()));
+        store.dispatch_batch(vec![TestAction::Increment, TestAction::Increment]);
+
+        let metrics = store.metrics();
+        assert_eq!(metrics.reducer_runs, 4);
+        assert_eq!(metrics.notifications, 3);
+        assert_eq!(metrics.actions.len(), 2);
+        assert_eq!(metrics.actions["Increment"].count, 3);
+        assert_eq!(metrics.actions["SetName"].count, 1);
+
+        let increment = &metrics.actions["Increment"];
+        assert!(increment.max_reducer_time <= increment.total_reducer_time);
+        assert!(increment.average_reducer_time() <= increment.max_reducer_time);
+    }
+
+    #[test]
+    fn test_metrics_reset_and_disabled() {
+        let initial_state = TestState {
+            count: 0,
+            name: "initial".to_string(),
+        };
+        let plain = Store::new(
+            initial_state.clone(),
+            Box::new(create_reducer(test_reducer)),
+        );
+        plain.dispatch(TestAction::Increment);
+        assert_eq!(plain.metrics(), StoreMetrics::default());
+
+        let store =
+            Store::new(initial_state, Box::new(create_reducer(test_reducer))).with_metrics();
+        store.dispatch(TestAction::Increment);
+        store.reset_metrics();
+        assert_eq!(store.metrics(), StoreMetrics::default());
+    }
+
+    #[test]
+    fn test_dispatch_with_priority_reduces_high_priority_first() {
+        let store = Store::new(
+            TestState {
+                count: 0,
+                name: "initial".to_string(),
+            },
+            Box::new(create_reducer(test_reducer)),
+        );
+        let names = Arc::new(Mutex::new(Vec::new()));
+
+        let queue_store = store.clone();
+        let seen = Arc::clone(&names);
+        store.subscribe(move |state: &TestState| {
+            seen.lock().unwrap().push(state.name.clone());
+            // Queued while the first action is still being handled
+            if state.name == "first" {
+                queue_store
+                    .dispatch_with_priority(TestAction::SetName("analytics".into()), Priority::Low);
+                queue_store
+                    .dispatch_with_priority(TestAction::SetName("input".into()), Priority::High);
+                assert_eq!(queue_store.queued_actions(), 2);
+            }
+        });
+
+        store.dispatch_with_priority(TestAction::SetName("first".into()), Priority::Normal);
+
+        assert_eq!(*names.lock().unwrap(), vec!["first", "input", "analytics"]);
+        assert_eq!(store.queued_actions(), 0);
+    }
+
+    #[test]
+    fn test_dispatch_with_priority_recovers_after_panic() {
+        let store = Store::new(
+            TestState {
+                count: 0,
+                name: "initial".to_string(),
+            },
+            Box::new(create_reducer(|state: &TestState, action: &TestAction| {
+                if let TestAction::Reset = action {
+                    panic!("reset not supported");
+                }
+                test_reducer(state, action)
+            })),
+        );
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            store.dispatch_with_priority(TestAction::Reset, Priority::High)
+        }));
+        assert!(result.is_err());
+
+        store.dispatch_with_priority(TestAction::Increment, Priority::Low);
+        assert_eq!(store.get_state().count, 1);
+    }
+
+    #[test]
+    fn test_batch_guard_notifies_once_on_drop() {
+        let store = Store::new(
+            TestState {
+                count: 0,
+                name: "initial".to_string(),
+            },
+            Box::new(create_reducer(test_reducer)),
+        );
+        let notified = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&notified);
+        store.subscribe(move |state: &TestState| seen.lock().unwrap().push(state.count));
+        let listened = Arc::new(Mutex::new(0));
+        let count = Arc::clone(&listened);
+        store.add_listener(
+            |_| true,
+            move |_: &TestAction, _: &Store<TestState, TestAction>| {
+                *count.lock().unwrap() += 1;
+            },
+        );
+
+        {
+            let batch = store.batch();
+            batch.dispatch(TestAction::Increment);
+            batch.dispatch(TestAction::Increment);
+            assert_eq!(store.get_state().count, 2);
+            assert_eq!(batch.len(), 2);
+            assert!(notified.lock().unwrap().is_empty());
+            assert_eq!(*listened.lock().unwrap(), 0);
+        }
+
+        assert_eq!(*notified.lock().unwrap(), vec![2]);
+        assert_eq!(*listened.lock().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_batch_guard_skips_notification_without_changes() {
+        let store = Store::new(
+            TestState {
+                count: 0,
+                name: "initial".to_string(),
+            },
+            Box::new(create_reducer(test_reducer)),
+        )
+        .with_change_detection();
+        let notified = Arc::new(Mutex::new(0));
+        let seen = Arc::clone(&notified);
+        store.subscribe(move |_: &TestState| *seen.lock().unwrap() += 1);
+
+        store.batch().end();
+        assert_eq!(*notified.lock().unwrap(), 0);
+
+        let batch = store.batch();
+        batch.dispatch(TestAction::Increment);
+        batch.dispatch(TestAction::Decrement);
+        batch.end();
+        assert_eq!(*notified.lock().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_read_guard_borrows_snapshot() {
+        let store = Store::new(
+            TestState {
+                count: 0,
+                name: "initial".to_string(),
+            },
+            Box::new(create_reducer(test_reducer)),
+        );
+
+        let before = store.read();
+        store.dispatch(TestAction::Increment);
+
+        assert_eq!(before.count, 0);
+        assert_eq!(before.name, "initial");
+        assert_eq!(store.read().count, 1);
+
+        let snapshot = store.read().into_arc();
+        assert!(Arc::ptr_eq(&snapshot, &store.get_state_shared()));
+    }
+
+    #[test]
+    fn test_subscribers_share_the_stored_snapshot() {
+        let store = Store::new(
+            TestState {
+                count: 0,
+                name: "initial".to_string(),
+            },
+            Box::new(create_reducer(test_reducer)),
+        );
+        let shared = Arc::new(Mutex::new(None));
+
+        let reader = store.clone();
+        let result = Arc::clone(&shared);
+        store.subscribe(move |state: &TestState| {
+            let snapshot = reader.get_state_shared();
+            *result.lock().unwrap() = Some(std::ptr::eq(state, &*snapshot));
+        });
+        store.dispatch(TestAction::Increment);
+
+        assert_eq!(*shared.lock().unwrap(), Some(true));
+    }
+
+    #[test]
+    fn test_subscribers_run_in_priority_then_registration_order() {
+        let store = Store::new(
+            TestState {
+                count: 0,
+                name: "initial".to_string(),
+            },
+            Box::new(create_reducer(test_reducer)),
+        );
+        let order = Arc::new(Mutex::new(Vec::new()));
+
+        for (name, priority) in [
+            ("persist", 0),
+            ("validate", 10),
+            ("log", 0),
+            ("audit", -5),
+            ("normalize", 10),
+        ] {
+            let order = Arc::clone(&order);
+            store.subscribe_with_priority(priority, move |_: &TestState| {
+                order.lock().unwrap().push(name)
+            });
+        }
+        let order_default = Arc::clone(&order);
+        store.subscribe(move |_: &TestState| order_default.lock().unwrap().push("default"));
+
+        store.dispatch(TestAction::Increment);
+        assert_eq!(
+            *order.lock().unwrap(),
+            vec![
+                "validate",
+                "normalize",
+                "persist",
+                "log",
+                "default",
+                "audit"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unsubscribe_keeps_priority_order() {
+        let store = Store::new(
+            TestState {
+                count: 0,
+                name: "initial".to_string(),
+            },
+            Box::new(create_reducer(test_reducer)),
+        );
+        let order = Arc::new(Mutex::new(Vec::new()));
+
+        let mut ids = Vec::new();
+        for (name, priority) in [("low", 1), ("high", 3), ("middle", 2)] {
+            let order = Arc::clone(&order);
+            ids.push(
+                store.subscribe_with_priority(priority, move |_: &TestState| {
+                    order.lock().unwrap().push(name)
+                }),
+            );
+        }
+
+        assert!(store.unsubscribe(ids[2]));
+        store.dispatch(TestAction::Increment);
+        assert_eq!(*order.lock().unwrap(), vec!["high", "low"]);
+        assert_eq!(store.subscriber_count(), 2);
+    }
+
+    #[test]
+    fn test_subscribe_filtered_only_fires_when_predicate_holds() {
+        let store = Store::new(
+            TestState {
+                count: 0,
+                name: "initial".to_string(),
+            },
+            Box::new(create_reducer(test_reducer)),
+        );
+        let seen = Arc::new(Mutex::new(Vec::new()));
+
+        let matched = Arc::clone(&seen);
+        let id = store.subscribe_filtered(
+            |state: &TestState| state.count > 1,
+            move |state: &TestState| matched.lock().unwrap().push(state.count),
+        );
+
+        store.dispatch(TestAction::Increment);
+        store.dispatch(TestAction::Increment);
+        store.dispatch(TestAction::SetName("unchanged count".into()));
+        store.dispatch(TestAction::Reset);
+        assert_eq!(*seen.lock().unwrap(), vec![2, 2]);
+
+        assert!(store.unsubscribe(id));
+        store.dispatch(TestAction::Increment);
+        store.dispatch(TestAction::Increment);
+        assert_eq!(seen.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_dispatch_checked_reports_change_and_new_state() {
+        let store = Store::new(
+            TestState {
+                count: 0,
+                name: "initial".to_string(),
+            },
+            Box::new(create_reducer(test_reducer)),
+        )
+        .with_change_detection();
+        let notifications = Arc::new(Mutex::new(0));
+        let counter = Arc::clone(&notifications);
+        store.subscribe(move |_: &TestState| *counter.lock().unwrap() += 1);
+
+        let outcome = store.dispatch_checked(TestAction::Increment).unwrap();
+        assert!(outcome.changed());
+        assert_eq!(outcome.count, 1);
+        assert!(Arc::ptr_eq(outcome.state(), &store.get_state_shared()));
+
+        let outcome = store
+            .dispatch_checked(TestAction::SetName("initial".into()))
+            .unwrap();
+        assert!(!outcome.changed());
+        assert_eq!(*outcome.into_state(), store.get_state());
+        assert_eq!(*notifications.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_dispatch_checked_returns_reducer_errors() {
+        let store = Store::new(
+            0u32,
+            Box::new(create_try_reducer(|count: &u32, _: &TestAction| {
+                count.checked_sub(1).ok_or("negative count")
+            })),
+        );
+
+        let error = store.dispatch_checked(TestAction::Decrement).unwrap_err();
+        assert_eq!(error.to_string(), "negative count");
+        assert_eq!(store.get_state(), 0);
+    }
+
+    #[test]
+    fn test_subscriber_can_dispatch_follow_up_actions() {
+        let store = Store::new(
+            TestState {
+                count: 0,
+                name: "initial".to_string(),
+            },
+            Box::new(create_reducer(test_reducer)),
+        );
+        let seen = Arc::new(Mutex::new(Vec::new()));
+
+        let inner = store.clone();
+        store.subscribe(move |state: &TestState| {
+            if state.name == "initial" {
+                inner.dispatch(TestAction::SetName("follow-up".into()));
+            }
+        });
+        let later = Arc::clone(&seen);
+        store.subscribe(move |state: &TestState| {
+            later.lock().unwrap().push(state.name.clone());
+        });
+
+        store.dispatch(TestAction::Increment);
+
+        assert_eq!(store.get_state().name, "follow-up");
+        // The second subscriber never sees the outdated state afterwards
+        assert_eq!(*seen.lock().unwrap(), vec!["follow-up"]);
+    }
+
+    #[test]
+    fn test_subscriber_can_unsubscribe_itself_and_subscribe_others() {
+        let store = Store::new(
+            TestState {
+                count: 0,
+                name: "initial".to_string(),
+            },
+            Box::new(create_reducer(test_reducer)),
+        );
+        let own_id = Arc::new(Mutex::new(None));
+
+        let inner = store.clone();
+        let id_slot = Arc::clone(&own_id);
+        let id = store.subscribe(move |_: &TestState| {
+            if let Some(id) = id_slot.lock().unwrap().take() {
+                inner.unsubscribe(id);
+                inner.subscribe(|_: &TestState| {});
+            }
+        });
+        *own_id.lock().unwrap() = Some(id);
+
+        store.dispatch(TestAction::Increment);
+        store.dispatch(TestAction::Increment);
+
+        assert_eq!(store.subscriber_count(), 1);
+        assert_eq!(store.get_state().count, 2);
+    }
+
+    #[test]
+    fn test_builder_applies_configuration() {
+        let store = Store::builder(
+            TestState {
+                count: 0,
+                name: "initial".to_string(),
+            },
+            create_reducer(|state: &TestState, action: &TestAction| match action {
+                TestAction::Reset => panic!("reset is not supported"),
+                _ => test_reducer(state, action),
+            }),
+        )
+        .panic_policy(PanicPolicy::Recover)
+        .subscriber_capacity(8)
+        .change_detection()
+        .metrics()
+        .build();
+        let notifications = Arc::new(Mutex::new(0));
+        let counter = Arc::clone(&notifications);
+        store.subscribe(move |_: &TestState| *counter.lock().unwrap() += 1);
+
+        store.dispatch(TestAction::Increment);
+        store.dispatch(TestAction::SetName("initial".into()));
+        assert!(store.dispatch_try(TestAction::Reset).is_err());
+
+        assert_eq!(store.get_state().count, 1);
+        assert_eq!(*notifications.lock().unwrap(), 1);
+        assert_eq!(store.metrics().reducer_runs, 3);
+    }
+
+    #[test]
+    fn test_coalesced_notifications_skip_intermediate_states() {
+        let store = Store::builder(
+            TestState {
+                count: 0,
+                name: "initial".to_string(),
+            },
+            create_reducer(test_reducer),
+        )
+        .notification_mode(NotificationMode::Coalesced)
+        .build();
+        let (started, wait_started) = std::sync::mpsc::channel();
+        let (release, wait_release) = std::sync::mpsc::channel::<()>();
+        let wait_release = Mutex::new(wait_release);
+        let seen = Arc::new(Mutex::new(Vec::new()));
+
+        let counts = Arc::clone(&seen);
+        store.subscribe(move |state: &TestState| {
+            counts.lock().unwrap().push(state.count);
+            if state.count == 1 {
+                started.send(()).unwrap();
+                wait_release.lock().unwrap().recv().unwrap();
+            }
+        });
+
+        store.dispatch(TestAction::Increment);
+        wait_started.recv().unwrap();
+        for _ in 0..4 {
+            store.dispatch(TestAction::Increment);
+        }
+        release.send(()).unwrap();
+        store.flush();
+
+        assert_eq!(*seen.lock().unwrap(), vec![1, 5]);
+    }
+
+    #[test]
+    fn test_parallel_notifications_complete_before_dispatch_returns() {
+        let store = Store::builder(
+            TestState {
+                count: 0,
+                name: "initial".to_string(),
+            },
+            create_reducer(test_reducer),
+        )
+        .parallel_notifications(4)
+        .build();
+        let threads = Arc::new(Mutex::new(Vec::new()));
+
+        for _ in 0..8 {
+            let threads = Arc::clone(&threads);
+            store.subscribe(move |_: &TestState| {
+                thread::sleep(Duration::from_millis(5));
+                threads.lock().unwrap().push(thread::current().id());
+            });
+        }
+
+        store.dispatch(TestAction::Increment);
+
+        let mut threads = threads.lock().unwrap().clone();
+        assert_eq!(threads.len(), 8);
+        threads.sort_by_key(|id| format!("{id:?}"));
+        threads.dedup();
+        assert_eq!(threads.len(), 4);
+        assert!(threads.contains(&thread::current().id()));
+    }
+
+    #[test]
+    fn test_parallel_notifications_reuse_their_threads() {
+        let store = Store::new(
+            TestState {
+                count: 0,
+                name: "initial".to_string(),
+            },
+            Box::new(create_reducer(test_reducer)),
+        )
+        .with_parallel_notifications(3);
+        let threads = Arc::new(Mutex::new(Vec::new()));
+
+        for _ in 0..6 {
+            let threads = Arc::clone(&threads);
+            store.subscribe(move |_: &TestState| {
+                threads.lock().unwrap().push(thread::current().id());
+            });
+        }
+
+        for _ in 0..20 {
+            store.dispatch(TestAction::Increment);
+        }
+
+        let mut threads = threads.lock().unwrap().clone();
+        assert_eq!(threads.len(), 120);
+        threads.sort_by_key(|id| format!("{id:?}"));
+        threads.dedup();
+        assert_eq!(threads.len(), 3);
+    }
+
+    #[test]
+    fn test_parallel_notifications_allow_dispatch_from_subscribers() {
+        let store = Store::new(
+            TestState {
+                count: 0,
+                name: "initial".to_string(),
+            },
+            Box::new(create_reducer(test_reducer)),
+        )
+        .with_parallel_notifications(2);
+        let dispatched = Arc::new(AtomicBool::new(false));
+        let caller = thread::current().id();
+
+        for _ in 0..4 {
+            let inner = store.clone();
+            let dispatched = Arc::clone(&dispatched);
+            store.subscribe(move |_: &TestState| {
+                // Dispatch from a fan-out thread, which must not wait on itself
+                if thread::current().id() != caller && !dispatched.swap(true, Ordering::SeqCst) {
+                    inner.dispatch(TestAction::Increment);
+                }
+            });
+        }
+
+        store.dispatch(TestAction::Increment);
+
+        assert_eq!(store.get_state().count, 2);
+    }
+
+    #[test]
+    fn test_parallel_notifications_drop_expired_subscribers() {
+        let store = Store::new(
+            TestState {
+                count: 0,
+                name: "initial".to_string(),
+            },
+            Box::new(create_reducer(test_reducer)),
+        )
+        .with_parallel_notifications(2);
+        let owners: Vec<_> = (0..4).map(|_| Arc::new(())).collect();
+        for owner in &owners {
+            store.subscribe_weak(&Arc::downgrade(owner), |_: &(), _: &TestState| {});
+        }
+
+        drop(owners);
+        store.dispatch(TestAction::Increment);
+
+        assert_eq!(store.subscriber_count(), 0);
+    }
+
+    #[test]
+    fn test_dispatch_from_iter_notifies_once() {
+        let store = Store::new(
+            TestState {
+                count: 0,
+                name: "initial".to_string(),
+            },
+            Box::new(create_reducer(test_reducer)),
+        );
+        let notifications = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&notifications);
+        store.subscribe(move |state: &TestState| seen.lock().unwrap().push(state.count));
+
+        let log = "inc inc dec inc";
+        store.dispatch_from_iter(log.split_whitespace().map(|entry| match entry {
+            "inc" => TestAction::Increment,
+            _ => TestAction::Decrement,
+        }));
+        store.dispatch_from_iter(std::iter::empty());
+
+        assert_eq!(*notifications.lock().unwrap(), vec![2]);
+    }
+
+    #[test]
+    fn test_time_travel_undo_redo_and_jump() {
+        let store = Store::new(
+            TestState {
+                count: 0,
+                name: "test".to_string(),
+            },
+            Box::new(create_reducer(test_reducer)),
+        );
+        assert!(!store.undo());
+        assert_eq!(store.time_travel_position(), None);
+
+        store.dispatch(TestAction::Increment);
+        assert!(store.enable_time_travel(TimeTravelConfig::new()));
+        assert!(!store.enable_time_travel(TimeTravelConfig::new()));
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let seen_clone = Arc::clone(&seen);
+        store.subscribe(move |state: &TestState| seen_clone.lock().unwrap().push(state.count));
+
+        store.dispatch(TestAction::Increment);
+        store.dispatch_batch(vec![TestAction::Increment, TestAction::Increment]);
+        assert_eq!(store.time_travel_position(), Some((3, 2)));
+
+        assert!(store.undo());
+        assert_eq!(store.get_state().count, 2);
+        assert!(store.can_redo());
+        assert!(store.jump_to(0));
+        assert_eq!(store.get_state().count, 1);
+        assert!(!store.can_undo());
+        assert!(!store.jump_to(0));
+        assert!(!store.jump_to(3));
+        assert!(store.redo());
+        assert_eq!(store.get_state().count, 2);
+
+        // Dispatching after an undo discards the redoable states
+        store.clone().dispatch(TestAction::Decrement);
+        assert!(!store.can_redo());
+        assert_eq!(store.time_travel_position(), Some((3, 2)));
+
+        assert_eq!(*seen.lock().unwrap(), vec![2, 4, 2, 1, 2, 1]);
+    }
+
+    #[test]
+    fn test_time_travel_max_history() {
+        let store = Store::new(
+            TestState {
+                count: 0,
+                name: "test".to_string(),
+            },
+            Box::new(create_reducer(test_reducer)),
+        );
+        store.enable_time_travel(TimeTravelConfig::new().max_history(3));
+
+        for _ in 0..5 {
+            store.dispatch(TestAction::Increment);
+        }
+        store.replace_state(TestState {
+            count: 10,
+            name: "replaced".to_string(),
+        });
+        assert_eq!(store.time_travel_position(), Some((3, 2)));
+
+        assert!(store.undo());
+        assert!(store.undo());
+        assert!(!store.undo());
+        assert_eq!(store.get_state().count, 4);
+    }
+
+    #[test]
+    fn test_time_travel_max_history_after_undo() {
+        let store = Store::new(
+            TestState {
+                count: 0,
+                name: "test".to_string(),
+            },
+            Box::new(create_reducer(test_reducer)),
+        );
+        store.enable_time_travel(TimeTravelConfig::new().max_history(3));
+
+        for _ in 0..20 {
+            store.dispatch(TestAction::Increment);
+        }
+        assert_eq!(store.time_travel_position(), Some((3, 2)));
+
+        // Dropped states stay dropped when undone states are discarded
+        assert!(store.undo());
+        assert!(store.undo());
+        store.dispatch(TestAction::Decrement);
+        assert_eq!(store.time_travel_position(), Some((2, 1)));
+        assert!(store.undo());
+        assert!(!store.undo());
+        assert_eq!(store.get_state().count, 18);
+    }
+
+    #[test]
+    fn test_time_travel_undoes_batch_in_one_step() {
+        let store = Store::new(
+            TestState {
+                count: 0,
+                name: "test".to_string(),
+            },
+            Box::new(create_reducer(test_reducer)),
+        );
+        store.enable_time_travel(TimeTravelConfig::new());
+
+        let batch = store.batch();
+        batch.dispatch(TestAction::Increment);
+        batch.dispatch(TestAction::Increment);
+        batch.dispatch(TestAction::Increment);
+        batch.end();
+        assert_eq!(store.time_travel_position(), Some((2, 1)));
+
+        assert!(store.undo());
+        assert_eq!(store.get_state().count, 0);
+        assert!(store.redo());
+        assert_eq!(store.get_state().count, 3);
+    }
+
+    #[test]
+    fn test_flush_propagates_background_subscriber_panic() {
+        let store = Store::new(
+            TestState {
+                count: 0,
+                name: "initial".to_string(),
+            },
+            Box::new(create_reducer(test_reducer)),
+        )
+        .with_background_notifications();
+        store.subscribe(|state: &TestState| {
+            if state.count == 1 {
+                panic!("subscriber failed");
+            }
+        });
+
+        store.dispatch(TestAction::Increment);
+        let payload =
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| store.flush())).unwrap_err();
+        assert_eq!(payload.downcast_ref::<&str>(), Some(&"subscriber failed"));
+
+        // The worker keeps running and the panic is only reported once
+        store.dispatch(TestAction::Increment);
+        store.flush();
+        assert_eq!(store.get_state().count, 2);
+    }
+
+    #[test]
+    fn test_flush_does_not_raise_recovered_background_panic() {
+        let store = Store::new(
+            TestState {
+                count: 0,
+                name: "initial".to_string(),
+            },
+            Box::new(create_reducer(test_reducer)),
+        )
+        .with_panic_policy(PanicPolicy::Recover)
+        .with_background_notifications();
+        store.subscribe(|_: &TestState| panic!("subscriber failed"));
+        let seen = Arc::new(Mutex::new(0));
+        let seen_clone = Arc::clone(&seen);
+        store.subscribe(move |_: &TestState| *seen_clone.lock().unwrap() += 1);
+
+        store.dispatch(TestAction::Increment);
+        store.flush();
+        assert_eq!(*seen.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_fair_dispatch_lock_applies_waiting_dispatches_in_order() {
+        let gate = Arc::new(Mutex::new(()));
+        let held = gate.lock().unwrap();
+        let reducer_gate = Arc::clone(&gate);
+        let store = Store::builder(
+            Vec::new(),
+            create_reducer(move |log: &Vec<u32>, id: &u32| {
+                if *id == 0 {
+                    // Keep the dispatch lock until the test releases the gate
+                    drop(reducer_gate.lock().unwrap());
+                }
+                let mut log = log.clone();
+                log.push(*id);
+                log
+            }),
+        )
+        .dispatch_lock(DispatchLock::Fair)
+        .build();
+
+        let mut threads = Vec::new();
+        for id in 0..5 {
+            let store = store.clone();
+            threads.push(thread::spawn(move || store.dispatch(id)));
+            // Let each thread start waiting before the next one
+            thread::sleep(Duration::from_millis(20));
+        }
+        drop(held);
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert_eq!(store.get_state(), vec![0, 1, 2, 3, 4]);
+    }
 }
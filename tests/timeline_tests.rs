@@ -1,7 +1,12 @@
-use std::any::Any;
-use zed::StateManager;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use zed::{
+    CheckpointPolicy, PersistError, RetentionPolicy, SharedStateManager, StateManager,
+    TimelineEvent, create_reducer,
+};
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 struct TestState {
     counter: i32,
     name: String,
@@ -15,28 +20,24 @@ enum TestAction {
     Reset,
 }
 
-fn test_reducer(state: &TestState, action: &dyn Any) -> TestState {
-    if let Some(action) = action.downcast_ref::<TestAction>() {
-        match action {
-            TestAction::Increment => TestState {
-                counter: state.counter + 1,
-                name: state.name.clone(),
-            },
-            TestAction::Decrement => TestState {
-                counter: state.counter - 1,
-                name: state.name.clone(),
-            },
-            TestAction::SetName(name) => TestState {
-                counter: state.counter,
-                name: name.clone(),
-            },
-            TestAction::Reset => TestState {
-                counter: 0,
-                name: "reset".to_string(),
-            },
-        }
-    } else {
-        state.clone()
+fn test_reducer(state: &TestState, action: &TestAction) -> TestState {
+    match action {
+        TestAction::Increment => TestState {
+            counter: state.counter + 1,
+            name: state.name.clone(),
+        },
+        TestAction::Decrement => TestState {
+            counter: state.counter - 1,
+            name: state.name.clone(),
+        },
+        TestAction::SetName(name) => TestState {
+            counter: state.counter,
+            name: name.clone(),
+        },
+        TestAction::Reset => TestState {
+            counter: 0,
+            name: "reset".to_string(),
+        },
     }
 }
 
@@ -208,4 +209,858 @@ mod tests {
         assert_eq!(manager.current_state().counter, 0);
         assert_eq!(manager.current_state().name, "reset");
     }
+
+    #[test]
+    fn test_state_manager_redo() {
+        let initial_state = TestState {
+            counter: 0,
+            name: "initial".to_string(),
+        };
+
+        let mut manager = StateManager::new(initial_state, test_reducer);
+        assert!(!manager.can_undo());
+        assert!(!manager.can_redo());
+
+        manager.dispatch(TestAction::Increment);
+        manager.dispatch(TestAction::Increment);
+        manager.dispatch(TestAction::Increment);
+
+        manager.rewind(2);
+        assert!(manager.can_undo());
+        assert!(manager.can_redo());
+
+        manager.redo(1);
+        assert_eq!(manager.current_state().counter, 2);
+
+        // Redoing past the newest state stops there
+        manager.redo(10);
+        assert_eq!(manager.current_state().counter, 3);
+        assert!(!manager.can_redo());
+    }
+
+    #[test]
+    fn test_state_manager_dispatch_discards_redo() {
+        let initial_state = TestState {
+            counter: 0,
+            name: "initial".to_string(),
+        };
+
+        let mut manager = StateManager::new(initial_state, test_reducer);
+        manager.dispatch(TestAction::Increment);
+        manager.dispatch(TestAction::Increment);
+        manager.rewind(1);
+
+        manager.dispatch(TestAction::SetName("edited".to_string()));
+        assert!(!manager.can_redo());
+
+        manager.redo(1);
+        assert_eq!(manager.current_state().name, "edited");
+        assert_eq!(manager.history_len(), 3);
+    }
+
+    #[test]
+    fn test_state_manager_jump_to() {
+        let initial_state = TestState {
+            counter: 0,
+            name: "initial".to_string(),
+        };
+
+        let mut manager = StateManager::new(initial_state, test_reducer);
+        for _ in 0..4 {
+            manager.dispatch(TestAction::Increment);
+        }
+
+        assert!(manager.jump_to(1));
+        assert_eq!(manager.current_state().counter, 1);
+        assert!(manager.jump_to(3));
+        assert_eq!(manager.current_position(), 3);
+
+        assert!(!manager.jump_to(5));
+        assert_eq!(manager.current_position(), 3);
+        assert_eq!(manager.history_len(), 5);
+    }
+
+    #[test]
+    fn test_state_manager_step_navigation() {
+        let initial_state = TestState {
+            counter: 0,
+            name: "initial".to_string(),
+        };
+
+        let mut manager = StateManager::new(initial_state, test_reducer);
+        manager.dispatch(TestAction::Increment);
+        manager.dispatch(TestAction::Increment);
+
+        assert!(!manager.step_forward());
+        assert!(manager.step_back());
+        assert!(manager.step_back());
+        assert!(!manager.step_back());
+        assert_eq!(manager.current_state().counter, 0);
+
+        assert!(manager.jump_by(2));
+        assert_eq!(manager.current_state().counter, 2);
+        assert!(!manager.jump_by(-3));
+        assert!(manager.jump_by(-1));
+        assert_eq!(manager.current_position(), 1);
+    }
+
+    #[test]
+    fn test_state_manager_checkpoints_survive_dispatches() {
+        let initial_state = TestState {
+            counter: 0,
+            name: "initial".to_string(),
+        };
+
+        let mut manager = StateManager::new(initial_state, test_reducer);
+        manager.dispatch(TestAction::Increment);
+        manager.checkpoint("before-import");
+
+        for _ in 0..3 {
+            manager.dispatch(TestAction::Increment);
+        }
+        manager.dispatch(TestAction::SetName("imported".to_string()));
+
+        assert!(manager.restore_checkpoint("before-import"));
+        assert_eq!(manager.current_state().counter, 1);
+        assert_eq!(manager.current_state().name, "initial");
+        assert_eq!(manager.checkpoint_position("before-import"), Some(1));
+        assert!(!manager.restore_checkpoint("missing"));
+
+        // The newer states can still be reached
+        manager.redo(4);
+        assert_eq!(manager.current_state().name, "imported");
+    }
+
+    #[test]
+    fn test_state_manager_discarded_checkpoints_are_removed() {
+        let initial_state = TestState {
+            counter: 0,
+            name: "initial".to_string(),
+        };
+
+        let mut manager = StateManager::new(initial_state, test_reducer);
+        manager.checkpoint("start");
+        manager.dispatch(TestAction::Increment);
+        manager.dispatch(TestAction::Increment);
+        manager.checkpoint("later");
+
+        manager.rewind(2);
+        manager.dispatch(TestAction::Decrement);
+
+        assert_eq!(manager.checkpoint_position("later"), None);
+        assert!(manager.restore_checkpoint("start"));
+        assert!(manager.remove_checkpoint("start"));
+        assert!(!manager.remove_checkpoint("start"));
+    }
+
+    #[test]
+    fn test_state_manager_save_and_load() {
+        let path = std::env::temp_dir().join(format!(
+            "zed-timeline-roundtrip-{}.timeline",
+            std::process::id()
+        ));
+        let initial_state = TestState {
+            counter: 0,
+            name: "initial".to_string(),
+        };
+
+        let mut manager = StateManager::new(initial_state, test_reducer);
+        manager.dispatch(TestAction::Increment);
+        manager.checkpoint("one");
+        manager.dispatch(TestAction::SetName("saved".to_string()));
+        manager.rewind(1);
+        manager.save(&path).unwrap();
+
+        let mut loaded = StateManager::load(&path, test_reducer).unwrap();
+        assert_eq!(loaded.history_len(), 3);
+        assert_eq!(loaded.current_position(), 1);
+        assert_eq!(loaded.checkpoint_position("one"), Some(1));
+        assert_eq!(loaded.timestamp_at(2), manager.timestamp_at(2));
+        // The undo history survives, including the rewound state
+        assert!(loaded.step_forward());
+        assert_eq!(loaded.current_state().name, "saved");
+        loaded.rewind(2);
+        assert_eq!(loaded.current_state().counter, 0);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_state_manager_load_rejects_damaged_files() {
+        let path = std::env::temp_dir().join(format!(
+            "zed-timeline-damaged-{}.timeline",
+            std::process::id()
+        ));
+        let initial_state = TestState {
+            counter: 0,
+            name: "initial".to_string(),
+        };
+        let mut manager = StateManager::new(initial_state, test_reducer);
+        manager.dispatch(TestAction::Increment);
+        manager.save(&path).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, contents.replace("\"counter\":1", "\"counter\":9")).unwrap();
+        let result = StateManager::<TestState, TestAction>::load(&path, test_reducer);
+        assert!(matches!(result, Err(PersistError::Corrupted(_))));
+
+        std::fs::write(&path, contents.replacen(" 1 ", " 2 ", 1)).unwrap();
+        let result = StateManager::<TestState, TestAction>::load(&path, test_reducer);
+        assert!(matches!(
+            result,
+            Err(PersistError::Migration { from: 2, to: 1 })
+        ));
+
+        std::fs::write(&path, "not a timeline").unwrap();
+        let result = StateManager::<TestState, TestAction>::load(&path, test_reducer);
+        assert!(matches!(result, Err(PersistError::Corrupted(_))));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_state_manager_with_capturing_closure() {
+        let step = 10;
+        let suffix = String::from("!");
+        let mut manager = StateManager::new(
+            TestState {
+                counter: 0,
+                name: "initial".to_string(),
+            },
+            move |state: &TestState, action: &TestAction| match action {
+                TestAction::Increment => TestState {
+                    counter: state.counter + step,
+                    name: format!("{}{}", state.name, suffix),
+                },
+                _ => state.clone(),
+            },
+        );
+
+        manager.dispatch(TestAction::Increment);
+        let mut branch = manager.branch();
+        branch.dispatch(TestAction::Increment);
+
+        assert_eq!(manager.current_state().counter, 10);
+        assert_eq!(branch.current_state().counter, 20);
+        assert_eq!(branch.current_state().name, "initial!!");
+    }
+
+    #[test]
+    fn test_state_manager_from_reducer() {
+        let reducer = create_reducer(test_reducer);
+        let mut manager = StateManager::from_reducer(
+            TestState {
+                counter: 0,
+                name: "initial".to_string(),
+            },
+            reducer,
+        );
+
+        manager.dispatch(TestAction::Increment);
+        manager.dispatch(TestAction::SetName("reduced".to_string()));
+        assert_eq!(manager.history_len(), 3);
+        assert_eq!(manager.current_state().counter, 1);
+        assert_eq!(manager.current_state().name, "reduced");
+
+        manager.rewind(2);
+        assert_eq!(manager.current_state().counter, 0);
+    }
+
+    #[test]
+    fn test_state_manager_named_branches_keep_history() {
+        let initial_state = TestState {
+            counter: 0,
+            name: "initial".to_string(),
+        };
+
+        let mut manager = StateManager::new(initial_state, test_reducer);
+        manager.dispatch(TestAction::Increment);
+        manager.dispatch(TestAction::Increment);
+
+        assert!(manager.create_branch("experiment"));
+        assert!(!manager.create_branch("experiment"));
+        assert_eq!(manager.current_branch(), "main");
+
+        assert!(manager.switch_branch("experiment"));
+        manager.dispatch(TestAction::SetName("experiment".to_string()));
+        assert_eq!(manager.history_len(), 4);
+        // The history from before the branch was created is still there
+        manager.rewind(3);
+        assert_eq!(manager.current_state().counter, 0);
+        manager.redo(3);
+
+        assert!(manager.switch_branch("main"));
+        assert_eq!(manager.current_state().name, "initial");
+        assert_eq!(manager.current_state().counter, 2);
+        assert_eq!(manager.history_len(), 3);
+
+        assert!(!manager.switch_branch("missing"));
+        assert_eq!(manager.list_branches(), vec!["experiment", "main"]);
+
+        assert!(!manager.delete_branch("main"));
+        assert!(manager.delete_branch("experiment"));
+        assert_eq!(manager.list_branches(), vec!["main"]);
+    }
+
+    #[test]
+    fn test_state_manager_merge_branch_uses_common_ancestor() {
+        let initial_state = TestState {
+            counter: 0,
+            name: "initial".to_string(),
+        };
+
+        let mut manager = StateManager::new(initial_state, test_reducer);
+        manager.dispatch(TestAction::Increment);
+        manager.create_branch("rename");
+
+        manager.switch_branch("rename");
+        manager.dispatch(TestAction::SetName("renamed".to_string()));
+        manager.create_branch("nested");
+        manager.switch_branch("nested");
+        manager.dispatch(TestAction::Increment);
+
+        manager.switch_branch("main");
+        manager.dispatch(TestAction::Increment);
+        manager.dispatch(TestAction::Increment);
+
+        let merged = manager.merge_branch("nested", |base, ours, theirs| {
+            assert_eq!(base.counter, 1);
+            assert_eq!(base.name, "initial");
+            TestState {
+                counter: ours.counter + theirs.counter - base.counter,
+                name: theirs.name.clone(),
+            }
+        });
+        assert!(merged);
+        assert_eq!(manager.current_state().counter, 4);
+        assert_eq!(manager.current_state().name, "renamed");
+        assert_eq!(manager.history_len(), 5);
+
+        // The merge is an ordinary history entry
+        manager.step_back();
+        assert_eq!(manager.current_state().name, "initial");
+        assert!(!manager.merge_branch("missing", |_, ours, _| ours.clone()));
+        assert!(!manager.merge_branch("main", |_, ours, _| ours.clone()));
+    }
+
+    #[test]
+    fn test_state_manager_history_queries() {
+        let initial_state = TestState {
+            counter: 0,
+            name: "initial".to_string(),
+        };
+
+        let mut manager = StateManager::new(initial_state, test_reducer);
+        manager.dispatch(TestAction::Decrement);
+        manager.dispatch(TestAction::Decrement);
+        manager.dispatch(TestAction::Increment);
+        manager.dispatch(TestAction::Increment);
+        manager.rewind(1);
+
+        let counters: Vec<_> = manager.iter_history().map(|state| state.counter).collect();
+        assert_eq!(counters, vec![0, -1, -2, -1, 0]);
+
+        let (index, state) = manager.find_last(|state| state.counter < 0).unwrap();
+        assert_eq!(index, 3);
+        assert_eq!(state.counter, -1);
+        assert_eq!(manager.find(|state| state.counter < 0).unwrap().0, 1);
+        assert!(manager.find(|state| state.counter > 0).is_none());
+
+        let range = manager.history_range(1..=2).unwrap();
+        assert_eq!(range.len(), 2);
+        assert_eq!(range[1].counter, -2);
+        assert!(manager.history_range(3..6).is_none());
+        assert_eq!(manager.state_at(4).map(|state| state.counter), Some(0));
+        assert_eq!(manager.state_at(5), None);
+    }
+
+    #[test]
+    fn test_state_manager_rewind_to_time() {
+        let initial_state = TestState {
+            counter: 0,
+            name: "initial".to_string(),
+        };
+
+        let mut manager = StateManager::new(initial_state, test_reducer);
+        manager.dispatch(TestAction::Increment);
+        manager.dispatch(TestAction::Increment);
+        std::thread::sleep(Duration::from_millis(20));
+        let before_rename = SystemTime::now();
+        std::thread::sleep(Duration::from_millis(20));
+        manager.dispatch(TestAction::SetName("renamed".to_string()));
+        manager.dispatch(TestAction::Increment);
+
+        let recent: Vec<_> = manager
+            .entries_between(before_rename, SystemTime::now())
+            .map(|(index, _, state)| (index, state.counter))
+            .collect();
+        assert_eq!(recent, vec![(3, 2), (4, 3)]);
+        assert!(manager.timestamp_at(3).unwrap() > before_rename);
+        assert!(manager.timestamp_at(5).is_none());
+
+        manager.rewind_to(before_rename);
+        assert_eq!(manager.current_position(), 2);
+        assert_eq!(manager.current_state().name, "initial");
+
+        // Rewinding never moves forward
+        manager.rewind_to(SystemTime::now());
+        assert_eq!(manager.current_position(), 2);
+        manager.rewind_to(SystemTime::UNIX_EPOCH);
+        assert_eq!(manager.current_position(), 0);
+    }
+
+    #[test]
+    fn test_state_manager_squash_range() {
+        let initial_state = TestState {
+            counter: 0,
+            name: "initial".to_string(),
+        };
+
+        let mut manager = StateManager::new(initial_state, test_reducer);
+        manager.dispatch(TestAction::SetName("importing".to_string()));
+        for _ in 0..3 {
+            manager.dispatch(TestAction::Increment);
+        }
+        manager.checkpoint("mid-import");
+        manager.dispatch(TestAction::SetName("imported".to_string()));
+        manager.dispatch(TestAction::Decrement);
+        manager.rewind(1);
+
+        assert!(manager.squash(1..6));
+        let names: Vec<_> = manager.iter_history().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["initial", "imported", "imported"]);
+        assert_eq!(manager.current_position(), 1);
+        assert_eq!(manager.checkpoint_position("mid-import"), Some(1));
+
+        assert!(!manager.squash(2..5));
+        assert!(!manager.squash(1..1));
+        assert_eq!(manager.history_len(), 3);
+    }
+
+    #[test]
+    fn test_state_manager_compact_keeps_endpoints() {
+        let initial_state = TestState {
+            counter: 0,
+            name: "initial".to_string(),
+        };
+
+        let mut manager = StateManager::new(initial_state, test_reducer);
+        for _ in 0..5 {
+            manager.dispatch(TestAction::Increment);
+        }
+        manager.jump_to(2);
+        manager.checkpoint("two");
+        manager.jump_to(4);
+
+        assert_eq!(manager.compact(), 2);
+        let counters: Vec<_> = manager.iter_history().map(|s| s.counter).collect();
+        assert_eq!(counters, vec![0, 2, 4, 5]);
+        assert_eq!(manager.current_state().counter, 4);
+        assert!(manager.restore_checkpoint("two"));
+        assert_eq!(manager.current_state().counter, 2);
+
+        // Only the state the timeline moved away from is left to drop
+        assert_eq!(manager.compact(), 1);
+        assert_eq!(manager.compact(), 0);
+        assert_eq!(manager.history_len(), 3);
+    }
+
+    #[test]
+    fn test_state_manager_subscribers_receive_events() {
+        let initial_state = TestState {
+            counter: 0,
+            name: "initial".to_string(),
+        };
+        let events = Arc::new(Mutex::new(Vec::new()));
+
+        let mut manager = StateManager::new(initial_state, test_reducer);
+        let recorded = Arc::clone(&events);
+        let id = manager.subscribe(move |event, timeline| {
+            recorded.lock().unwrap().push((
+                event.clone(),
+                timeline.can_undo(),
+                timeline.can_redo(),
+            ));
+        });
+
+        manager.dispatch(TestAction::Increment);
+        manager.dispatch(TestAction::Increment);
+        manager.rewind(2);
+        manager.rewind(1);
+        manager.redo(1);
+        manager.create_branch("other");
+        manager.switch_branch("other");
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                (TimelineEvent::Dispatched { position: 1 }, true, false),
+                (TimelineEvent::Dispatched { position: 2 }, true, false),
+                (TimelineEvent::Rewound { from: 2, to: 0 }, false, true),
+                (TimelineEvent::Redone { from: 0, to: 1 }, true, true),
+                (
+                    TimelineEvent::BranchSwitched {
+                        from: "main".to_string(),
+                        to: "other".to_string()
+                    },
+                    true,
+                    false
+                ),
+            ]
+        );
+
+        assert!(manager.unsubscribe(id));
+        assert!(!manager.unsubscribe(id));
+        manager.dispatch(TestAction::Increment);
+        assert_eq!(events.lock().unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_shared_state_manager_across_threads() {
+        let timeline = SharedStateManager::new(0, |count: &i32, step: &i32| count + step);
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let timeline = timeline.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..25 {
+                        timeline.dispatch(1);
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(timeline.current_state(), 100);
+        assert_eq!(timeline.history_len(), 101);
+
+        timeline.rewind(50);
+        assert_eq!(timeline.current_state(), 50);
+        assert!(timeline.can_redo());
+    }
+
+    #[test]
+    fn test_shared_state_manager_wraps_existing_timeline() {
+        let initial_state = TestState {
+            counter: 0,
+            name: "initial".to_string(),
+        };
+        let mut manager = StateManager::new(initial_state, test_reducer);
+        manager.dispatch(TestAction::Increment);
+
+        let shared = SharedStateManager::from(manager);
+        shared.checkpoint("one");
+        shared.dispatch(TestAction::Increment);
+
+        assert_eq!(shared.current_state().counter, 2);
+        assert_eq!(shared.state_at(1).unwrap().counter, 1);
+        assert!(shared.restore_checkpoint("one"));
+        assert_eq!(shared.with(|manager| manager.current_state().counter), 1);
+        assert_eq!(shared.current_branch(), "main");
+    }
+
+    #[test]
+    fn test_shared_state_manager_queries_return_clones() {
+        let timeline = SharedStateManager::new(0, |count: &i32, step: &i32| count + step);
+        for step in [1, 2, 3] {
+            timeline.dispatch(step);
+        }
+
+        assert_eq!(
+            timeline.iter_history().collect::<Vec<_>>(),
+            vec![0, 1, 3, 6]
+        );
+        assert_eq!(timeline.history_range(1..3), Some(vec![1, 3]));
+        assert_eq!(timeline.history_range(..10), None);
+        assert_eq!(timeline.find(|count| *count > 0), Some((1, 1)));
+        assert_eq!(timeline.find_last(|count| *count < 6), Some((2, 3)));
+        let start = timeline.timestamp_at(2).unwrap();
+        let entries = timeline.entries_between(start, SystemTime::now());
+        assert_eq!(
+            entries.first().map(|(index, _, state)| (*index, *state)),
+            Some((2, 3))
+        );
+
+        // The replay may dispatch, as the timeline is not locked meanwhile
+        let mut frames = Vec::new();
+        timeline.replay(0.0, |position, state| {
+            frames.push((position, *state));
+            timeline.dispatch(0);
+        });
+        assert_eq!(frames, [(0, 0), (1, 1), (2, 3), (3, 6)]);
+        assert_eq!(timeline.history_len(), 8);
+    }
+
+    #[test]
+    fn test_undo_entry_reapplies_later_actions() {
+        let mut manager = StateManager::new(Vec::new(), |items: &Vec<&str>, item: &&str| {
+            let mut items = items.clone();
+            items.push(item);
+            items
+        });
+        manager.dispatch("a");
+        manager.dispatch("b");
+        manager.dispatch("c");
+        manager.checkpoint("end");
+        manager.rewind(1);
+
+        assert!(manager.undo_entry(1));
+        assert_eq!(manager.history_len(), 3);
+        assert_eq!(manager.current_state(), &vec!["b"]);
+        assert_eq!(manager.state_at(2), Some(&vec!["b", "c"]));
+        assert_eq!(manager.checkpoint_position("end"), Some(2));
+    }
+
+    #[test]
+    fn test_undo_entry_requires_recorded_actions() {
+        let mut manager = StateManager::new(0, |count: &i32, step: &i32| count + step);
+        manager.dispatch(1);
+        manager.dispatch(2);
+        manager.dispatch(3);
+
+        assert!(!manager.undo_entry(0));
+        assert!(!manager.undo_entry(4));
+
+        // The squashed state stands for two actions and cannot be replayed
+        assert!(manager.squash(2..=3));
+        assert!(!manager.undo_entry(1));
+        assert!(manager.undo_entry(2));
+        assert_eq!(manager.iter_history().copied().collect::<Vec<_>>(), [0, 1]);
+    }
+
+    #[test]
+    fn test_replay_visits_every_state_without_moving() {
+        let mut manager = StateManager::new(0, |count: &i32, step: &i32| count + step);
+        manager.dispatch(1);
+        manager.dispatch(2);
+        manager.rewind(1);
+
+        let mut frames = Vec::new();
+        manager.replay(f64::INFINITY, |position, state| {
+            frames.push((position, *state))
+        });
+
+        assert_eq!(frames, [(0, 0), (1, 1), (2, 3)]);
+        assert_eq!(manager.current_position(), 1);
+    }
+
+    #[test]
+    fn test_replay_scales_recorded_delays() {
+        let mut manager = StateManager::new(0, |count: &i32, step: &i32| count + step);
+        std::thread::sleep(Duration::from_millis(200));
+        manager.dispatch(1);
+
+        let started = std::time::Instant::now();
+        manager.replay(4.0, |_, _| {});
+        let elapsed = started.elapsed();
+
+        assert!(elapsed >= Duration::from_millis(50));
+        assert!(elapsed < Duration::from_millis(150));
+    }
+
+    #[test]
+    fn test_gc_removes_stale_branches() {
+        let mut manager = StateManager::new(0, |count: &i32, step: &i32| count + step);
+        manager.create_branch("abandoned");
+        std::thread::sleep(Duration::from_millis(50));
+        manager.dispatch(1);
+        manager.create_branch("fresh");
+
+        let stats = manager.gc(&RetentionPolicy::new().max_branch_age(Duration::from_millis(25)));
+
+        assert_eq!(stats.branches_removed, 1);
+        assert_eq!(stats.states_removed, 1);
+        assert_eq!(manager.list_branches(), ["fresh", "main"]);
+        assert_eq!(manager.current_state(), &1);
+    }
+
+    #[test]
+    fn test_gc_trims_every_branch() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let mut manager = StateManager::new(0, |count: &i32, step: &i32| count + step);
+        let sink = Arc::clone(&events);
+        manager.subscribe(move |event, _| sink.lock().unwrap().push(event.clone()));
+
+        manager.dispatch(1);
+        manager.create_branch("side");
+        for step in [2, 3, 4] {
+            manager.dispatch(step);
+        }
+        manager.switch_branch("side");
+        for step in [5, 6, 7] {
+            manager.dispatch(step);
+        }
+        manager.switch_branch("main");
+        events.lock().unwrap().clear();
+
+        let stats = manager.gc(&RetentionPolicy::new().max_history(2));
+
+        // The states up to the fork point are shared, so they are kept
+        assert_eq!(stats.branches_removed, 0);
+        assert_eq!(stats.states_removed, 4);
+        assert_eq!(
+            manager.iter_history().copied().collect::<Vec<_>>(),
+            [0, 1, 10]
+        );
+        assert_eq!(manager.current_branch(), "main");
+        assert_eq!(
+            *events.lock().unwrap(),
+            [TimelineEvent::HistoryRewritten { removed: 4 }]
+        );
+
+        manager.switch_branch("side");
+        assert_eq!(
+            manager.iter_history().copied().collect::<Vec<_>>(),
+            [0, 1, 19]
+        );
+    }
+
+    #[test]
+    fn test_gc_keeps_the_base_of_later_merges() {
+        let mut manager = StateManager::new(0, |count: &i32, step: &i32| count + step);
+        for step in [1, 2] {
+            manager.dispatch(step);
+        }
+        manager.create_branch("exp");
+        manager.switch_branch("exp");
+        manager.dispatch(100);
+        manager.switch_branch("main");
+        for step in [3, 4, 5] {
+            manager.dispatch(step);
+        }
+
+        manager.gc(&RetentionPolicy::new().max_history(2));
+
+        assert!(manager.merge_branch("exp", |base, ours, theirs| {
+            assert_eq!(*base, 3);
+            ours + theirs - base
+        }));
+        assert_eq!(manager.current_state(), &115);
+    }
+
+    #[test]
+    fn test_merge_fails_once_the_shared_initial_state_is_dropped() {
+        let mut manager = StateManager::new(0, |count: &i32, step: &i32| count + step);
+        manager.dispatch(1);
+        manager.create_branch("a");
+        manager.create_branch("b");
+        manager.switch_branch("a");
+        manager.dispatch(10);
+        manager.dispatch(20);
+        // Deleting their parent leaves "a" and "b" unrelated
+        manager.switch_branch("b");
+        assert!(manager.delete_branch("main"));
+
+        manager.gc(&RetentionPolicy::new().max_history(2));
+        manager.switch_branch("a");
+        assert_eq!(
+            manager.iter_history().copied().collect::<Vec<_>>(),
+            [11, 31]
+        );
+
+        manager.switch_branch("b");
+        assert!(!manager.merge_branch("a", |_, ours, _| *ours));
+        assert_eq!(manager.history_len(), 2);
+    }
+
+    #[test]
+    fn test_squash_keeps_the_history_shared_with_branches() {
+        let mut manager = StateManager::new(0, |count: &i32, step: &i32| count + step);
+        for step in [1, 2] {
+            manager.dispatch(step);
+        }
+        manager.create_branch("side");
+        for step in [3, 4] {
+            manager.dispatch(step);
+        }
+
+        assert!(!manager.squash(1..=3));
+        assert!(manager.squash(3..=4));
+        assert_eq!(
+            manager.iter_history().copied().collect::<Vec<_>>(),
+            [0, 1, 3, 10]
+        );
+
+        assert_eq!(manager.compact(), 0);
+    }
+
+    #[test]
+    fn test_checkpoint_policy_every_actions_keeps_newest() {
+        let mut manager = StateManager::new(0, |count: &i32, step: &i32| count + step);
+        manager.set_checkpoint_policy(CheckpointPolicy::new("auto").every_actions(2).keep_last(2));
+
+        for step in 1..=6 {
+            manager.dispatch(step);
+        }
+
+        assert_eq!(manager.checkpoint_position("auto-1"), None);
+        assert_eq!(manager.checkpoint_position("auto-2"), Some(4));
+        assert_eq!(manager.checkpoint_position("auto-3"), Some(6));
+        assert_eq!(manager.latest_auto_checkpoint(), Some("auto-3"));
+
+        manager.clear_checkpoint_policy();
+        manager.dispatch(7);
+        manager.dispatch(8);
+        assert_eq!(manager.latest_auto_checkpoint(), None);
+        assert_eq!(manager.checkpoint_position("auto-3"), Some(6));
+    }
+
+    #[test]
+    fn test_checkpoint_policy_every_interval() {
+        let mut manager = StateManager::new(0, |count: &i32, step: &i32| count + step);
+        manager
+            .set_checkpoint_policy(CheckpointPolicy::new("timed").every(Duration::from_millis(50)));
+
+        manager.dispatch(1);
+        assert_eq!(manager.latest_auto_checkpoint(), None);
+
+        std::thread::sleep(Duration::from_millis(60));
+        manager.dispatch(2);
+        assert_eq!(manager.latest_auto_checkpoint(), Some("timed-1"));
+        assert_eq!(manager.checkpoint_position("timed-1"), Some(2));
+    }
+
+    #[test]
+    fn test_export_json_describes_entries() {
+        let initial_state = TestState {
+            counter: 0,
+            name: "initial".to_string(),
+        };
+        let mut manager = StateManager::new(initial_state, test_reducer);
+        manager.dispatch(TestAction::Increment);
+        manager.dispatch(TestAction::SetName("renamed".to_string()));
+        manager.checkpoint("named");
+        manager.rewind(1);
+
+        let document = manager.export_json().unwrap();
+
+        assert_eq!(document["branch"], "main");
+        assert_eq!(document["current"], 1);
+        assert_eq!(document["checkpoints"]["named"], 2);
+        assert_eq!(document["entries"].as_array().unwrap().len(), 3);
+        assert_eq!(document["entries"][0]["action"], serde_json::Value::Null);
+        assert_eq!(document["entries"][0]["state"]["name"], "initial");
+        assert_eq!(document["entries"][1]["action"], "Increment");
+        assert_eq!(document["entries"][2]["changes"][0]["path"], "/name");
+        assert_eq!(document["entries"][2]["changes"][0]["new"], "renamed");
+        assert!(document["entries"][2]["timestamp_ms"].as_u64().unwrap() > 0);
+    }
+
+    #[test]
+    fn test_export_json_lists_branches() {
+        let mut manager = StateManager::new(0, |count: &i32, step: &i32| count + step);
+        manager.dispatch(1);
+        manager.create_branch("feature");
+
+        let document = manager.export_json().unwrap();
+        let branches = document["branches"].as_array().unwrap();
+
+        assert_eq!(branches.len(), 2);
+        assert_eq!(branches[0]["name"], "feature");
+        assert_eq!(branches[0]["parent"], "main");
+        assert_eq!(branches[0]["fork_point"], 1);
+        assert_eq!(branches[1]["parent"], serde_json::Value::Null);
+    }
 }
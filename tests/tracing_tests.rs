@@ -0,0 +1,125 @@
+#![cfg(feature = "tracing")]
+
+use std::io;
+use std::sync::{Arc, Mutex};
+use tracing_subscriber::fmt::format::FmtSpan;
+use zed::*;
+
+#[derive(Clone, Debug, PartialEq)]
+struct CounterState {
+    count: i32,
+}
+
+#[derive(Clone, Debug)]
+enum CounterAction {
+    Add(i32),
+}
+
+fn counter_reducer(state: &CounterState, action: &CounterAction) -> CounterState {
+    match action {
+        CounterAction::Add(n) => CounterState {
+            count: state.count + n,
+        },
+    }
+}
+
+#[derive(Clone, Default)]
+struct Captured(Arc<Mutex<Vec<u8>>>);
+
+impl Captured {
+    fn output(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+impl io::Write for Captured {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Runs `f` with a subscriber that logs closed spans and events to a buffer.
+fn capture(f: impl FnOnce()) -> String {
+    let captured = Captured::default();
+    let writer = captured.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_ansi(false)
+        .with_writer(move || writer.clone())
+        .finish();
+    tracing::subscriber::with_default(subscriber, f);
+    captured.output()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dispatch_emits_dispatch_reduce_and_notify_spans() {
+        let output = capture(|| {
+            let store = Store::new(
+                CounterState { count: 0 },
+                Box::new(create_reducer(counter_reducer)),
+            );
+            store.subscribe(|_: &CounterState| {});
+            store.dispatch(CounterAction::Add(1));
+        });
+
+        assert!(output.contains("zed.dispatch"));
+        assert!(output.contains("zed.reduce"));
+        assert!(output.contains("zed.notify"));
+        assert!(output.contains("CounterAction"));
+    }
+
+    #[test]
+    fn test_dispatch_batch_span_records_action_count() {
+        let output = capture(|| {
+            let store = Store::new(
+                CounterState { count: 0 },
+                Box::new(create_reducer(counter_reducer)),
+            );
+            store.dispatch_batch(vec![CounterAction::Add(1), CounterAction::Add(2)]);
+        });
+
+        assert!(output.contains("zed.dispatch_batch"));
+        assert!(output.contains("actions=2"));
+    }
+
+    #[test]
+    fn test_rejected_action_emits_warning() {
+        let output = capture(|| {
+            let store = configure_store(
+                CounterState { count: 0 },
+                create_try_reducer(|_: &CounterState, _: &CounterAction| {
+                    Err::<CounterState, _>("not allowed")
+                }),
+            );
+            assert!(store.dispatch_try(CounterAction::Add(1)).is_err());
+        });
+
+        assert!(output.contains("WARN"));
+        assert!(output.contains("reducer rejected action"));
+        assert!(output.contains("not allowed"));
+    }
+
+    #[test]
+    fn test_state_node_propagation_emits_event() {
+        let output = capture(|| {
+            let mut node = StateNode::new("origin".to_string(), CounterState { count: 1 });
+            let peer = StateNode::new("peer".to_string(), CounterState { count: 0 }).into_shared();
+            node.connect(&peer);
+            node.propagate_update();
+        });
+
+        assert!(output.contains("propagated state"));
+        assert!(output.contains("node=origin"));
+        assert!(output.contains("sent=1"));
+    }
+}
@@ -0,0 +1,395 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io;
+use std::sync::{Arc, Mutex};
+use zed::{MeshLink, MeshMessage, StateNode, Transport};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Document {
+    title: String,
+    lines: Vec<String>,
+}
+
+fn document(title: &str, lines: &[&str]) -> Document {
+    Document {
+        title: title.to_string(),
+        lines: lines.iter().map(|line| line.to_string()).collect(),
+    }
+}
+
+/// One end of an in-memory connection.
+#[derive(Clone, Default)]
+struct MemoryTransport {
+    outbox: Arc<Mutex<VecDeque<MeshMessage>>>,
+    inbox: Arc<Mutex<VecDeque<MeshMessage>>>,
+}
+
+impl MemoryTransport {
+    fn pair() -> (Self, Self) {
+        let a = Self::default();
+        let b = Self {
+            outbox: Arc::clone(&a.inbox),
+            inbox: Arc::clone(&a.outbox),
+        };
+        (a, b)
+    }
+}
+
+impl Transport for MemoryTransport {
+    fn send(&mut self, message: &MeshMessage) -> io::Result<()> {
+        self.outbox.lock().unwrap().push_back(message.clone());
+        Ok(())
+    }
+
+    fn receive(&mut self) -> io::Result<Option<MeshMessage>> {
+        Ok(self.inbox.lock().unwrap().pop_front())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mesh_link_sends_state_then_deltas() {
+        let (a, b) = MemoryTransport::pair();
+        let sent = Arc::clone(&a.outbox);
+        let mut sender = MeshLink::new(a);
+        let mut receiver = MeshLink::new(b);
+
+        let mut local = StateNode::new("local".to_string(), document("Notes", &["one"]));
+        let mut remote = StateNode::new("remote".to_string(), document("", &[]));
+
+        sender.send_state(&local).unwrap();
+        assert!(matches!(
+            sent.lock().unwrap().back(),
+            Some(MeshMessage::State { .. })
+        ));
+        assert_eq!(receiver.receive(&mut remote).unwrap(), 1);
+        assert_eq!(remote.state, local.state);
+
+        local.state.lines.push("two".to_string());
+        sender.send_state(&local).unwrap();
+        assert!(matches!(
+            sent.lock().unwrap().back(),
+            Some(MeshMessage::Delta { .. })
+        ));
+        assert_eq!(receiver.receive(&mut remote).unwrap(), 1);
+        assert_eq!(remote.state, local.state);
+
+        // Unchanged states are not sent again
+        sender.send_state(&local).unwrap();
+        assert_eq!(receiver.receive(&mut remote).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_mesh_link_uses_conflict_resolver() {
+        let (a, b) = MemoryTransport::pair();
+        let mut sender = MeshLink::new(a);
+        let mut receiver = MeshLink::new(b);
+
+        let local = StateNode::new("local".to_string(), document("Remote", &["b"]));
+        let mut remote = StateNode::new("remote".to_string(), document("Local", &["a"]));
+        remote.set_conflict_resolver(|current: &mut Document, incoming: &Document| {
+            current.lines.extend(incoming.lines.iter().cloned());
+        });
+
+        sender.send_state(&local).unwrap();
+        receiver.receive(&mut remote).unwrap();
+
+        assert_eq!(remote.state, document("Local", &["a", "b"]));
+    }
+
+    #[test]
+    fn test_mesh_link_rejects_delta_without_base() {
+        let (mut a, b) = MemoryTransport::pair();
+        let mut receiver = MeshLink::new(b);
+        let mut remote = StateNode::new("remote".to_string(), document("", &[]));
+
+        a.send(&MeshMessage::Delta {
+            from: "unknown".to_string(),
+            delta: zed::JsonDelta::Unchanged,
+        })
+        .unwrap();
+
+        let err = receiver.receive(&mut remote).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[cfg(feature = "tcp")]
+    #[test]
+    fn test_tcp_transport_synchronizes_nodes() {
+        use std::net::TcpListener;
+        use zed::TcpTransport;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client =
+            MeshLink::new(TcpTransport::connect(listener.local_addr().unwrap()).unwrap());
+        let mut server =
+            MeshLink::new(TcpTransport::from_stream(listener.accept().unwrap().0).unwrap());
+
+        let mut local = StateNode::new("client".to_string(), document("Notes", &["one"]));
+        let mut remote = StateNode::new("server".to_string(), document("", &[]));
+
+        for line in ["two", "three"] {
+            client.send_state(&local).unwrap();
+            local.state.lines.push(line.to_string());
+        }
+        client.send_state(&local).unwrap();
+
+        let mut applied = 0;
+        while applied < 3 {
+            applied += server.receive(&mut remote).unwrap();
+        }
+        assert_eq!(remote.state, local.state);
+    }
+
+    #[cfg(feature = "websocket")]
+    #[test]
+    fn test_websocket_transport_synchronizes_nodes() {
+        use std::net::TcpListener;
+        use std::sync::mpsc;
+        use std::thread;
+        use zed::WebSocketTransport;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (done, client_done) = mpsc::channel::<()>();
+        let server = thread::spawn(move || {
+            let mut link =
+                MeshLink::new(WebSocketTransport::accept(listener.accept().unwrap().0).unwrap());
+            let mut node = StateNode::new("server".to_string(), document("", &[]));
+            while link.receive(&mut node).unwrap() < 1 {}
+            link.send_state(&node).unwrap();
+            // Keep the connection open until the client has read the reply
+            let _ = client_done.recv();
+            node.state
+        });
+
+        let mut client = MeshLink::new(WebSocketTransport::connect(addr).unwrap());
+        let mut local = StateNode::new("client".to_string(), document("Notes", &["one"]));
+        client.send_state(&local).unwrap();
+        let sent = local.state.clone();
+
+        local.state = document("", &[]);
+        while client.receive(&mut local).unwrap() < 1 {}
+        done.send(()).unwrap();
+
+        assert_eq!(server.join().unwrap(), sent);
+        assert_eq!(local.state, sent);
+    }
+
+    #[test]
+    fn test_mesh_link_counts_bytes_in_node_metrics() {
+        let (a, b) = MemoryTransport::pair();
+        let mut sender = MeshLink::new(a);
+        let mut receiver = MeshLink::new(b);
+
+        let mut local = StateNode::new("local".to_string(), document("Notes", &["one"]));
+        let mut remote = StateNode::new("remote".to_string(), document("", &[]));
+
+        sender.send_state(&local).unwrap();
+        receiver.receive(&mut remote).unwrap();
+        let full = local.metrics().bytes_sent;
+        assert!(full > 0);
+
+        local.state.lines.push("two".to_string());
+        sender.send_state(&local).unwrap();
+        receiver.receive(&mut remote).unwrap();
+
+        let sent = local.metrics();
+        let received = remote.metrics();
+        assert_eq!(sent.updates_sent, 2);
+        assert_eq!(received.updates_received, 2);
+        assert_eq!(received.bytes_received, sent.bytes_sent);
+        assert!(sent.bytes_sent > full);
+    }
+
+    #[cfg(feature = "lz4")]
+    #[test]
+    fn test_mesh_link_compresses_after_negotiation() {
+        use zed::Compression;
+
+        let (a, b) = MemoryTransport::pair();
+        let sent = Arc::clone(&a.outbox);
+        let mut sender = MeshLink::new(a).compression(Compression::Lz4);
+        let mut receiver = MeshLink::new(b).compression(Compression::Lz4);
+
+        let lines = vec!["the same line of text"; 200];
+        let mut local = StateNode::new("local".to_string(), document("Notes", &lines));
+        let mut remote = StateNode::new("remote".to_string(), document("", &[]));
+
+        // Nothing is compressed before the peer's announcement arrives
+        sender.send_state(&local).unwrap();
+        assert!(matches!(
+            sent.lock().unwrap().front(),
+            Some(MeshMessage::Hello { .. })
+        ));
+        assert!(matches!(
+            sent.lock().unwrap().back(),
+            Some(MeshMessage::State { .. })
+        ));
+        assert_eq!(receiver.receive(&mut remote).unwrap(), 1);
+        assert_eq!(sender.receive(&mut local).unwrap(), 0);
+        assert_eq!(sender.negotiated_compression(), Some(Compression::Lz4));
+        let plain = local.metrics().bytes_sent;
+
+        local.state = document("Rewritten", &vec!["another repeated line"; 200]);
+        sender.send_state(&local).unwrap();
+        assert!(matches!(
+            sent.lock().unwrap().back(),
+            Some(MeshMessage::Compressed { .. })
+        ));
+        assert_eq!(receiver.receive(&mut remote).unwrap(), 1);
+        assert_eq!(remote.state, local.state);
+        assert!(local.metrics().bytes_sent - plain < plain / 2);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_mesh_link_sends_plain_messages_to_peers_without_compression() {
+        use zed::Compression;
+
+        let (a, b) = MemoryTransport::pair();
+        let sent = Arc::clone(&a.outbox);
+        let mut sender = MeshLink::new(a).compression(Compression::Zstd);
+        let mut receiver = MeshLink::new(b);
+
+        let mut local = StateNode::new("local".to_string(), document("Log", &[]));
+        let mut remote = StateNode::new("remote".to_string(), document("", &[]));
+        for round in 0..3 {
+            local.state.lines = vec!["entry"; 100 * (round + 1)]
+                .into_iter()
+                .map(String::from)
+                .collect();
+            sender.send_state(&local).unwrap();
+            receiver.receive(&mut remote).unwrap();
+            sender.receive(&mut local).unwrap();
+        }
+
+        assert_eq!(sender.negotiated_compression(), None);
+        assert!(
+            !sent
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|message| matches!(message, MeshMessage::Compressed { .. }))
+        );
+        assert_eq!(remote.state, local.state);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_mesh_link_rejects_damaged_compressed_message() {
+        let (mut a, b) = MemoryTransport::pair();
+        let mut receiver = MeshLink::new(b);
+        let mut remote = StateNode::new("remote".to_string(), document("", &[]));
+
+        a.send(&MeshMessage::Compressed {
+            compression: zed::Compression::Zstd,
+            payload: "bm90IHpzdGQ=".to_string(),
+        })
+        .unwrap();
+
+        assert!(receiver.receive(&mut remote).is_err());
+    }
+
+    #[test]
+    fn test_mesh_link_grants_peer_authority() {
+        let (a, b) = MemoryTransport::pair();
+        let mut sender = MeshLink::new(a);
+        let mut from_server = MeshLink::new(b).peer_authority(1);
+
+        let server = StateNode::new("server".to_string(), document("Official", &["v1"]));
+        let mut client = StateNode::new("client".to_string(), document("Mine", &["v1"]));
+        client.set_authority_resolver(|doc: &Document| doc.lines.len());
+
+        sender.send_state(&server).unwrap();
+        from_server.receive(&mut client).unwrap();
+        assert_eq!(client.state.title, "Official");
+    }
+
+    #[test]
+    fn test_mesh_link_sends_several_nodes_over_one_link() {
+        let (a, b) = MemoryTransport::pair();
+        let sent = Arc::clone(&a.outbox);
+        let mut sender = MeshLink::new(a);
+        let mut receiver = MeshLink::new(b);
+
+        let mut x = StateNode::new("x".to_string(), document("X", &["one"]));
+        let y = StateNode::new("y".to_string(), document("Y", &["two"]));
+        let mut remote = StateNode::new("remote".to_string(), document("", &[]));
+
+        sender.send_state(&x).unwrap();
+        // A node's first state is sent whole, whatever went before it
+        sender.send_state(&y).unwrap();
+        assert!(matches!(
+            sent.lock().unwrap().back(),
+            Some(MeshMessage::State { from, .. }) if from == "y"
+        ));
+        assert_eq!(receiver.receive(&mut remote).unwrap(), 2);
+        assert_eq!(remote.state, y.state);
+
+        // Later states are deltas against the same node's last state
+        x.state.lines.push("three".to_string());
+        sender.send_state(&x).unwrap();
+        assert!(matches!(
+            sent.lock().unwrap().back(),
+            Some(MeshMessage::Delta { from, .. }) if from == "x"
+        ));
+        assert_eq!(receiver.receive(&mut remote).unwrap(), 1);
+        assert_eq!(remote.state, x.state);
+    }
+
+    #[test]
+    fn test_request_sync_exchanges_only_differing_entries() {
+        use std::collections::BTreeMap;
+
+        let (a, b) = MemoryTransport::pair();
+        let sent = Arc::clone(&b.outbox);
+        let mut requester = MeshLink::new(a);
+        let mut responder = MeshLink::new(b);
+
+        let rows: BTreeMap<u32, String> = (0..10_000).map(|i| (i, format!("row {i}"))).collect();
+        let mut replica = StateNode::new("replica".to_string(), rows.clone());
+        let mut primary = StateNode::new("primary".to_string(), rows);
+        primary.state.insert(5, "edited".to_string());
+        primary.state.remove(&6);
+        primary.state.insert(10_000, "new".to_string());
+
+        requester.request_sync().unwrap();
+        let mut applied = 0;
+        for _ in 0..8 {
+            responder.receive_keyed(&mut primary).unwrap();
+            applied += requester.receive_keyed(&mut replica).unwrap();
+        }
+
+        assert_eq!(applied, 1);
+        assert_eq!(replica.state, primary.state);
+        assert!(sent.lock().unwrap().is_empty());
+        // Far less than the whole state travelled
+        assert!(replica.metrics().bytes_received < 20_000);
+
+        requester.request_sync().unwrap();
+        responder.receive_keyed(&mut primary).unwrap();
+        assert_eq!(requester.receive_keyed(&mut replica).unwrap(), 0);
+        assert!(sent.lock().unwrap().is_empty());
+        assert!(requester.transport().outbox.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_receive_rejects_merkle_sync_messages() {
+        let (mut a, b) = MemoryTransport::pair();
+        let mut receiver = MeshLink::new(b);
+        let mut remote = StateNode::new("remote".to_string(), document("", &[]));
+
+        a.send(&MeshMessage::DigestRequest {
+            depth: 0,
+            indices: vec![0],
+        })
+        .unwrap();
+
+        let err = receiver.receive(&mut remote).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
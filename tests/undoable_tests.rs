@@ -0,0 +1,132 @@
+use std::sync::{Arc, Mutex};
+use zed::undoable::DEFAULT_MAX_HISTORY;
+use zed::*;
+
+#[derive(Clone, Debug, PartialEq)]
+struct Canvas {
+    shapes: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+enum CanvasAction {
+    Draw(String),
+    Erase,
+}
+
+fn canvas() -> UndoableStore<Canvas, CanvasAction> {
+    UndoableStore::new(
+        Canvas { shapes: vec![] },
+        create_try_reducer(|state: &Canvas, action: &CanvasAction| {
+            let mut shapes = state.shapes.clone();
+            match action {
+                CanvasAction::Draw(shape) => shapes.push(shape.clone()),
+                CanvasAction::Erase => {
+                    shapes.pop().ok_or("nothing to erase")?;
+                }
+            }
+            Ok::<_, &str>(Canvas { shapes })
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undo_and_redo_walk_the_history() {
+        let canvas = canvas();
+        assert!(!canvas.can_undo());
+        assert!(!canvas.undo());
+
+        canvas.dispatch(CanvasAction::Draw("circle".into()));
+        canvas.dispatch(CanvasAction::Draw("square".into()));
+
+        assert!(canvas.undo());
+        assert_eq!(canvas.get_state().shapes, vec!["circle"]);
+        assert!(canvas.undo());
+        assert!(canvas.get_state().shapes.is_empty());
+        assert!(!canvas.undo());
+
+        assert!(canvas.redo());
+        assert!(canvas.redo());
+        assert!(!canvas.can_redo());
+        assert!(!canvas.redo());
+        assert_eq!(canvas.get_state().shapes, vec!["circle", "square"]);
+    }
+
+    #[test]
+    fn test_dispatch_after_undo_discards_redo_and_skips_rejected_actions() {
+        let canvas = canvas();
+        canvas.dispatch(CanvasAction::Draw("circle".into()));
+        canvas.undo();
+
+        assert!(canvas.dispatch_try(CanvasAction::Erase).is_err());
+        assert!(canvas.can_redo());
+
+        canvas.dispatch(CanvasAction::Draw("line".into()));
+        assert!(!canvas.can_redo());
+        assert!(canvas.undo());
+        assert!(canvas.get_state().shapes.is_empty());
+        assert!(!canvas.can_undo());
+    }
+
+    #[test]
+    fn test_subscribers_see_undo_and_redo() {
+        let canvas = canvas();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let counts = Arc::clone(&seen);
+        canvas.subscribe(move |state: &Canvas| counts.lock().unwrap().push(state.shapes.len()));
+
+        canvas.dispatch(CanvasAction::Draw("circle".into()));
+        canvas.undo();
+        canvas.redo();
+
+        assert_eq!(*seen.lock().unwrap(), vec![1, 0, 1]);
+    }
+
+    #[test]
+    fn test_concurrent_dispatches_are_recorded_in_reducer_order() {
+        let counter = UndoableStore::with_config(
+            0,
+            create_reducer(|count: &i32, _: &()| count + 1),
+            TimeTravelConfig::new(),
+        );
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let counter = counter.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..50 {
+                        counter.dispatch(());
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        // Each undo step reverts exactly one increment
+        for expected in (0..200).rev() {
+            assert!(counter.undo());
+            assert_eq!(counter.get_state(), expected);
+        }
+        assert!(!counter.undo());
+    }
+
+    #[test]
+    fn test_history_is_capped() {
+        let canvas = canvas();
+        for index in 0..DEFAULT_MAX_HISTORY + 10 {
+            canvas.dispatch(CanvasAction::Draw(index.to_string()));
+        }
+
+        let mut undone = 0;
+        while canvas.undo() {
+            undone += 1;
+        }
+        assert_eq!(undone, DEFAULT_MAX_HISTORY - 1);
+        assert_eq!(canvas.get_state().shapes.len(), 11);
+    }
+}